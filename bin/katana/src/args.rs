@@ -12,21 +12,27 @@
 
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use alloy_primitives::U256;
 use clap::{Args, Parser, Subcommand};
 use clap_complete::Shell;
 use common::parse::parse_socket_address;
-use katana_core::backend::config::{Environment, StarknetConfig};
+use katana_core::backend::config::{
+    Environment, ForkRefreshPolicy, StarknetConfig, TimestampSource,
+};
 use katana_core::constants::{
-    DEFAULT_ETH_L1_GAS_PRICE, DEFAULT_INVOKE_MAX_STEPS, DEFAULT_SEQUENCER_ADDRESS,
-    DEFAULT_STRK_L1_GAS_PRICE, DEFAULT_VALIDATE_MAX_STEPS,
+    DEFAULT_DB_MAINTENANCE_INTERVAL, DEFAULT_ETH_L1_GAS_PRICE, DEFAULT_MAX_CALLDATA_LEN,
+    DEFAULT_SEQUENCER_ADDRESS, DEFAULT_STRK_L1_GAS_PRICE, MAX_RECURSION_DEPTH,
 };
-use katana_core::sequencer::SequencerConfig;
+use katana_core::sequencer::{DbPruningConfig, DbSnapshotConfig, SequencerConfig};
+use katana_core::service::block_producer::BlockLimits;
 use katana_primitives::block::GasPrices;
 use katana_primitives::chain::ChainId;
+use katana_primitives::chain_spec::ChainSpec;
 use katana_primitives::genesis::allocation::DevAllocationsGenerator;
 use katana_primitives::genesis::constant::DEFAULT_PREFUNDED_ACCOUNT_BALANCE;
+use katana_primitives::genesis::predeploy::Predeploy;
 use katana_primitives::genesis::Genesis;
 use katana_rpc::config::ServerConfig;
 use katana_rpc_api::ApiKind;
@@ -34,7 +40,9 @@ use tracing::Subscriber;
 use tracing_subscriber::{fmt, EnvFilter};
 use url::Url;
 
-use crate::utils::{parse_genesis, parse_seed};
+use crate::utils::{
+    parse_chain_spec, parse_genesis, parse_predeploys, parse_rate_limit, parse_seed,
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -54,6 +62,28 @@ pub struct KatanaArgs {
     #[arg(help = "Block time in milliseconds for interval mining.")]
     pub block_time: Option<u64>,
 
+    #[arg(long)]
+    #[arg(conflicts_with_all(["block_time", "no_mining"]))]
+    #[arg(help = "Leave the transactions in the pool for an external block builder.")]
+    #[arg(long_help = "Leave the transactions in the pool for an external block builder, which \
+                       follows them with `katana_subscribePoolTransactions` and seals the \
+                       blocks it builds with `katana_sealBlock`.")]
+    pub external_block_building: bool,
+
+    #[arg(long)]
+    #[arg(value_name = "STEPS")]
+    #[arg(help = "The maximum amount of Cairo steps the transactions in a block can use.")]
+    #[arg(long_help = "The maximum amount of Cairo steps the transactions in a block can use. \
+                       Transactions that don't fit in a block are carried over to the next one.")]
+    pub block_max_cairo_steps: Option<u128>,
+
+    #[arg(long)]
+    #[arg(value_name = "GAS")]
+    #[arg(help = "The maximum amount of L1 gas the transactions in a block can use.")]
+    #[arg(long_help = "The maximum amount of L1 gas the transactions in a block can use. \
+                       Transactions that don't fit in a block are carried over to the next one.")]
+    pub block_max_l1_gas: Option<u128>,
+
     #[arg(long)]
     #[arg(value_name = "PATH")]
     #[arg(help = "Directory path of the database to initialize from.")]
@@ -62,6 +92,63 @@ pub struct KatanaArgs {
                        initialized Katana database.")]
     pub db_dir: Option<PathBuf>,
 
+    #[arg(long)]
+    #[arg(value_name = "SECONDS")]
+    #[arg(requires = "db_dir")]
+    #[arg(help = "Report the space used by the database at this interval, in seconds.")]
+    #[arg(long_help = "Report the space used by the database, and how much of it can be \
+                       reclaimed by compacting it with `katana db compact`, at this interval in \
+                       seconds. The sizes are logged and recorded as metrics, along with the \
+                       size of every table. Defaults to every minute if metrics are enabled.")]
+    pub db_maintenance_interval: Option<u64>,
+
+    #[arg(long)]
+    #[arg(value_name = "SECONDS")]
+    #[arg(requires_all = ["db_dir", "snapshot_dir"])]
+    #[arg(help = "Take a snapshot of the database at this interval, in seconds.")]
+    #[arg(long_help = "Copy the database to a new directory of `--snapshot-dir` at this \
+                       interval in seconds. A snapshot is a database of its own, which a node \
+                       can be started from with `--db-dir`.")]
+    pub snapshot_interval: Option<u64>,
+
+    #[arg(long)]
+    #[arg(value_name = "PATH")]
+    #[arg(requires = "snapshot_interval")]
+    #[arg(help = "Directory path the database snapshots are written to.")]
+    pub snapshot_dir: Option<PathBuf>,
+
+    #[arg(long)]
+    #[arg(value_name = "COUNT")]
+    #[arg(default_value_t = 3)]
+    #[arg(help = "The number of database snapshots kept, the oldest ones being removed.")]
+    pub snapshot_retain: usize,
+
+    #[arg(long)]
+    #[arg(value_name = "SECONDS")]
+    #[arg(requires = "db_dir")]
+    #[arg(help = "Prune the state history of the database at this interval, in seconds.")]
+    #[arg(long_help = "Prune the state history of the database at this interval in seconds, so \
+                       that only the state of the `--db-prune-retain` latest blocks can be \
+                       queried. The blocks and their transactions are kept. The state history \
+                       is never pruned by default.")]
+    pub db_prune_interval: Option<u64>,
+
+    #[arg(long)]
+    #[arg(value_name = "BLOCKS")]
+    #[arg(requires = "db_prune_interval")]
+    #[arg(default_value_t = 1000)]
+    #[arg(value_parser = clap::value_parser!(u64).range(1..))]
+    #[arg(help = "The number of latest blocks whose state history is kept when pruning.")]
+    pub db_prune_retain: u64,
+
+    #[arg(long)]
+    #[arg(value_name = "SECONDS")]
+    #[arg(help = "Roll up the activity of the mined blocks at this interval, in seconds.")]
+    #[arg(long_help = "Roll up the activity of the blocks mined since the previous rollup at \
+                       this interval in seconds. The chain totals are logged and recorded as \
+                       metrics.")]
+    pub stats_rollup_interval: Option<u64>,
+
     #[arg(long)]
     #[arg(value_name = "URL")]
     #[arg(help = "The Starknet RPC provider to fork the network from.")]
@@ -70,6 +157,52 @@ pub struct KatanaArgs {
     #[arg(long)]
     pub dev: bool,
 
+    #[arg(long)]
+    #[arg(conflicts_with_all(["rpc_url", "block_time", "genesis"]))]
+    #[arg(help = "Produce the same blocks on every run, for snapshot testing.")]
+    #[arg(long_help = "Produce the same blocks, and block hashes, on every run, for snapshot \
+                       testing, as long as the same transactions are sent in the same order. \
+                       Block timestamps are generated by a counter that starts from the \
+                       timestamp of the latest block and advances by one second per block, \
+                       instead of being taken from the system clock. The blocks are produced by \
+                       the default sequencer address, and the pre-funded accounts are generated \
+                       from the accounts seed.")]
+    pub deterministic: bool,
+
+    #[arg(long)]
+    #[arg(help = "Execute every block twice and report any divergence between the two runs.")]
+    #[arg(long_help = "Execute the transactions of every block a second time, against the state \
+                       the block is built on, before committing it. Any difference between the \
+                       state updates of both runs is logged as an error, along with the \
+                       diverging keys. This doubles the execution cost of the blocks, and is \
+                       meant for debugging the execution backends.")]
+    pub check_determinism: bool,
+
+    #[arg(long, allow_hyphen_values = true)]
+    #[arg(value_name = "SECONDS")]
+    #[arg(conflicts_with_all(["deterministic", "time_server"]))]
+    #[arg(help = "Shift the clock of the block timestamps from the system clock.")]
+    pub timestamp_offset: Option<i64>,
+
+    #[arg(long)]
+    #[arg(value_name = "URL")]
+    #[arg(conflicts_with = "deterministic")]
+    #[arg(help = "Derive the block timestamps from the clock of a time server.")]
+    #[arg(long_help = "Derive the block timestamps from the clock of a time server, queried \
+                       every minute with a GET request answered by the current UNIX time in \
+                       seconds, either as a plain integer or as a JSON object with a `unixtime` \
+                       field. The system clock is used until the server first answers.")]
+    pub time_server: Option<Url>,
+
+    #[arg(long)]
+    #[arg(value_name = "SECONDS")]
+    #[arg(help = "The maximum drift of the block timestamps from their clock.")]
+    #[arg(long_help = "The maximum number of seconds the block timestamps may drift from their \
+                       clock, once shifted with `dev_setNextBlockTimestamp` or \
+                       `dev_increaseNextBlockTimestamp`. Timestamps drifting more are clamped. \
+                       The effective chain time can be queried with `dev_chainTime`.")]
+    pub max_timestamp_drift: Option<u64>,
+
     #[arg(long)]
     #[arg(help = "Output logs in JSON format.")]
     pub json_log: bool,
@@ -77,7 +210,8 @@ pub struct KatanaArgs {
     /// Enable Prometheus metrics.
     ///
     /// The metrics will be served at the given interface and port.
-    #[arg(long, value_name = "SOCKET", value_parser = parse_socket_address, help_heading = "Metrics")]
+    #[arg(long, visible_alias = "metrics.addr")]
+    #[arg(value_name = "SOCKET", value_parser = parse_socket_address, help_heading = "Metrics")]
     pub metrics: Option<SocketAddr>,
 
     #[arg(long)]
@@ -86,6 +220,23 @@ pub struct KatanaArgs {
     #[arg(help = "Fork the network at a specific block.")]
     pub fork_block_number: Option<u64>,
 
+    #[arg(long)]
+    #[arg(requires = "rpc_url")]
+    #[arg(value_name = "SECONDS")]
+    #[arg(help = "Refresh the data fetched from the forked network once it is older than this \
+                  number of seconds.")]
+    #[arg(long_help = "Refresh the data fetched from the forked network once it is older than \
+                       this number of seconds. The data is then fetched again at the latest \
+                       block of the forked network.")]
+    pub fork_refresh_interval: Option<u64>,
+
+    #[arg(long)]
+    #[arg(requires = "rpc_url")]
+    #[arg(value_name = "BLOCKS")]
+    #[arg(help = "Refresh the data fetched from the forked network once the forked network is \
+                  more than this number of blocks ahead of the block it is fetched at.")]
+    pub fork_max_head_lag: Option<u64>,
+
     #[cfg(feature = "messaging")]
     #[arg(long)]
     #[arg(value_name = "PATH")]
@@ -112,6 +263,43 @@ pub struct KatanaArgs {
 pub enum Commands {
     #[command(about = "Generate shell completion file for specified shell")]
     Completions { shell: Shell },
+
+    #[command(subcommand)]
+    #[command(about = "Maintain the database of a node")]
+    Db(DbCommands),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommands {
+    #[command(about = "Compact the database, giving its free pages back to the file system")]
+    #[command(long_about = "Compact the database, giving its free pages back to the file \
+                            system. All the tables are rebuilt into a new database file, which \
+                            replaces the original one. The database must not be used by a \
+                            running node.")]
+    Compact {
+        #[arg(long)]
+        #[arg(value_name = "PATH")]
+        #[arg(help = "Directory path of the database to compact.")]
+        db_dir: PathBuf,
+    },
+
+    #[command(about = "Copy the readable entries of a damaged database into a new database")]
+    #[command(long_about = "Copy the readable entries of a damaged database into a new \
+                            database, as a last resort after a disk incident. The damaged \
+                            database is opened read-only and its tables are copied one by one, \
+                            each up to its first unreadable entry. The entries lost in every \
+                            table are reported. The database must not be used by a running node.")]
+    Salvage {
+        #[arg(long)]
+        #[arg(value_name = "PATH")]
+        #[arg(help = "Directory path of the damaged database.")]
+        db_dir: PathBuf,
+
+        #[arg(long)]
+        #[arg(value_name = "PATH")]
+        #[arg(help = "Directory path of the salvaged database, which must not exist or be empty.")]
+        output: PathBuf,
+    },
 }
 
 #[derive(Debug, Args, Clone)]
@@ -129,6 +317,35 @@ pub struct ServerOptions {
     #[arg(default_value = "100")]
     #[arg(help = "Maximum number of concurrent connections allowed.")]
     pub max_connections: u32,
+
+    #[arg(long)]
+    #[arg(value_name = "SIZE")]
+    #[arg(help = "Maximum number of requests allowed in a single batch.")]
+    #[arg(long_help = "Maximum number of requests allowed in a single batch, sent over HTTP or \
+                       in a WebSocket message. The bigger batches are answered with an error.")]
+    pub max_batch_size: Option<u32>,
+
+    #[arg(long = "rate-limit")]
+    #[arg(value_name = "METHOD=LIMIT")]
+    #[arg(value_parser = parse_rate_limit)]
+    #[arg(help = "Maximum number of calls per second allowed for a method, eg. \
+                  `starknet_call=100`. Can be specified multiple times.")]
+    pub rate_limits: Vec<(String, u32)>,
+
+    #[arg(long)]
+    #[arg(help = "Log the caller of a request from its `x-forwarded-for` or `x-real-ip` header.")]
+    #[arg(long_help = "Log the caller of a request from its `x-forwarded-for` or `x-real-ip` \
+                       header instead of the address of its peer. Only set it behind a reverse \
+                       proxy that sets these headers, as any client can send them.")]
+    pub trust_forwarded_headers: bool,
+
+    #[arg(long)]
+    #[arg(help = "Serve a minimal Ethereum compatible API for generic tooling.")]
+    #[arg(long_help = "Serve the `eth_chainId`, `eth_blockNumber` and `eth_getBalance` methods, \
+                       mapped onto the Starknet chain id, block number and fee token balance, \
+                       so that generic health checkers and wallet reachability probes work \
+                       against Katana. The balances are in the smallest unit of the fee token.")]
+    pub eth_compat: bool,
 }
 
 #[derive(Debug, Args, Clone)]
@@ -160,6 +377,28 @@ pub struct StarknetOptions {
     #[arg(value_parser = parse_genesis)]
     #[arg(conflicts_with_all(["rpc_url", "seed", "total_accounts"]))]
     pub genesis: Option<Genesis>,
+
+    #[arg(long = "predeploy")]
+    #[arg(value_name = "DIR")]
+    #[arg(value_parser = parse_predeploys)]
+    #[arg(help = "Declare and deploy the contract classes of a directory at genesis.")]
+    #[arg(long_help = "Declare and deploy the contract classes of a directory at genesis. An \
+                       optional `predeploys.json` file of the directory gives the address, the \
+                       constructor calldata the address is computed with, and the storage of \
+                       the contracts, by artifact name. Can be given multiple times.")]
+    pub predeploys: Vec<Vec<Predeploy>>,
+
+    #[arg(long)]
+    #[arg(value_name = "PRESET_OR_PATH")]
+    #[arg(value_parser = parse_chain_spec)]
+    #[arg(conflicts_with_all(["genesis", "chain_id"]))]
+    #[arg(help = "The chain specification, either a preset (`dev`, `sepolia` or `mainnet`) or a \
+                  path to a TOML or JSON chain spec file.")]
+    #[arg(long_help = "The chain specification, either a preset (`dev`, `sepolia` or `mainnet`) \
+                       or a path to a TOML or JSON chain spec file. If the genesis of the chain \
+                       spec has no allocations, pre-funded dev accounts are generated the same \
+                       way as without a chain spec.")]
+    pub chain: Option<ChainSpec>,
 }
 
 #[derive(Debug, Args, Clone)]
@@ -181,6 +420,22 @@ pub struct EnvironmentOptions {
     #[arg(help = "The maximum number of steps available for the account execution logic.")]
     pub invoke_max_steps: Option<u32>,
 
+    #[arg(long)]
+    #[arg(default_value_t = MAX_RECURSION_DEPTH)]
+    #[arg(help = "The maximum depth of nested contract calls.")]
+    #[arg(long_help = "The maximum depth of nested contract calls. A transaction exceeding it \
+                       fails with a recursion depth error, instead of running out of steps when \
+                       a contract recurses infinitely.")]
+    pub max_recursion_depth: usize,
+
+    #[arg(long)]
+    #[arg(default_value_t = DEFAULT_MAX_CALLDATA_LEN)]
+    #[arg(help = "The maximum number of elements of the calldata of a transaction.")]
+    #[arg(long_help = "The maximum number of elements of the calldata of a submitted \
+                       transaction, or of the constructor calldata of an account deployment. \
+                       The transactions with a longer calldata are rejected.")]
+    pub max_calldata_len: usize,
+
     #[arg(long = "eth-gas-price")]
     #[arg(conflicts_with = "genesis")]
     #[arg(help = "The L1 ETH gas price.")]
@@ -215,6 +470,23 @@ impl KatanaArgs {
         SequencerConfig {
             block_time: self.block_time,
             no_mining: self.no_mining,
+            external_block_building: self.external_block_building,
+            block_limits: BlockLimits {
+                max_l1_gas: self.block_max_l1_gas,
+                max_cairo_steps: self.block_max_cairo_steps,
+            },
+            db_maintenance_interval: self.db_maintenance_interval.or_else(|| {
+                // the table metrics are only recorded by the maintenance job
+                let records_metrics = self.metrics.is_some() && self.db_dir.is_some();
+                records_metrics.then_some(DEFAULT_DB_MAINTENANCE_INTERVAL)
+            }),
+            db_snapshots: self.snapshot_interval.zip(self.snapshot_dir.clone()).map(
+                |(interval, dir)| DbSnapshotConfig { interval, dir, retain: self.snapshot_retain },
+            ),
+            db_pruning: self
+                .db_prune_interval
+                .map(|interval| DbPruningConfig { interval, retain: self.db_prune_retain }),
+            stats_rollup_interval: self.stats_rollup_interval,
             #[cfg(feature = "messaging")]
             messaging: self.messaging.clone(),
         }
@@ -226,18 +498,37 @@ impl KatanaArgs {
         if self.dev {
             apis.push(ApiKind::Dev);
         }
+        if self.server.eth_compat {
+            apis.push(ApiKind::Eth);
+        }
 
         ServerConfig {
             apis,
             port: self.server.port,
             host: self.server.host.clone().unwrap_or("0.0.0.0".into()),
             max_connections: self.server.max_connections,
+            max_batch_size: self.server.max_batch_size,
+            rate_limits: self.server.rate_limits.iter().cloned().collect(),
+            trust_forwarded_headers: self.server.trust_forwarded_headers,
         }
     }
 
-    pub fn starknet_config(&self) -> StarknetConfig {
-        let genesis = match self.starknet.genesis.clone() {
+    pub fn starknet_config(&self) -> anyhow::Result<StarknetConfig> {
+        let timestamp_source = match (self.timestamp_offset, &self.time_server) {
+            (Some(offset), _) => TimestampSource::Offset(offset),
+            (None, Some(url)) => TimestampSource::TimeServer(url.clone()),
+            (None, None) => TimestampSource::WallClock,
+        };
+
+        let chain = self.starknet.chain.clone().unwrap_or_else(|| ChainSpec {
+            id: self.starknet.environment.chain_id,
+            ..Default::default()
+        });
+
+        let mut genesis = match self.starknet.genesis.clone() {
             Some(genesis) => genesis,
+            // the genesis of the chain spec is used as is if it already allocates accounts
+            None if !chain.genesis.allocations.is_empty() => chain.genesis,
             None => {
                 let gas_prices = GasPrices {
                     eth: self
@@ -260,54 +551,74 @@ impl KatanaArgs {
                 let mut genesis = Genesis {
                     gas_prices,
                     sequencer_address: *DEFAULT_SEQUENCER_ADDRESS,
-                    ..Default::default()
+                    ..chain.genesis
                 };
 
                 genesis.extend_allocations(accounts.into_iter().map(|(k, v)| (k, v.into())));
                 genesis
             }
         };
+        genesis.extend_predeploys(self.starknet.predeploys.iter().flatten().cloned())?;
 
-        StarknetConfig {
+        Ok(StarknetConfig {
             disable_fee: self.starknet.disable_fee,
             disable_validate: self.starknet.disable_validate,
             fork_rpc_url: self.rpc_url.clone(),
             fork_block_number: self.fork_block_number,
+            fork_refresh_policy: ForkRefreshPolicy {
+                max_age: self.fork_refresh_interval.map(Duration::from_secs),
+                max_head_lag: self.fork_max_head_lag,
+            },
             env: Environment {
-                chain_id: self.starknet.environment.chain_id,
+                chain_id: chain.id,
                 invoke_max_steps: self
                     .starknet
                     .environment
                     .invoke_max_steps
-                    .unwrap_or(DEFAULT_INVOKE_MAX_STEPS),
+                    .unwrap_or(chain.constants.invoke_max_steps),
                 validate_max_steps: self
                     .starknet
                     .environment
                     .validate_max_steps
-                    .unwrap_or(DEFAULT_VALIDATE_MAX_STEPS),
+                    .unwrap_or(chain.constants.validate_max_steps),
+                max_recursion_depth: self.starknet.environment.max_recursion_depth,
+                max_calldata_len: self.starknet.environment.max_calldata_len,
+                supported_tx_versions: chain.supported_tx_versions,
+                version: chain.version,
             },
             db_dir: self.db_dir.clone(),
             genesis,
-        }
+            deterministic: self.deterministic,
+            check_determinism: self.check_determinism,
+            timestamp_source,
+            max_timestamp_drift: self.max_timestamp_drift,
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
+    use assert_matches::assert_matches;
+    use katana_core::constants::{DEFAULT_INVOKE_MAX_STEPS, DEFAULT_VALIDATE_MAX_STEPS};
+    use katana_primitives::chain_spec::ChainPreset;
+
     use super::*;
 
     #[test]
     fn test_starknet_config_default() {
         let args = KatanaArgs::parse_from(["katana"]);
-        let config = args.starknet_config();
+        let config = args.starknet_config().unwrap();
 
         assert!(!config.disable_fee);
         assert!(!config.disable_validate);
         assert_eq!(config.fork_rpc_url, None);
         assert_eq!(config.fork_block_number, None);
+        assert_eq!(config.fork_refresh_policy, ForkRefreshPolicy::default());
         assert_eq!(config.env.chain_id, ChainId::parse("KATANA").unwrap());
         assert_eq!(config.env.invoke_max_steps, DEFAULT_INVOKE_MAX_STEPS);
         assert_eq!(config.env.validate_max_steps, DEFAULT_VALIDATE_MAX_STEPS);
+        assert_eq!(config.env.max_recursion_depth, MAX_RECURSION_DEPTH);
+        assert_eq!(config.env.max_calldata_len, DEFAULT_MAX_CALLDATA_LEN);
         assert_eq!(config.db_dir, None);
         assert_eq!(config.genesis.gas_prices.eth, DEFAULT_ETH_L1_GAS_PRICE);
         assert_eq!(config.genesis.gas_prices.strk, DEFAULT_STRK_L1_GAS_PRICE);
@@ -326,6 +637,10 @@ mod test {
             "200",
             "--validate-max-steps",
             "100",
+            "--max-recursion-depth",
+            "50",
+            "--max-calldata-len",
+            "100",
             "--db-dir",
             "/path/to/db",
             "--eth-gas-price",
@@ -333,15 +648,247 @@ mod test {
             "--strk-gas-price",
             "20",
         ]);
-        let config = args.starknet_config();
+        let config = args.starknet_config().unwrap();
 
         assert!(config.disable_fee);
         assert!(config.disable_validate);
         assert_eq!(config.env.chain_id, ChainId::GOERLI);
         assert_eq!(config.env.invoke_max_steps, 200);
         assert_eq!(config.env.validate_max_steps, 100);
+        assert_eq!(config.env.max_recursion_depth, 50);
+        assert_eq!(config.env.max_calldata_len, 100);
         assert_eq!(config.db_dir, Some(PathBuf::from("/path/to/db")));
         assert_eq!(config.genesis.gas_prices.eth, 10);
         assert_eq!(config.genesis.gas_prices.strk, 20);
     }
+
+    #[test]
+    fn test_starknet_config_fork_refresh_policy() {
+        let args = KatanaArgs::parse_from([
+            "katana",
+            "--rpc-url",
+            "http://localhost:5050",
+            "--fork-refresh-interval",
+            "30",
+            "--fork-max-head-lag",
+            "10",
+        ]);
+        let config = args.starknet_config().unwrap();
+
+        assert_eq!(config.fork_refresh_policy.max_age, Some(Duration::from_secs(30)));
+        assert_eq!(config.fork_refresh_policy.max_head_lag, Some(10));
+
+        assert!(KatanaArgs::try_parse_from(["katana", "--fork-max-head-lag", "10"]).is_err());
+    }
+
+    #[test]
+    fn test_starknet_config_chain_preset() {
+        let args = KatanaArgs::parse_from(["katana", "--chain", "sepolia"]);
+        let config = args.starknet_config().unwrap();
+
+        assert_eq!(config.env.chain_id, ChainId::SEPOLIA);
+        assert_eq!(config.env.invoke_max_steps, 4_000_000);
+        assert_eq!(config.genesis.gas_prices.eth, DEFAULT_ETH_L1_GAS_PRICE);
+        assert_eq!(config.genesis.accounts().count(), 10);
+        assert_eq!(config.env.version, ChainPreset::Sepolia.spec().version);
+
+        assert!(KatanaArgs::try_parse_from(["katana", "--chain", "sepolia", "--chain-id", "FOO"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_starknet_config_deterministic() {
+        let args = KatanaArgs::parse_from(["katana", "--deterministic"]);
+        let config = args.starknet_config().unwrap();
+
+        assert!(config.deterministic);
+        assert_eq!(config.genesis.timestamp, 0);
+        assert_eq!(config.genesis.sequencer_address, *DEFAULT_SEQUENCER_ADDRESS);
+
+        assert!(KatanaArgs::try_parse_from(["katana", "--deterministic", "--block-time", "1000"])
+            .is_err());
+        let genesis = "./tests/test-data/genesis.json";
+        assert!(KatanaArgs::try_parse_from(["katana", "--genesis", genesis]).is_ok());
+        assert!(KatanaArgs::try_parse_from(["katana", "--deterministic", "--genesis", genesis])
+            .is_err());
+
+        // the accounts only depend on the seed
+        let accounts = |seed: &str| {
+            let args = KatanaArgs::parse_from(["katana", "--deterministic", "--seed", seed]);
+            let genesis = args.starknet_config().unwrap().genesis;
+            genesis.accounts().map(|(address, _)| *address).collect::<Vec<_>>()
+        };
+        assert_eq!(accounts("42"), accounts("42"));
+        assert_ne!(accounts("42"), accounts("43"));
+    }
+
+    #[test]
+    fn test_starknet_config_check_determinism() {
+        let config = KatanaArgs::parse_from(["katana"]).starknet_config().unwrap();
+        assert!(!config.check_determinism);
+
+        let args = KatanaArgs::parse_from(["katana", "--check-determinism"]);
+        assert!(args.starknet_config().unwrap().check_determinism);
+    }
+
+    #[test]
+    fn test_predeploys_dir_must_exist() {
+        let config = KatanaArgs::parse_from(["katana"]).starknet_config().unwrap();
+        assert_eq!(config.genesis.contracts().count(), 0);
+
+        assert!(KatanaArgs::try_parse_from(["katana", "--predeploy", "./does-not-exist"]).is_err());
+    }
+
+    #[test]
+    fn test_starknet_config_timestamp_source() {
+        let config = KatanaArgs::parse_from(["katana"]).starknet_config().unwrap();
+        assert_eq!(config.timestamp_source, TimestampSource::WallClock);
+        assert_eq!(config.max_timestamp_drift, None);
+
+        let args = KatanaArgs::parse_from([
+            "katana",
+            "--timestamp-offset",
+            "-3600",
+            "--max-timestamp-drift",
+            "60",
+        ]);
+        let config = args.starknet_config().unwrap();
+        assert_eq!(config.timestamp_source, TimestampSource::Offset(-3600));
+        assert_eq!(config.max_timestamp_drift, Some(60));
+
+        let args = KatanaArgs::parse_from(["katana", "--time-server", "http://localhost:8080"]);
+        assert_matches!(
+            args.starknet_config().unwrap().timestamp_source,
+            TimestampSource::TimeServer(_)
+        );
+
+        assert!(KatanaArgs::try_parse_from([
+            "katana",
+            "--timestamp-offset",
+            "10",
+            "--time-server",
+            "http://localhost:8080"
+        ])
+        .is_err());
+        assert!(KatanaArgs::try_parse_from([
+            "katana",
+            "--deterministic",
+            "--timestamp-offset",
+            "10"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_eth_compat_api() {
+        let is_eth = |api: &ApiKind| matches!(api, ApiKind::Eth);
+
+        let config = KatanaArgs::parse_from(["katana"]).server_config();
+        assert!(!config.apis.iter().any(is_eth));
+
+        let config = KatanaArgs::parse_from(["katana", "--eth-compat"]).server_config();
+        assert!(config.apis.iter().any(is_eth));
+    }
+
+    #[test]
+    fn test_db_maintenance_interval_requires_db_dir() {
+        assert!(KatanaArgs::try_parse_from(["katana", "--db-maintenance-interval", "60"]).is_err());
+
+        let args =
+            KatanaArgs::parse_from(["katana", "--db-dir", "db", "--db-maintenance-interval", "60"]);
+        assert_eq!(args.sequencer_config().db_maintenance_interval, Some(60));
+
+        // the database metrics are recorded by default when metrics are enabled
+        let args =
+            KatanaArgs::parse_from(["katana", "--db-dir", "db", "--metrics", "0.0.0.0:9100"]);
+        assert_eq!(
+            args.sequencer_config().db_maintenance_interval,
+            Some(DEFAULT_DB_MAINTENANCE_INTERVAL)
+        );
+        let args = KatanaArgs::parse_from(["katana", "--db-dir", "db"]);
+        assert_eq!(args.sequencer_config().db_maintenance_interval, None);
+    }
+
+    #[test]
+    fn test_scheduled_tasks() {
+        let config = KatanaArgs::parse_from(["katana"]).sequencer_config();
+        assert!(config.db_snapshots.is_none());
+        assert!(config.db_pruning.is_none());
+        assert_eq!(config.stats_rollup_interval, None);
+
+        assert!(KatanaArgs::try_parse_from(["katana", "--db-prune-interval", "60"]).is_err());
+        assert!(KatanaArgs::try_parse_from([
+            "katana",
+            "--db-dir",
+            "db",
+            "--db-prune-interval",
+            "60",
+            "--db-prune-retain",
+            "0"
+        ])
+        .is_err());
+
+        assert!(KatanaArgs::try_parse_from(["katana", "--snapshot-interval", "60"]).is_err());
+        assert!(KatanaArgs::try_parse_from([
+            "katana",
+            "--db-dir",
+            "db",
+            "--snapshot-interval",
+            "60"
+        ])
+        .is_err());
+
+        let config = KatanaArgs::parse_from([
+            "katana",
+            "--db-dir",
+            "db",
+            "--snapshot-interval",
+            "3600",
+            "--snapshot-dir",
+            "snapshots",
+            "--snapshot-retain",
+            "5",
+            "--stats-rollup-interval",
+            "60",
+            "--db-prune-interval",
+            "600",
+        ])
+        .sequencer_config();
+
+        let snapshots = config.db_snapshots.unwrap();
+        assert_eq!(snapshots.interval, 3600);
+        assert_eq!(snapshots.dir, PathBuf::from("snapshots"));
+        assert_eq!(snapshots.retain, 5);
+        let pruning = config.db_pruning.unwrap();
+        assert_eq!((pruning.interval, pruning.retain), (600, 1000));
+        assert_eq!(config.stats_rollup_interval, Some(60));
+    }
+
+    #[test]
+    fn test_db_compact_command() {
+        let args = KatanaArgs::parse_from(["katana", "db", "compact", "--db-dir", "db"]);
+        assert_matches!(
+            args.command,
+            Some(Commands::Db(DbCommands::Compact { db_dir })) if db_dir == PathBuf::from("db")
+        );
+    }
+
+    #[test]
+    fn test_db_salvage_command() {
+        let args = KatanaArgs::parse_from([
+            "katana", "db", "salvage", "--db-dir", "db", "--output", "salvaged",
+        ]);
+        assert_matches!(
+            args.command,
+            Some(Commands::Db(DbCommands::Salvage { db_dir, output }))
+                if db_dir == PathBuf::from("db") && output == PathBuf::from("salvaged")
+        );
+        assert!(KatanaArgs::try_parse_from(["katana", "db", "salvage", "--db-dir", "db"]).is_err());
+    }
+
+    #[test]
+    fn test_metrics_addr_alias() {
+        let args = KatanaArgs::parse_from(["katana", "--metrics.addr", "127.0.0.1:9100"]);
+        assert_eq!(args.metrics, Some("127.0.0.1:9100".parse().unwrap()));
+    }
 }
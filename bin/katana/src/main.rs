@@ -1,29 +1,23 @@
 use std::io;
 use std::net::SocketAddr;
-use std::sync::Arc;
 
 use clap::{CommandFactory, Parser};
 use clap_complete::{generate, Shell};
 use console::Style;
 use dojo_metrics::{metrics_process, prometheus_exporter};
-use katana_core::constants::MAX_RECURSION_DEPTH;
-use katana_core::env::get_default_vm_resource_fee_cost;
-use katana_core::sequencer::KatanaSequencer;
-use katana_executor::SimulationFlag;
+use katana_node::Builder;
 use katana_primitives::class::ClassHash;
 use katana_primitives::contract::ContractAddress;
-use katana_primitives::env::{CfgEnv, FeeTokenAddressses};
-use katana_primitives::genesis::allocation::GenesisAccountAlloc;
+use katana_primitives::genesis::allocation::{GenesisAccountAlloc, GenesisAllocation};
 use katana_primitives::genesis::Genesis;
-use katana_rpc::{spawn, NodeHandle};
 use tokio::signal::ctrl_c;
-use tracing::info;
+use tracing::{info, warn};
 
 mod args;
 mod utils;
 
-use args::Commands::Completions;
-use args::KatanaArgs;
+use args::Commands::{Completions, Db};
+use args::{DbCommands, KatanaArgs};
 
 pub(crate) const LOG_TARGET: &str = "katana::cli";
 
@@ -38,44 +32,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 print_completion(shell);
                 return Ok(());
             }
+            Db(DbCommands::Compact { db_dir }) => {
+                let report = katana_db::compaction::compact_db(&db_dir)?;
+                info!(
+                    target: LOG_TARGET,
+                    path = %db_dir.display(),
+                    size = %report.size_after,
+                    reclaimed = %report.reclaimed(),
+                    "Compacted database.",
+                );
+                return Ok(());
+            }
+            Db(DbCommands::Salvage { db_dir, output }) => {
+                let report = katana_db::salvage::salvage_db(&db_dir, &output)?;
+                for table in report.tables.iter().filter(|table| !table.is_complete()) {
+                    warn!(
+                        target: LOG_TARGET,
+                        table = %table.table.name(),
+                        salvaged = %table.salvaged,
+                        skipped = %table.skipped,
+                        lost = %table.lost().map_or("unknown".to_string(), |lost| lost.to_string()),
+                        error = ?table.error.as_ref().map(ToString::to_string),
+                        "Table partially salvaged.",
+                    );
+                }
+
+                let salvaged = report.tables.iter().map(|table| table.salvaged).sum::<usize>();
+                info!(
+                    target: LOG_TARGET,
+                    path = %output.display(),
+                    entries = %salvaged,
+                    complete = %report.is_complete(),
+                    "Salvaged database.",
+                );
+                return Ok(());
+            }
         }
     }
 
     let server_config = args.server_config();
     let sequencer_config = args.sequencer_config();
-    let starknet_config = args.starknet_config();
-
-    let cfg_env = CfgEnv {
-        chain_id: starknet_config.env.chain_id,
-        vm_resource_fee_cost: get_default_vm_resource_fee_cost(),
-        invoke_tx_max_n_steps: starknet_config.env.invoke_max_steps,
-        validate_max_n_steps: starknet_config.env.validate_max_steps,
-        max_recursion_depth: MAX_RECURSION_DEPTH,
-        fee_token_addresses: FeeTokenAddressses {
-            eth: starknet_config.genesis.fee_token.address,
-            strk: Default::default(),
-        },
-    };
-
-    let simulation_flags = SimulationFlag {
-        skip_validate: starknet_config.disable_validate,
-        skip_fee_transfer: starknet_config.disable_fee,
-        ..Default::default()
-    };
-
-    cfg_if::cfg_if! {
-        if #[cfg(all(feature = "blockifier", feature = "sir"))] {
-            compile_error!("Cannot enable both `blockifier` and `sir` features at the same time");
-        } else if #[cfg(feature = "blockifier")] {
-            use katana_executor::implementation::blockifier::BlockifierFactory;
-            let executor_factory = BlockifierFactory::new(cfg_env, simulation_flags);
-        } else if #[cfg(feature = "sir")] {
-            use katana_executor::implementation::sir::NativeExecutorFactory;
-            let executor_factory = NativeExecutorFactory::new(cfg_env, simulation_flags);
-        } else {
-            compile_error!("At least one of the following features must be enabled: blockifier, sir");
-        }
-    }
+    let starknet_config = args.starknet_config()?;
 
     if let Some(listen_addr) = args.metrics {
         let prometheus_handle = prometheus_exporter::install_recorder("katana")?;
@@ -89,18 +86,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await?;
     }
 
-    let sequencer =
-        Arc::new(KatanaSequencer::new(executor_factory, sequencer_config, starknet_config).await?);
-    let NodeHandle { addr, handle, .. } = spawn(Arc::clone(&sequencer), server_config).await?;
+    let node = Builder::new()
+        .starknet_config(starknet_config)
+        .sequencer_config(sequencer_config)
+        .server_config(server_config)
+        .launch()
+        .await?;
 
     if !args.silent {
-        let genesis = &sequencer.backend().config.genesis;
-        print_intro(&args, genesis, addr);
+        let genesis = &node.sequencer.backend().config.genesis;
+        print_intro(&args, genesis, node.addr());
     }
 
-    // Wait until Ctrl + C is pressed, then shutdown
+    // Wait until Ctrl + C is pressed, then shutdown, letting the block that is being produced, if
+    // any, be committed before exiting
     ctrl_c().await?;
-    handle.stop()?;
+    node.stop().await?;
+    info!(target: LOG_TARGET, "Shut down.");
 
     Ok(())
 }
@@ -193,6 +195,18 @@ PREDEPLOYED CONTRACTS
 | Class Hash      | {hash:#064x}"
         )
     }
+
+    for (address, allocation) in &genesis.allocations {
+        if let GenesisAllocation::Contract(contract) = allocation {
+            println!(
+                r"
+| Contract        | Predeployed Contract
+| Address         | {address}
+| Class Hash      | {:#064x}",
+                contract.class_hash.unwrap_or_default()
+            )
+        }
+    }
 }
 
 fn print_genesis_accounts<'a, Accounts>(accounts: Accounts)
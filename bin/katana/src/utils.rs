@@ -1,6 +1,9 @@
 use std::path::PathBuf;
+use std::str::FromStr;
 
+use katana_primitives::chain_spec::{ChainPreset, ChainSpec};
 use katana_primitives::genesis::json::GenesisJson;
+use katana_primitives::genesis::predeploy::{load_predeploys, Predeploy};
 use katana_primitives::genesis::Genesis;
 
 pub fn parse_seed(seed: &str) -> [u8; 32] {
@@ -22,10 +25,42 @@ pub fn parse_genesis(value: &str) -> Result<Genesis, anyhow::Error> {
     Ok(genesis)
 }
 
+/// Used as clap value parser for the contracts predeployed from a directory of class artifacts.
+pub fn parse_predeploys(value: &str) -> Result<Vec<Predeploy>, anyhow::Error> {
+    let path = PathBuf::from(shellexpand::full(value)?.into_owned());
+    Ok(load_predeploys(path)?)
+}
+
+/// Used as clap value parser for [ChainSpec]. The value is either the name of a preset or a path to
+/// a chain spec file.
+pub fn parse_chain_spec(value: &str) -> Result<ChainSpec, anyhow::Error> {
+    if let Ok(preset) = ChainPreset::from_str(value) {
+        return Ok(preset.spec());
+    }
+
+    let path = PathBuf::from(shellexpand::full(value)?.into_owned());
+    Ok(ChainSpec::load(path)?)
+}
+
+/// Used as clap value parser for a method rate limit, in the form of `METHOD=LIMIT`.
+pub fn parse_rate_limit(value: &str) -> Result<(String, u32), anyhow::Error> {
+    let (method, limit) = value
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("expected `METHOD=LIMIT`, got `{value}`"))?;
+    Ok((method.to_string(), limit.parse()?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_rate_limit_arg() {
+        assert_eq!(parse_rate_limit("starknet_call=10").unwrap(), ("starknet_call".into(), 10));
+        assert!(parse_rate_limit("starknet_call").is_err());
+        assert!(parse_rate_limit("starknet_call=abc").is_err());
+    }
+
     #[test]
     fn parse_genesis_file() {
         let path = "./tests/test-data/genesis.json";
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use clap::Args;
+use scarb::core::Config;
+use starknet::core::types::FieldElement;
+
+use super::options::fee_token::FeeTokenOptions;
+use super::options::starknet::StarknetOptions;
+use crate::utils;
+
+#[derive(Debug, Args)]
+#[command(about = "Retrieve the amount of an ERC20 token a spender is allowed to spend on behalf \
+                   of an owner.")]
+pub struct AllowanceArgs {
+    #[arg(help = "The address of the owner of the tokens.")]
+    pub owner: FieldElement,
+
+    #[arg(help = "The address of the spender.")]
+    pub spender: FieldElement,
+
+    #[arg(short, long)]
+    #[arg(help = "The block ID (could be a hash, a number, 'pending' or 'latest')")]
+    pub block_id: Option<String>,
+
+    #[command(flatten)]
+    pub token: FeeTokenOptions,
+
+    #[command(flatten)]
+    pub starknet: StarknetOptions,
+}
+
+impl AllowanceArgs {
+    pub fn run(self, config: &Config) -> Result<()> {
+        let env_metadata = utils::load_metadata_from_config(config)?;
+        let token = self.token.address(env_metadata.as_ref())?;
+        let provider = self.starknet.provider(env_metadata.as_ref())?;
+
+        config.tokio_handle().block_on(sozo_ops::erc20::allowance(
+            provider,
+            token,
+            self.owner,
+            self.spender,
+            self.block_id,
+        ))
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use clap::{Args, Subcommand};
 use dojo_world::metadata::Environment;
@@ -49,6 +51,29 @@ pub enum AuthCommand {
         #[command(flatten)]
         account: AccountOptions,
 
+        #[command(flatten)]
+        transaction: TransactionOptions,
+    },
+    #[command(about = "Apply the permissions of a permissions file, granting and revoking only \
+                       what differs from the World.")]
+    Sync {
+        #[arg(value_name = "PATH")]
+        #[arg(help = "The permissions file, in TOML or JSON.")]
+        permissions: PathBuf,
+
+        #[arg(long)]
+        #[arg(help = "Print the changes without sending any transaction.")]
+        dry_run: bool,
+
+        #[command(flatten)]
+        world: WorldOptions,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[command(flatten)]
+        account: AccountOptions,
+
         #[command(flatten)]
         transaction: TransactionOptions,
     },
@@ -65,6 +90,17 @@ impl AuthArgs {
             AuthCommand::Revoke { kind, world, starknet, account, transaction } => config
                 .tokio_handle()
                 .block_on(revoke(world, account, starknet, env_metadata, kind, transaction)),
+            AuthCommand::Sync { permissions, dry_run, world, starknet, account, transaction } => {
+                config.tokio_handle().block_on(sync(
+                    world,
+                    account,
+                    starknet,
+                    env_metadata,
+                    permissions,
+                    dry_run,
+                    transaction,
+                ))
+            }
         }
     }
 }
@@ -135,6 +171,19 @@ pub async fn revoke(
     }
 }
 
+pub async fn sync(
+    world: WorldOptions,
+    account: AccountOptions,
+    starknet: StarknetOptions,
+    env_metadata: Option<Environment>,
+    permissions: PathBuf,
+    dry_run: bool,
+    transaction: TransactionOptions,
+) -> Result<()> {
+    let world = utils::world_from_env_metadata(world, account, starknet, &env_metadata).await?;
+    auth::sync(&world, permissions, dry_run, transaction.into()).await
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use clap::Args;
+use scarb::core::Config;
+use starknet::core::types::FieldElement;
+
+use super::options::fee_token::FeeTokenOptions;
+use super::options::starknet::StarknetOptions;
+use crate::utils;
+
+#[derive(Debug, Args)]
+#[command(about = "Retrieve the balance of an account in an ERC20 token.")]
+pub struct BalanceArgs {
+    #[arg(help = "The address of the account.")]
+    pub address: FieldElement,
+
+    #[arg(short, long)]
+    #[arg(help = "The block ID (could be a hash, a number, 'pending' or 'latest')")]
+    pub block_id: Option<String>,
+
+    #[command(flatten)]
+    pub token: FeeTokenOptions,
+
+    #[command(flatten)]
+    pub starknet: StarknetOptions,
+}
+
+impl BalanceArgs {
+    pub fn run(self, config: &Config) -> Result<()> {
+        let env_metadata = utils::load_metadata_from_config(config)?;
+        let token = self.token.address(env_metadata.as_ref())?;
+        let provider = self.starknet.provider(env_metadata.as_ref())?;
+
+        config.tokio_handle().block_on(sozo_ops::erc20::balance(
+            provider,
+            token,
+            self.address,
+            self.block_id,
+        ))
+    }
+}
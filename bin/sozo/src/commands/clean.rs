@@ -1,31 +1,141 @@
 use std::fs;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use camino::Utf8PathBuf;
 use clap::Args;
-use dojo_lang::compiler::{ABIS_DIR, BASE_DIR, MANIFESTS_DIR};
+use dojo_lang::compiler::{
+    ABIS_DIR, BASE_DIR, CONTRACTS_DIR, MANIFESTS_DIR, MODELS_DIR, SOURCES_DIR,
+};
 use scarb::core::Config;
+use serde::Deserialize;
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args)]
 pub struct CleanArgs {
     #[arg(short, long)]
+    #[arg(conflicts_with_all = ["manifests_only", "artifacts_only", "packages"])]
     #[arg(help = "Removes all the generated files, including scarb artifacts and ALL the \
                   manifests files.")]
     pub all: bool,
+
+    #[arg(long, conflicts_with = "artifacts_only")]
+    #[arg(help = "Only removes the generated manifests and abis, keeping the compiled artifacts.")]
+    pub manifests_only: bool,
+
+    #[arg(long)]
+    #[arg(help = "Only removes the compiled artifacts, keeping the generated manifests.")]
+    pub artifacts_only: bool,
+
+    #[arg(long, value_delimiter = ',')]
+    #[arg(help = "Only removes the manifests and artifacts of the given packages, identified by \
+                  their namespace (eg. `dojo_examples`). The files of the other packages are \
+                  kept.")]
+    pub packages: Vec<String>,
+
+    #[arg(long)]
+    #[arg(help = "Also removes the bindings generated in this directory along with the manifests.")]
+    pub bindings_output: Option<Utf8PathBuf>,
+}
+
+/// The name of a contract or model in its manifest.
+#[derive(Debug, Deserialize)]
+struct ManifestName {
+    name: String,
 }
 
 impl CleanArgs {
+    /// Returns `true` if the contract or model at `path` (eg. `dojo_examples::models::moves`) is
+    /// part of the packages to clean.
+    fn is_selected(&self, path: &str) -> bool {
+        self.packages.is_empty()
+            || path
+                .split("::")
+                .next()
+                .is_some_and(|namespace| self.packages.iter().any(|p| p == namespace))
+    }
+
     /// Cleans the manifests and abis files that are generated at build time.
     ///
     /// # Arguments
     ///
     /// * `profile_dir` - The directory where the profile files are located.
     pub fn clean_manifests(&self, profile_dir: &Utf8PathBuf) -> Result<()> {
-        let dirs = vec![profile_dir.join(BASE_DIR), profile_dir.join(ABIS_DIR).join(BASE_DIR)];
+        let manifests_dir = profile_dir.join(BASE_DIR);
+        let abis_dir = profile_dir.join(ABIS_DIR).join(BASE_DIR);
+
+        if self.packages.is_empty() {
+            for d in [manifests_dir, abis_dir] {
+                if d.exists() {
+                    fs::remove_dir_all(d)?;
+                }
+            }
+
+            return Ok(());
+        }
+
+        for kind_dir in ["", CONTRACTS_DIR, MODELS_DIR] {
+            let dir = manifests_dir.join(kind_dir);
+            if !dir.exists() {
+                continue;
+            }
+
+            for entry in dir.read_dir_utf8()? {
+                let path = entry?.into_path();
+                if path.extension() != Some("toml") {
+                    continue;
+                }
+
+                let manifest: ManifestName = toml::from_str(&fs::read_to_string(&path)?)
+                    .with_context(|| format!("Failed to read manifest {path}"))?;
+                if !self.is_selected(&manifest.name) {
+                    continue;
+                }
+
+                fs::remove_file(&path)?;
+
+                let file_name = path.file_name().expect("Manifest path should be a file.");
+                let abi = abis_dir.join(kind_dir).join(file_name).with_extension("json");
+                if abi.exists() {
+                    fs::remove_file(abi)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cleans the artifacts of the selected packages that are compiled by dojo, keeping the
+    /// artifacts of the other packages.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_dir` - The directory where the artifacts of the profile are located.
+    pub fn clean_artifacts(&self, target_dir: &Utf8PathBuf) -> Result<()> {
+        if !target_dir.exists() {
+            return Ok(());
+        }
+
+        for entry in target_dir.read_dir_utf8()? {
+            let entry = entry?;
 
-        for d in dirs {
-            if d.exists() {
-                fs::remove_dir_all(d)?;
+            // dojo artifacts are named after the full path of their contract, unlike the scarb
+            // ones which are left untouched
+            let file_name = entry.file_name();
+            let Some(contract) =
+                file_name.strip_suffix(".casm.json").or_else(|| file_name.strip_suffix(".json"))
+            else {
+                continue;
+            };
+
+            if !contract.contains("::") || !self.is_selected(contract) {
+                continue;
+            }
+
+            fs::remove_file(entry.path())?;
+
+            let source =
+                target_dir.join(SOURCES_DIR).join(format!("{contract}.cairo").replace("::", "_"));
+            if source.exists() {
+                fs::remove_file(source)?;
             }
         }
 
@@ -42,11 +152,27 @@ impl CleanArgs {
         // parent folder.
         let manifest_dir = ws.manifest_path().parent().unwrap().to_path_buf();
 
-        let profile_dir = manifest_dir.join(MANIFESTS_DIR).join(profile_name);
+        let profile_dir = manifest_dir.join(MANIFESTS_DIR).join(&profile_name);
 
         // By default, this command cleans the build manifests and scarb artifacts.
-        scarb::ops::clean(config)?;
-        self.clean_manifests(&profile_dir)?;
+        if !self.manifests_only {
+            if self.packages.is_empty() {
+                scarb::ops::clean(config)?;
+            } else {
+                let target_dir = ws.target_dir().child(&profile_name).path_unchecked().to_owned();
+                self.clean_artifacts(&target_dir)?;
+            }
+        }
+
+        if !self.artifacts_only {
+            self.clean_manifests(&profile_dir)?;
+
+            if let Some(bindings_dir) = &self.bindings_output {
+                if bindings_dir.exists() {
+                    fs::remove_dir_all(bindings_dir)?;
+                }
+            }
+        }
 
         if self.all && profile_dir.exists() {
             fs::remove_dir_all(profile_dir)?;
@@ -61,7 +187,7 @@ mod tests {
     use dojo_test_utils::compiler;
     use dojo_world::migration::TxnConfig;
     use katana_runner::KatanaRunner;
-    use sozo_ops::migration;
+    use sozo_ops::migration::{self, DryRun, PlanFormat};
 
     use super::*;
 
@@ -85,14 +211,14 @@ mod tests {
                 runner.endpoint(),
                 &runner.account(0),
                 Some("dojo_examples".to_string()),
-                true,
+                Some(DryRun::Plan(PlanFormat::Text)),
                 TxnConfig::default(),
             )
             .await
             .unwrap()
         });
 
-        let clean_cmd = CleanArgs { all: false };
+        let clean_cmd = CleanArgs::default();
         clean_cmd.run(&config).unwrap();
 
         let profile_name = config.profile().to_string();
@@ -121,7 +247,7 @@ mod tests {
         assert!(manifest_toml.exists(), "Expected 'manifest.toml' to exist");
         assert!(manifest_json.exists(), "Expected 'manifest.json' to exist");
 
-        let clean_cmd = CleanArgs { all: true };
+        let clean_cmd = CleanArgs { all: true, ..Default::default() };
         clean_cmd.run(&config).unwrap();
 
         assert!(
@@ -131,4 +257,50 @@ mod tests {
         assert!(!manifest_toml.exists(), "Expected 'manifest.toml' to not exist");
         assert!(!manifest_json.exists(), "Expected 'manifest.json' to not exist");
     }
+
+    #[test]
+    fn test_clean_selected_packages() {
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let temp_dir = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+
+        let profile_dir = temp_dir.join("manifests").join("dev");
+        let base_dir = profile_dir.join("base");
+        let abis_dir = profile_dir.join("abis").join("base");
+        let target_dir = temp_dir.join("target").join("dev");
+
+        let write = |path: Utf8PathBuf, content: &str| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+
+        write(base_dir.join("dojo_world_world.toml"), "name = \"dojo::world::world\"");
+        write(abis_dir.join("dojo_world_world.json"), "[]");
+        write(
+            base_dir.join("models").join("dojo_examples_models_moves.toml"),
+            "name = \"dojo_examples::models::moves\"",
+        );
+        write(abis_dir.join("models").join("dojo_examples_models_moves.json"), "[]");
+
+        write(target_dir.join("dojo::world::world.json"), "{}");
+        write(target_dir.join("dojo_examples::models::moves.json"), "{}");
+        write(target_dir.join("dojo_examples::models::moves.casm.json"), "{}");
+        write(target_dir.join("src").join("dojo_examples_models_moves.cairo"), "");
+        write(target_dir.join("dojo_examples.starknet_artifacts.json"), "{}");
+
+        let clean_cmd =
+            CleanArgs { packages: vec!["dojo_examples".to_string()], ..Default::default() };
+        clean_cmd.clean_manifests(&profile_dir).unwrap();
+        clean_cmd.clean_artifacts(&target_dir).unwrap();
+
+        assert!(base_dir.join("dojo_world_world.toml").exists());
+        assert!(abis_dir.join("dojo_world_world.json").exists());
+        assert!(!base_dir.join("models").join("dojo_examples_models_moves.toml").exists());
+        assert!(!abis_dir.join("models").join("dojo_examples_models_moves.json").exists());
+
+        assert!(target_dir.join("dojo::world::world.json").exists());
+        assert!(target_dir.join("dojo_examples.starknet_artifacts.json").exists());
+        assert!(!target_dir.join("dojo_examples::models::moves.json").exists());
+        assert!(!target_dir.join("dojo_examples::models::moves.casm.json").exists());
+        assert!(!target_dir.join("src").join("dojo_examples_models_moves.cairo").exists());
+    }
 }
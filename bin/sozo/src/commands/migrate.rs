@@ -1,11 +1,14 @@
+use std::path::PathBuf;
+
 use anyhow::{anyhow, Context, Result};
 use clap::{Args, Subcommand};
 use dojo_lang::compiler::MANIFESTS_DIR;
+use dojo_world::contracts::world::WorldContract;
 use dojo_world::metadata::{dojo_metadata_from_workspace, Environment};
 use dojo_world::migration::TxnConfig;
 use katana_rpc_api::starknet::RPC_SPEC_VERSION;
 use scarb::core::{Config, Workspace};
-use sozo_ops::migration;
+use sozo_ops::migration::{self, DryRun, PlanFormat};
 use starknet::accounts::{Account, ConnectedAccount, SingleOwnerAccount};
 use starknet::core::types::{BlockId, BlockTag, FieldElement, StarknetError};
 use starknet::core::utils::parse_cairo_short_string;
@@ -26,7 +29,7 @@ pub struct MigrateArgs {
 
 #[derive(Debug, Subcommand)]
 pub enum MigrateCommand {
-    #[command(about = "Plan the migration and output the manifests.")]
+    #[command(about = "Plan the migration, estimate its fees and output the manifests.")]
     Plan {
         #[arg(long)]
         #[arg(help = "Name of the World.")]
@@ -34,6 +37,37 @@ pub enum MigrateCommand {
                            the contract to avoid address conflicts.")]
         name: Option<String>,
 
+        #[arg(short = 'j', long = "json")]
+        #[arg(help = "Print the plan as JSON. Use with `-q` to only print the JSON plan.")]
+        to_json: bool,
+
+        #[command(flatten)]
+        world: WorldOptions,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[command(flatten)]
+        account: AccountOptions,
+    },
+    #[command(about = "Simulate the whole migration and estimate the resources it uses.")]
+    #[command(long_about = "Simulate the whole migration and estimate the resources it uses, \
+                            without sending anything. The transactions are simulated on top of \
+                            each other against the node of `--rpc-url`, usually a local Katana \
+                            or a Katana forking the target network, and the total fee is \
+                            reported in ETH and STRK along with the resources used by every \
+                            operation.")]
+    Estimate {
+        #[arg(long)]
+        #[arg(help = "Name of the World.")]
+        #[arg(long_help = "Name of the World. It's hash will be used as a salt when deploying \
+                           the contract to avoid address conflicts.")]
+        name: Option<String>,
+
+        #[arg(short = 'j', long = "json")]
+        #[arg(help = "Print the estimate as JSON. Use with `-q` to only print the JSON estimate.")]
+        to_json: bool,
+
         #[command(flatten)]
         world: WorldOptions,
 
@@ -51,6 +85,14 @@ pub enum MigrateCommand {
                            the contract to avoid address conflicts.")]
         name: Option<String>,
 
+        #[arg(long, value_name = "PATH")]
+        #[arg(help = "Verify the migrated World with the checks of a verification file.")]
+        #[arg(long_help = "Verify the migrated World with the checks of a verification file, in \
+                           TOML or JSON: models which must be registered, expected owners, \
+                           read-only calls and simulated system calls. The command fails if any \
+                           check fails.")]
+        verify: Option<PathBuf>,
+
         #[command(flatten)]
         world: WorldOptions,
 
@@ -81,38 +123,17 @@ impl MigrateArgs {
         }
 
         match self.command {
-            MigrateCommand::Plan { mut name, world, starknet, account } => {
-                if name.is_none() {
-                    if let Some(root_package) = ws.root_package() {
-                        name = Some(root_package.id.name.to_string())
-                    }
-                };
-
-                config.tokio_handle().block_on(async {
-                    let (world_address, account, chain_id, rpc_url) = setup_env(
-                        &ws,
-                        account,
-                        starknet,
-                        world,
-                        name.as_ref(),
-                        env_metadata.as_ref(),
-                    )
-                    .await?;
-
-                    migration::migrate(
-                        &ws,
-                        world_address,
-                        chain_id,
-                        rpc_url,
-                        &account,
-                        name,
-                        true,
-                        TxnConfig::default(),
-                    )
-                    .await
-                })
+            MigrateCommand::Plan { name, to_json, world, starknet, account } => {
+                let format = if to_json { PlanFormat::Json } else { PlanFormat::Text };
+                let dry_run = DryRun::Plan(format);
+                run_dry(&ws, env_metadata.as_ref(), name, dry_run, world, starknet, account)
             }
-            MigrateCommand::Apply { mut name, world, starknet, account, transaction } => {
+            MigrateCommand::Estimate { name, to_json, world, starknet, account } => {
+                let format = if to_json { PlanFormat::Json } else { PlanFormat::Text };
+                let dry_run = DryRun::Estimate(format);
+                run_dry(&ws, env_metadata.as_ref(), name, dry_run, world, starknet, account)
+            }
+            MigrateCommand::Apply { mut name, verify, world, starknet, account, transaction } => {
                 let txn_config: TxnConfig = transaction.into();
 
                 if name.is_none() {
@@ -132,23 +153,70 @@ impl MigrateArgs {
                     )
                     .await?;
 
-                    migration::migrate(
+                    let world_address = migration::migrate(
                         &ws,
                         world_address,
                         chain_id,
                         rpc_url,
                         &account,
                         name,
-                        false,
+                        None,
                         txn_config,
                     )
-                    .await
+                    .await?;
+
+                    if let Some(path) = verify {
+                        let world_address = world_address.ok_or_else(|| {
+                            anyhow!("The World address is required to verify the migration.")
+                        })?;
+
+                        ws.config().ui().print("\nVerifying the World...");
+                        let world = WorldContract::new(world_address, account);
+                        sozo_ops::verify::verify(&world, path).await?;
+                    }
+
+                    Ok(())
                 })
             }
         }
     }
 }
 
+/// Runs the migration of the workspace without applying it.
+fn run_dry(
+    ws: &Workspace<'_>,
+    env_metadata: Option<&Environment>,
+    mut name: Option<String>,
+    dry_run: DryRun,
+    world: WorldOptions,
+    starknet: StarknetOptions,
+    account: AccountOptions,
+) -> Result<()> {
+    if name.is_none() {
+        if let Some(root_package) = ws.root_package() {
+            name = Some(root_package.id.name.to_string())
+        }
+    };
+
+    ws.config().tokio_handle().block_on(async {
+        let (world_address, account, chain_id, rpc_url) =
+            setup_env(ws, account, starknet, world, name.as_ref(), env_metadata).await?;
+
+        migration::migrate(
+            ws,
+            world_address,
+            chain_id,
+            rpc_url,
+            &account,
+            name,
+            Some(dry_run),
+            TxnConfig::default(),
+        )
+        .await
+        .map(|_| ())
+    })
+}
+
 pub async fn setup_env<'a>(
     ws: &'a Workspace<'a>,
     account: AccountOptions,
@@ -2,7 +2,9 @@ use anyhow::Result;
 use clap::{command, Subcommand};
 use scarb::core::Config;
 
+pub(crate) mod allowance;
 pub(crate) mod auth;
+pub(crate) mod balance;
 pub(crate) mod build;
 pub(crate) mod call;
 pub(crate) mod clean;
@@ -17,7 +19,9 @@ pub(crate) mod options;
 pub(crate) mod register;
 pub(crate) mod test;
 
+use allowance::AllowanceArgs;
 use auth::AuthArgs;
+use balance::BalanceArgs;
 use build::BuildArgs;
 use call::CallArgs;
 use clean::CleanArgs;
@@ -58,6 +62,14 @@ pub enum Commands {
     Events(EventsArgs),
     #[command(about = "Manage world authorization")]
     Auth(AuthArgs),
+    #[command(
+        about = "Retrieve the balance of an account in an ERC20 token, the fee token by default"
+    )]
+    Balance(BalanceArgs),
+    #[command(
+        about = "Retrieve the allowance of a spender in an ERC20 token, the fee token by default"
+    )]
+    Allowance(AllowanceArgs),
     #[command(about = "Generate shell completion file for specified shell")]
     Completions(CompletionsArgs),
 }
@@ -76,6 +88,8 @@ pub fn run(command: Commands, config: &Config) -> Result<()> {
         Commands::Model(args) => args.run(config),
         Commands::Register(args) => args.run(config),
         Commands::Events(args) => args.run(config),
+        Commands::Balance(args) => args.run(config),
+        Commands::Allowance(args) => args.run(config),
         Commands::Completions(args) => args.run(),
     }
 }
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use clap::Args;
+use dojo_world::metadata::Environment;
+use starknet::core::types::FieldElement;
+
+/// The address of the ETH fee token on Starknet networks and Katana.
+pub const DEFAULT_FEE_TOKEN_ADDRESS: &str =
+    "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+
+/// The address of the STRK fee token on Starknet networks.
+pub const DEFAULT_STRK_FEE_TOKEN_ADDRESS: &str =
+    "0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d";
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Fee token options")]
+pub struct FeeTokenOptions {
+    #[arg(long = "token")]
+    #[arg(value_name = "ADDRESS")]
+    #[arg(help = "The address of the ERC20 token. Defaults to the fee token of the profile, or \
+                  to the ETH fee token.")]
+    pub token_address: Option<FieldElement>,
+
+    #[arg(long)]
+    #[arg(conflicts_with = "token_address")]
+    #[arg(help = "Use the STRK fee token instead of the ETH one.")]
+    pub strk: bool,
+}
+
+impl FeeTokenOptions {
+    pub fn address(&self, env_metadata: Option<&Environment>) -> Result<FieldElement> {
+        if let Some(token_address) = self.token_address {
+            Ok(token_address)
+        } else if self.strk {
+            Ok(FieldElement::from_hex_be(DEFAULT_STRK_FEE_TOKEN_ADDRESS).unwrap())
+        } else if let Some(token_address) = env_metadata.and_then(|env| env.fee_token_address()) {
+            Ok(FieldElement::from_str(token_address)?)
+        } else {
+            Ok(FieldElement::from_hex_be(DEFAULT_FEE_TOKEN_ADDRESS).unwrap())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use starknet::core::types::FieldElement;
+
+    use super::{FeeTokenOptions, DEFAULT_FEE_TOKEN_ADDRESS, DEFAULT_STRK_FEE_TOKEN_ADDRESS};
+
+    #[derive(clap::Parser, Debug)]
+    struct Command {
+        #[clap(flatten)]
+        pub inner: FeeTokenOptions,
+    }
+
+    #[test]
+    fn token_address_from_args() {
+        let env_metadata = dojo_world::metadata::Environment {
+            fee_token_address: Some("0x1".to_owned()),
+            ..Default::default()
+        };
+
+        let cmd = Command::parse_from(["sozo", "--token", "0x2"]);
+        assert_eq!(
+            cmd.inner.address(Some(&env_metadata)).unwrap(),
+            FieldElement::from_hex_be("0x2").unwrap()
+        );
+    }
+
+    #[test]
+    fn token_address_from_env_metadata() {
+        let env_metadata = dojo_world::metadata::Environment {
+            fee_token_address: Some("0x1".to_owned()),
+            ..Default::default()
+        };
+
+        let cmd = Command::parse_from([""]);
+        assert_eq!(
+            cmd.inner.address(Some(&env_metadata)).unwrap(),
+            FieldElement::from_hex_be("0x1").unwrap()
+        );
+    }
+
+    #[test]
+    fn token_address_defaults_to_fee_token() {
+        let cmd = Command::parse_from([""]);
+        assert_eq!(
+            cmd.inner.address(None).unwrap(),
+            FieldElement::from_hex_be(DEFAULT_FEE_TOKEN_ADDRESS).unwrap()
+        );
+    }
+
+    #[test]
+    fn strk_fee_token() {
+        let env_metadata = dojo_world::metadata::Environment {
+            fee_token_address: Some("0x1".to_owned()),
+            ..Default::default()
+        };
+
+        let cmd = Command::parse_from(["sozo", "--strk"]);
+        assert_eq!(
+            cmd.inner.address(Some(&env_metadata)).unwrap(),
+            FieldElement::from_hex_be(DEFAULT_STRK_FEE_TOKEN_ADDRESS).unwrap()
+        );
+
+        assert!(Command::try_parse_from(["sozo", "--strk", "--token", "0x2"]).is_err());
+    }
+}
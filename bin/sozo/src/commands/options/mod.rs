@@ -1,4 +1,5 @@
 pub mod account;
+pub mod fee_token;
 pub mod starknet;
 pub mod transaction;
 pub mod world;
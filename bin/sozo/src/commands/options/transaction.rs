@@ -1,5 +1,6 @@
 use clap::Args;
 use dojo_world::migration::TxnConfig;
+use starknet::core::types::FieldElement;
 
 #[derive(Debug, Args)]
 #[command(next_help_heading = "Transaction options")]
@@ -12,6 +13,24 @@ pub struct TransactionOptions {
                        (max_fee = estimated_fee * multiplier)")]
     pub fee_estimate_multiplier: Option<f64>,
 
+    #[arg(long)]
+    #[arg(value_name = "WEI")]
+    #[arg(help = "The maximum fee a transaction can be sent with.")]
+    #[arg(long_help = "The maximum fee a transaction can be sent with. The max fee computed \
+                       from the fee estimate is capped to this value, and a transaction whose \
+                       estimated fee is higher isn't sent.")]
+    pub max_fee_cap: Option<FieldElement>,
+
+    #[arg(long)]
+    #[arg(value_name = "COUNT")]
+    #[arg(default_value_t = 0)]
+    #[arg(help = "The number of times a transaction rejected for an insufficient max fee is \
+                  sent again.")]
+    #[arg(long_help = "The number of times a transaction rejected for an insufficient max fee \
+                       is sent again. The max fee is multiplied by 1.5 on each retry, without \
+                       exceeding --max-fee-cap.")]
+    pub max_fee_retries: u32,
+
     #[arg(short, long)]
     #[arg(help = "Wait until the transaction is accepted by the sequencer, returning the status \
                   and hash.")]
@@ -27,14 +46,26 @@ pub struct TransactionOptions {
     #[arg(long_help = "If --wait is set, returns the full transaction receipt. Otherwise, it is \
                        a no-op.")]
     pub receipt: bool,
+
+    #[arg(long)]
+    #[arg(value_name = "COUNT")]
+    #[arg(help = "The maximum number of calls sent in a single multicall transaction.")]
+    #[arg(long_help = "The maximum number of calls sent in a single multicall transaction. \
+                       Calls are batched in as few transactions as possible, and batches are \
+                       split to fit in the calldata limit of a transaction.")]
+    #[arg(value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_calls: Option<u64>,
 }
 
 impl From<TransactionOptions> for TxnConfig {
     fn from(value: TransactionOptions) -> Self {
         Self {
             fee_estimate_multiplier: value.fee_estimate_multiplier,
+            max_fee_cap: value.max_fee_cap,
+            max_fee_retries: value.max_fee_retries,
             wait: value.wait,
             receipt: value.receipt,
+            max_calls: value.max_calls.map(|max_calls| max_calls as usize),
         }
     }
 }
@@ -0,0 +1,603 @@
+//! Torii library, used to build the Torii binary.
+//!
+//! Custom Torii binaries can index their own contracts by implementing
+//! [Processor](torii_core::processors::Processor) and passing it to [run]:
+//!
+//! ```ignore
+//! use clap::Parser;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     torii::init_tracing()?;
+//!     let shutdown_tx = torii::shutdown_on_ctrl_c()?;
+//!     torii::run(torii::Args::parse(), vec![Box::new(MarketplaceProcessor)], shutdown_tx).await
+//! }
+//! ```
+//!
+//! [run] installs nothing global, it's up to the binary to set up the tracing subscriber and the
+//! Ctrl-C handler, which can only be done once per process.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use clap::{ArgGroup, Parser, Subcommand};
+use common::parse::{parse_socket_address, parse_url};
+use dojo_metrics::{metrics_process, prometheus_exporter};
+use dojo_world::contracts::world::WorldContractReader;
+use dojo_world::lockfile::Lockfile;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use starknet::core::types::FieldElement;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::Sender;
+use tokio_stream::StreamExt;
+use torii_core::backend::{self, DatabaseBackend};
+use torii_core::backup;
+use torii_core::coalesce::CoalescingConfig;
+use torii_core::engine::{Engine, EngineConfig, Processors};
+use torii_core::filter::IndexingFilterFile;
+use torii_core::lag::LagSlo;
+use torii_core::privacy::Privacy;
+use torii_core::processors::{Processor, WorldProcessor};
+use torii_core::resolver::{
+    ContentResolver, ResolverConfig, DEFAULT_ARWEAVE_GATEWAY, DEFAULT_IPFS_GATEWAY,
+};
+use torii_core::simple_broker::SimpleBroker;
+use torii_core::sql::Sql;
+use torii_core::types::Model;
+use torii_server::proxy::Proxy;
+use tracing::{error, info, warn};
+use tracing_subscriber::{fmt, EnvFilter};
+use url::{form_urlencoded, Url};
+
+pub(crate) const LOG_TARGET: &str = "torii::cli";
+
+/// The provider used by the engine to fetch the world's blocks, transactions and events.
+pub type EngineProvider<'a> = &'a Arc<JsonRpcClient<HttpTransport>>;
+
+/// Processors plugged into the engine in addition to the ones indexing the Dojo world.
+pub type Plugins = Vec<Box<dyn for<'a> Processor<EngineProvider<'a>> + Send + Sync>>;
+
+/// Dojo World Indexer
+#[derive(Parser, Debug)]
+#[command(name = "torii", author, version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true)]
+#[command(group(
+    ArgGroup::new("lag_slo").multiple(true).args(["lag_slo_blocks", "lag_slo_seconds"])
+))]
+pub struct Args {
+    /// The world to index
+    #[arg(short, long = "world", env = "DOJO_WORLD_ADDRESS", required_unless_present = "lockfile")]
+    pub world_address: Option<FieldElement>,
+
+    /// Path to the `dojo.lock` of a project, from which the world of `--profile` and its
+    /// deployment block are read when they are not given.
+    #[arg(long, value_name = "PATH")]
+    pub lockfile: Option<PathBuf>,
+
+    /// The profile of the world read from the lockfile.
+    #[arg(long, value_name = "NAME", default_value = "dev", requires = "lockfile")]
+    pub profile: String,
+
+    /// The sequencer rpc endpoint to index.
+    #[arg(long, value_name = "URL", default_value = ":5050", value_parser = parse_url)]
+    pub rpc: Url,
+
+    /// Database filepath (ex: indexer.db). If specified file doesn't exist, it will be
+    /// created. Defaults to in-memory database. A Postgres url (ex:
+    /// postgres://torii@localhost/torii) writes the indexed data to Postgres, and to the SQLite
+    /// database of `--local-database` the api endpoints are served from.
+    #[arg(short, long, default_value = ":memory:")]
+    pub database: String,
+
+    /// The SQLite database filepath the api endpoints are served from when `--database` is a
+    /// Postgres url. The indexing resumes from the head of this database, so it should be a file
+    /// to not index the world again into Postgres after a restart.
+    #[arg(long, value_name = "PATH", default_value = ":memory:")]
+    pub local_database: String,
+
+    /// Specify a block to start indexing from, ignored if stored head exists. Defaults to the
+    /// deployment block of the world read from the lockfile, or to 0.
+    #[arg(short, long)]
+    pub start_block: Option<u64>,
+
+    /// Address to serve api endpoints at.
+    #[arg(long, value_name = "SOCKET", default_value = "0.0.0.0:8080", value_parser = parse_socket_address)]
+    pub addr: SocketAddr,
+
+    /// Port to serve Libp2p TCP & UDP Quic transports
+    #[arg(long, value_name = "PORT", default_value = "9090")]
+    pub relay_port: u16,
+
+    /// Port to serve Libp2p WebRTC transport
+    #[arg(long, value_name = "PORT", default_value = "9091")]
+    pub relay_webrtc_port: u16,
+
+    /// Path to a local identity key file. If not specified, a new identity will be generated
+    #[arg(long, value_name = "PATH")]
+    pub relay_local_key_path: Option<String>,
+
+    /// Path to a local certificate file. If not specified, a new certificate will be generated
+    /// for WebRTC connections
+    #[arg(long, value_name = "PATH")]
+    pub relay_cert_path: Option<String>,
+
+    /// Specify allowed origins for api endpoints (comma-separated list of allowed origins, or "*"
+    /// for all)
+    #[arg(long, default_value = "*")]
+    #[arg(value_delimiter = ',')]
+    pub allowed_origins: Vec<String>,
+
+    /// The external url of the server, used for configuring the GraphQL Playground in a hosted
+    /// environment
+    #[arg(long)]
+    pub external_url: Option<Url>,
+
+    /// Enable Prometheus metrics.
+    ///
+    /// The metrics will be served at the given interface and port.
+    #[arg(long, value_name = "SOCKET", value_parser = parse_socket_address, help_heading = "Metrics")]
+    pub metrics: Option<SocketAddr>,
+
+    /// The maximum number of blocks the indexer may be behind the head of the chain. The breaches
+    /// of the SLO are logged and posted to `--lag-alert-webhook`.
+    #[arg(long, value_name = "BLOCKS", help_heading = "Metrics")]
+    pub lag_slo_blocks: Option<u64>,
+
+    /// The maximum number of seconds the oldest block not indexed yet may have been waiting for.
+    /// The breaches of the SLO are logged and posted to `--lag-alert-webhook`.
+    #[arg(long, value_name = "SECONDS", help_heading = "Metrics")]
+    pub lag_slo_seconds: Option<u64>,
+
+    /// URL the alerts are posted to, in the format of the Alertmanager webhooks, when the
+    /// indexing lag exceeds its SLO and once it's back within it.
+    #[arg(long, value_name = "URL", value_parser = parse_url, help_heading = "Metrics")]
+    #[arg(requires = "lag_slo")]
+    pub lag_alert_webhook: Option<Url>,
+
+    /// Open World Explorer on the browser.
+    #[arg(long)]
+    pub explorer: bool,
+
+    /// Chunk size of the events page when indexing using events
+    #[arg(long, default_value = "1000")]
+    pub events_chunk_size: u64,
+
+    /// Path to a TOML file restricting indexing to the listed world addresses, model names and
+    /// event selectors. The file is reloaded when it is modified.
+    #[arg(long, value_name = "PATH")]
+    pub indexing_filter: Option<PathBuf>,
+
+    /// Path to a TOML file listing the models and model members which are indexed but excluded
+    /// from the GraphQL and gRPC responses.
+    #[arg(long, value_name = "PATH")]
+    pub privacy: Option<PathBuf>,
+
+    /// Directory where incremental backups of the database are written. A snapshot of the
+    /// database is taken on every start, followed by the log of the changes applied to it, which
+    /// `torii restore` restores from.
+    #[arg(long, value_name = "PATH")]
+    pub backup_dir: Option<PathBuf>,
+
+    /// Models whose successive updates are coalesced: the updates of an entity received within
+    /// `--coalesce-window` are merged into a single write of its latest value and a single
+    /// subscription push. Only for models whose intermediate values don't matter, eg. positions.
+    #[arg(long, value_name = "MODELS", value_delimiter = ',', requires = "coalesce_window")]
+    pub coalesce_models: Vec<String>,
+
+    /// The window, in milliseconds, within which the updates of the coalesced models are merged.
+    #[arg(long, value_name = "MILLISECONDS", requires = "coalesce_models")]
+    pub coalesce_window: Option<u64>,
+
+    /// The IPFS gateways the `ipfs://` URIs of the metadata are resolved through, tried in order.
+    #[arg(long, value_name = "URLS", value_delimiter = ',', help_heading = "Metadata")]
+    #[arg(default_value = DEFAULT_IPFS_GATEWAY, value_parser = parse_url)]
+    pub ipfs_gateways: Vec<Url>,
+
+    /// The Arweave gateways the `ar://` URIs of the metadata are resolved through, tried in order.
+    #[arg(long, value_name = "URLS", value_delimiter = ',', help_heading = "Metadata")]
+    #[arg(default_value = DEFAULT_ARWEAVE_GATEWAY, value_parser = parse_url)]
+    pub arweave_gateways: Vec<Url>,
+
+    /// The number of attempts made with every gateway to resolve a metadata URI.
+    #[arg(long, value_name = "ATTEMPTS", default_value = "3", help_heading = "Metadata")]
+    pub metadata_max_attempts: u32,
+
+    /// The maximum size, in bytes, of the resolved metadata and images.
+    #[arg(long, value_name = "BYTES", default_value = "10485760", help_heading = "Metadata")]
+    pub metadata_max_size: usize,
+
+    /// The maximum number of metadata URIs resolved at once.
+    #[arg(long, value_name = "COUNT", default_value = "16", help_heading = "Metadata")]
+    pub metadata_max_concurrent: usize,
+
+    /// Allows resolving the metadata from loopback, private and link-local addresses, eg. through
+    /// a local IPFS gateway. Any indexed URI can then make Torii query the local network.
+    #[arg(long, help_heading = "Metadata")]
+    pub metadata_allow_private_addresses: bool,
+
+    /// Databases of other Torii instances, each indexing another world, which the GraphQL search
+    /// also searches. They are opened read-only.
+    #[arg(long, value_name = "PATHS", value_delimiter = ',')]
+    pub search_databases: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Restore the database from its incremental backups, as it was once a block was indexed.
+    Restore(RestoreArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RestoreArgs {
+    /// The directory of the backups, as given to `--backup-dir`.
+    #[arg(long, value_name = "PATH")]
+    pub backup_dir: PathBuf,
+
+    /// Filepath of the restored database, which must not exist yet.
+    #[arg(short, long, value_name = "PATH")]
+    pub database: PathBuf,
+
+    /// The block to restore the database to. The sync cursor of the restored database is reset
+    /// to it, so that Torii indexes the following blocks again.
+    #[arg(long, value_name = "BLOCK_NUMBER")]
+    pub to_block: u64,
+}
+
+/// Sets the global tracing subscriber, logging at the level of the `RUST_LOG` environment
+/// variable, `info` by default.
+pub fn init_tracing() -> anyhow::Result<()> {
+    let filter_layer = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("info,hyper_reverse_proxy=off"));
+
+    fmt::Subscriber::builder()
+        .with_env_filter(filter_layer)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!("Failed to set the global tracing subscriber: {e}"))
+}
+
+/// Sets the Ctrl-C handler of the process, and returns the channel a message is sent on when
+/// Ctrl-C is pressed, to be given to [run].
+pub fn shutdown_on_ctrl_c() -> anyhow::Result<Sender<()>> {
+    let (shutdown_tx, _) = broadcast::channel(1);
+
+    let shutdown_tx_clone = shutdown_tx.clone();
+    ctrlc::set_handler(move || {
+        let _ = shutdown_tx_clone.send(());
+    })
+    .context("Error setting Ctrl-C handler")?;
+
+    Ok(shutdown_tx)
+}
+
+/// Runs Torii with the given arguments, until a message is sent on `shutdown_tx`.
+///
+/// The processors of `plugins` are registered to the engine after the ones indexing the Dojo
+/// world, allowing custom Torii binaries to index their own contracts.
+pub async fn run(args: Args, plugins: Plugins, shutdown_tx: Sender<()>) -> anyhow::Result<()> {
+    if let Some(Command::Restore(restore)) = args.command {
+        let head =
+            backup::restore(&restore.backup_dir, &restore.database, restore.to_block).await?;
+        if head < restore.to_block {
+            warn!(
+                target: LOG_TARGET,
+                to_block = %restore.to_block,
+                %head,
+                "The backup has no later state, the database was restored to an earlier block."
+            );
+        }
+        return Ok(());
+    }
+
+    let (world_address, start_block) = resolve_world(&args)?;
+
+    let (sqlite_database, mirror) = match DatabaseBackend::from_url(&args.database) {
+        DatabaseBackend::Sqlite => (&args.database, None),
+        DatabaseBackend::Postgres => {
+            let mirror =
+                backend::connect(&args.database).await.context("Failed to connect to Postgres")?;
+            (&args.local_database, Some(mirror))
+        }
+    };
+
+    let database_url = format!("sqlite:{}", sqlite_database);
+    let options =
+        SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true).with_regexp();
+    let pool = SqlitePoolOptions::new()
+        .min_connections(1)
+        .max_connections(5)
+        .connect_with(options)
+        .await?;
+
+    if sqlite_database == ":memory:" {
+        // Disable auto-vacuum
+        sqlx::query("PRAGMA auto_vacuum = NONE;").execute(&pool).await?;
+
+        // Switch DELETE journal mode
+        sqlx::query("PRAGMA journal_mode=DELETE;").execute(&pool).await?;
+    }
+
+    sqlx::migrate!("../../crates/torii/migrations").run(&pool).await?;
+
+    let mut search_pools = Vec::with_capacity(args.search_databases.len());
+    for database in &args.search_databases {
+        let options = SqliteConnectOptions::new().filename(database).read_only(true);
+        let search_pool = SqlitePoolOptions::new().max_connections(2).connect_with(options).await;
+        let context = || format!("Failed to open search database {}", database.display());
+        search_pools.push(search_pool.with_context(context)?);
+    }
+
+    let provider: Arc<_> = JsonRpcClient::new(HttpTransport::new(args.rpc)).into();
+
+    // Get world address
+    let world = WorldContractReader::new(world_address, &provider);
+
+    let db = Sql::new(pool.clone(), world_address).await?;
+    let db = match mirror {
+        Some(mirror) => db.with_mirror(mirror).await?,
+        None => db,
+    };
+    let db = match &args.backup_dir {
+        Some(backup_dir) => db.with_backup(backup_dir).await?,
+        None => db,
+    };
+
+    let resolver = ContentResolver::new(ResolverConfig {
+        ipfs_gateways: args.ipfs_gateways,
+        arweave_gateways: args.arweave_gateways,
+        max_attempts: args.metadata_max_attempts,
+        max_size: args.metadata_max_size,
+        max_concurrent: args.metadata_max_concurrent,
+        allow_private_addresses: args.metadata_allow_private_addresses,
+        ..Default::default()
+    });
+
+    let mut processors = Processors::default();
+    processors.register(&WorldProcessor::new(Arc::new(resolver)));
+    for plugin in &plugins {
+        processors.register(plugin.as_ref());
+    }
+
+    let filter = args.indexing_filter.map(IndexingFilterFile::load).transpose()?;
+    let privacy = args.privacy.map(Privacy::load).transpose()?.unwrap_or_default();
+
+    let (block_tx, block_rx) = tokio::sync::mpsc::channel(100);
+
+    // only the updates indexed by the engine are coalesced, the messages of the relay are written
+    // as they are received
+    let engine_db = match args.coalesce_window {
+        Some(window) => db.clone().with_coalescing(CoalescingConfig {
+            window: Duration::from_millis(window),
+            models: args.coalesce_models.into_iter().collect(),
+        }),
+        None => db.clone(),
+    };
+
+    let lag_slo =
+        (args.lag_slo_blocks.is_some() || args.lag_slo_seconds.is_some()).then(|| LagSlo {
+            max_blocks: args.lag_slo_blocks,
+            max_seconds: args.lag_slo_seconds,
+            webhook: args.lag_alert_webhook.clone(),
+        });
+
+    let mut engine = Engine::new(
+        world,
+        engine_db,
+        &provider,
+        processors,
+        EngineConfig {
+            start_block,
+            events_chunk_size: args.events_chunk_size,
+            filter,
+            lag_slo,
+            ..Default::default()
+        },
+        shutdown_tx.clone(),
+        Some(block_tx),
+    );
+
+    let shutdown_rx = shutdown_tx.subscribe();
+    let (grpc_addr, grpc_server) = torii_grpc::server::new(
+        shutdown_rx,
+        &pool,
+        block_rx,
+        world_address,
+        Arc::clone(&provider),
+        privacy.clone(),
+    )
+    .await?;
+
+    let mut libp2p_relay_server = torii_relay::server::Relay::new(
+        db,
+        provider.clone(),
+        args.relay_port,
+        args.relay_webrtc_port,
+        args.relay_local_key_path,
+        args.relay_cert_path,
+    )
+    .context("Failed to start libp2p relay server")?;
+
+    let proxy_server = Arc::new(Proxy::new(args.addr, args.allowed_origins, Some(grpc_addr), None));
+
+    let graphql_server = spawn_rebuilding_graphql_server(
+        shutdown_tx.clone(),
+        pool.into(),
+        args.external_url,
+        proxy_server.clone(),
+        privacy,
+        search_pools,
+    );
+
+    let endpoint = format!("http://{}", args.addr);
+    let gql_endpoint = format!("{}/graphql", endpoint);
+    let encoded: String =
+        form_urlencoded::byte_serialize(gql_endpoint.replace("0.0.0.0", "localhost").as_bytes())
+            .collect();
+    let explorer_url = format!("https://worlds.dev/torii?url={}", encoded);
+    info!(target: LOG_TARGET, endpoint = %endpoint, "Starting torii endpoint.");
+    info!(target: LOG_TARGET, endpoint = %gql_endpoint, "Serving Graphql playground.");
+    info!(target: LOG_TARGET, url = %explorer_url, "Serving World Explorer.");
+
+    if args.explorer {
+        if let Err(e) = webbrowser::open(&explorer_url) {
+            error!(target: LOG_TARGET, error = %e, "Opening World Explorer in the browser.");
+        }
+    }
+
+    if let Some(listen_addr) = args.metrics {
+        let prometheus_handle = prometheus_exporter::install_recorder("torii")?;
+
+        info!(target: LOG_TARGET, addr = %listen_addr, "Starting metrics endpoint.");
+        prometheus_exporter::serve(
+            listen_addr,
+            prometheus_handle,
+            metrics_process::Collector::default(),
+        )
+        .await?;
+    }
+
+    tokio::select! {
+        _ = engine.start() => {},
+        _ = proxy_server.start(shutdown_tx.subscribe()) => {},
+        _ = graphql_server => {},
+        _ = grpc_server => {},
+        _ = libp2p_relay_server.run() => {},
+    };
+
+    Ok(())
+}
+
+/// Returns the world to index and the block to start indexing from, read from the lockfile when
+/// they are not given.
+fn resolve_world(args: &Args) -> anyhow::Result<(FieldElement, u64)> {
+    let locked = match &args.lockfile {
+        Some(path) => {
+            let lockfile = Lockfile::load_from_path(path)?;
+            let world = lockfile.world(&args.profile).cloned();
+            if world.is_none() && args.world_address.is_none() {
+                anyhow::bail!(
+                    "No world of profile `{}` in lockfile {}",
+                    args.profile,
+                    path.display()
+                );
+            }
+            world
+        }
+        None => None,
+    };
+
+    let world_address = match (args.world_address, &locked) {
+        (Some(address), _) => address,
+        (None, Some(locked)) => locked.world_address,
+        (None, None) => unreachable!("the world address is required without a lockfile"),
+    };
+
+    // the deployment block of another world than the one indexed is meaningless
+    let deployment_block = locked
+        .filter(|locked| locked.world_address == world_address)
+        .and_then(|locked| locked.deployment_block);
+
+    Ok((world_address, args.start_block.or(deployment_block).unwrap_or(0)))
+}
+
+async fn spawn_rebuilding_graphql_server(
+    shutdown_tx: Sender<()>,
+    pool: Arc<SqlitePool>,
+    external_url: Option<Url>,
+    proxy_server: Arc<Proxy>,
+    privacy: Privacy,
+    search_pools: Vec<SqlitePool>,
+) {
+    let mut broker = SimpleBroker::<Model>::subscribe();
+
+    loop {
+        let shutdown_rx = shutdown_tx.subscribe();
+        let (new_addr, new_server) = torii_graphql::server::new(
+            shutdown_rx,
+            &pool,
+            external_url.clone(),
+            privacy.clone(),
+            search_pools.clone(),
+        )
+        .await;
+
+        tokio::spawn(new_server);
+
+        proxy_server.set_graphql_addr(new_addr).await;
+
+        // Break the loop if there are no more events
+        if broker.next().await.is_none() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_world::lockfile::LockedWorld;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_run_can_be_run_again() {
+        // nothing global is installed by a run, so that it can be run again after failing
+        for _ in 0..2 {
+            let args = Args::parse_from(["torii", "--lockfile", "/nonexistent/dojo.lock"]);
+            let (shutdown_tx, _) = broadcast::channel(1);
+            assert!(run(args, Vec::new(), shutdown_tx).await.is_err());
+        }
+    }
+
+    #[test]
+    fn resolve_world_from_args_and_lockfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let lockfile_path = dir.path().join("dojo.lock");
+        let mut lockfile = Lockfile::default();
+        lockfile.set_world(
+            "dev",
+            LockedWorld {
+                world_address: FieldElement::ONE,
+                deployment_block: Some(10),
+                rpc_url: None,
+                classes: Default::default(),
+            },
+        );
+        lockfile.write_to_path(&lockfile_path).unwrap();
+        let lockfile_path = lockfile_path.to_str().unwrap();
+
+        let resolve = |args: &[&str]| {
+            resolve_world(&Args::parse_from([&["torii"], args].concat())).map_err(|e| e.to_string())
+        };
+
+        // the world and its deployment block are read from the lockfile
+        assert_eq!(resolve(&["--lockfile", lockfile_path]), Ok((FieldElement::ONE, 10)));
+        assert_eq!(
+            resolve(&["--lockfile", lockfile_path, "--start-block", "3"]),
+            Ok((FieldElement::ONE, 3))
+        );
+
+        // the deployment block of another world is ignored
+        assert_eq!(resolve(&["--lockfile", lockfile_path, "--world", "0x2"]), Ok((2u8.into(), 0)));
+        assert_eq!(resolve(&["--world", "0x2", "--start-block", "3"]), Ok((2u8.into(), 3)));
+
+        let error = resolve(&["--lockfile", lockfile_path, "--profile", "prod"]).unwrap_err();
+        assert!(error.starts_with("No world of profile `prod` in lockfile"));
+        let world = ["--lockfile", lockfile_path, "--profile", "prod", "--world", "0x2"];
+        assert_eq!(resolve(&world), Ok((2u8.into(), 0)));
+    }
+
+    #[test]
+    fn tracing_is_only_initialized_once() {
+        init_tracing().unwrap();
+        assert!(init_tracing().is_err());
+    }
+}
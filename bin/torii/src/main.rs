@@ -10,269 +10,15 @@
 //!   documentation for usage details. This is **not recommended on Windows**. See [here](https://rust-lang.github.io/rfcs/1974-global-allocators.html#jemalloc)
 //!   for more info.
 
-use std::net::SocketAddr;
-use std::str::FromStr;
-use std::sync::Arc;
-
 use clap::Parser;
-use common::parse::{parse_socket_address, parse_url};
-use dojo_metrics::{metrics_process, prometheus_exporter};
-use dojo_world::contracts::world::WorldContractReader;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use sqlx::SqlitePool;
-use starknet::core::types::FieldElement;
-use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::JsonRpcClient;
-use tokio::sync::broadcast;
-use tokio::sync::broadcast::Sender;
-use tokio_stream::StreamExt;
-use torii_core::engine::{Engine, EngineConfig, Processors};
-use torii_core::processors::event_message::EventMessageProcessor;
-use torii_core::processors::metadata_update::MetadataUpdateProcessor;
-use torii_core::processors::register_model::RegisterModelProcessor;
-use torii_core::processors::store_del_record::StoreDelRecordProcessor;
-use torii_core::processors::store_set_record::StoreSetRecordProcessor;
-use torii_core::processors::store_transaction::StoreTransactionProcessor;
-use torii_core::simple_broker::SimpleBroker;
-use torii_core::sql::Sql;
-use torii_core::types::Model;
-use torii_server::proxy::Proxy;
-use tracing::{error, info};
-use tracing_subscriber::{fmt, EnvFilter};
-use url::{form_urlencoded, Url};
-
-pub(crate) const LOG_TARGET: &str = "torii::cli";
-
-/// Dojo World Indexer
-#[derive(Parser, Debug)]
-#[command(name = "torii", author, version, about, long_about = None)]
-struct Args {
-    /// The world to index
-    #[arg(short, long = "world", env = "DOJO_WORLD_ADDRESS")]
-    world_address: FieldElement,
-
-    /// The sequencer rpc endpoint to index.
-    #[arg(long, value_name = "URL", default_value = ":5050", value_parser = parse_url)]
-    rpc: Url,
-
-    /// Database filepath (ex: indexer.db). If specified file doesn't exist, it will be
-    /// created. Defaults to in-memory database
-    #[arg(short, long, default_value = ":memory:")]
-    database: String,
-
-    /// Specify a block to start indexing from, ignored if stored head exists
-    #[arg(short, long, default_value = "0")]
-    start_block: u64,
-
-    /// Address to serve api endpoints at.
-    #[arg(long, value_name = "SOCKET", default_value = "0.0.0.0:8080", value_parser = parse_socket_address)]
-    addr: SocketAddr,
-
-    /// Port to serve Libp2p TCP & UDP Quic transports
-    #[arg(long, value_name = "PORT", default_value = "9090")]
-    relay_port: u16,
-
-    /// Port to serve Libp2p WebRTC transport
-    #[arg(long, value_name = "PORT", default_value = "9091")]
-    relay_webrtc_port: u16,
-
-    /// Path to a local identity key file. If not specified, a new identity will be generated
-    #[arg(long, value_name = "PATH")]
-    relay_local_key_path: Option<String>,
-
-    /// Path to a local certificate file. If not specified, a new certificate will be generated
-    /// for WebRTC connections
-    #[arg(long, value_name = "PATH")]
-    relay_cert_path: Option<String>,
-
-    /// Specify allowed origins for api endpoints (comma-separated list of allowed origins, or "*"
-    /// for all)
-    #[arg(long, default_value = "*")]
-    #[arg(value_delimiter = ',')]
-    allowed_origins: Vec<String>,
-
-    /// The external url of the server, used for configuring the GraphQL Playground in a hosted
-    /// environment
-    #[arg(long)]
-    external_url: Option<Url>,
-
-    /// Enable Prometheus metrics.
-    ///
-    /// The metrics will be served at the given interface and port.
-    #[arg(long, value_name = "SOCKET", value_parser = parse_socket_address, help_heading = "Metrics")]
-    metrics: Option<SocketAddr>,
-
-    /// Open World Explorer on the browser.
-    #[arg(long)]
-    explorer: bool,
-
-    /// Chunk size of the events page when indexing using events
-    #[arg(long, default_value = "1000")]
-    events_chunk_size: u64,
-}
+use torii::Args;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
-    let filter_layer = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info,hyper_reverse_proxy=off"));
-
-    let subscriber = fmt::Subscriber::builder().with_env_filter(filter_layer).finish();
-
-    // Set the global subscriber
-    tracing::subscriber::set_global_default(subscriber)
-        .expect("Failed to set the global tracing subscriber");
-
-    // Setup cancellation for graceful shutdown
-    let (shutdown_tx, _) = broadcast::channel(1);
-
-    let shutdown_tx_clone = shutdown_tx.clone();
-    ctrlc::set_handler(move || {
-        let _ = shutdown_tx_clone.send(());
-    })
-    .expect("Error setting Ctrl-C handler");
-
-    let database_url = format!("sqlite:{}", &args.database);
-    let options =
-        SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true).with_regexp();
-    let pool = SqlitePoolOptions::new()
-        .min_connections(1)
-        .max_connections(5)
-        .connect_with(options)
-        .await?;
-
-    if args.database == ":memory:" {
-        // Disable auto-vacuum
-        sqlx::query("PRAGMA auto_vacuum = NONE;").execute(&pool).await?;
-
-        // Switch DELETE journal mode
-        sqlx::query("PRAGMA journal_mode=DELETE;").execute(&pool).await?;
-    }
-
-    sqlx::migrate!("../../crates/torii/migrations").run(&pool).await?;
-
-    let provider: Arc<_> = JsonRpcClient::new(HttpTransport::new(args.rpc)).into();
-
-    // Get world address
-    let world = WorldContractReader::new(args.world_address, &provider);
-
-    let db = Sql::new(pool.clone(), args.world_address).await?;
-    let processors = Processors {
-        event: vec![
-            Box::new(RegisterModelProcessor),
-            Box::new(StoreSetRecordProcessor),
-            Box::new(MetadataUpdateProcessor),
-            Box::new(StoreDelRecordProcessor),
-            Box::new(EventMessageProcessor),
-        ],
-        transaction: vec![Box::new(StoreTransactionProcessor)],
-        ..Processors::default()
-    };
-
-    let (block_tx, block_rx) = tokio::sync::mpsc::channel(100);
-
-    let mut engine = Engine::new(
-        world,
-        db.clone(),
-        &provider,
-        processors,
-        EngineConfig {
-            start_block: args.start_block,
-            events_chunk_size: args.events_chunk_size,
-            ..Default::default()
-        },
-        shutdown_tx.clone(),
-        Some(block_tx),
-    );
-
-    let shutdown_rx = shutdown_tx.subscribe();
-    let (grpc_addr, grpc_server) = torii_grpc::server::new(
-        shutdown_rx,
-        &pool,
-        block_rx,
-        args.world_address,
-        Arc::clone(&provider),
-    )
-    .await?;
-
-    let mut libp2p_relay_server = torii_relay::server::Relay::new(
-        db,
-        provider.clone(),
-        args.relay_port,
-        args.relay_webrtc_port,
-        args.relay_local_key_path,
-        args.relay_cert_path,
-    )
-    .expect("Failed to start libp2p relay server");
-
-    let proxy_server = Arc::new(Proxy::new(args.addr, args.allowed_origins, Some(grpc_addr), None));
-
-    let graphql_server = spawn_rebuilding_graphql_server(
-        shutdown_tx.clone(),
-        pool.into(),
-        args.external_url,
-        proxy_server.clone(),
-    );
-
-    let endpoint = format!("http://{}", args.addr);
-    let gql_endpoint = format!("{}/graphql", endpoint);
-    let encoded: String =
-        form_urlencoded::byte_serialize(gql_endpoint.replace("0.0.0.0", "localhost").as_bytes())
-            .collect();
-    let explorer_url = format!("https://worlds.dev/torii?url={}", encoded);
-    info!(target: LOG_TARGET, endpoint = %endpoint, "Starting torii endpoint.");
-    info!(target: LOG_TARGET, endpoint = %gql_endpoint, "Serving Graphql playground.");
-    info!(target: LOG_TARGET, url = %explorer_url, "Serving World Explorer.");
-
-    if args.explorer {
-        if let Err(e) = webbrowser::open(&explorer_url) {
-            error!(target: LOG_TARGET, error = %e, "Opening World Explorer in the browser.");
-        }
-    }
-
-    if let Some(listen_addr) = args.metrics {
-        let prometheus_handle = prometheus_exporter::install_recorder("torii")?;
-
-        info!(target: LOG_TARGET, addr = %listen_addr, "Starting metrics endpoint.");
-        prometheus_exporter::serve(
-            listen_addr,
-            prometheus_handle,
-            metrics_process::Collector::default(),
-        )
-        .await?;
-    }
-
-    tokio::select! {
-        _ = engine.start() => {},
-        _ = proxy_server.start(shutdown_tx.subscribe()) => {},
-        _ = graphql_server => {},
-        _ = grpc_server => {},
-        _ = libp2p_relay_server.run() => {},
-    };
-
-    Ok(())
-}
-
-async fn spawn_rebuilding_graphql_server(
-    shutdown_tx: Sender<()>,
-    pool: Arc<SqlitePool>,
-    external_url: Option<Url>,
-    proxy_server: Arc<Proxy>,
-) {
-    let mut broker = SimpleBroker::<Model>::subscribe();
-
-    loop {
-        let shutdown_rx = shutdown_tx.subscribe();
-        let (new_addr, new_server) =
-            torii_graphql::server::new(shutdown_rx, &pool, external_url.clone()).await;
-
-        tokio::spawn(new_server);
 
-        proxy_server.set_graphql_addr(new_addr).await;
+    torii::init_tracing()?;
+    let shutdown_tx = torii::shutdown_on_ctrl_c()?;
 
-        // Break the loop if there are no more events
-        if broker.next().await.is_none() {
-            break;
-        }
-    }
+    torii::run(args, Vec::new(), shutdown_tx).await
 }
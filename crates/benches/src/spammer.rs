@@ -1,34 +1,64 @@
 use std::time::Duration;
 
 use futures::future::join_all;
+use katana_primitives::chain::ChainId;
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::genesis::allocation::DevGenesisAccount;
+use katana_rpc_types::client::{InvokeTxBuilder, KatanaClient};
+use katana_rpc_types::transaction::BroadcastedInvokeTx;
 use katana_runner::KatanaRunner;
-use starknet::accounts::{Account, SingleOwnerAccount};
+use starknet::accounts::Call;
 use starknet::core::types::FieldElement;
-use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::JsonRpcClient;
-use starknet::signers::LocalWallet;
+use starknet::signers::SigningKey;
 use tokio::time::{sleep, Instant};
 
 use crate::summary::BenchSummary;
 use crate::{parse_calls, BenchCall, ENOUGH_GAS};
 
+/// Encodes `calls` as the calldata of the `__execute__` of a Cairo 1 account.
+fn encode_calls(calls: &[Call]) -> Vec<FieldElement> {
+    let mut calldata = vec![calls.len().into()];
+    for call in calls {
+        calldata.extend([call.to, call.selector, call.calldata.len().into()]);
+        calldata.extend_from_slice(&call.calldata);
+    }
+    calldata
+}
+
+/// Builds the invocation of `calls` by `account`, signed with the key of the account.
+fn invoke_tx(
+    chain_id: ChainId,
+    account: &(ContractAddress, DevGenesisAccount),
+    calls: &[Call],
+    nonce: FieldElement,
+) -> BroadcastedInvokeTx {
+    let max_fee = FieldElement::from_hex_be(ENOUGH_GAS).unwrap();
+    let builder =
+        InvokeTxBuilder::new(account.0).calldata(encode_calls(calls)).nonce(nonce).max_fee(max_fee);
+
+    let hash = builder.hash(chain_id).unwrap();
+    let signature = SigningKey::from_secret_scalar(account.1.private_key).sign(&hash).unwrap();
+    builder.signature(vec![signature.r, signature.s]).build().unwrap()
+}
+
 async fn spam_no_stats(
     runner: &KatanaRunner,
-    accounts: &[SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>],
+    client: &KatanaClient,
+    chain_id: ChainId,
     contract_address: FieldElement,
     calldata: Vec<BenchCall>,
     wait_time: Duration,
 ) -> FieldElement {
-    let max_fee = FieldElement::from_hex_be(ENOUGH_GAS).unwrap();
     let mut nonce = FieldElement::ONE;
 
     for call in parse_calls(calldata, contract_address) {
-        let transactions = accounts
+        let transactions = runner
+            .accounts_data()
             .iter()
-            .map(|account| account.execute(vec![call.clone()]).nonce(nonce).max_fee(max_fee))
+            .map(|account| invoke_tx(chain_id, account, &[call.clone()], nonce))
             .collect::<Vec<_>>();
 
-        join_all(transactions.iter().map(|t| t.send())).await;
+        join_all(transactions.into_iter().map(|tx| client.add_invoke_transaction(tx))).await;
 
         sleep(wait_time).await;
         runner.blocks_until_empty().await;
@@ -45,13 +75,14 @@ pub async fn spam_katana(
     additional_sleep: u64,
     sequential: bool,
 ) -> BenchSummary {
-    let max_fee = FieldElement::from_hex_be(ENOUGH_GAS).unwrap();
+    let client = KatanaClient::new(runner.endpoint()).unwrap();
+    let chain_id = client.chain_id().await.unwrap();
 
     let transaction_sum_before: u32 = runner.block_sizes().await.iter().sum();
     let steps_before = runner.steps().await;
 
     // generating all needed accounts
-    let accounts = runner.accounts();
+    let accounts = runner.accounts_data();
     let wait_time = Duration::from_millis(accounts.len() as u64 * 60 + 3000 + additional_sleep);
     let name = format!(
         "Benchmark: {} accounts, {} transactions, {} calls",
@@ -72,20 +103,18 @@ pub async fn spam_katana(
     let expected_transactions = (calldata.len() + 1) * accounts.len();
 
     // transactions preparing for the benchmarked one
-    let nonce = spam_no_stats(&runner, &accounts, contract_address, calldata, wait_time).await;
+    let nonce =
+        spam_no_stats(&runner, &client, chain_id, contract_address, calldata, wait_time).await;
 
     // the benchmarked transaction
     let final_transactions = accounts
         .iter()
-        .map(|account| {
-            let move_call = account.execute(calls.clone()).nonce(nonce).max_fee(max_fee);
-            move_call
-        })
+        .map(|account| invoke_tx(chain_id, account, &calls, nonce))
         .collect::<Vec<_>>();
 
     let before = Instant::now();
-    let transaction_hashes = join_all(final_transactions.iter().map(|t| async {
-        let r = t.send().await;
+    let transaction_hashes = join_all(final_transactions.into_iter().map(|tx| async {
+        let r = client.add_invoke_transaction(tx).await;
         (r, Instant::now())
     }))
     .await;
@@ -6,7 +6,9 @@ use cainome::parser::tokens::Token;
 use cainome::parser::{AbiParser, TokenizedAbi};
 use camino::Utf8PathBuf;
 use convert_case::{Case, Casing};
+use dojo_world::lockfile::{Lockfile, LOCKFILE_NAME};
 use dojo_world::manifest::BaseManifest;
+use starknet::core::types::FieldElement;
 pub mod error;
 use error::{BindgenResult, Error};
 
@@ -43,6 +45,10 @@ pub struct DojoContract {
 pub struct DojoWorld {
     /// The world's name from the Scarb manifest.
     pub name: String,
+    /// The world's address from the lockfile, if deployed for the profile.
+    pub address: Option<FieldElement>,
+    /// The block the world was deployed in, from the lockfile.
+    pub deployment_block: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -183,7 +189,13 @@ fn gather_dojo_data(
         }
     }
 
-    let world = DojoWorld { name: root_package_name.to_string() };
+    let lockfile = Lockfile::load_from_path(root_dir.join(LOCKFILE_NAME))?;
+    let locked_world = lockfile.world(profile_name);
+    let world = DojoWorld {
+        name: root_package_name.to_string(),
+        address: locked_world.map(|world| world.world_address),
+        deployment_block: locked_world.and_then(|world| world.deployment_block),
+    };
 
     Ok(DojoData { world, models, contracts })
 }
@@ -264,6 +276,8 @@ mod tests {
         assert_eq!(data.models.len(), 4);
 
         assert_eq!(data.world.name, "dojo_example");
+        // the test project has no lockfile
+        assert_eq!(data.world.address, None);
 
         let pos = data.models.get("Position").unwrap();
         assert_eq!(pos.name, "Position");
@@ -30,6 +30,8 @@ impl TypescriptPlugin {
             "felt252" => "RecsType.BigInt".to_string(),
             "ClassHash" => "RecsType.BigInt".to_string(),
             "ContractAddress" => "RecsType.BigInt".to_string(),
+            "ByteArray" => "RecsType.String".to_string(),
+            "array" | "span" => "RecsType.BigIntArray".to_string(),
 
             _ => type_name.to_string(),
         }
@@ -7,7 +7,7 @@ use convert_case::Casing;
 
 use crate::error::BindgenResult;
 use crate::plugins::BuiltinPlugin;
-use crate::{DojoContract, DojoData, DojoModel};
+use crate::{DojoContract, DojoData, DojoModel, DojoWorld};
 
 pub struct TypeScriptV2Plugin {}
 
@@ -30,6 +30,8 @@ impl TypeScriptV2Plugin {
             "felt252" => "string".to_string(),
             "ClassHash" => "string".to_string(),
             "ContractAddress" => "string".to_string(),
+            "ByteArray" => "string".to_string(),
+            "array" | "span" => "string[]".to_string(),
 
             _ => type_name.to_string(),
         }
@@ -241,6 +243,24 @@ function convertQueryToToriiClause(query: Query): Clause | undefined {{
         )
     }
 
+    // The deployment of the world recorded in the lockfile, so that the clients don't have to be
+    // given its address.
+    fn generate_world_constants(world: &DojoWorld) -> String {
+        let mut out = String::new();
+
+        if let Some(address) = world.address {
+            out += &format!("export const WORLD_ADDRESS = \"{address:#x}\";\n");
+        }
+        if let Some(block) = world.deployment_block {
+            out += &format!("export const WORLD_DEPLOYMENT_BLOCK = {block};\n");
+        }
+        if !out.is_empty() {
+            out += "\n";
+        }
+
+        out
+    }
+
     fn generate_world_class(world_name: &String, contracts: &[&DojoContract]) -> String {
         let mut out = String::new();
 
@@ -561,6 +581,7 @@ export enum {} {{
         code += "\n";
         code += TypeScriptV2Plugin::generate_query_types(models.as_slice()).as_str();
         code += "\n";
+        code += TypeScriptV2Plugin::generate_world_constants(&data.world).as_str();
         code += TypeScriptV2Plugin::generate_world_class(&data.world.name, contracts.as_slice())
             .as_str();
 
@@ -619,4 +640,22 @@ mod tests {
         assert_eq!(actual_output_without_header.len(), 7479);
         assert_eq!(expected_output_without_header.len(), 7479);
     }
+
+    #[test]
+    fn test_world_constants() {
+        let world =
+            DojoWorld { name: "dojo_examples".to_string(), address: None, deployment_block: None };
+        assert_eq!(TypeScriptV2Plugin::generate_world_constants(&world), "");
+
+        let world = DojoWorld {
+            address: Some(starknet::macros::felt!("0x1234")),
+            deployment_block: Some(3),
+            ..world
+        };
+        assert_eq!(
+            TypeScriptV2Plugin::generate_world_constants(&world),
+            "export const WORLD_ADDRESS = \"0x1234\";\nexport const WORLD_DEPLOYMENT_BLOCK = \
+             3;\n\n"
+        );
+    }
 }
@@ -28,6 +28,8 @@ impl UnityPlugin {
             "felt252" => "FieldElement".to_string(),
             "ClassHash" => "FieldElement".to_string(),
             "ContractAddress" => "FieldElement".to_string(),
+            "ByteArray" => "string".to_string(),
+            "array" | "span" => "FieldElement[]".to_string(),
 
             _ => type_name.to_string(),
         }
@@ -6,6 +6,8 @@ enum Ty {
     Tuple: Span<Span<felt252>>,
     // Store the capacity of the array.
     Array: u32,
+    // Store the capacity of the byte array, in bytes.
+    ByteArray: u32,
 }
 
 #[derive(Copy, Drop, Serde)]
@@ -43,6 +45,18 @@ fn serialize_member_type(m: @Ty) -> Span<felt252> {
     serialized.span()
 }
 
+/// Appends the layout of `size` values stored as full felts.
+fn append_felts_layout(ref layout: Array<u8>, size: usize) {
+    let mut i = 0;
+    loop {
+        if i == size {
+            break;
+        }
+        layout.append(251);
+        i += 1;
+    };
+}
+
 trait Introspect<T> {
     fn size() -> usize;
     fn layout(ref layout: Array<u8>);
@@ -12,6 +12,7 @@ use cairo_lang_filesystem::ids::{CrateId, CrateLongId};
 use cairo_lang_formatter::format_string;
 use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_starknet::abi;
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
 use cairo_lang_starknet::contract::{find_contracts, ContractDeclaration};
 use cairo_lang_starknet::contract_class::{compile_prepared_db, ContractClass};
 use cairo_lang_starknet::plugin::aux_data::StarkNetContractAuxData;
@@ -58,10 +59,19 @@ mod test;
 
 pub struct DojoCompiler;
 
+/// The class hash, compiled class hash (if built with proving artifacts) and abi of a compiled
+/// contract.
+type CompiledArtifact = (FieldElement, Option<FieldElement>, Option<abi::Contract>);
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Props {
     pub build_external_contracts: Option<Vec<ContractSelector>>,
+    /// Whether to compile the contracts to CASM and include their compiled class hashes (ie. the
+    /// program hashes committed to by the Starknet OS) in the manifests. The CASM artifacts are
+    /// written next to the Sierra ones.
+    #[serde(default)]
+    pub build_proving_artifacts: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -118,9 +128,7 @@ impl Compiler for DojoCompiler {
             compile_prepared_db(db, &contracts, compiler_config)?
         };
 
-        // (contract name, class hash)
-        let mut compiled_classes: HashMap<SmolStr, (FieldElement, Option<abi::Contract>)> =
-            HashMap::new();
+        let mut compiled_classes: HashMap<SmolStr, CompiledArtifact> = HashMap::new();
 
         for (decl, class) in zip(contracts, classes) {
             let contract_full_path = decl.module_id().full_path(db.upcast_mut());
@@ -153,7 +161,29 @@ impl Compiler for DojoCompiler {
             let class_hash = compute_class_hash_of_contract_class(&class).with_context(|| {
                 format!("problem computing class hash for contract `{contract_full_path}`")
             })?;
-            compiled_classes.insert(contract_full_path.into(), (class_hash, class.abi));
+
+            let compiled_class_hash = if props.build_proving_artifacts {
+                let casm_class = CasmContractClass::from_contract_class(class.clone(), true)
+                    .with_context(|| {
+                        format!("problem compiling contract `{contract_full_path}` to CASM")
+                    })?;
+
+                // save CASM artifact file
+                let file_name = format!("{contract_full_path}.casm.json");
+                let mut file =
+                    target_dir.open_rw(file_name.clone(), "compiled class file", ws.config())?;
+                serde_json::to_writer_pretty(file.deref_mut(), &casm_class).with_context(|| {
+                    format!("failed to serialize compiled contract artifact: {contract_full_path}")
+                })?;
+
+                let hash = casm_class.compiled_class_hash().to_be_bytes();
+                Some(FieldElement::from_bytes_be(&hash)?)
+            } else {
+                None
+            };
+
+            compiled_classes
+                .insert(contract_full_path.into(), (class_hash, compiled_class_hash, class.abi));
         }
 
         update_manifest(db, ws, &main_crate_ids, compiled_classes, props.build_external_contracts)?;
@@ -235,7 +265,7 @@ fn update_manifest(
     db: &RootDatabase,
     ws: &Workspace<'_>,
     crate_ids: &[CrateId],
-    compiled_artifacts: HashMap<SmolStr, (FieldElement, Option<abi::Contract>)>,
+    compiled_artifacts: HashMap<SmolStr, CompiledArtifact>,
     external_contracts: Option<Vec<ContractSelector>>,
 ) -> anyhow::Result<()> {
     let profile_name =
@@ -247,9 +277,9 @@ fn update_manifest(
     let manifest_dir = ws.manifest_path().parent().unwrap().to_path_buf();
 
     fn get_compiled_artifact_from_map<'a>(
-        artifacts: &'a HashMap<SmolStr, (FieldElement, Option<abi::Contract>)>,
+        artifacts: &'a HashMap<SmolStr, CompiledArtifact>,
         artifact_name: &str,
-    ) -> anyhow::Result<&'a (FieldElement, Option<abi::Contract>)> {
+    ) -> anyhow::Result<&'a CompiledArtifact> {
         artifacts.get(artifact_name).context(format!(
             "Contract `{artifact_name}` not found. Did you include `dojo` as a dependency?",
         ))
@@ -257,26 +287,38 @@ fn update_manifest(
 
     let mut crate_ids = crate_ids.to_vec();
 
-    let (hash, abi) = get_compiled_artifact_from_map(&compiled_artifacts, WORLD_CONTRACT_NAME)?;
+    let (hash, compiled_hash, abi) =
+        get_compiled_artifact_from_map(&compiled_artifacts, WORLD_CONTRACT_NAME)?;
     write_manifest_and_abi(
         &relative_manifests_dir,
         &relative_abis_dir,
         &manifest_dir,
         &mut Manifest::new(
             // abi path will be written by `write_manifest`
-            Class { class_hash: *hash, abi: None, original_class_hash: *hash },
+            Class {
+                class_hash: *hash,
+                abi: None,
+                original_class_hash: *hash,
+                compiled_class_hash: *compiled_hash,
+            },
             WORLD_CONTRACT_NAME.into(),
         ),
         abi,
     )?;
 
-    let (hash, _) = get_compiled_artifact_from_map(&compiled_artifacts, BASE_CONTRACT_NAME)?;
+    let (hash, compiled_hash, _) =
+        get_compiled_artifact_from_map(&compiled_artifacts, BASE_CONTRACT_NAME)?;
     write_manifest_and_abi(
         &relative_manifests_dir,
         &relative_abis_dir,
         &manifest_dir,
         &mut Manifest::new(
-            Class { class_hash: *hash, abi: None, original_class_hash: *hash },
+            Class {
+                class_hash: *hash,
+                abi: None,
+                original_class_hash: *hash,
+                compiled_class_hash: *compiled_hash,
+            },
             BASE_CONTRACT_NAME.into(),
         ),
         &None,
@@ -364,7 +406,7 @@ fn get_dojo_model_artifacts(
     db: &RootDatabase,
     aux_data: &DojoAuxData,
     module_id: ModuleId,
-    compiled_classes: &HashMap<SmolStr, (FieldElement, Option<abi::Contract>)>,
+    compiled_classes: &HashMap<SmolStr, CompiledArtifact>,
 ) -> anyhow::Result<HashMap<String, (Manifest<DojoModel>, Option<abi::Contract>)>> {
     let mut models = HashMap::with_capacity(aux_data.models.len());
 
@@ -380,7 +422,7 @@ fn get_dojo_model_artifacts(
 
             let compiled_class = compiled_classes.get(model_full_name.as_str()).cloned();
 
-            if let Some((class_hash, abi)) = compiled_class {
+            if let Some((class_hash, compiled_class_hash, abi)) = compiled_class {
                 models.insert(
                     model_full_name.clone(),
                     (
@@ -388,6 +430,7 @@ fn get_dojo_model_artifacts(
                             DojoModel {
                                 class_hash,
                                 abi: None,
+                                compiled_class_hash,
                                 members: model.members.clone(),
                                 original_class_hash: class_hash,
                             },
@@ -432,7 +475,7 @@ fn get_dojo_contract_artifacts(
     db: &RootDatabase,
     module_id: &ModuleId,
     aux_data: &StarkNetContractAuxData,
-    compiled_classes: &HashMap<SmolStr, (FieldElement, Option<abi::Contract>)>,
+    compiled_classes: &HashMap<SmolStr, CompiledArtifact>,
 ) -> anyhow::Result<HashMap<SmolStr, (Manifest<DojoContract>, Option<abi::Contract>)>> {
     let contract_name = &aux_data.contract_name;
 
@@ -441,7 +484,9 @@ fn get_dojo_contract_artifacts(
     if !matches!(contract_name.as_ref(), "world" | "resource_metadata" | "base") {
         let module_name: SmolStr = module_id.full_path(db).into();
 
-        if let Some((class_hash, abi)) = compiled_classes.get(&module_name as &str) {
+        if let Some((class_hash, compiled_class_hash, abi)) =
+            compiled_classes.get(&module_name as &str)
+        {
             let reads = SYSTEM_READS
                 .lock()
                 .unwrap()
@@ -462,6 +507,7 @@ fn get_dojo_contract_artifacts(
                     reads,
                     class_hash: *class_hash,
                     original_class_hash: *class_hash,
+                    compiled_class_hash: *compiled_class_hash,
                     ..Default::default()
                 },
                 module_name.clone(),
@@ -1,7 +1,13 @@
+use std::fs;
+
+use assert_fs::TempDir;
+use camino::Utf8PathBuf;
 use dojo_test_utils::compiler::build_test_config;
+use dojo_world::manifest::{BaseManifest, WORLD_CONTRACT_NAME};
 use scarb::core::TargetKind;
 use scarb::ops::CompileOpts;
 
+use super::{Props, BASE_DIR, MANIFESTS_DIR};
 use crate::scarb_internal;
 
 // TODO: Remove this ignore after issue mentioned in this PR is resolved:
@@ -19,3 +25,69 @@ fn test_compiler() {
         "compilation failed"
     );
 }
+
+#[test]
+fn test_props_build_proving_artifacts() {
+    let props: Props = toml::from_str("build-proving-artifacts = true").unwrap();
+    assert!(props.build_proving_artifacts);
+
+    let props: Props = toml::from_str("build-external-contracts = []").unwrap();
+    assert!(!props.build_proving_artifacts);
+}
+
+#[test]
+fn test_compiler_with_proving_artifacts() {
+    let project_dir = TempDir::new().unwrap();
+    let project_dir = Utf8PathBuf::from_path_buf(project_dir.to_path_buf()).unwrap();
+    let dojo_core = fs::canonicalize("../dojo-core").unwrap();
+
+    fs::create_dir_all(project_dir.join("src")).unwrap();
+    fs::copy(
+        "./src/manifest_test_data/compiler_cairo_v240/src/lib.cairo",
+        project_dir.join("src/lib.cairo"),
+    )
+    .unwrap();
+    fs::write(
+        project_dir.join("Scarb.toml"),
+        format!(
+            r#"
+[package]
+name = "cairo_v240"
+version = "0.1.0"
+edition = "2023_10"
+cairo-version = "2.4.0"
+
+[cairo]
+sierra-replace-ids = true
+
+[dependencies]
+dojo = {{ path = "{}" }}
+
+[[target.dojo]]
+build-proving-artifacts = true
+"#,
+            dojo_core.display()
+        ),
+    )
+    .unwrap();
+
+    let config = build_test_config(project_dir.join("Scarb.toml").as_str()).unwrap();
+    scarb_internal::compile_workspace(
+        &config,
+        CompileOpts { include_targets: vec![], exclude_targets: vec![TargetKind::TEST] },
+    )
+    .expect("compilation failed");
+
+    // the compiled class hashes are in the manifests, and the CASM artifacts next to the Sierra
+    // ones
+    let manifests_dir = project_dir.join(MANIFESTS_DIR).join("dev").join(BASE_DIR);
+    let manifest = BaseManifest::load_from_path(&manifests_dir).unwrap();
+
+    assert!(manifest.world.inner.compiled_class_hash.is_some());
+    assert!(manifest.base.inner.compiled_class_hash.is_some());
+    assert!(manifest.contracts.iter().all(|c| c.inner.compiled_class_hash.is_some()));
+    assert!(manifest.models.iter().all(|m| m.inner.compiled_class_hash.is_some()));
+
+    let target_dir = config.target_dir().path_unchecked().join("dev");
+    assert!(target_dir.join(format!("{WORLD_CONTRACT_NAME}.casm.json")).exists());
+}
@@ -20,6 +20,12 @@ use num_traits::ToPrimitive;
 
 const ARRAY_CAPACITY_ATTR: &str = "capacity";
 
+/// The number of bytes stored in each word of a `ByteArray`.
+const BYTES_PER_WORD: usize = 31;
+
+/// The types that can only be stored with a `#[capacity]` attribute.
+const CAPACITY_TYPES: [&str; 3] = ["Array<felt252>", "Span<felt252>", "ByteArray"];
+
 #[derive(Clone, Default)]
 struct TypeIntrospection(usize, Vec<usize>);
 
@@ -39,16 +45,25 @@ fn primitive_type_introspection() -> HashMap<String, TypeIntrospection> {
     ])
 }
 
+/// Returns the number of felts a `ByteArray` with the given capacity, in bytes, is serialized to:
+/// the length of the words array, the full words, the pending word and its length.
+pub fn byte_array_size(capacity: usize) -> usize {
+    capacity / BYTES_PER_WORD + 3
+}
+
 /// A handler for Dojo code derives Introspect for a struct
 /// Parameters:
 /// * db: The semantic database.
 /// * struct_ast: The AST of the struct.
+/// * is_model: Whether the struct is a model. `ByteArray` members are only supported in models, as
+///   their values are padded to the capacity of the member when they are stored.
 /// Returns:
 /// * A RewriteNode containing the generated code.
 pub fn handle_introspect_struct(
     db: &dyn SyntaxGroup,
     diagnostics: &mut Vec<PluginDiagnostic>,
     struct_ast: ItemStruct,
+    is_model: bool,
 ) -> RewriteNode {
     let name = struct_ast.name(db).text(db).into();
 
@@ -95,15 +110,39 @@ pub fn handle_introspect_struct(
                     });
                 }
 
-                if &ty != "Array<felt252>" && &ty != "Span<felt252>" {
+                if !CAPACITY_TYPES.contains(&ty.as_str()) {
                     diagnostics.push(PluginDiagnostic {
                         stable_ptr: member.stable_ptr().0,
-                        message: "Capacity is only supported for Array<felt252> or Span<felt252>."
+                        message: "Capacity is only supported for Array<felt252>, Span<felt252> or \
+                                  ByteArray."
                             .to_string(),
                         severity: Severity::Error,
                     });
                 }
 
+                if &ty == "ByteArray" {
+                    if !is_model {
+                        diagnostics.push(PluginDiagnostic {
+                            stable_ptr: member.stable_ptr().0,
+                            message: "ByteArray is only supported as a model member.".to_string(),
+                            severity: Severity::Error,
+                        });
+                    }
+
+                    member_types.push(format!(
+                        "dojo::database::introspect::serialize_member(@\
+                         dojo::database::introspect::Member {{
+                name: '{name}',
+                ty: dojo::database::introspect::Ty::ByteArray({c}),
+                attrs: array![{}].span()
+            }})",
+                        attrs.join(","),
+                    ));
+
+                    ty = format!("byte_array__{c}");
+                    return Member { name, ty, key };
+                }
+
                 member_types.push(format!(
                     "dojo::database::introspect::serialize_member(@\
                      dojo::database::introspect::Member {{
@@ -115,6 +154,12 @@ pub fn handle_introspect_struct(
                 ));
 
                 ty = format!("array_felts__{c}");
+            } else if CAPACITY_TYPES.contains(&ty.as_str()) {
+                diagnostics.push(PluginDiagnostic {
+                    stable_ptr: member.stable_ptr().0,
+                    message: format!("{ty} must have a #[capacity] attribute."),
+                    severity: Severity::Error,
+                });
             } else {
                 // It's a custom struct/enum
                 member_types.push(format!(
@@ -305,6 +350,10 @@ fn handle_introspect_internal(
         .collect::<Vec<_>>()
         .join(", ");
 
+    // the values following a byte array don't have a fixed offset, as byte arrays are serialized to
+    // a variable number of felts. they are thus stored as full felts.
+    let mut after_byte_array = false;
+
     members.iter().for_each(|m| {
         let primitive_intro = primitive_sizes.get(&m.ty);
         let mut attrs = vec![];
@@ -313,12 +362,36 @@ fn handle_introspect_internal(
             // It's a primitive type
             if m.key {
                 attrs.push("'key'");
+            } else if after_byte_array {
+                size_precompute += p_ty.0;
+                for _i in 0..p_ty.0 {
+                    layout.push(RewriteNode::Text("layout.append(251);\n".to_string()))
+                }
             } else {
                 size_precompute += p_ty.0;
                 p_ty.1.iter().for_each(|l| {
                     layout.push(RewriteNode::Text(format!("layout.append({});\n", l)))
                 });
             }
+        } else if m.ty.starts_with("byte_array__") {
+            let capacity =
+                m.ty.strip_prefix("byte_array__")
+                    .unwrap()
+                    .parse::<usize>()
+                    .expect("usize expected for byte array capacity");
+
+            if m.key {
+                attrs.push("'key'");
+            } else {
+                let felts = byte_array_size(capacity);
+                size.push(format!("{felts}"));
+
+                for _i in 0..felts {
+                    layout.push(RewriteNode::Text("layout.append(251);\n".to_string()))
+                }
+
+                after_byte_array = true;
+            }
         } else if m.ty.starts_with("array_felts__") {
             let capacity =
                 m.ty.strip_prefix("array_felts__")
@@ -340,6 +413,13 @@ fn handle_introspect_internal(
             // It's a custom type
             if m.key {
                 attrs.push("'key'");
+            } else if after_byte_array {
+                size.push(format!("dojo::database::introspect::Introspect::<{}>::size()", m.ty,));
+                layout.push(RewriteNode::Text(format!(
+                    "dojo::database::introspect::append_felts_layout(ref layout, \
+                     dojo::database::introspect::Introspect::<{}>::size());\n",
+                    m.ty
+                )));
             } else {
                 size.push(format!("dojo::database::introspect::Introspect::<{}>::size()", m.ty,));
                 layout.push(RewriteNode::Text(format!(
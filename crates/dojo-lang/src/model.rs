@@ -63,6 +63,17 @@ pub fn handle_model_struct(
                 severity: Severity::Error,
             });
         }
+
+        if k.ty == "ByteArray" {
+            diagnostics.push(PluginDiagnostic {
+                message: "Key is only supported for core types that are 1 felt long once \
+                          serialized. `ByteArray` is serialized to multiple felts, hence not \
+                          supported."
+                    .into(),
+                stable_ptr: struct_ast.name(db).stable_ptr().untyped(),
+                severity: Severity::Error,
+            });
+        }
     }
 
     let serialize_member = |m: &Member, include_key: bool| {
@@ -86,9 +97,26 @@ pub fn handle_model_struct(
     let serialized_keys: Vec<_> =
         keys.iter().filter_map(|m| serialize_member(m, true)).collect::<_>();
 
-    let serialized_values: Vec<_> =
+    let mut serialized_values: Vec<_> =
         members.iter().filter_map(|m| serialize_member(m, false)).collect::<_>();
 
+    // byte arrays are serialized to a variable number of felts, so the values are padded up to
+    // the size of the model, which accounts for the capacity of each byte array.
+    if members.iter().any(|m| !m.key && m.ty == "ByteArray") {
+        serialized_values.push(RewriteNode::Text(format!(
+            "let size = dojo::database::introspect::Introspect::<{name}>::size();
+                    let len = core::array::ArrayTrait::len(@serialized);
+                    assert(len <= size, 'value exceeds capacity');
+                    loop {{
+                        if core::array::ArrayTrait::len(@serialized) == size {{
+                            break;
+                        }}
+                        core::array::ArrayTrait::append(ref serialized, 0);
+                    }};",
+            name = struct_ast.name(db).text(db)
+        )));
+    }
+
     let name = struct_ast.name(db).text(db);
     aux_data.models.push(Model { name: name.to_string(), members: members.to_vec() });
 
@@ -187,7 +215,7 @@ pub fn handle_model_struct(
                 ),
                 (
                     "schema_introspection".to_string(),
-                    handle_introspect_struct(db, &mut diagnostics, struct_ast),
+                    handle_introspect_struct(db, &mut diagnostics, struct_ast, true),
                 ),
                 ("serialized_keys".to_string(), RewriteNode::new_modified(serialized_keys)),
                 ("serialized_values".to_string(), RewriteNode::new_modified(serialized_values)),
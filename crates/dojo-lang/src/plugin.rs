@@ -406,6 +406,7 @@ impl MacroPlugin for BuiltinDojoPlugin {
                                     db,
                                     &mut diagnostics,
                                     struct_ast.clone(),
+                                    false,
                                 ));
                             }
                             _ => continue,
@@ -469,9 +470,7 @@ impl MacroPlugin for BuiltinDojoPlugin {
             "dojo::event".to_string(),
             "key".to_string(),
             "computed".to_string(),
-            // Not adding capacity for now, this will automatically
-            // makes Scarb emitting a diagnostic saying this attribute is not supported.
-            // "capacity".to_string(),
+            "capacity".to_string(),
         ]
     }
 }
@@ -2,17 +2,12 @@ use std::sync::Arc;
 
 use jsonrpsee::core::Error;
 pub use katana_core::backend::config::{Environment, StarknetConfig};
-use katana_core::constants::MAX_RECURSION_DEPTH;
-use katana_core::env::get_default_vm_resource_fee_cost;
 use katana_core::sequencer::KatanaSequencer;
 pub use katana_core::sequencer::SequencerConfig;
 use katana_executor::implementation::blockifier::BlockifierFactory;
-use katana_executor::SimulationFlag;
+use katana_node::{Builder, Node};
 use katana_primitives::chain::ChainId;
-use katana_primitives::env::{CfgEnv, FeeTokenAddressses};
-use katana_rpc::config::ServerConfig;
-use katana_rpc::{spawn, NodeHandle};
-use katana_rpc_api::ApiKind;
+use katana_rpc::NodeHandle;
 use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
 use starknet::core::chain_id;
 use starknet::core::types::FieldElement;
@@ -36,51 +31,15 @@ pub struct TestSequencer {
 
 impl TestSequencer {
     pub async fn start(config: SequencerConfig, starknet_config: StarknetConfig) -> Self {
-        let cfg_env = CfgEnv {
-            chain_id: starknet_config.env.chain_id,
-            vm_resource_fee_cost: get_default_vm_resource_fee_cost(),
-            invoke_tx_max_n_steps: starknet_config.env.invoke_max_steps,
-            validate_max_n_steps: starknet_config.env.validate_max_steps,
-            max_recursion_depth: MAX_RECURSION_DEPTH,
-            fee_token_addresses: FeeTokenAddressses {
-                eth: starknet_config.genesis.fee_token.address,
-                strk: Default::default(),
-            },
-        };
-
-        let simulation_flags = SimulationFlag {
-            skip_validate: starknet_config.disable_validate,
-            skip_fee_transfer: starknet_config.disable_fee,
-            ..Default::default()
-        };
-
-        let executor_factory = BlockifierFactory::new(cfg_env, simulation_flags);
-
-        let sequencer = Arc::new(
-            KatanaSequencer::new(executor_factory, config, starknet_config)
-                .await
-                .expect("Failed to create sequencer"),
-        );
-
-        let handle = spawn(
-            Arc::clone(&sequencer),
-            ServerConfig {
-                port: 0,
-                host: "127.0.0.1".into(),
-                max_connections: 100,
-                apis: vec![
-                    ApiKind::Starknet,
-                    ApiKind::Katana,
-                    ApiKind::Dev,
-                    ApiKind::Saya,
-                    ApiKind::Torii,
-                ],
-            },
-        )
-        .await
-        .expect("Unable to spawn server");
-
-        let url = Url::parse(&format!("http://{}", handle.addr)).expect("Failed to parse URL");
+        let node = Builder::new()
+            .starknet_config(starknet_config)
+            .sequencer_config(config)
+            .launch()
+            .await
+            .expect("Failed to launch node");
+
+        let url = node.url();
+        let Node { sequencer, rpc: handle } = node;
 
         let account = sequencer.backend.config.genesis.accounts().next().unwrap();
         let account = TestAccount {
@@ -91,6 +91,9 @@ pub fn parse_ty(data: &[FieldElement]) -> Result<Ty, ParseError> {
         1 => parse_struct(&data[1..]),
         2 => parse_enum(&data[1..]),
         3 => parse_tuple(&data[1..]),
+        // arrays and byte arrays are stored with their capacity, which isn't needed to read them
+        4 => Ok(Ty::Array(vec![])),
+        5 => Ok(Ty::ByteArray(String::new())),
         _ => Err(ParseError::InvalidSchema),
     }
 }
@@ -202,10 +205,17 @@ mod tests {
     use starknet::core::types::FieldElement;
 
     use crate::packing::ParseError;
+    use crate::schema::Ty;
 
     #[test]
     fn parse_simple_with_invalid_value() {
         let data = [FieldElement::default()];
         assert!(matches!(super::parse_simple(&data), Err(ParseError::InvalidSchema)));
     }
+
+    #[test]
+    fn parse_byte_array() {
+        let data = [FieldElement::from(5_u8), FieldElement::from(64_u8)];
+        assert_eq!(super::parse_ty(&data).unwrap(), Ty::ByteArray(String::new()));
+    }
 }
@@ -47,6 +47,8 @@ pub enum PrimitiveError {
     UnsupportedType,
     #[error("Set value type mismatch")]
     TypeMismatch,
+    #[error("Invalid byte array")]
+    InvalidByteArray,
     #[error(transparent)]
     ValueOutOfRange(#[from] ValueOutOfRangeError),
 }
@@ -5,6 +5,9 @@ use strum_macros::AsRefStr;
 
 use crate::primitive::{Primitive, PrimitiveError};
 
+/// The number of bytes stored in each word of a Cairo `ByteArray`.
+const BYTES_PER_WORD: usize = 31;
+
 /// Represents a model member.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Hash, Eq)]
 pub struct Member {
@@ -40,6 +43,8 @@ pub enum Ty {
     Struct(Struct),
     Enum(Enum),
     Tuple(Vec<Ty>),
+    Array(Vec<FieldElement>),
+    ByteArray(String),
 }
 
 impl Ty {
@@ -49,6 +54,8 @@ impl Ty {
             Ty::Struct(s) => s.name.clone(),
             Ty::Enum(e) => e.name.clone(),
             Ty::Tuple(tys) => format!("({})", tys.iter().map(|ty| ty.name()).join(", ")),
+            Ty::Array(_) => "Array<felt252>".to_string(),
+            Ty::ByteArray(_) => "ByteArray".to_string(),
         }
     }
 
@@ -118,6 +125,21 @@ impl Ty {
                         serialize_inner(ty, felts)?;
                     }
                 }
+                Ty::Array(items) => {
+                    felts.push(FieldElement::from(items.len()));
+                    felts.extend(items);
+                }
+                Ty::ByteArray(string) => {
+                    let chunks = string.as_bytes().chunks_exact(BYTES_PER_WORD);
+                    let pending = chunks.remainder();
+
+                    felts.push(FieldElement::from(chunks.len()));
+                    for word in chunks {
+                        felts.push(FieldElement::from_byte_slice_be(word).expect("fits in a felt"));
+                    }
+                    felts.push(FieldElement::from_byte_slice_be(pending).expect("fits in a felt"));
+                    felts.push(FieldElement::from(pending.len()));
+                }
             }
             Ok(())
         }
@@ -149,11 +171,46 @@ impl Ty {
                     ty.deserialize(felts)?;
                 }
             }
+            Ty::Array(items) => {
+                let len: u32 = pop_felt(felts)?.try_into()?;
+                let len = len as usize;
+                if felts.len() < len {
+                    return Err(PrimitiveError::NotEnoughFieldElements);
+                }
+                *items = felts.drain(..len).collect();
+            }
+            Ty::ByteArray(string) => {
+                let words_len: u32 = pop_felt(felts)?.try_into()?;
+                let words_len = words_len as usize;
+
+                let mut bytes = Vec::with_capacity((words_len + 1) * BYTES_PER_WORD);
+                for _ in 0..words_len {
+                    bytes.extend(&pop_felt(felts)?.to_bytes_be()[32 - BYTES_PER_WORD..]);
+                }
+
+                let pending_word = pop_felt(felts)?;
+                let pending_len: u32 = pop_felt(felts)?.try_into()?;
+                let pending_len = pending_len as usize;
+                if pending_len >= BYTES_PER_WORD {
+                    return Err(PrimitiveError::InvalidByteArray);
+                }
+                bytes.extend(&pending_word.to_bytes_be()[32 - pending_len..]);
+
+                *string = String::from_utf8(bytes).map_err(|_| PrimitiveError::InvalidByteArray)?;
+            }
         }
         Ok(())
     }
 }
 
+/// Removes the first felt of `felts`.
+fn pop_felt(felts: &mut Vec<FieldElement>) -> Result<FieldElement, PrimitiveError> {
+    if felts.is_empty() {
+        return Err(PrimitiveError::MissingFieldElement);
+    }
+    Ok(felts.remove(0))
+}
+
 pub struct TyIter<'a> {
     stack: Vec<&'a Ty>,
 }
@@ -209,6 +266,7 @@ impl std::fmt::Display for Ty {
                         Some(ty.name())
                     }
                 }
+                Ty::Array(_) | Ty::ByteArray(_) => None,
             })
             .collect::<Vec<_>>()
             .join("\n\n");
@@ -359,3 +417,30 @@ fn format_member(m: &Member) -> String {
 
     str
 }
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::FieldElement;
+
+    use super::Ty;
+
+    #[test]
+    fn byte_array_serde() {
+        let string = "a string that is longer than a single word of 31 bytes".to_string();
+        let ty = Ty::ByteArray(string.clone());
+
+        let mut felts = ty.serialize().unwrap();
+        // 1 full word, the pending word and its length
+        assert_eq!(felts.len(), 4);
+        assert_eq!(felts[0], FieldElement::ONE);
+        assert_eq!(felts[3], FieldElement::from(string.len() - 31));
+
+        // trailing values, eg. the padding of the model, are left untouched
+        felts.push(FieldElement::ZERO);
+
+        let mut deserialized = Ty::ByteArray(String::new());
+        deserialized.deserialize(&mut felts).unwrap();
+        assert_eq!(deserialized, ty);
+        assert_eq!(felts, vec![FieldElement::ZERO]);
+    }
+}
@@ -0,0 +1,310 @@
+//! Typed decoding of the events emitted by the World contract.
+//!
+//! The World events have a single key, the selector of the event name, and their members
+//! serialized as data. [DecodedEvent] parses any of them into Rust types, which can be serialized
+//! to be forwarded or stored by indexers. When the kind of the event is already known, eg. when
+//! filtering on its selector, its data can be decoded directly, eg. with [StoreSetRecord::decode].
+//!
+//! The data following the members of an event is ignored.
+
+use serde::{Deserialize, Serialize};
+use starknet::core::types::{EmittedEvent, Event, FieldElement};
+use starknet::core::utils::{parse_cairo_short_string, ParseCairoShortStringError};
+use starknet::macros::selector;
+
+#[cfg(test)]
+#[path = "events_test.rs"]
+pub(crate) mod test;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EventError {
+    #[error("The event has no selector key.")]
+    MissingSelector,
+    #[error("Unknown World event selector {0:#x}.")]
+    UnknownSelector(FieldElement),
+    #[error("Missing data for event {event}, expected at least {expected} felts but got {got}.")]
+    MissingData { event: &'static str, expected: usize, got: usize },
+    #[error("Invalid value {value:#x} for the {member} member of event {event}.")]
+    InvalidValue { event: &'static str, member: &'static str, value: FieldElement },
+    #[error(transparent)]
+    ParseCairoShortString(#[from] ParseCairoShortStringError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldSpawned {
+    pub address: FieldElement,
+    pub creator: FieldElement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldUpgraded {
+    pub class_hash: FieldElement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractDeployed {
+    pub salt: FieldElement,
+    pub class_hash: FieldElement,
+    pub address: FieldElement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractUpgraded {
+    pub class_hash: FieldElement,
+    pub address: FieldElement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataUpdate {
+    /// The model name or contract address the metadata is set for, `0` for the World itself.
+    pub resource: FieldElement,
+    /// The metadata uri, split in cairo short strings.
+    pub uri: Vec<FieldElement>,
+}
+
+impl MetadataUpdate {
+    /// Returns the metadata uri as a string.
+    pub fn uri(&self) -> Result<String, ParseCairoShortStringError> {
+        Ok(self.uri.iter().map(parse_cairo_short_string).collect::<Result<Vec<_>, _>>()?.concat())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRegistered {
+    pub name: String,
+    pub class_hash: FieldElement,
+    pub prev_class_hash: FieldElement,
+    pub address: FieldElement,
+    pub prev_address: FieldElement,
+}
+
+impl ModelRegistered {
+    /// Returns `true` if the model was already registered, and has been upgraded.
+    pub fn is_upgrade(&self) -> bool {
+        self.prev_class_hash != FieldElement::ZERO
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreSetRecord {
+    /// The name of the model.
+    pub table: String,
+    pub keys: Vec<FieldElement>,
+    pub values: Vec<FieldElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreDelRecord {
+    /// The name of the model.
+    pub table: String,
+    pub keys: Vec<FieldElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriterUpdated {
+    pub model: FieldElement,
+    pub system: FieldElement,
+    pub value: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerUpdated {
+    pub address: FieldElement,
+    pub resource: FieldElement,
+    pub value: bool,
+}
+
+/// An event emitted by the World contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DecodedEvent {
+    WorldSpawned(WorldSpawned),
+    ContractDeployed(ContractDeployed),
+    ContractUpgraded(ContractUpgraded),
+    WorldUpgraded(WorldUpgraded),
+    MetadataUpdate(MetadataUpdate),
+    ModelRegistered(ModelRegistered),
+    StoreSetRecord(StoreSetRecord),
+    StoreDelRecord(StoreDelRecord),
+    WriterUpdated(WriterUpdated),
+    OwnerUpdated(OwnerUpdated),
+}
+
+impl DecodedEvent {
+    /// Decodes a World event from its raw `keys` and `data`.
+    pub fn decode(keys: &[FieldElement], data: &[FieldElement]) -> Result<Self, EventError> {
+        let selector = *keys.first().ok_or(EventError::MissingSelector)?;
+
+        if selector == selector!("WorldSpawned") {
+            WorldSpawned::decode(data).map(Self::WorldSpawned)
+        } else if selector == selector!("ContractDeployed") {
+            ContractDeployed::decode(data).map(Self::ContractDeployed)
+        } else if selector == selector!("ContractUpgraded") {
+            ContractUpgraded::decode(data).map(Self::ContractUpgraded)
+        } else if selector == selector!("WorldUpgraded") {
+            WorldUpgraded::decode(data).map(Self::WorldUpgraded)
+        } else if selector == selector!("MetadataUpdate") {
+            MetadataUpdate::decode(data).map(Self::MetadataUpdate)
+        } else if selector == selector!("ModelRegistered") {
+            ModelRegistered::decode(data).map(Self::ModelRegistered)
+        } else if selector == selector!("StoreSetRecord") {
+            StoreSetRecord::decode(data).map(Self::StoreSetRecord)
+        } else if selector == selector!("StoreDelRecord") {
+            StoreDelRecord::decode(data).map(Self::StoreDelRecord)
+        } else if selector == selector!("WriterUpdated") {
+            WriterUpdated::decode(data).map(Self::WriterUpdated)
+        } else if selector == selector!("OwnerUpdated") {
+            OwnerUpdated::decode(data).map(Self::OwnerUpdated)
+        } else {
+            Err(EventError::UnknownSelector(selector))
+        }
+    }
+}
+
+impl WorldSpawned {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("WorldSpawned", data);
+        Ok(Self { address: data.felt()?, creator: data.felt()? })
+    }
+}
+
+impl WorldUpgraded {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("WorldUpgraded", data);
+        Ok(Self { class_hash: data.felt()? })
+    }
+}
+
+impl ContractDeployed {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("ContractDeployed", data);
+        Ok(Self { salt: data.felt()?, class_hash: data.felt()?, address: data.felt()? })
+    }
+}
+
+impl ContractUpgraded {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("ContractUpgraded", data);
+        Ok(Self { class_hash: data.felt()?, address: data.felt()? })
+    }
+}
+
+impl MetadataUpdate {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("MetadataUpdate", data);
+        Ok(Self { resource: data.felt()?, uri: data.span("uri")? })
+    }
+}
+
+impl ModelRegistered {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("ModelRegistered", data);
+        Ok(Self {
+            name: data.short_string()?,
+            class_hash: data.felt()?,
+            prev_class_hash: data.felt()?,
+            address: data.felt()?,
+            prev_address: data.felt()?,
+        })
+    }
+}
+
+impl StoreSetRecord {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("StoreSetRecord", data);
+        Ok(Self {
+            table: data.short_string()?,
+            keys: data.span("keys")?,
+            values: data.span("values")?,
+        })
+    }
+}
+
+impl StoreDelRecord {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("StoreDelRecord", data);
+        Ok(Self { table: data.short_string()?, keys: data.span("keys")? })
+    }
+}
+
+impl WriterUpdated {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("WriterUpdated", data);
+        Ok(Self { model: data.felt()?, system: data.felt()?, value: data.bool("value")? })
+    }
+}
+
+impl OwnerUpdated {
+    pub fn decode(data: &[FieldElement]) -> Result<Self, EventError> {
+        let mut data = EventData::new("OwnerUpdated", data);
+        Ok(Self { address: data.felt()?, resource: data.felt()?, value: data.bool("value")? })
+    }
+}
+
+impl TryFrom<&Event> for DecodedEvent {
+    type Error = EventError;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        Self::decode(&event.keys, &event.data)
+    }
+}
+
+impl TryFrom<&EmittedEvent> for DecodedEvent {
+    type Error = EventError;
+
+    fn try_from(event: &EmittedEvent) -> Result<Self, Self::Error> {
+        Self::decode(&event.keys, &event.data)
+    }
+}
+
+/// Reads the members of an event from its data, in order.
+struct EventData<'a> {
+    event: &'static str,
+    data: &'a [FieldElement],
+    offset: usize,
+}
+
+impl<'a> EventData<'a> {
+    fn new(event: &'static str, data: &'a [FieldElement]) -> Self {
+        Self { event, data, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [FieldElement], EventError> {
+        let end = self.offset + len;
+        let felts = self.data.get(self.offset..end).ok_or(EventError::MissingData {
+            event: self.event,
+            expected: end,
+            got: self.data.len(),
+        })?;
+
+        self.offset = end;
+        Ok(felts)
+    }
+
+    fn felt(&mut self) -> Result<FieldElement, EventError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn short_string(&mut self) -> Result<String, EventError> {
+        Ok(parse_cairo_short_string(&self.felt()?)?)
+    }
+
+    fn bool(&mut self, member: &'static str) -> Result<bool, EventError> {
+        match self.felt()? {
+            value if value == FieldElement::ZERO => Ok(false),
+            value if value == FieldElement::ONE => Ok(true),
+            value => Err(EventError::InvalidValue { event: self.event, member, value }),
+        }
+    }
+
+    /// Reads a `Span<felt252>`, serialized as its length followed by its items.
+    fn span(&mut self, member: &'static str) -> Result<Vec<FieldElement>, EventError> {
+        let value = self.felt()?;
+        let len = u32::try_from(value).map_err(|_| EventError::InvalidValue {
+            event: self.event,
+            member,
+            value,
+        })?;
+        Ok(self.take(len as usize)?.to_vec())
+    }
+}
@@ -0,0 +1,105 @@
+use starknet::core::types::{EmittedEvent, FieldElement};
+use starknet::macros::{felt, selector, short_string};
+
+use super::{
+    DecodedEvent, EventError, MetadataUpdate, ModelRegistered, StoreSetRecord, WriterUpdated,
+};
+
+#[test]
+fn decode_store_set_record() {
+    let data = vec![
+        short_string!("Position"),
+        felt!("1"),
+        felt!("0x1234"),
+        felt!("2"),
+        felt!("10"),
+        felt!("20"),
+    ];
+
+    let event = DecodedEvent::decode(&[selector!("StoreSetRecord")], &data).unwrap();
+    assert_eq!(
+        event,
+        DecodedEvent::StoreSetRecord(StoreSetRecord {
+            table: "Position".to_string(),
+            keys: vec![felt!("0x1234")],
+            values: vec![felt!("10"), felt!("20")],
+        })
+    );
+}
+
+#[test]
+fn decode_model_registered_upgrade() {
+    let event = EmittedEvent {
+        from_address: felt!("0x1"),
+        keys: vec![selector!("ModelRegistered")],
+        data: vec![short_string!("Moves"), felt!("0x2"), felt!("0x3"), felt!("0x4"), felt!("0x5")],
+        block_hash: None,
+        block_number: None,
+        transaction_hash: FieldElement::ZERO,
+    };
+
+    let DecodedEvent::ModelRegistered(model) = DecodedEvent::try_from(&event).unwrap() else {
+        panic!("expected a ModelRegistered event");
+    };
+
+    assert!(model.is_upgrade());
+    assert_eq!(
+        model,
+        ModelRegistered {
+            name: "Moves".to_string(),
+            class_hash: felt!("0x2"),
+            prev_class_hash: felt!("0x3"),
+            address: felt!("0x4"),
+            prev_address: felt!("0x5"),
+        }
+    );
+}
+
+#[test]
+fn decode_metadata_update_uri() {
+    let data = vec![FieldElement::ZERO, felt!("2"), short_string!("ipfs://"), short_string!("Qm")];
+
+    let event = DecodedEvent::decode(&[selector!("MetadataUpdate")], &data).unwrap();
+    let DecodedEvent::MetadataUpdate(metadata) = event else {
+        panic!("expected a MetadataUpdate event");
+    };
+
+    assert_eq!(metadata.uri().unwrap(), "ipfs://Qm");
+    assert_eq!(MetadataUpdate { uri: vec![], ..metadata }.uri().unwrap(), "");
+}
+
+#[test]
+fn decode_invalid_events() {
+    assert!(matches!(DecodedEvent::decode(&[], &[]), Err(EventError::MissingSelector)));
+    assert!(matches!(
+        DecodedEvent::decode(&[selector!("Transfer")], &[]),
+        Err(EventError::UnknownSelector(_))
+    ));
+
+    // the keys span is longer than the data
+    let data = vec![short_string!("Position"), felt!("3"), felt!("0x1234")];
+    assert!(matches!(
+        DecodedEvent::decode(&[selector!("StoreDelRecord")], &data),
+        Err(EventError::MissingData { event: "StoreDelRecord", expected: 5, got: 3 })
+    ));
+
+    let data = vec![felt!("0x1"), felt!("0x2"), felt!("0x2")];
+    assert!(matches!(
+        DecodedEvent::decode(&[selector!("WriterUpdated")], &data),
+        Err(EventError::InvalidValue { member: "value", .. })
+    ));
+}
+
+#[test]
+fn serialize_decoded_event() {
+    let event = DecodedEvent::WriterUpdated(WriterUpdated {
+        model: short_string!("Position"),
+        system: felt!("0x1234"),
+        value: true,
+    });
+
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["type"], "WriterUpdated");
+    assert_eq!(json["value"], true);
+    assert_eq!(serde_json::from_value::<DecodedEvent>(json).unwrap(), event);
+}
@@ -1,5 +1,6 @@
 pub mod abi;
 pub mod cairo_utils;
+pub mod events;
 pub mod model;
 pub mod world;
 
@@ -1,6 +1,8 @@
 #[cfg(feature = "contracts")]
 pub mod contracts;
 #[cfg(feature = "manifest")]
+pub mod lockfile;
+#[cfg(feature = "manifest")]
 pub mod manifest;
 #[cfg(feature = "metadata")]
 pub mod metadata;
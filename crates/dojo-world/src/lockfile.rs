@@ -0,0 +1,135 @@
+//! The lockfile of a Dojo project, which records where its World is deployed for every profile.
+//!
+//! The `dojo.lock` file sits next to the `Scarb.toml` of the project and is updated by
+//! `sozo migrate` after every migration, eg:
+//!
+//! ```toml
+//! [profiles.dev]
+//! world_address = "0x1234"
+//! deployment_block = 3
+//! rpc_url = "http://localhost:5050/"
+//!
+//! [profiles.dev.classes]
+//! "dojo::world::world" = "0x5678"
+//! "dojo_examples::actions::actions" = "0x9abc"
+//! ```
+//!
+//! Torii and the bindings generator read the World address and its deployment block from it, so
+//! that they don't have to be copied around in the configurations of every environment.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet_crypto::FieldElement;
+
+use crate::manifest::AbstractManifestError;
+
+pub const LOCKFILE_NAME: &str = "dojo.lock";
+
+const LOCKFILE_HEADER: &str =
+    "# This file is maintained by sozo, it is not meant to be edited.\n\n";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// The deployed Worlds, by profile name.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, LockedWorld>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LockedWorld {
+    #[serde_as(as = "UfeHex")]
+    pub world_address: FieldElement,
+    /// The block the World was deployed in, from which its events are indexed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment_block: Option<u64>,
+    /// The RPC endpoint the World was migrated with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+    /// The hashes of the classes declared by the migration, by contract or model name.
+    #[serde_as(as = "BTreeMap<_, UfeHex>")]
+    #[serde(default)]
+    pub classes: BTreeMap<String, FieldElement>,
+}
+
+impl Lockfile {
+    /// Loads the lockfile at the given path, an empty lockfile if it doesn't exist.
+    pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Self, AbstractManifestError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let content = toml::to_string_pretty(self)?;
+        fs::write(path, format!("{LOCKFILE_HEADER}{content}"))?;
+
+        Ok(())
+    }
+
+    /// Returns the World deployed for the given profile.
+    pub fn world(&self, profile: &str) -> Option<&LockedWorld> {
+        self.profiles.get(profile)
+    }
+
+    /// Records the World deployed for the given profile, replacing the previous one.
+    pub fn set_world(&mut self, profile: &str, world: LockedWorld) {
+        self.profiles.insert(profile.to_string(), world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    #[test]
+    fn lockfile_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCKFILE_NAME);
+
+        let mut lockfile = Lockfile::load_from_path(&path).unwrap();
+        assert_eq!(lockfile, Lockfile::default());
+
+        let dev = LockedWorld {
+            world_address: felt!("0x1234"),
+            deployment_block: Some(3),
+            rpc_url: Some("http://localhost:5050/".to_string()),
+            classes: BTreeMap::from([("dojo::world::world".to_string(), felt!("0x5678"))]),
+        };
+        let prod = LockedWorld {
+            world_address: felt!("0x4321"),
+            deployment_block: None,
+            rpc_url: None,
+            classes: BTreeMap::new(),
+        };
+
+        lockfile.set_world("dev", dev.clone());
+        lockfile.set_world("prod", prod.clone());
+        lockfile.write_to_path(&path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("world_address = \"0x1234\""));
+        assert!(content.contains("\"dojo::world::world\" = \"0x5678\""));
+
+        let mut lockfile = Lockfile::load_from_path(&path).unwrap();
+        assert_eq!(lockfile.world("dev"), Some(&dev));
+        assert_eq!(lockfile.world("prod"), Some(&prod));
+        assert_eq!(lockfile.world("staging"), None);
+
+        // a new migration of a profile leaves the other profiles untouched
+        lockfile.set_world("dev", LockedWorld { world_address: felt!("0x1"), ..dev });
+        assert_eq!(lockfile.world("dev").unwrap().world_address, felt!("0x1"));
+        assert_eq!(lockfile.world("prod"), Some(&prod));
+    }
+}
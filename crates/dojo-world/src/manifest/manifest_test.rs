@@ -460,3 +460,15 @@ fn test_abi_format_load_abi_string() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn compiled_class_hash_is_only_serialized_if_known() {
+    let model = DojoModel { class_hash: felt!("0x1"), ..Default::default() };
+    let toml = toml::to_string(&Manifest::new(model.clone(), "model".into())).unwrap();
+    assert!(!toml.contains("compiled_class_hash"));
+
+    let model = DojoModel { compiled_class_hash: Some(felt!("0x2")), ..model };
+    let toml = toml::to_string(&Manifest::new(model.clone(), "model".into())).unwrap();
+    let parsed: Manifest<DojoModel> = toml::from_str(&toml).unwrap();
+    assert_eq!(parsed.inner, model);
+}
@@ -72,6 +72,7 @@ impl From<Manifest<Class>> for Manifest<WorldContract> {
                 class_hash: value.inner.class_hash,
                 abi: value.inner.abi,
                 original_class_hash: value.inner.original_class_hash,
+                compiled_class_hash: value.inner.compiled_class_hash,
                 ..Default::default()
             },
             value.name,
@@ -266,6 +267,7 @@ impl DeploymentManifest {
                     class_hash: base_class_hash,
                     abi: None,
                     original_class_hash: base_class_hash,
+                    compiled_class_hash: None,
                 },
                 BASE_CONTRACT_NAME.into(),
             ),
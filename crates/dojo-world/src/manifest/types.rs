@@ -90,6 +90,11 @@ pub struct DojoContract {
     pub class_hash: FieldElement,
     #[serde_as(as = "UfeHex")]
     pub original_class_hash: FieldElement,
+    /// The hash of the compiled (CASM) class. Only available if the project was built with
+    /// proving artifacts.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiled_class_hash: Option<FieldElement>,
     // base class hash used to deploy the contract
     #[serde_as(as = "UfeHex")]
     pub base_class_hash: FieldElement,
@@ -110,6 +115,11 @@ pub struct DojoModel {
     pub class_hash: FieldElement,
     #[serde_as(as = "UfeHex")]
     pub original_class_hash: FieldElement,
+    /// The hash of the compiled (CASM) class. Only available if the project was built with
+    /// proving artifacts.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiled_class_hash: Option<FieldElement>,
     pub abi: Option<AbiFormat>,
 }
 
@@ -122,6 +132,11 @@ pub struct WorldContract {
     pub class_hash: FieldElement,
     #[serde_as(as = "UfeHex")]
     pub original_class_hash: FieldElement,
+    /// The hash of the compiled (CASM) class. Only available if the project was built with
+    /// proving artifacts.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiled_class_hash: Option<FieldElement>,
     pub abi: Option<AbiFormat>,
     #[serde_as(as = "Option<UfeHex>")]
     pub address: Option<FieldElement>,
@@ -141,6 +156,11 @@ pub struct Class {
     pub class_hash: FieldElement,
     #[serde_as(as = "UfeHex")]
     pub original_class_hash: FieldElement,
+    /// The hash of the compiled (CASM) class. Only available if the project was built with
+    /// proving artifacts.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiled_class_hash: Option<FieldElement>,
     pub abi: Option<AbiFormat>,
 }
 
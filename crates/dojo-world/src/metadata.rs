@@ -234,6 +234,7 @@ pub struct Environment {
     pub keystore_path: Option<String>,
     pub keystore_password: Option<String>,
     pub world_address: Option<String>,
+    pub fee_token_address: Option<String>,
 }
 
 impl Environment {
@@ -260,6 +261,10 @@ impl Environment {
     pub fn keystore_password(&self) -> Option<&str> {
         self.keystore_password.as_deref()
     }
+
+    pub fn fee_token_address(&self) -> Option<&str> {
+        self.fee_token_address.as_deref()
+    }
 }
 
 impl ProjectWorldMetadata {
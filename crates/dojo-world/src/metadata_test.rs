@@ -21,6 +21,7 @@ private_key = "0x1800000000300000180000000000030000000000003006001800006600"
 keystore_path = "test/"
 keystore_password = "dojo"
 world_address = "0x0248cacaeac64c45be0c19ee8727e0bb86623ca7fa3f0d431a6c55e200697e5a"
+fee_token_address = "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"
 
 [world]
 name = "example"
@@ -51,6 +52,10 @@ socials.x = "https://x.com/dojostarknet"
         env.world_address(),
         Some("0x0248cacaeac64c45be0c19ee8727e0bb86623ca7fa3f0d431a6c55e200697e5a")
     );
+    assert_eq!(
+        env.fee_token_address(),
+        Some("0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7")
+    );
 
     assert!(metadata.world.is_some());
     let world = metadata.world.unwrap();
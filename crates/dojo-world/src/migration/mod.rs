@@ -9,7 +9,7 @@ use cairo_lang_starknet::contract_class::ContractClass;
 use starknet::accounts::{Account, AccountError, Call, ConnectedAccount, SingleOwnerAccount};
 use starknet::core::types::contract::{CompiledClass, SierraClass};
 use starknet::core::types::{
-    BlockId, BlockTag, DeclareTransactionResult, FieldElement, FlattenedSierraClass,
+    BlockId, BlockTag, DeclareTransactionResult, FeeEstimate, FieldElement, FlattenedSierraClass,
     InvokeTransactionResult, MaybePendingTransactionReceipt, StarknetError, TransactionReceipt,
 };
 use starknet::core::utils::{get_contract_address, CairoShortStringToFeltError};
@@ -27,6 +27,10 @@ pub mod world;
 
 pub type DeclareOutput = DeclareTransactionResult;
 
+/// The address of the Universal Deployer Contract the World is deployed with.
+pub const UDC_ADDRESS: FieldElement =
+    felt!("0x41a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf");
+
 #[derive(Clone, Debug)]
 pub struct DeployOutput {
     pub transaction_hash: FieldElement,
@@ -47,6 +51,15 @@ pub struct UpgradeOutput {
     pub declare: Option<DeclareOutput>,
 }
 
+/// The call migrating a Dojo contract through the World.
+#[derive(Clone, Debug)]
+pub struct DojoContractCall {
+    pub call: Call,
+    pub contract_address: FieldElement,
+    /// Whether the call upgrades an already deployed contract.
+    pub was_upgraded: bool,
+}
+
 #[derive(Debug)]
 pub struct RegisterOutput {
     pub transaction_hash: FieldElement,
@@ -96,8 +109,17 @@ pub struct TxnConfig {
     /// The multiplier for how much the actual transaction max fee should be relative to the
     /// estimated fee. If `None` is provided, the multiplier is set to `1.1`.
     pub fee_estimate_multiplier: Option<f64>,
+    /// The maximum fee a transaction can be sent with. A transaction whose estimated fee is
+    /// higher isn't sent.
+    pub max_fee_cap: Option<FieldElement>,
+    /// The number of times a transaction rejected for an insufficient max fee is sent again, with
+    /// a higher max fee each time.
+    pub max_fee_retries: u32,
     pub wait: bool,
     pub receipt: bool,
+    /// The maximum number of calls sent in a single multicall transaction. If `None`, the calls
+    /// are only split to fit in the calldata limit of a transaction.
+    pub max_calls: Option<usize>,
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -138,6 +160,53 @@ pub trait Declarable {
         return Ok(DeclareOutput { transaction_hash, class_hash });
     }
 
+    /// Estimates the fee of declaring the class, without sending the transaction.
+    async fn estimate_declare_fee<P, S>(
+        &self,
+        account: &SingleOwnerAccount<P, S>,
+    ) -> Result<FeeEstimate, MigrationError<<SingleOwnerAccount<P, S> as Account>::SignError>>
+    where
+        P: Provider + Sync + Send,
+        S: Signer + Sync + Send,
+    {
+        let (flattened_class, casm_class_hash) = self.declaration(account).await?;
+
+        account
+            .declare(Arc::new(flattened_class), casm_class_hash)
+            .estimate_fee()
+            .await
+            .map_err(MigrationError::Migrator)
+    }
+
+    /// Returns the class to declare along with its compiled class hash, failing with
+    /// [MigrationError::ClassAlreadyDeclared] if it is already declared.
+    async fn declaration<P, S>(
+        &self,
+        account: &SingleOwnerAccount<P, S>,
+    ) -> Result<
+        (FlattenedSierraClass, FieldElement),
+        MigrationError<<SingleOwnerAccount<P, S> as Account>::SignError>,
+    >
+    where
+        P: Provider + Sync + Send,
+        S: Signer + Sync + Send,
+    {
+        let (flattened_class, casm_class_hash) =
+            prepare_contract_declaration_params(self.artifact_path())?;
+
+        match account
+            .provider()
+            .get_class(BlockId::Tag(BlockTag::Pending), flattened_class.class_hash())
+            .await
+        {
+            Err(ProviderError::StarknetError(StarknetError::ClassHashNotFound)) => {}
+            Ok(_) => return Err(MigrationError::ClassAlreadyDeclared),
+            Err(e) => return Err(MigrationError::Provider(e)),
+        }
+
+        Ok((flattened_class, casm_class_hash))
+    }
+
     fn artifact_path(&self) -> &PathBuf;
 }
 
@@ -162,6 +231,43 @@ pub trait Deployable: Declarable + Sync {
             Err(e) => return Err(e),
         };
 
+        let DojoContractCall { call, contract_address, was_upgraded } = self
+            .deploy_dojo_contract_call(world_address, class_hash, base_class_hash, account)
+            .await?;
+
+        let InvokeTransactionResult { transaction_hash } = account
+            .execute(vec![call])
+            .send_with_cfg(&txn_config)
+            .await
+            .map_err(MigrationError::Migrator)?;
+
+        let receipt = TransactionWaiter::new(transaction_hash, account.provider()).await?;
+        let block_number = get_block_number_from_receipt(receipt);
+
+        Ok(DeployOutput {
+            transaction_hash,
+            block_number,
+            contract_address,
+            declare,
+            base_class_hash,
+            was_upgraded,
+            name: None,
+        })
+    }
+
+    /// Returns the call deploying the contract through the World, or upgrading it if it's already
+    /// deployed with another class, without sending it.
+    async fn deploy_dojo_contract_call<P, S>(
+        &self,
+        world_address: FieldElement,
+        class_hash: FieldElement,
+        base_class_hash: FieldElement,
+        account: &SingleOwnerAccount<P, S>,
+    ) -> Result<DojoContractCall, MigrationError<<SingleOwnerAccount<P, S> as Account>::SignError>>
+    where
+        P: Provider + Sync + Send,
+        S: Signer + Sync + Send,
+    {
         let contract_address =
             get_contract_address(self.salt(), base_class_hash, &[], world_address);
 
@@ -195,24 +301,7 @@ pub trait Deployable: Declarable + Sync {
             Err(e) => return Err(MigrationError::Provider(e)),
         };
 
-        let InvokeTransactionResult { transaction_hash } = account
-            .execute(vec![call])
-            .send_with_cfg(&txn_config)
-            .await
-            .map_err(MigrationError::Migrator)?;
-
-        let receipt = TransactionWaiter::new(transaction_hash, account.provider()).await?;
-        let block_number = get_block_number_from_receipt(receipt);
-
-        Ok(DeployOutput {
-            transaction_hash,
-            block_number,
-            contract_address,
-            declare,
-            base_class_hash,
-            was_upgraded,
-            name: None,
-        })
+        Ok(DojoContractCall { call, contract_address, was_upgraded })
     }
 
     async fn deploy<P, S>(
@@ -262,9 +351,8 @@ pub trait Deployable: Declarable + Sync {
 
         let txn = account.execute(vec![Call {
             calldata,
-            // devnet UDC address
             selector: selector!("deployContract"),
-            to: felt!("0x41a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf"),
+            to: UDC_ADDRESS,
         }]);
 
         let InvokeTransactionResult { transaction_hash } =
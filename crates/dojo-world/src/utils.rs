@@ -6,8 +6,8 @@ use std::time::Duration;
 use futures::FutureExt;
 use starknet::accounts::{AccountError, ConnectedAccount, Declaration, Execution};
 use starknet::core::types::{
-    DeclareTransactionResult, ExecutionResult, FieldElement, InvokeTransactionResult,
-    MaybePendingTransactionReceipt, PendingTransactionReceipt, StarknetError,
+    DeclareTransactionResult, ExecutionResult, FeeEstimate, FieldElement, InvokeTransactionResult,
+    MaybePendingTransactionReceipt, PendingTransactionReceipt, PriceUnit, StarknetError,
     TransactionFinalityStatus, TransactionReceipt, TransactionStatus,
 };
 use starknet::providers::{Provider, ProviderError};
@@ -332,6 +332,92 @@ pub fn block_number_from_receipt(receipt: &TransactionReceipt) -> u64 {
     }
 }
 
+/// The multiplier applied to the estimated fee when none is set in the [TxnConfig].
+const DEFAULT_FEE_ESTIMATE_MULTIPLIER: f64 = 1.1;
+
+/// The precision the fee estimate multiplier is applied with, in fractions of the estimated fee.
+const FEE_ESTIMATE_MULTIPLIER_PRECISION: u128 = 1000;
+
+/// The ratio the max fee is multiplied by each time a transaction is sent again after being
+/// rejected for an insufficient max fee.
+const MAX_FEE_RETRY_RATIO: (u128, u128) = (3, 2);
+
+/// Returns the max fee to send a transaction with, from its fee `estimate` and the number of
+/// times it has already been rejected for an insufficient max fee.
+///
+/// Returns `None` if the estimated fee isn't in WEI, which is the only unit the max fee of a V1
+/// transaction is paid in, if the max fee doesn't fit in a `u128`, or if the estimated fee is
+/// higher than the cap of `txn_config`.
+pub fn compute_max_fee(
+    estimate: &FeeEstimate,
+    retries: u32,
+    txn_config: &TxnConfig,
+) -> Option<FieldElement> {
+    if estimate.unit != PriceUnit::Wei {
+        return None;
+    }
+
+    let estimated_fee = u128::try_from(estimate.overall_fee).ok()?;
+    let cap = txn_config.max_fee_cap.map(u128::try_from).transpose().ok()?;
+    if cap.is_some_and(|cap| estimated_fee > cap) {
+        return None;
+    }
+
+    let multiplier = txn_config.fee_estimate_multiplier.unwrap_or(DEFAULT_FEE_ESTIMATE_MULTIPLIER);
+    let multiplier = (multiplier * FEE_ESTIMATE_MULTIPLIER_PRECISION as f64).round() as u128;
+    let mut max_fee = estimated_fee.checked_mul(multiplier)? / FEE_ESTIMATE_MULTIPLIER_PRECISION;
+
+    let (numerator, denominator) = MAX_FEE_RETRY_RATIO;
+    for _ in 0..retries {
+        max_fee = max_fee.checked_mul(numerator)? / denominator;
+    }
+
+    Some(FieldElement::from(cap.map_or(max_fee, |cap| max_fee.min(cap))))
+}
+
+/// Sends `txn` with the max fee computed from its fee `estimate`, with `send`. If it is rejected
+/// for an insufficient max fee, it is sent again with a higher max fee, up to `max_fee_retries`
+/// times.
+async fn send_with_retries<T, R, S, F, Fut>(
+    mut txn: T,
+    estimate: &FeeEstimate,
+    txn_config: &TxnConfig,
+    send: F,
+) -> Result<R, AccountError<S>>
+where
+    F: Fn(T, FieldElement) -> Fut,
+    Fut: Future<Output = (T, Result<R, AccountError<S>>)>,
+{
+    let mut retries = 0;
+
+    loop {
+        let max_fee =
+            compute_max_fee(estimate, retries, txn_config).ok_or(AccountError::FeeOutOfRange)?;
+
+        let (sent, result) = send(txn, max_fee).await;
+        match result {
+            Err(e) if should_retry(&e, max_fee, retries, txn_config) => retries += 1,
+            res => return res,
+        }
+        txn = sent;
+    }
+}
+
+/// Returns `true` if the transaction sent with `max_fee` should be sent again after failing with
+/// `error`, ie. if it was rejected for an insufficient max fee which can still be bumped.
+fn should_retry<S>(
+    error: &AccountError<S>,
+    max_fee: FieldElement,
+    retries: u32,
+    txn_config: &TxnConfig,
+) -> bool {
+    matches!(
+        error,
+        AccountError::Provider(ProviderError::StarknetError(StarknetError::InsufficientMaxFee))
+    ) && retries < txn_config.max_fee_retries
+        && txn_config.max_fee_cap != Some(max_fee)
+}
+
 /// Helper trait to abstract away setting `TxnConfig` configurations before sending a transaction
 /// Implemented by types from `starknet-accounts` like `Execution`, `Declaration`, etc...
 #[allow(async_fn_in_trait)]
@@ -341,8 +427,9 @@ where
 {
     type R;
 
-    /// Sets `fee_estimate_multiplier` from `TxnConfig` if its present before calling `send` method
-    /// on the respective type.
+    /// Estimates the fee of the transaction, then sends it with the max fee computed from the
+    /// `TxnConfig` (see [compute_max_fee]). If the transaction is rejected for an insufficient
+    /// max fee, it is sent again with a higher max fee, up to `max_fee_retries` times.
     async fn send_with_cfg(
         self,
         txn_config: &TxnConfig,
@@ -356,14 +443,16 @@ where
     type R = InvokeTransactionResult;
 
     async fn send_with_cfg(
-        mut self,
+        self,
         txn_config: &TxnConfig,
     ) -> Result<Self::R, AccountError<T::SignError>> {
-        if let TxnConfig { fee_estimate_multiplier: Some(fee_est_mul), .. } = txn_config {
-            self = self.fee_estimate_multiplier(*fee_est_mul);
-        }
-
-        self.send().await
+        let estimate = self.estimate_fee().await?;
+        send_with_retries(self, &estimate, txn_config, |txn, max_fee| async move {
+            let txn = txn.max_fee(max_fee);
+            let result = txn.send().await;
+            (txn, result)
+        })
+        .await
     }
 }
 
@@ -374,32 +463,40 @@ where
     type R = DeclareTransactionResult;
 
     async fn send_with_cfg(
-        mut self,
+        self,
         txn_config: &TxnConfig,
     ) -> Result<Self::R, AccountError<T::SignError>> {
-        if let TxnConfig { fee_estimate_multiplier: Some(fee_est_mul), .. } = txn_config {
-            self = self.fee_estimate_multiplier(*fee_est_mul);
-        }
-
-        self.send().await
+        let estimate = self.estimate_fee().await?;
+        send_with_retries(self, &estimate, txn_config, |txn, max_fee| async move {
+            let txn = txn.max_fee(max_fee);
+            let result = txn.send().await;
+            (txn, result)
+        })
+        .await
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::convert::Infallible;
+    use std::sync::Mutex;
+
     use assert_matches::assert_matches;
     use dojo_test_utils::sequencer::{
         get_default_test_starknet_config, SequencerConfig, TestSequencer,
     };
+    use starknet::accounts::AccountError;
     use starknet::core::types::{
-        ExecutionResources, ExecutionResult, FeePayment, FieldElement, InvokeTransactionReceipt,
-        MaybePendingTransactionReceipt, PendingInvokeTransactionReceipt, PendingTransactionReceipt,
-        PriceUnit, TransactionFinalityStatus, TransactionReceipt,
+        ExecutionResources, ExecutionResult, FeeEstimate, FeePayment, FieldElement,
+        InvokeTransactionReceipt, MaybePendingTransactionReceipt, PendingInvokeTransactionReceipt,
+        PendingTransactionReceipt, PriceUnit, StarknetError, TransactionFinalityStatus,
+        TransactionReceipt,
     };
     use starknet::providers::jsonrpc::HttpTransport;
-    use starknet::providers::JsonRpcClient;
+    use starknet::providers::{JsonRpcClient, ProviderError};
 
-    use super::{Duration, TransactionWaiter};
+    use super::{compute_max_fee, send_with_retries, Duration, TransactionWaiter};
+    use crate::migration::TxnConfig;
 
     async fn create_test_sequencer() -> (TestSequencer, JsonRpcClient<HttpTransport>) {
         let sequencer =
@@ -657,4 +754,86 @@ mod tests {
             assert!(err.to_string().contains("transaction reverted"))
         }
     }
+
+    fn fee_estimate(overall_fee: FieldElement, unit: PriceUnit) -> FeeEstimate {
+        FeeEstimate { gas_consumed: FieldElement::ONE, gas_price: overall_fee, overall_fee, unit }
+    }
+
+    #[test]
+    fn compute_max_fee_from_config() {
+        let estimate = fee_estimate(FieldElement::from(1000u64), PriceUnit::Wei);
+
+        let config = TxnConfig::default();
+        assert_eq!(compute_max_fee(&estimate, 0, &config), Some(FieldElement::from(1100u64)));
+        assert_eq!(compute_max_fee(&estimate, 1, &config), Some(FieldElement::from(1650u64)));
+        assert_eq!(compute_max_fee(&estimate, 2, &config), Some(FieldElement::from(2475u64)));
+
+        let config = TxnConfig { fee_estimate_multiplier: Some(2.0), ..Default::default() };
+        assert_eq!(compute_max_fee(&estimate, 0, &config), Some(FieldElement::from(2000u64)));
+
+        let config = TxnConfig {
+            fee_estimate_multiplier: Some(2.0),
+            max_fee_cap: Some(FieldElement::from(1500u64)),
+            ..Default::default()
+        };
+        assert_eq!(compute_max_fee(&estimate, 0, &config), Some(FieldElement::from(1500u64)));
+
+        // the transaction can't be paid with the capped max fee
+        let config =
+            TxnConfig { max_fee_cap: Some(FieldElement::from(999u64)), ..Default::default() };
+        assert_eq!(compute_max_fee(&estimate, 0, &config), None);
+
+        // fees above `u64::MAX` are computed without losing precision
+        let estimate = fee_estimate(FieldElement::from(u64::MAX as u128 * 10), PriceUnit::Wei);
+        let max_fee = FieldElement::from(u64::MAX as u128 * 11);
+        assert_eq!(compute_max_fee(&estimate, 0, &TxnConfig::default()), Some(max_fee));
+
+        let estimate = fee_estimate(FieldElement::from(u128::MAX), PriceUnit::Wei);
+        assert_eq!(compute_max_fee(&estimate, 0, &TxnConfig::default()), None);
+        let estimate = fee_estimate(FieldElement::MAX, PriceUnit::Wei);
+        assert_eq!(compute_max_fee(&estimate, 0, &TxnConfig::default()), None);
+
+        // the max fee of a V1 transaction can't be paid in STRK
+        let estimate = fee_estimate(FieldElement::from(1000u64), PriceUnit::Fri);
+        assert_eq!(compute_max_fee(&estimate, 0, &TxnConfig::default()), None);
+    }
+
+    #[tokio::test]
+    async fn underpriced_transaction_is_sent_again_with_a_bumped_max_fee() {
+        let estimate = fee_estimate(FieldElement::from(1000u64), PriceUnit::Wei);
+        let required_fee = FieldElement::from(2000u64);
+
+        // the transaction is only included with a max fee covering the required fee
+        let attempts = Mutex::new(Vec::new());
+        let send = |(), max_fee: FieldElement| {
+            attempts.lock().unwrap().push(max_fee);
+            let result = if max_fee < required_fee {
+                Err(AccountError::<Infallible>::Provider(ProviderError::StarknetError(
+                    StarknetError::InsufficientMaxFee,
+                )))
+            } else {
+                Ok(max_fee)
+            };
+            async move { ((), result) }
+        };
+
+        let config = TxnConfig { max_fee_retries: 2, ..Default::default() };
+        let included = send_with_retries((), &estimate, &config, send).await.unwrap();
+        assert_eq!(included, FieldElement::from(2475u64));
+        assert_eq!(
+            attempts.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![FieldElement::from(1100u64), FieldElement::from(1650u64), included]
+        );
+
+        // the transaction isn't sent again once the retries are exhausted
+        let config = TxnConfig { max_fee_retries: 1, ..Default::default() };
+        let result = send_with_retries((), &estimate, &config, send).await;
+        assert_matches!(
+            result,
+            Err(AccountError::Provider(ProviderError::StarknetError(
+                StarknetError::InsufficientMaxFee
+            )))
+        );
+        assert_eq!(attempts.lock().unwrap().len(), 2);
+    }
 }
@@ -2,12 +2,18 @@ use std::path::PathBuf;
 
 use alloy_primitives::U256;
 use katana_primitives::chain::ChainId;
+use katana_primitives::chain_spec::SupportedTxVersions;
 use katana_primitives::genesis::allocation::DevAllocationsGenerator;
 use katana_primitives::genesis::constant::DEFAULT_PREFUNDED_ACCOUNT_BALANCE;
 use katana_primitives::genesis::Genesis;
+use katana_primitives::version::{Version, CURRENT_STARKNET_VERSION};
+pub use katana_provider::providers::fork::backend::ForkRefreshPolicy;
 use url::Url;
 
-use crate::constants::{DEFAULT_INVOKE_MAX_STEPS, DEFAULT_VALIDATE_MAX_STEPS};
+use crate::constants::{
+    DEFAULT_INVOKE_MAX_STEPS, DEFAULT_MAX_CALLDATA_LEN, DEFAULT_VALIDATE_MAX_STEPS,
+    MAX_RECURSION_DEPTH,
+};
 use crate::env::BlockContextGenerator;
 
 #[derive(Debug, Clone)]
@@ -16,17 +22,56 @@ pub struct StarknetConfig {
     pub env: Environment,
     pub fork_rpc_url: Option<Url>,
     pub fork_block_number: Option<u64>,
+    /// When the data cached from the forked network is fetched again.
+    pub fork_refresh_policy: ForkRefreshPolicy,
     pub disable_validate: bool,
     pub db_dir: Option<PathBuf>,
     pub genesis: Genesis,
+    /// Makes the produced blocks only depend on the genesis and on the transactions, so that the
+    /// same blocks, and block hashes, are produced on every run. The block timestamps are
+    /// generated from a counter instead of the system clock, which starts from the timestamp of
+    /// the latest block and advances by one second per block, and the blocks are produced by
+    /// [`DEFAULT_SEQUENCER_ADDRESS`] whatever the sequencer address of the genesis.
+    ///
+    /// [`DEFAULT_SEQUENCER_ADDRESS`]: crate::constants::DEFAULT_SEQUENCER_ADDRESS
+    pub deterministic: bool,
+    /// Executes the transactions of every block a second time before committing it, and reports
+    /// any difference between the state updates of both runs. This is a debugging aid which
+    /// doubles the execution cost of the blocks.
+    pub check_determinism: bool,
+    /// The clock the block timestamps are derived from.
+    pub timestamp_source: TimestampSource,
+    /// The maximum number of seconds the block timestamps may drift from the clock of the
+    /// [`TimestampSource`], once shifted with `dev_setNextBlockTimestamp` or
+    /// `dev_increaseNextBlockTimestamp`. Timestamps drifting more are clamped.
+    pub max_timestamp_drift: Option<u64>,
 }
 
 impl StarknetConfig {
     pub fn block_context_generator(&self) -> BlockContextGenerator {
-        BlockContextGenerator::default()
+        let source_offset = match self.timestamp_source {
+            TimestampSource::Offset(offset) => offset,
+            // measured once the node is running, see `ClockSyncTask`
+            TimestampSource::WallClock | TimestampSource::TimeServer(_) => 0,
+        };
+
+        BlockContextGenerator { source_offset, ..Default::default() }
     }
 }
 
+/// The clock the block timestamps are derived from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TimestampSource {
+    /// The system clock of the node.
+    #[default]
+    WallClock,
+    /// The system clock shifted by a fixed number of seconds.
+    Offset(i64),
+    /// The clock of a time server, which is periodically asked for the current UNIX time over
+    /// HTTP. Until it first answers, the system clock is used.
+    TimeServer(Url),
+}
+
 impl Default for StarknetConfig {
     fn default() -> Self {
         let accounts = DevAllocationsGenerator::new(10)
@@ -40,10 +85,15 @@ impl Default for StarknetConfig {
             disable_fee: false,
             fork_rpc_url: None,
             fork_block_number: None,
+            fork_refresh_policy: ForkRefreshPolicy::default(),
             env: Environment::default(),
             disable_validate: false,
             db_dir: None,
             genesis,
+            deterministic: false,
+            check_determinism: false,
+            timestamp_source: TimestampSource::default(),
+            max_timestamp_drift: None,
         }
     }
 }
@@ -53,6 +103,15 @@ pub struct Environment {
     pub chain_id: ChainId,
     pub invoke_max_steps: u32,
     pub validate_max_steps: u32,
+    /// The maximum depth of nested contract calls, past which a transaction fails.
+    pub max_recursion_depth: usize,
+    /// The maximum number of elements of the calldata of the submitted transactions, the
+    /// constructor calldata of the account deployments.
+    pub max_calldata_len: usize,
+    /// The transaction versions accepted by the node.
+    pub supported_tx_versions: SupportedTxVersions,
+    /// The Starknet protocol version of the blocks produced by the node.
+    pub version: Version,
 }
 
 impl Default for Environment {
@@ -61,6 +120,10 @@ impl Default for Environment {
             chain_id: ChainId::parse("KATANA").unwrap(),
             invoke_max_steps: DEFAULT_INVOKE_MAX_STEPS,
             validate_max_steps: DEFAULT_VALIDATE_MAX_STEPS,
+            max_recursion_depth: MAX_RECURSION_DEPTH,
+            max_calldata_len: DEFAULT_MAX_CALLDATA_LEN,
+            supported_tx_versions: SupportedTxVersions::default(),
+            version: CURRENT_STARKNET_VERSION,
         }
     }
 }
@@ -0,0 +1,133 @@
+//! Checks that the execution of the blocks is deterministic.
+//!
+//! When enabled with [`StarknetConfig::check_determinism`], the transactions of every block are
+//! executed a second time against the state the block was built on, before the block is
+//! committed. The state updates of both runs must be identical, any difference is reported with
+//! the keys whose values diverge.
+//!
+//! [`StarknetConfig::check_determinism`]: super::config::StarknetConfig::check_determinism
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::LowerHex;
+
+use katana_executor::{ExecutionOutput, ExecutionResult};
+use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
+use katana_primitives::transaction::{
+    DeclareTxWithClass, ExecutableTx, ExecutableTxWithHash, Tx, TxWithHash,
+};
+
+/// Rebuilds the executable form of the transactions that were successfully executed, in their
+/// execution order. Returns `None` if the class of a declare transaction is missing from the
+/// state updates.
+pub(super) fn executed_transactions(output: &ExecutionOutput) -> Option<Vec<ExecutableTxWithHash>> {
+    output
+        .transactions
+        .iter()
+        .filter(|(_, res)| matches!(res, ExecutionResult::Success { .. }))
+        .map(|(tx, _)| executable_transaction(tx, &output.states))
+        .collect()
+}
+
+fn executable_transaction(
+    tx: &TxWithHash,
+    states: &StateUpdatesWithDeclaredClasses,
+) -> Option<ExecutableTxWithHash> {
+    let transaction = match &tx.transaction {
+        Tx::Invoke(tx) => ExecutableTx::Invoke(tx.clone()),
+        Tx::L1Handler(tx) => ExecutableTx::L1Handler(tx.clone()),
+        Tx::DeployAccount(tx) => ExecutableTx::DeployAccount(tx.clone()),
+        Tx::Declare(tx) => {
+            let class_hash = tx.class_hash();
+            ExecutableTx::Declare(DeclareTxWithClass {
+                sierra_class: states.declared_sierra_classes.get(&class_hash).cloned(),
+                compiled_class: states.declared_compiled_classes.get(&class_hash)?.clone(),
+                transaction: tx.clone(),
+            })
+        }
+    };
+
+    Some(ExecutableTxWithHash { hash: tx.hash, transaction })
+}
+
+/// Returns a description of every key whose value differs between the `expected` and the
+/// `actual` state updates, sorted by key.
+pub(super) fn diff_state_updates(expected: &StateUpdates, actual: &StateUpdates) -> Vec<String> {
+    let mut diffs = Vec::new();
+
+    diff("nonce", &expected.nonce_updates, &actual.nonce_updates, &mut diffs);
+    diff("class hash", &expected.contract_updates, &actual.contract_updates, &mut diffs);
+    diff("compiled class hash", &expected.declared_classes, &actual.declared_classes, &mut diffs);
+
+    let addresses = expected.storage_updates.keys().chain(actual.storage_updates.keys());
+    for address in addresses.collect::<BTreeSet<_>>() {
+        let expected = expected.storage_updates.get(address).cloned().unwrap_or_default();
+        let actual = actual.storage_updates.get(address).cloned().unwrap_or_default();
+        diff(&format!("storage of {address}"), &expected, &actual, &mut diffs);
+    }
+
+    diffs.sort();
+    diffs
+}
+
+fn diff<K: LowerHex, V: LowerHex>(
+    kind: &str,
+    expected: &HashMap<K, V>,
+    actual: &HashMap<K, V>,
+    diffs: &mut Vec<String>,
+) {
+    let to_hex = |map: &HashMap<K, V>| {
+        map.iter().map(|(k, v)| (format!("{k:#x}"), format!("{v:#x}"))).collect::<BTreeMap<_, _>>()
+    };
+
+    let (expected, actual) = (to_hex(expected), to_hex(actual));
+    for key in expected.keys().chain(actual.keys()).collect::<BTreeSet<_>>() {
+        let (expected, actual) = (expected.get(key), actual.get(key));
+        if expected != actual {
+            let value = |v: Option<&String>| v.cloned().unwrap_or_else(|| "none".to_string());
+            diffs.push(format!(
+                "{kind} at key {key}: {} on the first run, {} on the second run",
+                value(expected),
+                value(actual)
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::contract::ContractAddress;
+    use katana_primitives::FieldElement;
+
+    use super::*;
+
+    #[test]
+    fn diff_of_state_updates() {
+        let address = ContractAddress::from(FieldElement::ONE);
+
+        let mut expected = StateUpdates::default();
+        expected.nonce_updates.insert(address, FieldElement::ONE);
+        expected.storage_updates.insert(
+            address,
+            HashMap::from([
+                (FieldElement::ONE, FieldElement::ONE),
+                (FieldElement::TWO, 2u8.into()),
+            ]),
+        );
+
+        assert!(diff_state_updates(&expected, &expected.clone()).is_empty());
+
+        let mut actual = expected.clone();
+        actual.storage_updates.get_mut(&address).unwrap().insert(FieldElement::TWO, 3u8.into());
+        actual.storage_updates.get_mut(&address).unwrap().remove(&FieldElement::ONE);
+        actual.contract_updates.insert(address, FieldElement::TWO);
+
+        assert_eq!(
+            diff_state_updates(&expected, &actual),
+            vec![
+                "class hash at key 0x1: none on the first run, 0x2 on the second run",
+                "storage of 0x1 at key 0x1: 0x1 on the first run, none on the second run",
+                "storage of 0x1 at key 0x2: 0x2 on the first run, 0x3 on the second run",
+            ]
+        );
+    }
+}
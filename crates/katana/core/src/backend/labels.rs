@@ -0,0 +1,115 @@
+//! Names given to addresses with `dev_setAddressLabel`, rendered along with the addresses in the
+//! logs of the node and in the Katana responses.
+//!
+//! The names end up in the logs as they are, so they are restricted to a few printable characters
+//! which can't forge log lines, and their number is bounded as they are only kept in memory.
+
+use std::collections::{BTreeMap, HashMap};
+
+use katana_primitives::contract::ContractAddress;
+use parking_lot::RwLock;
+
+/// The maximum length (in characters) of the name of an address.
+pub const MAX_ADDRESS_LABEL_LEN: usize = 64;
+
+/// The maximum number of named addresses.
+pub const MAX_ADDRESS_LABELS: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AddressLabelError {
+    #[error("address label is longer than {MAX_ADDRESS_LABEL_LEN} characters")]
+    TooLong,
+    #[error("address label has characters other than ASCII letters, digits, spaces and `_-.:/`")]
+    InvalidCharacter,
+    #[error("{MAX_ADDRESS_LABELS} addresses are already named")]
+    TooMany,
+}
+
+#[derive(Debug, Default)]
+pub struct AddressLabels {
+    labels: RwLock<HashMap<ContractAddress, String>>,
+}
+
+impl AddressLabels {
+    /// Gives a name to an address, or removes its name if `name` is empty.
+    pub fn set(&self, address: ContractAddress, name: String) -> Result<(), AddressLabelError> {
+        if name.len() > MAX_ADDRESS_LABEL_LEN {
+            return Err(AddressLabelError::TooLong);
+        }
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || " _-.:/".contains(c)) {
+            return Err(AddressLabelError::InvalidCharacter);
+        }
+
+        let mut labels = self.labels.write();
+        if name.is_empty() {
+            labels.remove(&address);
+        } else if labels.len() >= MAX_ADDRESS_LABELS && !labels.contains_key(&address) {
+            return Err(AddressLabelError::TooMany);
+        } else {
+            labels.insert(address, name);
+        }
+
+        Ok(())
+    }
+
+    /// Formats an address for the logs, along with its name if it has one.
+    pub fn display(&self, address: ContractAddress) -> String {
+        match self.labels.read().get(&address) {
+            Some(name) => format!("{name} ({address})"),
+            None => address.to_string(),
+        }
+    }
+
+    /// Returns the names of all the named addresses, sorted by address.
+    pub fn all(&self) -> Vec<(ContractAddress, String)> {
+        let labels = self.labels.read();
+        let labels = labels.iter().map(|(address, name)| (*address, name.clone()));
+        labels.collect::<BTreeMap<_, _>>().into_iter().collect()
+    }
+
+    /// Returns the names of the named addresses among `addresses`, sorted by address.
+    pub fn of(
+        &self,
+        addresses: impl IntoIterator<Item = ContractAddress>,
+    ) -> Vec<(ContractAddress, String)> {
+        let labels = self.labels.read();
+        let labels = addresses
+            .into_iter()
+            .filter_map(|address| labels.get(&address).map(|name| (address, name.clone())));
+        labels.collect::<BTreeMap<_, _>>().into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::FieldElement;
+
+    use super::*;
+
+    #[test]
+    fn set_address_labels() {
+        let labels = AddressLabels::default();
+        let address = |n: u64| ContractAddress::from(FieldElement::from(n));
+
+        labels.set(address(2), "game".to_string()).unwrap();
+        labels.set(address(1), "world".to_string()).unwrap();
+        assert_eq!(labels.display(address(2)), "game (0x2)");
+        assert_eq!(labels.display(address(3)), "0x3");
+        assert_eq!(labels.of([address(3), address(2)]), [(address(2), "game".to_string())]);
+
+        let long = "a".repeat(MAX_ADDRESS_LABEL_LEN + 1);
+        assert_eq!(labels.set(address(3), long), Err(AddressLabelError::TooLong));
+        let injected = "game\nINFO forged line".to_string();
+        assert_eq!(labels.set(address(3), injected), Err(AddressLabelError::InvalidCharacter));
+
+        for n in 3..MAX_ADDRESS_LABELS as u64 + 1 {
+            labels.set(address(n), format!("contract {n}")).unwrap();
+        }
+        let more = labels.set(address(0), "more".to_string());
+        assert_eq!(more, Err(AddressLabelError::TooMany));
+        // the named addresses can still be renamed or unnamed
+        labels.set(address(1), "world 2".to_string()).unwrap();
+        labels.set(address(1), String::new()).unwrap();
+        assert_eq!(labels.all().len(), MAX_ADDRESS_LABELS - 1);
+    }
+}
@@ -0,0 +1,103 @@
+//! Consumption of the L2 to L1 messages on the settlement chain, as seen by the messaging service.
+//!
+//! Identical messages have the same hash, so the settlement chain only counts how many of them can
+//! still be consumed. Consuming one of them is attributed to the earliest one settled. Only the
+//! settled messages which haven't been consumed yet are kept, so that the memory used doesn't grow
+//! with the consumed messages.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use alloy_primitives::B256;
+use katana_primitives::block::BlockNumber;
+
+/// A message identified by the block it was sent in and its index among the messages of the block.
+pub type MessageId = (BlockNumber, usize);
+
+#[derive(Debug, Default)]
+pub struct ConsumedMessagesToL1 {
+    /// The latest block whose messages have been settled.
+    settled_block: Option<BlockNumber>,
+    /// The settled messages which haven't been consumed yet, in the order they were settled.
+    unconsumed: HashMap<B256, VecDeque<MessageId>>,
+    /// The number of consumptions seen before their message was settled.
+    early_consumptions: HashMap<B256, usize>,
+}
+
+impl ConsumedMessagesToL1 {
+    /// Records the settlement of the messages of `block`, given their hashes in the order they
+    /// were sent.
+    pub fn settle<I>(&mut self, block: BlockNumber, hashes: I)
+    where
+        I: IntoIterator<Item = B256>,
+    {
+        for (index, hash) in hashes.into_iter().enumerate() {
+            match self.early_consumptions.entry(hash) {
+                Entry::Occupied(mut count) => {
+                    *count.get_mut() -= 1;
+                    if *count.get() == 0 {
+                        count.remove();
+                    }
+                }
+                Entry::Vacant(_) => {
+                    self.unconsumed.entry(hash).or_default().push_back((block, index))
+                }
+            }
+        }
+
+        self.settled_block = self.settled_block.max(Some(block));
+    }
+
+    /// Records the consumption of a message of hash `hash`.
+    pub fn consume(&mut self, hash: B256) {
+        match self.unconsumed.entry(hash) {
+            Entry::Occupied(mut ids) => {
+                ids.get_mut().pop_front();
+                if ids.get().is_empty() {
+                    ids.remove();
+                }
+            }
+            Entry::Vacant(_) => *self.early_consumptions.entry(hash).or_default() += 1,
+        }
+    }
+
+    /// Returns `true` if the message `id` of hash `hash` has been settled and consumed.
+    pub fn is_consumed(&self, hash: &B256, id: MessageId) -> bool {
+        let settled = self.settled_block.is_some_and(|block| block >= id.0);
+        settled && !self.unconsumed.get(hash).is_some_and(|ids| ids.contains(&id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consume_identical_messages() {
+        let mut messages = ConsumedMessagesToL1::default();
+        let (hash, other) = (B256::repeat_byte(1), B256::repeat_byte(2));
+
+        // the messages aren't consumed before being settled
+        messages.settle(1, [hash, other]);
+        messages.settle(2, [hash]);
+        assert!(!messages.is_consumed(&hash, (1, 0)));
+        assert!(!messages.is_consumed(&hash, (3, 0)));
+
+        // only the earliest of the identical messages is consumed
+        messages.consume(hash);
+        assert!(messages.is_consumed(&hash, (1, 0)));
+        assert!(!messages.is_consumed(&other, (1, 1)));
+        assert!(!messages.is_consumed(&hash, (2, 0)));
+
+        messages.consume(hash);
+        assert!(messages.is_consumed(&hash, (2, 0)));
+        assert!(messages.unconsumed.get(&hash).is_none());
+
+        // a consumption seen before the settlement of its message is attributed to it
+        messages.consume(hash);
+        messages.settle(3, [hash, hash]);
+        assert!(messages.is_consumed(&hash, (3, 0)));
+        assert!(!messages.is_consumed(&hash, (3, 1)));
+        assert!(messages.early_consumptions.is_empty());
+    }
+}
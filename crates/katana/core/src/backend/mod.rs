@@ -1,35 +1,56 @@
+use std::collections::BTreeSet;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
+use futures::channel::mpsc::{channel, Receiver, Sender};
 use katana_executor::{ExecutionOutput, ExecutionResult, ExecutorFactory};
 use katana_primitives::block::{
-    Block, FinalityStatus, GasPrices, Header, PartialHeader, SealedBlockWithStatus,
+    Block, BlockNumber, FinalityStatus, GasPrices, Header, PartialHeader, SealedBlockWithStatus,
 };
 use katana_primitives::chain::ChainId;
 use katana_primitives::env::BlockEnv;
-use katana_primitives::version::CURRENT_STARKNET_VERSION;
+use katana_primitives::receipt::Receipt;
+use katana_primitives::transaction::{ExecutableTxWithHash, TxHash, TxOrigin, TxWithHash};
 use katana_primitives::FieldElement;
 use katana_provider::providers::fork::ForkedProvider;
 use katana_provider::providers::in_memory::InMemoryProvider;
-use katana_provider::traits::block::{BlockHashProvider, BlockWriter};
+use katana_provider::traits::block::{
+    BlockHashProvider, BlockNumberProvider, BlockWriter, HeaderProvider,
+};
+use katana_provider::traits::state::StateFactoryProvider;
+use lru::LruCache;
 use parking_lot::RwLock;
 use starknet::core::types::{BlockId, BlockStatus, MaybePendingBlockWithTxHashes};
 use starknet::core::utils::parse_cairo_short_string;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider};
-use tracing::{info, trace};
+use tracing::{error, info, trace, warn};
 
 pub mod config;
 pub mod contract;
+mod determinism;
+pub mod labels;
+pub mod messages;
 pub mod storage;
 
 use self::config::StarknetConfig;
+use self::labels::AddressLabels;
+use self::messages::ConsumedMessagesToL1;
 use self::storage::Blockchain;
+use crate::constants::DEFAULT_SEQUENCER_ADDRESS;
 use crate::env::BlockContextGenerator;
 use crate::service::block_producer::{BlockProductionError, MinedBlockOutcome};
 use crate::utils::get_current_timestamp;
 
 pub(crate) const LOG_TARGET: &str = "katana::core::backend";
 
+/// The maximum number of mined block notifications a listener can lag behind by.
+pub const MINED_BLOCK_LISTENER_BUFFER_SIZE: usize = 256;
+
+/// The maximum number of transactions whose origin metadata is kept. The origins of the oldest
+/// transactions are dropped first.
+pub const MAX_TX_ORIGINS: usize = 10_000;
+
 pub struct Backend<EF: ExecutorFactory> {
     /// The config used to generate the backend.
     pub config: StarknetConfig,
@@ -39,13 +60,30 @@ pub struct Backend<EF: ExecutorFactory> {
     pub chain_id: ChainId,
     /// The block context generator.
     pub block_context_generator: RwLock<BlockContextGenerator>,
+    /// The latest local block whose L2 to L1 messages have been settled on the settlement chain.
+    /// `None` if no messages have been settled yet, or if messaging is disabled.
+    pub messaging_settled_block: RwLock<Option<BlockNumber>>,
+    /// The L2 to L1 messages seen consumed on the settlement chain by the messaging service.
+    pub consumed_messages_to_l1: RwLock<ConsumedMessagesToL1>,
+    /// The origin metadata submitted along with the latest transactions, which is only kept in
+    /// memory.
+    tx_origins: RwLock<LruCache<TxHash, TxOrigin>>,
+    /// The names given to addresses, rendered along with them in the logs. Only kept in memory.
+    pub address_labels: Arc<AddressLabels>,
+    /// The listeners notified with the number of every mined block.
+    mined_block_listeners: RwLock<Vec<Sender<BlockNumber>>>,
 
     pub executor_factory: Arc<EF>,
 }
 
 impl<EF: ExecutorFactory> Backend<EF> {
     pub async fn new(executor_factory: Arc<EF>, mut config: StarknetConfig) -> Self {
-        let block_context_generator = config.block_context_generator();
+        let mut block_context_generator = config.block_context_generator();
+
+        if config.deterministic {
+            // the blocks don't depend on the sequencer the genesis was configured with
+            config.genesis.sequencer_address = *DEFAULT_SEQUENCER_ADDRESS;
+        }
 
         let blockchain: Blockchain = if let Some(forked_url) = &config.fork_rpc_url {
             let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(forked_url.clone())));
@@ -86,7 +124,12 @@ impl<EF: ExecutorFactory> Backend<EF> {
             );
 
             let blockchain = Blockchain::new_from_forked(
-                ForkedProvider::new(provider, forked_block_num.into()).unwrap(),
+                ForkedProvider::new_with_refresh_policy(
+                    provider,
+                    forked_block_num.into(),
+                    config.fork_refresh_policy,
+                )
+                .unwrap(),
                 block.block_hash,
                 &config.genesis,
                 match block.status {
@@ -100,27 +143,91 @@ impl<EF: ExecutorFactory> Backend<EF> {
             config.env.chain_id = forked_chain_id.into();
             blockchain
         } else if let Some(db_path) = &config.db_dir {
-            Blockchain::new_with_db(db_path, &config.genesis)
+            Blockchain::new_with_db(db_path, &config.genesis, config.env.version)
                 .expect("able to create blockchain from db")
         } else {
-            Blockchain::new_with_genesis(InMemoryProvider::new(), &config.genesis)
-                .expect("able to create blockchain from genesis block")
+            Blockchain::new_with_genesis(
+                InMemoryProvider::new(),
+                &config.genesis,
+                config.env.version,
+            )
+            .expect("able to create blockchain from genesis block")
         };
 
+        if config.deterministic {
+            let provider = blockchain.provider();
+            let latest = provider.latest_number().expect("able to get latest block number");
+            let header = provider
+                .header_by_number(latest)
+                .expect("able to get latest block header")
+                .expect("latest block header exists");
+            block_context_generator.deterministic_clock = Some(header.timestamp);
+        }
+
         Self {
             chain_id: config.env.chain_id,
             blockchain,
             config,
             executor_factory,
             block_context_generator: RwLock::new(block_context_generator),
+            messaging_settled_block: RwLock::new(None),
+            consumed_messages_to_l1: RwLock::new(ConsumedMessagesToL1::default()),
+            tx_origins: RwLock::new(LruCache::new(
+                NonZeroUsize::new(MAX_TX_ORIGINS).expect("non zero capacity"),
+            )),
+            address_labels: Arc::new(AddressLabels::default()),
+            mined_block_listeners: RwLock::new(Vec::new()),
         }
     }
 
+    /// Records the origin metadata a transaction accepted in the pool was submitted with.
+    pub fn record_tx_origin(&self, hash: TxHash, origin: TxOrigin) {
+        self.tx_origins.write().push(hash, origin);
+    }
+
+    /// Returns the origin metadata the transaction `hash` was submitted with, if it is still kept.
+    pub fn tx_origin(&self, hash: &TxHash) -> Option<TxOrigin> {
+        self.tx_origins.read().peek(hash).cloned()
+    }
+
+    /// Returns a receiver of the numbers of the blocks mined from now on.
+    ///
+    /// The receiver is closed if it lags behind by more than [`MINED_BLOCK_LISTENER_BUFFER_SIZE`]
+    /// blocks, so that it never silently misses a block.
+    pub fn add_mined_block_listener(&self) -> Receiver<BlockNumber> {
+        let (tx, rx) = channel(MINED_BLOCK_LISTENER_BUFFER_SIZE);
+        self.mined_block_listeners.write().push(tx);
+        rx
+    }
+
+    /// Notifies the listeners about a mined block, and drops the ones whose receiver is closed or
+    /// lagging behind.
+    fn notify_mined_block(&self, block_number: BlockNumber) {
+        self.mined_block_listeners.write().retain_mut(|listener| {
+            match listener.try_send(block_number) {
+                Ok(()) => true,
+                Err(e) if e.is_full() => {
+                    warn!(
+                        target: LOG_TARGET,
+                        %block_number,
+                        "Closing mined block listener lagging behind.",
+                    );
+                    false
+                }
+                Err(_) => false,
+            }
+        });
+    }
+
     pub fn do_mine_block(
         &self,
         block_env: &BlockEnv,
         execution_output: ExecutionOutput,
     ) -> Result<MinedBlockOutcome, BlockProductionError> {
+        if self.config.check_determinism {
+            self.check_determinism(block_env, &execution_output);
+        }
+
         // we optimistically allocate the maximum amount possible
         let mut txs = Vec::with_capacity(execution_output.transactions.len());
         let mut traces = Vec::with_capacity(execution_output.transactions.len());
@@ -129,6 +236,7 @@ impl<EF: ExecutorFactory> Backend<EF> {
         // only include successful transactions in the block
         for (tx, res) in execution_output.transactions {
             if let ExecutionResult::Success { receipt, trace, .. } = res {
+                self.log_included_transaction(&tx, &receipt);
                 txs.push(tx);
                 traces.push(trace);
                 receipts.push(receipt);
@@ -142,7 +250,7 @@ impl<EF: ExecutorFactory> Backend<EF> {
         let partial_header = PartialHeader {
             number: block_number,
             parent_hash: prev_hash,
-            version: CURRENT_STARKNET_VERSION,
+            version: self.config.env.version,
             timestamp: block_env.timestamp,
             sequencer_address: block_env.sequencer_address,
             gas_prices: GasPrices {
@@ -170,26 +278,203 @@ impl<EF: ExecutorFactory> Backend<EF> {
             "Block mined.",
         );
 
+        self.notify_mined_block(block_number);
+
         Ok(MinedBlockOutcome { block_number, stats: execution_output.stats })
     }
 
+    fn log_included_transaction(&self, tx: &TxWithHash, receipt: &Receipt) {
+        let sender = match tx.transaction.sender_address() {
+            Some(address) => self.address_labels.display(address),
+            None => "L1".to_string(),
+        };
+
+        let emitters = receipt.events().iter().map(|e| e.from_address).collect::<BTreeSet<_>>();
+        let emitters = emitters
+            .into_iter()
+            .map(|address| self.address_labels.display(address))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        trace!(
+            target: LOG_TARGET,
+            tx = %format!("{:#x}", tx.hash),
+            %sender,
+            %emitters,
+            "Transaction included.",
+        );
+    }
+
+    /// Executes the transactions of a block a second time, against the latest state the block is
+    /// built on, and reports the keys whose values differ between the state updates of both runs.
+    ///
+    /// Returns the reported differences, none if the block couldn't be replayed.
+    fn check_determinism(
+        &self,
+        block_env: &BlockEnv,
+        execution_output: &ExecutionOutput,
+    ) -> Vec<String> {
+        let block_number = block_env.number;
+
+        let Some(transactions) = determinism::executed_transactions(execution_output) else {
+            warn!(
+                target: LOG_TARGET,
+                block_number = %block_number,
+                "Skipping determinism check, a declared class is missing.",
+            );
+            return Vec::new();
+        };
+
+        let replayed = match self.replay_transactions(block_env, transactions) {
+            Ok(output) => output,
+            Err(error) => {
+                error!(
+                    target: LOG_TARGET,
+                    block_number = %block_number,
+                    %error,
+                    "Replaying block for the determinism check.",
+                );
+                return Vec::new();
+            }
+        };
+
+        let diffs = determinism::diff_state_updates(
+            &execution_output.states.state_updates,
+            &replayed.states.state_updates,
+        );
+
+        if diffs.is_empty() {
+            trace!(target: LOG_TARGET, block_number = %block_number, "Block is deterministic.");
+        } else {
+            error!(
+                target: LOG_TARGET,
+                block_number = %block_number,
+                diverging_keys = %diffs.len(),
+                "Block execution is not deterministic.",
+            );
+            for diff in &diffs {
+                error!(target: LOG_TARGET, block_number = %block_number, %diff, "Diverging state.");
+            }
+        }
+
+        diffs
+    }
+
+    fn replay_transactions(
+        &self,
+        block_env: &BlockEnv,
+        transactions: Vec<ExecutableTxWithHash>,
+    ) -> Result<ExecutionOutput, BlockProductionError> {
+        let state = StateFactoryProvider::latest(self.blockchain.provider())?;
+        let mut executor = self.executor_factory.with_state_and_block_env(state, block_env.clone());
+        executor.execute_transactions(transactions)?;
+        Ok(executor.take_execution_output()?)
+    }
+
     pub fn update_block_env(&self, block_env: &mut BlockEnv) {
         let mut context_gen = self.block_context_generator.write();
-        let current_timestamp_secs = get_current_timestamp().as_secs() as i64;
+        if let Some(clock) = context_gen.deterministic_clock.as_mut() {
+            // every block is one second after the previous one
+            *clock += 1;
+        }
 
-        let timestamp = if context_gen.next_block_start_time == 0 {
-            (current_timestamp_secs + context_gen.block_timestamp_offset) as u64
-        } else {
-            let timestamp = context_gen.next_block_start_time;
-            context_gen.block_timestamp_offset = timestamp as i64 - current_timestamp_secs;
-            context_gen.next_block_start_time = 0;
-            timestamp
-        };
+        let clock = Self::clock_timestamp_of(&context_gen);
+        let requested = Self::requested_timestamp(&context_gen, clock);
+        let timestamp = self.block_timestamp(&context_gen, clock, block_env.timestamp);
+        if timestamp != requested {
+            warn!(
+                target: LOG_TARGET,
+                %requested,
+                %timestamp,
+                "Block timestamp drifts too much from the clock, clamping it.",
+            );
+        }
+
+        // the following blocks keep the same offset from the clock
+        context_gen.block_timestamp_offset = timestamp as i64 - clock;
+        context_gen.next_block_start_time = 0;
 
         block_env.number += 1;
         block_env.timestamp = timestamp;
     }
 
+    /// Returns the current time of the clock the block timestamps are derived from, in seconds.
+    pub fn clock_timestamp(&self) -> u64 {
+        let context_gen = self.block_context_generator.read();
+        match context_gen.deterministic_clock {
+            // the clock only advances when a block is produced
+            Some(clock) => clock + 1,
+            None => Self::clock_timestamp_of(&context_gen) as u64,
+        }
+    }
+
+    /// Returns the timestamp the next block would have if it was produced now.
+    pub fn next_block_timestamp(&self) -> u64 {
+        let context_gen = self.block_context_generator.read();
+        let clock = match context_gen.deterministic_clock {
+            Some(clock) => (clock + 1) as i64,
+            None => Self::clock_timestamp_of(&context_gen),
+        };
+
+        let provider = self.blockchain.provider();
+        let latest = BlockNumberProvider::latest_number(provider).ok();
+        let header = latest.and_then(|number| provider.header(number.into()).ok().flatten());
+        let latest_timestamp = header.map_or(0, |header| header.timestamp);
+
+        self.block_timestamp(&context_gen, clock, latest_timestamp)
+    }
+
+    /// The timestamp of the block following the block of timestamp `latest_timestamp`, at the time
+    /// `clock`.
+    ///
+    /// The timestamps never go backwards, even if the clock does, eg. when the offset of the time
+    /// server changes, unless an earlier timestamp is explicitly requested and it is within the
+    /// maximum drift.
+    fn block_timestamp(
+        &self,
+        context_gen: &BlockContextGenerator,
+        clock: i64,
+        latest_timestamp: u64,
+    ) -> u64 {
+        let requested = Self::requested_timestamp(context_gen, clock);
+        let timestamp = self.clamp_to_max_drift(requested, clock);
+
+        let is_explicit = context_gen.next_block_start_time != 0 && timestamp == requested;
+        if is_explicit {
+            timestamp
+        } else {
+            timestamp.max(latest_timestamp)
+        }
+    }
+
+    fn clock_timestamp_of(context_gen: &BlockContextGenerator) -> i64 {
+        match context_gen.deterministic_clock {
+            Some(clock) => clock as i64,
+            None => get_current_timestamp().as_secs() as i64 + context_gen.source_offset,
+        }
+    }
+
+    /// The timestamp of the next block before the maximum drift is enforced, either set through
+    /// `dev_setNextBlockTimestamp` or offset from the `clock`.
+    fn requested_timestamp(context_gen: &BlockContextGenerator, clock: i64) -> u64 {
+        if context_gen.next_block_start_time == 0 {
+            (clock + context_gen.block_timestamp_offset).max(0) as u64
+        } else {
+            context_gen.next_block_start_time
+        }
+    }
+
+    fn clamp_to_max_drift(&self, timestamp: u64, clock: i64) -> u64 {
+        match self.config.max_timestamp_drift {
+            Some(max_drift) => {
+                let min = (clock - max_drift as i64).max(0) as u64;
+                let max = (clock + max_drift as i64).max(0) as u64;
+                timestamp.clamp(min, max)
+            }
+            None => timestamp,
+        }
+    }
+
     pub fn mine_empty_block(
         &self,
         block_env: &BlockEnv,
@@ -201,15 +486,30 @@ impl<EF: ExecutorFactory> Backend<EF> {
 #[cfg(test)]
 mod tests {
 
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU64, Ordering};
     use std::sync::Arc;
 
     use katana_executor::implementation::noop::NoopExecutorFactory;
+    use katana_executor::{
+        BlockExecutor, EntryPointCall, ExecutionError, ExecutionOutput, ExecutionResult,
+        ExecutorExt, ExecutorFactory, ExecutorResult, ResultAndStates, SimulationFlag,
+    };
+    use katana_primitives::block::ExecutableBlock;
+    use katana_primitives::contract::ContractAddress;
+    use katana_primitives::env::{BlockEnv, CfgEnv};
+    use katana_primitives::fee::TxFeeInfo;
     use katana_primitives::genesis::Genesis;
-    use katana_provider::traits::block::{BlockNumberProvider, BlockProvider};
+    use katana_primitives::transaction::{ExecutableTxWithHash, TxOrigin, TxWithHash};
+    use katana_primitives::FieldElement;
+    use katana_provider::traits::block::{BlockHashProvider, BlockNumberProvider, BlockProvider};
     use katana_provider::traits::env::BlockEnvProvider;
+    use katana_provider::traits::state::{StateFactoryProvider, StateProvider};
 
-    use super::Backend;
-    use crate::backend::config::{Environment, StarknetConfig};
+    use super::{Backend, MAX_TX_ORIGINS, MINED_BLOCK_LISTENER_BUFFER_SIZE};
+    use crate::backend::config::{Environment, StarknetConfig, TimestampSource};
+    use crate::constants::DEFAULT_SEQUENCER_ADDRESS;
+    use crate::utils::get_current_timestamp;
 
     fn create_test_starknet_config() -> StarknetConfig {
         let mut genesis = Genesis::default();
@@ -228,6 +528,109 @@ mod tests {
         Backend::new(Arc::new(NoopExecutorFactory::default()), create_test_starknet_config()).await
     }
 
+    /// Creates executors writing a storage value to the contract `0x1`, which is the number of
+    /// executors created before them unless `deterministic` is set.
+    #[derive(Debug, Default)]
+    struct CountingExecutorFactory {
+        inner: NoopExecutorFactory,
+        executors: AtomicU64,
+        deterministic: bool,
+    }
+
+    impl ExecutorFactory for CountingExecutorFactory {
+        fn with_state<'a, P>(&self, state: P) -> Box<dyn BlockExecutor<'a> + 'a>
+        where
+            P: StateProvider + 'a,
+        {
+            self.with_state_and_block_env(state, BlockEnv::default())
+        }
+
+        fn with_state_and_block_env<'a, P>(
+            &self,
+            state: P,
+            block_env: BlockEnv,
+        ) -> Box<dyn BlockExecutor<'a> + 'a>
+        where
+            P: StateProvider + 'a,
+        {
+            let count = self.executors.fetch_add(1, Ordering::SeqCst);
+            let value = if self.deterministic { 0 } else { count };
+            let inner = self.inner.with_state_and_block_env(state, block_env);
+            Box::new(CountingExecutor { inner, value: value.into() })
+        }
+
+        fn cfg(&self) -> &CfgEnv {
+            self.inner.cfg()
+        }
+    }
+
+    struct CountingExecutor<'a> {
+        inner: Box<dyn BlockExecutor<'a> + 'a>,
+        value: FieldElement,
+    }
+
+    impl ExecutorExt for CountingExecutor<'_> {
+        fn simulate(
+            &self,
+            transactions: Vec<ExecutableTxWithHash>,
+            flags: SimulationFlag,
+        ) -> Vec<ResultAndStates> {
+            self.inner.simulate(transactions, flags)
+        }
+
+        fn estimate_fee(
+            &self,
+            transactions: Vec<ExecutableTxWithHash>,
+            flags: SimulationFlag,
+        ) -> Vec<Result<TxFeeInfo, ExecutionError>> {
+            self.inner.estimate_fee(transactions, flags)
+        }
+
+        fn call(&self, call: EntryPointCall) -> Result<Vec<FieldElement>, ExecutionError> {
+            self.inner.call(call)
+        }
+    }
+
+    impl<'a> BlockExecutor<'a> for CountingExecutor<'a> {
+        fn execute_block(&mut self, block: ExecutableBlock) -> ExecutorResult<()> {
+            self.inner.execute_block(block)
+        }
+
+        fn execute_transactions(
+            &mut self,
+            transactions: Vec<ExecutableTxWithHash>,
+        ) -> ExecutorResult<()> {
+            self.inner.execute_transactions(transactions)
+        }
+
+        fn execute_transaction_if(
+            &mut self,
+            transaction: ExecutableTxWithHash,
+            keep: &dyn Fn(&ExecutionResult) -> bool,
+        ) -> ExecutorResult<bool> {
+            self.inner.execute_transaction_if(transaction, keep)
+        }
+
+        fn take_execution_output(&mut self) -> ExecutorResult<ExecutionOutput> {
+            let mut output = self.inner.take_execution_output()?;
+            let storage = HashMap::from([(FieldElement::ONE, self.value)]);
+            output.states.state_updates.storage_updates.insert(FieldElement::ONE.into(), storage);
+            Ok(output)
+        }
+
+        fn state(&self) -> Box<dyn StateProvider + 'a> {
+            self.inner.state()
+        }
+
+        fn transactions(&self) -> &[(TxWithHash, ExecutionResult)] {
+            self.inner.transactions()
+        }
+
+        fn block_env(&self) -> BlockEnv {
+            self.inner.block_env()
+        }
+    }
+
     #[tokio::test]
     async fn test_creating_blocks() {
         let backend = create_test_backend().await;
@@ -272,4 +675,176 @@ mod tests {
         assert_eq!(block1.header.number, 1);
         assert_eq!(block2.header.number, 2);
     }
+
+    #[tokio::test]
+    async fn test_deterministic_block_timestamps() {
+        async fn mine_two_blocks() -> Backend<NoopExecutorFactory> {
+            let mut config =
+                StarknetConfig { deterministic: true, ..create_test_starknet_config() };
+            config.genesis.sequencer_address = ContractAddress::from(FieldElement::from(0x1234u64));
+            let backend = Backend::new(Arc::new(NoopExecutorFactory::default()), config).await;
+
+            let provider = backend.blockchain.provider();
+            let mut block_env = provider.block_env_at(0u64.into()).unwrap().unwrap();
+            for _ in 0..2 {
+                backend.update_block_env(&mut block_env);
+                backend.mine_empty_block(&block_env).unwrap();
+            }
+
+            backend
+        }
+
+        let backend = mine_two_blocks().await;
+        let provider = backend.blockchain.provider();
+
+        let block1 = BlockProvider::block_by_number(provider, 1).unwrap().unwrap();
+        let block2 = BlockProvider::block_by_number(provider, 2).unwrap().unwrap();
+        assert_eq!(block1.header.timestamp, 1);
+        assert_eq!(block2.header.timestamp, 2);
+        assert_eq!(block2.header.sequencer_address, *DEFAULT_SEQUENCER_ADDRESS);
+
+        // the same blocks are produced on every run
+        let other = mine_two_blocks().await;
+        assert_eq!(
+            BlockHashProvider::latest_hash(provider).unwrap(),
+            BlockHashProvider::latest_hash(other.blockchain.provider()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_block_timestamps_are_clamped_to_max_drift() {
+        let mut config = StarknetConfig {
+            deterministic: true,
+            max_timestamp_drift: Some(10),
+            ..create_test_starknet_config()
+        };
+        config.genesis.timestamp = 100;
+        let backend = Backend::new(Arc::new(NoopExecutorFactory::default()), config).await;
+        let provider = backend.blockchain.provider();
+        let mut block_env = provider.block_env_at(0u64.into()).unwrap().unwrap();
+
+        backend.block_context_generator.write().next_block_start_time = 1000;
+        assert_eq!(backend.clock_timestamp(), 101);
+        assert_eq!(backend.next_block_timestamp(), 111);
+
+        let mine_block = |block_env: &mut BlockEnv| {
+            backend.update_block_env(block_env);
+            backend.mine_empty_block(block_env).unwrap();
+            block_env.timestamp
+        };
+
+        assert_eq!(mine_block(&mut block_env), 111);
+        // the following blocks keep the clamped offset from the clock
+        assert_eq!(mine_block(&mut block_env), 112);
+
+        // the timestamps don't go backwards when the offset is clamped to the earliest timestamp
+        backend.block_context_generator.write().block_timestamp_offset = -100;
+        assert_eq!(backend.next_block_timestamp(), 112);
+        assert_eq!(mine_block(&mut block_env), 112);
+
+        // the following blocks keep the offset of the latest timestamp
+        let timestamps = (0..3).map(|_| mine_block(&mut block_env)).collect::<Vec<_>>();
+        assert_eq!(timestamps, vec![113, 114, 115]);
+    }
+
+    #[tokio::test]
+    async fn test_block_timestamps_with_offset_source() {
+        let config = StarknetConfig {
+            timestamp_source: TimestampSource::Offset(-3600),
+            ..create_test_starknet_config()
+        };
+        let backend = Backend::new(Arc::new(NoopExecutorFactory::default()), config).await;
+        let provider = backend.blockchain.provider();
+        let mut block_env = provider.block_env_at(0u64.into()).unwrap().unwrap();
+
+        let now = get_current_timestamp().as_secs();
+        backend.update_block_env(&mut block_env);
+
+        assert!(block_env.timestamp >= now - 3600 && block_env.timestamp <= now - 3600 + 1);
+        assert!(backend.clock_timestamp() <= get_current_timestamp().as_secs() - 3600);
+    }
+
+    #[tokio::test]
+    async fn test_mined_block_listeners() {
+        let backend = create_test_backend().await;
+        let provider = backend.blockchain.provider();
+
+        let mut listener = backend.add_mined_block_listener();
+        let mut lagging = backend.add_mined_block_listener();
+        let dropped = backend.add_mined_block_listener();
+        drop(dropped);
+
+        let mut block_env = provider.block_env_at(0u64.into()).unwrap().unwrap();
+        let mut mine_blocks = |count: usize| {
+            for _ in 0..count {
+                backend.update_block_env(&mut block_env);
+                backend.mine_empty_block(&block_env).unwrap();
+            }
+        };
+
+        mine_blocks(2);
+        assert_eq!(listener.try_next().unwrap(), Some(1));
+        assert_eq!(listener.try_next().unwrap(), Some(2));
+        assert!(listener.try_next().is_err());
+        assert_eq!(backend.mined_block_listeners.read().len(), 2);
+
+        // the channel of a listener holds the buffer size plus one notification, one per sender
+        mine_blocks(MINED_BLOCK_LISTENER_BUFFER_SIZE);
+        while let Ok(Some(_)) = listener.try_next() {}
+        assert_eq!(backend.mined_block_listeners.read().len(), 1);
+
+        // the lagging listener receives the notifications sent until it lagged, then is closed
+        let received = std::iter::from_fn(|| lagging.try_next().unwrap()).collect::<Vec<_>>();
+        let expected = (1..=MINED_BLOCK_LISTENER_BUFFER_SIZE as u64 + 1).collect::<Vec<_>>();
+        assert_eq!(received, expected);
+    }
+
+    #[tokio::test]
+    async fn test_tx_origins_are_bounded() {
+        let backend = create_test_backend().await;
+
+        let origin = |i: usize| TxOrigin { client_id: Some(i.to_string()), ..Default::default() };
+        for i in 0..=MAX_TX_ORIGINS {
+            backend.record_tx_origin(FieldElement::from(i as u64), origin(i));
+        }
+
+        // the origin of the oldest transaction is dropped
+        assert_eq!(backend.tx_origin(&FieldElement::ZERO), None);
+        assert_eq!(backend.tx_origin(&FieldElement::ONE), Some(origin(1)));
+        let latest = FieldElement::from(MAX_TX_ORIGINS as u64);
+        assert_eq!(backend.tx_origin(&latest), Some(origin(MAX_TX_ORIGINS)));
+    }
+
+    #[tokio::test]
+    async fn test_nondeterministic_blocks_are_reported() {
+        for deterministic in [true, false] {
+            let factory = CountingExecutorFactory { deterministic, ..Default::default() };
+            let config =
+                StarknetConfig { check_determinism: true, ..create_test_starknet_config() };
+            let backend = Backend::new(Arc::new(factory), config).await;
+            let provider = backend.blockchain.provider();
+
+            let mut block_env = provider.block_env_at(0u64.into()).unwrap().unwrap();
+            backend.update_block_env(&mut block_env);
+
+            // the block is executed as by the block producer, then replayed by the check
+            let state = StateFactoryProvider::latest(provider).unwrap();
+            let mut executor =
+                backend.executor_factory.with_state_and_block_env(state, block_env.clone());
+            executor.execute_transactions(vec![]).unwrap();
+            let output = executor.take_execution_output().unwrap();
+
+            let diffs = backend.check_determinism(&block_env, &output);
+            if deterministic {
+                assert!(diffs.is_empty());
+            } else {
+                assert_eq!(diffs.len(), 1);
+                assert!(diffs[0].starts_with("storage of 0x1 at key 0x1: "), "{}", diffs[0]);
+            }
+
+            // the check only reports the divergences, the block is still mined
+            backend.do_mine_block(&block_env, output).unwrap();
+            assert_eq!(provider.latest_number().unwrap(), 1);
+        }
+    }
 }
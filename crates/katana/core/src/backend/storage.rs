@@ -2,11 +2,13 @@ use std::path::Path;
 
 use anyhow::{anyhow, Result};
 use katana_db::init_db;
+use katana_db::mdbx::DbEnv;
 use katana_primitives::block::{BlockHash, FinalityStatus, SealedBlockWithStatus};
 use katana_primitives::genesis::Genesis;
 use katana_primitives::state::StateUpdatesWithDeclaredClasses;
+use katana_primitives::version::Version;
 use katana_provider::providers::db::DbProvider;
-use katana_provider::traits::block::{BlockProvider, BlockWriter};
+use katana_provider::traits::block::{BlockProvider, BlockStatsProvider, BlockWriter};
 use katana_provider::traits::contract::ContractClassWriter;
 use katana_provider::traits::env::BlockEnvProvider;
 use katana_provider::traits::state::{StateFactoryProvider, StateRootProvider, StateWriter};
@@ -20,6 +22,7 @@ use katana_provider::BlockchainProvider;
 pub trait Database:
     BlockProvider
     + BlockWriter
+    + BlockStatsProvider
     + TransactionProvider
     + TransactionStatusProvider
     + TransactionTraceProvider
@@ -40,6 +43,7 @@ pub trait Database:
 impl<T> Database for T where
     T: BlockProvider
         + BlockWriter
+        + BlockStatsProvider
         + TransactionProvider
         + TransactionStatusProvider
         + TransactionTraceProvider
@@ -59,21 +63,31 @@ impl<T> Database for T where
 
 pub struct Blockchain {
     inner: BlockchainProvider<Box<dyn Database>>,
+    /// The database environment, if the blockchain is stored in a database.
+    db: Option<DbEnv>,
 }
 
 impl Blockchain {
     pub fn new(provider: impl Database) -> Self {
-        Self { inner: BlockchainProvider::new(Box::new(provider)) }
+        Self { inner: BlockchainProvider::new(Box::new(provider)), db: None }
     }
 
-    /// Creates a new [Blockchain] with the given [Database] implementation and genesis state.
-    pub fn new_with_genesis(provider: impl Database, genesis: &Genesis) -> Result<Self> {
+    /// Creates a new [Blockchain] with the given [Database] implementation and genesis state, the
+    /// genesis block being of the protocol `version`.
+    pub fn new_with_genesis(
+        provider: impl Database,
+        genesis: &Genesis,
+        version: Version,
+    ) -> Result<Self> {
+        let mut genesis_block = genesis.block();
+        genesis_block.header.version = version;
+
         // check whether the genesis block has been initialized
         let genesis_hash = provider.block_hash_by_num(genesis.number)?;
 
         match genesis_hash {
             Some(db_hash) => {
-                let genesis_hash = genesis.block().header.compute_hash();
+                let genesis_hash = genesis_block.header.compute_hash();
                 // check genesis should be the same
                 if db_hash == genesis_hash {
                     Ok(Self::new(provider))
@@ -85,7 +99,7 @@ impl Blockchain {
             }
 
             None => {
-                let block = genesis.block().seal();
+                let block = genesis_block.seal();
                 let block = SealedBlockWithStatus { block, status: FinalityStatus::AcceptedOnL1 };
                 let state_updates = genesis.state_updates();
 
@@ -95,10 +109,19 @@ impl Blockchain {
     }
 
     /// Creates a new [Blockchain] from a database at `path` and `genesis` state.
-    pub fn new_with_db(db_path: impl AsRef<Path>, genesis: &Genesis) -> Result<Self> {
+    pub fn new_with_db(
+        db_path: impl AsRef<Path>,
+        genesis: &Genesis,
+        version: Version,
+    ) -> Result<Self> {
         let db = init_db(db_path)?;
-        let provider = DbProvider::new(db);
-        Self::new_with_genesis(provider, genesis)
+        let provider = DbProvider::new(db.clone());
+        // unwind the latest block if the node was stopped while it was being written
+        provider.recover()?;
+
+        let mut blockchain = Self::new_with_genesis(provider, genesis, version)?;
+        blockchain.db = Some(db);
+        Ok(blockchain)
     }
 
     /// Builds a new blockchain with a forked block.
@@ -113,6 +136,11 @@ impl Blockchain {
         Self::new_with_block_and_state(provider, block, state_updates)
     }
 
+    /// Returns the database environment, if the blockchain is stored in a database.
+    pub fn db(&self) -> Option<&DbEnv> {
+        self.db.as_ref()
+    }
+
     pub fn provider(&self) -> &BlockchainProvider<Box<dyn Database>> {
         &self.inner
     }
@@ -147,6 +175,7 @@ mod tests {
     use katana_primitives::receipt::{InvokeTxReceipt, Receipt};
     use katana_primitives::state::StateUpdatesWithDeclaredClasses;
     use katana_primitives::transaction::{InvokeTx, Tx, TxWithHash};
+    use katana_primitives::version::CURRENT_STARKNET_VERSION;
     use katana_primitives::FieldElement;
     use katana_provider::providers::in_memory::InMemoryProvider;
     use katana_provider::traits::block::{
@@ -163,8 +192,9 @@ mod tests {
     fn blockchain_from_genesis_states() {
         let provider = InMemoryProvider::new();
 
-        let blockchain = Blockchain::new_with_genesis(provider, &Genesis::default())
-            .expect("failed to create blockchain from genesis block");
+        let blockchain =
+            Blockchain::new_with_genesis(provider, &Genesis::default(), CURRENT_STARKNET_VERSION)
+                .expect("failed to create blockchain from genesis block");
         let state = blockchain.provider().latest().expect("failed to get latest state");
 
         let latest_number = blockchain.provider().latest_number().unwrap();
@@ -245,7 +275,7 @@ mod tests {
         let genesis = Genesis::default();
 
         {
-            let blockchain = Blockchain::new_with_db(&db_path, &genesis)
+            let blockchain = Blockchain::new_with_db(&db_path, &genesis, CURRENT_STARKNET_VERSION)
                 .expect("Failed to create db-backed blockchain storage");
 
             blockchain
@@ -280,7 +310,7 @@ mod tests {
         // re open the db and assert the state is the same and not overwritten
 
         {
-            let blockchain = Blockchain::new_with_db(&db_path, &genesis)
+            let blockchain = Blockchain::new_with_db(&db_path, &genesis, CURRENT_STARKNET_VERSION)
                 .expect("Failed to create db-backed blockchain storage");
 
             // assert genesis state is correct
@@ -11,6 +11,13 @@ pub const DEFAULT_VALIDATE_MAX_STEPS: u32 = 1_000_000;
 
 pub const MAX_RECURSION_DEPTH: usize = 1000;
 
+/// The maximum number of elements of the calldata of a transaction, the limit of Starknet.
+pub const DEFAULT_MAX_CALLDATA_LEN: usize = 4000;
+
+/// The interval, in seconds, the database metrics are recorded at when metrics are enabled and no
+/// maintenance interval is set.
+pub const DEFAULT_DB_MAINTENANCE_INTERVAL: u64 = 60;
+
 lazy_static! {
 
     // Predefined contract addresses
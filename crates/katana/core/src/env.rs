@@ -10,6 +10,16 @@ use cairo_vm::vm::runners::builtin_runner::{
 pub struct BlockContextGenerator {
     pub block_timestamp_offset: i64,
     pub next_block_start_time: u64,
+    /// The timestamp of the latest block when block timestamps are generated deterministically
+    /// instead of being taken from the system clock. See [`StarknetConfig::deterministic`].
+    ///
+    /// [`StarknetConfig::deterministic`]: crate::backend::config::StarknetConfig::deterministic
+    pub deterministic_clock: Option<u64>,
+    /// The offset, in seconds, of the clock of the timestamp source from the system clock. See
+    /// [`StarknetConfig::timestamp_source`].
+    ///
+    /// [`StarknetConfig::timestamp_source`]: crate::backend::config::StarknetConfig::timestamp_source
+    pub source_offset: i64,
 }
 
 pub fn get_default_vm_resource_fee_cost() -> HashMap<String, f64> {
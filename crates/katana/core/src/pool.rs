@@ -1,31 +1,76 @@
 // Code adapted from Foundry's Anvil
 
+use std::collections::HashSet;
+use std::sync::Arc;
+
 use futures::channel::mpsc::{channel, Receiver, Sender};
-use katana_primitives::transaction::ExecutableTxWithHash;
+use katana_executor::ExecutionResult;
+use katana_primitives::transaction::{ExecutableTxWithHash, TxHash, TxWithHash};
 use parking_lot::RwLock;
 use starknet::core::types::FieldElement;
 use tracing::{info, warn};
 
+use crate::backend::labels::AddressLabels;
+use crate::service::metrics::PoolMetrics;
+
 pub(crate) const LOG_TARGET: &str = "txpool";
 
+/// A change of the transactions of the pool.
+#[derive(Debug, Clone)]
+pub enum PoolEvent {
+    /// A validated transaction entered the pool.
+    Added(ExecutableTxWithHash),
+    /// A transaction left the pool and was included in a block.
+    Removed(TxHash),
+    /// A transaction left the pool but failed to execute, and was dropped without being included
+    /// in a block.
+    Rejected(TxHash),
+}
+
 #[derive(Debug, Default)]
 pub struct TransactionPool {
     transactions: RwLock<Vec<ExecutableTxWithHash>>,
     transaction_listeners: RwLock<Vec<Sender<FieldElement>>>,
+    event_listeners: RwLock<Vec<Sender<PoolEvent>>>,
+    metrics: PoolMetrics,
+    /// The names of the addresses, rendered along with the senders in the logs.
+    address_labels: Arc<AddressLabels>,
 }
 
 impl TransactionPool {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn with_address_labels(address_labels: Arc<AddressLabels>) -> Self {
+        Self { address_labels, ..Default::default() }
+    }
 }
 
 impl TransactionPool {
     pub fn add_transaction(&self, transaction: ExecutableTxWithHash) {
         let hash = transaction.hash;
-        self.transactions.write().push(transaction);
+        let sender = match transaction.sender_address() {
+            Some(address) => self.address_labels.display(address),
+            None => "L1".to_string(),
+        };
 
-        info!(target: LOG_TARGET, hash = %format!("\"{hash:#x}\""), "Transaction received.");
+        let mut txs = self.transactions.write();
+        txs.push(transaction.clone());
+
+        self.metrics.transactions_received_total.increment(1);
+        self.metrics.pending_transactions.set(txs.len() as f64);
+        drop(txs);
+
+        // the listeners only learn about the transaction once it can be taken from the pool
+        self.notify_event_listeners(|| PoolEvent::Added(transaction.clone()));
+
+        info!(
+            target: LOG_TARGET,
+            hash = %format!("\"{hash:#x}\""),
+            %sender,
+            "Transaction received."
+        );
 
         // notify listeners of new tx added to the pool
         self.notify_listener(hash)
@@ -38,14 +83,70 @@ impl TransactionPool {
         rx
     }
 
-    /// Get all the transaction from the pool and clear it.
+    /// Returns a stream of the transactions entering and leaving the pool. The stream ends if the
+    /// listener doesn't keep up with the events, instead of silently missing some.
+    pub fn add_event_listener(&self) -> Receiver<PoolEvent> {
+        const EVENT_LISTENER_BUFFER_SIZE: usize = 2048;
+        let (tx, rx) = channel(EVENT_LISTENER_BUFFER_SIZE);
+        self.event_listeners.write().push(tx);
+        rx
+    }
+
+    /// Returns the transactions of the pool, in the order they were received, without taking them.
+    pub fn transactions(&self) -> Vec<ExecutableTxWithHash> {
+        self.transactions.read().clone()
+    }
+
+    /// Get all the transaction from the pool and clear it. The event listeners are notified once
+    /// the transactions are executed, with [Self::notify_executed].
     pub fn get_transactions(&self) -> Vec<ExecutableTxWithHash> {
         let mut txs = self.transactions.write();
         let transactions = txs.clone();
         txs.clear();
+        self.metrics.pending_transactions.set(0.0);
         transactions
     }
 
+    /// Takes the transactions with the given hashes from the pool, in the given order. Nothing is
+    /// taken if one of them isn't in the pool, whose hash is returned instead.
+    pub fn take_transactions(
+        &self,
+        hashes: &[TxHash],
+    ) -> Result<Vec<ExecutableTxWithHash>, TxHash> {
+        let mut txs = self.transactions.write();
+
+        let mut unique = HashSet::with_capacity(hashes.len());
+        let mut taken = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            // a transaction can only be included once
+            if !unique.insert(*hash) {
+                return Err(*hash);
+            }
+
+            let tx = txs.iter().find(|tx| tx.hash == *hash).ok_or(*hash)?;
+            taken.push(tx.clone());
+        }
+
+        txs.retain(|tx| !unique.contains(&tx.hash));
+        self.metrics.pending_transactions.set(txs.len() as f64);
+
+        Ok(taken)
+    }
+
+    /// Notifies the event listeners about the transactions taken from the pool once they are
+    /// executed, the successful ones being included in a block and the failed ones dropped.
+    pub fn notify_executed(&self, executed: &[(TxWithHash, ExecutionResult)]) {
+        for (tx, result) in executed {
+            self.notify_event_listeners(|| {
+                if result.is_success() {
+                    PoolEvent::Removed(tx.hash)
+                } else {
+                    PoolEvent::Rejected(tx.hash)
+                }
+            });
+        }
+    }
+
     /// notifies all listeners about the transaction
     fn notify_listener(&self, hash: FieldElement) {
         let mut listener = self.transaction_listeners.write();
@@ -72,4 +173,115 @@ impl TransactionPool {
             }
         }
     }
+
+    /// Notifies the event listeners, the event is only built if there are listeners. A listener
+    /// whose channel is full is dropped, ending its stream, so that it knows it missed events.
+    fn notify_event_listeners(&self, event: impl Fn() -> PoolEvent) {
+        let mut listeners = self.event_listeners.write();
+        listeners.retain_mut(|listener| match listener.try_send(event()) {
+            Ok(()) => true,
+            Err(e) if e.is_full() => {
+                warn!(target: LOG_TARGET, "Dropping pool event listener lagging behind.");
+                false
+            }
+            Err(_) => false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use katana_executor::ExecutionError;
+    use katana_primitives::fee::TxFeeInfo;
+    use katana_primitives::receipt::{InvokeTxReceipt, Receipt};
+    use katana_primitives::trace::TxExecInfo;
+    use katana_primitives::transaction::{ExecutableTx, InvokeTx, InvokeTxV1};
+    use starknet::core::types::PriceUnit;
+
+    use super::*;
+
+    fn tx(hash: u64) -> ExecutableTxWithHash {
+        ExecutableTxWithHash {
+            hash: hash.into(),
+            transaction: ExecutableTx::Invoke(InvokeTx::V1(InvokeTxV1::default())),
+        }
+    }
+
+    fn summary(events: &[PoolEvent]) -> Vec<(&'static str, TxHash)> {
+        events
+            .iter()
+            .map(|event| match event {
+                PoolEvent::Added(tx) => ("added", tx.hash),
+                PoolEvent::Removed(hash) => ("removed", *hash),
+                PoolEvent::Rejected(hash) => ("rejected", *hash),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn take_transactions_in_given_order() {
+        let pool = TransactionPool::new();
+        let mut events = pool.add_event_listener();
+
+        pool.add_transaction(tx(1));
+        pool.add_transaction(tx(2));
+        pool.add_transaction(tx(3));
+
+        // nothing is taken if a transaction is missing or repeated
+        assert_eq!(pool.take_transactions(&[3u64.into(), 4u64.into()]).unwrap_err(), 4u64.into());
+        assert_eq!(pool.take_transactions(&[3u64.into(), 3u64.into()]).unwrap_err(), 3u64.into());
+
+        let taken = pool.take_transactions(&[3u64.into(), 1u64.into()]).unwrap();
+        assert_eq!(taken.iter().map(|tx| tx.hash).collect::<Vec<_>>(), [3u64.into(), 1u64.into()]);
+
+        let remaining = pool.get_transactions();
+        assert_eq!(remaining.iter().map(|tx| tx.hash).collect::<Vec<_>>(), [2u64.into()]);
+
+        // the transactions leave the pool once executed
+        let receipt = Receipt::Invoke(InvokeTxReceipt {
+            actual_fee: 0,
+            events: Vec::new(),
+            messages_sent: Vec::new(),
+            revert_error: None,
+            execution_resources: Default::default(),
+        });
+        let fee = TxFeeInfo { gas_consumed: 0, gas_price: 0, overall_fee: 0, unit: PriceUnit::Wei };
+        let success = ExecutionResult::new_success(receipt, TxExecInfo::default(), fee);
+        let failure = ExecutionResult::new_failed(ExecutionError::EntryPointNotFound(0u8.into()));
+        pool.notify_executed(&[
+            (taken[0].clone().into(), success.clone()),
+            (taken[1].clone().into(), failure),
+            (remaining[0].clone().into(), success),
+        ]);
+
+        let events = events.by_ref().take(6).collect::<Vec<_>>().await;
+        assert_eq!(
+            summary(&events),
+            [
+                ("added", 1u64.into()),
+                ("added", 2u64.into()),
+                ("added", 3u64.into()),
+                ("removed", 3u64.into()),
+                ("rejected", 1u64.into()),
+                ("removed", 2u64.into()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn lagging_event_listener_is_dropped() {
+        let pool = TransactionPool::new();
+        let events = pool.add_event_listener();
+
+        // the channel holds its buffer plus one message per sender
+        for hash in 0..2050 {
+            pool.add_transaction(tx(hash));
+        }
+
+        // the events sent before the channel was full are received, and then the stream ends
+        let events = events.collect::<Vec<_>>().await;
+        assert_eq!(events.len(), 2049);
+        assert_eq!(pool.transactions().len(), 2050);
+    }
 }
@@ -1,17 +1,19 @@
 use std::cmp::Ordering;
 use std::iter::Skip;
+use std::path::PathBuf;
 use std::slice::Iter;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
-use katana_executor::ExecutorFactory;
+use katana_executor::{ExecutionResult, ExecutorFactory};
 use katana_primitives::block::{BlockHash, BlockHashOrNumber, BlockIdOrTag, BlockNumber};
 use katana_primitives::chain::ChainId;
 use katana_primitives::class::{ClassHash, CompiledClass};
 use katana_primitives::contract::{ContractAddress, Nonce, StorageKey, StorageValue};
 use katana_primitives::env::BlockEnv;
 use katana_primitives::event::{ContinuationToken, ContinuationTokenError};
-use katana_primitives::receipt::Event;
+use katana_primitives::receipt::{Event, MessageToL1};
 use katana_primitives::transaction::{ExecutableTxWithHash, TxHash, TxWithHash};
 use katana_primitives::FieldElement;
 use katana_provider::traits::block::{
@@ -25,33 +27,80 @@ use katana_provider::traits::transaction::{
 };
 use starknet::core::types::{BlockTag, EmittedEvent, EventsPage};
 
-use crate::backend::config::StarknetConfig;
+use crate::backend::config::{StarknetConfig, TimestampSource};
 use crate::backend::contract::StarknetContract;
 use crate::backend::Backend;
 use crate::pool::TransactionPool;
 use crate::sequencer_error::SequencerError;
-use crate::service::block_producer::{BlockProducer, BlockProducerMode, PendingExecutor};
+use crate::service::block_producer::{
+    execute_within_limits_after, BlockLimits, BlockProducer, BlockProducerMode,
+    BlockProductionError, MinedBlockOutcome, PendingExecutor, TxWithOutcome,
+};
+use crate::service::clock_sync::{ClockSyncTask, CLOCK_SYNC_INTERVAL};
+use crate::service::db_maintenance::{DbMaintenanceJob, DbPruningJob, DbSnapshotJob};
 #[cfg(feature = "messaging")]
 use crate::service::messaging::MessagingConfig;
 #[cfg(feature = "messaging")]
 use crate::service::messaging::MessagingService;
+use crate::service::scheduler::Scheduler;
+use crate::service::stats_rollup::StatsRollupJob;
 use crate::service::{NodeService, TransactionMiner};
 
 type SequencerResult<T> = Result<T, SequencerError>;
 
+/// The maximum number of transactions of the pool executed to preview the next block.
+pub const MAX_PREVIEWED_TRANSACTIONS: usize = 1000;
+
 #[derive(Debug, Default)]
 pub struct SequencerConfig {
     pub block_time: Option<u64>,
     pub no_mining: bool,
+    /// Leaves the transactions in the pool until an external block builder seals them in a block
+    /// with `katana_sealBlock`. Blocks are otherwise only mined on demand.
+    pub external_block_building: bool,
+    /// The resource limits of the produced blocks.
+    pub block_limits: BlockLimits,
+    /// The interval, in seconds, at which the database maintenance task reports the space used by
+    /// the database. The task is only run if the blockchain is stored in a database.
+    pub db_maintenance_interval: Option<u64>,
+    /// The snapshots of the database taken periodically. Only taken if the blockchain is stored in
+    /// a database.
+    pub db_snapshots: Option<DbSnapshotConfig>,
+    /// The periodic pruning of the state history of the database. The history is kept in full if
+    /// not set, and is only pruned if the blockchain is stored in a database.
+    pub db_pruning: Option<DbPruningConfig>,
+    /// The interval, in seconds, at which the activity of the mined blocks is rolled up into the
+    /// chain metrics.
+    pub stats_rollup_interval: Option<u64>,
     #[cfg(feature = "messaging")]
     pub messaging: Option<MessagingConfig>,
 }
 
+#[derive(Debug, Clone)]
+pub struct DbSnapshotConfig {
+    /// The interval, in seconds, at which a snapshot is taken.
+    pub interval: u64,
+    /// The directory the snapshots are written to.
+    pub dir: PathBuf,
+    /// The number of snapshots kept, the oldest ones being removed.
+    pub retain: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct DbPruningConfig {
+    /// The interval, in seconds, at which the state history is pruned.
+    pub interval: u64,
+    /// The number of latest blocks whose state history is kept.
+    pub retain: u64,
+}
+
 pub struct KatanaSequencer<EF: ExecutorFactory> {
     pub config: SequencerConfig,
     pub pool: Arc<TransactionPool>,
     pub backend: Arc<Backend<EF>>,
     pub block_producer: Arc<BlockProducer<EF>>,
+    /// Runs the recurring maintenance tasks of the node.
+    pub scheduler: Scheduler,
 }
 
 impl<EF: ExecutorFactory> KatanaSequencer<EF> {
@@ -63,19 +112,25 @@ impl<EF: ExecutorFactory> KatanaSequencer<EF> {
         let executor_factory = Arc::new(executor_factory);
         let backend = Arc::new(Backend::new(executor_factory.clone(), starknet_config).await);
 
-        let pool = Arc::new(TransactionPool::new());
-        let miner = TransactionMiner::new(pool.add_listener());
-
-        let block_producer = if config.block_time.is_some() || config.no_mining {
-            if let Some(interval) = config.block_time {
-                BlockProducer::interval(Arc::clone(&backend), interval)
-            } else {
-                BlockProducer::on_demand(Arc::clone(&backend))
-            }
+        let address_labels = Arc::clone(&backend.address_labels);
+        let pool = Arc::new(TransactionPool::with_address_labels(address_labels));
+        let miner = if config.external_block_building {
+            TransactionMiner::external()
         } else {
-            BlockProducer::instant(Arc::clone(&backend))
+            TransactionMiner::new(pool.add_listener())
         };
 
+        let block_producer =
+            if config.block_time.is_some() || config.no_mining || config.external_block_building {
+                if let Some(interval) = config.block_time {
+                    BlockProducer::interval(Arc::clone(&backend), Arc::clone(&pool), interval)
+                } else {
+                    BlockProducer::on_demand(Arc::clone(&backend), Arc::clone(&pool))
+                }
+            } else {
+                BlockProducer::instant(Arc::clone(&backend), Arc::clone(&pool))
+            };
+
         #[cfg(feature = "messaging")]
         let messaging = if let Some(config) = config.messaging.clone() {
             MessagingService::new(config, Arc::clone(&pool), Arc::clone(&backend)).await.ok()
@@ -83,7 +138,7 @@ impl<EF: ExecutorFactory> KatanaSequencer<EF> {
             None
         };
 
-        let block_producer = Arc::new(block_producer);
+        let block_producer = Arc::new(block_producer.with_limits(config.block_limits));
 
         tokio::spawn(NodeService::new(
             Arc::clone(&pool),
@@ -93,7 +148,36 @@ impl<EF: ExecutorFactory> KatanaSequencer<EF> {
             messaging,
         ));
 
-        Ok(Self { pool, config, backend, block_producer })
+        let scheduler = Scheduler::new();
+
+        if let Some(db) = backend.blockchain.db() {
+            if let Some(interval) = config.db_maintenance_interval {
+                let job = DbMaintenanceJob::new(db.clone());
+                scheduler.schedule(job, Duration::from_secs(interval));
+            }
+
+            if let Some(snapshots) = &config.db_snapshots {
+                let job = DbSnapshotJob::new(db.clone(), snapshots.dir.clone(), snapshots.retain);
+                scheduler.schedule(job, Duration::from_secs(snapshots.interval));
+            }
+
+            if let Some(pruning) = &config.db_pruning {
+                let job = DbPruningJob::new(db.clone(), pruning.retain);
+                scheduler.schedule(job, Duration::from_secs(pruning.interval));
+            }
+        }
+
+        if let Some(interval) = config.stats_rollup_interval {
+            let job = StatsRollupJob::new(Arc::clone(&backend))?;
+            scheduler.schedule(job, Duration::from_secs(interval));
+        }
+
+        if let TimestampSource::TimeServer(url) = &backend.config.timestamp_source {
+            let task = ClockSyncTask::new(Arc::clone(&backend), url.clone(), CLOCK_SYNC_INTERVAL);
+            tokio::spawn(task.run());
+        }
+
+        Ok(Self { pool, config, backend, block_producer, scheduler })
     }
 
     /// Returns the pending state if the sequencer is running in _interval_ mode. Otherwise `None`.
@@ -104,6 +188,17 @@ impl<EF: ExecutorFactory> KatanaSequencer<EF> {
         }
     }
 
+    /// Stops producing new blocks, and waits for the block that is being produced, if any, to be
+    /// committed to the database. The RPC server should be stopped beforehand so that no new
+    /// transactions are received.
+    pub async fn shutdown(&self) {
+        self.scheduler.stop();
+        self.block_producer.stop();
+        while self.block_producer.is_producing() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
     pub fn block_producer(&self) -> &BlockProducer<EF> {
         &self.block_producer
     }
@@ -176,6 +271,64 @@ impl<EF: ExecutorFactory> KatanaSequencer<EF> {
         self.pool.add_transaction(tx);
     }
 
+    /// Takes the transactions with the given hashes from the pool, and seals them in a new block
+    /// in the given order. The transactions are left in the pool if the block can't be produced
+    /// right now.
+    pub fn seal_block(
+        &self,
+        hashes: &[TxHash],
+    ) -> SequencerResult<(MinedBlockOutcome, Vec<TxWithOutcome>)> {
+        self.block_producer.seal_block(hashes).map_err(|e| match e {
+            BlockProductionError::TransactionNotInPool(hash) => {
+                SequencerError::TransactionNotInPool(hash)
+            }
+            e => e.into(),
+        })
+    }
+
+    /// Simulates the next block, as it would be produced from the current pending block and the
+    /// transactions of the pool within the block limits. Returns the environment of the block and
+    /// the transactions it would include, in order, with their execution results. Nothing is
+    /// committed.
+    ///
+    /// At most [MAX_PREVIEWED_TRANSACTIONS] transactions of the pool are executed.
+    pub fn preview_next_block(
+        &self,
+    ) -> SequencerResult<(BlockEnv, Vec<(TxWithHash, ExecutionResult)>)> {
+        let (block_env, state, mut transactions) = if let Some(exec) = self.pending_executor() {
+            let exec = exec.read();
+            let state: Box<dyn StateProvider> = Box::new(exec.state());
+            (exec.block_env(), state, exec.transactions().to_vec())
+        } else {
+            let provider = self.backend.blockchain.provider();
+            let num = provider.latest_number()?;
+            let mut block_env = provider
+                .block_env_at(num.into())?
+                .ok_or(SequencerError::BlockNotFound(BlockIdOrTag::Number(num)))?;
+
+            // the clock isn't moved, as the block is only previewed
+            block_env.number += 1;
+            block_env.timestamp = self.backend.next_block_timestamp();
+
+            (block_env, StateFactoryProvider::latest(provider)?, Vec::new())
+        };
+
+        let mut pool = self.pool.transactions();
+        pool.truncate(MAX_PREVIEWED_TRANSACTIONS);
+        if !pool.is_empty() {
+            // the transactions are executed as the block producer would, within the block limits,
+            // on an executor which is dropped along with its changes
+            let mut executor =
+                self.backend.executor_factory.with_state_and_block_env(state, block_env.clone());
+            let limits = &self.config.block_limits;
+            execute_within_limits_after(executor.as_mut(), &transactions, pool, limits)?;
+
+            transactions.extend(executor.transactions().iter().cloned());
+        }
+
+        Ok((block_env, transactions))
+    }
+
     pub fn block_hash_and_number(&self) -> SequencerResult<(BlockHash, BlockNumber)> {
         let provider = self.backend.blockchain.provider();
         let hash = BlockHashProvider::latest_hash(provider)?;
@@ -427,6 +580,37 @@ impl<EF: ExecutorFactory> KatanaSequencer<EF> {
         Ok(())
     }
 
+    /// Returns the messages sent to L1 by the transactions of the given block, along with the hash
+    /// of the transaction that sent them. Returns `None` if the block doesn't exist.
+    pub fn messages_to_l1(
+        &self,
+        block_num: BlockNumber,
+    ) -> SequencerResult<Option<Vec<(TxHash, MessageToL1)>>> {
+        let provider = self.backend.blockchain.provider();
+
+        let block_id = BlockHashOrNumber::Num(block_num);
+        let txs = TransactionProvider::transactions_by_block(provider, block_id)?;
+        let receipts = ReceiptProvider::receipts_by_block(provider, block_id)?;
+
+        let (Some(txs), Some(receipts)) = (txs, receipts) else {
+            return Ok(None);
+        };
+
+        let messages = txs
+            .into_iter()
+            .zip(receipts)
+            .flat_map(|(tx, receipt)| {
+                receipt
+                    .messages_sent()
+                    .iter()
+                    .map(move |msg| (tx.hash, msg.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        Ok(Some(messages))
+    }
+
     pub fn has_pending_transactions(&self) -> bool {
         if let Some(ref exec) = self.pending_executor() {
             !exec.read().transactions().is_empty()
@@ -1,8 +1,11 @@
 use katana_primitives::block::BlockIdOrTag;
 use katana_primitives::contract::ContractAddress;
 use katana_primitives::event::ContinuationTokenError;
+use katana_primitives::transaction::TxHash;
 use katana_provider::error::ProviderError;
 
+use crate::service::block_producer::BlockProductionError;
+
 #[derive(Debug, thiserror::Error)]
 pub enum SequencerError {
     #[error("Block {0:?} not found.")]
@@ -15,6 +18,10 @@ pub enum SequencerError {
     PendingTransactions,
     #[error(transparent)]
     ContinuationToken(#[from] ContinuationTokenError),
+    #[error("Transaction {0:#x} is not in the pool.")]
+    TransactionNotInPool(TxHash),
     #[error(transparent)]
     Provider(#[from] ProviderError),
+    #[error(transparent)]
+    BlockProduction(#[from] BlockProductionError),
 }
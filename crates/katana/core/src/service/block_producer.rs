@@ -1,19 +1,19 @@
 use std::collections::VecDeque;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant as StdInstant};
 
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::stream::{Stream, StreamExt};
 use futures::FutureExt;
 use katana_executor::{BlockExecutor, ExecutionResult, ExecutionStats, ExecutorFactory};
-use katana_primitives::block::{BlockHashOrNumber, ExecutableBlock, PartialHeader};
+use katana_primitives::block::BlockHashOrNumber;
 use katana_primitives::receipt::Receipt;
 use katana_primitives::trace::TxExecInfo;
-use katana_primitives::transaction::{ExecutableTxWithHash, TxWithHash};
-use katana_primitives::version::CURRENT_STARKNET_VERSION;
+use katana_primitives::transaction::{ExecutableTxWithHash, TxHash, TxWithHash};
 use katana_provider::error::ProviderError;
 use katana_provider::traits::block::{BlockHashProvider, BlockNumberProvider};
 use katana_provider::traits::env::BlockEnvProvider;
@@ -23,7 +23,9 @@ use parking_lot::RwLock;
 use tokio::time::{interval_at, Instant, Interval};
 use tracing::{error, info, trace, warn};
 
+use super::metrics::ExecutorMetrics;
 use crate::backend::Backend;
+use crate::pool::TransactionPool;
 
 pub(crate) const LOG_TARGET: &str = "miner";
 
@@ -40,6 +42,12 @@ pub enum BlockProductionError {
 
     #[error("transaction execution error: {0}")]
     TransactionExecutionError(#[from] katana_executor::ExecutorError),
+
+    #[error("a block is already being produced")]
+    BlockProductionOngoing,
+
+    #[error("transaction {0:#x} is not in the pool")]
+    TransactionNotInPool(TxHash),
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +56,33 @@ pub struct MinedBlockOutcome {
     pub stats: ExecutionStats,
 }
 
+/// The resource limits of a single block. Transactions that would cause a block to go over the
+/// limits are carried over to the next block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockLimits {
+    /// The maximum amount of L1 gas that the transactions in a block can use.
+    pub max_l1_gas: Option<u128>,
+    /// The maximum amount of Cairo steps that the transactions in a block can use.
+    pub max_cairo_steps: Option<u128>,
+}
+
+impl BlockLimits {
+    /// Returns `true` if there are no limits set.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_l1_gas.is_none() && self.max_cairo_steps.is_none()
+    }
+
+    /// Returns `true` if using `extra` on top of the `used` resources would go over the limits.
+    fn is_exceeded(&self, used: &ExecutionStats, extra: &ExecutionStats) -> bool {
+        let exceeds = |max: Option<u128>, used: u128, extra: u128| {
+            max.is_some_and(|max| used.saturating_add(extra) > max)
+        };
+
+        exceeds(self.max_l1_gas, used.l1_gas_used, extra.l1_gas_used)
+            || exceeds(self.max_cairo_steps, used.cairo_steps_used, extra.cairo_steps_used)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TxWithOutcome {
     pub tx: TxWithHash,
@@ -60,43 +95,67 @@ type ServiceFuture<T> = Pin<Box<dyn Future<Output = BlockingTaskResult<T>> + Sen
 type BlockProductionResult = Result<MinedBlockOutcome, BlockProductionError>;
 type BlockProductionFuture = ServiceFuture<BlockProductionResult>;
 
-type TxExecutionResult = Result<Vec<TxWithOutcome>, BlockProductionError>;
+/// The executed transactions, and the transactions that didn't fit in the current block.
+type TxExecutionResult =
+    Result<(Vec<TxWithOutcome>, Vec<ExecutableTxWithHash>), BlockProductionError>;
 type TxExecutionFuture = ServiceFuture<TxExecutionResult>;
 
-type BlockProductionWithTxnsFuture =
-    ServiceFuture<Result<(MinedBlockOutcome, Vec<TxWithOutcome>), BlockProductionError>>;
+type BlockProductionWithTxnsResult = Result<
+    (MinedBlockOutcome, Vec<TxWithOutcome>, Vec<ExecutableTxWithHash>),
+    BlockProductionError,
+>;
+type BlockProductionWithTxnsFuture = ServiceFuture<BlockProductionWithTxnsResult>;
 
 /// The type which responsible for block production.
 #[must_use = "BlockProducer does nothing unless polled"]
 pub struct BlockProducer<EF: ExecutorFactory> {
     /// The inner mode of mining.
     pub inner: RwLock<BlockProducerMode<EF>>,
+    /// Whether the block producer has been stopped. A stopped block producer finishes the block
+    /// that is being produced, if any, but doesn't start producing new ones.
+    is_stopped: AtomicBool,
 }
 
 impl<EF: ExecutorFactory> BlockProducer<EF> {
     /// Creates a block producer that mines a new block every `interval` milliseconds.
-    pub fn interval(backend: Arc<Backend<EF>>, interval: u64) -> Self {
+    pub fn interval(backend: Arc<Backend<EF>>, pool: Arc<TransactionPool>, interval: u64) -> Self {
         Self {
             inner: RwLock::new(BlockProducerMode::Interval(IntervalBlockProducer::new(
-                backend, interval,
+                backend, pool, interval,
             ))),
+            is_stopped: AtomicBool::new(false),
         }
     }
 
     /// Creates a new block producer that will only be possible to mine by calling the
     /// `katana_generateBlock` RPC method.
-    pub fn on_demand(backend: Arc<Backend<EF>>) -> Self {
+    pub fn on_demand(backend: Arc<Backend<EF>>, pool: Arc<TransactionPool>) -> Self {
         Self {
             inner: RwLock::new(BlockProducerMode::Interval(IntervalBlockProducer::new_no_mining(
-                backend,
+                backend, pool,
             ))),
+            is_stopped: AtomicBool::new(false),
         }
     }
 
     /// Creates a block producer that mines a new block as soon as there are ready transactions in
     /// the transactions pool.
-    pub fn instant(backend: Arc<Backend<EF>>) -> Self {
-        Self { inner: RwLock::new(BlockProducerMode::Instant(InstantBlockProducer::new(backend))) }
+    pub fn instant(backend: Arc<Backend<EF>>, pool: Arc<TransactionPool>) -> Self {
+        Self {
+            inner: RwLock::new(BlockProducerMode::Instant(InstantBlockProducer::new(
+                backend, pool,
+            ))),
+            is_stopped: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the resource limits of the blocks produced by this block producer.
+    pub fn with_limits(self, limits: BlockLimits) -> Self {
+        match &mut *self.inner.write() {
+            BlockProducerMode::Instant(producer) => producer.limits = limits,
+            BlockProducerMode::Interval(producer) => producer.limits = limits,
+        }
+        self
     }
 
     pub(super) fn queue(&self, transactions: Vec<ExecutableTxWithHash>) {
@@ -127,8 +186,47 @@ impl<EF: ExecutorFactory> BlockProducer<EF> {
         }
     }
 
+    /// Handler for the `katana_sealBlock` RPC method. Takes the transactions of a block built
+    /// externally from the pool, executes them in their order, and mines the block right away. The
+    /// block limits don't apply, the builder having already decided what fits in the block.
+    ///
+    /// The transactions are only taken from the pool once the block can be produced, nothing is
+    /// taken if a block is already being produced.
+    ///
+    /// Returns the outcome of the mined block and the transactions that executed successfully, the
+    /// failed ones being left out of the block.
+    pub fn seal_block(
+        &self,
+        hashes: &[TxHash],
+    ) -> Result<(MinedBlockOutcome, Vec<TxWithOutcome>), BlockProductionError> {
+        trace!(target: LOG_TARGET, count = %hashes.len(), "Sealing external block.");
+        let mut mode = self.inner.write();
+        match &mut *mode {
+            BlockProducerMode::Instant(producer) => producer.seal_block(hashes),
+            BlockProducerMode::Interval(producer) => producer.seal_block(hashes),
+        }
+    }
+
+    /// Stops the block producer. The block that is being produced, if any, is still committed to
+    /// the database but no new blocks will be produced.
+    pub fn stop(&self) {
+        self.is_stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if a block is currently being produced.
+    pub fn is_producing(&self) -> bool {
+        self.inner.read().is_producing()
+    }
+
     pub(super) fn poll_next(&self, cx: &mut Context<'_>) -> Poll<Option<BlockProductionResult>> {
         let mut mode = self.inner.write();
+
+        // once stopped, the producer is only polled to drive the ongoing block production to
+        // completion
+        if self.is_stopped.load(Ordering::Relaxed) && !mode.is_producing() {
+            return Poll::Pending;
+        }
+
         match &mut *mode {
             BlockProducerMode::Instant(producer) => producer.poll_next_unpin(cx),
             BlockProducerMode::Interval(producer) => producer.poll_next_unpin(cx),
@@ -154,6 +252,15 @@ pub enum BlockProducerMode<EF: ExecutorFactory> {
     Instant(InstantBlockProducer<EF>),
 }
 
+impl<EF: ExecutorFactory> BlockProducerMode<EF> {
+    fn is_producing(&self) -> bool {
+        match self {
+            BlockProducerMode::Interval(producer) => producer.ongoing_mining.is_some(),
+            BlockProducerMode::Instant(producer) => producer.block_mining.is_some(),
+        }
+    }
+}
+
 #[derive(Clone, derive_more::Deref)]
 pub struct PendingExecutor(#[deref] Arc<RwLock<Box<dyn BlockExecutor<'static>>>>);
 
@@ -176,10 +283,19 @@ pub struct IntervalBlockProducer<EF: ExecutorFactory> {
     ongoing_execution: Option<TxExecutionFuture>,
     /// Listeners notified when a new executed tx is added.
     tx_execution_listeners: RwLock<Vec<Sender<Vec<TxWithOutcome>>>>,
+    /// Metrics for recording the transactions execution.
+    metrics: ExecutorMetrics,
+    /// The resource limits of a block.
+    limits: BlockLimits,
+    /// Whether the current block has reached its resource limits. Queued transactions will only
+    /// be executed once a new block is opened.
+    is_block_full: bool,
+    /// The pool the transactions are taken from, notified once they are executed.
+    pool: Arc<TransactionPool>,
 }
 
 impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
-    pub fn new(backend: Arc<Backend<EF>>, interval: u64) -> Self {
+    pub fn new(backend: Arc<Backend<EF>>, pool: Arc<TransactionPool>, interval: u64) -> Self {
         let interval = {
             let duration = Duration::from_millis(interval);
             let mut interval = interval_at(Instant::now() + duration, duration);
@@ -208,13 +324,17 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
             interval: Some(interval),
             queued: VecDeque::default(),
             tx_execution_listeners: RwLock::new(vec![]),
+            metrics: ExecutorMetrics::default(),
+            limits: BlockLimits::default(),
+            is_block_full: false,
+            pool,
         }
     }
 
     /// Creates a new [IntervalBlockProducer] with no `interval`. This mode will not produce blocks
     /// for every fixed interval, although it will still execute all queued transactions and
     /// keep hold of the pending state.
-    pub fn new_no_mining(backend: Arc<Backend<EF>>) -> Self {
+    pub fn new_no_mining(backend: Arc<Backend<EF>>, pool: Arc<TransactionPool>) -> Self {
         let provider = backend.blockchain.provider();
 
         let latest_num = provider.latest_number().unwrap();
@@ -236,6 +356,10 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
             blocking_task_spawner,
             ongoing_execution: None,
             tx_execution_listeners: RwLock::new(vec![]),
+            metrics: ExecutorMetrics::default(),
+            limits: BlockLimits::default(),
+            is_block_full: false,
+            pool,
         }
     }
 
@@ -250,6 +374,7 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
                 info!(target: LOG_TARGET, block_number = %outcome.block_number, "Force mined block.");
                 self.executor =
                     self.create_new_executor_for_next_block().expect("fail to create executor");
+                self.is_block_full = false;
             }
             Err(e) => {
                 error!(target: LOG_TARGET, error = %e, "On force mine.");
@@ -257,6 +382,37 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
         }
     }
 
+    /// Executes the transactions in the pending block, which is then mined. The transactions
+    /// already executed in the pending block, if any, are kept before them.
+    fn seal_block(
+        &mut self,
+        hashes: &[TxHash],
+    ) -> Result<(MinedBlockOutcome, Vec<TxWithOutcome>), BlockProductionError> {
+        if self.ongoing_mining.is_some() || self.ongoing_execution.is_some() {
+            return Err(BlockProductionError::BlockProductionOngoing);
+        }
+
+        let transactions = self
+            .pool
+            .take_transactions(hashes)
+            .map_err(BlockProductionError::TransactionNotInPool)?;
+
+        let executor = self.executor.clone();
+        let pool = self.pool.clone();
+        let limits = BlockLimits::default();
+        let (txs, _) =
+            Self::execute_transactions(executor, pool, transactions, self.metrics.clone(), limits)?;
+        self.notify_listener(txs.clone());
+
+        let outcome = Self::do_mine(self.executor.clone(), self.backend.clone())?;
+        info!(target: LOG_TARGET, block_number = %outcome.block_number, "Sealed external block.");
+
+        self.executor = self.create_new_executor_for_next_block()?;
+        self.is_block_full = false;
+
+        Ok((outcome, txs))
+    }
+
     fn do_mine(
         executor: PendingExecutor,
         backend: Arc<Backend<EF>>,
@@ -276,20 +432,25 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
 
     fn execute_transactions(
         executor: PendingExecutor,
+        pool: Arc<TransactionPool>,
         transactions: Vec<ExecutableTxWithHash>,
+        metrics: ExecutorMetrics,
+        limits: BlockLimits,
     ) -> TxExecutionResult {
         let executor = &mut executor.write();
+        let executed_before = executor.transactions().len();
 
-        let new_txs_count = transactions.len();
-        executor.execute_transactions(transactions)?;
+        let started_at = StdInstant::now();
+        let remaining = execute_within_limits(executor.as_mut(), transactions, &limits)?;
+        metrics.execution_time_seconds.record(started_at.elapsed().as_secs_f64());
 
         let txs = executor.transactions();
-        let total_txs = txs.len();
+        let new_txs_count = txs.len() - executed_before;
 
         // Take only the results of the newly executed transactions
         let results = txs
             .iter()
-            .skip(total_txs - new_txs_count)
+            .skip(executed_before)
             .filter_map(|(tx, res)| match res {
                 ExecutionResult::Failed { .. } => None,
                 ExecutionResult::Success { receipt, trace, .. } => Some(TxWithOutcome {
@@ -300,7 +461,11 @@ impl<EF: ExecutorFactory> IntervalBlockProducer<EF> {
             })
             .collect::<Vec<TxWithOutcome>>();
 
-        Ok(results)
+        metrics.transactions_executed_total.increment(results.len() as u64);
+        metrics.transactions_failed_total.increment((new_txs_count - results.len()) as u64);
+        pool.notify_executed(&txs[executed_before..]);
+
+        Ok((results, remaining))
     }
 
     fn create_new_executor_for_next_block(&self) -> Result<PendingExecutor, BlockProductionError> {
@@ -370,16 +535,20 @@ impl<EF: ExecutorFactory> Stream for IntervalBlockProducer<EF> {
 
         loop {
             if !pin.queued.is_empty()
+                && !pin.is_block_full
                 && pin.ongoing_execution.is_none()
                 && pin.ongoing_mining.is_none()
             {
                 let executor = pin.executor.clone();
+                let pool = pin.pool.clone();
+                let metrics = pin.metrics.clone();
+                let limits = pin.limits;
                 let transactions: Vec<ExecutableTxWithHash> =
                     std::mem::take(&mut pin.queued).into_iter().flatten().collect();
 
-                let fut = pin
-                    .blocking_task_spawner
-                    .spawn(|| Self::execute_transactions(executor, transactions));
+                let fut = pin.blocking_task_spawner.spawn(move || {
+                    Self::execute_transactions(executor, pool, transactions, metrics, limits)
+                });
 
                 pin.ongoing_execution = Some(Box::pin(fut));
             }
@@ -388,7 +557,13 @@ impl<EF: ExecutorFactory> Stream for IntervalBlockProducer<EF> {
             if let Some(mut execution) = pin.ongoing_execution.take() {
                 if let Poll::Ready(executor) = execution.poll_unpin(cx) {
                     match executor {
-                        Ok(Ok(txs)) => {
+                        Ok(Ok((txs, remaining))) => {
+                            // carry over the transactions that didn't fit in the current block
+                            if !remaining.is_empty() {
+                                pin.is_block_full = true;
+                                pin.queued.push_front(remaining);
+                            }
+
                             pin.notify_listener(txs);
                             continue;
                         }
@@ -419,6 +594,7 @@ impl<EF: ExecutorFactory> Stream for IntervalBlockProducer<EF> {
                         match pin.create_new_executor_for_next_block() {
                             Ok(executor) => {
                                 pin.executor = executor;
+                                pin.is_block_full = false;
                             }
 
                             Err(e) => return Poll::Ready(Some(Err(e))),
@@ -453,32 +629,68 @@ pub struct InstantBlockProducer<EF: ExecutorFactory> {
     blocking_task_pool: BlockingTaskPool,
     /// Listeners notified when a new executed tx is added.
     tx_execution_listeners: RwLock<Vec<Sender<Vec<TxWithOutcome>>>>,
+    /// Metrics for recording the transactions execution.
+    metrics: ExecutorMetrics,
+    /// The resource limits of a block.
+    limits: BlockLimits,
+    /// The pool the transactions are taken from, notified once they are executed.
+    pool: Arc<TransactionPool>,
 }
 
 impl<EF: ExecutorFactory> InstantBlockProducer<EF> {
-    pub fn new(backend: Arc<Backend<EF>>) -> Self {
+    pub fn new(backend: Arc<Backend<EF>>, pool: Arc<TransactionPool>) -> Self {
         Self {
             backend,
             block_mining: None,
             queued: VecDeque::default(),
             blocking_task_pool: BlockingTaskPool::new().unwrap(),
             tx_execution_listeners: RwLock::new(vec![]),
+            metrics: ExecutorMetrics::default(),
+            limits: BlockLimits::default(),
+            pool,
         }
     }
 
     pub fn force_mine(&mut self) {
         if self.block_mining.is_none() {
             let txs = self.queued.pop_front().unwrap_or_default();
-            let _ = Self::do_mine(self.backend.clone(), txs);
+            let (backend, pool) = (self.backend.clone(), self.pool.clone());
+            let _ = Self::do_mine(backend, pool, txs, self.metrics.clone(), self.limits);
         } else {
             trace!(target: LOG_TARGET, "Unable to force mine while a mining process is running.")
         }
     }
 
+    fn seal_block(
+        &mut self,
+        hashes: &[TxHash],
+    ) -> Result<(MinedBlockOutcome, Vec<TxWithOutcome>), BlockProductionError> {
+        if self.block_mining.is_some() {
+            return Err(BlockProductionError::BlockProductionOngoing);
+        }
+
+        let transactions = self
+            .pool
+            .take_transactions(hashes)
+            .map_err(BlockProductionError::TransactionNotInPool)?;
+
+        let (backend, pool) = (self.backend.clone(), self.pool.clone());
+        let limits = BlockLimits::default();
+        let (outcome, txs, _) =
+            Self::do_mine(backend, pool, transactions, self.metrics.clone(), limits)?;
+        info!(target: LOG_TARGET, block_number = %outcome.block_number, "Sealed external block.");
+
+        self.notify_listener(txs.clone());
+        Ok((outcome, txs))
+    }
+
     fn do_mine(
         backend: Arc<Backend<EF>>,
+        pool: Arc<TransactionPool>,
         transactions: Vec<ExecutableTxWithHash>,
-    ) -> Result<(MinedBlockOutcome, Vec<TxWithOutcome>), BlockProductionError> {
+        metrics: ExecutorMetrics,
+        limits: BlockLimits,
+    ) -> BlockProductionWithTxnsResult {
         trace!(target: LOG_TARGET, "Creating new block.");
 
         let provider = backend.blockchain.provider();
@@ -487,43 +699,38 @@ impl<EF: ExecutorFactory> InstantBlockProducer<EF> {
         let mut block_env = provider.block_env_at(BlockHashOrNumber::Num(latest_num))?.unwrap();
         backend.update_block_env(&mut block_env);
 
-        let parent_hash = provider.latest_hash()?;
         let latest_state = provider.latest()?;
+        let mut executor =
+            backend.executor_factory.with_state_and_block_env(latest_state, block_env.clone());
 
-        let mut executor = backend.executor_factory.with_state(latest_state);
-
-        let block = ExecutableBlock {
-            body: transactions,
-            header: PartialHeader {
-                parent_hash,
-                number: block_env.number,
-                timestamp: block_env.timestamp,
-                gas_prices: block_env.l1_gas_prices.clone(),
-                sequencer_address: block_env.sequencer_address,
-                version: CURRENT_STARKNET_VERSION,
-            },
-        };
-
-        executor.execute_block(block)?;
+        let started_at = StdInstant::now();
+        let remaining = execute_within_limits(executor.as_mut(), transactions, &limits)?;
+        metrics.execution_time_seconds.record(started_at.elapsed().as_secs_f64());
 
         let execution_output = executor.take_execution_output()?;
-        let txs_outcomes = execution_output
-            .transactions
-            .clone()
-            .into_iter()
+        let executed = execution_output.transactions.clone();
+        let txs_outcomes = executed
+            .iter()
             .filter_map(|(tx, res)| match res {
-                ExecutionResult::Success { receipt, trace, .. } => {
-                    Some(TxWithOutcome { tx, receipt, exec_info: trace })
-                }
+                ExecutionResult::Success { receipt, trace, .. } => Some(TxWithOutcome {
+                    tx: tx.clone(),
+                    receipt: receipt.clone(),
+                    exec_info: trace.clone(),
+                }),
                 _ => None,
             })
             .collect::<Vec<_>>();
 
+        metrics.transactions_executed_total.increment(txs_outcomes.len() as u64);
+        let txs_count = executed.len();
+        metrics.transactions_failed_total.increment((txs_count - txs_outcomes.len()) as u64);
+
         let outcome = backend.do_mine_block(&block_env, execution_output)?;
+        pool.notify_executed(&executed);
 
         trace!(target: LOG_TARGET, block_number = %outcome.block_number, "Created new block.");
 
-        Ok((outcome, txs_outcomes))
+        Ok((outcome, txs_outcomes, remaining))
     }
 
     pub fn add_listener(&self) -> Receiver<Vec<TxWithOutcome>> {
@@ -570,9 +777,13 @@ impl<EF: ExecutorFactory> Stream for InstantBlockProducer<EF> {
         if !pin.queued.is_empty() && pin.block_mining.is_none() {
             let transactions = pin.queued.pop_front().expect("not empty; qed");
             let backend = pin.backend.clone();
+            let pool = pin.pool.clone();
+            let metrics = pin.metrics.clone();
+            let limits = pin.limits;
 
             pin.block_mining = Some(Box::pin(
-                pin.blocking_task_pool.spawn(|| Self::do_mine(backend, transactions)),
+                pin.blocking_task_pool
+                    .spawn(move || Self::do_mine(backend, pool, transactions, metrics, limits)),
             ));
         }
 
@@ -580,7 +791,12 @@ impl<EF: ExecutorFactory> Stream for InstantBlockProducer<EF> {
         if let Some(mut mining) = pin.block_mining.take() {
             if let Poll::Ready(outcome) = mining.poll_unpin(cx) {
                 match outcome {
-                    Ok(Ok((outcome, txs))) => {
+                    Ok(Ok((outcome, txs, remaining))) => {
+                        // carry over the transactions that didn't fit in the mined block
+                        if !remaining.is_empty() {
+                            pin.queued.push_front(remaining);
+                        }
+
                         pin.notify_listener(txs);
                         return Poll::Ready(Some(Ok(outcome)));
                     }
@@ -603,3 +819,260 @@ impl<EF: ExecutorFactory> Stream for InstantBlockProducer<EF> {
         Poll::Pending
     }
 }
+
+/// Executes the transactions one by one until the block limits are reached, and returns the
+/// transactions that didn't fit in the block.
+///
+/// The resources used by a transaction are only known after executing it, so a transaction that
+/// goes over the limits is executed and then left out of the block. A transaction is always kept
+/// if the block is still empty, otherwise a transaction that exceeds the limits would never be
+/// included.
+fn execute_within_limits<'a>(
+    executor: &mut (dyn BlockExecutor<'a> + 'a),
+    transactions: Vec<ExecutableTxWithHash>,
+    limits: &BlockLimits,
+) -> Result<Vec<ExecutableTxWithHash>, BlockProductionError> {
+    execute_within_limits_after(executor, &[], transactions, limits)
+}
+
+/// Executes the transactions like [execute_within_limits], the block also having the `included`
+/// transactions executed by another executor. Eg. to preview the next block on top of the pending
+/// block, without executing the transactions of the pending block again.
+pub(crate) fn execute_within_limits_after<'a>(
+    executor: &mut (dyn BlockExecutor<'a> + 'a),
+    included: &[(TxWithHash, ExecutionResult)],
+    transactions: Vec<ExecutableTxWithHash>,
+    limits: &BlockLimits,
+) -> Result<Vec<ExecutableTxWithHash>, BlockProductionError> {
+    if limits.is_unlimited() {
+        executor.execute_transactions(transactions)?;
+        return Ok(Vec::new());
+    }
+
+    // the resources used by the transactions that are already in the block
+    let mut used = ExecutionStats::default();
+    for (_, res) in included.iter().chain(executor.transactions()) {
+        add_execution_stats(&mut used, res);
+    }
+
+    let mut transactions = transactions.into_iter();
+
+    while let Some(tx) = transactions.next() {
+        let is_empty = included.is_empty() && executor.transactions().is_empty();
+        let fits = |res: &ExecutionResult| {
+            let mut needed = ExecutionStats::default();
+            add_execution_stats(&mut needed, res);
+            is_empty || !limits.is_exceeded(&used, &needed)
+        };
+
+        if !executor.execute_transaction_if(tx.clone(), &fits)? {
+            trace!(target: LOG_TARGET, tx = %format!("{:#x}", tx.hash), "Block limits reached.");
+            return Ok(std::iter::once(tx).chain(transactions).collect());
+        }
+
+        if let Some((_, res)) = executor.transactions().last() {
+            add_execution_stats(&mut used, res);
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// Adds the resources used by an executed transaction to `stats`.
+fn add_execution_stats(stats: &mut ExecutionStats, result: &ExecutionResult) {
+    if let ExecutionResult::Success { receipt, fee, .. } = result {
+        stats.l1_gas_used += fee.gas_consumed;
+        stats.cairo_steps_used += receipt.resources_used().steps as u128;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_executor::{
+        EntryPointCall, ExecutionError, ExecutionOutput, ExecutorExt, ExecutorResult,
+        ResultAndStates, SimulationFlag,
+    };
+    use katana_primitives::block::ExecutableBlock;
+    use katana_primitives::env::BlockEnv;
+    use katana_primitives::fee::TxFeeInfo;
+    use katana_primitives::receipt::{InvokeTxReceipt, TxExecutionResources};
+    use katana_primitives::transaction::{ExecutableTx, InvokeTx, InvokeTxV1};
+    use katana_primitives::FieldElement;
+    use katana_provider::traits::state::StateProvider;
+    use starknet::core::types::PriceUnit;
+
+    use super::*;
+
+    /// An executor whose every transaction uses as many Cairo steps as its nonce.
+    #[derive(Default)]
+    struct StepsExecutor {
+        transactions: Vec<(TxWithHash, ExecutionResult)>,
+    }
+
+    impl StepsExecutor {
+        fn execute(tx: &ExecutableTxWithHash) -> ExecutionResult {
+            let ExecutableTx::Invoke(InvokeTx::V1(invoke)) = &tx.transaction else {
+                unreachable!("only invoke transactions are used")
+            };
+
+            let steps = u64::try_from(invoke.nonce).unwrap();
+            let execution_resources = TxExecutionResources { steps, ..Default::default() };
+            let receipt = Receipt::Invoke(InvokeTxReceipt {
+                actual_fee: 0,
+                events: Vec::new(),
+                messages_sent: Vec::new(),
+                revert_error: None,
+                execution_resources,
+            });
+            let fee =
+                TxFeeInfo { gas_consumed: 0, gas_price: 0, overall_fee: 0, unit: PriceUnit::Wei };
+
+            ExecutionResult::new_success(receipt, TxExecInfo::default(), fee)
+        }
+    }
+
+    impl ExecutorExt for StepsExecutor {
+        fn simulate(
+            &self,
+            _: Vec<ExecutableTxWithHash>,
+            _: SimulationFlag,
+        ) -> Vec<ResultAndStates> {
+            unimplemented!()
+        }
+
+        fn estimate_fee(
+            &self,
+            _: Vec<ExecutableTxWithHash>,
+            _: SimulationFlag,
+        ) -> Vec<Result<TxFeeInfo, ExecutionError>> {
+            unimplemented!()
+        }
+
+        fn call(&self, _: EntryPointCall) -> Result<Vec<FieldElement>, ExecutionError> {
+            unimplemented!()
+        }
+    }
+
+    impl<'a> BlockExecutor<'a> for StepsExecutor {
+        fn execute_block(&mut self, _: ExecutableBlock) -> ExecutorResult<()> {
+            unimplemented!()
+        }
+
+        fn execute_transactions(&mut self, txs: Vec<ExecutableTxWithHash>) -> ExecutorResult<()> {
+            for tx in txs {
+                self.execute_transaction_if(tx, &|_| true)?;
+            }
+            Ok(())
+        }
+
+        fn execute_transaction_if(
+            &mut self,
+            tx: ExecutableTxWithHash,
+            keep: &dyn Fn(&ExecutionResult) -> bool,
+        ) -> ExecutorResult<bool> {
+            let result = Self::execute(&tx);
+            if !keep(&result) {
+                return Ok(false);
+            }
+            self.transactions.push((TxWithHash::from(&tx), result));
+            Ok(true)
+        }
+
+        fn take_execution_output(&mut self) -> ExecutorResult<ExecutionOutput> {
+            unimplemented!()
+        }
+
+        fn state(&self) -> Box<dyn StateProvider + 'a> {
+            unimplemented!()
+        }
+
+        fn transactions(&self) -> &[(TxWithHash, ExecutionResult)] {
+            &self.transactions
+        }
+
+        fn block_env(&self) -> BlockEnv {
+            BlockEnv::default()
+        }
+    }
+
+    fn tx_with_steps(steps: u64) -> ExecutableTxWithHash {
+        let invoke = InvokeTxV1 { nonce: steps.into(), ..Default::default() };
+        ExecutableTxWithHash::new(ExecutableTx::Invoke(InvokeTx::V1(invoke)))
+    }
+
+    fn executed_steps(executor: &StepsExecutor) -> Vec<u64> {
+        let steps = |(_, res): &(TxWithHash, ExecutionResult)| {
+            res.receipt().map(|receipt| receipt.resources_used().steps).unwrap()
+        };
+        executor.transactions().iter().map(steps).collect()
+    }
+
+    #[test]
+    fn transactions_over_the_limits_are_carried_over() {
+        let limits = BlockLimits { max_l1_gas: None, max_cairo_steps: Some(25) };
+        let txs = vec![tx_with_steps(10), tx_with_steps(10), tx_with_steps(10), tx_with_steps(1)];
+        let hashes = txs.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+
+        let mut executor = StepsExecutor::default();
+        let remaining = execute_within_limits(&mut executor, txs, &limits).unwrap();
+
+        // the third transaction doesn't fit, and is carried over along with the ones after it even
+        // if they would fit, to keep the order of the transactions
+        assert_eq!(executed_steps(&executor), vec![10, 10]);
+        let remaining_hashes = remaining.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+        assert_eq!(remaining_hashes, hashes[2..]);
+
+        // the transactions already in the block count towards the limits
+        let remaining = execute_within_limits(&mut executor, remaining, &limits).unwrap();
+        assert_eq!(executed_steps(&executor), vec![10, 10]);
+        assert_eq!(remaining.len(), 2);
+
+        // the carried over transactions make it in the next block
+        let mut next_block = StepsExecutor::default();
+        let remaining = execute_within_limits(&mut next_block, remaining, &limits).unwrap();
+        assert_eq!(executed_steps(&next_block), vec![10, 1]);
+        assert!(remaining.is_empty());
+    }
+
+    #[test]
+    fn transaction_over_the_limits_is_kept_in_an_empty_block() {
+        let limits = BlockLimits { max_l1_gas: None, max_cairo_steps: Some(5) };
+        let txs = vec![tx_with_steps(10), tx_with_steps(1)];
+
+        // a transaction going over the limits on its own would never be included otherwise
+        let mut executor = StepsExecutor::default();
+        let remaining = execute_within_limits(&mut executor, txs, &limits).unwrap();
+        assert_eq!(executed_steps(&executor), vec![10]);
+        assert_eq!(remaining.len(), 1);
+    }
+
+    #[test]
+    fn transactions_executed_elsewhere_count_towards_the_limits() {
+        let limits = BlockLimits { max_l1_gas: None, max_cairo_steps: Some(25) };
+
+        // eg. the pending block, when previewing the next block from a fresh executor
+        let mut pending = StepsExecutor::default();
+        execute_within_limits(&mut pending, vec![tx_with_steps(20)], &limits).unwrap();
+
+        let txs = vec![tx_with_steps(10), tx_with_steps(1)];
+        let mut executor = StepsExecutor::default();
+        let remaining =
+            execute_within_limits_after(&mut executor, pending.transactions(), txs, &limits)
+                .unwrap();
+
+        // the block isn't empty, so the transaction going over the limits isn't kept
+        assert!(executed_steps(&executor).is_empty());
+        assert_eq!(remaining.len(), 2);
+    }
+
+    #[test]
+    fn unlimited_block_executes_every_transaction() {
+        let limits = BlockLimits::default();
+        let txs = vec![tx_with_steps(1_000_000), tx_with_steps(1_000_000)];
+
+        let mut executor = StepsExecutor::default();
+        let remaining = execute_within_limits(&mut executor, txs, &limits).unwrap();
+        assert_eq!(executed_steps(&executor), vec![1_000_000, 1_000_000]);
+        assert!(remaining.is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+//! Synchronization of the block timestamps with a time server.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use katana_executor::ExecutorFactory;
+use tracing::{trace, warn};
+use url::Url;
+
+use crate::backend::Backend;
+use crate::utils::get_current_timestamp;
+
+pub(crate) const LOG_TARGET: &str = "clock::sync";
+
+/// The interval at which the time server is queried.
+pub const CLOCK_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A task which periodically measures the offset of a time server's clock from the system
+/// clock, and uses it as the clock of the block timestamps.
+///
+/// The time server is expected to answer a `GET` request with the current UNIX time in seconds,
+/// either as a plain integer or as a JSON object with a `unixtime` field. The transit time of the
+/// request is assumed to be symmetric, as with NTP.
+pub struct ClockSyncTask<EF: ExecutorFactory> {
+    backend: Arc<Backend<EF>>,
+    url: Url,
+    interval: Duration,
+    client: reqwest::Client,
+}
+
+impl<EF: ExecutorFactory> ClockSyncTask<EF> {
+    pub fn new(backend: Arc<Backend<EF>>, url: Url, interval: Duration) -> Self {
+        Self { backend, url, interval, client: reqwest::Client::new() }
+    }
+
+    /// Synchronizes the clock every `interval`, until the task is dropped. A failed
+    /// synchronization keeps the previously measured offset.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+            match self.measure_offset().await {
+                Ok(offset) => {
+                    trace!(target: LOG_TARGET, %offset, "Clock synchronized.");
+                    self.backend.block_context_generator.write().source_offset = offset;
+                }
+                Err(error) => {
+                    warn!(target: LOG_TARGET, %error, url = %self.url, "Synchronizing clock.");
+                }
+            }
+        }
+    }
+
+    async fn measure_offset(&self) -> Result<i64> {
+        let sent = get_current_timestamp();
+        let body =
+            self.client.get(self.url.clone()).send().await?.error_for_status()?.text().await?;
+        let received = get_current_timestamp();
+
+        let server_time = parse_unix_time(&body)?;
+        let local_time = ((sent + received) / 2).as_secs() as i64;
+        Ok(server_time - local_time)
+    }
+}
+
+fn parse_unix_time(body: &str) -> Result<i64> {
+    let body = body.trim();
+    if let Ok(time) = body.parse() {
+        return Ok(time);
+    }
+
+    let json: serde_json::Value =
+        serde_json::from_str(body).context("Parsing time server response")?;
+    json.get("unixtime")
+        .and_then(|time| time.as_i64())
+        .ok_or_else(|| anyhow!("Time server response has no `unixtime` field"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_unix_time;
+
+    #[test]
+    fn parse_time_server_response() {
+        assert_eq!(parse_unix_time("1700000000\n").unwrap(), 1700000000);
+        assert_eq!(
+            parse_unix_time(r#"{"unixtime":1700000000,"utc_offset":"+00:00"}"#).unwrap(),
+            1700000000
+        );
+        assert!(parse_unix_time(r#"{"datetime":"2023-11-14T22:13:20"}"#).is_err());
+        assert!(parse_unix_time("not a time").is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Background maintenance of the database of the node.
+//!
+//! The jobs report the space used by the database, take snapshots of it and prune its state
+//! history. The full history of the chain is kept unless the pruning job is scheduled, the blocks
+//! and their transactions being kept in any case.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use katana_db::mdbx::DbEnv;
+use katana_db::prune::prune_state_history;
+use katana_db::{init_db, tables};
+use tracing::{info, warn};
+
+use super::metrics::DbMetrics;
+use super::scheduler::ScheduledJob;
+use crate::utils::get_current_timestamp;
+
+pub(crate) const LOG_TARGET: &str = "db::maintenance";
+
+/// The prefix of the names of the snapshot directories, followed by the UNIX timestamp they were
+/// created at, and by a sequence number from the second snapshot created in the same second.
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+
+/// The directory a snapshot is written to, before being moved to its own directory once complete.
+const SNAPSHOT_IN_PROGRESS: &str = ".snapshot-in-progress";
+
+/// The share of free pages in the database file above which compacting it is advised.
+const COMPACTION_ADVISED_FREE_RATIO: f64 = 0.5;
+
+/// A job which reports the space used by the database, and how much of it can be reclaimed.
+///
+/// The free pages of the database are reused for new entries while the node is running, but
+/// giving them back to the file system requires an exclusive access to the database, with the
+/// node stopped, through `katana db compact`.
+pub struct DbMaintenanceJob {
+    db: DbEnv,
+    metrics: DbMetrics,
+}
+
+impl DbMaintenanceJob {
+    pub fn new(db: DbEnv) -> Self {
+        Self { db, metrics: DbMetrics::default() }
+    }
+}
+
+impl ScheduledJob for DbMaintenanceJob {
+    fn name(&self) -> &'static str {
+        "db_maintenance"
+    }
+
+    fn run(&mut self) -> anyhow::Result<()> {
+        self.db.record_table_metrics()?;
+
+        let used_size = self.db.used_size()?;
+        let free_size = self.db.free_size()?;
+        self.metrics.used_size_bytes.set(used_size as f64);
+        self.metrics.free_size_bytes.set(free_size as f64);
+
+        info!(target: LOG_TARGET, %used_size, %free_size, "Database space.");
+
+        if used_size > 0 && free_size as f64 / used_size as f64 > COMPACTION_ADVISED_FREE_RATIO {
+            warn!(
+                target: LOG_TARGET,
+                %used_size,
+                %free_size,
+                "Most of the database file is free pages, run `katana db compact` with the node \
+                 stopped to reclaim them.",
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// A job which prunes the state history of the database, so that only the state of the `retain`
+/// latest blocks can be queried.
+///
+/// The pruned changes free pages which are reused for the new entries, the database file only
+/// shrinking once compacted.
+pub struct DbPruningJob {
+    db: DbEnv,
+    retain: u64,
+}
+
+impl DbPruningJob {
+    pub fn new(db: DbEnv, retain: u64) -> Self {
+        Self { db, retain }
+    }
+
+    /// Returns the block the history is pruned at, the first one whose state is kept.
+    fn prune_block(&self) -> anyhow::Result<Option<u64>> {
+        let tx = self.db.tx()?;
+        let latest = tx.cursor::<tables::BlockHashes>()?.last()?.map(|(number, _)| number);
+        Ok(latest.map(|latest| (latest + 1).saturating_sub(self.retain)).filter(|block| *block > 0))
+    }
+}
+
+impl ScheduledJob for DbPruningJob {
+    fn name(&self) -> &'static str {
+        "db_pruning"
+    }
+
+    fn run(&mut self) -> anyhow::Result<()> {
+        let Some(block) = self.prune_block()? else { return Ok(()) };
+
+        let report = prune_state_history(&self.db, block)?;
+        if report.total() > 0 {
+            info!(
+                target: LOG_TARGET,
+                %block,
+                nonce_changes = %report.nonce_changes,
+                class_changes = %report.class_changes,
+                storage_changes = %report.storage_changes,
+                "Pruned state history."
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// A job which copies the database to a new directory of `dir`, and removes the oldest snapshots
+/// so that only the `retain` latest ones are kept.
+///
+/// A snapshot is a database of its own, which the node can be started from with `--db-dir`. A
+/// snapshot is written to a temporary directory first, so that the snapshot directories only ever
+/// contain complete databases.
+pub struct DbSnapshotJob {
+    db: DbEnv,
+    dir: PathBuf,
+    retain: usize,
+}
+
+impl DbSnapshotJob {
+    pub fn new(db: DbEnv, dir: PathBuf, retain: usize) -> Self {
+        Self { db, dir, retain }
+    }
+
+    /// Returns the snapshots of `dir`, from the oldest to the latest.
+    fn snapshots(&self) -> anyhow::Result<Vec<((u64, u64), PathBuf)>> {
+        let mut snapshots = fs::read_dir(&self.dir)
+            .with_context(|| format!("Reading snapshot directory {}", self.dir.display()))?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let name = path.file_name()?.to_str()?.strip_prefix(SNAPSHOT_PREFIX)?;
+                let (timestamp, sequence) = match name.split_once('-') {
+                    Some((timestamp, sequence)) => (timestamp, sequence.parse::<u64>().ok()?),
+                    None => (name, 0),
+                };
+                let timestamp = timestamp.parse::<u64>().ok()?;
+                path.is_dir().then_some(((timestamp, sequence), path))
+            })
+            .collect::<Vec<_>>();
+        snapshots.sort();
+        Ok(snapshots)
+    }
+
+    /// Returns the directory of a new snapshot created at `timestamp`.
+    fn snapshot_path(&self, timestamp: u64) -> PathBuf {
+        let mut path = self.dir.join(format!("{SNAPSHOT_PREFIX}{timestamp}"));
+        let mut sequence = 0;
+        while path.exists() {
+            sequence += 1;
+            path = self.dir.join(format!("{SNAPSHOT_PREFIX}{timestamp}-{sequence}"));
+        }
+        path
+    }
+
+    /// Writes a snapshot of the database to `tmp_path`, and moves it to `path` once complete.
+    fn create_snapshot(&self, tmp_path: &Path, path: &Path) -> anyhow::Result<()> {
+        let snapshot = init_db(tmp_path)?;
+        self.db.copy_tables_to(&snapshot)?;
+        drop(snapshot);
+
+        fs::rename(tmp_path, path)
+            .with_context(|| format!("Moving snapshot to {}", path.display()))?;
+        Ok(())
+    }
+}
+
+impl ScheduledJob for DbSnapshotJob {
+    fn name(&self) -> &'static str {
+        "db_snapshot"
+    }
+
+    fn run(&mut self) -> anyhow::Result<()> {
+        // left by a run interrupted by the node stopping
+        let tmp_path = self.dir.join(SNAPSHOT_IN_PROGRESS);
+        if tmp_path.exists() {
+            fs::remove_dir_all(&tmp_path)
+                .with_context(|| format!("Removing snapshot {}", tmp_path.display()))?;
+        }
+
+        let path = self.snapshot_path(get_current_timestamp().as_secs());
+        if let Err(error) = self.create_snapshot(&tmp_path, &path) {
+            let _ = fs::remove_dir_all(&tmp_path);
+            return Err(error);
+        }
+
+        info!(target: LOG_TARGET, path = %path.display(), "Created database snapshot.");
+
+        let snapshots = self.snapshots()?;
+        let outdated = snapshots.len().saturating_sub(self.retain);
+        for (_, path) in snapshots.into_iter().take(outdated) {
+            fs::remove_dir_all(&path)
+                .with_context(|| format!("Removing snapshot {}", path.display()))?;
+            info!(target: LOG_TARGET, path = %path.display(), "Removed outdated database snapshot.");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::FieldElement;
+
+    use super::*;
+
+    #[test]
+    fn prune_block_keeps_retained_blocks() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let db = init_db(db_dir.path()).unwrap();
+
+        let job = DbPruningJob::new(db.clone(), 3);
+        assert_eq!(job.prune_block().unwrap(), None);
+
+        db.update(|tx| {
+            for number in 0..=4u64 {
+                tx.put::<tables::BlockHashes>(number, FieldElement::from(number)).unwrap();
+            }
+        })
+        .unwrap();
+
+        // the blocks 2, 3 and 4 are retained
+        assert_eq!(job.prune_block().unwrap(), Some(2));
+        assert_eq!(DbPruningJob::new(db.clone(), 5).prune_block().unwrap(), None);
+        assert_eq!(DbPruningJob::new(db, 10).prune_block().unwrap(), None);
+    }
+
+    #[test]
+    fn snapshots_are_pruned() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let snapshot_dir = tempfile::tempdir().unwrap();
+        let db = init_db(db_dir.path()).unwrap();
+
+        // snapshots left by previous runs, along with an unrelated directory
+        for name in ["snapshot-10", "snapshot-20", "backups"] {
+            fs::create_dir(snapshot_dir.path().join(name)).unwrap();
+        }
+
+        // a snapshot interrupted by the node stopping
+        fs::create_dir(snapshot_dir.path().join(SNAPSHOT_IN_PROGRESS)).unwrap();
+
+        let mut job = DbSnapshotJob::new(db, snapshot_dir.path().to_path_buf(), 2);
+        job.run().unwrap();
+
+        let snapshots = job.snapshots().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].1, snapshot_dir.path().join("snapshot-20"));
+        assert!(katana_db::open_db(&snapshots[1].1).is_ok());
+        assert!(snapshot_dir.path().join("backups").exists());
+        assert!(!snapshot_dir.path().join(SNAPSHOT_IN_PROGRESS).exists());
+
+        // the snapshots taken in the same second don't overwrite each other
+        fs::create_dir(job.snapshot_path(30)).unwrap();
+        assert_eq!(job.snapshot_path(30), snapshot_dir.path().join("snapshot-30-1"));
+        job.run().unwrap();
+        job.run().unwrap();
+        let snapshots = job.snapshots().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_ne!(snapshots[0].1, snapshots[1].1);
+        assert!(snapshots.iter().all(|(_, path)| katana_db::open_db(path).is_ok()));
+    }
+}
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use alloy_network::Ethereum;
-use alloy_primitives::{Address, LogData, U256};
+use alloy_primitives::{Address, LogData, B256, U256};
 use alloy_provider::{HttpProvider, Provider};
 use alloy_rpc_types::{BlockNumberOrTag, Filter, FilterBlockOption, FilterSet, Log, Topic};
 use alloy_sol_types::{sol, SolEvent};
@@ -40,6 +40,18 @@ sol! {
     }
 }
 
+sol! {
+    #[sol(rpc)]
+    contract StarknetMessagingEvents {
+        #[derive(Debug, PartialEq)]
+        event ConsumedMessageToL1(
+            uint256 indexed from_address,
+            address indexed to_address,
+            uint256[] payload
+        );
+    }
+}
+
 pub struct EthereumMessaging {
     provider: Arc<HttpProvider<Ethereum>>,
     messaging_contract_address: Address,
@@ -116,6 +128,40 @@ impl EthereumMessaging {
 
         Ok(block_to_logs)
     }
+
+    /// Fetches the hashes of the L2 to L1 messages consumed on the settlement chain in the given
+    /// block range, both blocks included.
+    pub async fn fetch_consumed_messages(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> MessengerResult<Vec<B256>> {
+        trace!(target: LOG_TARGET, from_block = ?from_block, to_block = ?to_block, "Fetching consumed messages.");
+
+        let filters = Filter {
+            block_option: FilterBlockOption::Range {
+                from_block: Some(BlockNumberOrTag::Number(from_block)),
+                to_block: Some(BlockNumberOrTag::Number(to_block)),
+            },
+            address: FilterSet::<Address>::from(self.messaging_contract_address),
+            topics: [
+                Topic::from(StarknetMessagingEvents::ConsumedMessageToL1::SIGNATURE_HASH),
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            ],
+        };
+
+        let hashes = self
+            .provider
+            .get_logs(&filters)
+            .await?
+            .into_iter()
+            .filter_map(|log| message_hash_from_consumed_log(log).ok())
+            .collect();
+
+        Ok(hashes)
+    }
 }
 
 #[async_trait]
@@ -196,6 +242,14 @@ impl Messenger for EthereumMessaging {
 
         Ok(hashes)
     }
+
+    async fn gather_consumed_messages(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> MessengerResult<Vec<B256>> {
+        self.fetch_consumed_messages(from_block, to_block).await
+    }
 }
 
 fn l1_handler_tx_from_log(log: Log, chain_id: ChainId) -> MessengerResult<L1HandlerTx> {
@@ -228,6 +282,20 @@ fn l1_handler_tx_from_log(log: Log, chain_id: ChainId) -> MessengerResult<L1Hand
     })
 }
 
+/// Returns the hash of the message consumed by the `ConsumedMessageToL1` event of the log.
+fn message_hash_from_consumed_log(log: Log) -> MessengerResult<B256> {
+    let log = alloy_primitives::Log::<LogData>::new(log.address, log.topics, log.data)
+        .ok_or(Error::GatherError)?;
+    let parsed_log = StarknetMessagingEvents::ConsumedMessageToL1::decode_log(&log, false)
+        .map_err(|_| Error::GatherError)?;
+
+    let from_address = felt_from_u256(parsed_log.from_address);
+    let to_address = felt_from_address(parsed_log.to_address);
+    let payload = parsed_log.payload.iter().copied().map(felt_from_u256).collect::<Vec<_>>();
+
+    Ok(compute_l1_message_hash(from_address, to_address, &payload))
+}
+
 /// With Ethereum, the messages are following the conventional starknet messaging.
 fn parse_messages(messages: &[MessageToL1]) -> Vec<U256> {
     messages
@@ -337,4 +405,32 @@ mod tests {
             .unwrap()
         );
     }
+
+    #[test]
+    fn consumed_message_hash_from_log() {
+        let from_address = selector!("from_address");
+        let to_address = "0x000000000000000000000000be3C44c09bc1a3566F3e1CA12e5AbA0fA4Ca72Be";
+
+        // Payload two values: [1, 2].
+        let payload_buf = hex::decode("0000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002").unwrap();
+
+        let log = Log {
+            address: Address::from_str("0xde29d060D45901Fb19ED6C6e959EB22d8626708e").unwrap(),
+            topics: vec![
+                StarknetMessagingEvents::ConsumedMessageToL1::SIGNATURE_HASH,
+                B256::from(from_address.to_bytes_be()),
+                B256::from_str(to_address).unwrap(),
+            ],
+            data: payload_buf.into(),
+            ..Default::default()
+        };
+
+        // the consumed message is the one sent from L2
+        let to_address = FieldElement::from_hex_be(to_address).unwrap();
+        let payload = vec![FieldElement::ONE, FieldElement::TWO];
+        let message = MessageToL1 { from_address: from_address.into(), to_address, payload };
+
+        let hash = message_hash_from_consumed_log(log).expect("bad log format");
+        assert_eq!(U256::from_be_bytes(hash.into()), parse_messages(&[message])[0]);
+    }
 }
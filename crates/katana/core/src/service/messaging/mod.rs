@@ -15,7 +15,9 @@
 //! be consumed on the latter (by manually sending a transaction on the settlement chain). The
 //! hashes are registered using a custom contract that mimics the verification of Starknet state
 //! updates on Ethereum, since the process of proving and verifying of state updates, and then
-//! posting in on the settlement layer are not yet present in Katana.
+//! posting in on the settlement layer are not yet present in Katana. With Ethereum as settlement
+//! chain, the consumption of the messages is watched as well, which is reported along with the
+//! messages by `katana_getMessagesToL1`.
 //!
 //! Katana also has a `starknet-messaging` feature, where an opiniated implementation of L2 <-> L3
 //! messaging is implemented using Starknet as settlement chain.
@@ -40,6 +42,7 @@ mod starknet;
 use std::path::Path;
 
 use ::starknet::providers::ProviderError as StarknetProviderError;
+use alloy_primitives::B256;
 use alloy_transport::TransportError;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -160,6 +163,18 @@ pub trait Messenger {
         &self,
         messages: &[MessageToL1],
     ) -> MessengerResult<Vec<Self::MessageHash>>;
+
+    /// Gathers the hashes of the messages sent to the settlement chain that have been consumed on
+    /// it, between the given blocks of the settlement chain, both included.
+    ///
+    /// Settlement chains on which the consumption of the messages isn't watched return none.
+    async fn gather_consumed_messages(
+        &self,
+        _from_block: u64,
+        _to_block: u64,
+    ) -> MessengerResult<Vec<B256>> {
+        Ok(Vec::new())
+    }
 }
 
 pub enum MessengerMode {
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
+use alloy_primitives::B256;
 use futures::{Future, FutureExt, Stream};
 use katana_executor::ExecutorFactory;
 use katana_primitives::block::BlockHashOrNumber;
@@ -86,6 +87,12 @@ impl<EF: ExecutorFactory> MessagingService<EF> {
                     inner.gather_messages(from_block, max_block, backend.chain_id).await?;
                 let txs_count = txs.len();
 
+                if from_block <= block_num {
+                    let consumed = inner.gather_consumed_messages(from_block, block_num).await?;
+                    let mut consumed_messages = backend.consumed_messages_to_l1.write();
+                    consumed.into_iter().for_each(|hash| consumed_messages.consume(hash));
+                }
+
                 txs.into_iter().for_each(|tx| {
                     let hash = tx.calculate_hash();
                     trace_l1_handler_tx_exec(hash, &tx);
@@ -131,9 +138,14 @@ impl<EF: ExecutorFactory> MessagingService<EF> {
         } else {
             match messenger.as_ref() {
                 MessengerMode::Ethereum(inner) => {
-                    let hashes = inner.send_messages(&messages).await.map(|hashes| {
-                        hashes.iter().map(|h| format!("{h:#x}")).collect::<Vec<_>>()
-                    })?;
+                    let hashes = inner.send_messages(&messages).await?;
+                    // the consumption of the messages is only watched on Ethereum
+                    backend.consumed_messages_to_l1.write().settle(
+                        block_num,
+                        hashes.iter().map(|h| B256::from(h.to_be_bytes::<32>())),
+                    );
+
+                    let hashes = hashes.iter().map(|h| format!("{h:#x}")).collect::<Vec<_>>();
                     trace_msg_to_l1_sent(&messages, &hashes);
                     Ok(Some((block_num, hashes.len())))
                 }
@@ -225,6 +237,7 @@ impl<EF: ExecutorFactory> Stream for MessagingService<EF> {
                     // +1 to move to the next local block to check messages to be
                     // sent on the settlement chain.
                     pin.send_from_block += 1;
+                    *pin.backend.messaging_settled_block.write() = Some(block_num);
                     return Poll::Ready(Some(MessagingOutcome::Send { block_num, msg_count }));
                 }
                 Poll::Ready(Err(e)) => {
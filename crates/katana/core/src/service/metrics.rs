@@ -1,5 +1,5 @@
 use dojo_metrics::Metrics;
-use metrics::Counter;
+use metrics::{Counter, Gauge, Histogram};
 
 pub(crate) struct ServiceMetrics {
     pub(crate) block_producer: BlockProducerMetrics,
@@ -12,4 +12,61 @@ pub(crate) struct BlockProducerMetrics {
     pub(crate) l1_gas_processed_total: Counter,
     /// The amount of Cairo steps processed in a block.
     pub(crate) cairo_steps_processed_total: Counter,
+    /// The number of blocks mined.
+    pub(crate) blocks_mined_total: Counter,
+}
+
+#[derive(Metrics, Clone)]
+#[metrics(scope = "executor")]
+pub(crate) struct ExecutorMetrics {
+    /// The number of transactions successfully executed.
+    pub(crate) transactions_executed_total: Counter,
+    /// The number of transactions that failed to execute.
+    pub(crate) transactions_failed_total: Counter,
+    /// The time it takes to execute a batch of transactions, in seconds.
+    pub(crate) execution_time_seconds: Histogram,
+}
+
+#[derive(Metrics)]
+#[metrics(scope = "db")]
+pub(crate) struct DbMetrics {
+    /// The size of the pages of the database file that are in use, in bytes.
+    pub(crate) used_size_bytes: Gauge,
+    /// The size of the free pages of the database file, reclaimable by compacting it, in bytes.
+    pub(crate) free_size_bytes: Gauge,
+}
+
+#[derive(Metrics)]
+#[metrics(scope = "txpool")]
+pub(crate) struct PoolMetrics {
+    /// The number of transactions received by the pool.
+    pub(crate) transactions_received_total: Counter,
+    /// The number of transactions in the pool waiting to be executed.
+    pub(crate) pending_transactions: Gauge,
+}
+
+#[derive(Metrics)]
+#[metrics(scope = "chain")]
+pub(crate) struct ChainStatsMetrics {
+    /// The number of blocks rolled up.
+    pub(crate) blocks_total: Counter,
+    /// The number of transactions of the blocks rolled up.
+    pub(crate) transactions_total: Counter,
+    /// The number of events emitted in the blocks rolled up.
+    pub(crate) events_total: Counter,
+    /// The number of classes declared in the blocks rolled up.
+    pub(crate) declared_classes_total: Counter,
+    /// The average number of transactions of the blocks rolled up by the latest rollup.
+    pub(crate) transactions_per_block: Gauge,
+}
+
+#[derive(Metrics)]
+#[metrics(scope = "scheduler")]
+pub(crate) struct ScheduledTaskMetrics {
+    /// The number of completed runs of the task.
+    pub(crate) runs_total: Counter,
+    /// The number of failed runs of the task.
+    pub(crate) failures_total: Counter,
+    /// The time it takes to run the task, in seconds.
+    pub(crate) run_duration_seconds: Histogram,
 }
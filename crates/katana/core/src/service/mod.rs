@@ -17,9 +17,13 @@ use self::metrics::{BlockProducerMetrics, ServiceMetrics};
 use crate::pool::TransactionPool;
 
 pub mod block_producer;
+pub mod clock_sync;
+pub mod db_maintenance;
 #[cfg(feature = "messaging")]
 pub mod messaging;
-mod metrics;
+pub(crate) mod metrics;
+pub mod scheduler;
+pub mod stats_rollup;
 
 #[cfg(feature = "messaging")]
 use self::messaging::{MessagingOutcome, MessagingService};
@@ -98,6 +102,7 @@ impl<EF: ExecutorFactory> Future for NodeService<EF> {
                         let steps_used = outcome.stats.cairo_steps_used;
                         metrics.l1_gas_processed_total.increment(gas_used as u64);
                         metrics.cairo_steps_processed_total.increment(steps_used as u64);
+                        metrics.blocks_mined_total.increment(1);
                     }
 
                     Err(err) => {
@@ -125,11 +130,20 @@ pub struct TransactionMiner {
     has_pending_txs: Option<bool>,
     /// Receives hashes of transactions that are ready from the pool
     rx: Fuse<Receiver<FieldElement>>,
+    /// Whether the transactions are left in the pool for an external block builder.
+    is_external: bool,
 }
 
 impl TransactionMiner {
     pub fn new(rx: Receiver<FieldElement>) -> Self {
-        Self { rx: rx.fuse(), has_pending_txs: None }
+        Self { rx: rx.fuse(), has_pending_txs: None, is_external: false }
+    }
+
+    /// Creates a miner which never takes the transactions from the pool, an external block
+    /// builder sealing them in blocks through `katana_sealBlock` instead.
+    pub fn external() -> Self {
+        let (_, rx) = futures::channel::mpsc::channel(0);
+        Self { rx: rx.fuse(), has_pending_txs: None, is_external: true }
     }
 
     fn poll(
@@ -137,6 +151,10 @@ impl TransactionMiner {
         pool: &Arc<TransactionPool>,
         cx: &mut Context<'_>,
     ) -> Poll<Vec<ExecutableTxWithHash>> {
+        if self.is_external {
+            return Poll::Pending;
+        }
+
         // drain the notification stream
         while let Poll::Ready(Some(_)) = Pin::new(&mut self.rx).poll_next(cx) {
             self.has_pending_txs = Some(true);
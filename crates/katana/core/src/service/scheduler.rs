@@ -0,0 +1,275 @@
+//! Recurring maintenance jobs run in the background of the node.
+//!
+//! Every job runs on a blocking thread at its own interval, delayed by a random jitter of up to a
+//! tenth of the interval so that the jobs scheduled at the same interval don't all run at once.
+//! A job runs again only once its previous run is over. The outcome of the runs of every job is
+//! recorded as metrics labeled with the job name, and is listed by `katana_listScheduledTasks`.
+//!
+//! A job which panics is not run anymore, its panic being listed as the error of its last run.
+//! Stopping the scheduler stops scheduling the jobs, the runs in progress completing on their
+//! blocking threads.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Mutex, RwLock};
+use rand::Rng;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use super::metrics::ScheduledTaskMetrics;
+use crate::utils::get_current_timestamp;
+
+pub(crate) const LOG_TARGET: &str = "scheduler";
+
+/// A maintenance job run by the [Scheduler].
+pub trait ScheduledJob: Send + 'static {
+    /// The name the job is listed and its metrics labeled with.
+    fn name(&self) -> &'static str;
+
+    /// Runs the job once, on a blocking thread.
+    fn run(&mut self) -> anyhow::Result<()>;
+}
+
+/// The runs of a scheduled job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledTask {
+    pub name: &'static str,
+    pub interval: Duration,
+    /// The number of completed runs, failed ones included.
+    pub runs: u64,
+    pub failures: u64,
+    /// The time the last run started at, as a UNIX timestamp in seconds.
+    pub last_run_at: Option<u64>,
+    pub last_run_duration: Option<Duration>,
+    /// The error of the last run, `None` if it succeeded.
+    pub last_error: Option<String>,
+    /// The time the next run is due at, as a UNIX timestamp in seconds.
+    pub next_run_at: Option<u64>,
+    /// Whether the job is running right now.
+    pub running: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    tasks: Arc<RwLock<Vec<ScheduledTask>>>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns a task running `job` every `interval`, the first run being after one interval.
+    pub fn schedule(&self, job: impl ScheduledJob, interval: Duration) {
+        let name = job.name();
+        let index = {
+            let mut tasks = self.tasks.write();
+            tasks.push(ScheduledTask {
+                name,
+                interval,
+                runs: 0,
+                failures: 0,
+                last_run_at: None,
+                last_run_duration: None,
+                last_error: None,
+                next_run_at: None,
+                running: false,
+            });
+            tasks.len() - 1
+        };
+
+        info!(target: LOG_TARGET, task = %name, interval = ?interval, "Scheduled task.");
+
+        let tasks = Arc::clone(&self.tasks);
+        let metrics = ScheduledTaskMetrics::new_with_labels(&[("task", name)]);
+        let handle = tokio::spawn(async move {
+            let mut job = job;
+            loop {
+                let delay = interval + jitter(interval);
+                tasks.write()[index].next_run_at = Some(now() + delay.as_secs());
+                tokio::time::sleep(delay).await;
+
+                tasks.write()[index].running = true;
+                let started_at = now();
+                let start = Instant::now();
+
+                let run = tokio::task::spawn_blocking(move || {
+                    let result = job.run();
+                    (job, result)
+                })
+                .await;
+
+                let duration = start.elapsed();
+                metrics.runs_total.increment(1);
+                metrics.run_duration_seconds.record(duration.as_secs_f64());
+
+                let (returned, result) = match run {
+                    Ok((returned, result)) => (Some(returned), result),
+                    Err(error) => {
+                        let panic = panic_message(error);
+                        (
+                            None,
+                            Err(anyhow::anyhow!("task panicked, it is not run anymore: {panic}")),
+                        )
+                    }
+                };
+                if let Err(error) = &result {
+                    metrics.failures_total.increment(1);
+                    warn!(target: LOG_TARGET, task = %name, %error, "Running scheduled task.");
+                }
+
+                let mut tasks = tasks.write();
+                let task = &mut tasks[index];
+                task.running = false;
+                task.runs += 1;
+                task.failures += u64::from(result.is_err());
+                task.last_run_at = Some(started_at);
+                task.last_run_duration = Some(duration);
+                task.last_error = result.err().map(|e| e.to_string());
+                match returned {
+                    Some(returned) => job = returned,
+                    None => {
+                        task.next_run_at = None;
+                        return;
+                    }
+                }
+            }
+        });
+        self.handles.lock().push(handle);
+    }
+
+    /// Stops scheduling the jobs. The runs in progress complete, but aren't recorded.
+    pub fn stop(&self) {
+        for handle in self.handles.lock().drain(..) {
+            handle.abort();
+        }
+
+        for task in self.tasks.write().iter_mut() {
+            task.running = false;
+            task.next_run_at = None;
+        }
+    }
+
+    /// Returns the scheduled tasks, in the order they were scheduled.
+    pub fn tasks(&self) -> Vec<ScheduledTask> {
+        self.tasks.read().clone()
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Returns the message a task panicked with, if it's a string.
+fn panic_message(error: tokio::task::JoinError) -> String {
+    if !error.is_panic() {
+        return error.to_string();
+    }
+
+    let panic = error.into_panic();
+    match panic.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => panic.downcast_ref::<String>().cloned().unwrap_or_else(|| "unknown".to_string()),
+    }
+}
+
+/// Returns a random delay of up to a tenth of `interval`.
+fn jitter(interval: Duration) -> Duration {
+    let max = interval.as_millis() as u64 / 10;
+    if max == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(rand::thread_rng().gen_range(0..max))
+}
+
+fn now() -> u64 {
+    get_current_timestamp().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+
+    struct CountingJob {
+        runs: Arc<AtomicU64>,
+    }
+
+    impl ScheduledJob for CountingJob {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn run(&mut self) -> anyhow::Result<()> {
+            // every other run fails
+            if self.runs.fetch_add(1, Ordering::SeqCst) % 2 == 1 {
+                anyhow::bail!("odd run");
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_scheduled_jobs() {
+        let scheduler = Scheduler::new();
+        let runs = Arc::new(AtomicU64::new(0));
+        scheduler.schedule(CountingJob { runs: Arc::clone(&runs) }, Duration::from_millis(20));
+
+        while runs.load(Ordering::SeqCst) < 4 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // the runs are recorded once the job returns
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let tasks = scheduler.tasks();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name, "counting");
+        assert!(tasks[0].runs >= 4);
+        assert_eq!(tasks[0].failures, tasks[0].runs / 2);
+        assert!(tasks[0].last_run_at.is_some());
+
+        assert!(jitter(Duration::from_secs(10)) < Duration::from_secs(1));
+        assert_eq!(jitter(Duration::from_millis(5)), Duration::ZERO);
+
+        scheduler.stop();
+        // a run already handed to a blocking thread still completes
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let stopped_at = runs.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), stopped_at, "stopped tasks aren't run anymore");
+        assert_eq!(scheduler.tasks()[0].next_run_at, None);
+    }
+
+    struct PanickingJob;
+
+    impl ScheduledJob for PanickingJob {
+        fn name(&self) -> &'static str {
+            "panicking"
+        }
+
+        fn run(&mut self) -> anyhow::Result<()> {
+            panic!("broken job")
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_panicked_jobs() {
+        let scheduler = Scheduler::new();
+        scheduler.schedule(PanickingJob, Duration::from_millis(10));
+
+        while scheduler.tasks()[0].runs == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let task = &scheduler.tasks()[0];
+        assert_eq!(task.failures, 1);
+        assert_eq!(task.next_run_at, None);
+        let error = task.last_error.as_deref().unwrap();
+        assert!(error.contains("panicked") && error.contains("broken job"), "{error}");
+    }
+}
@@ -0,0 +1,73 @@
+//! Aggregation of the activity of the mined blocks into chain-wide metrics.
+
+use std::sync::Arc;
+
+use katana_executor::ExecutorFactory;
+use katana_provider::traits::block::{BlockNumberProvider, BlockStatsProvider};
+use tracing::info;
+
+use super::metrics::ChainStatsMetrics;
+use super::scheduler::ScheduledJob;
+use crate::backend::Backend;
+
+pub(crate) const LOG_TARGET: &str = "stats::rollup";
+
+/// A job which adds the activity of the blocks mined since its previous run to the chain totals,
+/// and reports the activity of those blocks.
+///
+/// The totals are counted from the block the node started at, as the counters of the metrics
+/// start over with the node anyway.
+pub struct StatsRollupJob<EF: ExecutorFactory> {
+    backend: Arc<Backend<EF>>,
+    /// The first block not rolled up yet.
+    next_block: u64,
+    metrics: ChainStatsMetrics,
+}
+
+impl<EF: ExecutorFactory> StatsRollupJob<EF> {
+    pub fn new(backend: Arc<Backend<EF>>) -> anyhow::Result<Self> {
+        let latest = BlockNumberProvider::latest_number(backend.blockchain.provider())?;
+        Ok(Self { backend, next_block: latest + 1, metrics: ChainStatsMetrics::default() })
+    }
+}
+
+impl<EF: ExecutorFactory> ScheduledJob for StatsRollupJob<EF> {
+    fn name(&self) -> &'static str {
+        "stats_rollup"
+    }
+
+    fn run(&mut self) -> anyhow::Result<()> {
+        let provider = self.backend.blockchain.provider();
+        let end = BlockNumberProvider::latest_number(provider)? + 1;
+        let stats = BlockStatsProvider::block_stats_in_range(provider, self.next_block..end)?;
+
+        let blocks = stats.len() as u64;
+        let (mut txs, mut events, mut classes) = (0, 0, 0);
+        for (_, block) in &stats {
+            txs += block.tx_count;
+            events += block.events_count;
+            classes += block.declared_classes_count;
+        }
+
+        self.metrics.blocks_total.increment(blocks);
+        self.metrics.transactions_total.increment(txs);
+        self.metrics.events_total.increment(events);
+        self.metrics.declared_classes_total.increment(classes);
+        if blocks > 0 {
+            self.metrics.transactions_per_block.set(txs as f64 / blocks as f64);
+        }
+
+        info!(
+            target: LOG_TARGET,
+            from = %self.next_block,
+            to = %end.saturating_sub(1),
+            %blocks,
+            transactions = %txs,
+            %events,
+            "Rolled up block stats."
+        );
+
+        self.next_block = end;
+        Ok(())
+    }
+}
@@ -21,8 +21,11 @@ pub enum ExecutionError {
     #[error("invalid input: {input_descriptor}; {info}")]
     InvalidInput { input_descriptor: String, info: String },
 
-    #[error("execution failed due to recursion depth exceeded")]
-    RecursionDepthExceeded,
+    #[error(
+        "execution exceeded the maximum depth of {max_depth} nested calls, the called contracts \
+         most likely recurse infinitely"
+    )]
+    RecursionDepthExceeded { max_depth: usize },
 
     #[error("contract with address {0} is not deployed")]
     ContractNotDeployed(ContractAddress),
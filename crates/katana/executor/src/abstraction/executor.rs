@@ -40,6 +40,16 @@ pub trait BlockExecutor<'a>: ExecutorExt + Send + Sync {
         transactions: Vec<ExecutableTxWithHash>,
     ) -> ExecutorResult<()>;
 
+    /// Executes the given transaction, and keeps it only if `keep` returns `true` for the result of
+    /// its execution. A transaction that isn't kept leaves the state of the executor untouched.
+    ///
+    /// Returns whether the transaction was kept.
+    fn execute_transaction_if(
+        &mut self,
+        transaction: ExecutableTxWithHash,
+        keep: &dyn Fn(&ExecutionResult) -> bool,
+    ) -> ExecutorResult<bool>;
+
     /// Takes the output state of the executor.
     fn take_execution_output(&mut self) -> ExecutorResult<ExecutionOutput>;
 
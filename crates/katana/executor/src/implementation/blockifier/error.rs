@@ -8,44 +8,75 @@ use blockifier::transaction::errors::{
 use crate::implementation::blockifier::utils::to_address;
 use crate::ExecutionError;
 
-impl From<TransactionExecutionError> for ExecutionError {
-    fn from(error: TransactionExecutionError) -> Self {
+/// The message of the recursion depth error of blockifier, as found in the errors it only reports
+/// as strings, eg. the revert reasons.
+const BLOCKIFIER_RECURSION_DEPTH_ERROR: &str = "Recursion depth exceeded.";
+
+impl ExecutionError {
+    /// Converts the error of a transaction whose calls are nested at most `max_depth` deep, as
+    /// blockifier doesn't report the maximum depth in its recursion depth errors.
+    pub(super) fn from_transaction_error(
+        error: TransactionExecutionError,
+        max_depth: usize,
+    ) -> Self {
         match error {
             TransactionExecutionError::DeclareTransactionError { class_hash } => {
                 Self::ClassAlreadyDeclared(class_hash.0.into())
             }
             TransactionExecutionError::ValidateTransactionError(e) => {
-                Self::TransactionValidationFailed(Box::new(Self::from(e)))
+                Self::TransactionValidationFailed(Box::new(Self::from_entry_point_error(
+                    e, max_depth,
+                )))
             }
             TransactionExecutionError::StateError(e) => Self::from(e),
             TransactionExecutionError::TransactionPreValidationError(e) => Self::from(e),
+            TransactionExecutionError::TransactionFeeError(
+                TransactionFeeError::ExecuteFeeTransferError(e),
+            ) => Self::FeeTransferError(with_max_recursion_depth(&e.to_string(), max_depth)),
             TransactionExecutionError::TransactionFeeError(e) => Self::from(e),
-            TransactionExecutionError::ExecutionError(e) => Self::from(e),
+            TransactionExecutionError::ExecutionError(e) => {
+                Self::from_entry_point_error(e, max_depth)
+            }
             TransactionExecutionError::ContractConstructorExecutionFailed(e) => {
-                Self::ConstructorExecutionFailed(Box::new(Self::from(e)))
+                Self::ConstructorExecutionFailed(Box::new(Self::from_entry_point_error(
+                    e, max_depth,
+                )))
             }
-            e => Self::Other(e.to_string()),
+            e => Self::Other(with_max_recursion_depth(&e.to_string(), max_depth)),
         }
     }
-}
 
-impl From<EntryPointExecutionError> for ExecutionError {
-    fn from(error: EntryPointExecutionError) -> Self {
+    /// Converts the error of a call whose nested calls are at most `max_depth` deep.
+    pub(super) fn from_entry_point_error(
+        error: EntryPointExecutionError,
+        max_depth: usize,
+    ) -> Self {
         match error {
             EntryPointExecutionError::ExecutionFailed { error_data } => {
-                Self::ExecutionFailed { reason: format_panic_data(&error_data) }
+                let reason = format_panic_data(&error_data);
+                Self::ExecutionFailed { reason: with_max_recursion_depth(&reason, max_depth) }
             }
             EntryPointExecutionError::InvalidExecutionInput { input_descriptor, info } => {
                 Self::InvalidInput { input_descriptor, info }
             }
-            EntryPointExecutionError::RecursionDepthExceeded => Self::RecursionDepthExceeded,
+            EntryPointExecutionError::RecursionDepthExceeded => {
+                Self::RecursionDepthExceeded { max_depth }
+            }
             EntryPointExecutionError::StateError(e) => Self::from(e),
             EntryPointExecutionError::PreExecutionError(e) => Self::from(e),
-            e => Self::Other(e.to_string()),
+            // the errors of the nested calls are only found in the messages of the other errors
+            e => Self::Other(with_max_recursion_depth(&e.to_string(), max_depth)),
         }
     }
 }
 
+/// Replaces the recursion depth error of blockifier in the message of an error, eg. the revert
+/// reason of a transaction, so that it is explained the same way as the rejected transactions.
+pub(super) fn with_max_recursion_depth(message: &str, max_depth: usize) -> String {
+    let error = ExecutionError::RecursionDepthExceeded { max_depth };
+    message.replace(BLOCKIFIER_RECURSION_DEPTH_ERROR, &error.to_string())
+}
+
 impl From<PreExecutionError> for ExecutionError {
     fn from(error: PreExecutionError) -> Self {
         match error {
@@ -101,3 +132,27 @@ impl From<StateError> for ExecutionError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recursion_depth_errors() {
+        let error = TransactionExecutionError::ValidateTransactionError(
+            EntryPointExecutionError::RecursionDepthExceeded,
+        );
+        let error = ExecutionError::from_transaction_error(error, 100);
+        let ExecutionError::TransactionValidationFailed(error) = error else {
+            panic!("unexpected error: {error}");
+        };
+        assert!(matches!(*error, ExecutionError::RecursionDepthExceeded { max_depth: 100 }));
+
+        let reason = with_max_recursion_depth(
+            "Error in the called contract (0x1234):\nRecursion depth exceeded.",
+            100,
+        );
+        assert!(reason.starts_with("Error in the called contract (0x1234):\n"));
+        assert!(reason.contains("maximum depth of 100 nested calls"));
+    }
+}
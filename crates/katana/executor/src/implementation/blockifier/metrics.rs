@@ -0,0 +1,11 @@
+use dojo_metrics::Metrics;
+use metrics::Counter;
+
+#[derive(Metrics, Clone)]
+#[metrics(scope = "executor.class_cache")]
+pub(crate) struct ClassCacheMetrics {
+    /// The number of compiled classes found in the cache.
+    pub(crate) hits_total: Counter,
+    /// The number of compiled classes missing from the cache, and loaded from the state.
+    pub(crate) misses_total: Counter,
+}
@@ -1,4 +1,5 @@
 mod error;
+mod metrics;
 mod output;
 mod state;
 mod utils;
@@ -6,10 +7,10 @@ mod utils;
 use blockifier::block_context::BlockContext;
 use blockifier::state::cached_state::{self, MutRefState};
 use blockifier::state::state_api::StateReader;
-use blockifier::transaction::objects::TransactionExecutionInfo;
 use katana_primitives::block::{ExecutableBlock, GasPrices, PartialHeader};
 use katana_primitives::env::{BlockEnv, CfgEnv};
 use katana_primitives::fee::TxFeeInfo;
+use katana_primitives::trace::TxExecInfo;
 use katana_primitives::transaction::{ExecutableTx, ExecutableTxWithHash, TxWithHash};
 use katana_primitives::FieldElement;
 use katana_provider::traits::state::StateProvider;
@@ -17,7 +18,8 @@ use starknet_api::block::{BlockNumber, BlockTimestamp};
 use tracing::info;
 
 use self::output::receipt_from_exec_info;
-use self::state::CachedState;
+pub use self::state::ClassCache;
+use self::state::{CachedState, ClassCachedDb};
 use crate::{
     BlockExecutor, EntryPointCall, ExecutionError, ExecutionOutput, ExecutionResult,
     ExecutionStats, ExecutorExt, ExecutorFactory, ExecutorResult, ResultAndStates, SimulationFlag,
@@ -30,12 +32,18 @@ pub(crate) const LOG_TARGET: &str = "katana::executor::blockifier";
 pub struct BlockifierFactory {
     cfg: CfgEnv,
     flags: SimulationFlag,
+    classes: ClassCache,
 }
 
 impl BlockifierFactory {
     /// Create a new factory with the given configuration and simulation flags.
     pub fn new(cfg: CfgEnv, flags: SimulationFlag) -> Self {
-        Self { cfg, flags }
+        Self { cfg, flags, classes: ClassCache::new() }
+    }
+
+    /// Returns the cache of the compiled classes shared by the executors of this factory.
+    pub fn class_cache(&self) -> &ClassCache {
+        &self.classes
     }
 }
 
@@ -57,7 +65,8 @@ impl ExecutorFactory for BlockifierFactory {
     {
         let cfg_env = self.cfg.clone();
         let flags = self.flags.clone();
-        Box::new(StarknetVMProcessor::new(Box::new(state), block_env, cfg_env, flags))
+        let classes = self.classes.clone();
+        Box::new(StarknetVMProcessor::new(Box::new(state), block_env, cfg_env, flags, classes))
     }
 
     fn cfg(&self) -> &CfgEnv {
@@ -67,10 +76,11 @@ impl ExecutorFactory for BlockifierFactory {
 
 pub struct StarknetVMProcessor<'a> {
     block_context: BlockContext,
-    state: CachedState<StateProviderDb<'a>>,
+    state: CachedState<ClassCachedDb<'a>>,
     transactions: Vec<(TxWithHash, ExecutionResult)>,
     simulation_flags: SimulationFlag,
     stats: ExecutionStats,
+    classes: ClassCache,
 }
 
 impl<'a> StarknetVMProcessor<'a> {
@@ -79,11 +89,20 @@ impl<'a> StarknetVMProcessor<'a> {
         block_env: BlockEnv,
         cfg_env: CfgEnv,
         simulation_flags: SimulationFlag,
+        classes: ClassCache,
     ) -> Self {
         let transactions = Vec::new();
         let block_context = utils::block_context_from_envs(&block_env, &cfg_env);
-        let state = state::CachedState::new(StateProviderDb(state));
-        Self { block_context, state, transactions, simulation_flags, stats: Default::default() }
+        let state = ClassCachedDb::new(StateProviderDb(state), classes.clone());
+        let state = state::CachedState::new(state);
+        Self {
+            block_context,
+            state,
+            transactions,
+            simulation_flags,
+            stats: Default::default(),
+            classes,
+        }
     }
 
     fn fill_block_env_from_header(&mut self, header: &PartialHeader) {
@@ -109,7 +128,7 @@ impl<'a> StarknetVMProcessor<'a> {
     where
         F: FnMut(
             &mut dyn StateReader,
-            (TxWithHash, Result<(TransactionExecutionInfo, TxFeeInfo), ExecutionError>),
+            (TxWithHash, Result<(TxExecInfo, TxFeeInfo), ExecutionError>),
         ) -> T,
     {
         let block_context = &self.block_context;
@@ -125,70 +144,100 @@ impl<'a> StarknetVMProcessor<'a> {
 
         results
     }
-}
-
-impl<'a> BlockExecutor<'a> for StarknetVMProcessor<'a> {
-    fn execute_block(&mut self, block: ExecutableBlock) -> ExecutorResult<()> {
-        self.fill_block_env_from_header(&block.header);
-        self.execute_transactions(block.body)?;
-        Ok(())
-    }
 
-    fn execute_transactions(
+    /// Executes the transaction, and keeps it only if `keep` returns `true` for the result of its
+    /// execution. Returns whether the transaction was kept.
+    fn execute_transaction(
         &mut self,
-        transactions: Vec<ExecutableTxWithHash>,
-    ) -> ExecutorResult<()> {
+        exec_tx: ExecutableTxWithHash,
+        keep: &dyn Fn(&ExecutionResult) -> bool,
+    ) -> bool {
         let block_context = &self.block_context;
         let flags = &self.simulation_flags;
         let mut state = self.state.write();
 
-        for exec_tx in transactions {
-            // Collect class artifacts if its a declare tx
-            let class_decl_artifacts = if let ExecutableTx::Declare(tx) = exec_tx.as_ref() {
-                let class_hash = tx.class_hash();
-                Some((class_hash, tx.compiled_class.clone(), tx.sierra_class.clone()))
-            } else {
-                None
-            };
-
-            let tx = TxWithHash::from(&exec_tx);
-            let res = match utils::transact(exec_tx, &mut state.inner, block_context, flags) {
-                Ok((info, fee)) => {
-                    // get the trace and receipt from the execution info
-                    let trace = utils::to_exec_info(info);
-                    let receipt = receipt_from_exec_info(&tx, &trace);
+        // Collect class artifacts if its a declare tx
+        let class_decl_artifacts = if let ExecutableTx::Declare(tx) = exec_tx.as_ref() {
+            let class_hash = tx.class_hash();
+            Some((class_hash, tx.compiled_class.clone(), tx.sierra_class.clone()))
+        } else {
+            None
+        };
+
+        let tx = TxWithHash::from(&exec_tx);
+
+        // executed on its own state, which is only committed to the block state if the transaction
+        // is kept
+        let mut tx_state = cached_state::CachedState::create_transactional(&mut state.inner);
+        let res = match utils::transact(exec_tx, &mut tx_state, block_context, flags) {
+            // get the receipt from the execution info
+            Ok((trace, fee)) => {
+                let receipt = receipt_from_exec_info(&tx, &trace);
+                ExecutionResult::new_success(receipt, trace, fee)
+            }
+            Err(e) => ExecutionResult::new_failed(e),
+        };
 
-                    crate::utils::log_resources(&trace.actual_resources);
-                    crate::utils::log_events(receipt.events());
+        if !keep(&res) {
+            return false;
+        }
+        tx_state.commit();
 
-                    self.stats.l1_gas_used += fee.gas_consumed;
-                    self.stats.cairo_steps_used += receipt.resources_used().steps as u128;
+        match &res {
+            ExecutionResult::Success { receipt, trace, fee } => {
+                crate::utils::log_resources(&trace.actual_resources);
+                crate::utils::log_events(receipt.events());
 
-                    if let Some(reason) = receipt.revert_reason() {
-                        info!(target: LOG_TARGET, reason = %reason, "Transaction reverted.");
-                    }
+                self.stats.l1_gas_used += fee.gas_consumed;
+                self.stats.cairo_steps_used += receipt.resources_used().steps as u128;
 
-                    ExecutionResult::new_success(receipt, trace, fee)
+                if let Some(reason) = receipt.revert_reason() {
+                    info!(target: LOG_TARGET, reason = %reason, "Transaction reverted.");
                 }
-                Err(e) => {
-                    info!(target: LOG_TARGET, error = %e, "Executing transaction.");
-                    ExecutionResult::new_failed(e)
-                }
-            };
 
-            // if the tx succeed, inserts the class artifacts into the contract class cache
-            if res.is_success() {
+                // if the tx succeed, inserts the class artifacts into the contract class cache
                 if let Some((class_hash, compiled, sierra)) = class_decl_artifacts {
+                    // a class resolved before being declared again is stale
+                    self.classes.invalidate(class_hash);
                     state.declared_classes.insert(class_hash, (compiled, sierra));
                 }
             }
 
-            self.transactions.push((tx, res));
+            ExecutionResult::Failed { error } => {
+                info!(target: LOG_TARGET, error = %error, "Executing transaction.");
+            }
         }
 
+        self.transactions.push((tx, res));
+        true
+    }
+}
+
+impl<'a> BlockExecutor<'a> for StarknetVMProcessor<'a> {
+    fn execute_block(&mut self, block: ExecutableBlock) -> ExecutorResult<()> {
+        self.fill_block_env_from_header(&block.header);
+        self.execute_transactions(block.body)?;
+        Ok(())
+    }
+
+    fn execute_transactions(
+        &mut self,
+        transactions: Vec<ExecutableTxWithHash>,
+    ) -> ExecutorResult<()> {
+        for exec_tx in transactions {
+            self.execute_transaction(exec_tx, &|_| true);
+        }
         Ok(())
     }
 
+    fn execute_transaction_if(
+        &mut self,
+        transaction: ExecutableTxWithHash,
+        keep: &dyn Fn(&ExecutionResult) -> bool,
+    ) -> ExecutorResult<bool> {
+        Ok(self.execute_transaction(transaction, keep))
+    }
+
     fn take_execution_output(&mut self) -> ExecutorResult<ExecutionOutput> {
         let states = utils::state_update_from_cached_state(&self.state);
         let transactions = std::mem::take(&mut self.transactions);
@@ -225,8 +274,7 @@ impl ExecutorExt for StarknetVMProcessor<'_> {
     ) -> Vec<ResultAndStates> {
         self.simulate_with(transactions, &flags, |_, (tx, res)| {
             let result = match res {
-                Ok((info, fee)) => {
-                    let trace = utils::to_exec_info(info);
+                Ok((trace, fee)) => {
                     let receipt = receipt_from_exec_info(&tx, &trace);
                     ExecutionResult::new_success(receipt, trace, fee)
                 }
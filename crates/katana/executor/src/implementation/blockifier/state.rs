@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
 use blockifier::state::cached_state::{self, GlobalContractCache};
@@ -10,12 +11,14 @@ use katana_provider::error::ProviderError;
 use katana_provider::traits::contract::ContractClassProvider;
 use katana_provider::traits::state::StateProvider;
 use katana_provider::ProviderResult;
-use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use lru::LruCache;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use starknet_api::core::{ClassHash, CompiledClassHash, Nonce, PatriciaKey};
 use starknet_api::hash::StarkHash;
 use starknet_api::patricia_key;
 use starknet_api::state::StorageKey;
 
+use super::metrics::ClassCacheMetrics;
 use super::utils::{self};
 use crate::StateProviderDb;
 
@@ -90,6 +93,211 @@ impl<'a> StateReader for StateProviderDb<'a> {
     }
 }
 
+/// The default maximum number of classes held by a [ClassCache].
+pub const DEFAULT_CLASS_CACHE_CAPACITY: usize = 1024;
+
+type CachedClass = (
+    katana_primitives::class::CompiledClassHash,
+    blockifier::execution::contract_class::ContractClass,
+);
+
+/// A cache of the compiled classes resolved by the executors of a
+/// [BlockifierFactory](super::BlockifierFactory), shared across blocks so that a class is only
+/// deserialized and converted once. The least recently used classes are evicted once the cache is
+/// full.
+///
+/// The cache is shared by states which may not all have declared the same classes, so a cached
+/// class is only used if the state it is resolved from has declared it with the same compiled
+/// class hash. A cached class is also invalidated when the class is declared again.
+#[derive(Clone)]
+pub struct ClassCache {
+    classes: Arc<Mutex<LruCache<katana_primitives::class::ClassHash, CachedClass>>>,
+    metrics: ClassCacheMetrics,
+}
+
+impl std::fmt::Debug for ClassCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClassCache").field("len", &self.len()).finish_non_exhaustive()
+    }
+}
+
+impl Default for ClassCache {
+    fn default() -> Self {
+        let capacity = NonZeroUsize::new(DEFAULT_CLASS_CACHE_CAPACITY).expect("non zero capacity");
+        Self::with_capacity(capacity)
+    }
+}
+
+impl ClassCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a cache holding at most `capacity` classes.
+    pub fn with_capacity(capacity: NonZeroUsize) -> Self {
+        Self {
+            classes: Arc::new(Mutex::new(LruCache::new(capacity))),
+            metrics: ClassCacheMetrics::default(),
+        }
+    }
+
+    /// Removes the class `hash` from the cache.
+    pub fn invalidate(&self, hash: katana_primitives::class::ClassHash) {
+        self.classes.lock().pop(&hash);
+    }
+
+    /// Removes all the classes from the cache.
+    pub fn clear(&self) {
+        self.classes.lock().clear();
+    }
+
+    /// Returns the number of cached classes.
+    pub fn len(&self) -> usize {
+        self.classes.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.lock().is_empty()
+    }
+
+    /// Returns the class `hash` if it is cached with the compiled class hash `compiled_hash`.
+    fn get(
+        &self,
+        hash: katana_primitives::class::ClassHash,
+        compiled_hash: katana_primitives::class::CompiledClassHash,
+    ) -> Option<blockifier::execution::contract_class::ContractClass> {
+        let class = match self.classes.lock().get(&hash) {
+            Some((cached_hash, class)) if *cached_hash == compiled_hash => Some(class.clone()),
+            _ => None,
+        };
+        if class.is_some() {
+            self.metrics.hits_total.increment(1);
+        } else {
+            self.metrics.misses_total.increment(1);
+        }
+        class
+    }
+
+    fn insert(
+        &self,
+        hash: katana_primitives::class::ClassHash,
+        compiled_hash: katana_primitives::class::CompiledClassHash,
+        class: blockifier::execution::contract_class::ContractClass,
+    ) {
+        self.classes.lock().put(hash, (compiled_hash, class));
+    }
+}
+
+/// A [StateProviderDb] resolving the compiled classes through a [ClassCache].
+pub(super) struct ClassCachedDb<'a> {
+    db: StateProviderDb<'a>,
+    classes: ClassCache,
+}
+
+impl<'a> ClassCachedDb<'a> {
+    pub(super) fn new(db: StateProviderDb<'a>, classes: ClassCache) -> Self {
+        Self { db, classes }
+    }
+}
+
+impl<'a> ContractClassProvider for ClassCachedDb<'a> {
+    fn class(
+        &self,
+        hash: katana_primitives::class::ClassHash,
+    ) -> ProviderResult<Option<CompiledClass>> {
+        self.db.class(hash)
+    }
+
+    fn compiled_class_hash_of_class_hash(
+        &self,
+        hash: katana_primitives::class::ClassHash,
+    ) -> ProviderResult<Option<katana_primitives::class::CompiledClassHash>> {
+        self.db.compiled_class_hash_of_class_hash(hash)
+    }
+
+    fn sierra_class(
+        &self,
+        hash: katana_primitives::class::ClassHash,
+    ) -> ProviderResult<Option<FlattenedSierraClass>> {
+        self.db.sierra_class(hash)
+    }
+}
+
+impl<'a> StateProvider for ClassCachedDb<'a> {
+    fn class_hash_of_contract(
+        &self,
+        address: katana_primitives::contract::ContractAddress,
+    ) -> ProviderResult<Option<katana_primitives::class::ClassHash>> {
+        self.db.class_hash_of_contract(address)
+    }
+
+    fn nonce(
+        &self,
+        address: katana_primitives::contract::ContractAddress,
+    ) -> ProviderResult<Option<katana_primitives::contract::Nonce>> {
+        self.db.nonce(address)
+    }
+
+    fn storage(
+        &self,
+        address: katana_primitives::contract::ContractAddress,
+        storage_key: katana_primitives::contract::StorageKey,
+    ) -> ProviderResult<Option<katana_primitives::contract::StorageValue>> {
+        self.db.storage(address, storage_key)
+    }
+}
+
+impl<'a> StateReader for ClassCachedDb<'a> {
+    fn get_class_hash_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+    ) -> StateResult<ClassHash> {
+        self.db.get_class_hash_at(contract_address)
+    }
+
+    fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        self.db.get_compiled_class_hash(class_hash)
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: ClassHash,
+    ) -> StateResult<blockifier::execution::contract_class::ContractClass> {
+        // the classes without a compiled class hash in this state are never cached
+        let hash = class_hash.0.into();
+        let compiled_hash = self
+            .db
+            .compiled_class_hash_of_class_hash(hash)
+            .map_err(|e| StateError::StateReadError(e.to_string()))?;
+        let Some(compiled_hash) = compiled_hash else {
+            return self.db.get_compiled_contract_class(class_hash);
+        };
+
+        if let Some(class) = self.classes.get(hash, compiled_hash) {
+            return Ok(class);
+        }
+
+        let class = self.db.get_compiled_contract_class(class_hash)?;
+        self.classes.insert(hash, compiled_hash, class.clone());
+        Ok(class)
+    }
+
+    fn get_nonce_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+    ) -> StateResult<Nonce> {
+        self.db.get_nonce_at(contract_address)
+    }
+
+    fn get_storage_at(
+        &mut self,
+        contract_address: starknet_api::core::ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<starknet_api::hash::StarkFelt> {
+        self.db.get_storage_at(contract_address, key)
+    }
+}
+
 pub(super) struct CachedState<S: StateDb>(pub(super) Arc<RwLock<CachedStateInner<S>>>);
 
 impl<S: StateDb> Clone for CachedState<S> {
@@ -169,7 +377,11 @@ impl<S: StateDb> StateProvider for CachedState<S> {
         };
 
         let hash = hash.0.into();
-        if hash == FieldElement::ZERO { Ok(None) } else { Ok(Some(hash)) }
+        if hash == FieldElement::ZERO {
+            Ok(None)
+        } else {
+            Ok(Some(hash))
+        }
     }
 
     fn nonce(
@@ -294,6 +506,43 @@ mod tests {
         provider.latest().unwrap()
     }
 
+    #[test]
+    fn class_cache_is_only_used_by_states_declaring_the_class() -> anyhow::Result<()> {
+        let class_hash = felt!("0x123");
+        let classes = ClassCache::new();
+
+        let mut db = ClassCachedDb::new(StateProviderDb(state_provider()), classes.clone());
+        let class = db.get_compiled_contract_class(ClassHash(class_hash.into()))?;
+        assert_eq!(classes.len(), 1);
+
+        // another state declaring the class resolves it from the cache
+        let mut db = ClassCachedDb::new(StateProviderDb(state_provider()), classes.clone());
+        assert_eq!(db.get_compiled_contract_class(ClassHash(class_hash.into()))?, class);
+
+        // a state without the class doesn't resolve it, even though it is cached
+        let empty = InMemoryProvider::new().latest()?;
+        let mut db = ClassCachedDb::new(StateProviderDb(empty), classes.clone());
+        assert!(db.get_compiled_contract_class(ClassHash(class_hash.into())).is_err());
+
+        // nor does a state declaring it with another compiled class hash
+        assert!(classes.get(class_hash, felt!("0x457")).is_none());
+        assert_eq!(classes.get(class_hash, felt!("0x456")), Some(class.clone()));
+
+        classes.invalidate(class_hash);
+        assert!(classes.is_empty());
+
+        // the least recently used classes are evicted once the cache is full
+        let classes = ClassCache::with_capacity(NonZeroUsize::new(2).unwrap());
+        for hash in [felt!("0x1"), felt!("0x2"), felt!("0x3")] {
+            classes.insert(hash, hash, class.clone());
+        }
+        assert_eq!(classes.len(), 2);
+        assert!(classes.get(felt!("0x1"), felt!("0x1")).is_none());
+        assert!(classes.get(felt!("0x3"), felt!("0x3")).is_some());
+
+        Ok(())
+    }
+
     #[test]
     fn can_fetch_from_inner_state_provider() -> anyhow::Result<()> {
         let state = state_provider();
@@ -9,7 +9,7 @@ use blockifier::execution::entry_point::{
     CallEntryPoint, CallType, EntryPointExecutionContext, ExecutionResources,
 };
 use blockifier::fee::fee_utils::{calculate_tx_fee, calculate_tx_l1_gas_usages};
-use blockifier::state::cached_state::{self};
+use blockifier::state::cached_state::{self, CommitmentStateDiff};
 use blockifier::state::state_api::{State, StateReader};
 use blockifier::transaction::account_transaction::AccountTransaction;
 use blockifier::transaction::objects::{
@@ -50,6 +50,7 @@ use starknet_api::transaction::{
     ResourceBoundsMapping, Tip, TransactionHash, TransactionSignature, TransactionVersion,
 };
 
+use super::error::with_max_recursion_depth;
 use super::state::{CachedState, StateDb};
 use crate::abstraction::{EntryPointCall, SimulationFlag};
 use crate::ExecutionError;
@@ -59,21 +60,34 @@ pub(super) fn transact<S: StateReader>(
     state: &mut cached_state::CachedState<S>,
     block_context: &BlockContext,
     simulation_flags: &SimulationFlag,
-) -> Result<(TransactionExecutionInfo, TxFeeInfo), ExecutionError> {
+) -> Result<(TxExecInfo, TxFeeInfo), ExecutionError> {
     let validate = !simulation_flags.skip_validate;
     let charge_fee = !simulation_flags.skip_fee_transfer;
 
     let transaction = to_executor_tx(tx);
     let fee_type = get_fee_type_from_tx(&transaction);
 
-    let info = match transaction {
+    // executed on its own state, for the changes of the transaction to be told apart from the
+    // ones of the transactions executed before it
+    let mut tx_state = cached_state::CachedState::create_transactional(state);
+
+    let max_depth = block_context.block_info.max_recursion_depth;
+    let mut info = match transaction {
         Transaction::AccountTransaction(tx) => {
-            tx.execute(state, block_context, charge_fee, validate)
+            tx.execute(&mut tx_state, block_context, charge_fee, validate)
         }
         Transaction::L1HandlerTransaction(tx) => {
-            tx.execute(state, block_context, charge_fee, validate)
+            tx.execute(&mut tx_state, block_context, charge_fee, validate)
         }
-    }?;
+    }
+    .map_err(|e| ExecutionError::from_transaction_error(e, max_depth))?;
+
+    let state_diff = to_state_updates(tx_state.to_state_diff());
+    tx_state.commit();
+
+    if let Some(reason) = info.revert_error.as_mut() {
+        *reason = with_max_recursion_depth(reason, max_depth);
+    }
 
     // There are a few case where the `actual_fee` field of the transaction info is not set where
     // the fee is skipped and thus not charged for the transaction (e.g. when the
@@ -93,7 +107,7 @@ pub(super) fn transact<S: StateReader>(
     };
 
     let fee = TxFeeInfo { gas_consumed, gas_price, unit, overall_fee };
-    Ok((info, fee))
+    Ok((to_exec_info(info, state_diff), fee))
 }
 
 /// Perform a function call on a contract and retrieve the return values.
@@ -123,17 +137,23 @@ pub fn call<S: StateReader>(
     // The blockifier patch must be adjusted to modify this function to return
     // the limit we have into the block context without min applied:
     // https://github.com/starkware-libs/blockifier/blob/51b343fe38139a309a69b2482f4b484e8caa5edf/crates/blockifier/src/execution/entry_point.rs#L215
-    let res = call.execute(
-        &mut state,
-        &mut ExecutionResources::default(),
-        &mut EntryPointExecutionContext::new(
-            block_context,
-            &AccountTransactionContext::Deprecated(DeprecatedAccountTransactionContext::default()),
-            ExecutionMode::Execute,
-            limit_steps_by_resources,
+    let res = call
+        .execute(
+            &mut state,
+            &mut ExecutionResources::default(),
+            &mut EntryPointExecutionContext::new(
+                block_context,
+                &AccountTransactionContext::Deprecated(
+                    DeprecatedAccountTransactionContext::default(),
+                ),
+                ExecutionMode::Execute,
+                limit_steps_by_resources,
+            )
+            .expect("shouldn't fail"),
         )
-        .expect("shouldn't fail"),
-    )?;
+        .map_err(|e| {
+            ExecutionError::from_entry_point_error(e, block_context.block_info.max_recursion_depth)
+        })?;
 
     let retdata = res.execution.retdata.0;
     let retdata = retdata.into_iter().map(|f| f.into()).collect::<Vec<FieldElement>>();
@@ -379,6 +399,15 @@ pub(super) fn state_update_from_cached_state<S: StateDb>(
         declared_compiled_classes.insert(hash, class);
     }
 
+    StateUpdatesWithDeclaredClasses {
+        declared_sierra_classes,
+        declared_compiled_classes,
+        state_updates: to_state_updates(state_diff),
+    }
+}
+
+/// Converts the state diff of a cached state to the state updates it applies.
+fn to_state_updates(state_diff: CommitmentStateDiff) -> StateUpdates {
     let nonce_updates =
         state_diff
             .address_to_nonce
@@ -425,16 +454,7 @@ pub(super) fn state_update_from_cached_state<S: StateDb>(
                 katana_primitives::class::CompiledClassHash,
             >>();
 
-    StateUpdatesWithDeclaredClasses {
-        declared_sierra_classes,
-        declared_compiled_classes,
-        state_updates: StateUpdates {
-            nonce_updates,
-            storage_updates,
-            contract_updates,
-            declared_classes,
-        },
-    }
+    StateUpdates { nonce_updates, storage_updates, contract_updates, declared_classes }
 }
 
 fn to_api_da_mode(mode: starknet::core::types::DataAvailabilityMode) -> DataAvailabilityMode {
@@ -509,7 +529,7 @@ fn starknet_api_ethaddr_to_felt(value: starknet_api::core::EthAddress) -> FieldE
     stark_felt.into()
 }
 
-pub fn to_exec_info(exec_info: TransactionExecutionInfo) -> TxExecInfo {
+pub fn to_exec_info(exec_info: TransactionExecutionInfo, state_diff: StateUpdates) -> TxExecInfo {
     TxExecInfo {
         validate_call_info: exec_info.validate_call_info.map(to_call_info),
         execute_call_info: exec_info.execute_call_info.map(to_call_info),
@@ -522,6 +542,7 @@ pub fn to_exec_info(exec_info: TransactionExecutionInfo) -> TxExecInfo {
             .map(|(k, v)| (k, v as u64))
             .collect(),
         revert_error: exec_info.revert_error.clone(),
+        state_diff: Some(state_diff),
     }
 }
 
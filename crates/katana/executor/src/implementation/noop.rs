@@ -101,6 +101,16 @@ impl<'a> BlockExecutor<'a> for NoopExecutor {
         Ok(())
     }
 
+    fn execute_transaction_if(
+        &mut self,
+        transaction: ExecutableTxWithHash,
+        keep: &dyn Fn(&ExecutionResult) -> bool,
+    ) -> ExecutorResult<bool> {
+        let _ = transaction;
+        let _ = keep;
+        Ok(true)
+    }
+
     fn take_execution_output(&mut self) -> ExecutorResult<ExecutionOutput> {
         Ok(ExecutionOutput::default())
     }
@@ -8,11 +8,11 @@ use std::sync::Arc;
 use katana_primitives::block::{ExecutableBlock, PartialHeader};
 use katana_primitives::env::{BlockEnv, CfgEnv};
 use katana_primitives::fee::TxFeeInfo;
+use katana_primitives::trace::TxExecInfo;
 use katana_primitives::transaction::{ExecutableTx, ExecutableTxWithHash, TxWithHash};
 use katana_primitives::FieldElement;
 use katana_provider::traits::state::StateProvider;
 use sir::definitions::block_context::{self, BlockContext};
-use sir::execution::TransactionExecutionInfo;
 use sir::state::cached_state;
 use sir::state::contract_class_cache::PermanentContractClassCache;
 use tracing::info;
@@ -112,7 +112,7 @@ impl<'a> StarknetVMProcessor<'a> {
         mut op: F,
     ) -> Vec<T>
     where
-        F: FnMut((TxWithHash, Result<(TransactionExecutionInfo, TxFeeInfo), ExecutionError>)) -> T,
+        F: FnMut((TxWithHash, Result<(TxExecInfo, TxFeeInfo), ExecutionError>)) -> T,
     {
         let block_context = &self.block_context;
 
@@ -131,64 +131,91 @@ impl<'a> StarknetVMProcessor<'a> {
 
         results
     }
-}
 
-impl<'a> BlockExecutor<'a> for StarknetVMProcessor<'a> {
-    fn execute_transactions(
+    /// Executes the transaction, and keeps it only if `keep` returns `true` for the result of its
+    /// execution. Returns whether the transaction was kept.
+    fn execute_transaction(
         &mut self,
-        transactions: Vec<ExecutableTxWithHash>,
-    ) -> ExecutorResult<()> {
+        exec_tx: ExecutableTxWithHash,
+        keep: &dyn Fn(&ExecutionResult) -> bool,
+    ) -> bool {
         let block_context = &self.block_context;
         let flags = &self.simulation_flags;
         let mut state = self.state.0.write();
 
-        for exec_tx in transactions {
-            // Collect class artifacts if its a declare tx
-            let class_decl_artifacts = if let ExecutableTx::Declare(tx) = exec_tx.as_ref() {
-                let class_hash = tx.class_hash();
-                Some((class_hash, tx.compiled_class.clone(), tx.sierra_class.clone()))
-            } else {
-                None
-            };
+        // Collect class artifacts if its a declare tx
+        let class_decl_artifacts = if let ExecutableTx::Declare(tx) = exec_tx.as_ref() {
+            let class_hash = tx.class_hash();
+            Some((class_hash, tx.compiled_class.clone(), tx.sierra_class.clone()))
+        } else {
+            None
+        };
 
-            let tx = TxWithHash::from(&exec_tx);
-            let res = match utils::transact(exec_tx, &mut state.inner, block_context, flags) {
-                Ok((info, fee)) => {
-                    // get the trace and receipt from the execution info
-                    let trace = utils::to_exec_info(&info);
-                    let receipt = receipt_from_exec_info(&tx, &trace);
+        // the writes of the state before the transaction, restored if the transaction isn't kept
+        let snapshot = state.inner.cache().clone();
+
+        let tx = TxWithHash::from(&exec_tx);
+        let res = match utils::transact(exec_tx, &mut state.inner, block_context, flags) {
+            // get the receipt from the execution info
+            Ok((trace, fee)) => {
+                let receipt = receipt_from_exec_info(&tx, &trace);
+                ExecutionResult::new_success(receipt, trace, fee)
+            }
+            Err(e) => ExecutionResult::new_failed(e),
+        };
 
-                    crate::utils::log_resources(&trace.actual_resources);
-                    crate::utils::log_events(receipt.events());
+        if !keep(&res) {
+            *state.inner.cache_mut() = snapshot;
+            return false;
+        }
 
-                    self.stats.l1_gas_used += fee.gas_consumed;
-                    self.stats.cairo_steps_used += receipt.resources_used().steps as u128;
+        match &res {
+            ExecutionResult::Success { receipt, trace, fee } => {
+                crate::utils::log_resources(&trace.actual_resources);
+                crate::utils::log_events(receipt.events());
 
-                    if let Some(reason) = receipt.revert_reason() {
-                        info!(target: LOG_TARGET, reason = %reason, "Transaction reverted.");
-                    }
+                self.stats.l1_gas_used += fee.gas_consumed;
+                self.stats.cairo_steps_used += receipt.resources_used().steps as u128;
 
-                    ExecutionResult::new_success(receipt, trace, fee)
-                }
-                Err(e) => {
-                    info!(target: LOG_TARGET, error = %e, "Executing transaction.");
-                    ExecutionResult::new_failed(e)
+                if let Some(reason) = receipt.revert_reason() {
+                    info!(target: LOG_TARGET, reason = %reason, "Transaction reverted.");
                 }
-            };
 
-            // if the tx succeed, inserts the class artifacts into the contract class cache
-            if res.is_success() {
+                // if the tx succeed, inserts the class artifacts into the contract class cache
                 if let Some((class_hash, compiled, sierra)) = class_decl_artifacts {
                     state.declared_classes.insert(class_hash, (compiled, sierra));
                 }
             }
 
-            self.transactions.push((tx, res));
+            ExecutionResult::Failed { error } => {
+                info!(target: LOG_TARGET, error = %error, "Executing transaction.");
+            }
         }
 
+        self.transactions.push((tx, res));
+        true
+    }
+}
+
+impl<'a> BlockExecutor<'a> for StarknetVMProcessor<'a> {
+    fn execute_transactions(
+        &mut self,
+        transactions: Vec<ExecutableTxWithHash>,
+    ) -> ExecutorResult<()> {
+        for exec_tx in transactions {
+            self.execute_transaction(exec_tx, &|_| true);
+        }
         Ok(())
     }
 
+    fn execute_transaction_if(
+        &mut self,
+        transaction: ExecutableTxWithHash,
+        keep: &dyn Fn(&ExecutionResult) -> bool,
+    ) -> ExecutorResult<bool> {
+        Ok(self.execute_transaction(transaction, keep))
+    }
+
     fn execute_block(&mut self, block: ExecutableBlock) -> ExecutorResult<()> {
         self.fill_block_env_from_header(&block.header);
         self.execute_transactions(block.body)?;
@@ -233,9 +260,8 @@ impl<'a> ExecutorExt for StarknetVMProcessor<'a> {
     ) -> Vec<ResultAndStates> {
         self.simulate_with(transactions, &flags, |(tx, res)| {
             let result = match res {
-                Ok((info, fee)) => {
-                    // get the trace and receipt from the execution info
-                    let trace = utils::to_exec_info(&info);
+                Ok((trace, fee)) => {
+                    // get the receipt from the execution info
                     let receipt = receipt_from_exec_info(&tx, &trace);
                     ExecutionResult::new_success(receipt, trace, fee)
                 }
@@ -45,7 +45,7 @@ pub(super) fn transact<S, C>(
     state: &mut cached_state::CachedState<S, C>,
     block_context: &BlockContext,
     simulation_flag: &SimulationFlag,
-) -> Result<(TransactionExecutionInfo, TxFeeInfo), ExecutionError>
+) -> Result<(TxExecInfo, TxFeeInfo), ExecutionError>
 where
     S: StateReader,
     C: ContractClassCache,
@@ -53,6 +53,10 @@ where
     let tx = to_executor_tx(tx, simulation_flag)?;
     let fee_type = tx.fee_type();
 
+    // the cache holds the writes of all the transactions executed on the state, so the changes of
+    // the transaction are the writes that differ from the ones before it
+    let writes_before = to_state_updates(&state_diff_from_state_cache(state.cache().clone()));
+
     let info = tx.execute(
         state,
         block_context,
@@ -61,6 +65,9 @@ where
         None,
     )?;
 
+    let writes_after = to_state_updates(&state_diff_from_state_cache(state.cache().clone()));
+    let state_diff = changes_since(&writes_before, writes_after);
+
     // There are a few case where the `actual_fee` field of the transaction info is not set where
     // the fee is skipped and thus not charged for the transaction (e.g. when the
     // `skip_fee_transfer` is explicitly set, or when the transaction `max_fee` is set to 0). In
@@ -78,7 +85,7 @@ where
     };
     let fee = TxFeeInfo { gas_consumed, gas_price, unit, overall_fee };
 
-    Ok((info, fee))
+    Ok((to_exec_info(&info, state_diff), fee))
 }
 
 pub fn call(
@@ -542,7 +549,7 @@ fn to_sir_current_account_tx_fields(
     })
 }
 
-pub fn to_exec_info(exec_info: &TransactionExecutionInfo) -> TxExecInfo {
+pub fn to_exec_info(exec_info: &TransactionExecutionInfo, state_diff: StateUpdates) -> TxExecInfo {
     TxExecInfo {
         validate_call_info: exec_info.validate_info.clone().map(from_sir_call_info),
         execute_call_info: exec_info.call_info.clone().map(from_sir_call_info),
@@ -555,6 +562,7 @@ pub fn to_exec_info(exec_info: &TransactionExecutionInfo) -> TxExecInfo {
             .map(|(k, v)| (k, v as u64))
             .collect(),
         revert_error: exec_info.revert_error.clone(),
+        state_diff: Some(state_diff),
         // exec_info.tx_type being dropped here.
     }
 }
@@ -675,12 +683,32 @@ where
     S: StateDb,
     C: ContractClassCache + Send + Sync,
 {
-    use katana_primitives::class::ClassHash;
-
     let state = &mut state.0.write();
     let state_changes = std::mem::take(state.inner.cache_mut());
     let state_diffs = state_diff_from_state_cache(state_changes);
     let compiled_classes = std::mem::take(&mut state.declared_classes);
+    let state_updates = to_state_updates(&state_diffs);
+
+    let total_classes = state_updates.declared_classes.len();
+    let mut declared_compiled_classes = HashMap::with_capacity(total_classes);
+    let mut declared_sierra_classes = HashMap::with_capacity(total_classes);
+
+    for (hash, (compiled, sierra)) in compiled_classes {
+        declared_compiled_classes.insert(hash, compiled);
+        if let Some(sierra) = sierra {
+            declared_sierra_classes.insert(hash, sierra);
+        }
+    }
+
+    StateUpdatesWithDeclaredClasses {
+        declared_sierra_classes,
+        declared_compiled_classes,
+        state_updates,
+    }
+}
+
+fn to_state_updates(state_diffs: &StateDiff) -> StateUpdates {
+    use katana_primitives::class::ClassHash;
 
     let nonce_updates: HashMap<ContractAddress, FieldElement> =
         state_diffs.address_to_nonce().iter().map(|(k, v)| (to_address(k), to_felt(v))).collect();
@@ -707,25 +735,21 @@ where
         })
         .collect();
 
-    let total_classes = declared_classes.len();
-    let mut declared_compiled_classes = HashMap::with_capacity(total_classes);
-    let mut declared_sierra_classes = HashMap::with_capacity(total_classes);
+    StateUpdates { nonce_updates, storage_updates, contract_updates, declared_classes }
+}
 
-    for (hash, (compiled, sierra)) in compiled_classes {
-        declared_compiled_classes.insert(hash, compiled);
-        if let Some(sierra) = sierra {
-            declared_sierra_classes.insert(hash, sierra);
+/// Returns the updates of `after` that aren't in `before`.
+fn changes_since(before: &StateUpdates, mut after: StateUpdates) -> StateUpdates {
+    after.nonce_updates.retain(|k, v| before.nonce_updates.get(k) != Some(v));
+    after.contract_updates.retain(|k, v| before.contract_updates.get(k) != Some(v));
+    after.declared_classes.retain(|k, v| before.declared_classes.get(k) != Some(v));
+
+    for (address, entries) in after.storage_updates.iter_mut() {
+        if let Some(before) = before.storage_updates.get(address) {
+            entries.retain(|k, v| before.get(k) != Some(v));
         }
     }
+    after.storage_updates.retain(|_, entries| !entries.is_empty());
 
-    StateUpdatesWithDeclaredClasses {
-        declared_sierra_classes,
-        declared_compiled_classes,
-        state_updates: StateUpdates {
-            nonce_updates,
-            storage_updates,
-            contract_updates,
-            declared_classes,
-        },
-    }
+    after
 }
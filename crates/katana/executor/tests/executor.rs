@@ -6,6 +6,7 @@ use fixtures::{state_provider, valid_blocks};
 use katana_executor::{ExecutionOutput, ExecutorFactory};
 use katana_primitives::block::ExecutableBlock;
 use katana_primitives::contract::ContractAddress;
+use katana_primitives::env::BlockEnv;
 use katana_primitives::genesis::constant::{
     DEFAULT_FEE_TOKEN_ADDRESS, DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH,
     DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH, DEFAULT_PREFUNDED_ACCOUNT_BALANCE, DEFAULT_UDC_ADDRESS,
@@ -53,6 +54,15 @@ fn test_executor_with_valid_blocks_impl<EF: ExecutorFactory>(
     let transactions = executor.transactions();
     assert_eq!(transactions.len(), 2, "2 transactions were executed");
 
+    // assert that the traces record the changes made by each transaction, the nonce of the main
+    // account being bumped by both of them
+    for (i, (_, result)) in transactions.iter().enumerate() {
+        let trace = result.trace().expect("transaction should succeed");
+        let state_diff = trace.state_diff.as_ref().expect("state diff should be recorded");
+        let expected_nonce = FieldElement::from(i as u64 + 1);
+        assert_eq!(state_diff.nonce_updates.get(&main_account), Some(&expected_nonce));
+    }
+
     // asserts that the states are updated correctly after executing the 1st block
 
     let state_provider = executor.state();
@@ -309,10 +319,52 @@ fn test_executor_with_valid_blocks_impl<EF: ExecutorFactory>(
     );
 }
 
+fn test_executor_keeps_transaction_if_impl<EF: ExecutorFactory>(
+    factory: EF,
+    state: Box<dyn StateProvider>,
+    blocks: [ExecutableBlock; 3],
+) {
+    let main_account: ContractAddress =
+        felt!("0x6b86e40118f29ebe393a75469b4d926c7a44c2e2681b6d319520b7c1156d114").into();
+
+    let block = &blocks[0];
+    let block_env = BlockEnv {
+        number: block.header.number,
+        timestamp: block.header.timestamp,
+        l1_gas_prices: block.header.gas_prices.clone(),
+        sequencer_address: block.header.sequencer_address,
+    };
+    let mut executor = factory.with_state_and_block_env(state, block_env);
+    let tx = block.body[0].clone();
+    let initial_nonce = executor.state().nonce(main_account).unwrap();
+
+    // a transaction that isn't kept is neither recorded nor applied to the state
+    let kept = executor.execute_transaction_if(tx.clone(), &|res| res.is_failed()).unwrap();
+    assert!(!kept, "the transaction succeeds, so it isn't kept");
+    assert!(executor.transactions().is_empty());
+    let nonce = executor.state().nonce(main_account).unwrap();
+    assert_eq!(nonce, initial_nonce, "account nonce is untouched");
+
+    let kept = executor.execute_transaction_if(tx, &|res| res.is_success()).unwrap();
+    assert!(kept);
+    assert_eq!(executor.transactions().len(), 1);
+    let nonce = executor.state().nonce(main_account).unwrap();
+    assert_eq!(nonce, Some(FieldElement::ONE), "account nonce is updated");
+}
+
 #[cfg(feature = "blockifier")]
 mod blockifier {
     use fixtures::blockifier::factory;
+    use fixtures::{cfg, genesis, legacy_contract_class};
     use katana_executor::implementation::blockifier::BlockifierFactory;
+    use katana_executor::{EntryPointCall, SimulationFlag};
+    use katana_primitives::block::{Block, FinalityStatus, SealedBlockWithStatus};
+    use katana_primitives::env::CfgEnv;
+    use katana_primitives::genesis::Genesis;
+    use katana_provider::providers::in_memory::InMemoryProvider;
+    use katana_provider::traits::block::BlockWriter;
+    use katana_provider::traits::state::StateFactoryProvider;
+    use starknet::core::utils::get_selector_from_name;
 
     use super::*;
 
@@ -324,6 +376,58 @@ mod blockifier {
     ) {
         test_executor_with_valid_blocks_impl(factory, state, blocks)
     }
+
+    #[rstest::rstest]
+    fn test_executor_keeps_transaction_if(
+        factory: BlockifierFactory,
+        #[from(state_provider)] state: Box<dyn StateProvider>,
+        #[from(valid_blocks)] blocks: [ExecutableBlock; 3],
+    ) {
+        test_executor_keeps_transaction_if_impl(factory, state, blocks)
+    }
+
+    #[rstest::rstest]
+    fn test_executor_exceeding_recursion_depth(cfg: CfgEnv, genesis: &Genesis) {
+        let class_hash = felt!("0x1234");
+        let contract_address = ContractAddress(felt!("0x5678"));
+
+        let mut states = genesis.state_updates();
+        states.state_updates.declared_classes.insert(class_hash, class_hash);
+        states.declared_compiled_classes.insert(class_hash, legacy_contract_class());
+        states.state_updates.contract_updates.insert(contract_address, class_hash);
+
+        let provider = InMemoryProvider::new();
+        let block = SealedBlockWithStatus {
+            status: FinalityStatus::AcceptedOnL2,
+            block: Block::default().seal_with_hash(123u64.into()),
+        };
+        provider.insert_block_with_states_and_receipts(block, states, vec![], vec![]).unwrap();
+        let state = <InMemoryProvider as StateFactoryProvider>::latest(&provider).unwrap();
+
+        let cfg = CfgEnv { max_recursion_depth: 5, ..cfg };
+        let factory = BlockifierFactory::new(cfg, SimulationFlag::default());
+        let executor = factory.with_state(state);
+
+        // a call to `test_library_call` library calling itself `depth` times
+        let recursive_call = |depth: usize| {
+            let library_call = get_selector_from_name("test_library_call").unwrap();
+            let mut calldata = vec![class_hash, get_selector_from_name("without_arg").unwrap()];
+            calldata.push(FieldElement::ZERO);
+            for _ in 1..depth {
+                let len = FieldElement::from(calldata.len());
+                calldata = [vec![class_hash, library_call, len], calldata].concat();
+            }
+            EntryPointCall { contract_address, calldata, entry_point_selector: library_call }
+        };
+
+        assert!(executor.call(recursive_call(3)).is_ok());
+
+        let error = executor.call(recursive_call(10)).unwrap_err();
+        assert!(
+            error.to_string().contains("maximum depth of 5 nested calls"),
+            "unexpected error: {error}"
+        );
+    }
 }
 
 #[cfg(feature = "sir")]
@@ -341,4 +445,13 @@ mod sir {
     ) {
         test_executor_with_valid_blocks_impl(factory, state, blocks)
     }
+
+    #[rstest::rstest]
+    fn test_executor_keeps_transaction_if(
+        factory: NativeExecutorFactory,
+        #[from(state_provider)] state: Box<dyn StateProvider>,
+        #[from(valid_blocks)] blocks: [ExecutableBlock; 3],
+    ) {
+        test_executor_keeps_transaction_if_impl(factory, state, blocks)
+    }
 }
@@ -0,0 +1,306 @@
+//! Assembles a Katana node from its components and runs it in-process.
+//!
+//! ```rust,no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use katana_node::{BlockProduction, Builder};
+//! use katana_rpc_api::ApiKind;
+//!
+//! let node = Builder::new()
+//!     .in_memory()
+//!     .apis(vec![ApiKind::Starknet, ApiKind::Dev])
+//!     .block_production(BlockProduction::OnDemand)
+//!     .launch()
+//!     .await?;
+//!
+//! let client = node.rpc_client()?;
+//! // ...
+//! node.stop().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use katana_core::backend::config::StarknetConfig;
+use katana_core::backend::storage::Database;
+use katana_core::env::get_default_vm_resource_fee_cost;
+use katana_core::sequencer::{KatanaSequencer, SequencerConfig};
+use katana_executor::{ExecutorFactory, SimulationFlag};
+use katana_primitives::env::{CfgEnv, FeeTokenAddressses};
+use katana_primitives::genesis::predeploy::Predeploy;
+use katana_provider::BlockchainProvider;
+use katana_rpc::config::ServerConfig;
+use katana_rpc::{spawn, NodeHandle};
+use katana_rpc_api::ApiKind;
+use url::Url;
+
+cfg_if::cfg_if! {
+    if #[cfg(all(feature = "blockifier", feature = "sir"))] {
+        compile_error!("Cannot enable both `blockifier` and `sir` features at the same time");
+    } else if #[cfg(feature = "blockifier")] {
+        /// The executor factory used when none is provided to the [Builder].
+        pub type DefaultExecutorFactory =
+            katana_executor::implementation::blockifier::BlockifierFactory;
+    } else if #[cfg(feature = "sir")] {
+        /// The executor factory used when none is provided to the [Builder].
+        pub type DefaultExecutorFactory =
+            katana_executor::implementation::sir::NativeExecutorFactory;
+    } else {
+        compile_error!("At least one of the following features must be enabled: blockifier, sir");
+    }
+}
+
+/// Creates the default executor factory, configured from `config`.
+pub fn default_executor_factory(config: &StarknetConfig) -> DefaultExecutorFactory {
+    let cfg_env = CfgEnv {
+        chain_id: config.env.chain_id,
+        vm_resource_fee_cost: get_default_vm_resource_fee_cost(),
+        invoke_tx_max_n_steps: config.env.invoke_max_steps,
+        validate_max_n_steps: config.env.validate_max_steps,
+        max_recursion_depth: config.env.max_recursion_depth,
+        fee_token_addresses: FeeTokenAddressses {
+            eth: config.genesis.fee_token.address,
+            strk: Default::default(),
+        },
+    };
+
+    let simulation_flags = SimulationFlag {
+        skip_validate: config.disable_validate,
+        skip_fee_transfer: config.disable_fee,
+        ..Default::default()
+    };
+
+    DefaultExecutorFactory::new(cfg_env, simulation_flags)
+}
+
+/// How the node produces blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockProduction {
+    /// A block is produced for every transaction.
+    Instant,
+    /// A block is produced at a fixed interval, in milliseconds.
+    Interval(u64),
+    /// Blocks are only produced when requested, eg. through `dev_generateBlock`.
+    OnDemand,
+    /// Blocks are built by an external block builder from the transactions of the pool, and
+    /// sealed through `katana_sealBlock`.
+    External,
+}
+
+/// Builds a Katana node from its components.
+///
+/// By default the node keeps its data in memory, produces a block for every transaction and
+/// serves all the RPC APIs on a random port of the local interface.
+#[derive(Debug)]
+pub struct Builder {
+    starknet_config: StarknetConfig,
+    sequencer_config: SequencerConfig,
+    server_config: ServerConfig,
+    predeploys: Vec<Predeploy>,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            starknet_config: StarknetConfig::default(),
+            sequencer_config: SequencerConfig::default(),
+            server_config: ServerConfig {
+                port: 0,
+                host: "127.0.0.1".into(),
+                max_connections: 100,
+                apis: vec![
+                    ApiKind::Starknet,
+                    ApiKind::Katana,
+                    ApiKind::Dev,
+                    ApiKind::Saya,
+                    ApiKind::Torii,
+                ],
+                max_batch_size: None,
+                rate_limits: Default::default(),
+                trust_forwarded_headers: false,
+            },
+            predeploys: Vec::new(),
+        }
+    }
+
+    pub fn starknet_config(mut self, config: StarknetConfig) -> Self {
+        self.starknet_config = config;
+        self
+    }
+
+    pub fn sequencer_config(mut self, config: SequencerConfig) -> Self {
+        self.sequencer_config = config;
+        self
+    }
+
+    pub fn server_config(mut self, config: ServerConfig) -> Self {
+        self.server_config = config;
+        self
+    }
+
+    /// Stores the data of the node in a database at `path`.
+    pub fn db_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.starknet_config.db_dir = Some(path.into());
+        self
+    }
+
+    /// Keeps the data of the node in memory.
+    pub fn in_memory(mut self) -> Self {
+        self.starknet_config.db_dir = None;
+        self
+    }
+
+    /// Declares and deploys the given contracts at genesis, eg. loaded from a directory of class
+    /// artifacts with [load_predeploys](katana_primitives::genesis::predeploy::load_predeploys).
+    /// They are added to the genesis when the node is launched, which fails if one of them is at
+    /// the address of another contract of the genesis.
+    pub fn predeploys(mut self, predeploys: Vec<Predeploy>) -> Self {
+        self.predeploys = predeploys;
+        self
+    }
+
+    /// Sets the RPC APIs served by the node.
+    pub fn apis(mut self, apis: Vec<ApiKind>) -> Self {
+        self.server_config.apis = apis;
+        self
+    }
+
+    pub fn block_production(mut self, mode: BlockProduction) -> Self {
+        let (block_time, no_mining) = match mode {
+            BlockProduction::Instant | BlockProduction::External => (None, false),
+            BlockProduction::Interval(interval) => (Some(interval), false),
+            BlockProduction::OnDemand => (None, true),
+        };
+
+        self.sequencer_config.block_time = block_time;
+        self.sequencer_config.no_mining = no_mining;
+        self.sequencer_config.external_block_building = matches!(mode, BlockProduction::External);
+        self
+    }
+
+    /// Starts the node with the [DefaultExecutorFactory].
+    pub async fn launch(self) -> Result<Node<DefaultExecutorFactory>> {
+        let executor_factory = default_executor_factory(&self.starknet_config);
+        self.launch_with_executor(executor_factory).await
+    }
+
+    /// Starts the node with a custom executor factory.
+    pub async fn launch_with_executor<EF: ExecutorFactory>(
+        mut self,
+        executor_factory: EF,
+    ) -> Result<Node<EF>> {
+        self.starknet_config.genesis.extend_predeploys(self.predeploys)?;
+        let sequencer = Arc::new(
+            KatanaSequencer::new(executor_factory, self.sequencer_config, self.starknet_config)
+                .await?,
+        );
+        let rpc = spawn(Arc::clone(&sequencer), self.server_config).await?;
+        Ok(Node { sequencer, rpc })
+    }
+}
+
+/// A Katana node running in-process.
+pub struct Node<EF: ExecutorFactory> {
+    pub sequencer: Arc<KatanaSequencer<EF>>,
+    pub rpc: NodeHandle,
+}
+
+impl<EF: ExecutorFactory> Node<EF> {
+    /// Returns the provider of the blockchain data of the node.
+    pub fn provider(&self) -> &BlockchainProvider<Box<dyn Database>> {
+        self.sequencer.backend().blockchain.provider()
+    }
+
+    /// Returns the address the RPC server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.rpc.addr
+    }
+
+    /// Returns the URL of the RPC server.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.rpc.addr)).expect("valid url")
+    }
+
+    /// Returns a client of the RPC server, to be used with the clients of the `katana-rpc-api`
+    /// traits.
+    pub fn rpc_client(&self) -> Result<HttpClient> {
+        Ok(HttpClientBuilder::default().build(self.url())?)
+    }
+
+    /// Stops the RPC server, then waits for the block that is being produced, if any, to be
+    /// committed.
+    pub async fn stop(self) -> Result<()> {
+        self.rpc.handle.stop()?;
+        self.rpc.handle.stopped().await;
+        self.sequencer.shutdown().await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::FieldElement;
+    use katana_rpc_api::eth::EthApiClient;
+    use katana_rpc_api::starknet::StarknetApiClient;
+    use katana_rpc_types::eth::{EthBlockId, Quantity};
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_in_memory_node() {
+        let node = Builder::new()
+            .in_memory()
+            .apis(vec![ApiKind::Starknet])
+            .block_production(BlockProduction::OnDemand)
+            .launch()
+            .await
+            .unwrap();
+
+        let client = node.rpc_client().unwrap();
+        let chain_id = client.chain_id().await.unwrap();
+        assert_eq!(*chain_id, node.sequencer.chain_id().id());
+
+        node.stop().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn eth_compat_api() {
+        let node = Builder::new()
+            .in_memory()
+            .apis(vec![ApiKind::Eth])
+            .block_production(BlockProduction::OnDemand)
+            .launch()
+            .await
+            .unwrap();
+
+        let client = node.rpc_client().unwrap();
+        let chain_id = node.sequencer.chain_id().id();
+        assert_eq!(EthApiClient::chain_id(&client).await.unwrap(), Quantity::from(chain_id));
+        assert_eq!(EthApiClient::block_number(&client).await.unwrap(), Quantity::from(0));
+
+        let (address, account) = node.sequencer.backend.config.genesis.accounts().next().unwrap();
+        let balance = account.balance().unwrap();
+        let address = (*address).into();
+        assert_eq!(client.get_balance(address, None).await.unwrap(), Quantity::from(balance));
+        assert_eq!(
+            client.get_balance(address, Some(EthBlockId::Earliest)).await.unwrap(),
+            Quantity::from(balance)
+        );
+
+        // unknown accounts have no balance
+        let unknown = FieldElement::from(0x1234u64);
+        assert_eq!(client.get_balance(unknown, None).await.unwrap(), Quantity::default());
+
+        node.stop().await.unwrap();
+    }
+}
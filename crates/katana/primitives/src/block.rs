@@ -1,6 +1,9 @@
+use std::collections::HashSet;
+
 use starknet::core::crypto::compute_hash_on_elements;
 
 use crate::contract::ContractAddress;
+use crate::receipt::Receipt;
 use crate::transaction::{ExecutableTxWithHash, TxHash, TxWithHash};
 use crate::version::Version;
 use crate::FieldElement;
@@ -173,6 +176,38 @@ pub struct SealedBlockWithStatus {
     pub status: FinalityStatus,
 }
 
+/// Aggregated activity of a block, computed when the block is inserted.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockStats {
+    /// The number of transactions in the block.
+    pub tx_count: u64,
+    /// The number of distinct accounts that sent a transaction in the block.
+    pub unique_senders: u64,
+    /// The number of events emitted by the transactions of the block.
+    pub events_count: u64,
+    /// The number of classes declared in the block.
+    pub declared_classes_count: u64,
+}
+
+impl BlockStats {
+    /// Computes the statistics of a block from its transactions, their receipts and the number of
+    /// classes declared in it.
+    pub fn new(txs: &[TxWithHash], receipts: &[Receipt], declared_classes_count: u64) -> Self {
+        let senders = txs
+            .iter()
+            .filter_map(|tx| tx.transaction.sender_address())
+            .collect::<HashSet<ContractAddress>>();
+
+        Self {
+            tx_count: txs.len() as u64,
+            unique_senders: senders.len() as u64,
+            events_count: receipts.iter().map(|receipt| receipt.events().len() as u64).sum(),
+            declared_classes_count,
+        }
+    }
+}
+
 impl From<BlockNumber> for BlockHashOrNumber {
     fn from(number: BlockNumber) -> Self {
         Self::Num(number)
@@ -0,0 +1,311 @@
+//! Chain specification.
+//!
+//! A [ChainSpec] bundles all the parameters that define a chain: its id, the genesis
+//! configuration (which includes the fee token), the protocol version, the transaction versions
+//! it accepts and its protocol constants. It can either be created from one of the built-in
+//! [ChainPreset]s or loaded from a TOML or JSON file using [ChainSpec::load].
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::{ChainId, ParseChainIdError};
+use crate::genesis::json::{GenesisJson, GenesisJsonError};
+use crate::genesis::Genesis;
+use crate::transaction::{DeclareTx, DeployAccountTx, ExecutableTx, InvokeTx};
+use crate::version::{Version, CURRENT_STARKNET_VERSION};
+
+/// The parameters that define a chain.
+#[derive(Debug, Clone)]
+pub struct ChainSpec {
+    /// The chain id.
+    pub id: ChainId,
+    /// The genesis configuration of the chain, including its fee token.
+    pub genesis: Genesis,
+    /// The Starknet protocol version of the chain.
+    pub version: Version,
+    /// The transaction versions accepted by the chain.
+    pub supported_tx_versions: SupportedTxVersions,
+    /// The protocol constants of the chain.
+    pub constants: ProtocolConstants,
+}
+
+/// The protocol constants of a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolConstants {
+    /// The maximum number of steps available for the account execution logic.
+    pub invoke_max_steps: u32,
+    /// The maximum number of steps available for the account validation logic.
+    pub validate_max_steps: u32,
+}
+
+/// The versions accepted for each transaction type. L1 handler transactions are always accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedTxVersions {
+    pub invoke: Vec<u8>,
+    pub declare: Vec<u8>,
+    pub deploy_account: Vec<u8>,
+}
+
+impl SupportedTxVersions {
+    /// Returns `true` if the version of the transaction is accepted.
+    pub fn supports(&self, tx: &ExecutableTx) -> bool {
+        match tx {
+            ExecutableTx::Invoke(tx) => {
+                let version = match tx {
+                    InvokeTx::V1(_) => 1,
+                    InvokeTx::V3(_) => 3,
+                };
+                self.invoke.contains(&version)
+            }
+
+            ExecutableTx::Declare(tx) => {
+                let version = match tx.transaction {
+                    DeclareTx::V1(_) => 1,
+                    DeclareTx::V2(_) => 2,
+                    DeclareTx::V3(_) => 3,
+                };
+                self.declare.contains(&version)
+            }
+
+            ExecutableTx::DeployAccount(tx) => {
+                let version = match tx {
+                    DeployAccountTx::V1(_) => 1,
+                    DeployAccountTx::V3(_) => 3,
+                };
+                self.deploy_account.contains(&version)
+            }
+
+            ExecutableTx::L1Handler(_) => true,
+        }
+    }
+}
+
+impl Default for SupportedTxVersions {
+    /// All the transaction versions supported by Katana.
+    fn default() -> Self {
+        Self { invoke: vec![1, 3], declare: vec![1, 2, 3], deploy_account: vec![1, 3] }
+    }
+}
+
+/// The built-in chain specifications.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ChainPreset {
+    /// A local development chain.
+    #[default]
+    Dev,
+    /// A chain compatible with the Starknet Sepolia testnet.
+    Sepolia,
+    /// A chain compatible with the Starknet mainnet.
+    Mainnet,
+}
+
+impl ChainPreset {
+    /// Returns the chain specification of the preset.
+    pub fn spec(&self) -> ChainSpec {
+        match self {
+            Self::Dev => ChainSpec {
+                id: ChainId::parse("KATANA").expect("valid chain id"),
+                genesis: Genesis::default(),
+                version: CURRENT_STARKNET_VERSION,
+                supported_tx_versions: SupportedTxVersions::default(),
+                constants: ProtocolConstants {
+                    invoke_max_steps: 1_000_000,
+                    validate_max_steps: 1_000_000,
+                },
+            },
+
+            Self::Sepolia | Self::Mainnet => ChainSpec {
+                id: if *self == Self::Sepolia { ChainId::SEPOLIA } else { ChainId::MAINNET },
+                genesis: Genesis::default(),
+                version: CURRENT_STARKNET_VERSION,
+                supported_tx_versions: SupportedTxVersions::default(),
+                constants: ProtocolConstants {
+                    invoke_max_steps: 4_000_000,
+                    validate_max_steps: 1_000_000,
+                },
+            },
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown chain preset `{0}`, expected one of `dev`, `sepolia` or `mainnet`")]
+pub struct ParseChainPresetError(String);
+
+impl FromStr for ChainPreset {
+    type Err = ParseChainPresetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dev" => Ok(Self::Dev),
+            "sepolia" => Ok(Self::Sepolia),
+            "mainnet" => Ok(Self::Mainnet),
+            _ => Err(ParseChainPresetError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChainSpecError {
+    #[error("Failed to read chain spec file at path {path}: {source}")]
+    FileNotFound { source: io::Error, path: PathBuf },
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+
+    #[error(transparent)]
+    ChainId(#[from] ParseChainIdError),
+
+    #[error("Invalid protocol version: {0}")]
+    Version(anyhow::Error),
+
+    #[error(transparent)]
+    Genesis(#[from] GenesisJsonError),
+}
+
+/// The file representation of a [ChainSpec]. Every field is optional and defaults to the value of
+/// the `preset` it is based on.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSpecFile {
+    /// The preset to base the chain spec on. Defaults to [ChainPreset::Dev].
+    #[serde(default)]
+    pub preset: ChainPreset,
+    /// The chain id, either as a hex string (`0x` prefix) or as a Cairo short string.
+    pub id: Option<String>,
+    /// The path to the genesis JSON file, relative to the chain spec file.
+    pub genesis: Option<PathBuf>,
+    /// The Starknet protocol version, eg `0.13.0`.
+    pub version: Option<String>,
+    pub supported_tx_versions: Option<SupportedTxVersions>,
+    pub constants: Option<ProtocolConstants>,
+}
+
+impl ChainSpec {
+    /// Loads a chain spec from a file. The file is parsed as TOML if it has a `.toml` extension,
+    /// and as JSON otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ChainSpecError> {
+        let path = path.as_ref();
+
+        let content = fs::read_to_string(path)
+            .map_err(|source| ChainSpecError::FileNotFound { path: path.to_path_buf(), source })?;
+
+        let file: ChainSpecFile = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content)?,
+            _ => serde_json::from_str(&content)?,
+        };
+
+        Self::from_file(file, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Creates a chain spec from its file representation. Relative paths in `file` are resolved
+    /// against `base_path`.
+    pub fn from_file(file: ChainSpecFile, base_path: &Path) -> Result<Self, ChainSpecError> {
+        let mut spec = file.preset.spec();
+
+        if let Some(id) = file.id {
+            spec.id = ChainId::parse(&id)?;
+        }
+
+        if let Some(genesis) = file.genesis {
+            let genesis = GenesisJson::load(base_path.join(genesis))?;
+            spec.genesis = Genesis::try_from(genesis)?;
+        }
+
+        if let Some(version) = file.version {
+            spec.version = Version::parse(&version).map_err(ChainSpecError::Version)?;
+        }
+
+        if let Some(versions) = file.supported_tx_versions {
+            spec.supported_tx_versions = versions;
+        }
+
+        if let Some(constants) = file.constants {
+            spec.constants = constants;
+        }
+
+        Ok(spec)
+    }
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        ChainPreset::Dev.spec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::genesis::constant::DEFAULT_FEE_TOKEN_ADDRESS;
+
+    #[test]
+    fn presets() {
+        assert_eq!(
+            ChainPreset::from_str("dev").unwrap().spec().id,
+            ChainId::parse("KATANA").unwrap()
+        );
+        assert_eq!(ChainPreset::from_str("sepolia").unwrap().spec().id, ChainId::SEPOLIA);
+        assert_eq!(ChainPreset::from_str("mainnet").unwrap().spec().id, ChainId::MAINNET);
+        assert!(ChainPreset::from_str("goerli").is_err());
+    }
+
+    #[test]
+    fn chain_spec_from_toml() {
+        let content = r#"
+            preset = "sepolia"
+            id = "MY_CHAIN"
+            genesis = "./src/genesis/test-genesis.json"
+            version = "0.13.0"
+
+            [supportedTxVersions]
+            invoke = [3]
+            declare = [2, 3]
+            deployAccount = [3]
+        "#;
+
+        let file: ChainSpecFile = toml::from_str(content).unwrap();
+        let spec = ChainSpec::from_file(file, Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap();
+
+        assert_eq!(spec.id, ChainId::parse("MY_CHAIN").unwrap());
+        assert_eq!(spec.version, Version::new(0, 13, 0));
+        assert_eq!(spec.supported_tx_versions.invoke, vec![3]);
+        assert_eq!(spec.supported_tx_versions.declare, vec![2, 3]);
+        assert_eq!(spec.supported_tx_versions.deploy_account, vec![3]);
+        // not overridden, so taken from the preset
+        assert_eq!(spec.constants, ChainPreset::Sepolia.spec().constants);
+        assert_eq!(spec.genesis.fee_token.name, "ETHER");
+        assert_ne!(spec.genesis.fee_token.address, DEFAULT_FEE_TOKEN_ADDRESS);
+    }
+
+    #[test]
+    fn chain_spec_from_json() {
+        let content =
+            r#"{ "id": "0x1337", "constants": { "invokeMaxSteps": 10, "validateMaxSteps": 20 } }"#;
+
+        let file: ChainSpecFile = serde_json::from_str(content).unwrap();
+        let spec = ChainSpec::from_file(file, Path::new("")).unwrap();
+
+        assert_eq!(spec.id, ChainId::parse("0x1337").unwrap());
+        assert_eq!(
+            spec.constants,
+            ProtocolConstants { invoke_max_steps: 10, validate_max_steps: 20 }
+        );
+        assert_eq!(spec.supported_tx_versions, SupportedTxVersions::default());
+        assert_eq!(spec.genesis.fee_token.address, DEFAULT_FEE_TOKEN_ADDRESS);
+    }
+}
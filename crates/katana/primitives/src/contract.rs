@@ -31,6 +31,12 @@ impl fmt::Display for ContractAddress {
     }
 }
 
+impl fmt::LowerHex for ContractAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
 impl From<FieldElement> for ContractAddress {
     fn from(value: FieldElement) -> Self {
         ContractAddress::new(value)
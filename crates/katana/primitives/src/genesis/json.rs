@@ -30,9 +30,8 @@ use super::constant::{
     DEFAULT_FEE_TOKEN_ADDRESS, DEFAULT_LEGACY_ERC20_CONTRACT_CASM,
     DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH, DEFAULT_LEGACY_ERC20_CONTRACT_COMPILED_CLASS_HASH,
     DEFAULT_LEGACY_UDC_CASM, DEFAULT_LEGACY_UDC_CLASS_HASH, DEFAULT_LEGACY_UDC_COMPILED_CLASS_HASH,
-    DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CASM,
-    DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH, DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
-    DEFAULT_UDC_ADDRESS,
+    DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
+    DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH, DEFAULT_UDC_ADDRESS,
 };
 use super::{FeeTokenConfig, Genesis, GenesisAllocation, UniversalDeployerConfig};
 use crate::block::{BlockHash, BlockNumber, GasPrices};
@@ -276,42 +275,7 @@ impl TryFrom<GenesisJson> for Genesis {
                     }
                 };
 
-                let sierra = serde_json::from_value::<SierraClass>(artifact.clone());
-
-                let (class_hash, compiled_class_hash, sierra, casm) = match sierra {
-                    Ok(sierra) => {
-                        let class = parse_compiled_class_v1(artifact)?;
-
-                        // check if the class hash is provided, otherwise compute it from the
-                        // artifacts
-                        let class_hash = class_hash.unwrap_or(sierra.class_hash()?);
-                        let compiled_hash = class.casm.compiled_class_hash().to_be_bytes();
-
-                        (
-                            class_hash,
-                            FieldElement::from_bytes_be(&compiled_hash)?,
-                            Some(Arc::new(sierra.flatten()?)),
-                            Arc::new(CompiledClass::Class(class)),
-                        )
-                    }
-
-                    // if the artifact is not a sierra contract, we check if it's a legacy contract
-                    Err(_) => {
-                        let casm = parse_deprecated_compiled_class(artifact.clone())?;
-
-                        let class_hash = if let Some(class_hash) = class_hash {
-                            class_hash
-                        } else {
-                            let casm: LegacyContractClass =
-                                serde_json::from_value(artifact.clone())?;
-                            casm.class_hash()?
-                        };
-
-                        (class_hash, class_hash, None, Arc::new(CompiledClass::Deprecated(casm)))
-                    }
-                };
-
-                Ok((class_hash, GenesisClass { compiled_class_hash, sierra, casm }))
+                parse_genesis_class(artifact, class_hash)
             })
             .collect::<Result<_, GenesisJsonError>>()?;
 
@@ -337,7 +301,7 @@ impl TryFrom<GenesisJson> for Genesis {
                     DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH,
                     GenesisClass {
                         sierra: None,
-                        casm: Arc::new(DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone()),
+                        casm: Some(Arc::new(DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone())),
                         compiled_class_hash: DEFAULT_LEGACY_ERC20_CONTRACT_COMPILED_CLASS_HASH,
                     },
                 );
@@ -368,7 +332,7 @@ impl TryFrom<GenesisJson> for Genesis {
                         DEFAULT_LEGACY_UDC_CLASS_HASH,
                         GenesisClass {
                             sierra: None,
-                            casm: Arc::new(DEFAULT_LEGACY_UDC_CASM.clone()),
+                            casm: Some(Arc::new(DEFAULT_LEGACY_UDC_CASM.clone())),
                             compiled_class_hash: DEFAULT_LEGACY_UDC_COMPILED_CLASS_HASH,
                         },
                     );
@@ -401,7 +365,7 @@ impl TryFrom<GenesisJson> for Genesis {
                     {
                         // insert default account class to the classes map
                         e.insert(GenesisClass {
-                            casm: Arc::new(DEFAULT_OZ_ACCOUNT_CONTRACT_CASM.clone()),
+                            casm: None,
                             sierra: Some(Arc::new(
                                 DEFAULT_OZ_ACCOUNT_CONTRACT.clone().flatten().unwrap(),
                             )),
@@ -517,7 +481,52 @@ pub fn from_base64(data: &[u8]) -> Result<GenesisJson, GenesisJsonError> {
     Ok(serde_json::from_slice::<GenesisJson>(&decoded)?)
 }
 
-fn class_artifact_at_path(
+/// Parses a class artifact, either a Sierra or a legacy class, into a [GenesisClass]. The class
+/// hash is computed from the artifact if it is not provided.
+pub(crate) fn parse_genesis_class(
+    artifact: Value,
+    class_hash: Option<ClassHash>,
+) -> Result<(ClassHash, GenesisClass), GenesisJsonError> {
+    let sierra = serde_json::from_value::<SierraClass>(artifact.clone());
+
+    let (class_hash, compiled_class_hash, sierra, casm) = match sierra {
+        Ok(sierra) => {
+            // the casm is only needed to compute the compiled class hash, it is
+            // compiled again from the sierra class when the class is executed
+            let class = parse_compiled_class_v1(artifact)?;
+
+            // check if the class hash is provided, otherwise compute it from the
+            // artifacts
+            let class_hash = class_hash.unwrap_or(sierra.class_hash()?);
+            let compiled_hash = class.casm.compiled_class_hash().to_be_bytes();
+
+            (
+                class_hash,
+                FieldElement::from_bytes_be(&compiled_hash)?,
+                Some(Arc::new(sierra.flatten()?)),
+                None,
+            )
+        }
+
+        // if the artifact is not a sierra contract, we check if it's a legacy contract
+        Err(_) => {
+            let casm = parse_deprecated_compiled_class(artifact.clone())?;
+
+            let class_hash = if let Some(class_hash) = class_hash {
+                class_hash
+            } else {
+                let casm: LegacyContractClass = serde_json::from_value(artifact.clone())?;
+                casm.class_hash()?
+            };
+
+            (class_hash, class_hash, None, Some(Arc::new(CompiledClass::Deprecated(casm))))
+        }
+    };
+
+    Ok((class_hash, GenesisClass { compiled_class_hash, sierra, casm }))
+}
+
+pub(crate) fn class_artifact_at_path(
     base_path: PathBuf,
     relative_path: &PathBuf,
 ) -> Result<serde_json::Value, GenesisJsonError> {
@@ -554,9 +563,8 @@ mod tests {
         DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH,
         DEFAULT_LEGACY_ERC20_CONTRACT_COMPILED_CLASS_HASH, DEFAULT_LEGACY_UDC_CASM,
         DEFAULT_LEGACY_UDC_CLASS_HASH, DEFAULT_LEGACY_UDC_COMPILED_CLASS_HASH,
-        DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CASM,
-        DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH, DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
-        DEFAULT_UDC_ADDRESS,
+        DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
+        DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH, DEFAULT_UDC_ADDRESS,
     };
     use crate::genesis::json::to_base64;
     use crate::genesis::{
@@ -748,7 +756,7 @@ mod tests {
                 felt!("0x07b3e05f48f0c69e4a65ce5e076a66271a527aff2c34ce1083ec6e1526997a69"),
                 GenesisClass {
                     sierra: None,
-                    casm: DEFAULT_LEGACY_UDC_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_UDC_CASM.clone().into()),
                     compiled_class_hash: felt!(
                         "0x07b3e05f48f0c69e4a65ce5e076a66271a527aff2c34ce1083ec6e1526997a69"
                     ),
@@ -758,7 +766,7 @@ mod tests {
                 felt!("0x80085"),
                 GenesisClass {
                     sierra: None,
-                    casm: DEFAULT_LEGACY_UDC_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_UDC_CASM.clone().into()),
                     compiled_class_hash: felt!("0x80085"),
                 },
             ),
@@ -767,14 +775,14 @@ mod tests {
                 GenesisClass {
                     sierra: None,
                     compiled_class_hash: felt!("0x8"),
-                    casm: DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone().into()),
                 },
             ),
             (
                 DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
                 GenesisClass {
                     compiled_class_hash: DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
-                    casm: DEFAULT_OZ_ACCOUNT_CONTRACT_CASM.clone().into(),
+                    casm: None,
                     sierra: Some(DEFAULT_OZ_ACCOUNT_CONTRACT.clone().flatten().unwrap().into()),
                 },
             ),
@@ -782,7 +790,7 @@ mod tests {
                 felt!("0xa55"),
                 GenesisClass {
                     compiled_class_hash: DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
-                    casm: DEFAULT_OZ_ACCOUNT_CONTRACT_CASM.clone().into(),
+                    casm: None,
                     sierra: Some(DEFAULT_OZ_ACCOUNT_CONTRACT.clone().flatten().unwrap().into()),
                 },
             ),
@@ -986,7 +994,7 @@ mod tests {
                 DEFAULT_LEGACY_UDC_CLASS_HASH,
                 GenesisClass {
                     sierra: None,
-                    casm: DEFAULT_LEGACY_UDC_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_UDC_CASM.clone().into()),
                     compiled_class_hash: DEFAULT_LEGACY_UDC_COMPILED_CLASS_HASH,
                 },
             ),
@@ -994,7 +1002,7 @@ mod tests {
                 DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH,
                 GenesisClass {
                     sierra: None,
-                    casm: DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone().into()),
                     compiled_class_hash: DEFAULT_LEGACY_ERC20_CONTRACT_COMPILED_CLASS_HASH,
                 },
             ),
@@ -1002,7 +1010,7 @@ mod tests {
                 DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
                 GenesisClass {
                     compiled_class_hash: DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
-                    casm: DEFAULT_OZ_ACCOUNT_CONTRACT_CASM.clone().into(),
+                    casm: None,
                     sierra: Some(DEFAULT_OZ_ACCOUNT_CONTRACT.clone().flatten().unwrap().into()),
                 },
             ),
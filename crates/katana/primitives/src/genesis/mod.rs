@@ -1,6 +1,7 @@
 pub mod allocation;
 pub mod constant;
 pub mod json;
+pub mod predeploy;
 
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
@@ -17,11 +18,10 @@ use self::constant::{
     DEFAULT_LEGACY_ERC20_CONTRACT_CASM, DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH,
     DEFAULT_LEGACY_ERC20_CONTRACT_COMPILED_CLASS_HASH, DEFAULT_LEGACY_UDC_CASM,
     DEFAULT_LEGACY_UDC_CLASS_HASH, DEFAULT_LEGACY_UDC_COMPILED_CLASS_HASH,
-    DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CASM,
-    DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH, DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
-    DEFAULT_UDC_ADDRESS, ERC20_DECIMAL_STORAGE_SLOT, ERC20_NAME_STORAGE_SLOT,
-    ERC20_SYMBOL_STORAGE_SLOT, ERC20_TOTAL_SUPPLY_STORAGE_SLOT,
-    OZ_ACCOUNT_CONTRACT_PUBKEY_STORAGE_SLOT,
+    DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
+    DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH, DEFAULT_UDC_ADDRESS,
+    ERC20_DECIMAL_STORAGE_SLOT, ERC20_NAME_STORAGE_SLOT, ERC20_SYMBOL_STORAGE_SLOT,
+    ERC20_TOTAL_SUPPLY_STORAGE_SLOT, OZ_ACCOUNT_CONTRACT_PUBKEY_STORAGE_SLOT,
 };
 use crate::block::{Block, BlockHash, BlockNumber, GasPrices, Header};
 use crate::class::{ClassHash, CompiledClass, CompiledClassHash, FlattenedSierraClass};
@@ -55,9 +55,10 @@ pub struct GenesisClass {
     /// The compiled class hash of the contract class.
     #[serde_as(as = "UfeHex")]
     pub compiled_class_hash: CompiledClassHash,
-    /// The casm class definition.
+    /// The casm class definition. Only set for legacy classes, as the casm of a Sierra class is
+    /// compiled from its Sierra definition when it is first executed.
     #[serde(skip_serializing)]
-    pub casm: Arc<CompiledClass>,
+    pub casm: Option<Arc<CompiledClass>>,
     /// The sierra class definition.
     #[serde(skip_serializing)]
     pub sierra: Option<Arc<FlattenedSierraClass>>,
@@ -158,7 +159,9 @@ impl Genesis {
             let class_hash = *class_hash;
 
             states.state_updates.declared_classes.insert(class_hash, class.compiled_class_hash);
-            states.declared_compiled_classes.insert(class_hash, class.casm.as_ref().clone());
+            if let Some(casm) = &class.casm {
+                states.declared_compiled_classes.insert(class_hash, casm.as_ref().clone());
+            }
 
             if let Some(sierra) = &class.sierra {
                 states.declared_sierra_classes.insert(class_hash, sierra.as_ref().clone());
@@ -262,7 +265,7 @@ impl Default for Genesis {
                 DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH,
                 GenesisClass {
                     sierra: None,
-                    casm: DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone().into()),
                     compiled_class_hash: DEFAULT_LEGACY_ERC20_CONTRACT_COMPILED_CLASS_HASH,
                 },
             ),
@@ -270,7 +273,7 @@ impl Default for Genesis {
                 DEFAULT_LEGACY_UDC_CLASS_HASH,
                 GenesisClass {
                     sierra: None,
-                    casm: DEFAULT_LEGACY_UDC_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_UDC_CASM.clone().into()),
                     compiled_class_hash: DEFAULT_LEGACY_UDC_COMPILED_CLASS_HASH,
                 },
             ),
@@ -278,7 +281,7 @@ impl Default for Genesis {
                 DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
                 GenesisClass {
                     sierra: Some(DEFAULT_OZ_ACCOUNT_CONTRACT.clone().flatten().unwrap().into()),
-                    casm: DEFAULT_OZ_ACCOUNT_CONTRACT_CASM.clone().into(),
+                    casm: None,
                     compiled_class_hash: DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
                 },
             ),
@@ -310,8 +313,8 @@ mod tests {
         DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH,
         DEFAULT_LEGACY_ERC20_CONTRACT_COMPILED_CLASS_HASH, DEFAULT_LEGACY_UDC_CASM,
         DEFAULT_LEGACY_UDC_CLASS_HASH, DEFAULT_LEGACY_UDC_COMPILED_CLASS_HASH,
-        DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CASM,
-        DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH, DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
+        DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
+        DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
     };
 
     use super::*;
@@ -325,7 +328,7 @@ mod tests {
                 DEFAULT_LEGACY_UDC_CLASS_HASH,
                 GenesisClass {
                     sierra: None,
-                    casm: DEFAULT_LEGACY_UDC_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_UDC_CASM.clone().into()),
                     compiled_class_hash: DEFAULT_LEGACY_UDC_COMPILED_CLASS_HASH,
                 },
             ),
@@ -333,7 +336,7 @@ mod tests {
                 DEFAULT_LEGACY_ERC20_CONTRACT_CLASS_HASH,
                 GenesisClass {
                     sierra: None,
-                    casm: DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone().into(),
+                    casm: Some(DEFAULT_LEGACY_ERC20_CONTRACT_CASM.clone().into()),
                     compiled_class_hash: DEFAULT_LEGACY_ERC20_CONTRACT_COMPILED_CLASS_HASH,
                 },
             ),
@@ -341,7 +344,7 @@ mod tests {
                 DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
                 GenesisClass {
                     compiled_class_hash: DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
-                    casm: DEFAULT_OZ_ACCOUNT_CONTRACT_CASM.clone().into(),
+                    casm: None,
                     sierra: Some(DEFAULT_OZ_ACCOUNT_CONTRACT.clone().flatten().unwrap().into()),
                 },
             ),
@@ -481,8 +484,8 @@ mod tests {
         assert_eq!(actual_block.body, expected_block.body);
 
         assert!(
-            actual_state_updates.declared_compiled_classes.len() == 3,
-            "should be 3 casm classes: udc, erc20, oz account"
+            actual_state_updates.declared_compiled_classes.len() == 2,
+            "should be 2 casm classes: udc, erc20"
         );
         assert!(
             actual_state_updates.declared_sierra_classes.len() == 1,
@@ -544,13 +547,12 @@ mod tests {
             "The default oz account class should be declared"
         );
 
-        assert_eq!(
+        assert!(
             actual_state_updates
                 .declared_compiled_classes
                 .get(&DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH)
-                .unwrap(),
-            &DEFAULT_OZ_ACCOUNT_CONTRACT_CASM.clone(),
-            "The default oz account contract casm class should be declared"
+                .is_none(),
+            "The default oz account contract casm class should be compiled from its sierra class"
         );
 
         assert_eq!(
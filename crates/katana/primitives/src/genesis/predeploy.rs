@@ -0,0 +1,300 @@
+//! Contracts predeployed at genesis from a directory of class artifacts.
+//!
+//! Every class artifact of the directory, Sierra or legacy, is declared in the genesis block and
+//! an instance of it is deployed. The optional `predeploys.json` file of the directory describes
+//! the instances, by artifact name (the file name without its `.json` or `.contract_class.json`
+//! extension), eg:
+//!
+//! ```json
+//! {
+//!     "multicall": {
+//!         "address": "0x1234"
+//!     },
+//!     "test_token": {
+//!         "salt": "0x1",
+//!         "storage": {
+//!             "0x10": "0x5445535420544f4b454e"
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The constructors are not executed, as there is no transaction in the genesis block, so the
+//! state the constructor of a contract would have initialized must be given as its `storage`, and
+//! a non-empty `constructorCalldata` is rejected rather than silently ignored. When the address of
+//! a contract is not given, it is the address the Universal Deployer computes for a non-unique
+//! deployment of the class with the `salt` and no calldata.
+//!
+//! A predeployed contract can't be allocated at the address of another contract of the genesis,
+//! nor can two artifacts of the directory have the same name, eg. `foo.json` and
+//! `foo.contract_class.json`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::U256;
+use serde::{Deserialize, Serialize};
+use starknet::core::utils::get_contract_address;
+
+use super::allocation::GenesisContractAlloc;
+use super::json::{class_artifact_at_path, parse_genesis_class, GenesisJsonError};
+use super::{Genesis, GenesisAllocation, GenesisClass};
+use crate::class::ClassHash;
+use crate::contract::{ContractAddress, StorageKey, StorageValue};
+use crate::FieldElement;
+
+/// The name of the file describing the predeployed contracts of a directory.
+pub const PREDEPLOYS_FILE_NAME: &str = "predeploys.json";
+
+/// The description of a predeployed contract.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PredeployJson {
+    /// The address of the contract. If not provided, the address is computed from the class hash
+    /// and the `salt`.
+    pub address: Option<ContractAddress>,
+    /// The salt the address of the contract is computed with. Defaults to zero.
+    pub salt: Option<FieldElement>,
+    /// The calldata of the constructor, which must be empty as the constructor is not executed.
+    #[serde(default)]
+    pub constructor_calldata: Vec<FieldElement>,
+    pub balance: Option<U256>,
+    pub storage: Option<HashMap<StorageKey, StorageValue>>,
+}
+
+/// A contract predeployed at genesis, along with its class.
+#[derive(Debug, Clone)]
+pub struct Predeploy {
+    /// The name of the artifact of the class.
+    pub name: String,
+    pub address: ContractAddress,
+    pub class_hash: ClassHash,
+    pub class: GenesisClass,
+    pub balance: Option<U256>,
+    pub storage: Option<HashMap<StorageKey, StorageValue>>,
+}
+
+/// Loads the contracts to predeploy from the class artifacts of the directory at `path`, sorted by
+/// name.
+pub fn load_predeploys<P: AsRef<Path>>(path: P) -> Result<Vec<Predeploy>, GenesisJsonError> {
+    let dir = path.as_ref().to_path_buf();
+
+    let manifest_path = dir.join(PREDEPLOYS_FILE_NAME);
+    let mut manifest: HashMap<String, PredeployJson> = if manifest_path.exists() {
+        serde_json::from_str(&fs::read_to_string(&manifest_path).map_err(|source| {
+            GenesisJsonError::FileNotFound { source, path: manifest_path.clone() }
+        })?)?
+    } else {
+        HashMap::new()
+    };
+
+    let mut artifacts = fs::read_dir(&dir)
+        .map_err(|source| GenesisJsonError::FileNotFound { source, path: dir.clone() })?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = artifact_name(&path)?;
+            path.is_file().then_some((name, path))
+        })
+        .collect::<Vec<(String, PathBuf)>>();
+    artifacts.sort();
+
+    if let Some([(name, first), (_, second)]) = artifacts.windows(2).find(|w| w[0].0 == w[1].0) {
+        return Err(GenesisJsonError::Other(anyhow::anyhow!(
+            "The class artifacts {} and {} are both named `{name}`",
+            first.display(),
+            second.display()
+        )));
+    }
+
+    let mut predeploys = Vec::with_capacity(artifacts.len());
+    for (name, path) in artifacts {
+        let artifact = class_artifact_at_path(dir.clone(), &path)?;
+        let (class_hash, class) = parse_genesis_class(artifact, None)?;
+
+        let PredeployJson { address, salt, constructor_calldata, balance, storage } =
+            manifest.remove(&name).unwrap_or_default();
+
+        if !constructor_calldata.is_empty() {
+            return Err(GenesisJsonError::Other(anyhow::anyhow!(
+                "The constructor of the predeployed contract `{name}` is not executed, its state \
+                 must be given as `storage` instead of `constructorCalldata`"
+            )));
+        }
+
+        let address = address.unwrap_or_else(|| {
+            let salt = salt.unwrap_or(FieldElement::ZERO);
+            get_contract_address(salt, class_hash, &[], FieldElement::ZERO).into()
+        });
+
+        predeploys.push(Predeploy { name, address, class_hash, class, balance, storage });
+    }
+
+    // the contracts described without an artifact are most likely typos
+    if let Some(name) = manifest.into_keys().min() {
+        return Err(GenesisJsonError::Other(anyhow::anyhow!(
+            "No class artifact for the predeployed contract `{name}` in {}",
+            dir.display()
+        )));
+    }
+
+    Ok(predeploys)
+}
+
+/// Returns the name of the class artifact at `path`, `None` if the file is not a class artifact.
+fn artifact_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    if file_name == PREDEPLOYS_FILE_NAME
+        // the CASM of the Sierra classes, and the index of the artifacts written by Scarb
+        || file_name.ends_with(".compiled_contract_class.json")
+        || file_name.ends_with(".starknet_artifacts.json")
+    {
+        return None;
+    }
+
+    let name = file_name.strip_suffix(".json")?;
+    Some(name.strip_suffix(".contract_class").unwrap_or(name).to_string())
+}
+
+impl Genesis {
+    /// Declares the classes of the predeployed contracts, and allocates the contracts.
+    ///
+    /// Fails if a contract is predeployed at the address of another contract of the genesis, be it
+    /// an allocation, the fee token or the Universal Deployer.
+    pub fn extend_predeploys<T>(&mut self, predeploys: T) -> Result<(), GenesisJsonError>
+    where
+        T: IntoIterator<Item = Predeploy>,
+    {
+        for predeploy in predeploys {
+            let address = predeploy.address;
+            if self.allocations.contains_key(&address)
+                || self.fee_token.address == address
+                || self.universal_deployer.as_ref().is_some_and(|udc| udc.address == address)
+            {
+                return Err(GenesisJsonError::Other(anyhow::anyhow!(
+                    "The predeployed contract `{}` is at the address {address} of another \
+                     contract of the genesis",
+                    predeploy.name
+                )));
+            }
+
+            self.classes.insert(predeploy.class_hash, predeploy.class);
+            self.allocations.insert(
+                predeploy.address,
+                GenesisAllocation::Contract(GenesisContractAlloc {
+                    class_hash: Some(predeploy.class_hash),
+                    balance: predeploy.balance,
+                    nonce: None,
+                    storage: predeploy.storage,
+                }),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    fn predeploy_dir(manifest: Option<&str>) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let artifacts = Path::new(env!("CARGO_MANIFEST_DIR")).join("../contracts/compiled");
+
+        fs::copy(artifacts.join("erc20.json"), dir.path().join("test_token.json")).unwrap();
+        fs::copy(
+            artifacts.join("cairo1_contract.json"),
+            dir.path().join("pkg_Multicall.contract_class.json"),
+        )
+        .unwrap();
+        fs::write(dir.path().join("pkg.starknet_artifacts.json"), "{}").unwrap();
+
+        if let Some(manifest) = manifest {
+            fs::write(dir.path().join(PREDEPLOYS_FILE_NAME), manifest).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn predeploy_artifacts_of_directory() {
+        let dir = predeploy_dir(Some(
+            r#"{
+                "test_token": {
+                    "salt": "0x1",
+                    "storage": { "0x10": "0x20" }
+                },
+                "pkg_Multicall": { "address": "0x1234" }
+            }"#,
+        ));
+
+        let predeploys = load_predeploys(dir.path()).unwrap();
+        let names = predeploys.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["pkg_Multicall", "test_token"]);
+
+        let multicall = &predeploys[0];
+        assert_eq!(multicall.address, ContractAddress::from(felt!("0x1234")));
+        assert!(multicall.class.sierra.is_some());
+
+        let token = &predeploys[1];
+        assert!(token.class.casm.is_some());
+        assert_eq!(
+            token.address,
+            get_contract_address(felt!("0x1"), token.class_hash, &[], FieldElement::ZERO).into()
+        );
+
+        let mut genesis = Genesis::default();
+        genesis.extend_predeploys(predeploys.clone()).unwrap();
+
+        let states = genesis.state_updates();
+        assert_eq!(
+            states.state_updates.contract_updates.get(&token.address),
+            Some(&token.class_hash)
+        );
+        assert_eq!(
+            states.state_updates.storage_updates.get(&token.address).unwrap().get(&felt!("0x10")),
+            Some(&felt!("0x20"))
+        );
+        assert!(states.declared_sierra_classes.contains_key(&multicall.class_hash));
+    }
+
+    #[test]
+    fn predeploys_without_manifest() {
+        let dir = predeploy_dir(None);
+        assert_eq!(load_predeploys(dir.path()).unwrap().len(), 2);
+
+        let dir = predeploy_dir(Some(r#"{ "oracle": {} }"#));
+        assert!(load_predeploys(dir.path()).is_err());
+    }
+
+    #[test]
+    fn invalid_predeploys() {
+        // the constructors are not executed
+        let dir = predeploy_dir(Some(r#"{ "test_token": { "constructorCalldata": ["0x2"] } }"#));
+        let error = load_predeploys(dir.path()).unwrap_err().to_string();
+        assert!(error.contains("`test_token` is not executed"));
+
+        let dir = predeploy_dir(None);
+        let artifacts = Path::new(env!("CARGO_MANIFEST_DIR")).join("../contracts/compiled");
+        fs::copy(artifacts.join("erc20.json"), dir.path().join("test_token.contract_class.json"))
+            .unwrap();
+        let error = load_predeploys(dir.path()).unwrap_err().to_string();
+        assert!(error.contains("are both named `test_token`"));
+
+        // a predeployed contract can't replace another contract of the genesis
+        let dir = predeploy_dir(None);
+        let predeploys = load_predeploys(dir.path()).unwrap();
+        let mut genesis = Genesis::default();
+        let alloc = GenesisAllocation::Contract(GenesisContractAlloc::default());
+        genesis.extend_allocations([(felt!("0x1234").into(), alloc)]);
+        for address in [genesis.fee_token.address, felt!("0x1234").into()] {
+            let mut predeploy = predeploys[1].clone();
+            predeploy.address = address;
+            assert!(genesis.clone().extend_predeploys([predeploy]).is_err());
+        }
+        assert!(genesis.extend_predeploys(predeploys).is_ok());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod block;
 pub mod chain;
+pub mod chain_spec;
 pub mod class;
 pub mod contract;
 pub mod env;
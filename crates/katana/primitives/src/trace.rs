@@ -4,6 +4,7 @@ use crate::class::ClassHash;
 use crate::contract::ContractAddress;
 use crate::event::OrderedEvent;
 use crate::message::OrderedL2ToL1Message;
+use crate::state::StateUpdates;
 use crate::FieldElement;
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -22,6 +23,8 @@ pub struct TxExecInfo {
     pub actual_resources: HashMap<String, u64>,
     /// Error string for reverted transactions; [None] if transaction execution was successful.
     pub revert_error: Option<String>,
+    /// The changes made to the state by the transaction; [None] if they weren't recorded.
+    pub state_diff: Option<StateUpdates>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
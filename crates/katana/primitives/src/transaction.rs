@@ -17,6 +17,20 @@ pub type TxHash = FieldElement;
 /// The sequential number for all the transactions.
 pub type TxNumber = u64;
 
+/// Opaque metadata submitted along with a transaction, describing where it comes from, eg. the
+/// game client and session that sent it. It is never part of the transaction nor of its receipt,
+/// so it doesn't affect the chain data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TxOrigin {
+    /// The id of the client that sent the transaction.
+    pub client_id: Option<String>,
+    /// The session the transaction was sent in.
+    pub session: Option<String>,
+    /// Any other data, which is left uninterpreted.
+    pub data: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Tx {
@@ -44,6 +58,22 @@ impl<'a> From<TxRef<'a>> for Tx {
     }
 }
 
+impl Tx {
+    /// Returns the address of the account that sent the transaction, or `None` for L1 handler
+    /// transactions which aren't sent by an account.
+    pub fn sender_address(&self) -> Option<ContractAddress> {
+        match self {
+            Tx::Invoke(InvokeTx::V1(tx)) => Some(tx.sender_address),
+            Tx::Invoke(InvokeTx::V3(tx)) => Some(tx.sender_address),
+            Tx::Declare(DeclareTx::V1(tx)) => Some(tx.sender_address),
+            Tx::Declare(DeclareTx::V2(tx)) => Some(tx.sender_address),
+            Tx::Declare(DeclareTx::V3(tx)) => Some(tx.sender_address),
+            Tx::DeployAccount(tx) => Some(tx.contract_address()),
+            Tx::L1Handler(_) => None,
+        }
+    }
+}
+
 /// Represents a transaction that has all the necessary data to be executed.
 #[derive(Debug, Clone, From)]
 pub enum ExecutableTx {
@@ -54,6 +84,22 @@ pub enum ExecutableTx {
 }
 
 impl ExecutableTx {
+    /// Returns the address of the account that sent the transaction, or `None` for L1 handler
+    /// transactions which aren't sent by an account.
+    pub fn sender_address(&self) -> Option<ContractAddress> {
+        match self {
+            ExecutableTx::Invoke(InvokeTx::V1(tx)) => Some(tx.sender_address),
+            ExecutableTx::Invoke(InvokeTx::V3(tx)) => Some(tx.sender_address),
+            ExecutableTx::Declare(tx) => match &tx.transaction {
+                DeclareTx::V1(tx) => Some(tx.sender_address),
+                DeclareTx::V2(tx) => Some(tx.sender_address),
+                DeclareTx::V3(tx) => Some(tx.sender_address),
+            },
+            ExecutableTx::DeployAccount(tx) => Some(tx.contract_address()),
+            ExecutableTx::L1Handler(_) => None,
+        }
+    }
+
     pub fn tx_ref(&self) -> TxRef<'_> {
         match self {
             ExecutableTx::Invoke(tx) => TxRef::Invoke(tx),
@@ -0,0 +1,11 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // only the messages are generated, the services that use them are defined by each component
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(false)
+        .compile(&["proto/types.proto"], &["proto"])?;
+
+    println!("cargo:rerun-if-changed=proto");
+
+    Ok(())
+}
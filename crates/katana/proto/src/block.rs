@@ -0,0 +1,124 @@
+use katana_primitives::block::{GasPrices, Header, SealedBlock, SealedHeader};
+use katana_primitives::version::Version;
+
+use crate::{
+    felt_from_bytes, felt_to_bytes, required, types as proto, u128_from_bytes, u128_to_bytes,
+    ProtoError,
+};
+
+impl From<Header> for proto::Header {
+    fn from(header: Header) -> Self {
+        Self {
+            parent_hash: felt_to_bytes(header.parent_hash),
+            number: header.number,
+            eth_gas_price: u128_to_bytes(header.gas_prices.eth),
+            strk_gas_price: u128_to_bytes(header.gas_prices.strk),
+            timestamp: header.timestamp,
+            state_root: felt_to_bytes(header.state_root),
+            sequencer_address: felt_to_bytes(header.sequencer_address.into()),
+            version: header.version.to_string(),
+        }
+    }
+}
+
+impl TryFrom<proto::Header> for Header {
+    type Error = ProtoError;
+
+    fn try_from(header: proto::Header) -> Result<Self, Self::Error> {
+        Ok(Self {
+            parent_hash: felt_from_bytes(&header.parent_hash, "parent_hash")?,
+            number: header.number,
+            gas_prices: GasPrices::new(
+                u128_from_bytes(&header.eth_gas_price, "eth_gas_price")?,
+                u128_from_bytes(&header.strk_gas_price, "strk_gas_price")?,
+            ),
+            timestamp: header.timestamp,
+            state_root: felt_from_bytes(&header.state_root, "state_root")?,
+            sequencer_address: felt_from_bytes(&header.sequencer_address, "sequencer_address")?
+                .into(),
+            version: Version::parse(&header.version)
+                .map_err(|_| ProtoError::InvalidVersion(header.version))?,
+        })
+    }
+}
+
+impl From<SealedBlock> for proto::Block {
+    fn from(block: SealedBlock) -> Self {
+        Self {
+            hash: felt_to_bytes(block.header.hash),
+            header: Some(block.header.header.into()),
+            transactions: block.body.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<proto::Block> for SealedBlock {
+    type Error = ProtoError;
+
+    fn try_from(block: proto::Block) -> Result<Self, Self::Error> {
+        let header = SealedHeader {
+            hash: felt_from_bytes(&block.hash, "hash")?,
+            header: required(block.header, "header")?.try_into()?,
+        };
+        let body =
+            block.transactions.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?;
+
+        Ok(Self { header, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::transaction::{InvokeTx, InvokeTxV1, Tx, TxWithHash};
+    use katana_primitives::version::CURRENT_STARKNET_VERSION;
+    use katana_primitives::FieldElement;
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn block_roundtrip() {
+        let header = Header {
+            parent_hash: FieldElement::ONE,
+            number: 5,
+            gas_prices: GasPrices::new(100, u128::MAX),
+            timestamp: 1710000000,
+            state_root: FieldElement::TWO,
+            sequencer_address: FieldElement::from(3u8).into(),
+            version: CURRENT_STARKNET_VERSION,
+        };
+        let tx = TxWithHash {
+            hash: FieldElement::from(7u8),
+            transaction: Tx::Invoke(InvokeTx::V1(InvokeTxV1 {
+                sender_address: FieldElement::ONE.into(),
+                calldata: vec![FieldElement::MAX],
+                max_fee: 1000,
+                ..Default::default()
+            })),
+        };
+        let block = SealedBlock {
+            header: SealedHeader { hash: FieldElement::from(42u8), header },
+            body: vec![tx],
+        };
+
+        let encoded = proto::Block::from(block.clone()).encode_to_vec();
+        let decoded =
+            SealedBlock::try_from(proto::Block::decode(encoded.as_slice()).unwrap()).unwrap();
+
+        assert_eq!(decoded.header.hash, block.header.hash);
+        assert_eq!(decoded.header.header, block.header.header);
+        assert_eq!(decoded.body, block.body);
+    }
+
+    #[test]
+    fn reject_invalid_header() {
+        let header = proto::Header { parent_hash: vec![1; 31], ..Default::default() };
+        assert!(matches!(
+            Header::try_from(header),
+            Err(ProtoError::InvalidLength { field: "parent_hash", expected: 32, actual: 31 })
+        ));
+
+        let block = proto::Block { hash: vec![0; 32], ..Default::default() };
+        assert!(matches!(SealedBlock::try_from(block), Err(ProtoError::MissingField("header"))));
+    }
+}
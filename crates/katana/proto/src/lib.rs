@@ -0,0 +1,81 @@
+//! Protobuf definitions of the Katana core types.
+//!
+//! The messages of [`types`] are the wire format of the blocks, transactions, receipts, events
+//! and state diffs exchanged by the Katana components, and are converted from and to their
+//! `katana-primitives` counterparts with the `From` and `TryFrom` implementations of this crate.
+//! Decoding a message fails with a [`ProtoError`] when it doesn't describe a valid value.
+
+mod block;
+mod receipt;
+mod state;
+mod transaction;
+
+use alloy_primitives::B256;
+use katana_primitives::FieldElement;
+
+/// The generated protobuf messages.
+pub mod types {
+    include!(concat!(env!("OUT_DIR"), "/katana.types.rs"));
+}
+
+/// Errors that can occur when converting a protobuf message into a Katana type.
+#[derive(Debug, thiserror::Error)]
+pub enum ProtoError {
+    #[error("missing required field `{0}`")]
+    MissingField(&'static str),
+    #[error("invalid length for `{field}`: expected {expected} bytes, got {actual}")]
+    InvalidLength { field: &'static str, expected: usize, actual: usize },
+    #[error("value of `{0}` is not a valid field element")]
+    InvalidFelt(&'static str),
+    #[error("unknown value {value} for enum `{field}`")]
+    UnknownEnumValue { field: &'static str, value: i32 },
+    #[error("invalid protocol version `{0}`")]
+    InvalidVersion(String),
+}
+
+pub(crate) fn required<T>(value: Option<T>, field: &'static str) -> Result<T, ProtoError> {
+    value.ok_or(ProtoError::MissingField(field))
+}
+
+pub(crate) fn felt_to_bytes(felt: FieldElement) -> Vec<u8> {
+    felt.to_bytes_be().to_vec()
+}
+
+pub(crate) fn felts_to_bytes(felts: Vec<FieldElement>) -> Vec<Vec<u8>> {
+    felts.into_iter().map(felt_to_bytes).collect()
+}
+
+pub(crate) fn felt_from_bytes(
+    bytes: &[u8],
+    field: &'static str,
+) -> Result<FieldElement, ProtoError> {
+    let bytes = fixed_bytes::<32>(bytes, field)?;
+    FieldElement::from_bytes_be(&bytes).map_err(|_| ProtoError::InvalidFelt(field))
+}
+
+pub(crate) fn felts_from_bytes(
+    values: &[Vec<u8>],
+    field: &'static str,
+) -> Result<Vec<FieldElement>, ProtoError> {
+    values.iter().map(|bytes| felt_from_bytes(bytes, field)).collect()
+}
+
+pub(crate) fn u128_to_bytes(value: u128) -> Vec<u8> {
+    value.to_be_bytes().to_vec()
+}
+
+pub(crate) fn u128_from_bytes(bytes: &[u8], field: &'static str) -> Result<u128, ProtoError> {
+    Ok(u128::from_be_bytes(fixed_bytes::<16>(bytes, field)?))
+}
+
+pub(crate) fn b256_from_bytes(bytes: &[u8], field: &'static str) -> Result<B256, ProtoError> {
+    Ok(B256::from(fixed_bytes::<32>(bytes, field)?))
+}
+
+fn fixed_bytes<const N: usize>(bytes: &[u8], field: &'static str) -> Result<[u8; N], ProtoError> {
+    bytes.try_into().map_err(|_| ProtoError::InvalidLength {
+        field,
+        expected: N,
+        actual: bytes.len(),
+    })
+}
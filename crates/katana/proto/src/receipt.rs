@@ -0,0 +1,287 @@
+use katana_primitives::receipt::{
+    DeclareTxReceipt, DeployAccountTxReceipt, Event, InvokeTxReceipt, L1HandlerTxReceipt,
+    MessageToL1, Receipt, TxExecutionResources,
+};
+
+use crate::{
+    b256_from_bytes, felt_from_bytes, felt_to_bytes, felts_from_bytes, felts_to_bytes, required,
+    types as proto, u128_from_bytes, u128_to_bytes, ProtoError,
+};
+
+impl From<Event> for proto::Event {
+    fn from(event: Event) -> Self {
+        Self {
+            from_address: felt_to_bytes(event.from_address.into()),
+            keys: felts_to_bytes(event.keys),
+            data: felts_to_bytes(event.data),
+        }
+    }
+}
+
+impl TryFrom<proto::Event> for Event {
+    type Error = ProtoError;
+
+    fn try_from(event: proto::Event) -> Result<Self, Self::Error> {
+        Ok(Self {
+            from_address: felt_from_bytes(&event.from_address, "from_address")?.into(),
+            keys: felts_from_bytes(&event.keys, "keys")?,
+            data: felts_from_bytes(&event.data, "data")?,
+        })
+    }
+}
+
+impl From<MessageToL1> for proto::MessageToL1 {
+    fn from(message: MessageToL1) -> Self {
+        Self {
+            from_address: felt_to_bytes(message.from_address.into()),
+            to_address: felt_to_bytes(message.to_address),
+            payload: felts_to_bytes(message.payload),
+        }
+    }
+}
+
+impl TryFrom<proto::MessageToL1> for MessageToL1 {
+    type Error = ProtoError;
+
+    fn try_from(message: proto::MessageToL1) -> Result<Self, Self::Error> {
+        Ok(Self {
+            from_address: felt_from_bytes(&message.from_address, "from_address")?.into(),
+            to_address: felt_from_bytes(&message.to_address, "to_address")?,
+            payload: felts_from_bytes(&message.payload, "payload")?,
+        })
+    }
+}
+
+impl From<TxExecutionResources> for proto::ExecutionResources {
+    fn from(resources: TxExecutionResources) -> Self {
+        Self {
+            steps: resources.steps,
+            memory_holes: resources.memory_holes,
+            range_check_builtin: resources.range_check_builtin,
+            pedersen_builtin: resources.pedersen_builtin,
+            poseidon_builtin: resources.poseidon_builtin,
+            ec_op_builtin: resources.ec_op_builtin,
+            ecdsa_builtin: resources.ecdsa_builtin,
+            bitwise_builtin: resources.bitwise_builtin,
+            keccak_builtin: resources.keccak_builtin,
+            segment_arena_builtin: resources.segment_arena_builtin,
+        }
+    }
+}
+
+impl From<proto::ExecutionResources> for TxExecutionResources {
+    fn from(resources: proto::ExecutionResources) -> Self {
+        Self {
+            steps: resources.steps,
+            memory_holes: resources.memory_holes,
+            range_check_builtin: resources.range_check_builtin,
+            pedersen_builtin: resources.pedersen_builtin,
+            poseidon_builtin: resources.poseidon_builtin,
+            ec_op_builtin: resources.ec_op_builtin,
+            ecdsa_builtin: resources.ecdsa_builtin,
+            bitwise_builtin: resources.bitwise_builtin,
+            keccak_builtin: resources.keccak_builtin,
+            segment_arena_builtin: resources.segment_arena_builtin,
+        }
+    }
+}
+
+impl From<Receipt> for proto::Receipt {
+    fn from(receipt: Receipt) -> Self {
+        // the fields shared by all the receipts, and those specific to some of them
+        let (r#type, fee, events, messages, revert_error, resources, message_hash, address) =
+            match receipt {
+                Receipt::Invoke(r) => (
+                    proto::TransactionType::Invoke,
+                    r.actual_fee,
+                    r.events,
+                    r.messages_sent,
+                    r.revert_error,
+                    r.execution_resources,
+                    None,
+                    None,
+                ),
+                Receipt::Declare(r) => (
+                    proto::TransactionType::Declare,
+                    r.actual_fee,
+                    r.events,
+                    r.messages_sent,
+                    r.revert_error,
+                    r.execution_resources,
+                    None,
+                    None,
+                ),
+                Receipt::L1Handler(r) => (
+                    proto::TransactionType::L1Handler,
+                    r.actual_fee,
+                    r.events,
+                    r.messages_sent,
+                    r.revert_error,
+                    r.execution_resources,
+                    Some(r.message_hash.to_vec()),
+                    None,
+                ),
+                Receipt::DeployAccount(r) => (
+                    proto::TransactionType::DeployAccount,
+                    r.actual_fee,
+                    r.events,
+                    r.messages_sent,
+                    r.revert_error,
+                    r.execution_resources,
+                    None,
+                    Some(felt_to_bytes(r.contract_address.into())),
+                ),
+            };
+
+        Self {
+            r#type: r#type as i32,
+            actual_fee: u128_to_bytes(fee),
+            events: events.into_iter().map(Into::into).collect(),
+            messages_sent: messages.into_iter().map(Into::into).collect(),
+            revert_error,
+            execution_resources: Some(resources.into()),
+            message_hash,
+            contract_address: address,
+        }
+    }
+}
+
+impl TryFrom<proto::Receipt> for Receipt {
+    type Error = ProtoError;
+
+    fn try_from(receipt: proto::Receipt) -> Result<Self, Self::Error> {
+        let r#type = proto::TransactionType::try_from(receipt.r#type)
+            .map_err(|_| ProtoError::UnknownEnumValue { field: "type", value: receipt.r#type })?;
+
+        let actual_fee = u128_from_bytes(&receipt.actual_fee, "actual_fee")?;
+        let events = receipt.events.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?;
+        let messages_sent =
+            receipt.messages_sent.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?;
+        let revert_error = receipt.revert_error;
+        let execution_resources =
+            required(receipt.execution_resources, "execution_resources")?.into();
+
+        Ok(match r#type {
+            proto::TransactionType::Invoke => Receipt::Invoke(InvokeTxReceipt {
+                actual_fee,
+                events,
+                messages_sent,
+                revert_error,
+                execution_resources,
+            }),
+            proto::TransactionType::Declare => Receipt::Declare(DeclareTxReceipt {
+                actual_fee,
+                events,
+                messages_sent,
+                revert_error,
+                execution_resources,
+            }),
+            proto::TransactionType::L1Handler => Receipt::L1Handler(L1HandlerTxReceipt {
+                actual_fee,
+                events,
+                message_hash: b256_from_bytes(
+                    &required(receipt.message_hash, "message_hash")?,
+                    "message_hash",
+                )?,
+                messages_sent,
+                revert_error,
+                execution_resources,
+            }),
+            proto::TransactionType::DeployAccount => {
+                Receipt::DeployAccount(DeployAccountTxReceipt {
+                    actual_fee,
+                    events,
+                    messages_sent,
+                    revert_error,
+                    execution_resources,
+                    contract_address: felt_from_bytes(
+                        &required(receipt.contract_address, "contract_address")?,
+                        "contract_address",
+                    )?
+                    .into(),
+                })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use katana_primitives::FieldElement;
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn receipt_roundtrip() {
+        let events = vec![Event {
+            from_address: FieldElement::ONE.into(),
+            keys: vec![FieldElement::TWO],
+            data: vec![FieldElement::MAX, FieldElement::ZERO],
+        }];
+        let messages_sent = vec![MessageToL1 {
+            from_address: FieldElement::ONE.into(),
+            to_address: FieldElement::TWO,
+            payload: vec![FieldElement::ONE],
+        }];
+        let execution_resources = TxExecutionResources {
+            steps: 100,
+            memory_holes: Some(3),
+            pedersen_builtin: Some(0),
+            ..Default::default()
+        };
+
+        let receipts = vec![
+            Receipt::Invoke(InvokeTxReceipt {
+                actual_fee: u128::MAX,
+                events: events.clone(),
+                messages_sent: messages_sent.clone(),
+                revert_error: Some("reverted".to_string()),
+                execution_resources: execution_resources.clone(),
+            }),
+            Receipt::L1Handler(L1HandlerTxReceipt {
+                actual_fee: 0,
+                events: vec![],
+                message_hash: B256::repeat_byte(1),
+                messages_sent,
+                revert_error: None,
+                execution_resources: execution_resources.clone(),
+            }),
+            Receipt::DeployAccount(DeployAccountTxReceipt {
+                actual_fee: 10,
+                events,
+                messages_sent: vec![],
+                revert_error: None,
+                execution_resources,
+                contract_address: FieldElement::TWO.into(),
+            }),
+        ];
+
+        for receipt in receipts {
+            let encoded = proto::Receipt::from(receipt.clone()).encode_to_vec();
+            let decoded = proto::Receipt::decode(encoded.as_slice()).unwrap();
+            assert_eq!(Receipt::try_from(decoded).unwrap(), receipt);
+        }
+    }
+
+    #[test]
+    fn reject_receipt_without_specific_fields() {
+        let receipt = proto::Receipt {
+            r#type: proto::TransactionType::L1Handler as i32,
+            actual_fee: vec![0; 16],
+            execution_resources: Some(Default::default()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Receipt::try_from(receipt),
+            Err(ProtoError::MissingField("message_hash"))
+        ));
+
+        let receipt = proto::Receipt { r#type: 10, ..Default::default() };
+        assert!(matches!(
+            Receipt::try_from(receipt),
+            Err(ProtoError::UnknownEnumValue { field: "type", value: 10 })
+        ));
+    }
+}
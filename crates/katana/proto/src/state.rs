@@ -0,0 +1,143 @@
+use katana_primitives::state::StateUpdates;
+
+use crate::{felt_from_bytes, felt_to_bytes, types as proto, ProtoError};
+
+impl From<StateUpdates> for proto::StateDiff {
+    fn from(updates: StateUpdates) -> Self {
+        // big-endian bytes sort in the same order as the values they encode
+        let mut nonces = updates
+            .nonce_updates
+            .into_iter()
+            .map(|(address, nonce)| proto::Nonce {
+                address: felt_to_bytes(address.into()),
+                nonce: felt_to_bytes(nonce),
+            })
+            .collect::<Vec<_>>();
+        nonces.sort_by(|a, b| a.address.cmp(&b.address));
+
+        let mut storage_diffs = updates
+            .storage_updates
+            .into_iter()
+            .map(|(address, storage)| {
+                let mut entries = storage
+                    .into_iter()
+                    .map(|(key, value)| proto::StorageEntry {
+                        key: felt_to_bytes(key),
+                        value: felt_to_bytes(value),
+                    })
+                    .collect::<Vec<_>>();
+                entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+                proto::StorageDiff { address: felt_to_bytes(address.into()), entries }
+            })
+            .collect::<Vec<_>>();
+        storage_diffs.sort_by(|a, b| a.address.cmp(&b.address));
+
+        let mut contract_updates = updates
+            .contract_updates
+            .into_iter()
+            .map(|(address, class_hash)| proto::ContractUpdate {
+                address: felt_to_bytes(address.into()),
+                class_hash: felt_to_bytes(class_hash),
+            })
+            .collect::<Vec<_>>();
+        contract_updates.sort_by(|a, b| a.address.cmp(&b.address));
+
+        let mut declared_classes = updates
+            .declared_classes
+            .into_iter()
+            .map(|(class_hash, compiled_class_hash)| proto::DeclaredClass {
+                class_hash: felt_to_bytes(class_hash),
+                compiled_class_hash: felt_to_bytes(compiled_class_hash),
+            })
+            .collect::<Vec<_>>();
+        declared_classes.sort_by(|a, b| a.class_hash.cmp(&b.class_hash));
+
+        Self { nonces, storage_diffs, contract_updates, declared_classes }
+    }
+}
+
+impl TryFrom<proto::StateDiff> for StateUpdates {
+    type Error = ProtoError;
+
+    fn try_from(diff: proto::StateDiff) -> Result<Self, Self::Error> {
+        let mut updates = StateUpdates::default();
+
+        for nonce in diff.nonces {
+            let address = felt_from_bytes(&nonce.address, "address")?.into();
+            updates.nonce_updates.insert(address, felt_from_bytes(&nonce.nonce, "nonce")?);
+        }
+
+        for storage in diff.storage_diffs {
+            let address = felt_from_bytes(&storage.address, "address")?.into();
+            let entries = updates.storage_updates.entry(address).or_default();
+            for entry in storage.entries {
+                let key = felt_from_bytes(&entry.key, "key")?;
+                entries.insert(key, felt_from_bytes(&entry.value, "value")?);
+            }
+        }
+
+        for contract in diff.contract_updates {
+            let address = felt_from_bytes(&contract.address, "address")?.into();
+            let class_hash = felt_from_bytes(&contract.class_hash, "class_hash")?;
+            updates.contract_updates.insert(address, class_hash);
+        }
+
+        for class in diff.declared_classes {
+            let class_hash = felt_from_bytes(&class.class_hash, "class_hash")?;
+            let compiled_class_hash =
+                felt_from_bytes(&class.compiled_class_hash, "compiled_class_hash")?;
+            updates.declared_classes.insert(class_hash, compiled_class_hash);
+        }
+
+        Ok(updates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use katana_primitives::FieldElement;
+    use prost::Message;
+
+    use super::*;
+
+    fn state_updates() -> StateUpdates {
+        let mut updates = StateUpdates::default();
+        for i in 1..20u64 {
+            let address = FieldElement::from(i).into();
+            updates.nonce_updates.insert(address, FieldElement::from(i * 2));
+            updates.contract_updates.insert(address, FieldElement::from(i * 3));
+            updates.declared_classes.insert(FieldElement::from(i * 3), FieldElement::from(i * 5));
+            updates.storage_updates.insert(
+                address,
+                HashMap::from([
+                    (FieldElement::ONE, FieldElement::from(i)),
+                    (FieldElement::MAX, FieldElement::ZERO),
+                ]),
+            );
+        }
+        updates
+    }
+
+    #[test]
+    fn state_diff_roundtrip() {
+        let updates = state_updates();
+
+        let encoded = proto::StateDiff::from(updates.clone()).encode_to_vec();
+        let decoded = proto::StateDiff::decode(encoded.as_slice()).unwrap();
+        assert_eq!(StateUpdates::try_from(decoded).unwrap(), updates);
+    }
+
+    #[test]
+    fn state_diff_encoding_is_deterministic() {
+        // the hash maps of both updates are iterated in different orders
+        let encoded = proto::StateDiff::from(state_updates()).encode_to_vec();
+        assert_eq!(proto::StateDiff::from(state_updates()).encode_to_vec(), encoded);
+
+        let diff = proto::StateDiff::from(state_updates());
+        assert!(diff.nonces.windows(2).all(|w| w[0].address < w[1].address));
+        assert!(diff.storage_diffs[0].entries.windows(2).all(|w| w[0].key < w[1].key));
+    }
+}
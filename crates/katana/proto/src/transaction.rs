@@ -0,0 +1,521 @@
+use katana_primitives::chain::ChainId;
+use katana_primitives::transaction::{
+    DeclareTx, DeclareTxV1, DeclareTxV2, DeclareTxV3, DeployAccountTx, DeployAccountTxV1,
+    DeployAccountTxV3, InvokeTx, InvokeTxV1, InvokeTxV3, L1HandlerTx, Tx, TxWithHash,
+};
+use starknet::core::types::{DataAvailabilityMode, ResourceBounds, ResourceBoundsMapping};
+
+use crate::types::{self as proto, transaction};
+use crate::{
+    b256_from_bytes, felt_from_bytes, felt_to_bytes, felts_from_bytes, felts_to_bytes, required,
+    u128_from_bytes, u128_to_bytes, ProtoError,
+};
+
+impl From<TxWithHash> for proto::Transaction {
+    fn from(tx: TxWithHash) -> Self {
+        let transaction = match tx.transaction {
+            Tx::Invoke(InvokeTx::V1(tx)) => transaction::Transaction::InvokeV1(tx.into()),
+            Tx::Invoke(InvokeTx::V3(tx)) => transaction::Transaction::InvokeV3(tx.into()),
+            Tx::Declare(DeclareTx::V1(tx)) => transaction::Transaction::DeclareV1(tx.into()),
+            Tx::Declare(DeclareTx::V2(tx)) => transaction::Transaction::DeclareV2(tx.into()),
+            Tx::Declare(DeclareTx::V3(tx)) => transaction::Transaction::DeclareV3(tx.into()),
+            Tx::L1Handler(tx) => transaction::Transaction::L1Handler(tx.into()),
+            Tx::DeployAccount(DeployAccountTx::V1(tx)) => {
+                transaction::Transaction::DeployAccountV1(tx.into())
+            }
+            Tx::DeployAccount(DeployAccountTx::V3(tx)) => {
+                transaction::Transaction::DeployAccountV3(tx.into())
+            }
+        };
+
+        Self { hash: felt_to_bytes(tx.hash), transaction: Some(transaction) }
+    }
+}
+
+impl TryFrom<proto::Transaction> for TxWithHash {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::Transaction) -> Result<Self, Self::Error> {
+        let transaction = match required(tx.transaction, "transaction")? {
+            transaction::Transaction::InvokeV1(tx) => Tx::Invoke(InvokeTx::V1(tx.try_into()?)),
+            transaction::Transaction::InvokeV3(tx) => Tx::Invoke(InvokeTx::V3(tx.try_into()?)),
+            transaction::Transaction::DeclareV1(tx) => Tx::Declare(DeclareTx::V1(tx.try_into()?)),
+            transaction::Transaction::DeclareV2(tx) => Tx::Declare(DeclareTx::V2(tx.try_into()?)),
+            transaction::Transaction::DeclareV3(tx) => Tx::Declare(DeclareTx::V3(tx.try_into()?)),
+            transaction::Transaction::L1Handler(tx) => Tx::L1Handler(tx.try_into()?),
+            transaction::Transaction::DeployAccountV1(tx) => {
+                Tx::DeployAccount(DeployAccountTx::V1(tx.try_into()?))
+            }
+            transaction::Transaction::DeployAccountV3(tx) => {
+                Tx::DeployAccount(DeployAccountTx::V3(tx.try_into()?))
+            }
+        };
+
+        Ok(Self { hash: felt_from_bytes(&tx.hash, "hash")?, transaction })
+    }
+}
+
+impl From<InvokeTxV1> for proto::InvokeTxV1 {
+    fn from(tx: InvokeTxV1) -> Self {
+        Self {
+            chain_id: felt_to_bytes(tx.chain_id.id()),
+            sender_address: felt_to_bytes(tx.sender_address.into()),
+            nonce: felt_to_bytes(tx.nonce),
+            calldata: felts_to_bytes(tx.calldata),
+            signature: felts_to_bytes(tx.signature),
+            max_fee: u128_to_bytes(tx.max_fee),
+        }
+    }
+}
+
+impl TryFrom<proto::InvokeTxV1> for InvokeTxV1 {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::InvokeTxV1) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: chain_id_from_bytes(&tx.chain_id)?,
+            sender_address: felt_from_bytes(&tx.sender_address, "sender_address")?.into(),
+            nonce: felt_from_bytes(&tx.nonce, "nonce")?,
+            calldata: felts_from_bytes(&tx.calldata, "calldata")?,
+            signature: felts_from_bytes(&tx.signature, "signature")?,
+            max_fee: u128_from_bytes(&tx.max_fee, "max_fee")?,
+        })
+    }
+}
+
+impl From<InvokeTxV3> for proto::InvokeTxV3 {
+    fn from(tx: InvokeTxV3) -> Self {
+        Self {
+            chain_id: felt_to_bytes(tx.chain_id.id()),
+            sender_address: felt_to_bytes(tx.sender_address.into()),
+            nonce: felt_to_bytes(tx.nonce),
+            calldata: felts_to_bytes(tx.calldata),
+            signature: felts_to_bytes(tx.signature),
+            resource_bounds: Some(tx.resource_bounds.into()),
+            tip: tx.tip,
+            paymaster_data: felts_to_bytes(tx.paymaster_data),
+            account_deployment_data: felts_to_bytes(tx.account_deployment_data),
+            nonce_data_availability_mode: da_mode_to_proto(tx.nonce_data_availability_mode),
+            fee_data_availability_mode: da_mode_to_proto(tx.fee_data_availability_mode),
+        }
+    }
+}
+
+impl TryFrom<proto::InvokeTxV3> for InvokeTxV3 {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::InvokeTxV3) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: chain_id_from_bytes(&tx.chain_id)?,
+            sender_address: felt_from_bytes(&tx.sender_address, "sender_address")?.into(),
+            nonce: felt_from_bytes(&tx.nonce, "nonce")?,
+            calldata: felts_from_bytes(&tx.calldata, "calldata")?,
+            signature: felts_from_bytes(&tx.signature, "signature")?,
+            resource_bounds: required(tx.resource_bounds, "resource_bounds")?.try_into()?,
+            tip: tx.tip,
+            paymaster_data: felts_from_bytes(&tx.paymaster_data, "paymaster_data")?,
+            account_deployment_data: felts_from_bytes(
+                &tx.account_deployment_data,
+                "account_deployment_data",
+            )?,
+            nonce_data_availability_mode: da_mode_from_proto(
+                tx.nonce_data_availability_mode,
+                "nonce_data_availability_mode",
+            )?,
+            fee_data_availability_mode: da_mode_from_proto(
+                tx.fee_data_availability_mode,
+                "fee_data_availability_mode",
+            )?,
+        })
+    }
+}
+
+impl From<DeclareTxV1> for proto::DeclareTxV1 {
+    fn from(tx: DeclareTxV1) -> Self {
+        Self {
+            chain_id: felt_to_bytes(tx.chain_id.id()),
+            sender_address: felt_to_bytes(tx.sender_address.into()),
+            nonce: felt_to_bytes(tx.nonce),
+            signature: felts_to_bytes(tx.signature),
+            class_hash: felt_to_bytes(tx.class_hash),
+            max_fee: u128_to_bytes(tx.max_fee),
+        }
+    }
+}
+
+impl TryFrom<proto::DeclareTxV1> for DeclareTxV1 {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::DeclareTxV1) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: chain_id_from_bytes(&tx.chain_id)?,
+            sender_address: felt_from_bytes(&tx.sender_address, "sender_address")?.into(),
+            nonce: felt_from_bytes(&tx.nonce, "nonce")?,
+            signature: felts_from_bytes(&tx.signature, "signature")?,
+            class_hash: felt_from_bytes(&tx.class_hash, "class_hash")?,
+            max_fee: u128_from_bytes(&tx.max_fee, "max_fee")?,
+        })
+    }
+}
+
+impl From<DeclareTxV2> for proto::DeclareTxV2 {
+    fn from(tx: DeclareTxV2) -> Self {
+        Self {
+            chain_id: felt_to_bytes(tx.chain_id.id()),
+            sender_address: felt_to_bytes(tx.sender_address.into()),
+            nonce: felt_to_bytes(tx.nonce),
+            signature: felts_to_bytes(tx.signature),
+            class_hash: felt_to_bytes(tx.class_hash),
+            compiled_class_hash: felt_to_bytes(tx.compiled_class_hash),
+            max_fee: u128_to_bytes(tx.max_fee),
+        }
+    }
+}
+
+impl TryFrom<proto::DeclareTxV2> for DeclareTxV2 {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::DeclareTxV2) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: chain_id_from_bytes(&tx.chain_id)?,
+            sender_address: felt_from_bytes(&tx.sender_address, "sender_address")?.into(),
+            nonce: felt_from_bytes(&tx.nonce, "nonce")?,
+            signature: felts_from_bytes(&tx.signature, "signature")?,
+            class_hash: felt_from_bytes(&tx.class_hash, "class_hash")?,
+            compiled_class_hash: felt_from_bytes(&tx.compiled_class_hash, "compiled_class_hash")?,
+            max_fee: u128_from_bytes(&tx.max_fee, "max_fee")?,
+        })
+    }
+}
+
+impl From<DeclareTxV3> for proto::DeclareTxV3 {
+    fn from(tx: DeclareTxV3) -> Self {
+        Self {
+            chain_id: felt_to_bytes(tx.chain_id.id()),
+            sender_address: felt_to_bytes(tx.sender_address.into()),
+            nonce: felt_to_bytes(tx.nonce),
+            signature: felts_to_bytes(tx.signature),
+            class_hash: felt_to_bytes(tx.class_hash),
+            compiled_class_hash: felt_to_bytes(tx.compiled_class_hash),
+            resource_bounds: Some(tx.resource_bounds.into()),
+            tip: tx.tip,
+            paymaster_data: felts_to_bytes(tx.paymaster_data),
+            account_deployment_data: felts_to_bytes(tx.account_deployment_data),
+            nonce_data_availability_mode: da_mode_to_proto(tx.nonce_data_availability_mode),
+            fee_data_availability_mode: da_mode_to_proto(tx.fee_data_availability_mode),
+        }
+    }
+}
+
+impl TryFrom<proto::DeclareTxV3> for DeclareTxV3 {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::DeclareTxV3) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: chain_id_from_bytes(&tx.chain_id)?,
+            sender_address: felt_from_bytes(&tx.sender_address, "sender_address")?.into(),
+            nonce: felt_from_bytes(&tx.nonce, "nonce")?,
+            signature: felts_from_bytes(&tx.signature, "signature")?,
+            class_hash: felt_from_bytes(&tx.class_hash, "class_hash")?,
+            compiled_class_hash: felt_from_bytes(&tx.compiled_class_hash, "compiled_class_hash")?,
+            resource_bounds: required(tx.resource_bounds, "resource_bounds")?.try_into()?,
+            tip: tx.tip,
+            paymaster_data: felts_from_bytes(&tx.paymaster_data, "paymaster_data")?,
+            account_deployment_data: felts_from_bytes(
+                &tx.account_deployment_data,
+                "account_deployment_data",
+            )?,
+            nonce_data_availability_mode: da_mode_from_proto(
+                tx.nonce_data_availability_mode,
+                "nonce_data_availability_mode",
+            )?,
+            fee_data_availability_mode: da_mode_from_proto(
+                tx.fee_data_availability_mode,
+                "fee_data_availability_mode",
+            )?,
+        })
+    }
+}
+
+impl From<L1HandlerTx> for proto::L1HandlerTx {
+    fn from(tx: L1HandlerTx) -> Self {
+        Self {
+            chain_id: felt_to_bytes(tx.chain_id.id()),
+            nonce: felt_to_bytes(tx.nonce),
+            paid_fee_on_l1: u128_to_bytes(tx.paid_fee_on_l1),
+            version: felt_to_bytes(tx.version),
+            message_hash: tx.message_hash.to_vec(),
+            calldata: felts_to_bytes(tx.calldata),
+            contract_address: felt_to_bytes(tx.contract_address.into()),
+            entry_point_selector: felt_to_bytes(tx.entry_point_selector),
+        }
+    }
+}
+
+impl TryFrom<proto::L1HandlerTx> for L1HandlerTx {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::L1HandlerTx) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: chain_id_from_bytes(&tx.chain_id)?,
+            nonce: felt_from_bytes(&tx.nonce, "nonce")?,
+            paid_fee_on_l1: u128_from_bytes(&tx.paid_fee_on_l1, "paid_fee_on_l1")?,
+            version: felt_from_bytes(&tx.version, "version")?,
+            message_hash: b256_from_bytes(&tx.message_hash, "message_hash")?,
+            calldata: felts_from_bytes(&tx.calldata, "calldata")?,
+            contract_address: felt_from_bytes(&tx.contract_address, "contract_address")?.into(),
+            entry_point_selector: felt_from_bytes(
+                &tx.entry_point_selector,
+                "entry_point_selector",
+            )?,
+        })
+    }
+}
+
+impl From<DeployAccountTxV1> for proto::DeployAccountTxV1 {
+    fn from(tx: DeployAccountTxV1) -> Self {
+        Self {
+            chain_id: felt_to_bytes(tx.chain_id.id()),
+            nonce: felt_to_bytes(tx.nonce),
+            signature: felts_to_bytes(tx.signature),
+            class_hash: felt_to_bytes(tx.class_hash),
+            contract_address: felt_to_bytes(tx.contract_address.into()),
+            contract_address_salt: felt_to_bytes(tx.contract_address_salt),
+            constructor_calldata: felts_to_bytes(tx.constructor_calldata),
+            max_fee: u128_to_bytes(tx.max_fee),
+        }
+    }
+}
+
+impl TryFrom<proto::DeployAccountTxV1> for DeployAccountTxV1 {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::DeployAccountTxV1) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: chain_id_from_bytes(&tx.chain_id)?,
+            nonce: felt_from_bytes(&tx.nonce, "nonce")?,
+            signature: felts_from_bytes(&tx.signature, "signature")?,
+            class_hash: felt_from_bytes(&tx.class_hash, "class_hash")?,
+            contract_address: felt_from_bytes(&tx.contract_address, "contract_address")?.into(),
+            contract_address_salt: felt_from_bytes(
+                &tx.contract_address_salt,
+                "contract_address_salt",
+            )?,
+            constructor_calldata: felts_from_bytes(
+                &tx.constructor_calldata,
+                "constructor_calldata",
+            )?,
+            max_fee: u128_from_bytes(&tx.max_fee, "max_fee")?,
+        })
+    }
+}
+
+impl From<DeployAccountTxV3> for proto::DeployAccountTxV3 {
+    fn from(tx: DeployAccountTxV3) -> Self {
+        Self {
+            chain_id: felt_to_bytes(tx.chain_id.id()),
+            nonce: felt_to_bytes(tx.nonce),
+            signature: felts_to_bytes(tx.signature),
+            class_hash: felt_to_bytes(tx.class_hash),
+            contract_address: felt_to_bytes(tx.contract_address.into()),
+            contract_address_salt: felt_to_bytes(tx.contract_address_salt),
+            constructor_calldata: felts_to_bytes(tx.constructor_calldata),
+            resource_bounds: Some(tx.resource_bounds.into()),
+            tip: tx.tip,
+            paymaster_data: felts_to_bytes(tx.paymaster_data),
+            nonce_data_availability_mode: da_mode_to_proto(tx.nonce_data_availability_mode),
+            fee_data_availability_mode: da_mode_to_proto(tx.fee_data_availability_mode),
+        }
+    }
+}
+
+impl TryFrom<proto::DeployAccountTxV3> for DeployAccountTxV3 {
+    type Error = ProtoError;
+
+    fn try_from(tx: proto::DeployAccountTxV3) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: chain_id_from_bytes(&tx.chain_id)?,
+            nonce: felt_from_bytes(&tx.nonce, "nonce")?,
+            signature: felts_from_bytes(&tx.signature, "signature")?,
+            class_hash: felt_from_bytes(&tx.class_hash, "class_hash")?,
+            contract_address: felt_from_bytes(&tx.contract_address, "contract_address")?.into(),
+            contract_address_salt: felt_from_bytes(
+                &tx.contract_address_salt,
+                "contract_address_salt",
+            )?,
+            constructor_calldata: felts_from_bytes(
+                &tx.constructor_calldata,
+                "constructor_calldata",
+            )?,
+            resource_bounds: required(tx.resource_bounds, "resource_bounds")?.try_into()?,
+            tip: tx.tip,
+            paymaster_data: felts_from_bytes(&tx.paymaster_data, "paymaster_data")?,
+            nonce_data_availability_mode: da_mode_from_proto(
+                tx.nonce_data_availability_mode,
+                "nonce_data_availability_mode",
+            )?,
+            fee_data_availability_mode: da_mode_from_proto(
+                tx.fee_data_availability_mode,
+                "fee_data_availability_mode",
+            )?,
+        })
+    }
+}
+
+impl From<ResourceBoundsMapping> for proto::ResourceBoundsMapping {
+    fn from(bounds: ResourceBoundsMapping) -> Self {
+        let to_proto = |bounds: ResourceBounds| proto::ResourceBounds {
+            max_amount: bounds.max_amount,
+            max_price_per_unit: u128_to_bytes(bounds.max_price_per_unit),
+        };
+
+        Self { l1_gas: Some(to_proto(bounds.l1_gas)), l2_gas: Some(to_proto(bounds.l2_gas)) }
+    }
+}
+
+impl TryFrom<proto::ResourceBoundsMapping> for ResourceBoundsMapping {
+    type Error = ProtoError;
+
+    fn try_from(bounds: proto::ResourceBoundsMapping) -> Result<Self, Self::Error> {
+        let from_proto = |bounds: Option<proto::ResourceBounds>, field| {
+            let bounds = required(bounds, field)?;
+            Ok::<_, ProtoError>(ResourceBounds {
+                max_amount: bounds.max_amount,
+                max_price_per_unit: u128_from_bytes(
+                    &bounds.max_price_per_unit,
+                    "max_price_per_unit",
+                )?,
+            })
+        };
+
+        Ok(Self {
+            l1_gas: from_proto(bounds.l1_gas, "l1_gas")?,
+            l2_gas: from_proto(bounds.l2_gas, "l2_gas")?,
+        })
+    }
+}
+
+fn chain_id_from_bytes(bytes: &[u8]) -> Result<ChainId, ProtoError> {
+    Ok(ChainId::from(felt_from_bytes(bytes, "chain_id")?))
+}
+
+fn da_mode_to_proto(mode: DataAvailabilityMode) -> i32 {
+    match mode {
+        DataAvailabilityMode::L1 => proto::DataAvailabilityMode::L1 as i32,
+        DataAvailabilityMode::L2 => proto::DataAvailabilityMode::L2 as i32,
+    }
+}
+
+fn da_mode_from_proto(value: i32, field: &'static str) -> Result<DataAvailabilityMode, ProtoError> {
+    match proto::DataAvailabilityMode::try_from(value) {
+        Ok(proto::DataAvailabilityMode::L1) => Ok(DataAvailabilityMode::L1),
+        Ok(proto::DataAvailabilityMode::L2) => Ok(DataAvailabilityMode::L2),
+        Err(_) => Err(ProtoError::UnknownEnumValue { field, value }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::B256;
+    use katana_primitives::FieldElement;
+    use prost::Message;
+
+    use super::*;
+
+    fn resource_bounds() -> ResourceBoundsMapping {
+        ResourceBoundsMapping {
+            l1_gas: ResourceBounds { max_amount: 10, max_price_per_unit: u128::MAX },
+            l2_gas: ResourceBounds { max_amount: 0, max_price_per_unit: 0 },
+        }
+    }
+
+    #[test]
+    fn transaction_roundtrip() {
+        let felts = vec![FieldElement::ONE, FieldElement::MAX];
+        let chain_id = ChainId::parse("KATANA").unwrap();
+
+        let transactions = vec![
+            Tx::Invoke(InvokeTx::V1(InvokeTxV1 {
+                chain_id,
+                sender_address: FieldElement::ONE.into(),
+                nonce: FieldElement::TWO,
+                calldata: felts.clone(),
+                signature: felts.clone(),
+                max_fee: u128::MAX,
+            })),
+            Tx::Invoke(InvokeTx::V3(InvokeTxV3 {
+                chain_id,
+                sender_address: FieldElement::ONE.into(),
+                nonce: FieldElement::TWO,
+                calldata: felts.clone(),
+                signature: vec![],
+                resource_bounds: resource_bounds(),
+                tip: 5,
+                paymaster_data: felts.clone(),
+                account_deployment_data: vec![],
+                nonce_data_availability_mode: DataAvailabilityMode::L1,
+                fee_data_availability_mode: DataAvailabilityMode::L2,
+            })),
+            Tx::Declare(DeclareTx::V2(DeclareTxV2 {
+                chain_id,
+                sender_address: FieldElement::ONE.into(),
+                nonce: FieldElement::ZERO,
+                signature: felts.clone(),
+                class_hash: FieldElement::TWO,
+                compiled_class_hash: FieldElement::MAX,
+                max_fee: 1,
+            })),
+            Tx::L1Handler(L1HandlerTx {
+                chain_id,
+                nonce: FieldElement::ONE,
+                paid_fee_on_l1: 100,
+                version: FieldElement::ZERO,
+                message_hash: B256::repeat_byte(0xab),
+                calldata: felts.clone(),
+                contract_address: FieldElement::TWO.into(),
+                entry_point_selector: FieldElement::MAX,
+            }),
+            Tx::DeployAccount(DeployAccountTx::V3(DeployAccountTxV3 {
+                chain_id,
+                nonce: FieldElement::ZERO,
+                signature: felts.clone(),
+                class_hash: FieldElement::ONE,
+                contract_address: FieldElement::TWO.into(),
+                contract_address_salt: FieldElement::MAX,
+                constructor_calldata: felts,
+                resource_bounds: resource_bounds(),
+                tip: 0,
+                paymaster_data: vec![],
+                nonce_data_availability_mode: DataAvailabilityMode::L2,
+                fee_data_availability_mode: DataAvailabilityMode::L1,
+            })),
+        ];
+
+        for (i, transaction) in transactions.into_iter().enumerate() {
+            let tx = TxWithHash { hash: FieldElement::from(i as u64), transaction };
+
+            let encoded = proto::Transaction::from(tx.clone()).encode_to_vec();
+            let decoded = proto::Transaction::decode(encoded.as_slice()).unwrap();
+            assert_eq!(TxWithHash::try_from(decoded).unwrap(), tx);
+        }
+    }
+
+    #[test]
+    fn reject_invalid_transaction() {
+        let tx = proto::Transaction { hash: vec![0; 32], transaction: None };
+        assert!(matches!(TxWithHash::try_from(tx), Err(ProtoError::MissingField("transaction"))));
+
+        // a felt must be lower than the field prime
+        let tx = proto::L1HandlerTx { chain_id: vec![0xff; 32], ..Default::default() };
+        assert!(matches!(L1HandlerTx::try_from(tx), Err(ProtoError::InvalidFelt("chain_id"))));
+
+        let bounds = proto::ResourceBoundsMapping {
+            l1_gas: Some(proto::ResourceBounds { max_amount: 1, max_price_per_unit: vec![0; 16] }),
+            l2_gas: None,
+        };
+        assert!(matches!(
+            ResourceBoundsMapping::try_from(bounds),
+            Err(ProtoError::MissingField("l2_gas"))
+        ));
+    }
+}
@@ -1,6 +1,9 @@
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
+use katana_primitives::transaction::TxOrigin;
 use katana_primitives::FieldElement;
+use katana_rpc_types::time::ChainTime;
+use katana_rpc_types::transaction::{BroadcastedInvokeTx, InvokeTxResult};
 
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "dev"))]
 #[cfg_attr(feature = "client", rpc(client, server, namespace = "dev"))]
@@ -8,8 +11,9 @@ pub trait DevApi {
     #[method(name = "generateBlock")]
     async fn generate_block(&self) -> RpcResult<()>;
 
+    /// Returns the timestamp the next block would have if it was produced now.
     #[method(name = "nextBlockTimestamp")]
-    async fn next_block_timestamp(&self) -> RpcResult<()>;
+    async fn next_block_timestamp(&self) -> RpcResult<u64>;
 
     #[method(name = "setNextBlockTimestamp")]
     async fn set_next_block_timestamp(&self, timestamp: u64) -> RpcResult<()>;
@@ -17,6 +21,11 @@ pub trait DevApi {
     #[method(name = "increaseNextBlockTimestamp")]
     async fn increase_next_block_timestamp(&self, timestamp: u64) -> RpcResult<()>;
 
+    /// Returns the time of the chain along with the clocks it's derived from, to detect the chain
+    /// time diverging from the time of the node.
+    #[method(name = "chainTime")]
+    async fn chain_time(&self) -> RpcResult<ChainTime>;
+
     #[method(name = "setStorageAt")]
     async fn set_storage_at(
         &self,
@@ -24,4 +33,22 @@ pub trait DevApi {
         key: FieldElement,
         value: FieldElement,
     ) -> RpcResult<()>;
+
+    /// Gives a name to an address, which is rendered along with the address in the logs of the
+    /// node and returned by `katana_getAddressLabels` and `katana_getTransactionReceipt`. An empty
+    /// name removes the name of the address.
+    ///
+    /// The name is at most 64 ASCII letters, digits, spaces and `_-.:/`, and at most 1024
+    /// addresses are named.
+    #[method(name = "setAddressLabel")]
+    async fn set_address_label(&self, address: FieldElement, name: String) -> RpcResult<()>;
+
+    /// Submits an invoke transaction along with metadata describing its origin, which is returned
+    /// with the receipt of the transaction by `katana_getTransactionReceipt`.
+    #[method(name = "addInvokeTransactionWithOrigin")]
+    async fn add_invoke_transaction_with_origin(
+        &self,
+        invoke_transaction: BroadcastedInvokeTx,
+        origin: TxOrigin,
+    ) -> RpcResult<InvokeTxResult>;
 }
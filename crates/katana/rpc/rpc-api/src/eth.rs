@@ -0,0 +1,27 @@
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use katana_primitives::FieldElement;
+use katana_rpc_types::eth::{EthBlockId, Quantity};
+
+/// A minimal subset of the Ethereum JSON-RPC API mapped onto Starknet, for the generic tools,
+/// such as health checkers and wallet reachability probes, which expect an Ethereum node.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "eth"))]
+#[cfg_attr(feature = "client", rpc(client, server, namespace = "eth"))]
+pub trait EthApi {
+    /// Returns the Starknet chain id.
+    #[method(name = "chainId")]
+    async fn chain_id(&self) -> RpcResult<Quantity>;
+
+    /// Returns the number of the latest block.
+    #[method(name = "blockNumber")]
+    async fn block_number(&self) -> RpcResult<Quantity>;
+
+    /// Returns the fee token balance of a Starknet account, in the smallest unit of the token.
+    /// The latest block is used if no block is given.
+    #[method(name = "getBalance")]
+    async fn get_balance(
+        &self,
+        address: FieldElement,
+        block: Option<EthBlockId>,
+    ) -> RpcResult<Quantity>;
+}
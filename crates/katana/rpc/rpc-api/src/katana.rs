@@ -1,10 +1,79 @@
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
-use katana_rpc_types::account::Account;
+use katana_primitives::block::{BlockIdOrTag, BlockNumber};
+use katana_primitives::transaction::TxHash;
+use katana_rpc_types::account::{Account, AddressLabel};
+use katana_rpc_types::limits::BlockLimits;
+use katana_rpc_types::message::MessageToL1WithStatus;
+use katana_rpc_types::pool::{NextBlockPreview, PoolTxEvent, SealedBlock};
+use katana_rpc_types::receipt::TxReceiptWithOrigin;
+use katana_rpc_types::scheduler::ScheduledTask;
+use katana_rpc_types::stats::ChainStats;
 
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "katana"))]
 #[cfg_attr(feature = "client", rpc(client, server, namespace = "katana"))]
 pub trait KatanaApi {
     #[method(name = "predeployedAccounts")]
     async fn predeployed_accounts(&self) -> RpcResult<Vec<Account>>;
+
+    /// Returns the messages sent to the settlement chain by the transactions of the given block,
+    /// along with the hash needed to consume them on the settlement chain.
+    #[method(name = "getMessagesToL1")]
+    async fn get_messages_to_l1(
+        &self,
+        block_id: BlockIdOrTag,
+    ) -> RpcResult<Vec<MessageToL1WithStatus>>;
+
+    /// Returns the aggregated activity of the blocks from `from_block` to `to_block`, inclusive.
+    #[method(name = "getChainStats")]
+    async fn get_chain_stats(
+        &self,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+    ) -> RpcResult<ChainStats>;
+
+    /// Returns the receipt of a transaction along with the origin metadata it was submitted with
+    /// through `dev_addInvokeTransactionWithOrigin`, and the names of the addresses of its
+    /// execution trace.
+    #[method(name = "getTransactionReceipt")]
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: TxHash,
+    ) -> RpcResult<TxReceiptWithOrigin>;
+
+    /// Returns the names given to addresses with `dev_setAddressLabel`, sorted by address.
+    #[method(name = "getAddressLabels")]
+    async fn get_address_labels(&self) -> RpcResult<Vec<AddressLabel>>;
+
+    /// Subscribes to the validated transactions entering the transaction pool, and to the ones
+    /// leaving it, either included in a block or rejected for failing to execute. The
+    /// subscription is closed with an error if it lags behind.
+    #[subscription(
+        name = "subscribePoolTransactions",
+        unsubscribe = "unsubscribePoolTransactions",
+        item = PoolTxEvent
+    )]
+    fn subscribe_pool_transactions(&self);
+
+    /// Seals a block built externally from the transactions of the pool, which are executed in
+    /// the given order. Only available when the node is started with external block building.
+    #[method(name = "sealBlock")]
+    async fn seal_block(&self, transaction_hashes: Vec<TxHash>) -> RpcResult<SealedBlock>;
+
+    /// Simulates the next block, from the pending block and the transactions of the pool, and
+    /// returns the transactions it would include with their receipts. The outcome of the
+    /// transactions can be shown before the block is mined, but isn't final: the transactions
+    /// received in the meantime may change it.
+    #[method(name = "previewNextBlock")]
+    async fn preview_next_block(&self) -> RpcResult<NextBlockPreview>;
+
+    /// Returns the recurring maintenance tasks of the node, in the order they were scheduled,
+    /// along with the outcome of their runs.
+    #[method(name = "listScheduledTasks")]
+    async fn list_scheduled_tasks(&self) -> RpcResult<Vec<ScheduledTask>>;
+
+    /// Returns the resource limits of the blocks produced by the node, set with
+    /// `--block-max-l1-gas` and `--block-max-cairo-steps`.
+    #[method(name = "getBlockLimits")]
+    async fn get_block_limits(&self) -> RpcResult<BlockLimits>;
 }
@@ -1,4 +1,5 @@
 pub mod dev;
+pub mod eth;
 pub mod katana;
 pub mod saya;
 pub mod starknet;
@@ -12,4 +13,5 @@ pub enum ApiKind {
     Torii,
     Dev,
     Saya,
+    Eth,
 }
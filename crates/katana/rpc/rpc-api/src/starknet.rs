@@ -18,7 +18,9 @@ use katana_rpc_types::{
     ContractClass, FeeEstimate, FeltAsHex, FunctionCall, SimulationFlag,
     SimulationFlagForEstimateFee, SyncingStatus,
 };
-use starknet::core::types::{SimulatedTransaction, TransactionStatus};
+use starknet::core::types::{
+    SimulatedTransaction, TransactionStatus, TransactionTrace, TransactionTraceWithHash,
+};
 
 /// The currently supported version of the Starknet JSON-RPC specification.
 pub const RPC_SPEC_VERSION: &str = "0.6.0";
@@ -194,4 +196,25 @@ pub trait StarknetApi {
         transactions: Vec<BroadcastedTx>,
         simulation_flags: Vec<SimulationFlag>,
     ) -> RpcResult<Vec<SimulatedTransaction>>;
+
+    /// Returns the execution trace of the transaction designated by the input hash.
+    #[method(name = "traceTransaction")]
+    async fn trace_transaction(&self, transaction_hash: TxHash) -> RpcResult<TransactionTrace>;
+
+    /// Returns the execution traces of all transactions included in the given block.
+    #[method(name = "traceBlockTransactions")]
+    async fn trace_block_transactions(
+        &self,
+        block_id: BlockIdOrTag,
+    ) -> RpcResult<Vec<TransactionTraceWithHash>>;
+
+    /// Subscribes to the state updates of the blocks mined after the subscription. If
+    /// `contract_addresses` is provided, the updates only include the changes made to these
+    /// contracts.
+    #[subscription(
+        name = "subscribeNewStateUpdates",
+        unsubscribe = "unsubscribeNewStateUpdates",
+        item = StateUpdate
+    )]
+    fn subscribe_new_state_updates(&self, contract_addresses: Option<Vec<FieldElement>>);
 }
@@ -22,6 +22,13 @@ pub struct Account {
     pub balance: U256,
 }
 
+/// A name given to an address with `dev_setAddressLabel`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressLabel {
+    pub address: ContractAddress,
+    pub name: String,
+}
+
 impl Account {
     pub fn new(address: ContractAddress, account: &GenesisAccountAlloc) -> Self {
         Self {
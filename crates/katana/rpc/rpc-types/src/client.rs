@@ -0,0 +1,648 @@
+//! Typed construction of the requests sent to a Katana node, and a thin JSON-RPC client to send
+//! them, for the tools driving a node such as the test runners and the benchmarks.
+//!
+//! The requests are built from the types of this crate, so that the tools share the types of the
+//! node instead of each converting between the types of their own Starknet client. The builders
+//! reject the transactions no node accepts, eg. ones without a fee bound, before anything is sent.
+//! The legacy declarations of Cairo 0 classes are not supported.
+
+use std::sync::Arc;
+
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::Error;
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::rpc_params;
+/// The maximum number of elements of the calldata the builders accept by default, the default
+/// limit of the node, which may be lowered with `--max-calldata-len`.
+pub use katana_core::constants::DEFAULT_MAX_CALLDATA_LEN;
+use katana_primitives::block::{BlockIdOrTag, BlockNumber};
+use katana_primitives::chain::ChainId;
+use katana_primitives::class::{ClassHash, CompiledClassHash, FlattenedSierraClass};
+use katana_primitives::contract::{ContractAddress, Nonce};
+use katana_primitives::transaction::{DeclareTx, DeclareTxV2, DeclareTxV3, TxHash};
+use katana_primitives::FieldElement;
+use starknet::core::types::{
+    BlockTag, BroadcastedDeclareTransaction, BroadcastedDeclareTransactionV2,
+    BroadcastedDeclareTransactionV3, BroadcastedDeployAccountTransaction,
+    BroadcastedDeployAccountTransactionV1, BroadcastedDeployAccountTransactionV3,
+    BroadcastedInvokeTransaction, BroadcastedInvokeTransactionV1, BroadcastedInvokeTransactionV3,
+    DataAvailabilityMode, DeclareTransactionResult, DeployAccountTransactionResult,
+    InvokeTransactionResult, ResourceBounds, ResourceBoundsMapping,
+};
+use starknet::core::utils::get_contract_address;
+
+use crate::account::Account;
+use crate::receipt::MaybePendingTxReceipt;
+use crate::transaction::{BroadcastedDeclareTx, BroadcastedDeployAccountTx, BroadcastedInvokeTx};
+use crate::FeltAsHex;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RequestError {
+    #[error("missing nonce")]
+    MissingNonce,
+    #[error("missing fee bound, either a max fee or L1 gas bounds")]
+    MissingFeeBound,
+    #[error("zero fee bound, only allowed for queries")]
+    ZeroFeeBound,
+    #[error("max fee {0:#x} exceeds the maximum of 2^128 - 1")]
+    MaxFeeTooLarge(FieldElement),
+    #[error("calldata of {len} elements exceeds the limit of {max}")]
+    CalldataTooLong { len: usize, max: usize },
+}
+
+/// Checks that a calldata of `len` elements is within the limit of `max` elements.
+fn check_calldata_len(len: usize, max: usize) -> Result<(), RequestError> {
+    if len > max {
+        return Err(RequestError::CalldataTooLong { len, max });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+enum FeeBound {
+    /// The max fee of a V1 transaction (V2 for a declaration), in wei.
+    MaxFee(FieldElement),
+    /// The L1 gas bounds of a V3 transaction, and its tip.
+    L1Gas { bounds: ResourceBounds, tip: u64 },
+}
+
+impl FeeBound {
+    /// The resource bounds of a V3 transaction using at most `bounds` of L1 gas, and no L2 gas.
+    fn resource_bounds(bounds: ResourceBounds) -> ResourceBoundsMapping {
+        let l2_gas = ResourceBounds { max_amount: 0, max_price_per_unit: 0 };
+        ResourceBoundsMapping { l1_gas: bounds, l2_gas }
+    }
+}
+
+/// The fields shared by the transactions of all kinds.
+#[derive(Debug, Clone)]
+struct TxFields {
+    signature: Vec<FieldElement>,
+    nonce: Option<Nonce>,
+    fee_bound: Option<FeeBound>,
+    is_query: bool,
+    /// The fields only found in the V3 transactions.
+    paymaster_data: Vec<FieldElement>,
+    nonce_data_availability_mode: DataAvailabilityMode,
+    fee_data_availability_mode: DataAvailabilityMode,
+}
+
+impl TxFields {
+    fn new() -> Self {
+        Self {
+            signature: Vec::new(),
+            nonce: None,
+            fee_bound: None,
+            is_query: false,
+            paymaster_data: Vec::new(),
+            nonce_data_availability_mode: DataAvailabilityMode::L1,
+            fee_data_availability_mode: DataAvailabilityMode::L1,
+        }
+    }
+
+    /// Returns the nonce and the fee bound of the transaction, once checked.
+    fn checked(&self) -> Result<(Nonce, FeeBound), RequestError> {
+        let nonce = self.nonce.ok_or(RequestError::MissingNonce)?;
+        let fee_bound = self.fee_bound.clone().ok_or(RequestError::MissingFeeBound)?;
+
+        match &fee_bound {
+            FeeBound::MaxFee(max_fee) => {
+                if u128::try_from(*max_fee).is_err() {
+                    return Err(RequestError::MaxFeeTooLarge(*max_fee));
+                }
+                if *max_fee == FieldElement::ZERO && !self.is_query {
+                    return Err(RequestError::ZeroFeeBound);
+                }
+            }
+            FeeBound::L1Gas { bounds, .. } => {
+                if (bounds.max_amount == 0 || bounds.max_price_per_unit == 0) && !self.is_query {
+                    return Err(RequestError::ZeroFeeBound);
+                }
+            }
+        }
+
+        Ok((nonce, fee_bound))
+    }
+}
+
+/// The setters of the fields shared by the transactions of all kinds, for the builders having
+/// them as `fields`.
+macro_rules! tx_fields_setters {
+    () => {
+        pub fn signature(mut self, signature: Vec<FieldElement>) -> Self {
+            self.fields.signature = signature;
+            self
+        }
+
+        pub fn nonce(mut self, nonce: Nonce) -> Self {
+            self.fields.nonce = Some(nonce);
+            self
+        }
+
+        /// Builds a V1 transaction (V2 for a declaration) paying at most `max_fee`.
+        pub fn max_fee(mut self, max_fee: FieldElement) -> Self {
+            self.fields.fee_bound = Some(FeeBound::MaxFee(max_fee));
+            self
+        }
+
+        /// Builds a V3 transaction using at most `max_amount` of L1 gas, at most at
+        /// `max_price_per_unit`.
+        pub fn l1_gas(mut self, max_amount: u64, max_price_per_unit: u128, tip: u64) -> Self {
+            let bounds = ResourceBounds { max_amount, max_price_per_unit };
+            self.fields.fee_bound = Some(FeeBound::L1Gas { bounds, tip });
+            self
+        }
+
+        /// Builds a transaction only meant to be simulated or estimated, which may have a zero
+        /// fee bound.
+        pub fn query(mut self, is_query: bool) -> Self {
+            self.fields.is_query = is_query;
+            self
+        }
+
+        /// Sets the data of the paymaster of a V3 transaction.
+        pub fn paymaster_data(mut self, paymaster_data: Vec<FieldElement>) -> Self {
+            self.fields.paymaster_data = paymaster_data;
+            self
+        }
+
+        /// Sets where the nonce and the fee balance of the account of a V3 transaction are
+        /// stored, L1 by default.
+        pub fn data_availability_modes(
+            mut self,
+            nonce: DataAvailabilityMode,
+            fee: DataAvailabilityMode,
+        ) -> Self {
+            self.fields.nonce_data_availability_mode = nonce;
+            self.fields.fee_data_availability_mode = fee;
+            self
+        }
+    };
+}
+
+/// A builder of invoke transactions, V1 ones if built with a max fee and V3 ones if built with
+/// L1 gas bounds.
+///
+/// The transaction is signed by the caller, from its hash computed with [`Self::hash`].
+#[derive(Debug, Clone)]
+pub struct InvokeTxBuilder {
+    sender_address: ContractAddress,
+    calldata: Vec<FieldElement>,
+    account_deployment_data: Vec<FieldElement>,
+    max_calldata_len: usize,
+    fields: TxFields,
+}
+
+impl InvokeTxBuilder {
+    pub fn new(sender_address: ContractAddress) -> Self {
+        Self {
+            sender_address,
+            calldata: Vec::new(),
+            account_deployment_data: Vec::new(),
+            max_calldata_len: DEFAULT_MAX_CALLDATA_LEN,
+            fields: TxFields::new(),
+        }
+    }
+
+    tx_fields_setters!();
+
+    pub fn calldata(mut self, calldata: Vec<FieldElement>) -> Self {
+        self.calldata = calldata;
+        self
+    }
+
+    /// Sets the data deploying the account of a V3 transaction.
+    pub fn account_deployment_data(mut self, account_deployment_data: Vec<FieldElement>) -> Self {
+        self.account_deployment_data = account_deployment_data;
+        self
+    }
+
+    /// Sets the maximum number of elements of the calldata, to match the limit of a node
+    /// started with a `--max-calldata-len` other than [`DEFAULT_MAX_CALLDATA_LEN`].
+    pub fn max_calldata_len(mut self, max_calldata_len: usize) -> Self {
+        self.max_calldata_len = max_calldata_len;
+        self
+    }
+
+    /// Returns the hash of the transaction on the chain `chain_id`, which is the message the
+    /// sender signs.
+    pub fn hash(&self, chain_id: ChainId) -> Result<TxHash, RequestError> {
+        let is_query = self.fields.is_query;
+        let tx = self.clone().build()?.into_tx_with_chain_id(chain_id);
+        Ok(tx.calculate_hash(is_query))
+    }
+
+    pub fn build(self) -> Result<BroadcastedInvokeTx, RequestError> {
+        let (nonce, fee_bound) = self.fields.checked()?;
+        check_calldata_len(self.calldata.len(), self.max_calldata_len)?;
+        let fields = self.fields;
+
+        let tx = match fee_bound {
+            FeeBound::MaxFee(max_fee) => {
+                BroadcastedInvokeTransaction::V1(BroadcastedInvokeTransactionV1 {
+                    sender_address: self.sender_address.into(),
+                    calldata: self.calldata,
+                    max_fee,
+                    signature: fields.signature,
+                    nonce,
+                    is_query: fields.is_query,
+                })
+            }
+
+            FeeBound::L1Gas { bounds, tip } => {
+                BroadcastedInvokeTransaction::V3(BroadcastedInvokeTransactionV3 {
+                    sender_address: self.sender_address.into(),
+                    calldata: self.calldata,
+                    signature: fields.signature,
+                    nonce,
+                    resource_bounds: FeeBound::resource_bounds(bounds),
+                    tip,
+                    paymaster_data: fields.paymaster_data,
+                    account_deployment_data: self.account_deployment_data,
+                    nonce_data_availability_mode: fields.nonce_data_availability_mode,
+                    fee_data_availability_mode: fields.fee_data_availability_mode,
+                    is_query: fields.is_query,
+                })
+            }
+        };
+
+        Ok(BroadcastedInvokeTx(tx))
+    }
+}
+
+/// A builder of declarations of Sierra classes, V2 ones if built with a max fee and V3 ones if
+/// built with L1 gas bounds.
+///
+/// The transaction is signed by the caller, from its hash computed with [`Self::hash`].
+#[derive(Debug, Clone)]
+pub struct DeclareTxBuilder {
+    sender_address: ContractAddress,
+    contract_class: Arc<FlattenedSierraClass>,
+    compiled_class_hash: CompiledClassHash,
+    account_deployment_data: Vec<FieldElement>,
+    fields: TxFields,
+}
+
+impl DeclareTxBuilder {
+    pub fn new(
+        sender_address: ContractAddress,
+        contract_class: FlattenedSierraClass,
+        compiled_class_hash: CompiledClassHash,
+    ) -> Self {
+        Self {
+            sender_address,
+            contract_class: Arc::new(contract_class),
+            compiled_class_hash,
+            account_deployment_data: Vec::new(),
+            fields: TxFields::new(),
+        }
+    }
+
+    tx_fields_setters!();
+
+    /// Sets the data deploying the account of a V3 transaction.
+    pub fn account_deployment_data(mut self, account_deployment_data: Vec<FieldElement>) -> Self {
+        self.account_deployment_data = account_deployment_data;
+        self
+    }
+
+    /// Returns the hash of the declared class.
+    pub fn class_hash(&self) -> ClassHash {
+        self.contract_class.class_hash()
+    }
+
+    /// Returns the hash of the transaction on the chain `chain_id`, which is the message the
+    /// sender signs.
+    pub fn hash(&self, chain_id: ChainId) -> Result<TxHash, RequestError> {
+        let is_query = self.fields.is_query;
+        let class_hash = self.class_hash();
+
+        // the class isn't compiled, as it's not part of the hash
+        let tx = match self.clone().build()?.0 {
+            BroadcastedDeclareTransaction::V2(tx) => DeclareTx::V2(DeclareTxV2 {
+                chain_id,
+                class_hash,
+                nonce: tx.nonce,
+                signature: tx.signature,
+                sender_address: tx.sender_address.into(),
+                compiled_class_hash: tx.compiled_class_hash,
+                max_fee: tx.max_fee.try_into().expect("max fee checked"),
+            }),
+
+            BroadcastedDeclareTransaction::V3(tx) => DeclareTx::V3(DeclareTxV3 {
+                chain_id,
+                class_hash,
+                nonce: tx.nonce,
+                signature: tx.signature,
+                sender_address: tx.sender_address.into(),
+                compiled_class_hash: tx.compiled_class_hash,
+                tip: tx.tip,
+                paymaster_data: tx.paymaster_data,
+                account_deployment_data: tx.account_deployment_data,
+                resource_bounds: tx.resource_bounds,
+                fee_data_availability_mode: tx.fee_data_availability_mode,
+                nonce_data_availability_mode: tx.nonce_data_availability_mode,
+            }),
+
+            BroadcastedDeclareTransaction::V1(_) => unreachable!("legacy declarations not built"),
+        };
+
+        Ok(tx.calculate_hash(is_query))
+    }
+
+    pub fn build(self) -> Result<BroadcastedDeclareTx, RequestError> {
+        let (nonce, fee_bound) = self.fields.checked()?;
+        let fields = self.fields;
+
+        let tx = match fee_bound {
+            FeeBound::MaxFee(max_fee) => {
+                BroadcastedDeclareTransaction::V2(BroadcastedDeclareTransactionV2 {
+                    sender_address: self.sender_address.into(),
+                    compiled_class_hash: self.compiled_class_hash,
+                    max_fee,
+                    signature: fields.signature,
+                    nonce,
+                    contract_class: self.contract_class,
+                    is_query: fields.is_query,
+                })
+            }
+
+            FeeBound::L1Gas { bounds, tip } => {
+                BroadcastedDeclareTransaction::V3(BroadcastedDeclareTransactionV3 {
+                    sender_address: self.sender_address.into(),
+                    compiled_class_hash: self.compiled_class_hash,
+                    signature: fields.signature,
+                    nonce,
+                    contract_class: self.contract_class,
+                    resource_bounds: FeeBound::resource_bounds(bounds),
+                    tip,
+                    paymaster_data: fields.paymaster_data,
+                    account_deployment_data: self.account_deployment_data,
+                    nonce_data_availability_mode: fields.nonce_data_availability_mode,
+                    fee_data_availability_mode: fields.fee_data_availability_mode,
+                    is_query: fields.is_query,
+                })
+            }
+        };
+
+        Ok(BroadcastedDeclareTx(tx))
+    }
+}
+
+/// A builder of deployments of accounts, V1 ones if built with a max fee and V3 ones if built
+/// with L1 gas bounds.
+///
+/// The transaction is signed by the caller, from its hash computed with [`Self::hash`], with the
+/// key of the deployed account. The account must be funded beforehand, at the address returned by
+/// [`Self::contract_address`].
+#[derive(Debug, Clone)]
+pub struct DeployAccountTxBuilder {
+    class_hash: ClassHash,
+    contract_address_salt: FieldElement,
+    constructor_calldata: Vec<FieldElement>,
+    max_calldata_len: usize,
+    fields: TxFields,
+}
+
+impl DeployAccountTxBuilder {
+    pub fn new(class_hash: ClassHash, contract_address_salt: FieldElement) -> Self {
+        Self {
+            class_hash,
+            contract_address_salt,
+            constructor_calldata: Vec::new(),
+            max_calldata_len: DEFAULT_MAX_CALLDATA_LEN,
+            // the nonce of an account not deployed yet
+            fields: TxFields { nonce: Some(FieldElement::ZERO), ..TxFields::new() },
+        }
+    }
+
+    tx_fields_setters!();
+
+    pub fn constructor_calldata(mut self, constructor_calldata: Vec<FieldElement>) -> Self {
+        self.constructor_calldata = constructor_calldata;
+        self
+    }
+
+    /// Sets the maximum number of elements of the constructor calldata, to match the limit of a
+    /// node started with a `--max-calldata-len` other than [`DEFAULT_MAX_CALLDATA_LEN`].
+    pub fn max_calldata_len(mut self, max_calldata_len: usize) -> Self {
+        self.max_calldata_len = max_calldata_len;
+        self
+    }
+
+    /// Returns the address the account is deployed at.
+    pub fn contract_address(&self) -> ContractAddress {
+        let address = get_contract_address(
+            self.contract_address_salt,
+            self.class_hash,
+            &self.constructor_calldata,
+            FieldElement::ZERO,
+        );
+        address.into()
+    }
+
+    /// Returns the hash of the transaction on the chain `chain_id`, which is the message the
+    /// deployed account signs.
+    pub fn hash(&self, chain_id: ChainId) -> Result<TxHash, RequestError> {
+        let is_query = self.fields.is_query;
+        let tx = self.clone().build()?.into_tx_with_chain_id(chain_id);
+        Ok(tx.calculate_hash(is_query))
+    }
+
+    pub fn build(self) -> Result<BroadcastedDeployAccountTx, RequestError> {
+        let (nonce, fee_bound) = self.fields.checked()?;
+        check_calldata_len(self.constructor_calldata.len(), self.max_calldata_len)?;
+        let fields = self.fields;
+
+        let tx = match fee_bound {
+            FeeBound::MaxFee(max_fee) => {
+                BroadcastedDeployAccountTransaction::V1(BroadcastedDeployAccountTransactionV1 {
+                    max_fee,
+                    signature: fields.signature,
+                    nonce,
+                    contract_address_salt: self.contract_address_salt,
+                    constructor_calldata: self.constructor_calldata,
+                    class_hash: self.class_hash,
+                    is_query: fields.is_query,
+                })
+            }
+
+            FeeBound::L1Gas { bounds, tip } => {
+                BroadcastedDeployAccountTransaction::V3(BroadcastedDeployAccountTransactionV3 {
+                    signature: fields.signature,
+                    nonce,
+                    contract_address_salt: self.contract_address_salt,
+                    constructor_calldata: self.constructor_calldata,
+                    class_hash: self.class_hash,
+                    resource_bounds: FeeBound::resource_bounds(bounds),
+                    tip,
+                    paymaster_data: fields.paymaster_data,
+                    nonce_data_availability_mode: fields.nonce_data_availability_mode,
+                    fee_data_availability_mode: fields.fee_data_availability_mode,
+                    is_query: fields.is_query,
+                })
+            }
+        };
+
+        Ok(BroadcastedDeployAccountTx(tx))
+    }
+}
+
+/// A JSON-RPC client of a Katana node, over HTTP.
+#[derive(Debug, Clone)]
+pub struct KatanaClient {
+    inner: HttpClient,
+}
+
+impl KatanaClient {
+    pub fn new(url: impl AsRef<str>) -> Result<Self, Error> {
+        Ok(Self { inner: HttpClientBuilder::default().build(url)? })
+    }
+
+    pub async fn chain_id(&self) -> Result<ChainId, Error> {
+        let chain_id: FeltAsHex = self.inner.request("starknet_chainId", rpc_params![]).await?;
+        Ok(FieldElement::from(chain_id).into())
+    }
+
+    pub async fn block_number(&self) -> Result<BlockNumber, Error> {
+        self.inner.request("starknet_blockNumber", rpc_params![]).await
+    }
+
+    /// Returns the nonce of the contract at `address` in the pending block.
+    pub async fn nonce(&self, address: ContractAddress) -> Result<Nonce, Error> {
+        let block_id = BlockIdOrTag::Tag(BlockTag::Pending);
+        let address = FieldElement::from(address);
+        let nonce: FeltAsHex =
+            self.inner.request("starknet_getNonce", rpc_params![block_id, address]).await?;
+        Ok(nonce.into())
+    }
+
+    /// Submits an invoke transaction, and returns its hash.
+    pub async fn add_invoke_transaction(&self, tx: BroadcastedInvokeTx) -> Result<TxHash, Error> {
+        let result: InvokeTransactionResult =
+            self.inner.request("starknet_addInvokeTransaction", rpc_params![tx]).await?;
+        Ok(result.transaction_hash)
+    }
+
+    /// Submits a declaration, and returns its hash along with the hash of the declared class.
+    pub async fn add_declare_transaction(
+        &self,
+        tx: BroadcastedDeclareTx,
+    ) -> Result<DeclareTransactionResult, Error> {
+        self.inner.request("starknet_addDeclareTransaction", rpc_params![tx]).await
+    }
+
+    /// Submits an account deployment, and returns its hash along with the address of the account.
+    pub async fn add_deploy_account_transaction(
+        &self,
+        tx: BroadcastedDeployAccountTx,
+    ) -> Result<DeployAccountTransactionResult, Error> {
+        self.inner.request("starknet_addDeployAccountTransaction", rpc_params![tx]).await
+    }
+
+    pub async fn transaction_receipt(&self, hash: TxHash) -> Result<MaybePendingTxReceipt, Error> {
+        self.inner.request("starknet_getTransactionReceipt", rpc_params![hash]).await
+    }
+
+    pub async fn predeployed_accounts(&self) -> Result<Vec<Account>, Error> {
+        self.inner.request("katana_predeployedAccounts", rpc_params![]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::transaction::DeployAccountTx;
+    use starknet::macros::felt;
+
+    use super::*;
+
+    fn builder() -> InvokeTxBuilder {
+        InvokeTxBuilder::new(felt!("0x1").into()).nonce(felt!("0x2")).calldata(vec![felt!("0x3")])
+    }
+
+    #[test]
+    fn build_invoke_tx() {
+        let tx = builder().max_fee(felt!("0x100")).build().unwrap();
+        assert!(
+            matches!(&tx.0, BroadcastedInvokeTransaction::V1(tx) if tx.max_fee == felt!("0x100"))
+        );
+
+        let tx = builder().l1_gas(10, 20, 1).build().unwrap();
+        let BroadcastedInvokeTransaction::V3(tx) = &tx.0 else { panic!("expected a V3 tx") };
+        assert_eq!(tx.resource_bounds.l1_gas.max_amount, 10);
+        assert_eq!(tx.resource_bounds.l1_gas.max_price_per_unit, 20);
+        assert_eq!(tx.tip, 1);
+        assert_eq!(tx.fee_data_availability_mode, DataAvailabilityMode::L1);
+
+        let tx = builder()
+            .l1_gas(10, 20, 0)
+            .paymaster_data(vec![felt!("0x4")])
+            .data_availability_modes(DataAvailabilityMode::L2, DataAvailabilityMode::L1)
+            .build()
+            .unwrap();
+        let BroadcastedInvokeTransaction::V3(tx) = &tx.0 else { panic!("expected a V3 tx") };
+        assert_eq!(tx.paymaster_data, [felt!("0x4")]);
+        assert_eq!(tx.nonce_data_availability_mode, DataAvailabilityMode::L2);
+
+        // the hash commits to whether the transaction is a query
+        let hash = builder().max_fee(felt!("0x100")).hash(ChainId::GOERLI).unwrap();
+        let query_hash = builder().max_fee(felt!("0x100")).query(true).hash(ChainId::GOERLI);
+        assert_ne!(hash, query_hash.unwrap());
+    }
+
+    #[test]
+    fn invalid_invoke_tx() {
+        let no_nonce = InvokeTxBuilder::new(felt!("0x1").into()).max_fee(felt!("0x1")).build();
+        assert_eq!(no_nonce.unwrap_err(), RequestError::MissingNonce);
+        assert_eq!(builder().build().unwrap_err(), RequestError::MissingFeeBound);
+
+        assert_eq!(
+            builder().max_fee(felt!("0x0")).build().unwrap_err(),
+            RequestError::ZeroFeeBound
+        );
+        assert_eq!(builder().l1_gas(10, 0, 0).build().unwrap_err(), RequestError::ZeroFeeBound);
+        assert!(builder().max_fee(felt!("0x0")).query(true).build().is_ok());
+
+        let max_fee = felt!("0x100000000000000000000000000000000");
+        assert_eq!(
+            builder().max_fee(max_fee).build().unwrap_err(),
+            RequestError::MaxFeeTooLarge(max_fee)
+        );
+
+        let calldata = builder().calldata(vec![felt!("0x0"); 3]).max_calldata_len(2);
+        assert_eq!(
+            calldata.max_fee(felt!("0x1")).build().unwrap_err(),
+            RequestError::CalldataTooLong { len: 3, max: 2 }
+        );
+        let calldata = builder().calldata(vec![felt!("0x0"); DEFAULT_MAX_CALLDATA_LEN + 1]);
+        assert!(matches!(
+            calldata.max_fee(felt!("0x1")).build(),
+            Err(RequestError::CalldataTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn build_deploy_account_tx() {
+        let builder = DeployAccountTxBuilder::new(felt!("0x1"), felt!("0x2"))
+            .constructor_calldata(vec![felt!("0x3")])
+            .max_fee(felt!("0x100"));
+        let address = builder.contract_address();
+        let hash = builder.hash(ChainId::GOERLI).unwrap();
+
+        let tx = builder.build().unwrap().into_tx_with_chain_id(ChainId::GOERLI);
+        assert_eq!(tx.calculate_hash(false), hash);
+        let DeployAccountTx::V1(tx) = tx else { panic!("expected a V1 tx") };
+        assert_eq!(tx.contract_address, address);
+        assert_eq!(tx.nonce, FieldElement::ZERO);
+
+        let tx = DeployAccountTxBuilder::new(felt!("0x1"), felt!("0x2")).l1_gas(10, 20, 0).build();
+        assert!(matches!(tx.unwrap().0, BroadcastedDeployAccountTransaction::V3(_)));
+
+        let tx = DeployAccountTxBuilder::new(felt!("0x1"), felt!("0x2"))
+            .constructor_calldata(vec![felt!("0x0"); 2])
+            .max_calldata_len(1)
+            .max_fee(felt!("0x100"))
+            .build();
+        assert_eq!(tx.unwrap_err(), RequestError::CalldataTooLong { len: 2, max: 1 });
+    }
+}
@@ -1,6 +1,6 @@
 use jsonrpsee::core::Error;
 use jsonrpsee::types::error::CallError;
-use jsonrpsee::types::ErrorObject;
+use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
 
 #[derive(thiserror::Error, Clone, Copy, Debug)]
 #[allow(clippy::enum_variant_names)]
@@ -11,10 +11,32 @@ pub enum KatanaApiError {
     FailedToDumpState = 2,
     #[error("Failed to update storage.")]
     FailedToUpdateStorage = 3,
+    #[error("Transaction origin metadata is too large.")]
+    TransactionOriginTooLarge = 4,
+    #[error("External block building is disabled.")]
+    ExternalBlockBuildingDisabled = 5,
+    #[error("Transaction is not in the pool.")]
+    TransactionNotInPool = 6,
+    #[error("A block is already being produced.")]
+    BlockProductionOngoing = 7,
+    #[error("Address label is too long.")]
+    AddressLabelTooLong = 8,
+    #[error("Subscription closed because it lagged behind.")]
+    SubscriptionLagged = 9,
+    #[error("Address label has invalid characters.")]
+    InvalidAddressLabel = 10,
+    #[error("Too many addresses are labeled.")]
+    TooManyAddressLabels = 11,
+}
+
+impl From<KatanaApiError> for ErrorObjectOwned {
+    fn from(err: KatanaApiError) -> Self {
+        ErrorObject::owned(err as i32, err.to_string(), None::<()>)
+    }
 }
 
 impl From<KatanaApiError> for Error {
     fn from(err: KatanaApiError) -> Self {
-        Error::Call(CallError::Custom(ErrorObject::owned(err as i32, err.to_string(), None::<()>)))
+        Error::Call(CallError::Custom(err.into()))
     }
 }
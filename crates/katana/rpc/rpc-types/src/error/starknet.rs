@@ -12,6 +12,8 @@ use serde::Serialize;
 pub enum StarknetApiError {
     #[error("Failed to write transaction")]
     FailedToReceiveTxn,
+    #[error("No trace available for transaction")]
+    NoTraceAvailable,
     #[error("Contract not found")]
     ContractNotFound,
     #[error("Invalid message selector")]
@@ -81,6 +83,7 @@ impl StarknetApiError {
     pub fn code(&self) -> i32 {
         match self {
             StarknetApiError::FailedToReceiveTxn => 1,
+            StarknetApiError::NoTraceAvailable => 10,
             StarknetApiError::ContractNotFound => 20,
             StarknetApiError::InvalidMessageSelector => 21,
             StarknetApiError::InvalidCallData => 22,
@@ -169,6 +172,7 @@ mod tests {
     #[case(StarknetApiError::InvalidContractClass, 50, "Invalid contract class")]
     #[case(StarknetApiError::PageSizeTooBig, 31, "Requested page size is too big")]
     #[case(StarknetApiError::FailedToReceiveTxn, 1, "Failed to write transaction")]
+    #[case(StarknetApiError::NoTraceAvailable, 10, "No trace available for transaction")]
     #[case(StarknetApiError::InvalidMessageSelector, 21, "Invalid message selector")]
     #[case(StarknetApiError::InvalidTransactionNonce, 52, "Invalid transaction nonce")]
     #[case(StarknetApiError::NonAccount, 58, "Sender address in not an account contract")]
@@ -0,0 +1,157 @@
+//! Types of the `eth_*` compatibility methods, encoded as in the Ethereum JSON-RPC API.
+
+use std::fmt;
+
+use alloy_primitives::U256;
+use katana_primitives::block::{BlockIdOrTag, BlockTag};
+use katana_primitives::FieldElement;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A number encoded as an Ethereum quantity, a `0x` prefixed hex string without leading zeros.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quantity(pub U256);
+
+impl From<u64> for Quantity {
+    fn from(value: u64) -> Self {
+        Self(U256::from(value))
+    }
+}
+
+impl From<U256> for Quantity {
+    fn from(value: U256) -> Self {
+        Self(value)
+    }
+}
+
+impl From<FieldElement> for Quantity {
+    fn from(value: FieldElement) -> Self {
+        Self(U256::from_be_bytes(value.to_bytes_be()))
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#x}", self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        parse_quantity(&value).map(Self).map_err(de::Error::custom)
+    }
+}
+
+/// The block parameter of the `eth_*` methods, a block tag or a block number.
+///
+/// Blocks are final as soon as they are produced by Katana, so the `safe` and `finalized` tags
+/// are the latest block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EthBlockId {
+    Earliest,
+    #[default]
+    Latest,
+    Pending,
+    Number(u64),
+}
+
+impl From<EthBlockId> for BlockIdOrTag {
+    fn from(value: EthBlockId) -> Self {
+        match value {
+            EthBlockId::Earliest => BlockIdOrTag::Number(0),
+            EthBlockId::Latest => BlockIdOrTag::Tag(BlockTag::Latest),
+            EthBlockId::Pending => BlockIdOrTag::Tag(BlockTag::Pending),
+            EthBlockId::Number(number) => BlockIdOrTag::Number(number),
+        }
+    }
+}
+
+impl Serialize for EthBlockId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            EthBlockId::Earliest => serializer.serialize_str("earliest"),
+            EthBlockId::Latest => serializer.serialize_str("latest"),
+            EthBlockId::Pending => serializer.serialize_str("pending"),
+            EthBlockId::Number(number) => Quantity::from(*number).serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EthBlockId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EthBlockIdVisitor;
+
+        impl<'de> Visitor<'de> for EthBlockIdVisitor {
+            type Value = EthBlockId;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a block tag or a hex encoded block number")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                match value {
+                    "earliest" => Ok(EthBlockId::Earliest),
+                    "latest" | "safe" | "finalized" => Ok(EthBlockId::Latest),
+                    "pending" => Ok(EthBlockId::Pending),
+                    value => {
+                        let number = parse_quantity(value).map_err(E::custom)?;
+                        let number =
+                            number.try_into().map_err(|_| E::custom("block number overflow"))?;
+                        Ok(EthBlockId::Number(number))
+                    }
+                }
+            }
+        }
+
+        deserializer.deserialize_str(EthBlockIdVisitor)
+    }
+}
+
+fn parse_quantity(value: &str) -> Result<U256, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .filter(|digits| !digits.is_empty())
+        .ok_or_else(|| format!("invalid quantity `{value}`, expected a 0x prefixed hex string"))?;
+    U256::from_str_radix(digits, 16).map_err(|e| format!("invalid quantity `{value}`: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn quantity_serde() {
+        assert_eq!(serde_json::to_value(Quantity::from(0)).unwrap(), json!("0x0"));
+        assert_eq!(serde_json::to_value(Quantity::from(1024)).unwrap(), json!("0x400"));
+
+        // the chain id of Starknet goerli, `SN_GOERLI`
+        let chain_id = FieldElement::from_hex_be("0x534e5f474f45524c49").unwrap();
+        assert_eq!(
+            serde_json::to_value(Quantity::from(chain_id)).unwrap(),
+            json!("0x534e5f474f45524c49")
+        );
+
+        let quantity: Quantity = serde_json::from_value(json!("0x400")).unwrap();
+        assert_eq!(quantity, Quantity::from(1024));
+        assert!(serde_json::from_value::<Quantity>(json!("0x")).is_err());
+        assert!(serde_json::from_value::<Quantity>(json!("400")).is_err());
+    }
+
+    #[test]
+    fn block_id_serde() {
+        let block_id = |value| serde_json::from_value::<EthBlockId>(value).unwrap();
+        assert_eq!(block_id(json!("earliest")), EthBlockId::Earliest);
+        assert_eq!(block_id(json!("latest")), EthBlockId::Latest);
+        assert_eq!(block_id(json!("finalized")), EthBlockId::Latest);
+        assert_eq!(block_id(json!("pending")), EthBlockId::Pending);
+        assert_eq!(block_id(json!("0x10")), EthBlockId::Number(16));
+        assert!(serde_json::from_value::<EthBlockId>(json!("0x10000000000000000")).is_err());
+        assert!(serde_json::from_value::<EthBlockId>(json!(16)).is_err());
+
+        assert_eq!(BlockIdOrTag::from(EthBlockId::Earliest), BlockIdOrTag::Number(0));
+        assert_eq!(serde_json::to_value(EthBlockId::Number(16)).unwrap(), json!("0x10"));
+    }
+}
@@ -5,11 +5,19 @@
 
 pub mod account;
 pub mod block;
+#[cfg(feature = "client")]
+pub mod client;
 pub mod error;
+pub mod eth;
 pub mod event;
+pub mod limits;
 pub mod message;
+pub mod pool;
 pub mod receipt;
+pub mod scheduler;
 pub mod state_update;
+pub mod stats;
+pub mod time;
 pub mod trace;
 pub mod transaction;
 
@@ -0,0 +1,18 @@
+use katana_core::service::block_producer;
+use serde::{Deserialize, Serialize};
+
+/// The resource limits of the blocks produced by the node. The transactions which would take a
+/// block over one of its limits are carried over to the next block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockLimits {
+    /// The maximum amount of L1 gas used by the transactions of a block, `None` if unlimited.
+    pub max_l1_gas: Option<u128>,
+    /// The maximum number of Cairo steps run by the transactions of a block, `None` if unlimited.
+    pub max_cairo_steps: Option<u128>,
+}
+
+impl From<block_producer::BlockLimits> for BlockLimits {
+    fn from(limits: block_producer::BlockLimits) -> Self {
+        Self { max_l1_gas: limits.max_l1_gas, max_cairo_steps: limits.max_cairo_steps }
+    }
+}
@@ -1,8 +1,13 @@
+use alloy_primitives::B256;
 use katana_primitives::chain::ChainId;
-use katana_primitives::transaction::L1HandlerTx;
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::receipt::MessageToL1;
+use katana_primitives::transaction::{L1HandlerTx, TxHash};
 use katana_primitives::utils::transaction::compute_l1_message_hash;
 use katana_primitives::FieldElement;
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MsgFromL1(starknet::core::types::MsgFromL1);
@@ -29,3 +34,44 @@ impl MsgFromL1 {
         }
     }
 }
+
+/// A message sent from L2 to the settlement chain.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageToL1WithStatus {
+    /// The hash of the transaction that sent the message.
+    #[serde_as(as = "UfeHex")]
+    pub transaction_hash: TxHash,
+    pub from_address: ContractAddress,
+    #[serde_as(as = "UfeHex")]
+    pub to_address: FieldElement,
+    #[serde_as(as = "Vec<UfeHex>")]
+    pub payload: Vec<FieldElement>,
+    /// The hash of the message, used to consume it on the settlement chain.
+    pub message_hash: B256,
+    /// Whether the message hash has been registered on the settlement chain, meaning that the
+    /// message can be consumed.
+    pub settled: bool,
+    /// Whether the message has been seen consumed on the settlement chain.
+    pub consumed: bool,
+}
+
+impl MessageToL1WithStatus {
+    pub fn new(transaction_hash: TxHash, message: MessageToL1, settled: bool) -> Self {
+        let message_hash = compute_l1_message_hash(
+            message.from_address.into(),
+            message.to_address,
+            &message.payload,
+        );
+
+        Self {
+            settled,
+            consumed: false,
+            message_hash,
+            transaction_hash,
+            payload: message.payload,
+            to_address: message.to_address,
+            from_address: message.from_address,
+        }
+    }
+}
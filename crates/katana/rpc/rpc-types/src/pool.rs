@@ -0,0 +1,51 @@
+use katana_primitives::block::{BlockHash, BlockNumber};
+use katana_primitives::transaction::TxHash;
+use serde::{Deserialize, Serialize};
+
+use crate::receipt::PendingTxReceipt;
+use crate::transaction::Tx;
+
+/// A transaction entering or leaving the transaction pool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PoolTxEvent {
+    /// A validated transaction entered the pool.
+    Added { transaction: Tx },
+    /// A transaction left the pool and was included in a block.
+    Removed { transaction_hash: TxHash },
+    /// A transaction left the pool but failed to execute, and was dropped.
+    Rejected { transaction_hash: TxHash },
+}
+
+/// A block built externally and sealed with `katana_sealBlock`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedBlock {
+    pub block_number: BlockNumber,
+    pub block_hash: BlockHash,
+    /// The hashes of the transactions included in the block, in order. The transactions which
+    /// failed to execute aren't included.
+    pub transaction_hashes: Vec<TxHash>,
+}
+
+/// The next block as it would be produced right now, returned by `katana_previewNextBlock`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NextBlockPreview {
+    pub block_number: BlockNumber,
+    pub timestamp: u64,
+    /// The transactions the block would include, in order, with their simulated receipts.
+    pub transactions: Vec<PreviewedTx>,
+    /// The transactions of the pool which would fail to execute, and wouldn't be included.
+    pub rejected: Vec<RejectedTx>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewedTx {
+    pub transaction: Tx,
+    pub receipt: PendingTxReceipt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedTx {
+    pub transaction_hash: TxHash,
+    pub reason: String,
+}
@@ -1,6 +1,6 @@
 use katana_primitives::block::{BlockHash, BlockNumber, FinalityStatus};
 use katana_primitives::receipt::{MessageToL1, Receipt, TxExecutionResources};
-use katana_primitives::transaction::TxHash;
+use katana_primitives::transaction::{TxHash, TxOrigin};
 use serde::{Deserialize, Serialize};
 use starknet::core::types::{
     DeclareTransactionReceipt, DeployAccountTransactionReceipt, ExecutionResult, FeePayment,
@@ -10,6 +10,8 @@ use starknet::core::types::{
     PriceUnit, TransactionFinalityStatus, TransactionReceipt,
 };
 
+use crate::account::AddressLabel;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct TxReceipt(starknet::core::types::TransactionReceipt);
@@ -220,6 +222,19 @@ pub enum MaybePendingTxReceipt {
     Pending(PendingTxReceipt),
 }
 
+/// The receipt of a transaction along with the origin metadata submitted with it, which isn't
+/// part of the Starknet receipt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxReceiptWithOrigin {
+    pub receipt: MaybePendingTxReceipt,
+    /// `None` if the transaction wasn't submitted with any origin metadata.
+    pub origin: Option<TxOrigin>,
+    /// The names given to the addresses of the transaction: its sender, the contracts of its
+    /// execution trace and the emitters of its events.
+    #[serde(default)]
+    pub labels: Vec<AddressLabel>,
+}
+
 impl From<starknet::core::types::TransactionReceipt> for TxReceipt {
     fn from(receipt: starknet::core::types::TransactionReceipt) -> Self {
         Self(receipt)
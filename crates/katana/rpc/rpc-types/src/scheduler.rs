@@ -0,0 +1,38 @@
+use katana_core::service::scheduler;
+use serde::{Deserialize, Serialize};
+
+/// The runs of a recurring maintenance task of the node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    pub name: String,
+    /// The interval between the runs of the task, in seconds.
+    pub interval: u64,
+    /// The number of completed runs, failed ones included.
+    pub runs: u64,
+    pub failures: u64,
+    /// The time the last run started at, as a UNIX timestamp in seconds.
+    pub last_run_at: Option<u64>,
+    /// The duration of the last run, in milliseconds.
+    pub last_run_duration_ms: Option<u64>,
+    /// The error of the last run, `None` if it succeeded.
+    pub last_error: Option<String>,
+    /// The time the next run is due at, as a UNIX timestamp in seconds.
+    pub next_run_at: Option<u64>,
+    pub running: bool,
+}
+
+impl From<scheduler::ScheduledTask> for ScheduledTask {
+    fn from(task: scheduler::ScheduledTask) -> Self {
+        Self {
+            name: task.name.to_string(),
+            interval: task.interval.as_secs(),
+            runs: task.runs,
+            failures: task.failures,
+            last_run_at: task.last_run_at,
+            last_run_duration_ms: task.last_run_duration.map(|d| d.as_millis() as u64),
+            last_error: task.last_error,
+            next_run_at: task.next_run_at,
+            running: task.running,
+        }
+    }
+}
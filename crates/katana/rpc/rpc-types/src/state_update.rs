@@ -1,3 +1,7 @@
+use std::collections::HashSet;
+
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::FieldElement;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::{
     ContractStorageDiffItem, DeclaredClassItem, DeployedContractItem, NonceUpdate, StorageEntry,
@@ -22,6 +26,20 @@ pub struct PendingStateUpdate(starknet::core::types::PendingStateUpdate);
 #[serde(transparent)]
 pub struct StateDiff(pub starknet::core::types::StateDiff);
 
+impl StateUpdate {
+    /// Removes the changes of the contracts that aren't in `contracts`. Class declarations aren't
+    /// related to any contract and are kept.
+    pub fn retain_contracts(&mut self, contracts: &HashSet<ContractAddress>) {
+        let diff = &mut self.0.state_diff;
+        let retained = |address: FieldElement| contracts.contains(&ContractAddress::from(address));
+
+        diff.storage_diffs.retain(|diff| retained(diff.address));
+        diff.deployed_contracts.retain(|contract| retained(contract.address));
+        diff.replaced_classes.retain(|class| retained(class.contract_address));
+        diff.nonces.retain(|nonce| retained(nonce.contract_address));
+    }
+}
+
 impl From<starknet::core::types::StateUpdate> for StateUpdate {
     fn from(value: starknet::core::types::StateUpdate) -> Self {
         StateUpdate(value)
@@ -0,0 +1,52 @@
+use katana_primitives::block::{BlockNumber, BlockStats};
+use serde::{Deserialize, Serialize};
+
+/// The aggregated activity of a range of blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainStats {
+    /// The total number of transactions in the range.
+    pub tx_count: u64,
+    /// The total number of events emitted in the range.
+    pub events_count: u64,
+    /// The total number of classes declared in the range.
+    pub declared_classes_count: u64,
+    /// The activity of each block of the range.
+    pub blocks: Vec<BlockStatsWithNumber>,
+}
+
+/// The aggregated activity of a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockStatsWithNumber {
+    pub block_number: BlockNumber,
+    pub tx_count: u64,
+    /// The number of distinct accounts that sent a transaction in the block.
+    pub unique_senders: u64,
+    pub events_count: u64,
+    pub declared_classes_count: u64,
+}
+
+impl ChainStats {
+    /// Aggregates the stats of the given blocks.
+    ///
+    /// The unique senders of each block aren't summed, as an account that sent transactions in
+    /// several blocks would be counted more than once.
+    pub fn new(stats: Vec<(BlockNumber, BlockStats)>) -> Self {
+        let mut chain_stats = Self::default();
+
+        for (block_number, stats) in stats {
+            chain_stats.tx_count += stats.tx_count;
+            chain_stats.events_count += stats.events_count;
+            chain_stats.declared_classes_count += stats.declared_classes_count;
+
+            chain_stats.blocks.push(BlockStatsWithNumber {
+                block_number,
+                tx_count: stats.tx_count,
+                unique_senders: stats.unique_senders,
+                events_count: stats.events_count,
+                declared_classes_count: stats.declared_classes_count,
+            });
+        }
+
+        chain_stats
+    }
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// The time of the chain, compared to the clock of the node. All timestamps are UNIX timestamps
+/// in seconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainTime {
+    /// The timestamp of the latest block.
+    pub latest_block_timestamp: u64,
+    /// The timestamp the next block would have if it was produced now.
+    pub next_block_timestamp: u64,
+    /// The current time of the clock the block timestamps are derived from.
+    pub clock_timestamp: u64,
+    /// The current time of the system clock of the node.
+    pub wall_clock_timestamp: u64,
+    /// The number of seconds the next block timestamp is ahead of the clock, negative if it's
+    /// behind.
+    pub drift: i64,
+}
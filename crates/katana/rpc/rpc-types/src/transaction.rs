@@ -39,6 +39,13 @@ impl BroadcastedInvokeTx {
         }
     }
 
+    pub fn calldata_len(&self) -> usize {
+        match &self.0 {
+            BroadcastedInvokeTransaction::V1(tx) => tx.calldata.len(),
+            BroadcastedInvokeTransaction::V3(tx) => tx.calldata.len(),
+        }
+    }
+
     pub fn into_tx_with_chain_id(self, chain_id: ChainId) -> InvokeTx {
         match self.0 {
             BroadcastedInvokeTransaction::V1(tx) => InvokeTx::V1(InvokeTxV1 {
@@ -181,6 +188,13 @@ impl BroadcastedDeployAccountTx {
         }
     }
 
+    pub fn constructor_calldata_len(&self) -> usize {
+        match &self.0 {
+            BroadcastedDeployAccountTransaction::V1(tx) => tx.constructor_calldata.len(),
+            BroadcastedDeployAccountTransaction::V3(tx) => tx.constructor_calldata.len(),
+        }
+    }
+
     pub fn into_tx_with_chain_id(self, chain_id: ChainId) -> DeployAccountTx {
         match self.0 {
             BroadcastedDeployAccountTransaction::V1(tx) => {
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use katana_rpc_api::ApiKind;
 
 #[derive(Debug, Clone)]
@@ -6,6 +8,13 @@ pub struct ServerConfig {
     pub host: String,
     pub max_connections: u32,
     pub apis: Vec<ApiKind>,
+    /// The maximum number of requests allowed in a single batch. No limit if `None`.
+    pub max_batch_size: Option<u32>,
+    /// The maximum number of calls per second allowed for each method.
+    pub rate_limits: HashMap<String, u32>,
+    /// Whether the caller of a request is read from the `x-forwarded-for` and `x-real-ip`
+    /// headers, which are only trustworthy behind a reverse proxy setting them.
+    pub trust_forwarded_headers: bool,
 }
 
 impl ServerConfig {
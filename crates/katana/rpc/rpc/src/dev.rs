@@ -1,11 +1,21 @@
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use jsonrpsee::core::{async_trait, Error};
+use katana_core::backend::labels::AddressLabelError;
 use katana_core::sequencer::KatanaSequencer;
 use katana_executor::ExecutorFactory;
+use katana_primitives::transaction::{ExecutableTx, ExecutableTxWithHash, TxOrigin};
 use katana_primitives::FieldElement;
+use katana_provider::traits::block::{BlockNumberProvider, HeaderProvider};
 use katana_rpc_api::dev::DevApiServer;
 use katana_rpc_types::error::katana::KatanaApiError;
+use katana_rpc_types::error::starknet::StarknetApiError;
+use katana_rpc_types::time::ChainTime;
+use katana_rpc_types::transaction::{BroadcastedInvokeTx, InvokeTxResult};
+
+/// The maximum size (in bytes) of the JSON serialized origin metadata of a transaction.
+const MAX_TX_ORIGIN_SIZE: usize = 4096;
 
 pub struct DevApi<EF: ExecutorFactory> {
     sequencer: Arc<KatanaSequencer<EF>>,
@@ -24,9 +34,8 @@ impl<EF: ExecutorFactory> DevApiServer for DevApi<EF> {
         Ok(())
     }
 
-    async fn next_block_timestamp(&self) -> Result<(), Error> {
-        // Ok(self.sequencer.backend().env.read().block.block_timestamp.0)
-        Ok(())
+    async fn next_block_timestamp(&self) -> Result<u64, Error> {
+        Ok(self.sequencer.backend().next_block_timestamp())
     }
 
     async fn set_next_block_timestamp(&self, timestamp: u64) -> Result<(), Error> {
@@ -41,6 +50,33 @@ impl<EF: ExecutorFactory> DevApiServer for DevApi<EF> {
             .map_err(|_| Error::from(KatanaApiError::FailedToChangeNextBlockTimestamp))
     }
 
+    async fn chain_time(&self) -> Result<ChainTime, Error> {
+        let backend = self.sequencer.backend();
+        let provider = backend.blockchain.provider();
+
+        let latest_num =
+            BlockNumberProvider::latest_number(provider).map_err(StarknetApiError::from)?;
+        let latest_block_timestamp = HeaderProvider::header_by_number(provider, latest_num)
+            .map_err(StarknetApiError::from)?
+            .ok_or(StarknetApiError::BlockNotFound)?
+            .timestamp;
+
+        let next_block_timestamp = backend.next_block_timestamp();
+        let clock_timestamp = backend.clock_timestamp();
+        let wall_clock_timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("should get current UNIX timestamp")
+            .as_secs();
+
+        Ok(ChainTime {
+            latest_block_timestamp,
+            next_block_timestamp,
+            clock_timestamp,
+            wall_clock_timestamp,
+            drift: next_block_timestamp as i64 - clock_timestamp as i64,
+        })
+    }
+
     async fn set_storage_at(
         &self,
         _contract_address: FieldElement,
@@ -53,4 +89,52 @@ impl<EF: ExecutorFactory> DevApiServer for DevApi<EF> {
         //     .map_err(|_| Error::from(KatanaApiError::FailedToUpdateStorage))
         Ok(())
     }
+
+    async fn set_address_label(&self, address: FieldElement, name: String) -> Result<(), Error> {
+        let labels = &self.sequencer.backend().address_labels;
+        labels.set(address.into(), name).map_err(|error| match error {
+            AddressLabelError::TooLong => KatanaApiError::AddressLabelTooLong,
+            AddressLabelError::InvalidCharacter => KatanaApiError::InvalidAddressLabel,
+            AddressLabelError::TooMany => KatanaApiError::TooManyAddressLabels,
+        })?;
+        Ok(())
+    }
+
+    async fn add_invoke_transaction_with_origin(
+        &self,
+        invoke_transaction: BroadcastedInvokeTx,
+        origin: TxOrigin,
+    ) -> Result<InvokeTxResult, Error> {
+        if invoke_transaction.is_query() {
+            return Err(StarknetApiError::UnsupportedTransactionVersion.into());
+        }
+
+        let origin_size =
+            serde_json::to_vec(&origin).map(|bytes| bytes.len()).unwrap_or(usize::MAX);
+        if origin_size > MAX_TX_ORIGIN_SIZE {
+            return Err(KatanaApiError::TransactionOriginTooLarge.into());
+        }
+
+        let max_calldata_len = self.sequencer.backend().config.env.max_calldata_len;
+        if invoke_transaction.calldata_len() > max_calldata_len {
+            return Err(StarknetApiError::InvalidCallData.into());
+        }
+
+        let chain_id = self.sequencer.chain_id();
+
+        let tx = invoke_transaction.into_tx_with_chain_id(chain_id);
+        let tx = ExecutableTxWithHash::new(ExecutableTx::Invoke(tx));
+
+        if !self.sequencer.backend().config.env.supported_tx_versions.supports(&tx) {
+            return Err(StarknetApiError::UnsupportedTransactionVersion.into());
+        }
+
+        let tx_hash = tx.hash;
+
+        // the origin is only recorded once the pool accepted the transaction
+        self.sequencer.add_transaction_to_pool(tx);
+        self.sequencer.backend().record_tx_origin(tx_hash, origin);
+
+        Ok(tx_hash.into())
+    }
 }
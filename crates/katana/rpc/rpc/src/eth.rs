@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use alloy_primitives::U256;
+use jsonrpsee::core::{async_trait, RpcResult};
+use katana_core::sequencer::KatanaSequencer;
+use katana_executor::ExecutorFactory;
+use katana_primitives::genesis::constant::get_fee_token_balance_base_storage_address;
+use katana_primitives::FieldElement;
+use katana_rpc_api::eth::EthApiServer;
+use katana_rpc_types::error::starknet::StarknetApiError;
+use katana_rpc_types::eth::{EthBlockId, Quantity};
+
+pub struct EthApi<EF: ExecutorFactory> {
+    sequencer: Arc<KatanaSequencer<EF>>,
+}
+
+impl<EF: ExecutorFactory> EthApi<EF> {
+    pub fn new(sequencer: Arc<KatanaSequencer<EF>>) -> Self {
+        Self { sequencer }
+    }
+}
+
+#[async_trait]
+impl<EF: ExecutorFactory> EthApiServer for EthApi<EF> {
+    async fn chain_id(&self) -> RpcResult<Quantity> {
+        Ok(self.sequencer.chain_id().id().into())
+    }
+
+    async fn block_number(&self) -> RpcResult<Quantity> {
+        let block_number = self.sequencer.block_number().map_err(StarknetApiError::from)?;
+        Ok(block_number.into())
+    }
+
+    async fn get_balance(
+        &self,
+        address: FieldElement,
+        block: Option<EthBlockId>,
+    ) -> RpcResult<Quantity> {
+        let block_id = block.unwrap_or_default().into();
+        let fee_token = self.sequencer.backend.config.genesis.fee_token.address;
+
+        // the balance is an u256 stored as its low and high u128 halves
+        let low_key = get_fee_token_balance_base_storage_address(address.into());
+        let high_key = low_key + FieldElement::ONE;
+
+        let low = self
+            .sequencer
+            .storage_at(fee_token, low_key, block_id)
+            .map_err(StarknetApiError::from)?;
+        let high = self
+            .sequencer
+            .storage_at(fee_token, high_key, block_id)
+            .map_err(StarknetApiError::from)?;
+
+        let low = U256::from_be_bytes(low.to_bytes_be());
+        let high = U256::from_be_bytes(high.to_bytes_be());
+        Ok(((high << 128) + low).into())
+    }
+}
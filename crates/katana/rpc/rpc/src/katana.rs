@@ -1,10 +1,36 @@
 use std::sync::Arc;
 
+use futures::StreamExt;
+use jsonrpsee::core::error::SubscriptionClosed;
 use jsonrpsee::core::{async_trait, Error};
+use jsonrpsee::types::SubscriptionResult;
+use jsonrpsee::SubscriptionSink;
+use katana_core::pool::PoolEvent;
 use katana_core::sequencer::KatanaSequencer;
-use katana_executor::ExecutorFactory;
+use katana_core::sequencer_error::SequencerError;
+use katana_core::service::block_producer::BlockProductionError;
+use katana_executor::{ExecutionResult, ExecutorFactory};
+use katana_primitives::block::{BlockIdOrTag, BlockNumber};
+use katana_primitives::contract::ContractAddress;
+use katana_primitives::receipt::Receipt;
+use katana_primitives::trace::TxExecInfo;
+use katana_primitives::transaction::{TxHash, TxWithHash};
+use katana_provider::traits::block::{BlockHashProvider, BlockIdReader, BlockStatsProvider};
+use katana_provider::traits::transaction::{
+    ReceiptProvider, TransactionProvider, TransactionTraceProvider,
+};
 use katana_rpc_api::katana::KatanaApiServer;
-use katana_rpc_types::account::Account;
+use katana_rpc_types::account::{Account, AddressLabel};
+use katana_rpc_types::error::katana::KatanaApiError;
+use katana_rpc_types::error::starknet::StarknetApiError;
+use katana_rpc_types::limits::BlockLimits;
+use katana_rpc_types::message::MessageToL1WithStatus;
+use katana_rpc_types::pool::{NextBlockPreview, PoolTxEvent, PreviewedTx, RejectedTx, SealedBlock};
+use katana_rpc_types::receipt::{PendingTxReceipt, TxReceiptWithOrigin};
+use katana_rpc_types::scheduler::ScheduledTask;
+use katana_rpc_types::stats::ChainStats;
+
+use crate::starknet::transaction_receipt;
 
 pub struct KatanaApi<EF: ExecutorFactory> {
     sequencer: Arc<KatanaSequencer<EF>>,
@@ -28,4 +54,209 @@ impl<EF: ExecutorFactory> KatanaApiServer for KatanaApi<EF> {
             .map(|e| Account::new(*e.0, e.1))
             .collect())
     }
+
+    async fn get_messages_to_l1(
+        &self,
+        block_id: BlockIdOrTag,
+    ) -> Result<Vec<MessageToL1WithStatus>, Error> {
+        let provider = self.sequencer.backend().blockchain.provider();
+
+        let block_num: BlockNumber = BlockIdReader::convert_block_id(provider, block_id)
+            .map_err(StarknetApiError::from)?
+            .ok_or(StarknetApiError::BlockNotFound)?;
+
+        let messages = self
+            .sequencer
+            .messages_to_l1(block_num)
+            .map_err(StarknetApiError::from)?
+            .ok_or(StarknetApiError::BlockNotFound)?;
+
+        let settled_block = *self.sequencer.backend().messaging_settled_block.read();
+        let settled = settled_block.is_some_and(|num| num >= block_num);
+        let consumed = self.sequencer.backend().consumed_messages_to_l1.read();
+
+        Ok(messages
+            .into_iter()
+            .enumerate()
+            .map(|(index, (tx_hash, msg))| {
+                let mut msg = MessageToL1WithStatus::new(tx_hash, msg, settled);
+                msg.consumed = consumed.is_consumed(&msg.message_hash, (block_num, index));
+                msg
+            })
+            .collect())
+    }
+
+    async fn get_chain_stats(
+        &self,
+        from_block: BlockNumber,
+        to_block: BlockNumber,
+    ) -> Result<ChainStats, Error> {
+        let provider = self.sequencer.backend().blockchain.provider();
+
+        let stats = BlockStatsProvider::block_stats_in_range(
+            provider,
+            from_block..to_block.saturating_add(1),
+        )
+        .map_err(StarknetApiError::from)?;
+
+        Ok(ChainStats::new(stats))
+    }
+
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: TxHash,
+    ) -> Result<TxReceiptWithOrigin, Error> {
+        let receipt = transaction_receipt(&self.sequencer, transaction_hash)?;
+        let origin = self.sequencer.backend().tx_origin(&transaction_hash);
+
+        let addresses = transaction_addresses(&self.sequencer, transaction_hash)?;
+        let labels = self.sequencer.backend().address_labels.of(addresses);
+        let labels = labels.into_iter().map(|(address, name)| AddressLabel { address, name });
+
+        Ok(TxReceiptWithOrigin { receipt, origin, labels: labels.collect() })
+    }
+
+    async fn get_address_labels(&self) -> Result<Vec<AddressLabel>, Error> {
+        let labels = self.sequencer.backend().address_labels.all();
+        Ok(labels.into_iter().map(|(address, name)| AddressLabel { address, name }).collect())
+    }
+
+    fn subscribe_pool_transactions(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+        sink.accept()?;
+
+        let events = self.sequencer.pool.add_event_listener().map(|event| match event {
+            PoolEvent::Added(tx) => PoolTxEvent::Added { transaction: TxWithHash::from(tx).into() },
+            PoolEvent::Removed(transaction_hash) => PoolTxEvent::Removed { transaction_hash },
+            PoolEvent::Rejected(transaction_hash) => PoolTxEvent::Rejected { transaction_hash },
+        });
+
+        // the stream of pool events only ends if the subscription lags behind, in which case the
+        // subscriber is told so, to fetch the pool again instead of silently missing events
+        tokio::spawn(async move {
+            match sink.pipe_from_stream(events.boxed()).await {
+                SubscriptionClosed::Success => {
+                    sink.close(KatanaApiError::SubscriptionLagged);
+                }
+                SubscriptionClosed::Failed(error) => {
+                    sink.close(error);
+                }
+                SubscriptionClosed::RemotePeerAborted => {}
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn seal_block(&self, transaction_hashes: Vec<TxHash>) -> Result<SealedBlock, Error> {
+        if !self.sequencer.config.external_block_building {
+            return Err(KatanaApiError::ExternalBlockBuildingDisabled.into());
+        }
+
+        // the transactions are executed as the block is sealed
+        let sequencer = Arc::clone(&self.sequencer);
+        let sealed = tokio::task::spawn_blocking(move || sequencer.seal_block(&transaction_hashes))
+            .await
+            .map_err(|e| StarknetApiError::UnexpectedError { reason: e.to_string() })?;
+
+        let (outcome, txs) = match sealed {
+            Ok(sealed) => sealed,
+            Err(SequencerError::TransactionNotInPool(_)) => {
+                return Err(KatanaApiError::TransactionNotInPool.into());
+            }
+            Err(SequencerError::BlockProduction(BlockProductionError::BlockProductionOngoing)) => {
+                return Err(KatanaApiError::BlockProductionOngoing.into());
+            }
+            Err(e) => return Err(StarknetApiError::from(e).into()),
+        };
+
+        let provider = self.sequencer.backend().blockchain.provider();
+        let block_hash = BlockHashProvider::block_hash_by_num(provider, outcome.block_number)
+            .map_err(StarknetApiError::from)?
+            .ok_or(StarknetApiError::BlockNotFound)?;
+
+        Ok(SealedBlock {
+            block_number: outcome.block_number,
+            block_hash,
+            transaction_hashes: txs.into_iter().map(|tx| tx.tx.hash).collect(),
+        })
+    }
+
+    async fn preview_next_block(&self) -> Result<NextBlockPreview, Error> {
+        let sequencer = Arc::clone(&self.sequencer);
+        let (block_env, results) =
+            tokio::task::spawn_blocking(move || sequencer.preview_next_block())
+                .await
+                .map_err(|e| StarknetApiError::UnexpectedError { reason: e.to_string() })?
+                .map_err(StarknetApiError::from)?;
+
+        let mut transactions = Vec::new();
+        let mut rejected = Vec::new();
+        for (tx, result) in results {
+            match result {
+                ExecutionResult::Success { receipt, .. } => transactions.push(PreviewedTx {
+                    receipt: PendingTxReceipt::new(tx.hash, receipt),
+                    transaction: tx.into(),
+                }),
+                ExecutionResult::Failed { error } => rejected
+                    .push(RejectedTx { transaction_hash: tx.hash, reason: error.to_string() }),
+            }
+        }
+
+        Ok(NextBlockPreview {
+            block_number: block_env.number,
+            timestamp: block_env.timestamp,
+            transactions,
+            rejected,
+        })
+    }
+
+    async fn list_scheduled_tasks(&self) -> Result<Vec<ScheduledTask>, Error> {
+        Ok(self.sequencer.scheduler.tasks().into_iter().map(ScheduledTask::from).collect())
+    }
+
+    async fn get_block_limits(&self) -> Result<BlockLimits, Error> {
+        Ok(self.sequencer.config.block_limits.into())
+    }
+}
+
+/// Returns the addresses of the transaction `hash`: its sender, the callers and callees of its
+/// execution trace and the emitters of its events.
+fn transaction_addresses<EF: ExecutorFactory>(
+    sequencer: &KatanaSequencer<EF>,
+    hash: TxHash,
+) -> Result<Vec<ContractAddress>, StarknetApiError> {
+    let mut addresses = Vec::new();
+    let mut add = |tx: &TxWithHash, receipt: &Receipt, trace: Option<&TxExecInfo>| {
+        addresses.extend(tx.transaction.sender_address());
+        addresses.extend(receipt.events().iter().map(|event| event.from_address));
+
+        let calls = trace.into_iter().flat_map(|trace| {
+            [&trace.validate_call_info, &trace.execute_call_info, &trace.fee_transfer_call_info]
+        });
+        let mut calls = calls.flatten().collect::<Vec<_>>();
+        while let Some(call) = calls.pop() {
+            addresses.extend([call.caller_address, call.contract_address]);
+            calls.extend(&call.inner_calls);
+        }
+    };
+
+    // the transaction might still be in the pending block
+    if let Some(executor) = sequencer.pending_executor() {
+        let executor = executor.read();
+        let pending = executor.transactions().iter().find(|(tx, _)| tx.hash == hash);
+        if let Some((tx, ExecutionResult::Success { receipt, trace, .. })) = pending {
+            add(tx, receipt, Some(trace));
+            return Ok(addresses);
+        }
+    }
+
+    let provider = sequencer.backend().blockchain.provider();
+    let tx = TransactionProvider::transaction_by_hash(provider, hash)?;
+    let receipt = ReceiptProvider::receipt_by_hash(provider, hash)?;
+    if let (Some(tx), Some(receipt)) = (tx, receipt) {
+        let trace = TransactionTraceProvider::transaction_execution(provider, hash)?;
+        add(&tx, &receipt, trace.as_ref());
+    }
+
+    Ok(addresses)
 }
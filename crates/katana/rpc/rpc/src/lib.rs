@@ -2,39 +2,57 @@
 
 pub mod config;
 pub mod dev;
+pub mod eth;
 pub mod katana;
 pub mod metrics;
+pub mod middleware;
 pub mod saya;
 pub mod starknet;
 pub mod torii;
 
-use std::net::SocketAddr;
+use std::convert::Infallible;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
 use config::ServerConfig;
-use hyper::Method;
+use hyper::server::conn::AddrStream;
+use hyper::service::make_service_fn;
+use hyper::{Method, Server};
 use jsonrpsee::server::middleware::proxy_get_request::ProxyGetRequestLayer;
 use jsonrpsee::server::{AllowHosts, ServerBuilder, ServerHandle};
 use jsonrpsee::RpcModule;
 use katana_core::sequencer::KatanaSequencer;
 use katana_executor::ExecutorFactory;
 use katana_rpc_api::dev::DevApiServer;
+use katana_rpc_api::eth::EthApiServer;
 use katana_rpc_api::katana::KatanaApiServer;
 use katana_rpc_api::saya::SayaApiServer;
 use katana_rpc_api::starknet::StarknetApiServer;
 use katana_rpc_api::torii::ToriiApiServer;
 use katana_rpc_api::ApiKind;
 use metrics::RpcServerMetrics;
+use middleware::{rate_limit_methods, Forward, RpcFilterLayer};
+use tower::Layer;
+use tower_http::compression::predicate::SizeAbove;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{Any, CorsLayer};
+use tracing::error;
 
 use crate::dev::DevApi;
+use crate::eth::EthApi;
 use crate::katana::KatanaApi;
 use crate::saya::SayaApi;
 use crate::starknet::StarknetApi;
 use crate::torii::ToriiApi;
 
+/// The minimum size (in bytes) of a response body for it to be compressed.
+const MIN_COMPRESSION_SIZE: u16 = 1024;
+
+/// The maximum size (in bytes) of a request body.
+const MAX_REQUEST_BODY_SIZE: u32 = 10 * 1024 * 1024;
+
 pub async fn spawn<EF: ExecutorFactory>(
     sequencer: Arc<KatanaSequencer<EF>>,
     config: ServerConfig,
@@ -59,9 +77,14 @@ pub async fn spawn<EF: ExecutorFactory>(
             ApiKind::Saya => {
                 methods.merge(SayaApi::new(sequencer.clone()).into_rpc())?;
             }
+            ApiKind::Eth => {
+                methods.merge(EthApi::new(sequencer.clone()).into_rpc())?;
+            }
         }
     }
 
+    rate_limit_methods(&mut methods, config.rate_limits.clone())?;
+
     let cors = CorsLayer::new()
             // Allow `POST` when accessing the resource
             .allow_methods([Method::POST, Method::GET])
@@ -69,8 +92,18 @@ pub async fn spawn<EF: ExecutorFactory>(
             .allow_origin(Any)
             .allow_headers([hyper::header::CONTENT_TYPE]);
 
+    // Compress responses based on the `Accept-Encoding` header of the request. Because the size of
+    // a compressed body is not known upfront, it will be streamed using chunked transfer encoding.
+    let compression = CompressionLayer::new()
+        .gzip(true)
+        .deflate(true)
+        .br(false)
+        .zstd(false)
+        .compress_when(SizeAbove::new(MIN_COMPRESSION_SIZE));
+
     let middleware = tower::ServiceBuilder::new()
         .layer(cors)
+        .layer(compression)
         .layer(ProxyGetRequestLayer::new("/", "health")?)
         .timeout(Duration::from_secs(20));
 
@@ -78,13 +111,36 @@ pub async fn spawn<EF: ExecutorFactory>(
         .set_logger(RpcServerMetrics::new(&methods))
         .set_host_filtering(AllowHosts::Any)
         .set_middleware(middleware)
+        .max_request_body_size(MAX_REQUEST_BODY_SIZE)
         .max_connections(config.max_connections)
-        .build(config.addr())
+        .build((Ipv4Addr::LOCALHOST, 0))
         .await?;
 
-    let addr = server.local_addr()?;
+    let server_addr = server.local_addr()?;
     let handle = server.start(methods)?;
 
+    // the server doesn't expose the peer addresses of its connections, so it listens on the
+    // loopback interface behind the filter applied to each connection accepted on the address
+    let filter = RpcFilterLayer::new(
+        config.max_batch_size,
+        MAX_REQUEST_BODY_SIZE,
+        config.trust_forwarded_headers,
+    );
+    let forward = Forward::new(server_addr);
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let service = filter.with_peer(conn.remote_addr()).layer(forward.clone());
+        async move { Ok::<_, Infallible>(service) }
+    });
+
+    let listener = Server::from_tcp(TcpListener::bind(config.addr())?)?.serve(make_service);
+    let addr = listener.local_addr();
+    let stopped = handle.clone().stopped();
+    tokio::spawn(async move {
+        if let Err(error) = listener.with_graceful_shutdown(stopped).await {
+            error!(target: middleware::LOG_TARGET, %error, "Serving connections.");
+        }
+    });
+
     Ok(NodeHandle { config, handle, addr })
 }
 
@@ -0,0 +1,617 @@
+//! Request filtering for the RPC server.
+//!
+//! The [RpcFilterLayer] inspects every JSON-RPC request before it reaches the server in order to:
+//!
+//! - Log the request (caller, methods, a hash of the params) and the time it took to be served.
+//! - Reject bodies bigger than the maximum request body size.
+//! - Reject batches that contain more requests than the configured maximum batch size, whether they
+//!   are sent over HTTP or in a WebSocket message.
+//!
+//! Rejected requests are answered with an error, without being forwarded to the server. The layer
+//! is applied to the connections accepted by [spawn](crate::spawn), in front of the server which
+//! listens on the loopback interface, so that it knows the peer address of each connection and
+//! relays the messages of the WebSocket connections itself.
+//!
+//! The caller of a request is the address of its peer, or the address reported by the
+//! `x-forwarded-for` or `x-real-ip` header when the server is configured to trust them, eg.
+//! behind a reverse proxy.
+//!
+//! The rate limits are instead enforced on the methods themselves by [rate_limit_methods], so that
+//! they apply to the calls made over both HTTP and WebSocket.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures::{SinkExt, StreamExt};
+use hyper::body::{Bytes, HttpBody};
+use hyper::client::{HttpConnector, ResponseFuture};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE, UPGRADE};
+use hyper::{Body, Client, Method, Request, Response, StatusCode, Version};
+use jsonrpsee::core::server::rpc_module::MethodCallback;
+use jsonrpsee::core::traits::ToRpcParams;
+use jsonrpsee::core::Error;
+use jsonrpsee::types::error::CallError;
+use jsonrpsee::types::ErrorObject;
+use jsonrpsee::{Methods, RpcModule};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tower::{Layer, Service};
+use tracing::{debug, warn};
+
+pub(crate) const LOG_TARGET: &str = "rpc";
+
+/// JSON-RPC error code returned when a batch is bigger than the maximum batch size.
+const BATCH_TOO_LARGE_CODE: i32 = -32010;
+/// JSON-RPC error code returned when a method exceeded its rate limit.
+const RATE_LIMITED_CODE: i32 = -32011;
+/// JSON-RPC error code returned when a request body is bigger than the maximum request body size.
+const BODY_TOO_LARGE_CODE: i32 = -32012;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Layer that applies [RpcFilter] to the inner service.
+#[derive(Debug, Clone)]
+pub struct RpcFilterLayer {
+    inner: Arc<RpcFilterInner>,
+    /// The address of the peer of the connection the layer is applied to.
+    peer: Option<SocketAddr>,
+}
+
+impl RpcFilterLayer {
+    /// Creates a new layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_batch_size` - The maximum number of requests in a batch. No limit if `None`.
+    /// * `max_request_body_size` - The maximum size of a request body, in bytes.
+    /// * `trust_forwarded_headers` - Whether the caller is read from the `x-forwarded-for` and
+    ///   `x-real-ip` headers set by a reverse proxy.
+    pub fn new(
+        max_batch_size: Option<u32>,
+        max_request_body_size: u32,
+        trust_forwarded_headers: bool,
+    ) -> Self {
+        let inner =
+            RpcFilterInner { max_batch_size, max_request_body_size, trust_forwarded_headers };
+        Self { inner: Arc::new(inner), peer: None }
+    }
+
+    /// Returns the layer applied to a connection from `peer`.
+    pub fn with_peer(&self, peer: SocketAddr) -> Self {
+        Self { inner: self.inner.clone(), peer: Some(peer) }
+    }
+}
+
+impl<S> Layer<S> for RpcFilterLayer {
+    type Service = RpcFilter<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcFilter { inner, filter: self.inner.clone(), peer: self.peer }
+    }
+}
+
+#[derive(Debug)]
+struct RpcFilterInner {
+    max_batch_size: Option<u32>,
+    max_request_body_size: u32,
+    trust_forwarded_headers: bool,
+}
+
+impl RpcFilterInner {
+    /// Returns the address of the caller of `req`, received from `peer`.
+    fn caller_of(&self, req: &Request<Body>, peer: Option<SocketAddr>) -> String {
+        let forwarded = || {
+            let value =
+                req.headers().get("x-forwarded-for").or_else(|| req.headers().get("x-real-ip"));
+            // the first address of the list is the one of the client
+            Some(value?.to_str().ok()?.split(',').next()?.trim().to_string())
+        };
+
+        match self.trust_forwarded_headers.then(forwarded).flatten() {
+            Some(caller) => caller,
+            None => peer.map_or_else(|| "unknown".to_string(), |peer| peer.ip().to_string()),
+        }
+    }
+}
+
+/// Middleware that logs, and enforces the body and batch size limits of, JSON-RPC requests.
+#[derive(Debug, Clone)]
+pub struct RpcFilter<S> {
+    inner: S,
+    filter: Arc<RpcFilterInner>,
+    peer: Option<SocketAddr>,
+}
+
+impl<S> Service<Request<Body>> for RpcFilter<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError> + 'static,
+{
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // take the service that was driven to readiness and leave a clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let filter = self.filter.clone();
+        let caller = filter.caller_of(&req, self.peer);
+
+        Box::pin(async move {
+            if is_websocket_upgrade(&req) {
+                return relay_websocket(req, inner, filter.max_batch_size, caller).await;
+            }
+
+            if req.method() != Method::POST {
+                return inner.call(req).await.map_err(Into::into);
+            }
+
+            let (parts, body) = req.into_parts();
+
+            let max_size = filter.max_request_body_size;
+            let Some(bytes) = read_body(&parts, body, max_size as usize).await? else {
+                warn!(target: LOG_TARGET, %caller, "Request body too large.");
+                let message = format!("Request body exceeds the limit of {max_size} bytes");
+                return Ok(error_response(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    BODY_TOO_LARGE_CODE,
+                    message,
+                ));
+            };
+
+            let calls = match serde_json::from_slice::<Value>(&bytes) {
+                Ok(Value::Array(batch)) => {
+                    if let Some(max) = filter.max_batch_size {
+                        if batch.len() > max as usize {
+                            warn!(target: LOG_TARGET, %caller, size = %batch.len(), "Batch too large.");
+                            let message = format!("Batch size exceeds the limit of {max}");
+                            return Ok(error_response(
+                                StatusCode::OK,
+                                BATCH_TOO_LARGE_CODE,
+                                message,
+                            ));
+                        }
+                    }
+                    batch.iter().filter_map(Call::from_value).collect::<Vec<_>>()
+                }
+                Ok(value) => Call::from_value(&value).into_iter().collect(),
+                // let the server itself reply to malformed requests
+                Err(_) => Vec::new(),
+            };
+
+            let started_at = Instant::now();
+            let req = Request::from_parts(parts, Body::from(bytes));
+            let res = inner.call(req).await.map_err(Into::into)?;
+
+            for call in &calls {
+                debug!(
+                    target: LOG_TARGET,
+                    %caller,
+                    method = %call.method,
+                    params_hash = %format!("{:#x}", call.params_hash),
+                    status = %res.status().as_u16(),
+                    latency = ?started_at.elapsed(),
+                    "Served request."
+                );
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+/// Forwards the WebSocket handshake of `req` to the server and, once both connections are
+/// upgraded, relays their messages.
+async fn relay_websocket<S>(
+    mut req: Request<Body>,
+    mut inner: S,
+    max_batch_size: Option<u32>,
+    caller: String,
+) -> Result<Response<Body>, BoxError>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Error: Into<BoxError>,
+{
+    let client = hyper::upgrade::on(&mut req);
+    let mut res = inner.call(req).await.map_err(Into::into)?;
+    if res.status() != StatusCode::SWITCHING_PROTOCOLS {
+        return Ok(res);
+    }
+
+    let server = hyper::upgrade::on(&mut res);
+    tokio::spawn(async move {
+        match tokio::try_join!(client, server) {
+            Ok((client, server)) => {
+                let client = WebSocketStream::from_raw_socket(client, Role::Server, None).await;
+                let server = WebSocketStream::from_raw_socket(server, Role::Client, None).await;
+                relay_messages(client, server, max_batch_size, &caller).await;
+            }
+            Err(error) => warn!(target: LOG_TARGET, %caller, %error, "WebSocket upgrade failed."),
+        }
+    });
+
+    Ok(res)
+}
+
+/// Relays the messages between the WebSocket connections of the client and of the server until
+/// either is closed. The batches bigger than `max_batch_size` sent by the client are answered with
+/// an error instead of being forwarded.
+async fn relay_messages<C, S>(
+    client: WebSocketStream<C>,
+    server: WebSocketStream<S>,
+    max_batch_size: Option<u32>,
+    caller: &str,
+) where
+    C: AsyncRead + AsyncWrite + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (client_tx, mut client_rx) = client.split();
+    let (mut server_tx, mut server_rx) = server.split();
+    // the messages of the server and the errors of the refused batches are both sent to the client
+    let client_tx = tokio::sync::Mutex::new(client_tx);
+
+    let to_server = async {
+        while let Some(Ok(message)) = client_rx.next().await {
+            let size = match &message {
+                Message::Text(text) => batch_size(text.as_bytes()),
+                Message::Binary(bytes) => batch_size(bytes),
+                Message::Close(_) => None,
+                // the pings are answered by each connection
+                _ => continue,
+            };
+
+            if let (Some(size), Some(max)) = (size, max_batch_size) {
+                if size > max as usize {
+                    warn!(target: LOG_TARGET, %caller, %size, "Batch too large.");
+                    let message = format!("Batch size exceeds the limit of {max}");
+                    let error = Message::Text(error_body(BATCH_TOO_LARGE_CODE, message));
+                    if client_tx.lock().await.send(error).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            }
+
+            if server_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+    };
+
+    let to_client = async {
+        while let Some(Ok(message)) = server_rx.next().await {
+            if matches!(message, Message::Ping(_) | Message::Pong(_)) {
+                continue;
+            }
+            if client_tx.lock().await.send(message).await.is_err() {
+                break;
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = to_server => {}
+        _ = to_client => {}
+    }
+}
+
+/// Returns the number of requests of the JSON-RPC `message`, if it's a batch.
+fn batch_size(message: &[u8]) -> Option<usize> {
+    match serde_json::from_slice::<Value>(message) {
+        Ok(Value::Array(batch)) => Some(batch.len()),
+        _ => None,
+    }
+}
+
+/// Service forwarding the requests to the server listening at an address.
+#[derive(Debug, Clone)]
+pub struct Forward {
+    client: Client<HttpConnector>,
+    addr: SocketAddr,
+}
+
+impl Forward {
+    pub fn new(addr: SocketAddr) -> Self {
+        Self { client: Client::new(), addr }
+    }
+}
+
+impl Service<Request<Body>> for Forward {
+    type Response = Response<Body>;
+    type Error = hyper::Error;
+    type Future = ResponseFuture;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let path = req.uri().path_and_query().map_or("/", |path| path.as_str());
+        *req.uri_mut() = format!("http://{}{path}", self.addr).parse().expect("valid uri");
+        *req.version_mut() = Version::HTTP_11;
+        self.client.request(req)
+    }
+}
+
+/// Wraps the methods of `module` that have a rate limit in `rate_limits`, the maximum number of
+/// calls per second for each method, so that the calls over the limit are answered with an error.
+///
+/// Subscriptions aren't rate limited.
+pub fn rate_limit_methods(
+    module: &mut RpcModule<()>,
+    rate_limits: HashMap<String, u32>,
+) -> Result<(), Error> {
+    let limiter = Arc::new(RateLimiter::new(rate_limits));
+    let methods = Methods::from(module.clone());
+
+    let limited = methods
+        .method_names()
+        .filter(|name| limiter.limits.contains_key(*name))
+        .filter(|name| {
+            let callback = methods.method(name);
+            matches!(callback, Some(MethodCallback::Sync(_) | MethodCallback::Async(_)))
+        })
+        .collect::<Vec<_>>();
+
+    for name in limited {
+        module.remove_method(name);
+
+        let methods = methods.clone();
+        let limiter = limiter.clone();
+        module.register_async_method(name, move |params, _| {
+            let methods = methods.clone();
+            let limiter = limiter.clone();
+            async move {
+                if !limiter.check(name) {
+                    warn!(target: LOG_TARGET, method = %name, "Rate limit exceeded.");
+                    let message = format!("Rate limit exceeded for method {name}");
+                    let err = ErrorObject::owned(RATE_LIMITED_CODE, message, None::<()>);
+                    return Err(Error::Call(CallError::Custom(err)));
+                }
+
+                let params = RawParams(params.as_str().map(ToString::to_string));
+                methods.call::<_, Box<RawValue>>(name, params).await
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+/// The raw params of a call, forwarded as-is to the wrapped method.
+struct RawParams(Option<String>);
+
+impl ToRpcParams for RawParams {
+    fn to_rpc_params(self) -> Result<Option<Box<RawValue>>, serde_json::Error> {
+        self.0.map(RawValue::from_string).transpose()
+    }
+}
+
+/// Reads the body of a request, up to `max_size` bytes. Returns `None` if the body is bigger.
+async fn read_body(
+    parts: &hyper::http::request::Parts,
+    mut body: Body,
+    max_size: usize,
+) -> Result<Option<Bytes>, hyper::Error> {
+    let content_length = parts.headers.get(CONTENT_LENGTH);
+    let content_length = content_length.and_then(|v| v.to_str().ok()?.parse::<usize>().ok());
+    if content_length.is_some_and(|len| len > max_size) {
+        return Ok(None);
+    }
+
+    let mut bytes = Vec::with_capacity(content_length.unwrap_or_default());
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if bytes.len() + chunk.len() > max_size {
+            return Ok(None);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(Some(bytes.into()))
+}
+
+fn is_websocket_upgrade(req: &Request<Body>) -> bool {
+    let upgrade = req.headers().get(UPGRADE).and_then(|value| value.to_str().ok());
+    upgrade.is_some_and(|value| value.eq_ignore_ascii_case("websocket"))
+}
+
+/// A single call in a JSON-RPC request.
+struct Call {
+    method: String,
+    params_hash: u64,
+}
+
+impl Call {
+    fn from_value(value: &Value) -> Option<Self> {
+        let method = value.get("method")?.as_str()?.to_string();
+
+        let mut hasher = DefaultHasher::new();
+        value.get("params").map(|p| p.to_string()).unwrap_or_default().hash(&mut hasher);
+
+        Some(Self { method, params_hash: hasher.finish() })
+    }
+}
+
+/// Limits the number of calls per second of each method, using a fixed one second window.
+#[derive(Debug)]
+struct RateLimiter {
+    /// The maximum number of calls per second for each method.
+    limits: HashMap<String, u32>,
+    /// The start of the current window and the number of calls made in it, for each method.
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    const WINDOW: Duration = Duration::from_secs(1);
+
+    fn new(limits: HashMap<String, u32>) -> Self {
+        Self { limits, windows: Mutex::default() }
+    }
+
+    /// Records a call to `method` and returns `false` if the call exceeds the method's limit.
+    fn check(&self, method: &str) -> bool {
+        let Some(limit) = self.limits.get(method) else { return true };
+
+        let now = Instant::now();
+        let mut windows = self.windows.lock().expect("poisoned lock");
+        let (started_at, count) = windows.entry(method.to_string()).or_insert((now, 0));
+
+        if now.duration_since(*started_at) >= Self::WINDOW {
+            *started_at = now;
+            *count = 0;
+        }
+
+        if *count >= *limit {
+            false
+        } else {
+            *count += 1;
+            true
+        }
+    }
+}
+
+fn error_body(code: i32, message: String) -> String {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": { "code": code, "message": message },
+    });
+    body.to_string()
+}
+
+fn error_response(status: StatusCode, code: i32, message: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(error_body(code, message)))
+        .expect("valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use jsonrpsee::rpc_params;
+
+    use super::*;
+
+    #[test]
+    fn rate_limiter_only_limits_configured_methods() {
+        let limiter = RateLimiter::new(HashMap::from([("starknet_call".to_string(), 2)]));
+
+        assert!(limiter.check("starknet_call"));
+        assert!(limiter.check("starknet_call"));
+        assert!(!limiter.check("starknet_call"));
+
+        assert!(limiter.check("starknet_chainId"));
+        assert!(limiter.check("starknet_chainId"));
+        assert!(limiter.check("starknet_chainId"));
+    }
+
+    #[tokio::test]
+    async fn rate_limits_apply_to_the_methods() {
+        let mut module = RpcModule::new(());
+        module.register_method("limited", |_, _| Ok("limited")).unwrap();
+        module.register_method("unlimited", |_, _| Ok("unlimited")).unwrap();
+
+        let limits = HashMap::from([("limited".to_string(), 1)]);
+        rate_limit_methods(&mut module, limits).unwrap();
+
+        let res = module.call::<_, String>("limited", rpc_params![]).await.unwrap();
+        assert_eq!(res, "limited");
+        let err = module.call::<_, String>("limited", rpc_params![]).await.unwrap_err();
+        assert_matches!(err, Error::Call(CallError::Custom(err)) if err.code() == RATE_LIMITED_CODE);
+
+        for _ in 0..3 {
+            let res = module.call::<_, String>("unlimited", rpc_params![]).await.unwrap();
+            assert_eq!(res, "unlimited");
+        }
+    }
+
+    #[test]
+    fn caller_is_the_peer_unless_the_forwarded_headers_are_trusted() {
+        let peer = Some(SocketAddr::from(([10, 0, 0, 1], 4242)));
+        let req = Request::builder()
+            .header("x-forwarded-for", "1.2.3.4, 10.0.0.2")
+            .body(Body::empty())
+            .unwrap();
+
+        let filter = RpcFilterInner {
+            max_batch_size: None,
+            max_request_body_size: 0,
+            trust_forwarded_headers: false,
+        };
+        assert_eq!(filter.caller_of(&req, peer), "10.0.0.1");
+        assert_eq!(filter.caller_of(&req, None), "unknown");
+
+        let filter = RpcFilterInner { trust_forwarded_headers: true, ..filter };
+        assert_eq!(filter.caller_of(&req, peer), "1.2.3.4");
+        assert_eq!(filter.caller_of(&Request::new(Body::empty()), peer), "10.0.0.1");
+    }
+
+    #[tokio::test]
+    async fn websocket_batches_are_limited() {
+        let (client, relay_client) = tokio::io::duplex(1024);
+        let (relay_server, server) = tokio::io::duplex(1024);
+        let mut client = WebSocketStream::from_raw_socket(client, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server, Role::Server, None).await;
+
+        tokio::spawn(async move {
+            let relay_client =
+                WebSocketStream::from_raw_socket(relay_client, Role::Server, None).await;
+            let relay_server =
+                WebSocketStream::from_raw_socket(relay_server, Role::Client, None).await;
+            relay_messages(relay_client, relay_server, Some(2), "test").await;
+        });
+
+        let batch = |size: usize| {
+            let call = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "health" });
+            Message::Text(Value::Array(vec![call; size]).to_string())
+        };
+
+        // a batch over the limit is answered without reaching the server
+        client.send(batch(3)).await.unwrap();
+        let Some(Ok(Message::Text(reply))) = client.next().await else { panic!("no reply") };
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply["error"]["code"], BATCH_TOO_LARGE_CODE);
+
+        // a batch within the limit reaches the server, and its reply the client
+        client.send(batch(2)).await.unwrap();
+        assert_eq!(server.next().await.unwrap().unwrap(), batch(2));
+        server.send(Message::Text("[]".to_string())).await.unwrap();
+        assert_eq!(client.next().await.unwrap().unwrap(), Message::Text("[]".to_string()));
+    }
+
+    #[tokio::test]
+    async fn request_body_is_read_up_to_the_limit() {
+        let (parts, _) = Request::new(()).into_parts();
+        let body = read_body(&parts, Body::from(vec![0u8; 10]), 10).await.unwrap();
+        assert_eq!(body.unwrap().len(), 10);
+
+        let body = read_body(&parts, Body::from(vec![0u8; 11]), 10).await.unwrap();
+        assert!(body.is_none());
+
+        // a body announcing a bigger size is refused before being read
+        let (parts, _) =
+            Request::builder().header(CONTENT_LENGTH, "11").body(()).unwrap().into_parts();
+        let body = read_body(&parts, Body::empty(), 10).await.unwrap();
+        assert!(body.is_none());
+    }
+}
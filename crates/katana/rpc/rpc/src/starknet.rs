@@ -1,29 +1,36 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
+use futures::StreamExt;
+use jsonrpsee::core::error::SubscriptionClosed;
 use jsonrpsee::core::{async_trait, Error, RpcResult};
+use jsonrpsee::types::SubscriptionResult;
+use jsonrpsee::SubscriptionSink;
 use katana_core::backend::contract::StarknetContract;
 use katana_core::sequencer::KatanaSequencer;
 use katana_executor::{EntryPointCall, ExecutionResult, ExecutorFactory, ResultAndStates};
 use katana_primitives::block::{BlockHashOrNumber, BlockIdOrTag, FinalityStatus, PartialHeader};
+use katana_primitives::contract::ContractAddress;
 use katana_primitives::conversion::rpc::legacy_inner_to_rpc_class;
 use katana_primitives::receipt::Receipt;
+use katana_primitives::trace::TxExecInfo;
 use katana_primitives::transaction::{ExecutableTx, ExecutableTxWithHash, TxHash};
-use katana_primitives::version::CURRENT_STARKNET_VERSION;
 use katana_primitives::FieldElement;
 use katana_provider::traits::block::{BlockHashProvider, BlockIdReader, BlockNumberProvider};
 use katana_provider::traits::transaction::{
-    ReceiptProvider, TransactionProvider, TransactionStatusProvider,
+    ReceiptProvider, TransactionProvider, TransactionStatusProvider, TransactionTraceProvider,
 };
 use katana_rpc_api::starknet::StarknetApiServer;
 use katana_rpc_types::block::{
     BlockHashAndNumber, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs,
     PendingBlockWithTxHashes, PendingBlockWithTxs,
 };
+use katana_rpc_types::error::katana::KatanaApiError;
 use katana_rpc_types::error::starknet::StarknetApiError;
 use katana_rpc_types::event::{EventFilterWithPage, EventsPage};
 use katana_rpc_types::message::MsgFromL1;
 use katana_rpc_types::receipt::{MaybePendingTxReceipt, PendingTxReceipt};
-use katana_rpc_types::state_update::StateUpdate;
+use katana_rpc_types::state_update::{StateDiff, StateUpdate};
 use katana_rpc_types::trace::FunctionInvocation;
 use katana_rpc_types::transaction::{
     BroadcastedDeclareTx, BroadcastedDeployAccountTx, BroadcastedInvokeTx, BroadcastedTx,
@@ -33,13 +40,14 @@ use katana_rpc_types::{
     ContractClass, FeeEstimate, FeltAsHex, FunctionCall, SimulationFlag,
     SimulationFlagForEstimateFee,
 };
-use katana_rpc_types_builder::ReceiptBuilder;
+use katana_rpc_types_builder::{ReceiptBuilder, StateUpdateBuilder};
 use katana_tasks::{BlockingTaskPool, TokioTaskSpawner};
 use starknet::core::types::{
     BlockTag, DeclareTransactionTrace, DeployAccountTransactionTrace, ExecuteInvocation,
     InvokeTransactionTrace, L1HandlerTransactionTrace, RevertedInvocation, SimulatedTransaction,
-    TransactionExecutionStatus, TransactionStatus, TransactionTrace,
+    TransactionExecutionStatus, TransactionStatus, TransactionTrace, TransactionTraceWithHash,
 };
+use tracing::warn;
 
 pub struct StarknetApi<EF: ExecutorFactory> {
     inner: Arc<StarknetApiInner<EF>>,
@@ -224,7 +232,7 @@ impl<EF: ExecutorFactory> StarknetApiServer for StarknetApi<EF> {
                         gas_prices,
                         parent_hash: latest_hash,
                         timestamp: block_env.timestamp,
-                        version: CURRENT_STARKNET_VERSION,
+                        version: this.inner.sequencer.backend.config.env.version,
                         sequencer_address: block_env.sequencer_address,
                     };
 
@@ -302,7 +310,7 @@ impl<EF: ExecutorFactory> StarknetApiServer for StarknetApi<EF> {
                         number: block_env.number,
                         gas_prices,
                         parent_hash: latest_hash,
-                        version: CURRENT_STARKNET_VERSION,
+                        version: this.inner.sequencer.backend.config.env.version,
                         timestamp: block_env.timestamp,
                         sequencer_address: block_env.sequencer_address,
                     };
@@ -365,39 +373,7 @@ impl<EF: ExecutorFactory> StarknetApiServer for StarknetApi<EF> {
         transaction_hash: FieldElement,
     ) -> RpcResult<MaybePendingTxReceipt> {
         self.on_io_blocking_task(move |this| {
-            let provider = this.inner.sequencer.backend.blockchain.provider();
-            let receipt = ReceiptBuilder::new(transaction_hash, provider)
-                .build()
-                .map_err(|e| StarknetApiError::UnexpectedError { reason: e.to_string() })?;
-
-            match receipt {
-                Some(receipt) => Ok(MaybePendingTxReceipt::Receipt(receipt)),
-
-                None => {
-                    let executor = this.inner.sequencer.pending_executor();
-                    let pending_receipt = executor
-                        .and_then(|executor| {
-                            executor.read().transactions().iter().find_map(|(tx, res)| {
-                                if tx.hash == transaction_hash {
-                                    match res {
-                                        ExecutionResult::Failed { .. } => None,
-                                        ExecutionResult::Success { receipt, .. } => {
-                                            Some(receipt.clone())
-                                        }
-                                    }
-                                } else {
-                                    None
-                                }
-                            })
-                        })
-                        .ok_or(Error::from(StarknetApiError::TxnHashNotFound))?;
-
-                    Ok(MaybePendingTxReceipt::Pending(PendingTxReceipt::new(
-                        transaction_hash,
-                        pending_receipt,
-                    )))
-                }
-            }
+            Ok(transaction_receipt(&this.inner.sequencer, transaction_hash)?)
         })
         .await
     }
@@ -528,12 +504,22 @@ impl<EF: ExecutorFactory> StarknetApiServer for StarknetApi<EF> {
                 return Err(StarknetApiError::UnsupportedTransactionVersion.into());
             }
 
+            let max_calldata_len = this.inner.sequencer.backend.config.env.max_calldata_len;
+            if deploy_account_transaction.constructor_calldata_len() > max_calldata_len {
+                return Err(StarknetApiError::InvalidCallData.into());
+            }
+
             let chain_id = this.inner.sequencer.chain_id();
 
             let tx = deploy_account_transaction.into_tx_with_chain_id(chain_id);
             let contract_address = tx.contract_address();
 
             let tx = ExecutableTxWithHash::new(ExecutableTx::DeployAccount(tx));
+
+            if !this.inner.sequencer.backend.config.env.supported_tx_versions.supports(&tx) {
+                return Err(StarknetApiError::UnsupportedTransactionVersion.into());
+            }
+
             let tx_hash = tx.hash;
 
             this.inner.sequencer.add_transaction_to_pool(tx);
@@ -662,6 +648,11 @@ impl<EF: ExecutorFactory> StarknetApiServer for StarknetApi<EF> {
 
             let class_hash = tx.class_hash();
             let tx = ExecutableTxWithHash::new(ExecutableTx::Declare(tx));
+
+            if !this.inner.sequencer.backend.config.env.supported_tx_versions.supports(&tx) {
+                return Err(StarknetApiError::UnsupportedTransactionVersion.into());
+            }
+
             let tx_hash = tx.hash;
 
             this.inner.sequencer.add_transaction_to_pool(tx);
@@ -680,10 +671,20 @@ impl<EF: ExecutorFactory> StarknetApiServer for StarknetApi<EF> {
                 return Err(StarknetApiError::UnsupportedTransactionVersion.into());
             }
 
+            let max_calldata_len = this.inner.sequencer.backend.config.env.max_calldata_len;
+            if invoke_transaction.calldata_len() > max_calldata_len {
+                return Err(StarknetApiError::InvalidCallData.into());
+            }
+
             let chain_id = this.inner.sequencer.chain_id();
 
             let tx = invoke_transaction.into_tx_with_chain_id(chain_id);
             let tx = ExecutableTxWithHash::new(ExecutableTx::Invoke(tx));
+
+            if !this.inner.sequencer.backend.config.env.supported_tx_versions.supports(&tx) {
+                return Err(StarknetApiError::UnsupportedTransactionVersion.into());
+            }
+
             let tx_hash = tx.hash;
 
             this.inner.sequencer.add_transaction_to_pool(tx);
@@ -819,61 +820,7 @@ impl<EF: ExecutorFactory> StarknetApiServer for StarknetApi<EF> {
             for (i, ResultAndStates { result, .. }) in results.into_iter().enumerate() {
                 match result {
                     ExecutionResult::Success { trace, fee, receipt } => {
-                        let fee_transfer_invocation =
-                            trace.fee_transfer_call_info.map(|f| FunctionInvocation::from(f).0);
-                        let validate_invocation =
-                            trace.validate_call_info.map(|f| FunctionInvocation::from(f).0);
-                        let execute_invocation =
-                            trace.execute_call_info.map(|f| FunctionInvocation::from(f).0);
-                        let revert_reason = trace.revert_error;
-                        // TODO: compute the state diff
-                        let state_diff = None;
-
-                        let transaction_trace = match receipt {
-                            Receipt::Invoke(_) => {
-                                TransactionTrace::Invoke(InvokeTransactionTrace {
-                                    fee_transfer_invocation,
-                                    validate_invocation,
-                                    state_diff,
-                                    execute_invocation: if let Some(revert_reason) = revert_reason {
-                                        ExecuteInvocation::Reverted(RevertedInvocation {
-                                            revert_reason,
-                                        })
-                                    } else {
-                                        ExecuteInvocation::Success(
-                                            execute_invocation
-                                                .expect("should exist if not reverted"),
-                                        )
-                                    },
-                                })
-                            }
-
-                            Receipt::Declare(_) => {
-                                TransactionTrace::Declare(DeclareTransactionTrace {
-                                    fee_transfer_invocation,
-                                    validate_invocation,
-                                    state_diff,
-                                })
-                            }
-
-                            Receipt::DeployAccount(_) => {
-                                TransactionTrace::DeployAccount(DeployAccountTransactionTrace {
-                                    fee_transfer_invocation,
-                                    validate_invocation,
-                                    state_diff,
-                                    constructor_invocation: execute_invocation
-                                        .expect("should exist bcs tx succeed"),
-                                })
-                            }
-
-                            Receipt::L1Handler(_) => {
-                                TransactionTrace::L1Handler(L1HandlerTransactionTrace {
-                                    state_diff,
-                                    function_invocation: execute_invocation
-                                        .expect("should exist bcs tx succeed"),
-                                })
-                            }
-                        };
+                        let transaction_trace = to_rpc_trace(trace, &receipt);
 
                         simulated.push(SimulatedTransaction {
                             transaction_trace,
@@ -899,4 +846,243 @@ impl<EF: ExecutorFactory> StarknetApiServer for StarknetApi<EF> {
         })
         .await
     }
+
+    async fn trace_transaction(&self, transaction_hash: TxHash) -> RpcResult<TransactionTrace> {
+        self.on_io_blocking_task(move |this| {
+            // the transaction might still be in the pending block
+            if let Some(executor) = this.inner.sequencer.pending_executor() {
+                let executor = executor.read();
+                let pending_txs = executor.transactions();
+
+                if let Some((_, res)) =
+                    pending_txs.iter().find(|(tx, _)| tx.hash == transaction_hash)
+                {
+                    return match res {
+                        ExecutionResult::Success { trace, receipt, .. } => {
+                            Ok(to_rpc_trace(trace.clone(), receipt))
+                        }
+                        ExecutionResult::Failed { .. } => {
+                            Err(Error::from(StarknetApiError::NoTraceAvailable))
+                        }
+                    };
+                }
+            }
+
+            let provider = this.inner.sequencer.backend.blockchain.provider();
+
+            let receipt = ReceiptProvider::receipt_by_hash(provider, transaction_hash)
+                .map_err(StarknetApiError::from)?
+                .ok_or(StarknetApiError::TxnHashNotFound)?;
+
+            let trace = TransactionTraceProvider::transaction_execution(provider, transaction_hash)
+                .map_err(StarknetApiError::from)?
+                .ok_or(StarknetApiError::NoTraceAvailable)?;
+
+            Ok(to_rpc_trace(trace, &receipt))
+        })
+        .await
+    }
+
+    async fn trace_block_transactions(
+        &self,
+        block_id: BlockIdOrTag,
+    ) -> RpcResult<Vec<TransactionTraceWithHash>> {
+        self.on_io_blocking_task(move |this| {
+            if BlockIdOrTag::Tag(BlockTag::Pending) == block_id {
+                if let Some(executor) = this.inner.sequencer.pending_executor() {
+                    let traces = executor
+                        .read()
+                        .transactions()
+                        .iter()
+                        .filter_map(|(tx, res)| match res {
+                            ExecutionResult::Success { trace, receipt, .. } => {
+                                Some(TransactionTraceWithHash {
+                                    transaction_hash: tx.hash,
+                                    trace_root: to_rpc_trace(trace.clone(), receipt),
+                                })
+                            }
+                            ExecutionResult::Failed { .. } => None,
+                        })
+                        .collect::<Vec<_>>();
+
+                    return Ok(traces);
+                }
+            }
+
+            let provider = this.inner.sequencer.backend.blockchain.provider();
+
+            let block_id = BlockIdReader::convert_block_id(provider, block_id)
+                .map_err(StarknetApiError::from)?
+                .map(BlockHashOrNumber::Num)
+                .ok_or(StarknetApiError::BlockNotFound)?;
+
+            let transactions = TransactionProvider::transactions_by_block(provider, block_id)
+                .map_err(StarknetApiError::from)?
+                .ok_or(StarknetApiError::BlockNotFound)?;
+            let receipts = ReceiptProvider::receipts_by_block(provider, block_id)
+                .map_err(StarknetApiError::from)?
+                .ok_or(StarknetApiError::BlockNotFound)?;
+            let executions =
+                TransactionTraceProvider::transactions_executions_by_block(provider, block_id)
+                    .map_err(StarknetApiError::from)?
+                    .ok_or(StarknetApiError::BlockNotFound)?;
+
+            // blocks that weren't produced locally (eg. inserted without their executions) have no
+            // traces stored for them
+            if executions.len() != transactions.len() {
+                return Err(StarknetApiError::NoTraceAvailable.into());
+            }
+
+            let traces = transactions
+                .into_iter()
+                .zip(receipts)
+                .zip(executions)
+                .map(|((tx, receipt), trace)| TransactionTraceWithHash {
+                    transaction_hash: tx.hash,
+                    trace_root: to_rpc_trace(trace, &receipt),
+                })
+                .collect::<Vec<_>>();
+
+            Ok(traces)
+        })
+        .await
+    }
+
+    fn subscribe_new_state_updates(
+        &self,
+        mut sink: SubscriptionSink,
+        contract_addresses: Option<Vec<FieldElement>>,
+    ) -> SubscriptionResult {
+        sink.accept()?;
+
+        let contracts = contract_addresses.map(|addresses| {
+            addresses.into_iter().map(ContractAddress::from).collect::<HashSet<_>>()
+        });
+
+        let this = self.clone();
+        let updates = self.inner.sequencer.backend.add_mined_block_listener().filter_map(
+            move |block_number| {
+                let provider = this.inner.sequencer.backend.blockchain.provider();
+                let update = match StateUpdateBuilder::new(block_number.into(), provider).build() {
+                    Ok(Some(mut update)) => {
+                        if let Some(contracts) = &contracts {
+                            update.retain_contracts(contracts);
+                        }
+                        Some(update)
+                    }
+                    Ok(None) => None,
+                    Err(error) => {
+                        warn!(
+                            target: "rpc::starknet",
+                            %block_number, %error,
+                            "Building state update."
+                        );
+                        None
+                    }
+                };
+                futures::future::ready(update)
+            },
+        );
+
+        // the stream of mined blocks only ends if the subscription lags behind, in which case
+        // the subscriber is told so instead of silently missing blocks
+        tokio::spawn(async move {
+            match sink.pipe_from_stream(updates.boxed()).await {
+                SubscriptionClosed::Success => {
+                    sink.close(KatanaApiError::SubscriptionLagged);
+                }
+                SubscriptionClosed::Failed(error) => {
+                    sink.close(error);
+                }
+                SubscriptionClosed::RemotePeerAborted => {}
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// Converts the stored execution info of a transaction into its RPC trace representation. The
+/// receipt is used to determine the type of the transaction.
+fn to_rpc_trace(trace: TxExecInfo, receipt: &Receipt) -> TransactionTrace {
+    let fee_transfer_invocation =
+        trace.fee_transfer_call_info.map(|f| FunctionInvocation::from(f).0);
+    let validate_invocation = trace.validate_call_info.map(|f| FunctionInvocation::from(f).0);
+    let execute_invocation = trace.execute_call_info.map(|f| FunctionInvocation::from(f).0);
+    let revert_reason = trace.revert_error;
+    let state_diff = trace.state_diff.map(|diff| StateDiff::from(diff).0);
+
+    match receipt {
+        Receipt::Invoke(_) => TransactionTrace::Invoke(InvokeTransactionTrace {
+            fee_transfer_invocation,
+            validate_invocation,
+            state_diff,
+            execute_invocation: if let Some(revert_reason) = revert_reason {
+                ExecuteInvocation::Reverted(RevertedInvocation { revert_reason })
+            } else {
+                ExecuteInvocation::Success(
+                    execute_invocation.expect("should exist if not reverted"),
+                )
+            },
+        }),
+
+        Receipt::Declare(_) => TransactionTrace::Declare(DeclareTransactionTrace {
+            fee_transfer_invocation,
+            validate_invocation,
+            state_diff,
+        }),
+
+        Receipt::DeployAccount(_) => {
+            TransactionTrace::DeployAccount(DeployAccountTransactionTrace {
+                fee_transfer_invocation,
+                validate_invocation,
+                state_diff,
+                constructor_invocation: execute_invocation.expect("should exist bcs tx succeed"),
+            })
+        }
+
+        Receipt::L1Handler(_) => TransactionTrace::L1Handler(L1HandlerTransactionTrace {
+            state_diff,
+            function_invocation: execute_invocation.expect("should exist bcs tx succeed"),
+        }),
+    }
+}
+
+/// Returns the receipt of the transaction `transaction_hash`, which is either mined or in the
+/// pending block.
+pub(crate) fn transaction_receipt<EF: ExecutorFactory>(
+    sequencer: &KatanaSequencer<EF>,
+    transaction_hash: TxHash,
+) -> Result<MaybePendingTxReceipt, StarknetApiError> {
+    let provider = sequencer.backend.blockchain.provider();
+    let receipt = ReceiptBuilder::new(transaction_hash, provider)
+        .build()
+        .map_err(|e| StarknetApiError::UnexpectedError { reason: e.to_string() })?;
+
+    match receipt {
+        Some(receipt) => Ok(MaybePendingTxReceipt::Receipt(receipt)),
+
+        None => {
+            let executor = sequencer.pending_executor();
+            let pending_receipt = executor
+                .and_then(|executor| {
+                    executor.read().transactions().iter().find_map(|(tx, res)| {
+                        if tx.hash == transaction_hash {
+                            match res {
+                                ExecutionResult::Failed { .. } => None,
+                                ExecutionResult::Success { receipt, .. } => Some(receipt.clone()),
+                            }
+                        } else {
+                            None
+                        }
+                    })
+                })
+                .ok_or(StarknetApiError::TxnHashNotFound)?;
+
+            Ok(MaybePendingTxReceipt::Pending(PendingTxReceipt::new(
+                transaction_hash,
+                pending_receipt,
+            )))
+        }
+    }
 }
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use dojo_test_utils::sequencer::{get_default_test_starknet_config, TestSequencer};
+use katana_core::sequencer::SequencerConfig;
+use katana_primitives::chain::ChainId;
+use katana_rpc_types::client::{DeclareTxBuilder, InvokeTxBuilder, KatanaClient};
+use katana_rpc_types::receipt::MaybePendingTxReceipt;
+use starknet::core::types::FieldElement;
+use starknet::macros::felt;
+use starknet::signers::SigningKey;
+
+mod common;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_client_invoke_transaction() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let client = KatanaClient::new(sequencer.url()).unwrap();
+
+    let chain_id = client.chain_id().await.unwrap();
+    assert_eq!(chain_id, ChainId::GOERLI);
+    assert_eq!(client.block_number().await.unwrap(), 0);
+
+    let account = client.predeployed_accounts().await.unwrap().remove(0);
+    let nonce = client.nonce(account.address).await.unwrap();
+    assert_eq!(nonce, FieldElement::ZERO);
+
+    // an empty multicall
+    let builder = InvokeTxBuilder::new(account.address)
+        .calldata(vec![felt!("0x0")])
+        .nonce(nonce)
+        .max_fee(felt!("0x100000000000000000"));
+
+    let hash = builder.hash(ChainId::GOERLI).unwrap();
+    let signature =
+        SigningKey::from_secret_scalar(account.private_key.unwrap()).sign(&hash).unwrap();
+    let tx = builder.signature(vec![signature.r, signature.s]).build().unwrap();
+
+    let tx_hash = client.add_invoke_transaction(tx).await.unwrap();
+    assert_eq!(tx_hash, hash);
+
+    // wait for the tx to be mined
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    let receipt = client.transaction_receipt(tx_hash).await.unwrap();
+    assert!(matches!(receipt, MaybePendingTxReceipt::Receipt(_)));
+    assert_eq!(client.nonce(account.address).await.unwrap(), felt!("0x1"));
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_client_declare_transaction() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let client = KatanaClient::new(sequencer.url()).unwrap();
+    let account = client.predeployed_accounts().await.unwrap().remove(0);
+
+    let path = PathBuf::from("tests/test_data/cairo1_contract.json");
+    let (contract, compiled_class_hash) =
+        common::prepare_contract_declaration_params(&path).unwrap();
+    let builder = DeclareTxBuilder::new(account.address, contract, compiled_class_hash)
+        .nonce(client.nonce(account.address).await.unwrap())
+        .max_fee(felt!("0x100000000000000000"));
+
+    let hash = builder.hash(ChainId::GOERLI).unwrap();
+    let class_hash = builder.class_hash();
+    let signature =
+        SigningKey::from_secret_scalar(account.private_key.unwrap()).sign(&hash).unwrap();
+    let tx = builder.signature(vec![signature.r, signature.s]).build().unwrap();
+
+    let result = client.add_declare_transaction(tx).await.unwrap();
+    assert_eq!(result.transaction_hash, hash);
+    assert_eq!(result.class_hash, class_hash);
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
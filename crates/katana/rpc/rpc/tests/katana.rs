@@ -0,0 +1,277 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dojo_test_utils::sequencer::{get_default_test_starknet_config, TestSequencer};
+use jsonrpsee::http_client::HttpClientBuilder;
+use katana_core::sequencer::SequencerConfig;
+use katana_core::service::block_producer;
+use katana_primitives::block::BlockIdOrTag;
+use katana_primitives::genesis::constant::DEFAULT_FEE_TOKEN_ADDRESS;
+use katana_primitives::transaction::TxOrigin;
+use katana_rpc_api::dev::DevApiClient;
+use katana_rpc_api::katana::KatanaApiClient;
+use katana_rpc_types::limits::BlockLimits;
+use katana_rpc_types::receipt::{MaybePendingTxReceipt, TxReceiptWithOrigin};
+use katana_rpc_types::transaction::BroadcastedInvokeTx;
+use serde_json::json;
+use starknet::accounts::{Account, Call, ConnectedAccount};
+use starknet::core::types::{FieldElement, MaybePendingTransactionReceipt, TransactionReceipt};
+use starknet::core::utils::get_contract_address;
+use starknet::macros::{felt, selector};
+use starknet::providers::Provider;
+
+mod common;
+
+const ENOUGH_GAS: &str = "0x100000000000000000";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transaction_receipt_with_origin() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+    let account = sequencer.account();
+
+    let call = Call {
+        to: DEFAULT_FEE_TOKEN_ADDRESS.into(),
+        selector: selector!("transfer"),
+        calldata: vec![felt!("0x1"), felt!("0x99"), felt!("0x0")],
+    };
+
+    let nonce = account.get_nonce().await.unwrap();
+    let max_fee = FieldElement::from_hex_be(ENOUGH_GAS).unwrap();
+    let execution = account.execute(vec![call]).nonce(nonce).max_fee(max_fee).prepared().unwrap();
+    let tx_hash = execution.transaction_hash(false);
+    let tx = BroadcastedInvokeTx(execution.get_invoke_request(false).await.unwrap());
+
+    // origin metadata that is too large is rejected, along with its transaction
+    let origin = TxOrigin { data: Some(json!("a".repeat(5000))), ..Default::default() };
+    assert!(client.add_invoke_transaction_with_origin(tx.clone(), origin).await.is_err());
+
+    let origin = TxOrigin {
+        client_id: Some("client".to_string()),
+        session: Some("session".to_string()),
+        data: Some(json!({ "level": 1 })),
+    };
+    let fee_token = DEFAULT_FEE_TOKEN_ADDRESS.into();
+    client.set_address_label(fee_token, "fee token".to_string()).await.unwrap();
+    client.add_invoke_transaction_with_origin(tx, origin.clone()).await.unwrap();
+
+    // wait for the tx to be mined
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    let receipt: TxReceiptWithOrigin = client.get_transaction_receipt(tx_hash).await.unwrap();
+    assert!(matches!(receipt.receipt, MaybePendingTxReceipt::Receipt(_)));
+    assert_eq!(receipt.origin, Some(origin));
+    // the fee token is called, and emits the transfer event
+    let labels = receipt.labels.iter().map(|l| (l.address.0, l.name.as_str())).collect::<Vec<_>>();
+    assert_eq!(labels, [(fee_token, "fee token")]);
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_seal_external_block() {
+    let config = SequencerConfig { external_block_building: true, ..Default::default() };
+    let sequencer = TestSequencer::start(config, get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+    let account = sequencer.account();
+    let provider = account.provider();
+
+    let call = Call {
+        to: DEFAULT_FEE_TOKEN_ADDRESS.into(),
+        selector: selector!("transfer"),
+        calldata: vec![felt!("0x1"), felt!("0x99"), felt!("0x0")],
+    };
+
+    let max_fee = FieldElement::from_hex_be(ENOUGH_GAS).unwrap();
+    let res = account.execute(vec![call]).max_fee(max_fee).send().await.unwrap();
+
+    // the transaction is left in the pool for the block builder
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    assert_eq!(provider.block_number().await.unwrap(), 0);
+    assert!(client.seal_block(vec![felt!("0x1234")]).await.is_err());
+
+    let sealed = client.seal_block(vec![res.transaction_hash]).await.unwrap();
+    assert_eq!(sealed.block_number, 1);
+    assert_eq!(sealed.transaction_hashes, vec![res.transaction_hash]);
+    assert_eq!(provider.block_number().await.unwrap(), 1);
+
+    // the transaction left the pool with the sealed block
+    assert!(client.seal_block(vec![res.transaction_hash]).await.is_err());
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_preview_next_block() {
+    let config = SequencerConfig { external_block_building: true, ..Default::default() };
+    let sequencer = TestSequencer::start(config, get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+    let account = sequencer.account();
+    let provider = account.provider();
+
+    let preview = client.preview_next_block().await.unwrap();
+    assert_eq!(preview.block_number, 1);
+    assert!(preview.transactions.is_empty());
+
+    let call = Call {
+        to: DEFAULT_FEE_TOKEN_ADDRESS.into(),
+        selector: selector!("transfer"),
+        calldata: vec![felt!("0x1"), felt!("0x99"), felt!("0x0")],
+    };
+
+    let max_fee = FieldElement::from_hex_be(ENOUGH_GAS).unwrap();
+    let res = account.execute(vec![call]).max_fee(max_fee).send().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    let preview = client.preview_next_block().await.unwrap();
+    assert_eq!(preview.block_number, 1);
+    assert_eq!(preview.transactions.len(), 1);
+    assert_eq!(preview.transactions[0].transaction.0.transaction_hash(), &res.transaction_hash);
+    assert!(preview.rejected.is_empty());
+
+    // previewing the block doesn't produce it
+    assert_eq!(provider.block_number().await.unwrap(), 0);
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_seal_block_requires_external_block_building() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+
+    assert!(client.seal_block(vec![]).await.is_err());
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_address_labels() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+
+    client.set_address_label(felt!("0x2"), "actions".to_string()).await.unwrap();
+    client.set_address_label(felt!("0x1"), "world".to_string()).await.unwrap();
+    client.set_address_label(felt!("0x2"), "game".to_string()).await.unwrap();
+    assert!(client.set_address_label(felt!("0x3"), "a".repeat(65)).await.is_err());
+    // the labels are logged, so they can't forge log lines
+    assert!(client.set_address_label(felt!("0x3"), "a\nb".to_string()).await.is_err());
+
+    let labels = client.get_address_labels().await.unwrap();
+    let labels = labels.iter().map(|l| (l.address.0, l.name.as_str())).collect::<Vec<_>>();
+    assert_eq!(labels, [(felt!("0x1"), "world"), (felt!("0x2"), "game")]);
+
+    // an empty name removes the label
+    client.set_address_label(felt!("0x1"), String::new()).await.unwrap();
+    assert_eq!(client.get_address_labels().await.unwrap().len(), 1);
+
+    let labels = &sequencer.sequencer.backend().address_labels;
+    assert_eq!(labels.display(felt!("0x2").into()), "game (0x2)");
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_list_scheduled_tasks() {
+    let config = SequencerConfig { stats_rollup_interval: Some(1), ..Default::default() };
+    let sequencer = TestSequencer::start(config, get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+
+    let tasks = client.list_scheduled_tasks().await.unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].name, "stats_rollup");
+    assert_eq!(tasks[0].interval, 1);
+
+    // the first run is after one interval, delayed by a jitter of up to a tenth of it
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+
+    let tasks = client.list_scheduled_tasks().await.unwrap();
+    assert!(tasks[0].runs >= 1);
+    assert_eq!(tasks[0].failures, 0);
+    assert_eq!(tasks[0].last_error, None);
+    assert!(tasks[0].last_run_at.is_some());
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_block_limits() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+
+    let limits = client.get_block_limits().await.unwrap();
+    assert_eq!(limits, BlockLimits { max_l1_gas: None, max_cairo_steps: None });
+    sequencer.stop().expect("failed to stop sequencer");
+
+    let block_limits = block_producer::BlockLimits { max_l1_gas: None, max_cairo_steps: Some(500) };
+    let config = SequencerConfig { block_limits, ..Default::default() };
+    let sequencer = TestSequencer::start(config, get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+
+    let limits = client.get_block_limits().await.unwrap();
+    assert_eq!(limits, BlockLimits { max_l1_gas: None, max_cairo_steps: Some(500) });
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_messages_to_l1() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+    let account = sequencer.account();
+    let provider = account.provider();
+
+    let path = PathBuf::from("tests/test_data/cairo1_contract.json");
+    let (contract, compiled_class_hash) =
+        common::prepare_contract_declaration_params(&path).unwrap();
+    let class_hash = contract.class_hash();
+    account.declare(Arc::new(contract), compiled_class_hash).send().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    let deploy_call = common::build_deploy_cairo1_contract_call(class_hash, FieldElement::ZERO);
+    account.execute(vec![deploy_call]).send().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    let contract_address = get_contract_address(
+        FieldElement::ZERO,
+        class_hash,
+        &[FieldElement::from(1_u32), FieldElement::from(2_u32)],
+        FieldElement::ZERO,
+    );
+
+    let call = Call {
+        to: contract_address,
+        selector: selector!("test_send_message_to_l1"),
+        calldata: vec![felt!("0x1234"), felt!("0x2"), felt!("0x1"), felt!("0x2")],
+    };
+    let res = account.execute(vec![call]).send().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    let receipt = provider.get_transaction_receipt(res.transaction_hash).await.unwrap();
+    let MaybePendingTransactionReceipt::Receipt(TransactionReceipt::Invoke(receipt)) = receipt
+    else {
+        panic!("invalid tx receipt")
+    };
+
+    let block_id = BlockIdOrTag::Number(receipt.block_number);
+    let messages = client.get_messages_to_l1(block_id).await.unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].transaction_hash, res.transaction_hash);
+    assert_eq!(messages[0].from_address, contract_address.into());
+    assert_eq!(messages[0].to_address, felt!("0x1234"));
+    assert_eq!(messages[0].payload, vec![felt!("0x1"), felt!("0x2")]);
+    // messaging is disabled, so the message is never settled
+    assert!(!messages[0].settled);
+    assert!(!messages[0].consumed);
+
+    // the blocks without messages have none, and the blocks not mined yet are not found
+    assert!(client.get_messages_to_l1(BlockIdOrTag::Number(1)).await.unwrap().is_empty());
+    assert!(client.get_messages_to_l1(BlockIdOrTag::Number(100)).await.is_err());
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
@@ -1,15 +1,37 @@
 use std::fs::{self};
+use std::io::Read;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use dojo_test_utils::sequencer::{get_default_test_starknet_config, TestSequencer};
+use alloy_primitives::U256;
+use assert_matches::assert_matches;
+use dojo_test_utils::sequencer::{get_default_test_starknet_config, StarknetConfig, TestSequencer};
+use flate2::read::GzDecoder;
+use hyper::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE};
+use jsonrpsee::core::client::SubscriptionClientT;
+use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::rpc_params;
+use jsonrpsee::ws_client::WsClientBuilder;
+use katana_core::constants::DEFAULT_SEQUENCER_ADDRESS;
 use katana_core::sequencer::SequencerConfig;
+use katana_primitives::genesis::allocation::DevAllocationsGenerator;
+use katana_primitives::genesis::constant::{
+    get_fee_token_balance_base_storage_address, DEFAULT_FEE_TOKEN_ADDRESS,
+    DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH, DEFAULT_PREFUNDED_ACCOUNT_BALANCE,
+};
+use katana_primitives::genesis::Genesis;
+use katana_primitives::version::Version;
+use katana_provider::traits::block::BlockNumberProvider;
+use katana_provider::traits::env::BlockEnvProvider;
+use katana_rpc_api::dev::DevApiClient;
+use katana_rpc_api::starknet::StarknetApiClient;
 use starknet::accounts::{Account, Call, ConnectedAccount};
 use starknet::core::types::contract::legacy::LegacyContractClass;
 use starknet::core::types::{
-    BlockId, BlockTag, DeclareTransactionReceipt, FieldElement, MaybePendingTransactionReceipt,
-    TransactionFinalityStatus, TransactionReceipt,
+    BlockId, BlockTag, DeclareTransactionReceipt, ExecuteInvocation, FieldElement,
+    MaybePendingBlockWithTxHashes, MaybePendingTransactionReceipt, StateUpdate,
+    TransactionFinalityStatus, TransactionReceipt, TransactionTrace,
 };
 use starknet::core::utils::{get_contract_address, get_selector_from_name};
 use starknet::providers::Provider;
@@ -174,3 +196,222 @@ async fn test_send_declare_and_deploy_legacy_contract() {
 
     sequencer.stop().expect("failed to stop sequencer");
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_compressed_response() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "starknet_getClass",
+        "params": ["latest", format!("{DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH:#x}")],
+    });
+
+    let request = hyper::Request::post(sequencer.url().as_str())
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCEPT_ENCODING, "gzip")
+        .body(hyper::Body::from(body.to_string()))
+        .unwrap();
+
+    let response = hyper::Client::new().request(request).await.unwrap();
+    assert_eq!(response.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+
+    let compressed = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let mut decompressed = String::new();
+    GzDecoder::new(compressed.as_ref()).read_to_string(&mut decompressed).unwrap();
+
+    let response: serde_json::Value = serde_json::from_str(&decompressed).unwrap();
+    assert!(response.get("result").is_some());
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_blocks_have_the_chain_protocol_version() {
+    let mut starknet_config = get_default_test_starknet_config();
+    starknet_config.env.version = Version::new(0, 13, 1);
+
+    let sequencer = TestSequencer::start(SequencerConfig::default(), starknet_config).await;
+
+    // mine a block on top of the genesis block
+    let backend = &sequencer.sequencer.backend;
+    let provider = backend.blockchain.provider();
+    let latest = provider.latest_number().unwrap();
+    let mut block_env = provider.block_env_at(latest.into()).unwrap().unwrap();
+    backend.update_block_env(&mut block_env);
+    backend.mine_empty_block(&block_env).unwrap();
+
+    for block_number in [0, 1] {
+        let block = sequencer
+            .provider()
+            .get_block_with_tx_hashes(BlockId::Number(block_number))
+            .await
+            .unwrap();
+
+        let MaybePendingBlockWithTxHashes::Block(block) = block else {
+            panic!("block {block_number} should not be pending")
+        };
+        assert_eq!(block.starknet_version, "0.13.1");
+    }
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_subscribe_new_state_updates() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let mut url = sequencer.url();
+    url.set_scheme("ws").unwrap();
+    let client = WsClientBuilder::default().build(url).await.unwrap();
+
+    let subscribe = |contracts: Option<Vec<FieldElement>>| {
+        client.subscribe::<StateUpdate, _>(
+            "starknet_subscribeNewStateUpdates",
+            rpc_params![contracts],
+            "starknet_unsubscribeNewStateUpdates",
+        )
+    };
+    let fee_token = FieldElement::from(DEFAULT_FEE_TOKEN_ADDRESS);
+    let mut all = subscribe(None).await.unwrap();
+    let mut fee_token_only = subscribe(Some(vec![fee_token])).await.unwrap();
+
+    // a transfer changes the balances of the fee token and the nonce of the sender
+    let account = sequencer.account();
+    let call = Call {
+        to: fee_token,
+        selector: get_selector_from_name("transfer").unwrap(),
+        calldata: vec![FieldElement::ONE, FieldElement::from(0x99u8), FieldElement::ZERO],
+    };
+    account.execute(vec![call]).send().await.unwrap();
+
+    let update = all.next().await.unwrap().unwrap();
+    assert_eq!(
+        update.block_hash,
+        sequencer.provider().block_hash_and_number().await.unwrap().block_hash
+    );
+    assert!(update.state_diff.storage_diffs.iter().any(|diff| diff.address == fee_token));
+    assert!(update
+        .state_diff
+        .nonces
+        .iter()
+        .any(|nonce| nonce.contract_address == account.address()));
+
+    let update = fee_token_only.next().await.unwrap().unwrap();
+    assert!(!update.state_diff.storage_diffs.is_empty());
+    assert!(update.state_diff.storage_diffs.iter().all(|diff| diff.address == fee_token));
+    assert!(update.state_diff.nonces.is_empty());
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deterministic_nodes_produce_the_same_blocks() {
+    async fn start_node(seed: [u8; 32]) -> TestSequencer {
+        let accounts = DevAllocationsGenerator::new(2)
+            .with_seed(seed)
+            .with_balance(U256::from(DEFAULT_PREFUNDED_ACCOUNT_BALANCE))
+            .generate();
+
+        let mut genesis = Genesis::default();
+        genesis.extend_allocations(accounts.into_iter().map(|(k, v)| (k, v.into())));
+
+        let config =
+            StarknetConfig { deterministic: true, genesis, ..get_default_test_starknet_config() };
+        TestSequencer::start(SequencerConfig::default(), config).await
+    }
+
+    let nodes = [start_node([7u8; 32]).await, start_node([7u8; 32]).await];
+
+    let mut blocks = Vec::new();
+    for node in &nodes {
+        let call = Call {
+            to: FieldElement::from(DEFAULT_FEE_TOKEN_ADDRESS),
+            selector: get_selector_from_name("transfer").unwrap(),
+            calldata: vec![FieldElement::ONE, FieldElement::from(0x99u8), FieldElement::ZERO],
+        };
+        let res = node.account().execute(vec![call]).send().await.unwrap();
+
+        // wait for the tx to be mined
+        tokio::time::sleep(Duration::from_millis(WAIT_TX_DELAY_MILLIS)).await;
+
+        let block = node.provider().get_block_with_tx_hashes(BlockId::Number(1)).await.unwrap();
+        let MaybePendingBlockWithTxHashes::Block(block) = block else {
+            panic!("block 1 should not be pending")
+        };
+        assert_eq!(block.transactions, vec![res.transaction_hash]);
+        assert_eq!(block.sequencer_address, FieldElement::from(*DEFAULT_SEQUENCER_ADDRESS));
+        blocks.push(block);
+    }
+
+    assert_eq!(blocks[0].block_hash, blocks[1].block_hash);
+    assert_eq!(blocks[0].timestamp, blocks[1].timestamp);
+
+    for node in nodes {
+        node.stop().expect("failed to stop sequencer");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_transactions() {
+    let sequencer = TestSequencer::start(
+        SequencerConfig { block_time: None, no_mining: true, ..Default::default() },
+        get_default_test_starknet_config(),
+    )
+    .await;
+    let client = HttpClientBuilder::default().build(sequencer.url()).unwrap();
+    let account = sequencer.account();
+
+    let recipient = FieldElement::from(0x1234u64);
+    let call = Call {
+        to: FieldElement::from(DEFAULT_FEE_TOKEN_ADDRESS),
+        selector: get_selector_from_name("transfer").unwrap(),
+        calldata: vec![recipient, FieldElement::from(0x99u8), FieldElement::ZERO],
+    };
+    let tx_hash = account.execute(vec![call]).send().await.unwrap().transaction_hash;
+
+    // the transfer changes the balance of the recipient and the nonce of the sender
+    let assert_trace = |trace: &TransactionTrace| {
+        let TransactionTrace::Invoke(trace) = trace else { panic!("should be an invoke trace") };
+        assert_matches!(trace.execute_invocation, ExecuteInvocation::Success(_));
+
+        let diff = trace.state_diff.as_ref().expect("should have a state diff");
+        let balance = get_fee_token_balance_base_storage_address(recipient.into());
+        let fee_token = diff
+            .storage_diffs
+            .iter()
+            .find(|diff| diff.address == FieldElement::from(DEFAULT_FEE_TOKEN_ADDRESS))
+            .expect("should change the fee token storage");
+        assert!(fee_token
+            .storage_entries
+            .iter()
+            .any(|entry| entry.key == balance && entry.value == FieldElement::from(0x99u8)));
+        assert!(diff.nonces.iter().any(|nonce| {
+            nonce.contract_address == account.address() && nonce.nonce == FieldElement::ONE
+        }));
+    };
+
+    // wait for the tx to be executed in the pending block
+    tokio::time::sleep(Duration::from_millis(WAIT_TX_DELAY_MILLIS)).await;
+
+    assert_trace(&client.trace_transaction(tx_hash).await.unwrap());
+    let traces = client.trace_block_transactions(BlockId::Tag(BlockTag::Pending)).await.unwrap();
+    assert_eq!(traces.len(), 1);
+    assert_eq!(traces[0].transaction_hash, tx_hash);
+    assert_trace(&traces[0].trace_root);
+
+    // the traces of the mined transactions are read from the storage
+    let _: () = client.generate_block().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(WAIT_TX_DELAY_MILLIS)).await;
+
+    assert_trace(&client.trace_transaction(tx_hash).await.unwrap());
+    let traces = client.trace_block_transactions(BlockId::Number(1)).await.unwrap();
+    assert_eq!(traces.len(), 1);
+    assert_eq!(traces[0].transaction_hash, tx_hash);
+    assert_trace(&traces[0].trace_root);
+
+    sequencer.stop().expect("failed to stop sequencer");
+}
@@ -73,6 +73,19 @@ macro_rules! impl_encode_and_decode_for_felts {
 impl_encode_and_decode_for_uints!(u64);
 impl_encode_and_decode_for_felts!(FieldElement, ContractAddress);
 
+impl Compress for Vec<u8> {
+    type Compressed = Vec<u8>;
+    fn compress(self) -> Self::Compressed {
+        self
+    }
+}
+
+impl Decompress for Vec<u8> {
+    fn decompress<B: AsRef<[u8]>>(bytes: B) -> Result<Self, CodecError> {
+        Ok(bytes.as_ref().to_vec())
+    }
+}
+
 impl Compress for FlattenedSierraClass {
     type Compressed = Vec<u8>;
     fn compress(self) -> Self::Compressed {
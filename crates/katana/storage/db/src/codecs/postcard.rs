@@ -1,4 +1,4 @@
-use katana_primitives::block::Header;
+use katana_primitives::block::{BlockStats, Header};
 use katana_primitives::contract::{ContractAddress, GenericContractInfo};
 use katana_primitives::receipt::Receipt;
 use katana_primitives::trace::TxExecInfo;
@@ -36,6 +36,7 @@ impl_compress_and_decompress_for_table_values!(
     Tx,
     TxExecInfo,
     Header,
+    BlockStats,
     Receipt,
     FieldElement,
     ContractAddress,
@@ -0,0 +1,128 @@
+//! Offline compaction of the database.
+//!
+//! MDBX reuses the pages freed by the updated and deleted entries, but never gives them back to
+//! the file system, so the database file of a node with a lot of state churn only grows. Compacting
+//! the database copies all its tables into a new database file, without the free pages, which then
+//! replaces the original one.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::mdbx::{DbEnv, DbEnvKind};
+use crate::version::check_db_version;
+
+/// The name of the MDBX data file, in the database directory.
+const DATA_FILE_NAME: &str = "mdbx.dat";
+/// The name of the MDBX lock file, in the database directory.
+const LOCK_FILE_NAME: &str = "mdbx.lck";
+
+/// The outcome of the compaction of a database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// The size (in bytes) of the pages used by the database before the compaction.
+    pub size_before: usize,
+    /// The size (in bytes) of the pages used by the database after the compaction.
+    pub size_after: usize,
+}
+
+impl CompactionReport {
+    /// Returns the size (in bytes) reclaimed by the compaction.
+    pub fn reclaimed(&self) -> usize {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
+
+/// Compacts the database at `path`, which must not be in use by another process.
+///
+/// The tables are first copied into a temporary database next to `path`, so the database is left
+/// untouched if the compaction fails midway.
+pub fn compact_db<P: AsRef<Path>>(path: P) -> anyhow::Result<CompactionReport> {
+    let path = path.as_ref();
+    check_db_version(path)
+        .with_context(|| format!("Checking database version at path {}", path.display()))?;
+
+    let compacted_path = compacted_db_path(path);
+    if compacted_path.exists() {
+        fs::remove_dir_all(&compacted_path).with_context(|| {
+            format!("Removing leftover compacted database at path {}", compacted_path.display())
+        })?;
+    }
+
+    // the environments are closed when dropped, before their files are moved
+    let report = {
+        let env = DbEnv::open_exclusive(path).with_context(|| {
+            format!(
+                "Opening database at path {}, it must not be used by a running node",
+                path.display()
+            )
+        })?;
+
+        let compacted = DbEnv::open(&compacted_path, DbEnvKind::RW).with_context(|| {
+            format!("Creating compacted database at path {}", compacted_path.display())
+        })?;
+        compacted.create_tables()?;
+        env.copy_tables_to(&compacted).context("Copying tables into the compacted database")?;
+
+        CompactionReport { size_before: env.used_size()?, size_after: compacted.used_size()? }
+    };
+
+    fs::rename(compacted_path.join(DATA_FILE_NAME), path.join(DATA_FILE_NAME)).with_context(
+        || format!("Replacing the data file of the database at {}", path.display()),
+    )?;
+    // the lock file describes the previous data file, and is recreated on the next opening
+    let _ = fs::remove_file(path.join(LOCK_FILE_NAME));
+    fs::remove_dir_all(&compacted_path)?;
+
+    Ok(report)
+}
+
+/// Returns the path of the temporary database the database at `path` is compacted into.
+fn compacted_db_path(path: &Path) -> PathBuf {
+    let mut compacted = OsString::from(path.as_os_str());
+    compacted.push(".compact");
+    PathBuf::from(compacted)
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::FieldElement;
+
+    use super::*;
+    use crate::init_db;
+    use crate::tables::BlockHashes;
+
+    #[test]
+    fn compact_db_reclaims_free_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db");
+
+        {
+            let env = init_db(&path).unwrap();
+            env.update(|tx| {
+                for block in 0..10_000u64 {
+                    tx.put::<BlockHashes>(block, FieldElement::from(block)).unwrap();
+                }
+            })
+            .unwrap();
+            env.update(|tx| {
+                for block in 100..10_000u64 {
+                    tx.delete::<BlockHashes>(block, None).unwrap();
+                }
+            })
+            .unwrap();
+        }
+
+        let report = compact_db(&path).unwrap();
+        assert!(report.size_after < report.size_before);
+        assert!(report.reclaimed() > 0);
+        assert!(!compacted_db_path(&path).exists());
+
+        let env = init_db(&path).unwrap();
+        let tx = env.tx().unwrap();
+        assert_eq!(tx.entries::<BlockHashes>().unwrap(), 100);
+        assert_eq!(tx.get::<BlockHashes>(42).unwrap(), Some(FieldElement::from(42u64)));
+    }
+}
@@ -6,9 +6,12 @@ use std::path::Path;
 use anyhow::{anyhow, Context};
 
 pub mod codecs;
+pub mod compaction;
 pub mod error;
 pub mod mdbx;
 pub mod models;
+pub mod prune;
+pub mod salvage;
 pub mod tables;
 pub mod utils;
 pub mod version;
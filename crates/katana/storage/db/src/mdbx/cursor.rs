@@ -5,30 +5,61 @@ use std::marker::PhantomData;
 
 use libmdbx::{self, TransactionKind, WriteFlags, RW};
 
+use super::overflow;
 use crate::codecs::{Compress, Encode};
 use crate::error::DatabaseError;
 use crate::tables::{DupSort, Table};
-use crate::utils::{decode_one, decode_value, decoder, KeyValue};
+use crate::utils::{decode_one, decoder, KeyValue};
 
 /// Takes key/value pair from the database and decodes it appropriately.
 macro_rules! decode {
-    ($v:expr) => {
-        $v.map_err($crate::error::DatabaseError::Read)?.map($crate::utils::decoder::<T>).transpose()
+    ($cursor:ident, $v:expr) => {
+        $v.map_err($crate::error::DatabaseError::Read)?
+            .map(|kv| $cursor.decode_entry(kv))
+            .transpose()
     };
 }
 
+/// Alias for a cursor of a read-only transaction.
+pub type CursorRO<T> = Cursor<libmdbx::RO, T>;
+/// Alias for a cursor of a read-write transaction.
+pub type CursorRW<T> = Cursor<libmdbx::RW, T>;
+
 /// Cursor for navigating the items within a database.
 #[derive(Debug)]
 pub struct Cursor<K: TransactionKind, T: Table> {
     /// Inner `libmdbx` cursor.
     inner: libmdbx::Cursor<K>,
+    /// Cursor over the [`DupSortOverflow`](crate::tables::DupSortOverflow) table, for `DUPSORT`
+    /// tables only.
+    overflow: Option<libmdbx::Cursor<K>>,
     /// Phantom data to enforce encoding/decoding.
     _dbi: PhantomData<T>,
 }
 
 impl<K: TransactionKind, T: Table> Cursor<K, T> {
-    pub(crate) fn new(inner: libmdbx::Cursor<K>) -> Self {
-        Self { inner, _dbi: PhantomData }
+    pub(crate) fn new(inner: libmdbx::Cursor<K>, overflow: Option<libmdbx::Cursor<K>>) -> Self {
+        Self { inner, overflow, _dbi: PhantomData }
+    }
+
+    /// Returns the value stored at `value`, reading it from the overflow table if it is a pointer
+    /// to an overflowed value.
+    fn resolve<'a>(&mut self, value: Cow<'a, [u8]>) -> Result<Cow<'a, [u8]>, DatabaseError> {
+        match &mut self.overflow {
+            Some(overflow) if overflow::is_pointer(&value) => {
+                overflow::read(overflow, &value).map(Cow::Owned)
+            }
+            _ => Ok(value),
+        }
+    }
+
+    /// Decodes a key/value pair, resolving the overflowed value if necessary.
+    fn decode_entry(
+        &mut self,
+        (key, value): (Cow<'_, [u8]>, Cow<'_, [u8]>),
+    ) -> Result<KeyValue<T>, DatabaseError> {
+        let value = self.resolve(value)?;
+        decoder::<T>((key, value))
     }
 }
 
@@ -36,43 +67,43 @@ impl<K: TransactionKind, T: Table> Cursor<K, T> {
     /// Retrieves the first key/value pair, positioning the cursor at the first key/value pair in
     /// the table.
     pub fn first(&mut self) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::first(&mut self.inner))
+        decode!(self, libmdbx::Cursor::first(&mut self.inner))
     }
 
     /// Retrieves key/value pair at current cursor position.
     pub fn current(&mut self) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::get_current(&mut self.inner))
+        decode!(self, libmdbx::Cursor::get_current(&mut self.inner))
     }
 
     /// Retrieves the next key/value pair, positioning the cursor at the next key/value pair in
     /// the table.
     #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::next(&mut self.inner))
+        decode!(self, libmdbx::Cursor::next(&mut self.inner))
     }
 
     /// Retrieves the previous key/value pair, positioning the cursor at the previous key/value pair
     /// in the table.
     pub fn prev(&mut self) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::prev(&mut self.inner))
+        decode!(self, libmdbx::Cursor::prev(&mut self.inner))
     }
 
     /// Retrieves the last key/value pair, positioning the cursor at the last key/value pair in
     /// the table.
     pub fn last(&mut self) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::last(&mut self.inner))
+        decode!(self, libmdbx::Cursor::last(&mut self.inner))
     }
 
     /// Set the cursor to the specified key, returning and positioning the cursor at the item if
     /// found.
     pub fn set(&mut self, key: <T as Table>::Key) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::set_key(&mut self.inner, key.encode().as_ref()))
+        decode!(self, libmdbx::Cursor::set_key(&mut self.inner, key.encode().as_ref()))
     }
 
     /// Search for a `key` in a table, returning and positioning the cursor at the first item whose
     /// key is greater than or equal to `key`.
     pub fn seek(&mut self, key: <T as Table>::Key) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::set_range(&mut self.inner, key.encode().as_ref()))
+        decode!(self, libmdbx::Cursor::set_range(&mut self.inner, key.encode().as_ref()))
     }
 
     /// Creates a walker to iterate over the table items.
@@ -84,7 +115,7 @@ impl<K: TransactionKind, T: Table> Cursor<K, T> {
             self.inner
                 .set_range(start_key.encode().as_ref())
                 .map_err(DatabaseError::Read)?
-                .map(decoder::<T>)
+                .map(|kv| self.decode_entry(kv))
         } else {
             self.first().transpose()
         };
@@ -97,21 +128,21 @@ impl<K: TransactionKind, T: DupSort> Cursor<K, T> {
     /// Positions the cursor at next data item of current key, returning the next `key-value`
     /// pair of a DUPSORT table.
     pub fn next_dup(&mut self) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::next_dup(&mut self.inner))
+        decode!(self, libmdbx::Cursor::next_dup(&mut self.inner))
     }
 
     /// Similar to [`Self::next_dup()`], but instead of returning a `key-value` pair, it returns
     /// only the `value`.
     pub fn next_dup_val(&mut self) -> Result<Option<<T as Table>::Value>, DatabaseError> {
-        libmdbx::Cursor::next_dup(&mut self.inner)
+        libmdbx::Cursor::next_dup::<Cow<'_, [u8]>, Cow<'_, [u8]>>(&mut self.inner)
             .map_err(DatabaseError::Read)?
-            .map(decode_value::<T>)
+            .map(|(_, value)| self.resolve(value).and_then(decode_one::<T>))
             .transpose()
     }
 
     /// Returns the next key/value pair skipping the duplicates.
     pub fn next_no_dup(&mut self) -> Result<Option<KeyValue<T>>, DatabaseError> {
-        decode!(libmdbx::Cursor::next_nodup(&mut self.inner))
+        decode!(self, libmdbx::Cursor::next_nodup(&mut self.inner))
     }
 
     /// Search for a `key` and `subkey` pair in a DUPSORT table. Positioning the cursor at the first
@@ -127,7 +158,7 @@ impl<K: TransactionKind, T: DupSort> Cursor<K, T> {
             subkey.encode().as_ref(),
         )
         .map_err(DatabaseError::Read)?
-        .map(decode_one::<T>)
+        .map(|value| self.resolve(value).and_then(decode_one::<T>))
         .transpose()
     }
 
@@ -149,7 +180,7 @@ impl<K: TransactionKind, T: DupSort> Cursor<K, T> {
                 self.inner
                     .get_both_range(key.as_ref(), subkey.encode().as_ref())
                     .map_err(DatabaseError::Read)?
-                    .map(|val| decoder::<T>((Cow::Owned(key), val)))
+                    .map(|val| self.decode_entry((Cow::Owned(key), val)))
             }
 
             (Some(key), None) => {
@@ -159,7 +190,7 @@ impl<K: TransactionKind, T: DupSort> Cursor<K, T> {
                     .inner
                     .set(key.as_ref())
                     .map_err(DatabaseError::Read)?
-                    .map(|val| decoder::<T>((Cow::Owned(key), val)))
+                    .map(|val| self.decode_entry((Cow::Owned(key), val)))
                 else {
                     return Ok(None);
                 };
@@ -173,7 +204,7 @@ impl<K: TransactionKind, T: DupSort> Cursor<K, T> {
                     self.inner
                         .get_both_range(key.as_ref(), subkey.encode().as_ref())
                         .map_err(DatabaseError::Read)?
-                        .map(|val| decoder::<T>((Cow::Owned(key), val)))
+                        .map(|val| self.decode_entry((Cow::Owned(key), val)))
                 } else {
                     Some(Err(DatabaseError::Read(libmdbx::Error::NotFound)))
                 }
@@ -197,13 +228,7 @@ impl<T: Table> Cursor<RW, T> {
     pub fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
         let key = Encode::encode(key);
         let value = Compress::compress(value);
-
-        libmdbx::Cursor::put(&mut self.inner, key.as_ref(), value.as_ref(), WriteFlags::UPSERT)
-            .map_err(|error| DatabaseError::Write {
-                error,
-                table: T::NAME,
-                key: Box::from(key.as_ref()),
-            })
+        self.put(key.as_ref(), value.as_ref(), WriteFlags::UPSERT)
     }
 
     /// Puts a key/value pair into the database. The cursor will be positioned at the new data item,
@@ -211,18 +236,7 @@ impl<T: Table> Cursor<RW, T> {
     pub fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
         let key = Encode::encode(key);
         let value = Compress::compress(value);
-
-        libmdbx::Cursor::put(
-            &mut self.inner,
-            key.as_ref(),
-            value.as_ref(),
-            WriteFlags::NO_OVERWRITE,
-        )
-        .map_err(|error| DatabaseError::Write {
-            error,
-            table: T::NAME,
-            key: Box::from(key.as_ref()),
-        })
+        self.put(key.as_ref(), value.as_ref(), WriteFlags::NO_OVERWRITE)
     }
 
     /// Appends the data to the end of the table. Consequently, the append operation
@@ -230,19 +244,171 @@ impl<T: Table> Cursor<RW, T> {
     pub fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
         let key = Encode::encode(key);
         let value = Compress::compress(value);
-
-        libmdbx::Cursor::put(&mut self.inner, key.as_ref(), value.as_ref(), WriteFlags::APPEND)
-            .map_err(|error| DatabaseError::Write {
-                error,
-                table: T::NAME,
-                key: Box::from(key.as_ref()),
-            })
+        self.put(key.as_ref(), value.as_ref(), WriteFlags::APPEND)
     }
 
     /// Deletes the current key/value pair.
     pub fn delete_current(&mut self) -> Result<(), DatabaseError> {
+        if let Some(overflow) = &mut self.overflow {
+            let current =
+                libmdbx::Cursor::get_current::<Cow<'_, [u8]>, Cow<'_, [u8]>>(&mut self.inner)
+                    .map_err(DatabaseError::Read)?;
+
+            if let Some((_, value)) = current {
+                overflow::free(overflow, &value)?;
+            }
+        }
+
         libmdbx::Cursor::del(&mut self.inner, WriteFlags::CURRENT).map_err(DatabaseError::Delete)
     }
+
+    /// Puts an encoded key/value pair, spilling the value into the overflow table if it is too
+    /// large for a `DUPSORT` table.
+    ///
+    /// A value already stored under the key isn't spilled again: upserting it leaves the entry as
+    /// is, and the other writes are given the pointer already stored.
+    fn put(&mut self, key: &[u8], value: &[u8], flags: WriteFlags) -> Result<(), DatabaseError> {
+        let existing = if self.overflow.is_some() && overflow::needs_overflow(value) {
+            self.find_overflowed(key, value)?
+        } else {
+            None
+        };
+
+        let (stored, spilled) = match (existing, &mut self.overflow) {
+            (Some(_), _) if flags == WriteFlags::UPSERT => return Ok(()),
+            (Some(pointer), _) => (Cow::Owned(pointer), false),
+            (None, Some(overflow)) => {
+                let stored = overflow::spill(overflow, value)?;
+                let spilled = overflow::is_pointer(&stored);
+                (stored, spilled)
+            }
+            (None, None) => (Cow::Borrowed(value), false),
+        };
+
+        let result = libmdbx::Cursor::put(&mut self.inner, key, stored.as_ref(), flags);
+
+        if let (Err(_), Some(overflow), true) = (&result, &mut self.overflow, spilled) {
+            // don't leave the spilled value behind if it couldn't be stored
+            overflow::free(overflow, &stored)?;
+        }
+
+        result.map_err(|error| DatabaseError::Write { error, table: T::NAME, key: Box::from(key) })
+    }
+
+    /// Deletes the entries of the encoded `key`, or only its entry equal to `value` if it is
+    /// [Some]. Returns `true` if an entry was deleted.
+    pub(super) fn delete_entries(
+        &mut self,
+        key: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<bool, DatabaseError> {
+        let Some(value) = value else {
+            if libmdbx::Cursor::set::<Cow<'_, [u8]>>(&mut self.inner, key)
+                .map_err(DatabaseError::Read)?
+                .is_none()
+            {
+                return Ok(false);
+            }
+
+            self.free_current_duplicates()?;
+            libmdbx::Cursor::del(&mut self.inner, WriteFlags::NO_DUP_DATA)
+                .map_err(DatabaseError::Delete)?;
+            return Ok(true);
+        };
+
+        if self.overflow.is_none() || !overflow::needs_overflow(value) {
+            if libmdbx::Cursor::get_both::<Cow<'_, [u8]>>(&mut self.inner, key, value)
+                .map_err(DatabaseError::Read)?
+                .is_none()
+            {
+                return Ok(false);
+            }
+
+            self.delete_current()?;
+            return Ok(true);
+        }
+
+        if self.find_overflowed(key, value)?.is_none() {
+            return Ok(false);
+        }
+
+        self.delete_current()?;
+        Ok(true)
+    }
+
+    /// Searches the entries of the encoded `key` for the pointer to the overflowed `value`,
+    /// returning it and leaving the cursor at it if found.
+    fn find_overflowed(
+        &mut self,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, DatabaseError> {
+        // the value is stored as a pointer starting with the same prefix, but several pointers
+        // may share it so every one of them has to be compared with the value
+        let prefix = &value[..overflow::POINTER_PREFIX_SIZE];
+        let mut current =
+            libmdbx::Cursor::get_both_range::<Cow<'_, [u8]>>(&mut self.inner, key, prefix)
+                .map_err(DatabaseError::Read)?;
+
+        while let Some(stored) = current.filter(|stored| stored.starts_with(prefix)) {
+            if overflow::is_pointer(&stored) {
+                let pointer = stored.into_owned();
+                if self.resolve(Cow::Borrowed(&pointer))?.as_ref() == value {
+                    return Ok(Some(pointer));
+                }
+            }
+
+            current = libmdbx::Cursor::next_dup::<Cow<'_, [u8]>, Cow<'_, [u8]>>(&mut self.inner)
+                .map_err(DatabaseError::Read)?
+                .map(|(_, stored)| stored);
+        }
+
+        Ok(None)
+    }
+
+    /// Deletes the overflowed values of all the entries of the table.
+    pub(super) fn free_all(&mut self) -> Result<(), DatabaseError> {
+        let Some(overflow) = &mut self.overflow else { return Ok(()) };
+
+        let mut current = libmdbx::Cursor::first::<Cow<'_, [u8]>, Cow<'_, [u8]>>(&mut self.inner)
+            .map_err(DatabaseError::Read)?;
+
+        while let Some((_, value)) = current {
+            overflow::free(overflow, &value)?;
+            current = libmdbx::Cursor::next::<Cow<'_, [u8]>, Cow<'_, [u8]>>(&mut self.inner)
+                .map_err(DatabaseError::Read)?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the overflowed values of all the duplicates of the current key, leaving the cursor
+    /// at the first duplicate.
+    fn free_current_duplicates(&mut self) -> Result<(), DatabaseError> {
+        let Some(overflow) = &mut self.overflow else { return Ok(()) };
+
+        let Some((key, _)) =
+            libmdbx::Cursor::get_current::<Cow<'_, [u8]>, Cow<'_, [u8]>>(&mut self.inner)
+                .map_err(DatabaseError::Read)?
+        else {
+            return Ok(());
+        };
+
+        let key = key.into_owned();
+        let mut current = libmdbx::Cursor::set::<Cow<'_, [u8]>>(&mut self.inner, &key)
+            .map_err(DatabaseError::Read)?;
+
+        while let Some(value) = current {
+            overflow::free(overflow, &value)?;
+            current = libmdbx::Cursor::next_dup::<Cow<'_, [u8]>, Cow<'_, [u8]>>(&mut self.inner)
+                .map_err(DatabaseError::Read)?
+                .map(|(_, value)| value);
+        }
+
+        libmdbx::Cursor::set::<Cow<'_, [u8]>>(&mut self.inner, &key)
+            .map_err(DatabaseError::Read)?;
+        Ok(())
+    }
 }
 
 impl<T: DupSort> Cursor<RW, T> {
@@ -251,6 +417,7 @@ impl<T: DupSort> Cursor<RW, T> {
     /// This will delete all values for the current duplicate key of a `DUPSORT` table, including
     /// the current item.
     pub fn delete_current_duplicates(&mut self) -> Result<(), DatabaseError> {
+        self.free_current_duplicates()?;
         libmdbx::Cursor::del(&mut self.inner, WriteFlags::NO_DUP_DATA)
             .map_err(DatabaseError::Delete)
     }
@@ -258,13 +425,7 @@ impl<T: DupSort> Cursor<RW, T> {
     pub fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
         let key = Encode::encode(key);
         let value = Compress::compress(value);
-
-        libmdbx::Cursor::put(&mut self.inner, key.as_ref(), value.as_ref(), WriteFlags::APPEND_DUP)
-            .map_err(|error| DatabaseError::Write {
-                error,
-                table: T::NAME,
-                key: Box::from(key.as_ref()),
-            })
+        self.put(key.as_ref(), value.as_ref(), WriteFlags::APPEND_DUP)
     }
 }
 
@@ -3,14 +3,23 @@
 //! The code is adapted from `reth` mdbx implementation:  <https://github.com/paradigmxyz/reth/blob/227e1b7ad513977f4f48b18041df02686fca5f94/crates/storage/db/src/implementation/mdbx/mod.rs>
 
 pub mod cursor;
+pub mod overflow;
 pub mod tx;
 
+use std::borrow::Cow;
 use std::path::Path;
+use std::sync::Arc;
 
-use libmdbx::{DatabaseFlags, EnvironmentFlags, Geometry, Mode, PageSize, SyncMode, RO, RW};
+use libmdbx::ffi::DBI;
+use libmdbx::{
+    DatabaseFlags, EnvironmentFlags, Geometry, Mode, PageSize, SyncMode, Transaction, WriteFlags,
+    RO, RW,
+};
+use metrics::gauge;
 
 use self::tx::Tx;
 use crate::error::DatabaseError;
+use crate::salvage::TableSalvage;
 use crate::tables::{TableType, Tables};
 use crate::utils;
 
@@ -30,14 +39,37 @@ pub enum DbEnvKind {
 }
 
 /// Wrapper for `libmdbx-sys` environment.
-#[derive(Debug)]
-pub struct DbEnv(libmdbx::Environment);
+///
+/// The environment is reference counted, so that it can be shared with the background tasks of the
+/// node.
+#[derive(Debug, Clone)]
+pub struct DbEnv(Arc<libmdbx::Environment>);
 
 impl DbEnv {
     /// Opens the database at the specified path with the given `EnvKind`.
     ///
     /// It does not create the tables, for that call [`DbEnv::create_tables`].
     pub fn open(path: impl AsRef<Path>, kind: DbEnvKind) -> Result<DbEnv, DatabaseError> {
+        Self::open_with_exclusivity(path, kind, false)
+    }
+
+    /// Opens the database at the specified path in read-write mode, failing if it is already in
+    /// use by another process.
+    pub fn open_exclusive(path: impl AsRef<Path>) -> Result<DbEnv, DatabaseError> {
+        Self::open_with_exclusivity(path, DbEnvKind::RW, true)
+    }
+
+    /// Opens a possibly damaged database at the specified path, read-only so that its files are
+    /// never written to.
+    pub fn open_for_recovery(path: impl AsRef<Path>) -> Result<DbEnv, DatabaseError> {
+        Self::open_with_exclusivity(path, DbEnvKind::RO, false)
+    }
+
+    fn open_with_exclusivity(
+        path: impl AsRef<Path>,
+        kind: DbEnvKind,
+        exclusive: bool,
+    ) -> Result<DbEnv, DatabaseError> {
         let mode = match kind {
             DbEnvKind::RO => Mode::ReadOnly,
             DbEnvKind::RW => Mode::ReadWrite { sync_mode: SyncMode::Durable },
@@ -57,6 +89,7 @@ impl DbEnv {
             })
             .set_flags(EnvironmentFlags {
                 mode,
+                exclusive,
                 // We disable readahead because it improves performance for linear scans, but
                 // worsens it for random access (which is our access pattern outside of sync)
                 no_rdahead: true,
@@ -65,7 +98,7 @@ impl DbEnv {
             })
             .set_max_readers(DEFAULT_MAX_READERS);
 
-        Ok(DbEnv(builder.open(path.as_ref()).map_err(DatabaseError::OpenEnv)?))
+        Ok(DbEnv(Arc::new(builder.open(path.as_ref()).map_err(DatabaseError::OpenEnv)?)))
     }
 
     /// Creates all the defined tables in [`Tables`], if necessary.
@@ -107,6 +140,253 @@ impl DbEnv {
         tx.commit()?;
         Ok(res)
     }
+
+    /// Takes a fallible function and passes a read-write transaction into it. The transaction is
+    /// only committed if the function succeeds, otherwise it is aborted and none of the writes
+    /// made by the function are persisted.
+    pub fn try_update<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        E: From<DatabaseError>,
+        F: FnOnce(&Tx<RW>) -> Result<T, E>,
+    {
+        let tx = self.tx_mut()?;
+        match f(&tx) {
+            Ok(res) => {
+                tx.commit()?;
+                Ok(res)
+            }
+            Err(error) => {
+                tx.abort();
+                Err(error)
+            }
+        }
+    }
+
+    /// Records the size (in bytes) and the number of entries of every table as metrics gauges.
+    pub fn record_table_metrics(&self) -> Result<(), DatabaseError> {
+        let tx = self.tx()?;
+
+        for table in Tables::ALL {
+            let stat = tx.table_stat(table)?;
+
+            let page_size = stat.page_size() as usize;
+            let num_pages = stat.leaf_pages() + stat.branch_pages() + stat.overflow_pages();
+            let table_size = page_size * num_pages;
+
+            gauge!("db.table_size", table_size as f64, "table" => table.name().to_string());
+            gauge!("db.table_entries", stat.entries() as f64, "table" => table.name().to_string());
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the size (in bytes) of the pages of the database file that are in use, including
+    /// the free pages.
+    pub fn used_size(&self) -> Result<usize, DatabaseError> {
+        let info = self.0.info().map_err(DatabaseError::Stat)?;
+        Ok((info.last_pgno() + 1) * self.page_size()?)
+    }
+
+    /// Returns the size (in bytes) of the free pages of the database file. They are reused for new
+    /// entries, but are only given back to the file system when the database is compacted.
+    pub fn free_size(&self) -> Result<usize, DatabaseError> {
+        let free_pages = self.0.freelist().map_err(DatabaseError::Stat)?;
+        Ok(free_pages * self.page_size()?)
+    }
+
+    fn page_size(&self) -> Result<usize, DatabaseError> {
+        Ok(self.0.stat().map_err(DatabaseError::Stat)?.page_size() as usize)
+    }
+
+    /// Copies the entries of all the tables into `dest`, whose tables must have been created and
+    /// be empty. The tables are rebuilt from scratch, so their copies have no free pages.
+    pub fn copy_tables_to(&self, dest: &DbEnv) -> Result<(), DatabaseError> {
+        let tx = self.0.begin_ro_txn().map_err(DatabaseError::CreateROTx)?;
+
+        // borrowed for `'static`, for the table names to be as well
+        let tables: &'static [Tables] = &Tables::ALL;
+        for table in tables {
+            let dbi = tx.open_db(Some(table.name())).map_err(DatabaseError::OpenDb)?.dbi();
+            let mut cursor = tx.cursor_with_dbi(dbi).map_err(DatabaseError::CreateCursor)?;
+
+            let dest_tx = dest.0.begin_rw_txn().map_err(DatabaseError::CreateRWTx)?;
+            let dest_dbi =
+                dest_tx.open_db(Some(table.name())).map_err(DatabaseError::OpenDb)?.dbi();
+            let mut dest_cursor =
+                dest_tx.cursor_with_dbi(dest_dbi).map_err(DatabaseError::CreateCursor)?;
+
+            // the entries are read in order, so they can be appended. the values of `DUPSORT`
+            // tables are copied as is, pointers to the overflow table included.
+            let flags = match table.table_type() {
+                TableType::Table => WriteFlags::APPEND,
+                TableType::DupSort => WriteFlags::APPEND_DUP,
+            };
+
+            let mut entry =
+                cursor.first::<Cow<'_, [u8]>, Cow<'_, [u8]>>().map_err(DatabaseError::Read)?;
+            while let Some((key, value)) = entry {
+                dest_cursor.put(&key, &value, flags).map_err(|error| DatabaseError::Write {
+                    error,
+                    table: table.name(),
+                    key: Box::from(key.as_ref()),
+                })?;
+                entry = cursor.next().map_err(DatabaseError::Read)?;
+            }
+
+            drop(dest_cursor);
+            dest_tx.commit().map_err(DatabaseError::Commit)?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies the readable entries of `table` into `dest`, skipping the entries that can't be read
+    /// or written. The table is read in its own transactions, so that the other tables can still
+    /// be salvaged if it is damaged. Fails only if the copied entries can't be committed to `dest`.
+    pub fn salvage_table_to(
+        &self,
+        table: Tables,
+        dest: &DbEnv,
+    ) -> Result<TableSalvage, DatabaseError> {
+        let dest_tx = dest.0.begin_rw_txn().map_err(DatabaseError::CreateRWTx)?;
+        let dest_dbi = dest_tx.open_db(Some(table.name())).map_err(DatabaseError::OpenDb)?.dbi();
+
+        let mut salvage = TableSalvage::new(table);
+        self.copy_readable_entries(table, &dest_tx, dest_dbi, &mut salvage);
+
+        dest_tx.commit().map_err(DatabaseError::Commit)?;
+        Ok(salvage)
+    }
+
+    fn copy_readable_entries(
+        &self,
+        table: Tables,
+        dest_tx: &Transaction<RW>,
+        dest_dbi: DBI,
+        salvage: &mut TableSalvage,
+    ) {
+        salvage.expected = self.0.begin_ro_txn().ok().and_then(|tx| {
+            let dbi = tx.open_db(Some(table.name())).ok()?.dbi();
+            tx.db_stat_with_dbi(dbi).ok().map(|stat| stat.entries())
+        });
+
+        let mut from = None;
+        let mut last_key = None;
+        loop {
+            let copy = self.copy_entries_from(
+                table,
+                from.as_deref(),
+                dest_tx,
+                dest_dbi,
+                salvage,
+                &mut last_key,
+            );
+            let Err(error) = copy else { return };
+
+            salvage.skipped += 1;
+            salvage.error = Some(error);
+
+            // the copy resumes at the first readable entry after the last one copied. if even the
+            // first entry is unreadable, there is no key to look for the following ones from.
+            match last_key.as_deref().and_then(|key| self.next_readable_key(table, key)) {
+                Some(key) => from = Some(key),
+                None => return,
+            }
+        }
+    }
+
+    /// Copies the entries of `table` from the first one whose key is at least `from`, or from the
+    /// first one of the table, until an entry can't be read. The entries which can't be written
+    /// to `dest` are skipped.
+    fn copy_entries_from(
+        &self,
+        table: Tables,
+        from: Option<&[u8]>,
+        dest_tx: &Transaction<RW>,
+        dest_dbi: DBI,
+        salvage: &mut TableSalvage,
+        last_key: &mut Option<Vec<u8>>,
+    ) -> Result<(), DatabaseError> {
+        // a fresh transaction is used, as a read error may leave the previous one unusable
+        let tx = self.0.begin_ro_txn().map_err(DatabaseError::CreateROTx)?;
+        let dbi = tx.open_db(Some(table.name())).map_err(DatabaseError::OpenDb)?.dbi();
+
+        // the entries of a damaged table may be out of order, so they aren't appended
+        let mut cursor = tx.cursor_with_dbi(dbi).map_err(DatabaseError::CreateCursor)?;
+        let mut entry = match from {
+            Some(key) => cursor.set_range::<Cow<'_, [u8]>, Cow<'_, [u8]>>(key),
+            None => cursor.first::<Cow<'_, [u8]>, Cow<'_, [u8]>>(),
+        }
+        .map_err(DatabaseError::Read)?;
+
+        while let Some((key, value)) = entry {
+            match dest_tx.put(dest_dbi, &key, &value, WriteFlags::UPSERT) {
+                Ok(()) => salvage.salvaged += 1,
+                Err(error) => {
+                    let key = Box::from(key.as_ref());
+                    salvage.skipped += 1;
+                    salvage.error = Some(DatabaseError::Write { error, table: table.name(), key });
+                }
+            }
+
+            *last_key = Some(key.into_owned());
+            entry = cursor.next().map_err(DatabaseError::Read)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the smallest key after `last` from which `table` can be read again, `None` if
+    /// there is none.
+    ///
+    /// Looking up a key from a damaged page fails, whereas looking up a key from the next intact
+    /// page succeeds. So the keys after `last` are looked up at exponentially growing distances
+    /// until one succeeds, and the smallest one is then narrowed down bit by bit.
+    fn next_readable_key(&self, table: Tables, last: &[u8]) -> Option<Vec<u8>> {
+        let is_readable_from = |key: &[u8]| {
+            let Ok(tx) = self.0.begin_ro_txn() else { return false };
+            let Ok(db) = tx.open_db(Some(table.name())) else { return false };
+            let Ok(mut cursor) = tx.cursor_with_dbi(db.dbi()) else { return false };
+            cursor.set_range::<Cow<'_, [u8]>, Cow<'_, [u8]>>(key).is_ok()
+        };
+
+        let bits = last.len() * 8;
+        let exponent = (0..bits).find(|&exponent| {
+            add_power_of_two(last, exponent).is_some_and(|key| is_readable_from(&key))
+        })?;
+
+        if exponent == 0 {
+            return add_power_of_two(last, 0);
+        }
+
+        // the largest unreadable key below `last + 2^exponent`
+        let mut unreadable = add_power_of_two(last, exponent - 1)?;
+        for bit in (0..exponent - 1).rev() {
+            let key = add_power_of_two(&unreadable, bit)?;
+            if !is_readable_from(&key) {
+                unreadable = key;
+            }
+        }
+
+        add_power_of_two(&unreadable, 0)
+    }
+}
+
+/// Returns `key + 2^exponent`, the key being read as a big-endian integer, or `None` if it
+/// overflows the length of the key.
+fn add_power_of_two(key: &[u8], exponent: usize) -> Option<Vec<u8>> {
+    let mut key = key.to_vec();
+    let mut carry = 1u16 << (exponent % 8);
+    for byte in key.iter_mut().rev().skip(exponent / 8) {
+        let sum = u16::from(*byte) + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+        if carry == 0 {
+            return Some(key);
+        }
+    }
+    None
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -143,10 +423,14 @@ mod tests {
 
     use super::*;
     use crate::codecs::Encode;
+    use crate::error::CodecError;
     use crate::mdbx::cursor::Walker;
     use crate::mdbx::test_utils::create_test_db;
     use crate::models::storage::StorageEntry;
-    use crate::tables::{BlockHashes, ContractInfo, ContractStorage, Headers, Table};
+    use crate::tables::{
+        BlockHashes, ClassDeclarations, ContractInfo, ContractStorage, DupSortOverflow, Headers,
+        Table,
+    };
 
     const ERROR_PUT: &str = "Not able to insert value into table.";
     const ERROR_DELETE: &str = "Failed to delete value from table.";
@@ -163,6 +447,25 @@ mod tests {
         create_test_db(DbEnvKind::RW);
     }
 
+    #[test]
+    fn db_try_update_aborts_on_error() {
+        let env = create_test_db(DbEnvKind::RW);
+
+        let res = env.try_update(|tx| -> Result<(), DatabaseError> {
+            tx.put::<Headers>(1u64, Header::default()).expect(ERROR_PUT);
+            Err(CodecError::Decode("invalid data".to_string()).into())
+        });
+        assert!(res.is_err());
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<Headers>(1u64).expect(ERROR_GET), None, "{}", ERROR_RETURN_VALUE);
+
+        env.try_update(|tx| tx.put::<Headers>(1u64, Header::default())).unwrap();
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.get::<Headers>(1u64).expect(ERROR_GET), Some(Header::default()));
+    }
+
     #[test]
     fn db_manual_put_get() {
         let env = create_test_db(DbEnvKind::RW);
@@ -402,4 +705,73 @@ mod tests {
             );
         }
     }
+
+    /// A `DUPSORT` table with values large enough to overflow, stored in the database of
+    /// [`ClassDeclarations`].
+    #[derive(Debug)]
+    struct LargeDupValues;
+
+    impl Table for LargeDupValues {
+        const NAME: &'static str = ClassDeclarations::NAME;
+        type Key = u64;
+        type Value = Vec<u8>;
+        const IS_DUPSORT: bool = true;
+    }
+
+    #[test]
+    fn db_dup_sort_overflow_round_trip() {
+        let env = create_test_db(DbEnvKind::RW);
+
+        let small = vec![1u8; 8];
+        let large1 = vec![2u8; overflow::MAX_DUPSORT_VALUE_SIZE * 2];
+        let large2 = vec![3u8; overflow::MAX_DUPSORT_VALUE_SIZE * 2];
+
+        env.update(|tx| {
+            tx.put::<LargeDupValues>(1, small.clone()).expect(ERROR_PUT);
+            tx.put::<LargeDupValues>(1, large1.clone()).expect(ERROR_PUT);
+            tx.put::<LargeDupValues>(1, large2.clone()).expect(ERROR_PUT);
+            tx.put::<LargeDupValues>(2, large1.clone()).expect(ERROR_PUT);
+            // putting a value again reuses its overflowed value
+            tx.put::<LargeDupValues>(1, large1.clone()).expect(ERROR_PUT);
+        })
+        .unwrap();
+
+        {
+            let tx = env.tx().expect(ERROR_INIT_TX);
+            assert_eq!(tx.entries::<LargeDupValues>().unwrap(), 4);
+            assert_eq!(tx.entries::<DupSortOverflow>().unwrap(), 3);
+
+            let mut cursor = tx.cursor::<LargeDupValues>().expect(ERROR_INIT_CURSOR);
+            let values = cursor.walk(None).unwrap().map(|res| res.unwrap()).collect::<Vec<_>>();
+            assert_eq!(
+                values,
+                vec![
+                    (1, small.clone()),
+                    (1, large1.clone()),
+                    (1, large2.clone()),
+                    (2, large1.clone())
+                ]
+            );
+            assert_eq!(tx.get::<LargeDupValues>(2).expect(ERROR_GET), Some(large1.clone()));
+        }
+
+        env.update(|tx| {
+            assert!(tx.delete::<LargeDupValues>(1, Some(large2.clone())).expect(ERROR_DELETE));
+            assert!(!tx.delete::<LargeDupValues>(1, Some(large2)).expect(ERROR_DELETE));
+            assert!(tx.delete::<LargeDupValues>(1, None).expect(ERROR_DELETE));
+        })
+        .unwrap();
+
+        {
+            let tx = env.tx().expect(ERROR_INIT_TX);
+            assert_eq!(tx.entries::<LargeDupValues>().unwrap(), 1);
+            assert_eq!(tx.entries::<DupSortOverflow>().unwrap(), 1);
+        }
+
+        env.update(|tx| tx.clear::<LargeDupValues>().expect("Failed to clear table.")).unwrap();
+
+        let tx = env.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.entries::<LargeDupValues>().unwrap(), 0);
+        assert_eq!(tx.entries::<DupSortOverflow>().unwrap(), 0);
+    }
 }
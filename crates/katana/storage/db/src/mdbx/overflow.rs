@@ -0,0 +1,134 @@
+//! Overflow of the values of `DUPSORT` tables.
+//!
+//! The values of a `DUPSORT` table are stored as the keys of a nested tree, so MDBX limits their
+//! size to the maximum key size (about 2KB with the minimum page size of 4KB). Values that are too
+//! large are spilled into the [`DupSortOverflow`] table, keyed by a generated id. In their place,
+//! the `DUPSORT` table stores a pointer made of the first bytes of the value followed by the id.
+//! The prefix contains the subkey of the value, so that the entries are still sorted and can still
+//! be searched by subkey.
+//!
+//! The values stored inline are always shorter than [`MAX_DUPSORT_VALUE_SIZE`], while pointers are
+//! exactly [`MAX_DUPSORT_VALUE_SIZE`] bytes long, so they can be told apart without a marker.
+
+use std::borrow::Cow;
+
+use libmdbx::{TransactionKind, WriteFlags, RW};
+
+use crate::codecs::Decode;
+use crate::error::DatabaseError;
+use crate::tables::{DupSortOverflow, Table};
+
+/// The maximum size (in bytes) of the values stored inline in a `DUPSORT` table. Larger values are
+/// spilled into the [`DupSortOverflow`] table.
+pub const MAX_DUPSORT_VALUE_SIZE: usize = 1024;
+
+/// The size of the id of an overflowed value.
+const OVERFLOW_ID_SIZE: usize = std::mem::size_of::<u64>();
+
+/// The number of bytes of the overflowed value kept at the start of its pointer.
+pub(crate) const POINTER_PREFIX_SIZE: usize = MAX_DUPSORT_VALUE_SIZE - OVERFLOW_ID_SIZE;
+
+/// Returns `true` if `value` is too large to be stored inline in a `DUPSORT` table.
+pub(crate) fn needs_overflow(value: &[u8]) -> bool {
+    value.len() >= MAX_DUPSORT_VALUE_SIZE
+}
+
+/// Returns `true` if the stored `value` is a pointer to an overflowed value.
+pub(crate) fn is_pointer(value: &[u8]) -> bool {
+    value.len() == MAX_DUPSORT_VALUE_SIZE
+}
+
+/// Returns the key of the overflowed value `pointer` points to.
+fn pointer_id(pointer: &[u8]) -> &[u8] {
+    &pointer[POINTER_PREFIX_SIZE..]
+}
+
+/// Reads the overflowed value `pointer` points to.
+pub(crate) fn read<K: TransactionKind>(
+    overflow: &mut libmdbx::Cursor<K>,
+    pointer: &[u8],
+) -> Result<Vec<u8>, DatabaseError> {
+    overflow
+        .set::<Cow<'_, [u8]>>(pointer_id(pointer))
+        .map_err(DatabaseError::Read)?
+        .map(Cow::into_owned)
+        .ok_or(DatabaseError::Read(libmdbx::Error::NotFound))
+}
+
+/// Spills `value` into the overflow table if it is too large to be stored inline, returning the
+/// pointer to store in its place. Otherwise `value` is returned as is.
+pub(crate) fn spill<'a>(
+    overflow: &mut libmdbx::Cursor<RW>,
+    value: &'a [u8],
+) -> Result<Cow<'a, [u8]>, DatabaseError> {
+    if !needs_overflow(value) {
+        return Ok(Cow::Borrowed(value));
+    }
+
+    let id = match overflow.last::<Cow<'_, [u8]>, Cow<'_, [u8]>>().map_err(DatabaseError::Read)? {
+        Some((last, _)) => u64::decode(last)? + 1,
+        None => 0,
+    };
+
+    let id = id.to_be_bytes();
+    overflow.put(&id, value, WriteFlags::APPEND).map_err(|error| DatabaseError::Write {
+        error,
+        table: DupSortOverflow::NAME,
+        key: Box::from(id.as_ref()),
+    })?;
+
+    let mut pointer = Vec::with_capacity(MAX_DUPSORT_VALUE_SIZE);
+    pointer.extend_from_slice(&value[..POINTER_PREFIX_SIZE]);
+    pointer.extend_from_slice(&id);
+    Ok(Cow::Owned(pointer))
+}
+
+/// Deletes the overflowed value of the stored `value`, if it is a pointer.
+pub(crate) fn free(overflow: &mut libmdbx::Cursor<RW>, value: &[u8]) -> Result<(), DatabaseError> {
+    if !is_pointer(value) {
+        return Ok(());
+    }
+
+    if overflow.set::<Cow<'_, [u8]>>(pointer_id(value)).map_err(DatabaseError::Read)?.is_some() {
+        overflow.del(WriteFlags::CURRENT).map_err(DatabaseError::Delete)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mdbx::test_utils::create_test_db;
+    use crate::mdbx::DbEnvKind;
+
+    #[test]
+    fn spill_read_and_free_overflowed_values() {
+        let env = create_test_db(DbEnvKind::RW);
+        let tx = env.0.begin_rw_txn().unwrap();
+        let dbi = tx.open_db(Some(DupSortOverflow::NAME)).unwrap().dbi();
+        let mut overflow = tx.cursor_with_dbi(dbi).unwrap();
+
+        // small values are stored inline
+        let small = vec![1u8; MAX_DUPSORT_VALUE_SIZE - 1];
+        assert_eq!(spill(&mut overflow, &small).unwrap(), Cow::Borrowed(small.as_slice()));
+        assert!(!is_pointer(&small));
+
+        let large = (0..MAX_DUPSORT_VALUE_SIZE * 4).map(|i| i as u8).collect::<Vec<_>>();
+        let pointer = spill(&mut overflow, &large).unwrap().into_owned();
+        let other_pointer = spill(&mut overflow, &large).unwrap().into_owned();
+
+        assert!(is_pointer(&pointer));
+        assert!(pointer.starts_with(&large[..POINTER_PREFIX_SIZE]));
+        assert_ne!(pointer, other_pointer, "each overflowed value should get its own id");
+        assert_eq!(read(&mut overflow, &pointer).unwrap(), large);
+        assert_eq!(read(&mut overflow, &other_pointer).unwrap(), large);
+
+        free(&mut overflow, &pointer).unwrap();
+        assert_eq!(
+            read(&mut overflow, &pointer),
+            Err(DatabaseError::Read(libmdbx::Error::NotFound))
+        );
+        assert_eq!(read(&mut overflow, &other_pointer).unwrap(), large);
+    }
+}
@@ -3,13 +3,13 @@
 use std::str::FromStr;
 
 use libmdbx::ffi::DBI;
-use libmdbx::{TransactionKind, WriteFlags, RW};
+use libmdbx::{Stat, TransactionKind, WriteFlags, RW};
 use parking_lot::RwLock;
 
 use super::cursor::Cursor;
 use crate::codecs::{Compress, Encode};
 use crate::error::DatabaseError;
-use crate::tables::{Table, Tables, NUM_TABLES};
+use crate::tables::{DupSortOverflow, Table, Tables, NUM_TABLES};
 use crate::utils::decode_one;
 
 /// Alias for read-only transaction.
@@ -36,10 +36,20 @@ impl<K: TransactionKind> Tx<K> {
 
     /// Creates a cursor to iterate over a table items.
     pub fn cursor<T: Table>(&self) -> Result<Cursor<K, T>, DatabaseError> {
-        self.inner
+        let inner = self
+            .inner
             .cursor_with_dbi(self.get_dbi::<T>()?)
-            .map(Cursor::new)
-            .map_err(DatabaseError::CreateCursor)
+            .map_err(DatabaseError::CreateCursor)?;
+
+        // the values of `DUPSORT` tables may be spilled into the overflow table
+        let overflow = if T::IS_DUPSORT {
+            let dbi = self.get_dbi::<DupSortOverflow>()?;
+            Some(self.inner.cursor_with_dbi(dbi).map_err(DatabaseError::CreateCursor)?)
+        } else {
+            None
+        };
+
+        Ok(Cursor::new(inner, overflow))
     }
 
     /// Gets a table database handle if it exists, otherwise creates it.
@@ -58,6 +68,10 @@ impl<K: TransactionKind> Tx<K> {
 
     /// Gets a value from a table using the given key.
     pub fn get<T: Table>(&self, key: T::Key) -> Result<Option<<T as Table>::Value>, DatabaseError> {
+        if T::IS_DUPSORT {
+            return Ok(self.cursor::<T>()?.set(key)?.map(|(_, value)| value));
+        }
+
         let key = Encode::encode(key);
         self.inner
             .get(self.get_dbi::<T>()?, key.as_ref())
@@ -74,6 +88,12 @@ impl<K: TransactionKind> Tx<K> {
             .map_err(DatabaseError::Stat)
     }
 
+    /// Returns the database statistics of the given table.
+    pub fn table_stat(&self, table: Tables) -> Result<Stat, DatabaseError> {
+        let dbi = self.inner.open_db(Some(table.name())).map_err(DatabaseError::OpenDb)?.dbi();
+        self.inner.db_stat_with_dbi(dbi).map_err(DatabaseError::Stat)
+    }
+
     /// Commits the transaction.
     pub fn commit(self) -> Result<bool, DatabaseError> {
         self.inner.commit().map_err(DatabaseError::Commit)
@@ -87,6 +107,10 @@ impl Tx<RW> {
     /// new key/data pair, replacing any previously existing key if duplicates are disallowed, or
     /// adding a duplicate data item if duplicates are allowed (DatabaseFlags::DUP_SORT).
     pub fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        if T::IS_DUPSORT {
+            return self.cursor::<T>()?.upsert(key, value);
+        }
+
         let key = key.encode();
         let value = value.compress();
         self.inner.put(self.get_dbi::<T>()?, key, value, WriteFlags::UPSERT).unwrap();
@@ -106,11 +130,20 @@ impl Tx<RW> {
     ) -> Result<bool, DatabaseError> {
         let value = value.map(Compress::compress);
         let value = value.as_ref().map(|v| v.as_ref());
+
+        if T::IS_DUPSORT {
+            return self.cursor::<T>()?.delete_entries(key.encode().as_ref(), value);
+        }
+
         self.inner.del(self.get_dbi::<T>()?, key.encode(), value).map_err(DatabaseError::Delete)
     }
 
     /// Clears all entries in the given database. This will emtpy the database.
     pub fn clear<T: Table>(&self) -> Result<(), DatabaseError> {
+        if T::IS_DUPSORT {
+            self.cursor::<T>()?.free_all()?;
+        }
+
         self.inner.clear_db(self.get_dbi::<T>()?).map_err(DatabaseError::Clear)
     }
 
@@ -34,7 +34,7 @@ impl Decompress for StorageEntry {
     }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct ContractStorageKey {
     pub contract_address: ContractAddress,
     pub key: StorageKey,
@@ -0,0 +1,207 @@
+//! Pruning of the state history of the database.
+//!
+//! The changes of the nonces, classes and storage of the contracts are kept for every block, so
+//! that the state of any past block can be served. Pruning the history at a block removes the
+//! changes which aren't needed to serve the state of that block and of the later ones, ie. every
+//! change of a value older than its latest change at that block. The state of the earlier blocks
+//! can't be served anymore, so the block is recorded in [PruneCheckpoints] for the provider to
+//! refuse them.
+//!
+//! The blocks, the transactions and their receipts are never pruned.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use katana_primitives::block::BlockNumber;
+use katana_primitives::contract::ContractAddress;
+
+use crate::error::DatabaseError;
+use crate::mdbx::tx::TxRW;
+use crate::mdbx::DbEnv;
+use crate::models::contract::ContractInfoChangeList;
+use crate::models::list::BlockList;
+use crate::tables::{
+    ClassChangeHistory, ContractInfoChangeSet, NonceChangeHistory, PruneCheckpoints,
+    StorageChangeHistory, StorageChangeSet, Table,
+};
+
+/// The key of the block the state history is pruned at, in [PruneCheckpoints].
+pub const STATE_HISTORY_CHECKPOINT: u64 = 0;
+
+/// The number of changes removed by a pruning of the state history.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub nonce_changes: usize,
+    pub class_changes: usize,
+    pub storage_changes: usize,
+}
+
+impl PruneReport {
+    /// Returns the number of changes removed.
+    pub fn total(&self) -> usize {
+        self.nonce_changes + self.class_changes + self.storage_changes
+    }
+}
+
+/// Prunes the state history at `block`, the state of the blocks before it not being served
+/// anymore. The history is left untouched if it is already pruned at `block` or after it.
+pub fn prune_state_history(env: &DbEnv, block: BlockNumber) -> Result<PruneReport, DatabaseError> {
+    env.try_update(|tx| {
+        let pruned_at = tx.get::<PruneCheckpoints>(STATE_HISTORY_CHECKPOINT)?;
+        if pruned_at.is_some_and(|pruned_at| pruned_at >= block) {
+            return Ok(PruneReport::default());
+        }
+
+        let mut report = PruneReport::default();
+        let mut contract_lists = HashMap::new();
+
+        for (number, change) in changes_before::<NonceChangeHistory>(tx, block)? {
+            let lists = contract_change_lists(tx, &mut contract_lists, change.contract_address)?;
+            if is_outdated(&mut lists.nonce_change_list, number, block) {
+                tx.delete::<NonceChangeHistory>(number, Some(change))?;
+                report.nonce_changes += 1;
+            }
+        }
+
+        for (number, change) in changes_before::<ClassChangeHistory>(tx, block)? {
+            let lists = contract_change_lists(tx, &mut contract_lists, change.contract_address)?;
+            if is_outdated(&mut lists.class_change_list, number, block) {
+                tx.delete::<ClassChangeHistory>(number, Some(change))?;
+                report.class_changes += 1;
+            }
+        }
+
+        let mut storage_lists = HashMap::new();
+        for (number, entry) in changes_before::<StorageChangeHistory>(tx, block)? {
+            let list = match storage_lists.entry(entry.key.clone()) {
+                Entry::Occupied(list) => list.into_mut(),
+                Entry::Vacant(list) => {
+                    let stored = tx.get::<StorageChangeSet>(entry.key.clone())?;
+                    list.insert(stored.unwrap_or_default())
+                }
+            };
+
+            if is_outdated(list, number, block) {
+                tx.delete::<StorageChangeHistory>(number, Some(entry))?;
+                report.storage_changes += 1;
+            }
+        }
+
+        for (address, lists) in contract_lists {
+            tx.put::<ContractInfoChangeSet>(address, lists)?;
+        }
+        for (key, list) in storage_lists {
+            tx.put::<StorageChangeSet>(key, list)?;
+        }
+
+        tx.put::<PruneCheckpoints>(STATE_HISTORY_CHECKPOINT, block)?;
+        Ok(report)
+    })
+}
+
+/// Returns the changes of the history table `T` made before `block`.
+fn changes_before<T>(
+    tx: &TxRW,
+    block: BlockNumber,
+) -> Result<Vec<(BlockNumber, T::Value)>, DatabaseError>
+where
+    T: Table<Key = BlockNumber>,
+{
+    let mut changes = Vec::new();
+    let mut cursor = tx.cursor::<T>()?;
+    for entry in cursor.walk(None)? {
+        let (number, change) = entry?;
+        if number >= block {
+            break;
+        }
+        changes.push((number, change));
+    }
+    Ok(changes)
+}
+
+/// Returns the change lists of the contract at `address`, read from the database the first time.
+fn contract_change_lists<'a>(
+    tx: &TxRW,
+    lists: &'a mut HashMap<ContractAddress, ContractInfoChangeList>,
+    address: ContractAddress,
+) -> Result<&'a mut ContractInfoChangeList, DatabaseError> {
+    Ok(match lists.entry(address) {
+        Entry::Occupied(lists) => lists.into_mut(),
+        Entry::Vacant(lists) => {
+            lists.insert(tx.get::<ContractInfoChangeSet>(address)?.unwrap_or_default())
+        }
+    })
+}
+
+/// Returns `true`, and removes `number` from the change list, if the change made at `number` is
+/// older than the latest change of the list at `block`.
+fn is_outdated(list: &mut BlockList, number: BlockNumber, block: BlockNumber) -> bool {
+    let rank = list.rank(block);
+    let latest = if rank == 0 { None } else { list.select(rank - 1) };
+    if latest == Some(number) {
+        return false;
+    }
+
+    list.remove(number);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::FieldElement;
+
+    use super::*;
+    use crate::init_db;
+    use crate::models::contract::ContractNonceChange;
+    use crate::models::storage::{ContractStorageEntry, ContractStorageKey};
+
+    #[test]
+    fn prune_state_history_at_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = init_db(dir.path()).unwrap();
+
+        let address = ContractAddress::from(FieldElement::ONE);
+        let key = ContractStorageKey { contract_address: address, key: FieldElement::TWO };
+
+        // the nonce and the storage value change at the blocks 1, 3 and 5
+        env.update(|tx| {
+            let mut nonces = BlockList::new();
+            let mut values = BlockList::new();
+            for number in [1u64, 3, 5] {
+                let value = FieldElement::from(number);
+                let nonce = ContractNonceChange { contract_address: address, nonce: value };
+                tx.put::<NonceChangeHistory>(number, nonce).unwrap();
+                let entry = ContractStorageEntry { key: key.clone(), value };
+                tx.put::<StorageChangeHistory>(number, entry).unwrap();
+                nonces.insert(number);
+                values.insert(number);
+            }
+
+            let lists = ContractInfoChangeList { nonce_change_list: nonces, ..Default::default() };
+            tx.put::<ContractInfoChangeSet>(address, lists).unwrap();
+            tx.put::<StorageChangeSet>(key.clone(), values).unwrap();
+        })
+        .unwrap();
+
+        // the change of the block 3 is the latest one at the block 4, only the first one is pruned
+        let report = prune_state_history(&env, 4).unwrap();
+        assert_eq!(report, PruneReport { nonce_changes: 1, class_changes: 0, storage_changes: 1 });
+
+        let tx = env.tx().unwrap();
+        assert_eq!(tx.get::<PruneCheckpoints>(STATE_HISTORY_CHECKPOINT).unwrap(), Some(4));
+        assert_eq!(tx.entries::<NonceChangeHistory>().unwrap(), 2);
+        assert_eq!(tx.entries::<StorageChangeHistory>().unwrap(), 2);
+
+        let lists = tx.get::<ContractInfoChangeSet>(address).unwrap().unwrap();
+        assert!(!lists.nonce_change_list.contains(1));
+        assert!(lists.nonce_change_list.contains(3) && lists.nonce_change_list.contains(5));
+        let values = tx.get::<StorageChangeSet>(key).unwrap().unwrap();
+        assert!(!values.contains(1) && values.contains(3));
+        drop(tx);
+
+        // the history is never pruned back
+        assert_eq!(prune_state_history(&env, 2).unwrap().total(), 0);
+        let tx = env.tx().unwrap();
+        assert_eq!(tx.get::<PruneCheckpoints>(STATE_HISTORY_CHECKPOINT).unwrap(), Some(4));
+    }
+}
@@ -0,0 +1,202 @@
+//! Salvage of the readable entries of a damaged database.
+//!
+//! A last resort after a disk incident: the damaged database is opened read-only, so that its files
+//! are never written to, and its tables are copied one by one into a fresh database. Each table is
+//! read in its own transactions, so an unreadable table doesn't prevent the others from being
+//! copied. A damaged page of a table is skipped, its entries being lost, and the copy resumes at
+//! the first readable entry after it, which is looked up past the last entry copied. A table whose
+//! first entry can't be read is not copied at all.
+//!
+//! The entries are copied as they are stored, without being decoded, so a corrupted value that
+//! can still be read is copied as well. The values of the `DUPSORT` tables spilled into the
+//! overflow table are only readable if their entries of the overflow table were salvaged too.
+
+use std::path::Path;
+
+use anyhow::{bail, Context};
+
+use crate::error::DatabaseError;
+use crate::init_db;
+use crate::mdbx::DbEnv;
+use crate::tables::Tables;
+use crate::utils::is_database_empty;
+use crate::version::{check_db_version, DatabaseVersionError};
+
+/// The outcome of the salvage of a table.
+#[derive(Debug)]
+pub struct TableSalvage {
+    pub table: Tables,
+    /// The number of entries the damaged table reports having, if it could be read.
+    pub expected: Option<usize>,
+    /// The number of entries copied to the salvaged database.
+    pub salvaged: usize,
+    /// The number of times the copy skipped an entry it couldn't write or a part of the table it
+    /// couldn't read.
+    pub skipped: usize,
+    /// The last error met while copying the table, if any.
+    pub error: Option<DatabaseError>,
+}
+
+impl TableSalvage {
+    pub(crate) fn new(table: Tables) -> Self {
+        Self { table, expected: None, salvaged: 0, skipped: 0, error: None }
+    }
+
+    /// Returns the number of entries lost, if known.
+    pub fn lost(&self) -> Option<usize> {
+        self.expected.map(|expected| expected.saturating_sub(self.salvaged))
+    }
+
+    /// Returns `true` if the whole table was copied.
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none() && self.lost() == Some(0)
+    }
+}
+
+/// The outcome of the salvage of a database.
+#[derive(Debug)]
+pub struct SalvageReport {
+    pub tables: Vec<TableSalvage>,
+}
+
+impl SalvageReport {
+    /// Returns `true` if all the tables were copied.
+    pub fn is_complete(&self) -> bool {
+        self.tables.iter().all(TableSalvage::is_complete)
+    }
+}
+
+/// Copies the readable entries of the possibly damaged database at `path` into a new database at
+/// `dest`, which must not exist or be empty. The damaged database must not be used by a running
+/// node.
+pub fn salvage_db<P: AsRef<Path>, Q: AsRef<Path>>(
+    path: P,
+    dest: Q,
+) -> anyhow::Result<SalvageReport> {
+    let (path, dest) = (path.as_ref(), dest.as_ref());
+
+    if !is_database_empty(dest) {
+        bail!("Salvaged database path {} is not empty", dest.display());
+    }
+
+    // a damaged version file is ignored, but the tables of another version can't be copied as is
+    if let Err(err @ DatabaseVersionError::MismatchVersion { .. }) = check_db_version(path) {
+        return Err(err).with_context(|| format!("Salvaging database at path {}", path.display()));
+    }
+
+    let env = DbEnv::open_for_recovery(path)
+        .with_context(|| format!("Opening damaged database at path {}", path.display()))?;
+    let salvaged = init_db(dest)
+        .with_context(|| format!("Creating salvaged database at path {}", dest.display()))?;
+
+    let mut tables = Vec::with_capacity(Tables::ALL.len());
+    for table in Tables::ALL {
+        let salvage = env
+            .salvage_table_to(table, &salvaged)
+            .with_context(|| format!("Writing table {} to the salvaged database", table.name()))?;
+        tables.push(salvage);
+    }
+
+    Ok(SalvageReport { tables })
+}
+
+#[cfg(test)]
+mod tests {
+    use katana_primitives::FieldElement;
+
+    use super::*;
+    use crate::tables::{BlockHashes, BlockNumbers};
+    use crate::utils::default_page_size;
+
+    #[test]
+    fn salvage_intact_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db");
+        let dest = dir.path().join("salvaged");
+
+        {
+            let env = init_db(&path).unwrap();
+            env.update(|tx| {
+                for block in 0..100u64 {
+                    tx.put::<BlockHashes>(block, FieldElement::from(block)).unwrap();
+                    tx.put::<BlockNumbers>(FieldElement::from(block), block).unwrap();
+                }
+            })
+            .unwrap();
+        }
+
+        let report = salvage_db(&path, &dest).unwrap();
+        assert!(report.is_complete());
+
+        let hashes = report.tables.iter().find(|t| t.table == Tables::BlockHashes).unwrap();
+        assert_eq!((hashes.expected, hashes.salvaged), (Some(100), 100));
+
+        let env = init_db(&dest).unwrap();
+        let tx = env.tx().unwrap();
+        assert_eq!(tx.entries::<BlockNumbers>().unwrap(), 100);
+        assert_eq!(tx.get::<BlockHashes>(42).unwrap(), Some(FieldElement::from(42u64)));
+
+        // the salvaged database is never overwritten
+        assert!(salvage_db(&path, &dest).is_err());
+    }
+
+    #[test]
+    fn salvage_corrupted_database() {
+        // the keys are made distinctive, for their pages to be found in the file
+        const KEY: u64 = 0xc0ffee << 40;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db");
+        let dest = dir.path().join("salvaged");
+
+        {
+            let env = init_db(&path).unwrap();
+            env.update(|tx| {
+                for block in 0..5000u64 {
+                    tx.put::<BlockHashes>(KEY + block, FieldElement::from(block)).unwrap();
+                }
+                tx.put::<BlockNumbers>(FieldElement::ONE, 1).unwrap();
+            })
+            .unwrap();
+        }
+
+        // zeroes the page of a key in the middle of the table. a key stored only once in the file
+        // is on a leaf page, the keys of the branch pages being also stored on the leaf pages.
+        let file = path.join("mdbx.dat");
+        let mut bytes = std::fs::read(&file).unwrap();
+        let (damaged, offset) = (2500..5000u64)
+            .find_map(|block| {
+                let key = (KEY + block).to_be_bytes();
+                let mut offsets = bytes.windows(key.len()).enumerate().filter(|(_, w)| *w == key);
+                match (offsets.next(), offsets.next()) {
+                    (Some((offset, _)), None) => Some((block, offset)),
+                    _ => None,
+                }
+            })
+            .unwrap();
+        let page_size = default_page_size();
+        let page = offset / page_size * page_size;
+        bytes[page..page + page_size].fill(0);
+        std::fs::write(&file, bytes).unwrap();
+
+        let report = salvage_db(&path, &dest).unwrap();
+        assert!(!report.is_complete());
+
+        let hashes = report.tables.iter().find(|t| t.table == Tables::BlockHashes).unwrap();
+        assert_eq!(hashes.expected, Some(5000));
+        assert_eq!(hashes.skipped, 1);
+        assert!(hashes.error.is_some());
+        // only the entries of the damaged page are lost
+        assert!(hashes.salvaged < 5000 && hashes.salvaged > 4500, "{}", hashes.salvaged);
+        let mut others = report.tables.iter().filter(|t| t.table != Tables::BlockHashes);
+        assert!(others.all(TableSalvage::is_complete));
+
+        let env = init_db(&dest).unwrap();
+        let tx = env.tx().unwrap();
+        assert_eq!(tx.entries::<BlockHashes>().unwrap(), hashes.salvaged);
+        assert_eq!(tx.get::<BlockHashes>(KEY).unwrap(), Some(FieldElement::ZERO));
+        assert_eq!(tx.get::<BlockHashes>(KEY + damaged).unwrap(), None);
+        assert_eq!(tx.get::<BlockHashes>(KEY + 4999).unwrap(), Some(FieldElement::from(4999u64)));
+        assert_eq!(tx.get::<BlockNumbers>(FieldElement::ONE).unwrap(), Some(1));
+    }
+}
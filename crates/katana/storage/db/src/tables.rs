@@ -1,4 +1,4 @@
-use katana_primitives::block::{BlockHash, BlockNumber, FinalityStatus, Header};
+use katana_primitives::block::{BlockHash, BlockNumber, BlockStats, FinalityStatus, Header};
 use katana_primitives::class::{ClassHash, CompiledClass, CompiledClassHash, FlattenedSierraClass};
 use katana_primitives::contract::{ContractAddress, GenericContractInfo, StorageKey};
 use katana_primitives::receipt::Receipt;
@@ -25,6 +25,8 @@ pub trait Table {
     type Key: Key;
     /// The value type of the table.
     type Value: Value;
+    /// Whether the table is a `DUPSORT` table.
+    const IS_DUPSORT: bool;
 }
 
 /// DupSort allows for keys to be repeated in the database.
@@ -44,7 +46,7 @@ pub enum TableType {
     DupSort,
 }
 
-pub const NUM_TABLES: usize = 22;
+pub const NUM_TABLES: usize = 26;
 
 /// Macro to declare `libmdbx` tables.
 #[macro_export]
@@ -119,6 +121,7 @@ macro_rules! tables {
                 const NAME: &'static str = stringify!($table_name);
                 type Key = $key;
                 type Value = $value;
+                const IS_DUPSORT: bool = is_dupsort!($($key_type2)?);
             }
 
             $(
@@ -144,17 +147,31 @@ macro_rules! dupsort {
     };
 }
 
+/// Macro to tell whether a table declared with [`tables!`] is a `DUPSORT` table, ie. whether it
+/// has a subkey.
+#[macro_export]
+macro_rules! is_dupsort {
+    () => {
+        false
+    };
+    ($subkey:ty) => {
+        true
+    };
+}
+
 define_tables_enum! {[
     (Headers, TableType::Table),
     (BlockHashes, TableType::Table),
     (BlockNumbers, TableType::Table),
     (BlockBodyIndices, TableType::Table),
     (BlockStatusses, TableType::Table),
+    (BlockStatistics, TableType::Table),
     (TxNumbers, TableType::Table),
     (TxBlocks, TableType::Table),
     (TxHashes, TableType::Table),
     (Transactions, TableType::Table),
     (Receipts, TableType::Table),
+    (TxExecutions, TableType::Table),
     (CompiledClassHashes, TableType::Table),
     (CompiledClasses, TableType::Table),
     (SierraClasses, TableType::Table),
@@ -166,7 +183,9 @@ define_tables_enum! {[
     (NonceChangeHistory, TableType::DupSort),
     (ClassChangeHistory, TableType::DupSort),
     (StorageChangeHistory, TableType::DupSort),
-    (StorageChangeSet, TableType::Table)
+    (StorageChangeSet, TableType::Table),
+    (DupSortOverflow, TableType::Table),
+    (PruneCheckpoints, TableType::Table)
 ]}
 
 tables! {
@@ -178,6 +197,8 @@ tables! {
     BlockNumbers: (BlockHash) => BlockNumber,
     /// Stores block finality status according to its block number
     BlockStatusses: (BlockNumber) => FinalityStatus,
+    /// Stores the aggregated activity of a block according to its block number
+    BlockStatistics: (BlockNumber) => BlockStats,
     /// Block number to its body indices which stores the tx number of
     /// the first tx in the block and the number of txs in the block.
     BlockBodyIndices: (BlockNumber) => StoredBlockBodyIndices,
@@ -222,8 +243,14 @@ tables! {
     /// storage change set
     StorageChangeSet: (ContractStorageKey) => BlockList,
     /// Account storage change set
-    StorageChangeHistory: (BlockNumber, ContractStorageKey) => ContractStorageEntry
+    StorageChangeHistory: (BlockNumber, ContractStorageKey) => ContractStorageEntry,
+
+    /// Stores the values of the `DUPSORT` tables that are too large to be stored inline, according
+    /// to their generated id.
+    DupSortOverflow: (u64) => Vec<u8>,
 
+    /// Stores the block a pruned history is pruned at, according to the key of the history.
+    PruneCheckpoints: (u64) => BlockNumber
 }
 
 #[cfg(test)]
@@ -239,34 +266,40 @@ mod tests {
         assert_eq!(Tables::ALL[2].name(), BlockNumbers::NAME);
         assert_eq!(Tables::ALL[3].name(), BlockBodyIndices::NAME);
         assert_eq!(Tables::ALL[4].name(), BlockStatusses::NAME);
-        assert_eq!(Tables::ALL[5].name(), TxNumbers::NAME);
-        assert_eq!(Tables::ALL[6].name(), TxBlocks::NAME);
-        assert_eq!(Tables::ALL[7].name(), TxHashes::NAME);
-        assert_eq!(Tables::ALL[8].name(), Transactions::NAME);
-        assert_eq!(Tables::ALL[9].name(), Receipts::NAME);
-        assert_eq!(Tables::ALL[10].name(), CompiledClassHashes::NAME);
-        assert_eq!(Tables::ALL[11].name(), CompiledClasses::NAME);
-        assert_eq!(Tables::ALL[12].name(), SierraClasses::NAME);
-        assert_eq!(Tables::ALL[13].name(), ContractInfo::NAME);
-        assert_eq!(Tables::ALL[14].name(), ContractStorage::NAME);
-        assert_eq!(Tables::ALL[15].name(), ClassDeclarationBlock::NAME);
-        assert_eq!(Tables::ALL[16].name(), ClassDeclarations::NAME);
-        assert_eq!(Tables::ALL[17].name(), ContractInfoChangeSet::NAME);
-        assert_eq!(Tables::ALL[18].name(), NonceChangeHistory::NAME);
-        assert_eq!(Tables::ALL[19].name(), ClassChangeHistory::NAME);
-        assert_eq!(Tables::ALL[20].name(), StorageChangeHistory::NAME);
-        assert_eq!(Tables::ALL[21].name(), StorageChangeSet::NAME);
+        assert_eq!(Tables::ALL[5].name(), BlockStatistics::NAME);
+        assert_eq!(Tables::ALL[6].name(), TxNumbers::NAME);
+        assert_eq!(Tables::ALL[7].name(), TxBlocks::NAME);
+        assert_eq!(Tables::ALL[8].name(), TxHashes::NAME);
+        assert_eq!(Tables::ALL[9].name(), Transactions::NAME);
+        assert_eq!(Tables::ALL[10].name(), Receipts::NAME);
+        assert_eq!(Tables::ALL[11].name(), TxExecutions::NAME);
+        assert_eq!(Tables::ALL[12].name(), CompiledClassHashes::NAME);
+        assert_eq!(Tables::ALL[13].name(), CompiledClasses::NAME);
+        assert_eq!(Tables::ALL[14].name(), SierraClasses::NAME);
+        assert_eq!(Tables::ALL[15].name(), ContractInfo::NAME);
+        assert_eq!(Tables::ALL[16].name(), ContractStorage::NAME);
+        assert_eq!(Tables::ALL[17].name(), ClassDeclarationBlock::NAME);
+        assert_eq!(Tables::ALL[18].name(), ClassDeclarations::NAME);
+        assert_eq!(Tables::ALL[19].name(), ContractInfoChangeSet::NAME);
+        assert_eq!(Tables::ALL[20].name(), NonceChangeHistory::NAME);
+        assert_eq!(Tables::ALL[21].name(), ClassChangeHistory::NAME);
+        assert_eq!(Tables::ALL[22].name(), StorageChangeHistory::NAME);
+        assert_eq!(Tables::ALL[23].name(), StorageChangeSet::NAME);
+        assert_eq!(Tables::ALL[24].name(), DupSortOverflow::NAME);
+        assert_eq!(Tables::ALL[25].name(), PruneCheckpoints::NAME);
 
         assert_eq!(Tables::Headers.table_type(), TableType::Table);
         assert_eq!(Tables::BlockHashes.table_type(), TableType::Table);
         assert_eq!(Tables::BlockNumbers.table_type(), TableType::Table);
         assert_eq!(Tables::BlockBodyIndices.table_type(), TableType::Table);
         assert_eq!(Tables::BlockStatusses.table_type(), TableType::Table);
+        assert_eq!(Tables::BlockStatistics.table_type(), TableType::Table);
         assert_eq!(Tables::TxNumbers.table_type(), TableType::Table);
         assert_eq!(Tables::TxBlocks.table_type(), TableType::Table);
         assert_eq!(Tables::TxHashes.table_type(), TableType::Table);
         assert_eq!(Tables::Transactions.table_type(), TableType::Table);
         assert_eq!(Tables::Receipts.table_type(), TableType::Table);
+        assert_eq!(Tables::TxExecutions.table_type(), TableType::Table);
         assert_eq!(Tables::CompiledClassHashes.table_type(), TableType::Table);
         assert_eq!(Tables::CompiledClasses.table_type(), TableType::Table);
         assert_eq!(Tables::SierraClasses.table_type(), TableType::Table);
@@ -279,9 +312,16 @@ mod tests {
         assert_eq!(Tables::ClassChangeHistory.table_type(), TableType::DupSort);
         assert_eq!(Tables::StorageChangeHistory.table_type(), TableType::DupSort);
         assert_eq!(Tables::StorageChangeSet.table_type(), TableType::Table);
+        assert_eq!(Tables::DupSortOverflow.table_type(), TableType::Table);
+        assert_eq!(Tables::PruneCheckpoints.table_type(), TableType::Table);
+
+        assert!(!Headers::IS_DUPSORT);
+        assert!(ContractStorage::IS_DUPSORT);
+        assert!(StorageChangeHistory::IS_DUPSORT);
+        assert!(!DupSortOverflow::IS_DUPSORT);
     }
 
-    use katana_primitives::block::{BlockHash, BlockNumber, FinalityStatus, Header};
+    use katana_primitives::block::{BlockHash, BlockNumber, BlockStats, FinalityStatus, Header};
     use katana_primitives::class::{ClassHash, CompiledClass, CompiledClassHash};
     use katana_primitives::contract::{ContractAddress, GenericContractInfo};
     use katana_primitives::receipt::Receipt;
@@ -347,6 +387,7 @@ mod tests {
             (BlockHash, BlockHash::default()),
             (BlockNumber, BlockNumber::default()),
             (FinalityStatus, FinalityStatus::AcceptedOnL1),
+            (BlockStats, BlockStats { tx_count: 3, unique_senders: 2, events_count: 7, declared_classes_count: 1 }),
             (StoredBlockBodyIndices, StoredBlockBodyIndices::default()),
             (TxNumber, 77),
             (TxHash, felt!("0x123456789")),
@@ -362,7 +403,8 @@ mod tests {
             (ContractNonceChange, ContractNonceChange::default()),
             (ContractClassChange, ContractClassChange::default()),
             (BlockList, BlockList::default()),
-            (ContractStorageEntry, ContractStorageEntry::default())
+            (ContractStorageEntry, ContractStorageEntry::default()),
+            (Vec<u8>, vec![1, 2, 3])
         }
     }
 }
@@ -53,19 +53,6 @@ where
     Ok((key, value))
 }
 
-/// Helper function to decode only a value from a `(key, value)` pair.
-pub(crate) fn decode_value<'a, T>(
-    kv: (Cow<'a, [u8]>, Cow<'a, [u8]>),
-) -> Result<T::Value, DatabaseError>
-where
-    T: Table,
-{
-    Ok(match kv.1 {
-        Cow::Borrowed(v) => Decompress::decompress(v)?,
-        Cow::Owned(v) => Decompress::decompress(v)?,
-    })
-}
-
 /// Helper function to decode a value. It can be a key or subkey.
 pub(crate) fn decode_one<T>(value: Cow<'_, [u8]>) -> Result<T::Value, DatabaseError>
 where
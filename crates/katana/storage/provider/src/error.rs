@@ -59,6 +59,15 @@ pub enum ProviderError {
     #[error("Missing compiled class hash for class hash {0:#x}")]
     MissingCompiledClassHash(ClassHash),
 
+    /// Error when the compiled class of a class can't be compiled from its Sierra class.
+    #[error("Failed to compile class {class_hash:#x}: {error}")]
+    ClassCompilation {
+        /// The hash of the class.
+        class_hash: ClassHash,
+        /// The compilation error.
+        error: String,
+    },
+
     /// Error when a contract class change entry is not found but the block number of when the
     /// change happen exists in the class change list.
     #[error("Missing contract class change entry")]
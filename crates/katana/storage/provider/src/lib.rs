@@ -2,8 +2,8 @@ use std::ops::{Range, RangeInclusive};
 
 use katana_db::models::block::StoredBlockBodyIndices;
 use katana_primitives::block::{
-    Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithTxHashes, FinalityStatus, Header,
-    SealedBlockWithStatus,
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockStats, BlockWithTxHashes,
+    FinalityStatus, Header, SealedBlockWithStatus,
 };
 use katana_primitives::class::{ClassHash, CompiledClass, CompiledClassHash, FlattenedSierraClass};
 use katana_primitives::contract::{ContractAddress, GenericContractInfo, StorageKey, StorageValue};
@@ -13,7 +13,7 @@ use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
 use katana_primitives::trace::TxExecInfo;
 use katana_primitives::transaction::{TxHash, TxNumber, TxWithHash};
 use katana_primitives::FieldElement;
-use traits::block::{BlockIdReader, BlockStatusProvider, BlockWriter};
+use traits::block::{BlockIdReader, BlockStatsProvider, BlockStatusProvider, BlockWriter};
 use traits::contract::{ContractClassProvider, ContractClassWriter};
 use traits::env::BlockEnvProvider;
 use traits::state::{StateRootProvider, StateWriter};
@@ -32,6 +32,9 @@ use crate::traits::transaction::{ReceiptProvider, TransactionProvider, Transacti
 /// A result type for blockchain providers.
 pub type ProviderResult<T> = Result<T, error::ProviderError>;
 
+/// A lazy iterator over items read from the storage, one item at a time.
+pub type ProviderIter<'a, T> = Box<dyn Iterator<Item = ProviderResult<T>> + 'a>;
+
 /// A blockchain provider that can be used to access the storage.
 ///
 /// Serves as the main entrypoint for interacting with the storage storage. Every read/write
@@ -65,6 +68,10 @@ where
         self.provider.blocks_in_range(range)
     }
 
+    fn blocks_range(&self, range: Range<BlockNumber>) -> ProviderResult<ProviderIter<'_, Block>> {
+        self.provider.blocks_range(range)
+    }
+
     fn block_body_indices(
         &self,
         id: BlockHashOrNumber,
@@ -119,6 +126,22 @@ where
     }
 }
 
+impl<Db> BlockStatsProvider for BlockchainProvider<Db>
+where
+    Db: BlockStatsProvider,
+{
+    fn block_stats(&self, id: BlockHashOrNumber) -> ProviderResult<Option<BlockStats>> {
+        BlockStatsProvider::block_stats(&self.provider, id)
+    }
+
+    fn block_stats_in_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, BlockStats)>> {
+        BlockStatsProvider::block_stats_in_range(&self.provider, range)
+    }
+}
+
 impl<Db> BlockWriter for BlockchainProvider<Db>
 where
     Db: BlockWriter,
@@ -224,6 +247,13 @@ where
     ) -> ProviderResult<Option<Vec<Receipt>>> {
         self.provider.receipts_by_block(block_id)
     }
+
+    fn receipts_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<ProviderIter<'_, Vec<Receipt>>> {
+        self.provider.receipts_range(range)
+    }
 }
 
 impl<Db> StateProvider for BlockchainProvider<Db>
@@ -296,6 +326,13 @@ where
     fn state_update(&self, block_id: BlockHashOrNumber) -> ProviderResult<Option<StateUpdates>> {
         self.provider.state_update(block_id)
     }
+
+    fn state_updates_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<ProviderIter<'_, StateUpdates>> {
+        self.provider.state_updates_range(range)
+    }
 }
 
 impl<Db> ContractInfoProvider for BlockchainProvider<Db>
@@ -0,0 +1,178 @@
+//! Iterators over ranges of blocks that walk the database cursors lazily.
+//!
+//! Each iterator holds its own read-only transaction, so it sees a consistent snapshot of the
+//! database for as long as it lives, and reads the entries of the range sequentially instead of
+//! looking up every block separately.
+
+use std::ops::Range;
+
+use katana_db::error::DatabaseError;
+use katana_db::mdbx::cursor::CursorRO;
+use katana_db::mdbx::tx::TxRO;
+use katana_db::models::block::StoredBlockBodyIndices;
+use katana_db::tables::{self, Table};
+use katana_db::utils::KeyValue;
+use katana_primitives::block::{Block, BlockNumber};
+use katana_primitives::receipt::Receipt;
+use katana_primitives::state::StateUpdates;
+use katana_primitives::transaction::TxWithHash;
+
+use super::state_update_at;
+use crate::error::ProviderError;
+use crate::ProviderResult;
+
+/// Walks the entries of a table whose keys are in a range, moving the cursor forward at each step.
+struct RangeWalker<T: Table<Key = u64>> {
+    cursor: CursorRO<T>,
+    start: Option<u64>,
+    end: u64,
+    done: bool,
+}
+
+impl<T: Table<Key = u64>> RangeWalker<T> {
+    fn new(db_tx: &TxRO, range: Range<u64>) -> Result<Self, DatabaseError> {
+        let cursor = db_tx.cursor::<T>()?;
+        Ok(Self { cursor, start: Some(range.start), end: range.end, done: range.is_empty() })
+    }
+}
+
+impl<T: Table<Key = u64>> Iterator for RangeWalker<T> {
+    type Item = Result<KeyValue<T>, DatabaseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let entry = match self.start.take() {
+            Some(start) => self.cursor.seek(start),
+            None => self.cursor.next(),
+        };
+
+        match entry {
+            Ok(Some((key, value))) if key < self.end => Some(Ok((key, value))),
+            Ok(_) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Reads the values of the transactions in `indices` from a table keyed by transaction number.
+fn tx_values<T: Table<Key = u64>>(
+    cursor: &mut CursorRO<T>,
+    indices: &StoredBlockBodyIndices,
+) -> Result<Vec<T::Value>, DatabaseError> {
+    let end = indices.tx_offset + indices.tx_count;
+    cursor
+        .walk(Some(indices.tx_offset))?
+        .take_while(|entry| entry.as_ref().map_or(true, |(num, _)| *num < end))
+        .map(|entry| entry.map(|(_, value)| value))
+        .collect()
+}
+
+/// Iterator over the blocks in a range.
+pub(super) struct BlocksIter {
+    headers: RangeWalker<tables::Headers>,
+    indices: CursorRO<tables::BlockBodyIndices>,
+    hashes: CursorRO<tables::TxHashes>,
+    transactions: CursorRO<tables::Transactions>,
+    _db_tx: TxRO,
+}
+
+impl BlocksIter {
+    pub(super) fn new(db_tx: TxRO, range: Range<BlockNumber>) -> ProviderResult<Self> {
+        Ok(Self {
+            headers: RangeWalker::new(&db_tx, range)?,
+            indices: db_tx.cursor()?,
+            hashes: db_tx.cursor()?,
+            transactions: db_tx.cursor()?,
+            _db_tx: db_tx,
+        })
+    }
+
+    fn read(&mut self, num: BlockNumber) -> ProviderResult<Vec<TxWithHash>> {
+        let (_, indices) =
+            self.indices.set(num)?.ok_or(ProviderError::MissingBlockBodyIndices(num))?;
+
+        let hashes = tx_values(&mut self.hashes, &indices)?;
+        let transactions = tx_values(&mut self.transactions, &indices)?;
+
+        if hashes.len() != transactions.len() || hashes.len() as u64 != indices.tx_count {
+            return Err(ProviderError::MissingBlockTxs(num));
+        }
+
+        Ok(hashes
+            .into_iter()
+            .zip(transactions)
+            .map(|(hash, transaction)| TxWithHash { hash, transaction })
+            .collect())
+    }
+}
+
+impl Iterator for BlocksIter {
+    type Item = ProviderResult<Block>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (num, header) = match self.headers.next()? {
+            Ok(entry) => entry,
+            Err(error) => return Some(Err(error.into())),
+        };
+        Some(self.read(num).map(|body| Block { header, body }))
+    }
+}
+
+/// Iterator over the receipts of each block in a range.
+pub(super) struct ReceiptsIter {
+    indices: RangeWalker<tables::BlockBodyIndices>,
+    receipts: CursorRO<tables::Receipts>,
+    _db_tx: TxRO,
+}
+
+impl ReceiptsIter {
+    pub(super) fn new(db_tx: TxRO, range: Range<BlockNumber>) -> ProviderResult<Self> {
+        let indices = RangeWalker::new(&db_tx, range)?;
+        Ok(Self { indices, receipts: db_tx.cursor()?, _db_tx: db_tx })
+    }
+}
+
+impl Iterator for ReceiptsIter {
+    type Item = ProviderResult<Vec<Receipt>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, indices) = match self.indices.next()? {
+            Ok(entry) => entry,
+            Err(error) => return Some(Err(error.into())),
+        };
+        Some(tx_values(&mut self.receipts, &indices).map_err(Into::into))
+    }
+}
+
+/// Iterator over the state updates of each block in a range.
+pub(super) struct StateUpdatesIter {
+    blocks: RangeWalker<tables::BlockHashes>,
+    db_tx: TxRO,
+}
+
+impl StateUpdatesIter {
+    pub(super) fn new(db_tx: TxRO, range: Range<BlockNumber>) -> ProviderResult<Self> {
+        Ok(Self { blocks: RangeWalker::new(&db_tx, range)?, db_tx })
+    }
+}
+
+impl Iterator for StateUpdatesIter {
+    type Item = ProviderResult<StateUpdates>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (num, _) = match self.blocks.next()? {
+            Ok(entry) => entry,
+            Err(error) => return Some(Err(error.into())),
+        };
+        Some(state_update_at(&self.db_tx, num))
+    }
+}
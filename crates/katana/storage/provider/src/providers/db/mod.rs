@@ -1,8 +1,11 @@
+mod iter;
+mod recovery;
 pub mod state;
 
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::ops::{Range, RangeInclusive};
+use std::sync::Arc;
 
 use katana_db::error::DatabaseError;
 use katana_db::mdbx::{self, DbEnv};
@@ -12,11 +15,12 @@ use katana_db::models::contract::{
 };
 use katana_db::models::list::BlockList;
 use katana_db::models::storage::{ContractStorageEntry, ContractStorageKey, StorageEntry};
+use katana_db::prune::STATE_HISTORY_CHECKPOINT;
 use katana_db::tables::{self, DupSort, Table};
 use katana_db::utils::KeyValue;
 use katana_primitives::block::{
-    Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithTxHashes, FinalityStatus, Header,
-    SealedBlockWithStatus,
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockStats, BlockWithTxHashes,
+    FinalityStatus, Header, SealedBlock, SealedBlockWithStatus,
 };
 use katana_primitives::class::{ClassHash, CompiledClassHash};
 use katana_primitives::contract::{
@@ -29,10 +33,11 @@ use katana_primitives::trace::TxExecInfo;
 use katana_primitives::transaction::{TxHash, TxNumber, TxWithHash};
 use katana_primitives::FieldElement;
 
+use self::iter::{BlocksIter, ReceiptsIter, StateUpdatesIter};
 use crate::error::ProviderError;
 use crate::traits::block::{
-    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
-    HeaderProvider,
+    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatsProvider, BlockStatusProvider,
+    BlockWriter, HeaderProvider,
 };
 use crate::traits::env::BlockEnvProvider;
 use crate::traits::state::{StateFactoryProvider, StateProvider, StateRootProvider};
@@ -41,22 +46,25 @@ use crate::traits::transaction::{
     ReceiptProvider, TransactionProvider, TransactionStatusProvider, TransactionTraceProvider,
     TransactionsProviderExt,
 };
-use crate::ProviderResult;
+use crate::{ProviderIter, ProviderResult};
 
 /// A provider implementation that uses a persistent database as the backend.
+///
+/// The database environment is shared with the state providers, which need to write the classes
+/// that they compile.
 #[derive(Debug)]
-pub struct DbProvider(DbEnv);
+pub struct DbProvider(Arc<DbEnv>);
 
 impl DbProvider {
     /// Creates a new [`DbProvider`] from the given [`DbEnv`].
     pub fn new(db: DbEnv) -> Self {
-        Self(db)
+        Self(Arc::new(db))
     }
 }
 
 impl StateFactoryProvider for DbProvider {
     fn latest(&self) -> ProviderResult<Box<dyn StateProvider>> {
-        Ok(Box::new(self::state::LatestStateProvider::new(self.0.tx()?)))
+        Ok(Box::new(self::state::LatestStateProvider::new(Arc::clone(&self.0), self.0.tx()?)))
     }
 
     fn historical(
@@ -79,7 +87,13 @@ impl StateFactoryProvider for DbProvider {
 
         let Some(num) = block_number else { return Ok(None) };
 
-        Ok(Some(Box::new(self::state::HistoricalStateProvider::new(self.0.tx()?, num))))
+        let db_tx = self.0.tx()?;
+        if is_state_pruned(&db_tx, num)? {
+            return Ok(None);
+        }
+
+        let provider = self::state::HistoricalStateProvider::new(Arc::clone(&self.0), db_tx, num);
+        Ok(Some(Box::new(provider)))
     }
 }
 
@@ -219,6 +233,10 @@ impl BlockProvider for DbProvider {
         db_tx.commit()?;
         Ok(blocks)
     }
+
+    fn blocks_range(&self, range: Range<BlockNumber>) -> ProviderResult<ProviderIter<'_, Block>> {
+        Ok(Box::new(BlocksIter::new(self.0.tx()?, range)?))
+    }
 }
 
 impl BlockStatusProvider for DbProvider {
@@ -242,6 +260,41 @@ impl BlockStatusProvider for DbProvider {
     }
 }
 
+impl BlockStatsProvider for DbProvider {
+    fn block_stats(&self, id: BlockHashOrNumber) -> ProviderResult<Option<BlockStats>> {
+        let db_tx = self.0.tx()?;
+
+        let block_num = match id {
+            BlockHashOrNumber::Num(num) => Some(num),
+            BlockHashOrNumber::Hash(hash) => db_tx.get::<tables::BlockNumbers>(hash)?,
+        };
+
+        let stats = match block_num {
+            Some(num) => db_tx.get::<tables::BlockStatistics>(num)?,
+            None => None,
+        };
+
+        db_tx.commit()?;
+        Ok(stats)
+    }
+
+    fn block_stats_in_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, BlockStats)>> {
+        let db_tx = self.0.tx()?;
+
+        let stats = db_tx
+            .cursor::<tables::BlockStatistics>()?
+            .walk(Some(range.start))?
+            .take_while(|entry| entry.as_ref().map_or(true, |(num, _)| *num < range.end))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        db_tx.commit()?;
+        Ok(stats)
+    }
+}
+
 impl StateRootProvider for DbProvider {
     fn state_root(&self, block_id: BlockHashOrNumber) -> ProviderResult<Option<FieldElement>> {
         let db_tx = self.0.tx()?;
@@ -263,91 +316,110 @@ impl StateRootProvider for DbProvider {
 
 impl StateUpdateProvider for DbProvider {
     fn state_update(&self, block_id: BlockHashOrNumber) -> ProviderResult<Option<StateUpdates>> {
-        // A helper function that iterates over all entries in a dupsort table and collects the
-        // results into `V`. If `key` is not found, `V::default()` is returned.
-        fn dup_entries<Tb, V, T>(
-            db_tx: &mdbx::tx::TxRO,
-            key: <Tb as Table>::Key,
-            f: impl FnMut(Result<KeyValue<Tb>, DatabaseError>) -> ProviderResult<T>,
-        ) -> ProviderResult<V>
-        where
-            Tb: DupSort + Debug,
-            V: FromIterator<T> + Default,
-        {
-            Ok(db_tx
-                .cursor::<Tb>()?
-                .walk_dup(Some(key), None)?
-                .map(|walker| walker.map(f).collect::<ProviderResult<V>>())
-                .transpose()?
-                .unwrap_or_default())
-        }
-
         let db_tx = self.0.tx()?;
         let block_num = self.block_number_by_id(block_id)?;
 
         if let Some(block_num) = block_num {
-            let nonce_updates = dup_entries::<
-                tables::NonceChangeHistory,
-                HashMap<ContractAddress, Nonce>,
-                _,
-            >(&db_tx, block_num, |entry| {
-                let (_, ContractNonceChange { contract_address, nonce }) = entry?;
-                Ok((contract_address, nonce))
-            })?;
-
-            let contract_updates = dup_entries::<
-                tables::ClassChangeHistory,
-                HashMap<ContractAddress, ClassHash>,
-                _,
-            >(&db_tx, block_num, |entry| {
-                let (_, ContractClassChange { contract_address, class_hash }) = entry?;
-                Ok((contract_address, class_hash))
-            })?;
-
-            let declared_classes = dup_entries::<
-                tables::ClassDeclarations,
-                HashMap<ClassHash, CompiledClassHash>,
-                _,
-            >(&db_tx, block_num, |entry| {
-                let (_, class_hash) = entry?;
-
-                let compiled_hash = db_tx
-                    .get::<tables::CompiledClassHashes>(class_hash)?
-                    .ok_or(ProviderError::MissingCompiledClassHash(class_hash))?;
-
-                Ok((class_hash, compiled_hash))
-            })?;
-
-            let storage_updates = {
-                let entries = dup_entries::<
-                    tables::StorageChangeHistory,
-                    Vec<(ContractAddress, (StorageKey, StorageValue))>,
-                    _,
-                >(&db_tx, block_num, |entry| {
-                    let (_, ContractStorageEntry { key, value }) = entry?;
-                    Ok((key.contract_address, (key.key, value)))
-                })?;
-
-                let mut map: HashMap<_, HashMap<StorageKey, StorageValue>> = HashMap::new();
-
-                entries.into_iter().for_each(|(addr, (key, value))| {
-                    map.entry(addr).or_default().insert(key, value);
-                });
-
-                map
-            };
+            if is_state_pruned(&db_tx, block_num)? {
+                return Ok(None);
+            }
 
+            let state_update = state_update_at(&db_tx, block_num)?;
             db_tx.commit()?;
-            Ok(Some(StateUpdates {
-                nonce_updates,
-                storage_updates,
-                contract_updates,
-                declared_classes,
-            }))
+            Ok(Some(state_update))
         } else {
             Ok(None)
         }
     }
+
+    fn state_updates_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<ProviderIter<'_, StateUpdates>> {
+        Ok(Box::new(StateUpdatesIter::new(self.0.tx()?, range)?))
+    }
+}
+
+/// Returns `true` if the state history has been pruned past the block `block_num`, whose state
+/// and state changes can't be served anymore.
+fn is_state_pruned(db_tx: &mdbx::tx::TxRO, block_num: BlockNumber) -> ProviderResult<bool> {
+    let pruned_at = db_tx.get::<tables::PruneCheckpoints>(STATE_HISTORY_CHECKPOINT)?;
+    Ok(pruned_at.is_some_and(|pruned_at| block_num < pruned_at))
+}
+
+/// Returns the state changes made in the block `block_num`.
+fn state_update_at(db_tx: &mdbx::tx::TxRO, block_num: BlockNumber) -> ProviderResult<StateUpdates> {
+    let nonce_updates = dup_entries::<
+        tables::NonceChangeHistory,
+        HashMap<ContractAddress, Nonce>,
+        _,
+    >(db_tx, block_num, |entry| {
+        let (_, ContractNonceChange { contract_address, nonce }) = entry?;
+        Ok((contract_address, nonce))
+    })?;
+
+    let contract_updates = dup_entries::<
+        tables::ClassChangeHistory,
+        HashMap<ContractAddress, ClassHash>,
+        _,
+    >(db_tx, block_num, |entry| {
+        let (_, ContractClassChange { contract_address, class_hash }) = entry?;
+        Ok((contract_address, class_hash))
+    })?;
+
+    let declared_classes = dup_entries::<
+        tables::ClassDeclarations,
+        HashMap<ClassHash, CompiledClassHash>,
+        _,
+    >(db_tx, block_num, |entry| {
+        let (_, class_hash) = entry?;
+
+        let compiled_hash = db_tx
+            .get::<tables::CompiledClassHashes>(class_hash)?
+            .ok_or(ProviderError::MissingCompiledClassHash(class_hash))?;
+
+        Ok((class_hash, compiled_hash))
+    })?;
+
+    let storage_updates = {
+        let entries = dup_entries::<
+            tables::StorageChangeHistory,
+            Vec<(ContractAddress, (StorageKey, StorageValue))>,
+            _,
+        >(db_tx, block_num, |entry| {
+            let (_, ContractStorageEntry { key, value }) = entry?;
+            Ok((key.contract_address, (key.key, value)))
+        })?;
+
+        let mut map: HashMap<_, HashMap<StorageKey, StorageValue>> = HashMap::new();
+
+        entries.into_iter().for_each(|(addr, (key, value))| {
+            map.entry(addr).or_default().insert(key, value);
+        });
+
+        map
+    };
+
+    Ok(StateUpdates { nonce_updates, storage_updates, contract_updates, declared_classes })
+}
+
+/// A helper function that iterates over all entries in a dupsort table and collects the
+/// results into `V`. If `key` is not found, `V::default()` is returned.
+fn dup_entries<Tb, V, T>(
+    db_tx: &mdbx::tx::TxRO,
+    key: <Tb as Table>::Key,
+    f: impl FnMut(Result<KeyValue<Tb>, DatabaseError>) -> ProviderResult<T>,
+) -> ProviderResult<V>
+where
+    Tb: DupSort + Debug,
+    V: FromIterator<T> + Default,
+{
+    Ok(db_tx
+        .cursor::<Tb>()?
+        .walk_dup(Some(key), None)?
+        .map(|walker| walker.map(f).collect::<ProviderResult<V>>())
+        .transpose()?
+        .unwrap_or_default())
 }
 
 impl TransactionProvider for DbProvider {
@@ -492,15 +564,37 @@ impl TransactionStatusProvider for DbProvider {
 }
 
 impl TransactionTraceProvider for DbProvider {
-    fn transaction_execution(&self, _hash: TxHash) -> ProviderResult<Option<TxExecInfo>> {
-        todo!()
+    fn transaction_execution(&self, hash: TxHash) -> ProviderResult<Option<TxExecInfo>> {
+        let db_tx = self.0.tx()?;
+        if let Some(num) = db_tx.get::<tables::TxNumbers>(hash)? {
+            let execution = db_tx.get::<tables::TxExecutions>(num)?;
+            db_tx.commit()?;
+            Ok(execution)
+        } else {
+            Ok(None)
+        }
     }
 
     fn transactions_executions_by_block(
         &self,
-        _block_id: BlockHashOrNumber,
+        block_id: BlockHashOrNumber,
     ) -> ProviderResult<Option<Vec<TxExecInfo>>> {
-        todo!()
+        if let Some(indices) = self.block_body_indices(block_id)? {
+            let db_tx = self.0.tx()?;
+            let mut executions = Vec::with_capacity(indices.tx_count as usize);
+
+            let range = Range::from(indices);
+            for i in range {
+                if let Some(execution) = db_tx.get::<tables::TxExecutions>(i)? {
+                    executions.push(execution);
+                }
+            }
+
+            db_tx.commit()?;
+            Ok(Some(executions))
+        } else {
+            Ok(None)
+        }
     }
 }
 
@@ -540,6 +634,13 @@ impl ReceiptProvider for DbProvider {
             Ok(None)
         }
     }
+
+    fn receipts_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<ProviderIter<'_, Vec<Receipt>>> {
+        Ok(Box::new(ReceiptsIter::new(self.0.tx()?, range)?))
+    }
 }
 
 impl BlockEnvProvider for DbProvider {
@@ -561,172 +662,215 @@ impl BlockWriter for DbProvider {
         block: SealedBlockWithStatus,
         states: StateUpdatesWithDeclaredClasses,
         receipts: Vec<Receipt>,
-        _executions: Vec<TxExecInfo>,
+        executions: Vec<TxExecInfo>,
     ) -> ProviderResult<()> {
-        self.0.update(move |db_tx| -> ProviderResult<()> {
-            let block_hash = block.block.header.hash;
+        // everything is written in a single transaction which is only committed if all the writes
+        // succeed, so a block is either fully inserted or not at all
+        self.0.try_update(move |db_tx| -> ProviderResult<()> {
             let block_number = block.block.header.header.number;
-
-            let block_header = block.block.header.header;
-            let transactions = block.block.body;
-
-            let tx_count = transactions.len() as u64;
-            let tx_offset = db_tx.entries::<tables::Transactions>()? as u64;
-            let block_body_indices = StoredBlockBodyIndices { tx_offset, tx_count };
-
-            db_tx.put::<tables::BlockHashes>(block_number, block_hash)?;
-            db_tx.put::<tables::BlockNumbers>(block_hash, block_number)?;
+            let stats = BlockStats::new(
+                &block.block.body,
+                &receipts,
+                states.state_updates.declared_classes.len() as u64,
+            );
+
+            insert_block(db_tx, block.block, receipts, executions)?;
+            db_tx.put::<tables::BlockStatistics>(block_number, stats)?;
+            insert_state_updates(db_tx, block_number, states)?;
+
+            // a block without a status is one that was partially written by a version committing
+            // blocks across several transactions, and is unwound by `DbProvider::recover`
             db_tx.put::<tables::BlockStatusses>(block_number, block.status)?;
 
-            db_tx.put::<tables::Headers>(block_number, block_header)?;
-            db_tx.put::<tables::BlockBodyIndices>(block_number, block_body_indices)?;
+            Ok(())
+        })?
+    }
+}
 
-            for (i, (transaction, receipt)) in transactions.into_iter().zip(receipts).enumerate() {
-                let tx_number = tx_offset + i as u64;
-                let tx_hash = transaction.hash;
+/// Inserts the header, the transactions, the receipts and the executions of a block.
+fn insert_block(
+    db_tx: &mdbx::tx::TxRW,
+    block: SealedBlock,
+    receipts: Vec<Receipt>,
+    executions: Vec<TxExecInfo>,
+) -> ProviderResult<()> {
+    let block_hash = block.header.hash;
+    let block_number = block.header.header.number;
+
+    let block_header = block.header.header;
+    let transactions = block.body;
+
+    let tx_count = transactions.len() as u64;
+    let tx_offset = db_tx.entries::<tables::Transactions>()? as u64;
+    let block_body_indices = StoredBlockBodyIndices { tx_offset, tx_count };
+
+    db_tx.put::<tables::BlockHashes>(block_number, block_hash)?;
+    db_tx.put::<tables::BlockNumbers>(block_hash, block_number)?;
+
+    db_tx.put::<tables::Headers>(block_number, block_header)?;
+    db_tx.put::<tables::BlockBodyIndices>(block_number, block_body_indices)?;
+
+    for (i, (transaction, receipt)) in transactions.into_iter().zip(receipts).enumerate() {
+        let tx_number = tx_offset + i as u64;
+        let tx_hash = transaction.hash;
+
+        db_tx.put::<tables::TxHashes>(tx_number, tx_hash)?;
+        db_tx.put::<tables::TxNumbers>(tx_hash, tx_number)?;
+        db_tx.put::<tables::TxBlocks>(tx_number, block_number)?;
+        db_tx.put::<tables::Transactions>(tx_number, transaction.transaction)?;
+        db_tx.put::<tables::Receipts>(tx_number, receipt)?;
+    }
 
-                db_tx.put::<tables::TxHashes>(tx_number, tx_hash)?;
-                db_tx.put::<tables::TxNumbers>(tx_hash, tx_number)?;
-                db_tx.put::<tables::TxBlocks>(tx_number, block_number)?;
-                db_tx.put::<tables::Transactions>(tx_number, transaction.transaction)?;
-                db_tx.put::<tables::Receipts>(tx_number, receipt)?;
-            }
+    // executions are optional, eg. blocks that are synced from a remote source may not have
+    // them available
+    for (i, execution) in executions.into_iter().enumerate() {
+        let tx_number = tx_offset + i as u64;
+        db_tx.put::<tables::TxExecutions>(tx_number, execution)?;
+    }
 
-            // insert classes
+    Ok(())
+}
 
-            for (class_hash, compiled_hash) in states.state_updates.declared_classes {
-                db_tx.put::<tables::CompiledClassHashes>(class_hash, compiled_hash)?;
+/// Inserts the classes declared in the block `block_number` and applies its state changes, along
+/// with their history.
+fn insert_state_updates(
+    db_tx: &mdbx::tx::TxRW,
+    block_number: BlockNumber,
+    states: StateUpdatesWithDeclaredClasses,
+) -> ProviderResult<()> {
+    // insert classes
 
-                db_tx.put::<tables::ClassDeclarationBlock>(class_hash, block_number)?;
-                db_tx.put::<tables::ClassDeclarations>(block_number, class_hash)?
-            }
+    for (class_hash, compiled_hash) in states.state_updates.declared_classes {
+        db_tx.put::<tables::CompiledClassHashes>(class_hash, compiled_hash)?;
 
-            for (hash, compiled_class) in states.declared_compiled_classes {
-                db_tx.put::<tables::CompiledClasses>(hash, compiled_class)?;
-            }
+        db_tx.put::<tables::ClassDeclarationBlock>(class_hash, block_number)?;
+        db_tx.put::<tables::ClassDeclarations>(block_number, class_hash)?
+    }
 
-            for (class_hash, sierra_class) in states.declared_sierra_classes {
-                db_tx.put::<tables::SierraClasses>(class_hash, sierra_class)?;
-            }
+    for (hash, compiled_class) in states.declared_compiled_classes {
+        db_tx.put::<tables::CompiledClasses>(hash, compiled_class)?;
+    }
+
+    for (class_hash, sierra_class) in states.declared_sierra_classes {
+        db_tx.put::<tables::SierraClasses>(class_hash, sierra_class)?;
+    }
 
-            // insert storage changes
-            {
-                let mut storage_cursor = db_tx.cursor::<tables::ContractStorage>()?;
-                for (addr, entries) in states.state_updates.storage_updates {
-                    let entries =
-                        entries.into_iter().map(|(key, value)| StorageEntry { key, value });
-
-                    for entry in entries {
-                        match storage_cursor.seek_by_key_subkey(addr, entry.key)? {
-                            Some(current) if current.key == entry.key => {
-                                storage_cursor.delete_current()?;
-                            }
-
-                            _ => {}
-                        }
-
-                        // update block list in the change set
-                        let changeset_key =
-                            ContractStorageKey { contract_address: addr, key: entry.key };
-                        let list = db_tx.get::<tables::StorageChangeSet>(changeset_key.clone())?;
-
-                        let updated_list = match list {
-                            Some(mut list) => {
-                                list.insert(block_number);
-                                list
-                            }
-                            // create a new block list if it doesn't yet exist, and insert the block
-                            // number
-                            None => BlockList::from([block_number]),
-                        };
-
-                        db_tx.put::<tables::StorageChangeSet>(changeset_key, updated_list)?;
-                        storage_cursor.upsert(addr, entry)?;
-
-                        let storage_change_sharded_key =
-                            ContractStorageKey { contract_address: addr, key: entry.key };
-
-                        db_tx.put::<tables::StorageChangeHistory>(
-                            block_number,
-                            ContractStorageEntry {
-                                key: storage_change_sharded_key,
-                                value: entry.value,
-                            },
-                        )?;
+    // insert storage changes
+    {
+        let mut storage_cursor = db_tx.cursor::<tables::ContractStorage>()?;
+        for (addr, entries) in states.state_updates.storage_updates {
+            let entries = entries.into_iter().map(|(key, value)| StorageEntry { key, value });
+
+            for entry in entries {
+                // update block list in the change set
+                let changeset_key = ContractStorageKey { contract_address: addr, key: entry.key };
+                let list = db_tx.get::<tables::StorageChangeSet>(changeset_key.clone())?;
+
+                let updated_list = match list {
+                    Some(mut list) => {
+                        list.insert(block_number);
+                        list
                     }
-                }
-            }
+                    // create a new block list if it doesn't yet exist, and insert the block
+                    // number
+                    None => BlockList::from([block_number]),
+                };
 
-            // update contract info
+                db_tx.put::<tables::StorageChangeSet>(changeset_key, updated_list)?;
 
-            for (addr, class_hash) in states.state_updates.contract_updates {
-                let value = if let Some(info) = db_tx.get::<tables::ContractInfo>(addr)? {
-                    GenericContractInfo { class_hash, ..info }
-                } else {
-                    GenericContractInfo { class_hash, ..Default::default() }
-                };
+                let storage_change_sharded_key =
+                    ContractStorageKey { contract_address: addr, key: entry.key };
 
-                let new_change_set = if let Some(mut change_set) =
-                    db_tx.get::<tables::ContractInfoChangeSet>(addr)?
-                {
-                    change_set.class_change_list.insert(block_number);
-                    change_set
-                } else {
-                    ContractInfoChangeList {
-                        class_change_list: BlockList::from([block_number]),
-                        ..Default::default()
+                db_tx.put::<tables::StorageChangeHistory>(
+                    block_number,
+                    ContractStorageEntry { key: storage_change_sharded_key, value: entry.value },
+                )?;
+
+                match storage_cursor.seek_by_key_subkey(addr, entry.key)? {
+                    Some(current) if current.key == entry.key => {
+                        storage_cursor.delete_current()?;
                     }
-                };
 
-                db_tx.put::<tables::ContractInfo>(addr, value)?;
+                    _ => {}
+                }
 
-                let class_change_key = ContractClassChange { contract_address: addr, class_hash };
-                db_tx.put::<tables::ClassChangeHistory>(block_number, class_change_key)?;
-                db_tx.put::<tables::ContractInfoChangeSet>(addr, new_change_set)?;
+                storage_cursor.upsert(addr, entry)?;
             }
+        }
+    }
 
-            for (addr, nonce) in states.state_updates.nonce_updates {
-                let value = if let Some(info) = db_tx.get::<tables::ContractInfo>(addr)? {
-                    GenericContractInfo { nonce, ..info }
-                } else {
-                    GenericContractInfo { nonce, ..Default::default() }
-                };
+    // update contract info
 
-                let new_change_set = if let Some(mut change_set) =
-                    db_tx.get::<tables::ContractInfoChangeSet>(addr)?
-                {
-                    change_set.nonce_change_list.insert(block_number);
-                    change_set
-                } else {
-                    ContractInfoChangeList {
-                        nonce_change_list: BlockList::from([block_number]),
-                        ..Default::default()
-                    }
-                };
+    for (addr, class_hash) in states.state_updates.contract_updates {
+        let value = if let Some(info) = db_tx.get::<tables::ContractInfo>(addr)? {
+            GenericContractInfo { class_hash, ..info }
+        } else {
+            GenericContractInfo { class_hash, ..Default::default() }
+        };
 
-                db_tx.put::<tables::ContractInfo>(addr, value)?;
+        let new_change_set =
+            if let Some(mut change_set) = db_tx.get::<tables::ContractInfoChangeSet>(addr)? {
+                change_set.class_change_list.insert(block_number);
+                change_set
+            } else {
+                ContractInfoChangeList {
+                    class_change_list: BlockList::from([block_number]),
+                    ..Default::default()
+                }
+            };
 
-                let nonce_change_key = ContractNonceChange { contract_address: addr, nonce };
-                db_tx.put::<tables::NonceChangeHistory>(block_number, nonce_change_key)?;
-                db_tx.put::<tables::ContractInfoChangeSet>(addr, new_change_set)?;
-            }
+        let class_change_key = ContractClassChange { contract_address: addr, class_hash };
+        db_tx.put::<tables::ClassChangeHistory>(block_number, class_change_key)?;
+        db_tx.put::<tables::ContractInfoChangeSet>(addr, new_change_set)?;
+        db_tx.put::<tables::ContractInfo>(addr, value)?;
+    }
 
-            Ok(())
-        })?
+    for (addr, nonce) in states.state_updates.nonce_updates {
+        let value = if let Some(info) = db_tx.get::<tables::ContractInfo>(addr)? {
+            GenericContractInfo { nonce, ..info }
+        } else {
+            GenericContractInfo { nonce, ..Default::default() }
+        };
+
+        let new_change_set =
+            if let Some(mut change_set) = db_tx.get::<tables::ContractInfoChangeSet>(addr)? {
+                change_set.nonce_change_list.insert(block_number);
+                change_set
+            } else {
+                ContractInfoChangeList {
+                    nonce_change_list: BlockList::from([block_number]),
+                    ..Default::default()
+                }
+            };
+
+        let nonce_change_key = ContractNonceChange { contract_address: addr, nonce };
+        db_tx.put::<tables::NonceChangeHistory>(block_number, nonce_change_key)?;
+        db_tx.put::<tables::ContractInfoChangeSet>(addr, new_change_set)?;
+        db_tx.put::<tables::ContractInfo>(addr, value)?;
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
+    use katana_db::error::DatabaseError;
     use katana_db::mdbx::DbEnvKind;
+    use katana_db::tables;
     use katana_primitives::block::{
         Block, BlockHashOrNumber, FinalityStatus, Header, SealedBlockWithStatus,
     };
+    use katana_primitives::class::CompiledClass;
     use katana_primitives::contract::ContractAddress;
+    use katana_primitives::genesis::constant::{
+        DEFAULT_OZ_ACCOUNT_CONTRACT, DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH,
+        DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
+    };
     use katana_primitives::receipt::Receipt;
     use katana_primitives::state::{StateUpdates, StateUpdatesWithDeclaredClasses};
+    use katana_primitives::trace::TxExecInfo;
     use katana_primitives::transaction::{InvokeTx, Tx, TxHash, TxWithHash};
     use starknet::macros::felt;
 
@@ -734,8 +878,12 @@ mod tests {
     use crate::traits::block::{
         BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
     };
+    use crate::traits::contract::{ContractClassProvider, ContractClassWriter};
     use crate::traits::state::StateFactoryProvider;
-    use crate::traits::transaction::TransactionProvider;
+    use crate::traits::state_update::StateUpdateProvider;
+    use crate::traits::transaction::{
+        ReceiptProvider, TransactionProvider, TransactionTraceProvider,
+    };
 
     fn create_dummy_block() -> SealedBlockWithStatus {
         let header = Header { parent_hash: 199u8.into(), number: 0, ..Default::default() };
@@ -796,7 +944,7 @@ mod tests {
     }
 
     fn create_db_provider() -> DbProvider {
-        DbProvider(katana_db::mdbx::test_utils::create_test_db(DbEnvKind::RW))
+        DbProvider::new(katana_db::mdbx::test_utils::create_test_db(DbEnvKind::RW))
     }
 
     #[test]
@@ -805,6 +953,7 @@ mod tests {
 
         let block = create_dummy_block();
         let state_updates = create_dummy_state_updates();
+        let execution = TxExecInfo { actual_fee: 100, ..Default::default() };
 
         // insert block
         BlockWriter::insert_block_with_states_and_receipts(
@@ -812,7 +961,7 @@ mod tests {
             block.clone(),
             state_updates,
             vec![Receipt::Invoke(Default::default())],
-            vec![],
+            vec![execution.clone()],
         )
         .expect("failed to insert block");
 
@@ -830,6 +979,8 @@ mod tests {
 
         let tx_hash: TxHash = 24u8.into();
         let tx = provider.transaction_by_hash(tx_hash).unwrap().unwrap();
+        let tx_exec = provider.transaction_execution(tx_hash).unwrap().unwrap();
+        let block_execs = provider.transactions_executions_by_block(block_id).unwrap().unwrap();
 
         let state_prov = StateFactoryProvider::latest(&provider).unwrap();
 
@@ -853,6 +1004,8 @@ mod tests {
 
         assert_eq!(tx_hash, tx.hash);
         assert_eq!(tx.transaction, Tx::Invoke(InvokeTx::V1(Default::default())));
+        assert_eq!(tx_exec, execution);
+        assert_eq!(block_execs, vec![execution]);
 
         assert_eq!(tx_count, 1);
         assert_eq!(body_indices.tx_offset, 0);
@@ -928,4 +1081,232 @@ mod tests {
         assert_eq!(storage1, felt!("100"));
         assert_eq!(storage2, felt!("200"));
     }
+
+    #[test]
+    fn pruned_state_history() {
+        let provider = create_db_provider();
+
+        let mut blocks = Vec::new();
+        for (number, state_updates) in
+            [create_dummy_state_updates(), create_dummy_state_updates_2(), Default::default()]
+                .into_iter()
+                .enumerate()
+        {
+            let header = Header { number: number as u64, ..Default::default() };
+            let block = Block { header, body: vec![] }.seal();
+            let block = SealedBlockWithStatus { block, status: FinalityStatus::AcceptedOnL2 };
+            blocks.push(block.block.header.hash);
+
+            BlockWriter::insert_block_with_states_and_receipts(
+                &provider,
+                block,
+                state_updates,
+                vec![],
+                vec![],
+            )
+            .expect("failed to insert block");
+        }
+
+        let report = katana_db::prune::prune_state_history(&provider.0, 1).unwrap();
+        assert_eq!((report.nonce_changes, report.class_changes), (2, 2));
+
+        // the state of the pruned block isn't served anymore, the blocks themselves still are
+        assert!(StateFactoryProvider::historical(&provider, BlockHashOrNumber::Num(0))
+            .unwrap()
+            .is_none());
+        assert!(StateFactoryProvider::historical(&provider, blocks[0].into()).unwrap().is_none());
+        assert!(provider.state_update(BlockHashOrNumber::Num(0)).unwrap().is_none());
+        assert!(provider.block(BlockHashOrNumber::Num(0)).unwrap().is_some());
+
+        let state = StateFactoryProvider::historical(&provider, BlockHashOrNumber::Num(1))
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.nonce(ContractAddress::from(felt!("1"))).unwrap(), Some(felt!("5")));
+        assert_eq!(state.class_hash_of_contract(felt!("2").into()).unwrap(), Some(felt!("66")));
+        let state_update = provider.state_update(BlockHashOrNumber::Num(1)).unwrap().unwrap();
+        assert_eq!(
+            state_update.nonce_updates,
+            create_dummy_state_updates_2().state_updates.nonce_updates
+        );
+    }
+
+    #[test]
+    fn iterate_block_ranges() {
+        let provider = create_db_provider();
+
+        BlockWriter::insert_block_with_states_and_receipts(
+            &provider,
+            create_dummy_block(),
+            create_dummy_state_updates(),
+            vec![Receipt::Invoke(Default::default())],
+            vec![],
+        )
+        .expect("failed to insert block");
+
+        let header = Header { parent_hash: 200u8.into(), number: 1, ..Default::default() };
+        let block = Block {
+            header,
+            body: vec![TxWithHash {
+                hash: 25u8.into(),
+                transaction: Tx::Invoke(InvokeTx::V1(Default::default())),
+            }],
+        }
+        .seal();
+        let block = SealedBlockWithStatus { block, status: FinalityStatus::AcceptedOnL2 };
+
+        BlockWriter::insert_block_with_states_and_receipts(
+            &provider,
+            block,
+            create_dummy_state_updates_2(),
+            vec![Receipt::Invoke(Default::default())],
+            vec![],
+        )
+        .expect("failed to insert block");
+
+        // the range goes past the latest block
+        let blocks = provider.blocks_range(0..5).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(blocks, provider.blocks_in_range(0..=1).unwrap());
+
+        let receipts =
+            provider.receipts_range(0..2).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(
+            receipts[1],
+            provider.receipts_by_block(BlockHashOrNumber::Num(1)).unwrap().unwrap()
+        );
+
+        let state_updates =
+            provider.state_updates_range(1..5).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            state_updates,
+            vec![provider.state_update(BlockHashOrNumber::Num(1)).unwrap().unwrap()]
+        );
+
+        assert_eq!(provider.blocks_range(2..5).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn recover_partially_written_block() {
+        let provider = create_db_provider();
+
+        BlockWriter::insert_block_with_states_and_receipts(
+            &provider,
+            create_dummy_block(),
+            create_dummy_state_updates(),
+            vec![Receipt::Invoke(Default::default())],
+            vec![],
+        )
+        .expect("failed to insert block");
+
+        let tx_hash: TxHash = 25u8.into();
+        let header = Header { parent_hash: 200u8.into(), number: 1, ..Default::default() };
+        let block = Block {
+            header,
+            body: vec![TxWithHash {
+                hash: tx_hash,
+                transaction: Tx::Invoke(InvokeTx::V1(Default::default())),
+            }],
+        }
+        .seal();
+        let block = SealedBlockWithStatus { block, status: FinalityStatus::AcceptedOnL2 };
+
+        BlockWriter::insert_block_with_states_and_receipts(
+            &provider,
+            block,
+            create_dummy_state_updates_2(),
+            vec![Receipt::Invoke(Default::default())],
+            vec![],
+        )
+        .expect("failed to insert block");
+
+        // nothing to recover if the latest block was fully written
+        assert_eq!(provider.recover().unwrap(), None);
+
+        // simulate a block that was only partially written
+        provider
+            .0
+            .update(|db_tx| db_tx.delete::<tables::BlockStatusses>(1, None))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(provider.recover().unwrap(), Some(1));
+        assert_eq!(provider.latest_number().unwrap(), 0);
+        assert!(provider.transaction_by_hash(tx_hash).unwrap().is_none());
+
+        // the state is reverted to the one of the previous block
+        let state_prov = StateFactoryProvider::latest(&provider).unwrap();
+
+        let nonce1 = state_prov.nonce(ContractAddress::from(felt!("1"))).unwrap().unwrap();
+        let class_hash1 = state_prov.class_hash_of_contract(felt!("1").into()).unwrap().unwrap();
+        let storage1 =
+            state_prov.storage(ContractAddress::from(felt!("1")), felt!("1")).unwrap().unwrap();
+
+        assert_eq!(nonce1, felt!("1"));
+        assert_eq!(class_hash1, felt!("3"));
+        assert_eq!(storage1, felt!("1"));
+
+        // recovering an already recovered database is a no-op
+        assert_eq!(provider.recover().unwrap(), None);
+    }
+
+    #[test]
+    fn recover_dangling_transactions() {
+        let provider = create_db_provider();
+
+        BlockWriter::insert_block_with_states_and_receipts(
+            &provider,
+            create_dummy_block(),
+            create_dummy_state_updates(),
+            vec![Receipt::Invoke(Default::default())],
+            vec![],
+        )
+        .expect("failed to insert block");
+
+        // simulate a transaction written without the block it belongs to
+        let tx_hash: TxHash = 25u8.into();
+        provider
+            .0
+            .update(|db_tx| -> Result<(), DatabaseError> {
+                db_tx.put::<tables::TxHashes>(1, tx_hash)?;
+                db_tx.put::<tables::TxNumbers>(tx_hash, 1)?;
+                db_tx.put::<tables::TxBlocks>(1, 1)?;
+                db_tx
+                    .put::<tables::Transactions>(1, Tx::Invoke(InvokeTx::V1(Default::default())))?;
+                db_tx.put::<tables::Receipts>(1, Receipt::Invoke(Default::default()))?;
+                Ok(())
+            })
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(provider.recover().unwrap(), None);
+        assert_eq!(provider.latest_number().unwrap(), 0);
+        assert!(provider.transaction_by_hash(tx_hash).unwrap().is_none());
+        assert_eq!(provider.0.tx().unwrap().entries::<tables::Transactions>().unwrap(), 1);
+    }
+
+    #[test]
+    fn compile_sierra_class_lazily() {
+        let provider = create_db_provider();
+
+        let class_hash = DEFAULT_OZ_ACCOUNT_CONTRACT_CLASS_HASH;
+        let sierra = DEFAULT_OZ_ACCOUNT_CONTRACT.clone().flatten().unwrap();
+
+        // only the sierra class is stored, as it is for the classes declared at genesis
+        provider
+            .set_compiled_class_hash_of_class_hash(
+                class_hash,
+                DEFAULT_OZ_ACCOUNT_CONTRACT_COMPILED_CLASS_HASH,
+            )
+            .unwrap();
+        provider.set_sierra_class(class_hash, sierra).unwrap();
+
+        let state_prov = StateFactoryProvider::latest(&provider).unwrap();
+        let class = state_prov.class(class_hash).unwrap();
+        assert!(matches!(class, Some(CompiledClass::Class(_))));
+        drop(state_prov);
+
+        // the compiled class is stored so that it isn't compiled again
+        let stored = provider.0.tx().unwrap().get::<tables::CompiledClasses>(class_hash).unwrap();
+        assert_eq!(stored, class);
+    }
 }
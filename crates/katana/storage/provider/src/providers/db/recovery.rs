@@ -0,0 +1,251 @@
+//! Recovery of blocks that have only been partially written to the database.
+//!
+//! The block status is the last entry written when inserting a block, so a latest block without a
+//! status has been torn, eg. because the node was killed while it was being written. Such a block
+//! is unwound from every table so that the database is left at the state of the previous block.
+//!
+//! Blocks are inserted in a single transaction that is only committed once every table has been
+//! written, so this only happens with databases written by versions that committed partial blocks.
+
+use katana_db::error::DatabaseError;
+use katana_db::mdbx::tx::TxRW;
+use katana_db::models::storage::StorageEntry;
+use katana_db::tables::{self, DupSort, Table};
+use katana_primitives::block::{BlockHash, BlockNumber};
+use katana_primitives::contract::GenericContractInfo;
+use katana_primitives::transaction::TxNumber;
+use katana_primitives::FieldElement;
+use tracing::warn;
+
+use super::state::recent_change_from_block;
+use super::DbProvider;
+use crate::error::ProviderError;
+use crate::ProviderResult;
+
+impl DbProvider {
+    /// Checks whether the latest block has only been partially written to the database and, if so,
+    /// unwinds it. Transactions that don't belong to any block are removed as well. Returns the
+    /// number of the unwound block, if any.
+    ///
+    /// This should be called on startup, before the database is used.
+    pub fn recover(&self) -> ProviderResult<Option<BlockNumber>> {
+        self.0.try_update(|db_tx| -> ProviderResult<Option<BlockNumber>> {
+            let mut unwound = None;
+
+            if let Some((block_number, block_hash)) = db_tx.cursor::<tables::BlockHashes>()?.last()?
+            {
+                if db_tx.get::<tables::BlockStatusses>(block_number)?.is_none() {
+                    warn!(target: "provider::db", %block_number, "Unwinding partially written block.");
+                    unwind_block(db_tx, block_number, block_hash)?;
+                    unwound = Some(block_number);
+                }
+            }
+
+            // transactions that don't belong to any block are left over from a torn block whose
+            // block entries didn't make it to the database
+            let tx_end = next_tx_number(db_tx)?;
+            let removed = truncate_transactions(db_tx, tx_end)?;
+            if removed > 0 {
+                warn!(target: "provider::db", %removed, "Removed dangling transactions.");
+            }
+
+            Ok(unwound)
+        })
+    }
+}
+
+/// Removes every entry of the block `block_number` from the database, and reverts the state
+/// changes it made.
+fn unwind_block(
+    db_tx: &TxRW,
+    block_number: BlockNumber,
+    block_hash: BlockHash,
+) -> ProviderResult<()> {
+    db_tx.delete::<tables::BlockHashes>(block_number, None)?;
+    db_tx.delete::<tables::BlockNumbers>(block_hash, None)?;
+    db_tx.delete::<tables::BlockStatusses>(block_number, None)?;
+    db_tx.delete::<tables::BlockStatistics>(block_number, None)?;
+    db_tx.delete::<tables::Headers>(block_number, None)?;
+    db_tx.delete::<tables::BlockBodyIndices>(block_number, None)?;
+
+    // the transactions of the block are the ones following the last transaction of the previous
+    // block
+    let tx_offset = next_tx_number(db_tx)?;
+    truncate_transactions(db_tx, tx_offset)?;
+
+    // declared classes
+
+    for class_hash in dup_values::<tables::ClassDeclarations>(db_tx, block_number)? {
+        db_tx.delete::<tables::ClassDeclarationBlock>(class_hash, None)?;
+        db_tx.delete::<tables::CompiledClassHashes>(class_hash, None)?;
+        db_tx.delete::<tables::CompiledClasses>(class_hash, None)?;
+        db_tx.delete::<tables::SierraClasses>(class_hash, None)?;
+    }
+
+    db_tx.delete::<tables::ClassDeclarations>(block_number, None)?;
+
+    // storage changes
+
+    for change in dup_values::<tables::StorageChangeHistory>(db_tx, block_number)? {
+        let address = change.key.contract_address;
+        let key = change.key.key;
+
+        let mut list =
+            db_tx.get::<tables::StorageChangeSet>(change.key.clone())?.unwrap_or_default();
+        list.remove(block_number);
+
+        let previous = match recent_change_from_block(block_number, &list) {
+            Some(num) => {
+                let mut cursor = db_tx.cursor::<tables::StorageChangeHistory>()?;
+                let entry = cursor
+                    .seek_by_key_subkey(num, change.key.clone())?
+                    .filter(|entry| entry.key == change.key)
+                    .ok_or(ProviderError::MissingStorageChangeEntry {
+                        block: num,
+                        storage_key: key,
+                        contract_address: address,
+                    })?;
+                Some(entry.value)
+            }
+            None => None,
+        };
+
+        if list.is_empty() {
+            db_tx.delete::<tables::StorageChangeSet>(change.key, None)?;
+        } else {
+            db_tx.put::<tables::StorageChangeSet>(change.key, list)?;
+        }
+
+        let mut cursor = db_tx.cursor::<tables::ContractStorage>()?;
+        match cursor.seek_by_key_subkey(address, key)? {
+            Some(current) if current.key == key => cursor.delete_current()?,
+            _ => {}
+        }
+
+        if let Some(value) = previous {
+            cursor.upsert(address, StorageEntry { key, value })?;
+        }
+    }
+
+    db_tx.delete::<tables::StorageChangeHistory>(block_number, None)?;
+
+    // contract class hash and nonce changes
+
+    let class_changes = dup_values::<tables::ClassChangeHistory>(db_tx, block_number)?;
+    let nonce_changes = dup_values::<tables::NonceChangeHistory>(db_tx, block_number)?;
+
+    let addresses = class_changes
+        .iter()
+        .map(|change| change.contract_address)
+        .chain(nonce_changes.iter().map(|change| change.contract_address));
+
+    for address in addresses {
+        let Some(mut change_set) = db_tx.get::<tables::ContractInfoChangeSet>(address)? else {
+            continue;
+        };
+
+        change_set.class_change_list.remove(block_number);
+        change_set.nonce_change_list.remove(block_number);
+
+        let class_hash = match recent_change_from_block(block_number, &change_set.class_change_list)
+        {
+            Some(num) => {
+                let mut cursor = db_tx.cursor::<tables::ClassChangeHistory>()?;
+                cursor
+                    .seek_by_key_subkey(num, address)?
+                    .filter(|entry| entry.contract_address == address)
+                    .ok_or(ProviderError::MissingContractClassChangeEntry {
+                        block: num,
+                        contract_address: address,
+                    })?
+                    .class_hash
+            }
+            None => FieldElement::ZERO,
+        };
+
+        let nonce = match recent_change_from_block(block_number, &change_set.nonce_change_list) {
+            Some(num) => {
+                let mut cursor = db_tx.cursor::<tables::NonceChangeHistory>()?;
+                cursor
+                    .seek_by_key_subkey(num, address)?
+                    .filter(|entry| entry.contract_address == address)
+                    .ok_or(ProviderError::MissingContractNonceChangeEntry {
+                        block: num,
+                        contract_address: address,
+                    })?
+                    .nonce
+            }
+            None => FieldElement::ZERO,
+        };
+
+        if change_set.class_change_list.is_empty() && change_set.nonce_change_list.is_empty() {
+            // the contract didn't exist before this block
+            db_tx.delete::<tables::ContractInfoChangeSet>(address, None)?;
+            db_tx.delete::<tables::ContractInfo>(address, None)?;
+        } else {
+            db_tx.put::<tables::ContractInfoChangeSet>(address, change_set)?;
+            db_tx
+                .put::<tables::ContractInfo>(address, GenericContractInfo { class_hash, nonce })?;
+        }
+    }
+
+    db_tx.delete::<tables::ClassChangeHistory>(block_number, None)?;
+    db_tx.delete::<tables::NonceChangeHistory>(block_number, None)?;
+
+    Ok(())
+}
+
+/// Returns the number of the transaction following the last transaction of the latest block.
+fn next_tx_number(db_tx: &TxRW) -> Result<TxNumber, DatabaseError> {
+    Ok(db_tx
+        .cursor::<tables::BlockBodyIndices>()?
+        .last()?
+        .map(|(_, indices)| indices.tx_offset + indices.tx_count)
+        .unwrap_or_default())
+}
+
+/// Deletes the transactions whose number is greater than or equal to `from`, along with their
+/// receipts and executions. Returns the number of deleted transactions.
+fn truncate_transactions(db_tx: &TxRW, from: TxNumber) -> Result<usize, DatabaseError> {
+    let mut removed = 0;
+
+    let mut cursor = db_tx.cursor::<tables::TxHashes>()?;
+    for entry in cursor.walk(Some(from))? {
+        let (_, tx_hash) = entry?;
+        db_tx.delete::<tables::TxNumbers>(tx_hash, None)?;
+        removed += 1;
+    }
+
+    truncate::<tables::TxHashes>(db_tx, from)?;
+    truncate::<tables::TxBlocks>(db_tx, from)?;
+    truncate::<tables::Transactions>(db_tx, from)?;
+    truncate::<tables::Receipts>(db_tx, from)?;
+    truncate::<tables::TxExecutions>(db_tx, from)?;
+
+    Ok(removed)
+}
+
+/// Deletes all the entries of the table `Tb` whose key is greater than or equal to `from`.
+fn truncate<Tb>(db_tx: &TxRW, from: u64) -> Result<(), DatabaseError>
+where
+    Tb: Table<Key = u64>,
+{
+    let mut cursor = db_tx.cursor::<Tb>()?;
+    while let Some((key, _)) = cursor.last()? {
+        if key < from {
+            break;
+        }
+        cursor.delete_current()?;
+    }
+    Ok(())
+}
+
+/// Returns all the values of `key` in the DUPSORT table `Tb`.
+fn dup_values<Tb: DupSort>(
+    db_tx: &TxRW,
+    key: <Tb as Table>::Key,
+) -> Result<Vec<<Tb as Table>::Value>, DatabaseError> {
+    let mut cursor = db_tx.cursor::<Tb>()?;
+    let Some(walker) = cursor.walk_dup(Some(key), None)? else { return Ok(Vec::new()) };
+    walker.map(|entry| entry.map(|(_, value)| value)).collect()
+}
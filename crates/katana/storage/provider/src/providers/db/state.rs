@@ -1,4 +1,6 @@
-use katana_db::mdbx::{self};
+use std::sync::Arc;
+
+use katana_db::mdbx::{self, DbEnv};
 use katana_db::models::contract::ContractInfoChangeList;
 use katana_db::models::list::BlockList;
 use katana_db::models::storage::{ContractStorageKey, StorageEntry};
@@ -8,9 +10,11 @@ use katana_primitives::class::{ClassHash, CompiledClass, CompiledClassHash, Flat
 use katana_primitives::contract::{
     ContractAddress, GenericContractInfo, Nonce, StorageKey, StorageValue,
 };
+use tracing::warn;
 
 use super::DbProvider;
 use crate::error::ProviderError;
+use crate::providers::compile_sierra_class;
 use crate::traits::contract::{ContractClassProvider, ContractClassWriter};
 use crate::traits::state::{StateProvider, StateWriter};
 use crate::ProviderResult;
@@ -99,37 +103,41 @@ impl ContractClassWriter for DbProvider {
 }
 
 /// A state provider that provides the latest states from the database.
-pub(super) struct LatestStateProvider(mdbx::tx::TxRO);
+pub(super) struct LatestStateProvider {
+    /// The database environment, used to store the classes compiled by the provider.
+    db: Arc<DbEnv>,
+    /// The database transaction used to read the database.
+    tx: mdbx::tx::TxRO,
+}
 
 impl LatestStateProvider {
-    pub fn new(tx: mdbx::tx::TxRO) -> Self {
-        Self(tx)
+    pub fn new(db: Arc<DbEnv>, tx: mdbx::tx::TxRO) -> Self {
+        Self { db, tx }
     }
 }
 
 impl ContractClassProvider for LatestStateProvider {
     fn class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>> {
-        let class = self.0.get::<tables::CompiledClasses>(hash)?;
-        Ok(class)
+        compiled_class(&self.db, &self.tx, hash)
     }
 
     fn compiled_class_hash_of_class_hash(
         &self,
         hash: ClassHash,
     ) -> ProviderResult<Option<CompiledClassHash>> {
-        let hash = self.0.get::<tables::CompiledClassHashes>(hash)?;
+        let hash = self.tx.get::<tables::CompiledClassHashes>(hash)?;
         Ok(hash)
     }
 
     fn sierra_class(&self, hash: ClassHash) -> ProviderResult<Option<FlattenedSierraClass>> {
-        let class = self.0.get::<tables::SierraClasses>(hash)?;
+        let class = self.tx.get::<tables::SierraClasses>(hash)?;
         Ok(class)
     }
 }
 
 impl StateProvider for LatestStateProvider {
     fn nonce(&self, address: ContractAddress) -> ProviderResult<Option<Nonce>> {
-        let info = self.0.get::<tables::ContractInfo>(address)?;
+        let info = self.tx.get::<tables::ContractInfo>(address)?;
         Ok(info.map(|info| info.nonce))
     }
 
@@ -137,7 +145,7 @@ impl StateProvider for LatestStateProvider {
         &self,
         address: ContractAddress,
     ) -> ProviderResult<Option<ClassHash>> {
-        let info = self.0.get::<tables::ContractInfo>(address)?;
+        let info = self.tx.get::<tables::ContractInfo>(address)?;
         Ok(info.map(|info| info.class_hash))
     }
 
@@ -146,7 +154,7 @@ impl StateProvider for LatestStateProvider {
         address: ContractAddress,
         storage_key: StorageKey,
     ) -> ProviderResult<Option<StorageValue>> {
-        let mut cursor = self.0.cursor::<tables::ContractStorage>()?;
+        let mut cursor = self.tx.cursor::<tables::ContractStorage>()?;
         let entry = cursor.seek_by_key_subkey(address, storage_key)?;
         match entry {
             Some(entry) if entry.key == storage_key => Ok(Some(entry.value)),
@@ -157,6 +165,8 @@ impl StateProvider for LatestStateProvider {
 
 /// A historical state provider.
 pub(super) struct HistoricalStateProvider {
+    /// The database environment, used to store the classes compiled by the provider.
+    db: Arc<DbEnv>,
     /// The database transaction used to read the database.
     tx: mdbx::tx::TxRO,
     /// The block number of the state.
@@ -164,8 +174,8 @@ pub(super) struct HistoricalStateProvider {
 }
 
 impl HistoricalStateProvider {
-    pub fn new(tx: mdbx::tx::TxRO, block_number: u64) -> Self {
-        Self { tx, block_number }
+    pub fn new(db: Arc<DbEnv>, tx: mdbx::tx::TxRO, block_number: u64) -> Self {
+        Self { db, tx, block_number }
     }
 }
 
@@ -188,8 +198,7 @@ impl ContractClassProvider for HistoricalStateProvider {
 
     fn class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>> {
         if self.compiled_class_hash_of_class_hash(hash)?.is_some() {
-            let contract = self.tx.get::<tables::CompiledClasses>(hash)?;
-            Ok(contract)
+            compiled_class(&self.db, &self.tx, hash)
         } else {
             Ok(None)
         }
@@ -204,6 +213,32 @@ impl ContractClassProvider for HistoricalStateProvider {
     }
 }
 
+/// Returns the compiled class of `hash`. If only its Sierra class is stored, it is compiled and the
+/// result is written to the database so that it is only compiled once.
+fn compiled_class(
+    db: &DbEnv,
+    db_tx: &mdbx::tx::TxRO,
+    hash: ClassHash,
+) -> ProviderResult<Option<CompiledClass>> {
+    if let class @ Some(_) = db_tx.get::<tables::CompiledClasses>(hash)? {
+        return Ok(class);
+    }
+
+    let Some(sierra) = db_tx.get::<tables::SierraClasses>(hash)? else { return Ok(None) };
+    let class = compile_sierra_class(hash, &sierra)?;
+
+    // failing to store the class isn't fatal, it will be compiled again the next time
+    let stored = class.clone();
+    if let Err(error) = db
+        .update(move |db_tx| db_tx.put::<tables::CompiledClasses>(hash, stored))
+        .and_then(|res| res)
+    {
+        warn!(target: "provider::db", hash = %format!("{hash:#x}"), %error, "Storing compiled class.");
+    }
+
+    Ok(Some(class))
+}
+
 impl StateProvider for HistoricalStateProvider {
     fn nonce(&self, address: ContractAddress) -> ProviderResult<Option<Nonce>> {
         let change_list = self.tx.get::<tables::ContractInfoChangeSet>(address)?;
@@ -284,7 +319,7 @@ impl StateProvider for HistoricalStateProvider {
 
 /// This is a helper function for getting the block number of the most
 /// recent change that occurred relative to the given block number.
-fn recent_change_from_block(
+pub(super) fn recent_change_from_block(
     block_number: BlockNumber,
     block_list: &BlockList,
 ) -> Option<BlockNumber> {
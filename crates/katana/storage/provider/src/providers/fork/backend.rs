@@ -4,12 +4,13 @@ use std::sync::mpsc::{channel as oneshot, RecvError, Sender as OneshotSender};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use futures::channel::mpsc::{channel, Receiver, SendError, Sender};
 use futures::future::BoxFuture;
 use futures::stream::Stream;
 use futures::{Future, FutureExt};
-use katana_primitives::block::BlockHashOrNumber;
+use katana_primitives::block::{BlockHashOrNumber, BlockNumber};
 use katana_primitives::class::{ClassHash, CompiledClass, CompiledClassHash, FlattenedSierraClass};
 use katana_primitives::contract::{
     ContractAddress, GenericContractInfo, Nonce, StorageKey, StorageValue,
@@ -19,12 +20,13 @@ use katana_primitives::conversion::rpc::{
     legacy_rpc_to_compiled_class,
 };
 use katana_primitives::FieldElement;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use starknet::core::types::{BlockId, ContractClass, StarknetError};
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider, ProviderError as StarknetProviderError};
-use tracing::{error, trace};
+use tracing::{error, trace, warn};
 
+use super::metrics::ForkedBackendMetrics;
 use crate::error::ProviderError;
 use crate::providers::in_memory::cache::CacheStateDb;
 use crate::traits::contract::{ContractClassProvider, ContractInfoProvider};
@@ -35,9 +37,16 @@ type GetNonceResult = Result<Nonce, ForkedBackendError>;
 type GetStorageResult = Result<StorageValue, ForkedBackendError>;
 type GetClassHashAtResult = Result<ClassHash, ForkedBackendError>;
 type GetClassAtResult = Result<starknet::core::types::ContractClass, ForkedBackendError>;
+type GetBlockNumberResult = Result<BlockNumber, ForkedBackendError>;
 
 pub(crate) const LOG_TARGET: &str = "forked_backend";
 
+/// The minimum interval between two requests for the head of the forked network.
+const HEAD_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// The interval between two requests for the head of the forked network backs off up to this
+/// duration while the requests fail.
+const MAX_HEAD_POLL_INTERVAL: Duration = Duration::from_secs(300);
+
 #[derive(Debug, thiserror::Error)]
 pub enum ForkedBackendError {
     #[error("Failed to send request to the forked backend: {0}")]
@@ -54,14 +63,39 @@ pub enum ForkedBackendError {
 
 /// The request types that is processed by [`Backend`].
 ///
-/// Each request is accompanied by the sender-half of a oneshot channel that will be used
-/// to send the [`ProviderResult`] back to the backend client, [`ForkedBackend`], which sent the
-/// requests.
+/// Each request is made at the block of the forked network it is accompanied by, along with the
+/// sender-half of a oneshot channel that will be used to send the [`ProviderResult`] back to the
+/// backend client, [`ForkedBackend`], which sent the requests.
 pub enum BackendRequest {
-    GetClassAt(ClassHash, OneshotSender<GetClassAtResult>),
-    GetNonce(ContractAddress, OneshotSender<GetNonceResult>),
-    GetClassHashAt(ContractAddress, OneshotSender<GetClassHashAtResult>),
-    GetStorage(ContractAddress, StorageKey, OneshotSender<GetStorageResult>),
+    GetClassAt(BlockId, ClassHash, OneshotSender<GetClassAtResult>),
+    GetNonce(BlockId, ContractAddress, OneshotSender<GetNonceResult>),
+    GetClassHashAt(BlockId, ContractAddress, OneshotSender<GetClassHashAtResult>),
+    GetStorage(BlockId, ContractAddress, StorageKey, OneshotSender<GetStorageResult>),
+    /// Get the number of the latest block of the forked network.
+    GetBlockNumber(OneshotSender<GetBlockNumberResult>),
+}
+
+/// When the data cached from the forked network is considered stale and fetched again.
+///
+/// Refreshing moves the block the data is fetched at to the latest block of the forked network, and
+/// the storage, nonces and class hashes are then fetched again. The data is only refreshed once a
+/// block is mined, so that every block is executed against a single block of the forked network,
+/// and the states of the blocks mined before keep reading the data of the block they were executed
+/// against. Classes are never refreshed as they can't change once declared. The data is never
+/// refreshed by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ForkRefreshPolicy {
+    /// Refresh the data once it has been cached for longer than this duration.
+    pub max_age: Option<Duration>,
+    /// Refresh the data once the forked network is more than this number of blocks ahead of the
+    /// block the data is fetched at.
+    pub max_head_lag: Option<u64>,
+}
+
+impl ForkRefreshPolicy {
+    pub fn is_disabled(&self) -> bool {
+        self.max_age.is_none() && self.max_head_lag.is_none()
+    }
 }
 
 type BackendRequestFuture = BoxFuture<'static, ()>;
@@ -78,8 +112,6 @@ pub struct Backend {
     queued_requests: VecDeque<BackendRequest>,
     /// A channel for receiving requests from the [ForkedBackend]'s.
     incoming: Receiver<BackendRequest>,
-    /// Pinned block id for all requests.
-    block: BlockId,
 }
 
 impl Backend {
@@ -89,11 +121,10 @@ impl Backend {
     /// Each request is accompanied by the sender-half of a oneshot channel that will be used
     /// to send the ProviderResult back to the [ForkedBackend] which sent the requests.
     fn handle_requests(&mut self, request: BackendRequest) {
-        let block = self.block;
         let provider = self.provider.clone();
 
         match request {
-            BackendRequest::GetNonce(contract_address, sender) => {
+            BackendRequest::GetNonce(block, contract_address, sender) => {
                 let fut = Box::pin(async move {
                     let res = provider
                         .get_nonce(block, Into::<FieldElement>::into(contract_address))
@@ -106,7 +137,7 @@ impl Backend {
                 self.pending_requests.push(fut);
             }
 
-            BackendRequest::GetStorage(contract_address, key, sender) => {
+            BackendRequest::GetStorage(block, contract_address, key, sender) => {
                 let fut = Box::pin(async move {
                     let res = provider
                         .get_storage_at(Into::<FieldElement>::into(contract_address), key, block)
@@ -119,7 +150,7 @@ impl Backend {
                 self.pending_requests.push(fut);
             }
 
-            BackendRequest::GetClassHashAt(contract_address, sender) => {
+            BackendRequest::GetClassHashAt(block, contract_address, sender) => {
                 let fut = Box::pin(async move {
                     let res = provider
                         .get_class_hash_at(block, Into::<FieldElement>::into(contract_address))
@@ -132,7 +163,7 @@ impl Backend {
                 self.pending_requests.push(fut);
             }
 
-            BackendRequest::GetClassAt(class_hash, sender) => {
+            BackendRequest::GetClassAt(block, class_hash, sender) => {
                 let fut = Box::pin(async move {
                     let res = provider
                         .get_class(block, class_hash)
@@ -144,6 +175,17 @@ impl Backend {
 
                 self.pending_requests.push(fut);
             }
+
+            BackendRequest::GetBlockNumber(sender) => {
+                let fut = Box::pin(async move {
+                    let res =
+                        provider.block_number().await.map_err(ForkedBackendError::StarknetProvider);
+
+                    sender.send(res).expect("failed to send block number result")
+                });
+
+                self.pending_requests.push(fut);
+            }
         }
     }
 }
@@ -194,11 +236,49 @@ impl Future for Backend {
 
 /// A thread safe handler to the [`Backend`]. This is the primary interface for sending
 /// request to the backend thread to fetch data from the forked provider.
-pub struct ForkedBackend(Mutex<Sender<BackendRequest>>);
+pub struct ForkedBackend {
+    sender: Mutex<Sender<BackendRequest>>,
+    /// The block of the forked network the requests are made at.
+    block: BlockId,
+    /// Shared by all the clones of the handler.
+    refresh: Arc<RefreshState>,
+}
 
 impl Clone for ForkedBackend {
     fn clone(&self) -> Self {
-        Self(Mutex::new(self.0.lock().clone()))
+        Self {
+            sender: Mutex::new(self.sender.lock().clone()),
+            block: self.block,
+            refresh: self.refresh.clone(),
+        }
+    }
+}
+
+/// Tracks how stale the data fetched by the [`Backend`] is.
+struct RefreshState {
+    policy: ForkRefreshPolicy,
+    pinned: Mutex<PinnedBlock>,
+    metrics: ForkedBackendMetrics,
+}
+
+struct PinnedBlock {
+    /// The number of the block the data is fetched at, unknown until the first head check if the
+    /// fork is pinned by block hash.
+    number: Option<BlockNumber>,
+    /// When the block was last pinned.
+    pinned_at: Instant,
+    /// When the head of the forked network was last checked.
+    head_checked_at: Option<Instant>,
+    /// The number of consecutive failed head checks.
+    head_check_failures: u32,
+}
+
+impl PinnedBlock {
+    /// Returns the interval the head of the forked network is checked at, which doubles with
+    /// every failed check.
+    fn head_poll_interval(&self) -> Duration {
+        let backoff = 2u32.saturating_pow(self.head_check_failures);
+        HEAD_POLL_INTERVAL.saturating_mul(backoff).min(MAX_HEAD_POLL_INTERVAL)
     }
 }
 
@@ -209,8 +289,9 @@ impl ForkedBackend {
     pub fn new_with_backend_thread(
         provider: Arc<JsonRpcClient<HttpTransport>>,
         block_id: BlockHashOrNumber,
+        refresh_policy: ForkRefreshPolicy,
     ) -> Result<Self, ForkedBackendError> {
-        let (handler, backend) = Self::new(provider, block_id);
+        let (handler, backend) = Self::new(provider, block_id, refresh_policy);
 
         thread::Builder::new().spawn(move || {
             tokio::runtime::Builder::new_current_thread()
@@ -228,22 +309,122 @@ impl ForkedBackend {
     fn new(
         provider: Arc<JsonRpcClient<HttpTransport>>,
         block_id: BlockHashOrNumber,
+        refresh_policy: ForkRefreshPolicy,
     ) -> (Self, Backend) {
-        let block = match block_id {
-            BlockHashOrNumber::Hash(hash) => BlockId::Hash(hash),
-            BlockHashOrNumber::Num(number) => BlockId::Number(number),
+        let (block, number) = match block_id {
+            BlockHashOrNumber::Hash(hash) => (BlockId::Hash(hash), None),
+            BlockHashOrNumber::Num(number) => (BlockId::Number(number), Some(number)),
         };
 
+        let metrics = ForkedBackendMetrics::default();
+        if let Some(number) = number {
+            metrics.pinned_block.set(number as f64);
+        }
+
+        let refresh = Arc::new(RefreshState {
+            policy: refresh_policy,
+            pinned: Mutex::new(PinnedBlock {
+                number,
+                pinned_at: Instant::now(),
+                head_checked_at: None,
+                head_check_failures: 0,
+            }),
+            metrics,
+        });
+
         let (sender, rx) = channel(1);
         let backend = Backend {
             incoming: rx,
             provider,
-            block,
             queued_requests: VecDeque::new(),
             pending_requests: Vec::new(),
         };
 
-        (Self(Mutex::new(sender)), backend)
+        (Self { sender: Mutex::new(sender), block, refresh }, backend)
+    }
+
+    /// Returns a handler making its requests at `block`.
+    fn at(&self, block: BlockId) -> Self {
+        Self { block, ..self.clone() }
+    }
+
+    /// Returns the latest block of the forked network if the data fetched so far is stale
+    /// according to the [`ForkRefreshPolicy`], in which case the data must be fetched again at that
+    /// block.
+    ///
+    /// The head of the forked network is checked at most every [`HEAD_POLL_INTERVAL`], backing off
+    /// up to [`MAX_HEAD_POLL_INTERVAL`] while the checks fail.
+    pub fn refresh_if_stale(&self) -> Result<Option<BlockNumber>, ForkedBackendError> {
+        let policy = self.refresh.policy;
+        if policy.is_disabled() {
+            return Ok(None);
+        }
+
+        // another thread is already checking
+        let Some(mut pinned) = self.refresh.pinned.try_lock() else {
+            return Ok(None);
+        };
+
+        let now = Instant::now();
+        let polled_recently = pinned
+            .head_checked_at
+            .is_some_and(|at| now.duration_since(at) < pinned.head_poll_interval());
+        let expired = policy.max_age.is_some_and(|age| now.duration_since(pinned.pinned_at) >= age);
+
+        if polled_recently || (!expired && policy.max_head_lag.is_none()) {
+            return Ok(None);
+        }
+
+        pinned.head_checked_at = Some(now);
+        let head = match self.do_get_block_number() {
+            Ok(head) => {
+                pinned.head_check_failures = 0;
+                head
+            }
+            Err(e) => {
+                pinned.head_check_failures = pinned.head_check_failures.saturating_add(1);
+                return Err(e);
+            }
+        };
+
+        let lag = pinned.number.map(|number| head.saturating_sub(number));
+        let lagging = match (policy.max_head_lag, lag) {
+            (Some(max_lag), Some(lag)) => lag > max_lag,
+            _ => false,
+        };
+
+        if !expired && !lagging {
+            // without a block number, the lag is counted from the first head check
+            pinned.number.get_or_insert(head);
+            self.refresh.metrics.head_lag_blocks.set(lag.unwrap_or_default() as f64);
+            return Ok(None);
+        }
+
+        pinned.pinned_at = now;
+        // the forked network hasn't moved, the cached data is still valid
+        if lag == Some(0) {
+            return Ok(None);
+        }
+
+        let previous = pinned.number.replace(head);
+
+        self.refresh.metrics.refreshes_total.increment(1);
+        self.refresh.metrics.pinned_block.set(head as f64);
+        self.refresh.metrics.head_lag_blocks.set(0.0);
+
+        trace!(target: LOG_TARGET, from = ?previous, to = head, "Refreshed forked data.");
+
+        Ok(Some(head))
+    }
+
+    pub fn do_get_block_number(&self) -> Result<BlockNumber, ForkedBackendError> {
+        trace!(target: LOG_TARGET, "Requesting latest block number.");
+        let (sender, rx) = oneshot();
+        self.sender
+            .lock()
+            .try_send(BackendRequest::GetBlockNumber(sender))
+            .map_err(|e| e.into_send_error())?;
+        rx.recv()?
     }
 
     pub fn do_get_nonce(
@@ -252,9 +433,9 @@ impl ForkedBackend {
     ) -> Result<Nonce, ForkedBackendError> {
         trace!(target: LOG_TARGET, contract_address = %contract_address, "Requesting nonce for contract address.");
         let (sender, rx) = oneshot();
-        self.0
+        self.sender
             .lock()
-            .try_send(BackendRequest::GetNonce(contract_address, sender))
+            .try_send(BackendRequest::GetNonce(self.block, contract_address, sender))
             .map_err(|e| e.into_send_error())?;
         rx.recv()?
     }
@@ -271,9 +452,9 @@ impl ForkedBackend {
             "Requesting storage."
         );
         let (sender, rx) = oneshot();
-        self.0
+        self.sender
             .lock()
-            .try_send(BackendRequest::GetStorage(contract_address, key, sender))
+            .try_send(BackendRequest::GetStorage(self.block, contract_address, key, sender))
             .map_err(|e| e.into_send_error())?;
         rx.recv()?
     }
@@ -284,9 +465,9 @@ impl ForkedBackend {
     ) -> Result<ClassHash, ForkedBackendError> {
         trace!(target: LOG_TARGET, contract_address = %contract_address, "Requesting class hash at address.");
         let (sender, rx) = oneshot();
-        self.0
+        self.sender
             .lock()
-            .try_send(BackendRequest::GetClassHashAt(contract_address, sender))
+            .try_send(BackendRequest::GetClassHashAt(self.block, contract_address, sender))
             .map_err(|e| e.into_send_error())?;
         rx.recv()?
     }
@@ -301,9 +482,9 @@ impl ForkedBackend {
             "Requesting class."
         );
         let (sender, rx) = oneshot();
-        self.0
+        self.sender
             .lock()
-            .try_send(BackendRequest::GetClassAt(class_hash, sender))
+            .try_send(BackendRequest::GetClassAt(self.block, class_hash, sender))
             .map_err(|e| e.into_send_error())?;
         rx.recv()?
     }
@@ -335,29 +516,91 @@ impl ForkedBackend {
 /// Check in cache first, if not found, then fetch from the forked provider and store it in the
 /// cache to avoid fetching it again. This is shared across multiple instances of
 /// [`ForkedStateDb`](super::state::ForkedStateDb).
+///
+/// The storage, nonces and class hashes are cached per block of the forked network, the provider
+/// either following the block the data is refreshed to or being pinned to one.
 #[derive(Clone)]
-pub struct SharedStateProvider(Arc<CacheStateDb<ForkedBackend>>);
+pub struct SharedStateProvider {
+    /// The data fetched at the latest block the data was refreshed to.
+    latest: Arc<RwLock<Arc<CacheStateDb<ForkedBackend>>>>,
+    /// The data fetched at the block the provider is pinned to, `None` if it follows the
+    /// refreshes.
+    pinned: Option<Arc<CacheStateDb<ForkedBackend>>>,
+}
 
 impl SharedStateProvider {
     pub(crate) fn new_with_backend(backend: ForkedBackend) -> Self {
-        Self(Arc::new(CacheStateDb::new(backend)))
+        Self::new_with_cache(CacheStateDb::new(backend))
+    }
+
+    fn new_with_cache(cache: CacheStateDb<ForkedBackend>) -> Self {
+        Self { latest: Arc::new(RwLock::new(Arc::new(cache))), pinned: None }
+    }
+
+    /// Returns a provider which keeps reading at the current block of the forked network.
+    pub(crate) fn pinned(&self) -> Self {
+        Self { latest: Arc::clone(&self.latest), pinned: Some(self.cache()) }
+    }
+
+    /// Returns the data fetched at the block the provider reads at. It is read once for every
+    /// value, so that the value is fetched and cached at a single block.
+    fn cache(&self) -> Arc<CacheStateDb<ForkedBackend>> {
+        self.pinned.clone().unwrap_or_else(|| Arc::clone(&self.latest.read()))
+    }
+
+    /// Moves the latest data to the latest block of the forked network if it is stale, the storage,
+    /// nonces and class hashes being fetched again from then on. On failure, the cached data keeps
+    /// being served.
+    ///
+    /// Meant to be called between two blocks, so that a block is executed against a single block of
+    /// the forked network.
+    pub(crate) fn refresh_if_stale(&self) {
+        let current = self.cache();
+        match current.refresh_if_stale() {
+            Ok(Some(head)) => {
+                let refreshed = CacheStateDb {
+                    db: current.at(BlockId::Number(head)),
+                    storage: Default::default(),
+                    contract_state: Default::default(),
+                    shared_contract_classes: Arc::clone(&current.shared_contract_classes),
+                    compiled_class_hashes: RwLock::new(
+                        current.compiled_class_hashes.read().clone(),
+                    ),
+                };
+                *self.latest.write() = Arc::new(refreshed);
+
+                let dropped = current.storage.read().values().map(|s| s.len()).sum::<usize>()
+                    + current.contract_state.read().len();
+                current.refresh.metrics.stale_entries_dropped_total.increment(dropped as u64);
+            }
+
+            Ok(None) => {}
+
+            Err(e) => {
+                warn!(target: LOG_TARGET, error = %e, "Refreshing forked data.");
+            }
+        }
     }
 }
 
 impl ContractInfoProvider for SharedStateProvider {
     fn contract(&self, address: ContractAddress) -> ProviderResult<Option<GenericContractInfo>> {
-        let info = self.0.contract_state.read().get(&address).cloned();
+        let info = self.cache().contract_state.read().get(&address).cloned();
         Ok(info)
     }
 }
 
 impl StateProvider for SharedStateProvider {
     fn nonce(&self, address: ContractAddress) -> ProviderResult<Option<Nonce>> {
-        if let nonce @ Some(_) = self.contract(address)?.map(|i| i.nonce) {
+        let cache = self.cache();
+        if let nonce @ Some(_) = cache.contract_state.read().get(&address).map(|i| i.nonce) {
+            cache.refresh.metrics.cache_hits_total.increment(1);
             return Ok(nonce);
         }
 
-        if let Some(nonce) = handle_contract_or_class_not_found_err(self.0.do_get_nonce(address))
+        cache.refresh.metrics.cache_misses_total.increment(1);
+
+        if let Some(nonce) = handle_contract_or_class_not_found_err(cache.do_get_nonce(address))
             .map_err(|e| {
                 error!(
                     target: LOG_TARGET,
@@ -368,7 +611,7 @@ impl StateProvider for SharedStateProvider {
                 e
             })?
         {
-            self.0.contract_state.write().entry(address).or_default().nonce = nonce;
+            cache.contract_state.write().entry(address).or_default().nonce = nonce;
             Ok(Some(nonce))
         } else {
             Ok(None)
@@ -380,14 +623,18 @@ impl StateProvider for SharedStateProvider {
         address: ContractAddress,
         storage_key: StorageKey,
     ) -> ProviderResult<Option<StorageValue>> {
+        let cache = self.cache();
         if let value @ Some(_) =
-            self.0.storage.read().get(&address).and_then(|s| s.get(&storage_key))
+            cache.storage.read().get(&address).and_then(|s| s.get(&storage_key))
         {
+            cache.refresh.metrics.cache_hits_total.increment(1);
             return Ok(value.copied());
         }
 
+        cache.refresh.metrics.cache_misses_total.increment(1);
+
         let value =
-            handle_contract_or_class_not_found_err(self.0.do_get_storage(address, storage_key))
+            handle_contract_or_class_not_found_err(cache.do_get_storage(address, storage_key))
                 .map_err(|e| {
                     error!(
                         target: LOG_TARGET,
@@ -399,7 +646,7 @@ impl StateProvider for SharedStateProvider {
                     e
                 })?;
 
-        self.0
+        cache
             .storage
             .write()
             .entry(address)
@@ -413,12 +660,16 @@ impl StateProvider for SharedStateProvider {
         &self,
         address: ContractAddress,
     ) -> ProviderResult<Option<ClassHash>> {
-        if let hash @ Some(_) = self.contract(address)?.map(|i| i.class_hash) {
+        let cache = self.cache();
+        if let hash @ Some(_) = cache.contract_state.read().get(&address).map(|i| i.class_hash) {
+            cache.refresh.metrics.cache_hits_total.increment(1);
             return Ok(hash);
         }
 
+        cache.refresh.metrics.cache_misses_total.increment(1);
+
         if let Some(hash) = handle_contract_or_class_not_found_err(
-            self.0.do_get_class_hash_at(address),
+            cache.do_get_class_hash_at(address),
         )
         .map_err(|e| {
             error!(
@@ -429,7 +680,7 @@ impl StateProvider for SharedStateProvider {
             );
             e
         })? {
-            self.0.contract_state.write().entry(address).or_default().class_hash = hash;
+            cache.contract_state.write().entry(address).or_default().class_hash = hash;
             Ok(Some(hash))
         } else {
             Ok(None)
@@ -439,11 +690,12 @@ impl StateProvider for SharedStateProvider {
 
 impl ContractClassProvider for SharedStateProvider {
     fn sierra_class(&self, hash: ClassHash) -> ProviderResult<Option<FlattenedSierraClass>> {
-        if let class @ Some(_) = self.0.shared_contract_classes.sierra_classes.read().get(&hash) {
+        let cache = self.cache();
+        if let class @ Some(_) = cache.shared_contract_classes.sierra_classes.read().get(&hash) {
             return Ok(class.cloned());
         }
 
-        let Some(class) = handle_contract_or_class_not_found_err(self.0.do_get_class_at(hash))
+        let Some(class) = handle_contract_or_class_not_found_err(cache.do_get_class_at(hash))
             .map_err(|e| {
                 error!(
                     target: LOG_TARGET,
@@ -460,7 +712,7 @@ impl ContractClassProvider for SharedStateProvider {
         match class {
             starknet::core::types::ContractClass::Legacy(_) => Ok(None),
             starknet::core::types::ContractClass::Sierra(sierra_class) => {
-                self.0
+                cache
                     .shared_contract_classes
                     .sierra_classes
                     .write()
@@ -474,23 +726,24 @@ impl ContractClassProvider for SharedStateProvider {
         &self,
         hash: ClassHash,
     ) -> ProviderResult<Option<CompiledClassHash>> {
-        if let hash @ Some(_) = self.0.compiled_class_hashes.read().get(&hash) {
+        let cache = self.cache();
+        if let hash @ Some(_) = cache.compiled_class_hashes.read().get(&hash) {
             return Ok(hash.cloned());
         }
 
-        if let Some(hash) =
-            handle_contract_or_class_not_found_err(self.0.do_get_compiled_class_hash(hash))
-                .map_err(|e| {
-                    error!(
-                        target: LOG_TARGET,
-                        hash = %format!("{:#x}", hash),
-                        error = %e,
-                        "Fetching compiled class hash."
-                    );
-                    e
-                })?
-        {
-            self.0.compiled_class_hashes.write().insert(hash, hash);
+        if let Some(hash) = handle_contract_or_class_not_found_err(
+            cache.do_get_compiled_class_hash(hash),
+        )
+        .map_err(|e| {
+            error!(
+                target: LOG_TARGET,
+                hash = %format!("{:#x}", hash),
+                error = %e,
+                "Fetching compiled class hash."
+            );
+            e
+        })? {
+            cache.compiled_class_hashes.write().insert(hash, hash);
             Ok(Some(hash))
         } else {
             Ok(None)
@@ -498,11 +751,12 @@ impl ContractClassProvider for SharedStateProvider {
     }
 
     fn class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>> {
-        if let Some(class) = self.0.shared_contract_classes.compiled_classes.read().get(&hash) {
+        let cache = self.cache();
+        if let Some(class) = cache.shared_contract_classes.compiled_classes.read().get(&hash) {
             return Ok(Some(class.clone()));
         }
 
-        let Some(class) = handle_contract_or_class_not_found_err(self.0.do_get_class_at(hash))
+        let Some(class) = handle_contract_or_class_not_found_err(cache.do_get_class_at(hash))
             .map_err(|e| {
                 error!(
                     target: LOG_TARGET,
@@ -547,9 +801,9 @@ impl ContractClassProvider for SharedStateProvider {
             }
         };
 
-        self.0.compiled_class_hashes.write().insert(class_hash, compiled_class_hash);
+        cache.compiled_class_hashes.write().insert(class_hash, compiled_class_hash);
 
-        self.0
+        cache
             .shared_contract_classes
             .compiled_classes
             .write()
@@ -557,7 +811,7 @@ impl ContractClassProvider for SharedStateProvider {
             .or_insert(casm.clone());
 
         if let Some(sierra) = sierra {
-            self.0
+            cache
                 .shared_contract_classes
                 .sierra_classes
                 .write()
@@ -601,11 +855,20 @@ mod tests {
     const ADDR_1_CLASS_HASH: StorageKey = felt!("0x1");
 
     fn create_forked_backend(rpc_url: String, block_num: BlockNumber) -> (ForkedBackend, Backend) {
+        create_forked_backend_with_refresh_policy(rpc_url, block_num, ForkRefreshPolicy::default())
+    }
+
+    fn create_forked_backend_with_refresh_policy(
+        rpc_url: String,
+        block_num: BlockNumber,
+        refresh_policy: ForkRefreshPolicy,
+    ) -> (ForkedBackend, Backend) {
         ForkedBackend::new(
             Arc::new(JsonRpcClient::new(HttpTransport::new(
                 Url::parse(&rpc_url).expect("valid url"),
             ))),
             BlockHashOrNumber::Num(block_num),
+            refresh_policy,
         )
     }
 
@@ -618,6 +881,7 @@ mod tests {
                 Url::parse(&rpc_url).expect("valid url"),
             ))),
             BlockHashOrNumber::Num(block_num),
+            ForkRefreshPolicy::default(),
         )
         .unwrap()
     }
@@ -640,7 +904,7 @@ mod tests {
             GenericContractInfo { nonce: ADDR_1_NONCE, class_hash: ADDR_1_CLASS_HASH },
         );
 
-        let provider = SharedStateProvider(Arc::new(state_db));
+        let provider = SharedStateProvider::new_with_cache(state_db);
 
         assert_eq!(StateProvider::nonce(&provider, ADDR_1).unwrap(), Some(ADDR_1_NONCE));
         assert_eq!(
@@ -656,10 +920,74 @@ mod tests {
     #[test]
     fn fetch_from_fork_will_err_if_backend_thread_not_running() {
         let (backend, _) = create_forked_backend(LOCAL_RPC_URL.into(), 1);
-        let provider = SharedStateProvider(Arc::new(CacheStateDb::new(backend)));
+        let provider = SharedStateProvider::new_with_backend(backend);
         assert!(StateProvider::nonce(&provider, ADDR_1).is_err())
     }
 
+    #[test]
+    fn refresh_is_disabled_by_default() {
+        let (backend, _) = create_forked_backend(LOCAL_RPC_URL.into(), 1);
+        assert_eq!(backend.refresh_if_stale().unwrap(), None);
+    }
+
+    #[test]
+    fn serve_cache_if_refresh_fails() {
+        let policy = ForkRefreshPolicy { max_age: Some(Duration::ZERO), max_head_lag: Some(0) };
+        let (backend, _) =
+            create_forked_backend_with_refresh_policy(LOCAL_RPC_URL.into(), 1, policy);
+        assert!(backend.refresh_if_stale().is_err());
+        // the head isn't requested again until the backoff has elapsed
+        assert_eq!(backend.refresh_if_stale().unwrap(), None);
+        assert_eq!(backend.refresh.pinned.lock().head_check_failures, 1);
+
+        let state_db = CacheStateDb::new(backend);
+        state_db.contract_state.write().insert(
+            ADDR_1,
+            GenericContractInfo { nonce: ADDR_1_NONCE, class_hash: ADDR_1_CLASS_HASH },
+        );
+
+        let provider = SharedStateProvider::new_with_cache(state_db);
+        assert_eq!(StateProvider::nonce(&provider, ADDR_1).unwrap(), Some(ADDR_1_NONCE));
+    }
+
+    #[test]
+    fn head_poll_interval_backs_off() {
+        let mut pinned = PinnedBlock {
+            number: None,
+            pinned_at: Instant::now(),
+            head_checked_at: None,
+            head_check_failures: 0,
+        };
+        assert_eq!(pinned.head_poll_interval(), HEAD_POLL_INTERVAL);
+
+        pinned.head_check_failures = 2;
+        assert_eq!(pinned.head_poll_interval(), HEAD_POLL_INTERVAL * 4);
+
+        pinned.head_check_failures = u32::MAX;
+        assert_eq!(pinned.head_poll_interval(), MAX_HEAD_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn pinned_provider_keeps_the_data_of_its_block() {
+        let (backend, _) = create_forked_backend(LOCAL_RPC_URL.into(), 1);
+        let state_db = CacheStateDb::new(backend.clone());
+        state_db.contract_state.write().insert(
+            ADDR_1,
+            GenericContractInfo { nonce: ADDR_1_NONCE, class_hash: ADDR_1_CLASS_HASH },
+        );
+
+        let provider = SharedStateProvider::new_with_cache(state_db);
+        let pinned = provider.pinned();
+
+        // as after a refresh to the block 2
+        let refreshed = CacheStateDb::new(backend.at(BlockId::Number(2)));
+        *provider.latest.write() = Arc::new(refreshed);
+
+        assert_eq!(StateProvider::nonce(&pinned, ADDR_1).unwrap(), Some(ADDR_1_NONCE));
+        assert_eq!(provider.cache().db.block, BlockId::Number(2));
+        assert!(provider.cache().contract_state.read().is_empty());
+    }
+
     const FORKED_URL: &str =
         "https://starknet-goerli.infura.io/v3/369ce5ac40614952af936e4d64e40474";
 
@@ -673,7 +1001,7 @@ mod tests {
     #[ignore]
     fn fetch_from_fork_if_not_in_cache() {
         let backend = create_forked_backend_with_backend_thread(FORKED_URL.into(), 908622);
-        let provider = SharedStateProvider(Arc::new(CacheStateDb::new(backend)));
+        let provider = SharedStateProvider::new_with_backend(backend);
 
         // fetch from remote
 
@@ -687,16 +1015,16 @@ mod tests {
         // fetch from cache
 
         let class_hash_in_cache =
-            provider.0.contract_state.read().get(&GOERLI_CONTRACT_ADDR).map(|i| i.class_hash);
+            provider.cache().contract_state.read().get(&GOERLI_CONTRACT_ADDR).map(|i| i.class_hash);
         let storage_value_in_cache = provider
-            .0
+            .cache()
             .storage
             .read()
             .get(&GOERLI_CONTRACT_ADDR)
             .and_then(|s| s.get(&GOERLI_CONTRACT_STORAGE_KEY))
             .copied();
         let nonce_in_cache =
-            provider.0.contract_state.read().get(&GOERLI_CONTRACT_ADDR).map(|i| i.nonce);
+            provider.cache().contract_state.read().get(&GOERLI_CONTRACT_ADDR).map(|i| i.nonce);
 
         // check
 
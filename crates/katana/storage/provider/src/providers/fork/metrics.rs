@@ -0,0 +1,20 @@
+use dojo_metrics::Metrics;
+use metrics::{Counter, Gauge};
+
+#[derive(Metrics, Clone)]
+#[metrics(scope = "forked_backend")]
+pub(crate) struct ForkedBackendMetrics {
+    /// The number of times the data cached from the forked network was refreshed.
+    pub(crate) refreshes_total: Counter,
+    /// The number of cached entries the latest state stopped reading from after the refreshes.
+    pub(crate) stale_entries_dropped_total: Counter,
+    /// The block of the forked network the data is fetched at.
+    pub(crate) pinned_block: Gauge,
+    /// The number of blocks the pinned block is behind the head of the forked network, as of the
+    /// last check.
+    pub(crate) head_lag_blocks: Gauge,
+    /// The number of state values found in the cache.
+    pub(crate) cache_hits_total: Counter,
+    /// The number of state values missing from the cache, and fetched from the forked network.
+    pub(crate) cache_misses_total: Counter,
+}
@@ -1,4 +1,5 @@
 pub mod backend;
+mod metrics;
 pub mod state;
 
 use std::ops::{Range, RangeInclusive};
@@ -6,8 +7,8 @@ use std::sync::Arc;
 
 use katana_db::models::block::StoredBlockBodyIndices;
 use katana_primitives::block::{
-    Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithTxHashes, FinalityStatus, Header,
-    SealedBlockWithStatus,
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockStats, BlockWithTxHashes,
+    FinalityStatus, Header, SealedBlockWithStatus,
 };
 use katana_primitives::class::{ClassHash, CompiledClass, CompiledClassHash, FlattenedSierraClass};
 use katana_primitives::contract::ContractAddress;
@@ -20,13 +21,13 @@ use parking_lot::RwLock;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
 
-use self::backend::{ForkedBackend, ForkedBackendError, SharedStateProvider};
+use self::backend::{ForkRefreshPolicy, ForkedBackend, ForkedBackendError, SharedStateProvider};
 use self::state::ForkedStateDb;
 use super::in_memory::cache::{CacheDb, CacheStateDb};
 use super::in_memory::state::HistoricalStates;
 use crate::traits::block::{
-    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
-    HeaderProvider,
+    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatsProvider, BlockStatusProvider,
+    BlockWriter, HeaderProvider,
 };
 use crate::traits::contract::ContractClassWriter;
 use crate::traits::env::BlockEnvProvider;
@@ -50,7 +51,17 @@ impl ForkedProvider {
         provider: Arc<JsonRpcClient<HttpTransport>>,
         block_id: BlockHashOrNumber,
     ) -> Result<Self, ForkedBackendError> {
-        let backend = ForkedBackend::new_with_backend_thread(provider, block_id)?;
+        Self::new_with_refresh_policy(provider, block_id, ForkRefreshPolicy::default())
+    }
+
+    /// Creates a provider whose data fetched from the forked network is refreshed according to
+    /// `refresh_policy`.
+    pub fn new_with_refresh_policy(
+        provider: Arc<JsonRpcClient<HttpTransport>>,
+        block_id: BlockHashOrNumber,
+        refresh_policy: ForkRefreshPolicy,
+    ) -> Result<Self, ForkedBackendError> {
+        let backend = ForkedBackend::new_with_backend_thread(provider, block_id, refresh_policy)?;
         let shared_provider = SharedStateProvider::new_with_backend(backend);
 
         let storage = RwLock::new(CacheDb::new(()));
@@ -122,6 +133,28 @@ impl BlockStatusProvider for ForkedProvider {
     }
 }
 
+impl BlockStatsProvider for ForkedProvider {
+    fn block_stats(&self, id: BlockHashOrNumber) -> ProviderResult<Option<BlockStats>> {
+        let storage = self.storage.read();
+        let num = match id {
+            BlockHashOrNumber::Num(num) => Some(num),
+            BlockHashOrNumber::Hash(hash) => storage.block_numbers.get(&hash).copied(),
+        };
+        Ok(num.and_then(|num| storage.block_stats.get(&num).cloned()))
+    }
+
+    fn block_stats_in_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, BlockStats)>> {
+        let storage = self.storage.read();
+        let end = range.end.min(storage.latest_block_number + 1);
+        Ok((range.start..end)
+            .filter_map(|num| storage.block_stats.get(&num).map(|stats| (num, stats.clone())))
+            .collect())
+    }
+}
+
 impl BlockProvider for ForkedProvider {
     fn block(&self, id: BlockHashOrNumber) -> ProviderResult<Option<Block>> {
         let block_num = match id {
@@ -456,7 +489,7 @@ impl BlockWriter for ForkedProvider {
         block: SealedBlockWithStatus,
         states: StateUpdatesWithDeclaredClasses,
         receipts: Vec<Receipt>,
-        _executions: Vec<TxExecInfo>,
+        executions: Vec<TxExecInfo>,
     ) -> ProviderResult<()> {
         let mut storage = self.storage.write();
 
@@ -465,6 +498,8 @@ impl BlockWriter for ForkedProvider {
 
         let block_header = block.block.header.header;
         let txs = block.block.body;
+        let stats =
+            BlockStats::new(&txs, &receipts, states.state_updates.declared_classes.len() as u64);
 
         // create block body indices
         let tx_count = txs.len() as u64;
@@ -487,9 +522,11 @@ impl BlockWriter for ForkedProvider {
         storage.block_hashes.insert(block_number, block_hash);
         storage.block_headers.insert(block_number, block_header);
         storage.block_statusses.insert(block_number, block.status);
+        storage.block_stats.insert(block_number, stats);
         storage.block_body_indices.insert(block_number, block_body_indices);
 
         storage.transactions.extend(txs);
+        storage.transactions_executions.extend(executions);
         storage.transaction_hashes.extend(txs_id);
         storage.transaction_numbers.extend(txs_num);
         storage.transaction_block.extend(txs_block);
@@ -502,6 +539,9 @@ impl BlockWriter for ForkedProvider {
         let snapshot = self.state.create_snapshot();
         self.historical_states.write().insert(block_number, Box::new(snapshot));
 
+        // the next block is executed against the refreshed data, if it was stale
+        self.state.db.refresh_if_stale();
+
         Ok(())
     }
 }
@@ -17,10 +17,10 @@ pub type ForkedSnapshot = StateSnapshot<SharedStateProvider>;
 
 impl ForkedStateDb {
     pub(crate) fn create_snapshot(&self) -> ForkedSnapshot {
-        ForkedSnapshot {
-            inner: self.create_snapshot_without_classes(),
-            classes: Arc::clone(&self.shared_contract_classes),
-        }
+        let mut inner = self.create_snapshot_without_classes();
+        // the snapshot keeps reading the forked network at the block it was taken at
+        inner.db = self.db.pinned();
+        ForkedSnapshot { inner, classes: Arc::clone(&self.shared_contract_classes) }
     }
 }
 
@@ -84,8 +84,8 @@ impl ContractClassProvider for CacheStateDb<SharedStateProvider> {
     }
 
     fn class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>> {
-        if let class @ Some(_) = self.shared_contract_classes.compiled_classes.read().get(&hash) {
-            return Ok(class.cloned());
+        if let class @ Some(_) = self.shared_contract_classes.compiled_class(hash)? {
+            return Ok(class);
         }
         ContractClassProvider::class(&self.db, hash)
     }
@@ -201,7 +201,7 @@ impl ContractClassProvider for ForkedSnapshot {
 
     fn class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>> {
         if self.inner.compiled_class_hashes.get(&hash).is_some() {
-            Ok(self.classes.compiled_classes.read().get(&hash).cloned())
+            self.classes.compiled_class(hash)
         } else {
             ContractClassProvider::class(&self.inner.db, hash)
         }
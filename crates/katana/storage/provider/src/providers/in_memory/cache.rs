@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use katana_db::models::block::StoredBlockBodyIndices;
-use katana_primitives::block::{BlockHash, BlockNumber, FinalityStatus, Header};
+use katana_primitives::block::{BlockHash, BlockNumber, BlockStats, FinalityStatus, Header};
 use katana_primitives::class::{ClassHash, CompiledClass, CompiledClassHash, FlattenedSierraClass};
 use katana_primitives::contract::{ContractAddress, GenericContractInfo, StorageKey, StorageValue};
 use katana_primitives::receipt::Receipt;
@@ -11,6 +11,9 @@ use katana_primitives::trace::TxExecInfo;
 use katana_primitives::transaction::{Tx, TxHash, TxNumber};
 use parking_lot::RwLock;
 
+use crate::providers::compile_sierra_class;
+use crate::ProviderResult;
+
 type ContractStorageMap = HashMap<ContractAddress, HashMap<StorageKey, StorageValue>>;
 type ContractStateMap = HashMap<ContractAddress, GenericContractInfo>;
 
@@ -24,6 +27,24 @@ pub struct SharedContractClasses {
     pub(crate) compiled_classes: RwLock<CompiledClassesMap>,
 }
 
+impl SharedContractClasses {
+    /// Returns the compiled class of `hash`. If only its Sierra class is stored, it is compiled and
+    /// the result is stored so that it is only compiled once.
+    pub(crate) fn compiled_class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>> {
+        if let class @ Some(_) = self.compiled_classes.read().get(&hash) {
+            return Ok(class.cloned());
+        }
+
+        let Some(sierra) = self.sierra_classes.read().get(&hash).cloned() else {
+            return Ok(None);
+        };
+
+        let class = compile_sierra_class(hash, &sierra)?;
+        self.compiled_classes.write().insert(hash, class.clone());
+        Ok(Some(class))
+    }
+}
+
 pub struct CacheSnapshotWithoutClasses<Db> {
     pub(crate) db: Db,
     pub(crate) storage: ContractStorageMap,
@@ -75,6 +96,7 @@ pub struct CacheDb<Db> {
     pub(crate) block_hashes: HashMap<BlockNumber, BlockHash>,
     pub(crate) block_numbers: HashMap<BlockHash, BlockNumber>,
     pub(crate) block_statusses: HashMap<BlockNumber, FinalityStatus>,
+    pub(crate) block_stats: HashMap<BlockNumber, BlockStats>,
     pub(crate) block_body_indices: HashMap<BlockNumber, StoredBlockBodyIndices>,
     pub(crate) latest_block_hash: BlockHash,
     pub(crate) latest_block_number: BlockNumber,
@@ -110,6 +132,7 @@ impl<Db> CacheDb<Db> {
             block_headers: HashMap::new(),
             block_numbers: HashMap::new(),
             block_statusses: HashMap::new(),
+            block_stats: HashMap::new(),
             transaction_block: HashMap::new(),
             transaction_hashes: HashMap::new(),
             block_body_indices: HashMap::new(),
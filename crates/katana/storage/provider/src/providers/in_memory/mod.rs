@@ -6,8 +6,8 @@ use std::sync::Arc;
 
 use katana_db::models::block::StoredBlockBodyIndices;
 use katana_primitives::block::{
-    Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithTxHashes, FinalityStatus, Header,
-    SealedBlockWithStatus,
+    Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockStats, BlockWithTxHashes,
+    FinalityStatus, Header, SealedBlockWithStatus,
 };
 use katana_primitives::class::{ClassHash, CompiledClass, CompiledClassHash, FlattenedSierraClass};
 use katana_primitives::contract::ContractAddress;
@@ -21,8 +21,8 @@ use parking_lot::RwLock;
 use self::cache::CacheDb;
 use self::state::{HistoricalStates, InMemoryStateDb, LatestStateProvider};
 use crate::traits::block::{
-    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
-    HeaderProvider,
+    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatsProvider, BlockStatusProvider,
+    BlockWriter, HeaderProvider,
 };
 use crate::traits::contract::ContractClassWriter;
 use crate::traits::env::BlockEnvProvider;
@@ -116,6 +116,28 @@ impl BlockStatusProvider for InMemoryProvider {
     }
 }
 
+impl BlockStatsProvider for InMemoryProvider {
+    fn block_stats(&self, id: BlockHashOrNumber) -> ProviderResult<Option<BlockStats>> {
+        let storage = self.storage.read();
+        let num = match id {
+            BlockHashOrNumber::Num(num) => Some(num),
+            BlockHashOrNumber::Hash(hash) => storage.block_numbers.get(&hash).copied(),
+        };
+        Ok(num.and_then(|num| storage.block_stats.get(&num).cloned()))
+    }
+
+    fn block_stats_in_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, BlockStats)>> {
+        let storage = self.storage.read();
+        let end = range.end.min(storage.latest_block_number + 1);
+        Ok((range.start..end)
+            .filter_map(|num| storage.block_stats.get(&num).map(|stats| (num, stats.clone())))
+            .collect())
+    }
+}
+
 impl BlockProvider for InMemoryProvider {
     fn block(&self, id: BlockHashOrNumber) -> ProviderResult<Option<Block>> {
         let block_num = match id {
@@ -459,6 +481,8 @@ impl BlockWriter for InMemoryProvider {
 
         let block_header = block.block.header.header;
         let txs = block.block.body;
+        let stats =
+            BlockStats::new(&txs, &receipts, states.state_updates.declared_classes.len() as u64);
 
         // create block body indices
         let tx_count = txs.len() as u64;
@@ -481,6 +505,7 @@ impl BlockWriter for InMemoryProvider {
         storage.block_hashes.insert(block_number, block_hash);
         storage.block_headers.insert(block_number, block_header);
         storage.block_statusses.insert(block_number, block.status);
+        storage.block_stats.insert(block_number, stats);
         storage.block_body_indices.insert(block_number, block_body_indices);
 
         storage.transactions.extend(txs);
@@ -159,7 +159,7 @@ impl ContractClassProvider for InMemorySnapshot {
 
     fn class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>> {
         if self.compiled_class_hash_of_class_hash(hash)?.is_some() {
-            Ok(self.classes.compiled_classes.read().get(&hash).cloned())
+            self.classes.compiled_class(hash)
         } else {
             Ok(None)
         }
@@ -214,8 +214,7 @@ impl ContractClassProvider for LatestStateProvider {
     }
 
     fn class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>> {
-        let class = self.0.shared_contract_classes.compiled_classes.read().get(&hash).cloned();
-        Ok(class)
+        self.0.shared_contract_classes.compiled_class(hash)
     }
 
     fn compiled_class_hash_of_class_hash(
@@ -3,3 +3,22 @@ pub mod db;
 pub mod fork;
 #[cfg(feature = "in-memory")]
 pub mod in_memory;
+
+use katana_primitives::class::{ClassHash, CompiledClass, FlattenedSierraClass};
+use katana_primitives::conversion::rpc::flattened_sierra_to_compiled_class;
+
+use crate::error::ProviderError;
+use crate::ProviderResult;
+
+/// Compiles the Sierra class of `class_hash` to its compiled class.
+///
+/// Only the Sierra definition of a class is stored when it is declared at genesis, so its compiled
+/// class is compiled the first time it is requested.
+pub(crate) fn compile_sierra_class(
+    class_hash: ClassHash,
+    class: &FlattenedSierraClass,
+) -> ProviderResult<CompiledClass> {
+    let (_, _, compiled) = flattened_sierra_to_compiled_class(class)
+        .map_err(|e| ProviderError::ClassCompilation { class_hash, error: e.to_string() })?;
+    Ok(compiled)
+}
@@ -1,16 +1,16 @@
-use std::ops::RangeInclusive;
+use std::ops::{Range, RangeInclusive};
 
 use katana_db::models::block::StoredBlockBodyIndices;
 use katana_primitives::block::{
-    Block, BlockHash, BlockHashOrNumber, BlockIdOrTag, BlockNumber, BlockTag, BlockWithTxHashes,
-    FinalityStatus, Header, SealedBlockWithStatus,
+    Block, BlockHash, BlockHashOrNumber, BlockIdOrTag, BlockNumber, BlockStats, BlockTag,
+    BlockWithTxHashes, FinalityStatus, Header, SealedBlockWithStatus,
 };
 use katana_primitives::receipt::Receipt;
 use katana_primitives::state::StateUpdatesWithDeclaredClasses;
 use katana_primitives::trace::TxExecInfo;
 
 use super::transaction::{TransactionProvider, TransactionsProviderExt};
-use crate::ProviderResult;
+use crate::{ProviderIter, ProviderResult};
 
 #[auto_impl::auto_impl(&, Box, Arc)]
 pub trait BlockIdReader: BlockNumberProvider + Send + Sync {
@@ -100,6 +100,19 @@ pub trait BlockStatusProvider: Send + Sync {
     fn block_status(&self, id: BlockHashOrNumber) -> ProviderResult<Option<FinalityStatus>>;
 }
 
+#[auto_impl::auto_impl(&, Box, Arc)]
+pub trait BlockStatsProvider: Send + Sync {
+    /// Retrieves the aggregated activity of a block.
+    fn block_stats(&self, id: BlockHashOrNumber) -> ProviderResult<Option<BlockStats>>;
+
+    /// Retrieves the aggregated activity of the blocks in the given range, along with their block
+    /// number, ordered by block number. Blocks of the range that don't exist are omitted.
+    fn block_stats_in_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, BlockStats)>>;
+}
+
 #[auto_impl::auto_impl(&, Box, Arc)]
 pub trait BlockProvider:
     BlockHashProvider
@@ -123,6 +136,13 @@ pub trait BlockProvider:
     /// Returns all available blocks in the given range.
     fn blocks_in_range(&self, range: RangeInclusive<u64>) -> ProviderResult<Vec<Block>>;
 
+    /// Returns an iterator over the blocks in the given range. Unlike [Self::blocks_in_range], the
+    /// blocks are only read as the iterator is advanced. The iterator ends at the first block that
+    /// doesn't exist.
+    fn blocks_range(&self, range: Range<BlockNumber>) -> ProviderResult<ProviderIter<'_, Block>> {
+        Ok(Box::new(range.map_while(move |num| self.block_by_number(num).transpose())))
+    }
+
     /// Returns the block body indices of a block.
     fn block_body_indices(
         &self,
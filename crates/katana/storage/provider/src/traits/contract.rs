@@ -19,6 +19,9 @@ pub trait ContractClassProvider: Send + Sync {
     ) -> ProviderResult<Option<CompiledClassHash>>;
 
     /// Returns the compiled class definition of a contract class given its class hash.
+    ///
+    /// Classes whose compiled class isn't stored are compiled from their Sierra class the first
+    /// time they are requested.
     fn class(&self, hash: ClassHash) -> ProviderResult<Option<CompiledClass>>;
 
     /// Retrieves the Sierra class definition of a contract class given its class hash.
@@ -1,10 +1,22 @@
-use katana_primitives::block::BlockHashOrNumber;
+use std::ops::Range;
+
+use katana_primitives::block::{BlockHashOrNumber, BlockNumber};
 use katana_primitives::state::StateUpdates;
 
-use crate::ProviderResult;
+use crate::{ProviderIter, ProviderResult};
 
 #[auto_impl::auto_impl(&, Box, Arc)]
 pub trait StateUpdateProvider: Send + Sync {
     /// Returns the state update at the given block.
     fn state_update(&self, block_id: BlockHashOrNumber) -> ProviderResult<Option<StateUpdates>>;
+
+    /// Returns an iterator over the state updates of each block in the given range. The state
+    /// updates are only read as the iterator is advanced. The iterator ends at the first block
+    /// that doesn't exist.
+    fn state_updates_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<ProviderIter<'_, StateUpdates>> {
+        Ok(Box::new(range.map_while(move |num| self.state_update(num.into()).transpose())))
+    }
 }
@@ -5,7 +5,7 @@ use katana_primitives::receipt::Receipt;
 use katana_primitives::trace::TxExecInfo;
 use katana_primitives::transaction::{TxHash, TxNumber, TxWithHash};
 
-use crate::ProviderResult;
+use crate::{ProviderIter, ProviderResult};
 
 #[auto_impl::auto_impl(&, Box, Arc)]
 pub trait TransactionProvider: Send + Sync {
@@ -75,4 +75,14 @@ pub trait ReceiptProvider: Send + Sync {
         &self,
         block_id: BlockHashOrNumber,
     ) -> ProviderResult<Option<Vec<Receipt>>>;
+
+    /// Returns an iterator over the receipts of each block in the given range. The receipts are
+    /// only read as the iterator is advanced. The iterator ends at the first block that doesn't
+    /// exist.
+    fn receipts_range(
+        &self,
+        range: Range<BlockNumber>,
+    ) -> ProviderResult<ProviderIter<'_, Vec<Receipt>>> {
+        Ok(Box::new(range.map_while(move |num| self.receipts_by_block(num.into()).transpose())))
+    }
 }
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use katana_primitives::block::{
-    Block, BlockHashOrNumber, BlockNumber, BlockWithTxHashes, FinalityStatus,
+    Block, BlockHashOrNumber, BlockNumber, BlockStats, BlockWithTxHashes, FinalityStatus,
 };
 use katana_primitives::env::BlockEnv;
 use katana_primitives::state::StateUpdatesWithDeclaredClasses;
@@ -9,7 +9,8 @@ use katana_provider::providers::db::DbProvider;
 use katana_provider::providers::fork::ForkedProvider;
 use katana_provider::providers::in_memory::InMemoryProvider;
 use katana_provider::traits::block::{
-    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatusProvider, BlockWriter,
+    BlockHashProvider, BlockNumberProvider, BlockProvider, BlockStatsProvider, BlockStatusProvider,
+    BlockWriter,
 };
 use katana_provider::traits::env::BlockEnvProvider;
 use katana_provider::traits::state::StateRootProvider;
@@ -57,6 +58,7 @@ fn insert_block_test_impl<Db>(provider: BlockchainProvider<Db>, count: u64) -> R
 where
     Db: BlockProvider
         + BlockWriter
+        + BlockStatsProvider
         + ReceiptProvider
         + StateRootProvider
         + TransactionStatusProvider
@@ -85,6 +87,9 @@ where
     assert_eq!(total_txs, actual_transactions_in_range.len() as u64);
     assert_eq!(txs, actual_transactions_in_range);
 
+    let actual_stats_in_range = provider.block_stats_in_range(0..count + 1)?;
+    assert_eq!(actual_stats_in_range.len(), count as usize);
+
     assert_eq!(actual_blocks_in_range.len(), count as usize);
     assert_eq!(
         actual_blocks_in_range,
@@ -121,6 +126,7 @@ where
         };
 
         let actual_block_with_tx_hashes = provider.block_with_tx_hashes(block_id)?;
+        let actual_block_stats = provider.block_stats(block_id)?;
         let actual_block_env = provider.block_env_at(block_id)?;
 
         assert_eq!(actual_status, Some(FinalityStatus::AcceptedOnL2));
@@ -141,6 +147,15 @@ where
             assert_eq!(actual_tx, Some(tx.clone()));
         }
 
+        // all the dummy transactions are sent by the same account and emit no events
+        let expected_block_stats = BlockStats {
+            tx_count: expected_block.body.len() as u64,
+            unique_senders: if expected_block.body.is_empty() { 0 } else { 1 },
+            events_count: 0,
+            declared_classes_count: 0,
+        };
+
+        assert_eq!(actual_block_stats, Some(expected_block_stats));
         assert_eq!(actual_block_env, Some(expected_block_env));
 
         assert_eq!(actual_receipts.as_ref().map(|r| r.len()), Some(expected_block.body.len()));
@@ -1,18 +1,26 @@
+use std::collections::HashSet;
+use std::path::Path;
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
+use dojo_world::contracts::events::{DecodedEvent, OwnerUpdated, WriterUpdated};
 use dojo_world::contracts::model::ModelError;
 use dojo_world::contracts::world::WorldContract;
 use dojo_world::contracts::{cairo_utils, WorldContractReader};
 use dojo_world::migration::TxnConfig;
 use dojo_world::utils::TransactionExt;
-use starknet::accounts::ConnectedAccount;
-use starknet::core::types::{BlockId, BlockTag};
-use starknet::core::utils::parse_cairo_short_string;
+use serde::Deserialize;
+use starknet::accounts::{Call, ConnectedAccount};
+use starknet::core::types::{BlockId, BlockTag, EventFilter};
+use starknet::core::utils::{parse_cairo_short_string, starknet_keccak};
+use starknet::providers::Provider;
 use starknet_crypto::FieldElement;
 
 use crate::utils;
 
+/// The number of events fetched per request when discovering the permissions of a World.
+const EVENTS_CHUNK_SIZE: u64 = 100;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ResourceType {
     Contract(String),
@@ -69,8 +77,17 @@ impl FromStr for OwnerResource {
         let owner = FieldElement::from_hex_be(owner_part)
             .map_err(|_| anyhow::anyhow!("Invalid owner address: {}", owner_part))?;
 
-        let resource_parts = resource_part.split_once(':');
-        let resource = match resource_parts {
+        let resource = resource_part.parse()?;
+
+        Ok(OwnerResource { owner, resource })
+    }
+}
+
+impl FromStr for ResourceType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let resource = match s.split_once(':') {
             Some(("contract", name)) => ResourceType::Contract(name.to_string()),
             Some(("model", name)) => {
                 let model = cairo_utils::str_to_felt(name)
@@ -83,8 +100,277 @@ impl FromStr for OwnerResource {
             ),
         };
 
-        Ok(OwnerResource { owner, resource })
+        Ok(resource)
+    }
+}
+
+/// The permissions of a World described in a permissions file, eg:
+///
+/// ```toml
+/// [[writers]]
+/// model = "Position"
+/// contracts = ["dojo_examples::actions::actions"]
+///
+/// [[owners]]
+/// resource = "model:Position"
+/// owners = ["0x1234"]
+/// ```
+///
+/// The file is read as JSON if its extension is `json`, and as TOML otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PermissionsManifest {
+    #[serde(default)]
+    pub writers: Vec<WriterPermission>,
+    #[serde(default)]
+    pub owners: Vec<OwnerPermission>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WriterPermission {
+    /// The name of the model.
+    pub model: String,
+    /// The names or addresses of the contracts allowed to write to the model.
+    pub contracts: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OwnerPermission {
+    /// The resource, as `model:model_name` or `contract:name_or_address`.
+    pub resource: String,
+    /// The addresses of the owners of the resource.
+    pub owners: Vec<FieldElement>,
+}
+
+impl PermissionsManifest {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        utils::load_toml_or_json(path.as_ref(), "permissions")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WriterGrant {
+    pub model: FieldElement,
+    pub contract: FieldElement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OwnerGrant {
+    pub resource: FieldElement,
+    pub owner: FieldElement,
+}
+
+/// The calls needed to bring the permissions of a World in line with a [`PermissionsManifest`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PermissionsDiff {
+    pub grant_writers: Vec<WriterGrant>,
+    pub revoke_writers: Vec<WriterGrant>,
+    pub grant_owners: Vec<OwnerGrant>,
+    pub revoke_owners: Vec<OwnerGrant>,
+}
+
+impl PermissionsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.grant_writers.is_empty()
+            && self.revoke_writers.is_empty()
+            && self.grant_owners.is_empty()
+            && self.revoke_owners.is_empty()
+    }
+}
+
+/// Computes the grants and revocations needed for the World to have exactly the permissions of
+/// `manifest` on the models and resources it lists. Models and resources absent from the manifest
+/// are left untouched.
+///
+/// The current writers and owners are discovered from the `WriterUpdated` and `OwnerUpdated`
+/// events of the World and checked against its state. The ownerships given by the World without
+/// an event, to its creator and to the accounts registering models or deploying contracts, are
+/// only checked for the owners listed in the manifest, and thus never revoked.
+pub async fn permissions_diff<A>(
+    world: &WorldContract<A>,
+    manifest: &PermissionsManifest,
+) -> Result<PermissionsDiff>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+{
+    let mut writers = vec![];
+    for permission in &manifest.writers {
+        let model = cairo_utils::str_to_felt(&permission.model)
+            .map_err(|_| anyhow::anyhow!("Invalid model name: {}", permission.model))?;
+
+        for contract in &permission.contracts {
+            let contract = utils::get_contract_address(world, contract.clone()).await?;
+            let grant = WriterGrant { model, contract };
+            if !writers.contains(&grant) {
+                writers.push(grant);
+            }
+        }
+    }
+
+    let mut owners = vec![];
+    for permission in &manifest.owners {
+        let resource = match permission.resource.parse()? {
+            ResourceType::Model(name) => name,
+            ResourceType::Contract(name_or_address) => {
+                utils::get_contract_address(world, name_or_address).await?
+            }
+        };
+
+        for owner in &permission.owners {
+            let grant = OwnerGrant { resource, owner: *owner };
+            if !owners.contains(&grant) {
+                owners.push(grant);
+            }
+        }
     }
+
+    let (current_writers, current_owners) =
+        permissions_from_events(world.account.provider(), world.address).await?;
+
+    let world_reader = WorldContractReader::new(world.address, world.account.provider())
+        .with_block(BlockId::Tag(BlockTag::Pending));
+
+    let mut diff = PermissionsDiff::default();
+
+    for grant in &writers {
+        if !world_reader.is_writer(&grant.model, &grant.contract.into()).call().await? {
+            diff.grant_writers.push(*grant);
+        }
+    }
+
+    let models = writers.iter().map(|grant| grant.model).collect::<HashSet<_>>();
+    for grant in current_writers {
+        if models.contains(&grant.model)
+            && !writers.contains(&grant)
+            && world_reader.is_writer(&grant.model, &grant.contract.into()).call().await?
+        {
+            diff.revoke_writers.push(grant);
+        }
+    }
+
+    for grant in &owners {
+        if !world_reader.is_owner(&grant.owner.into(), &grant.resource).call().await? {
+            diff.grant_owners.push(*grant);
+        }
+    }
+
+    let resources = owners.iter().map(|grant| grant.resource).collect::<HashSet<_>>();
+    for grant in current_owners {
+        if resources.contains(&grant.resource)
+            && !owners.contains(&grant)
+            && world_reader.is_owner(&grant.owner.into(), &grant.resource).call().await?
+        {
+            diff.revoke_owners.push(grant);
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Applies the permissions of the file at `path` to the World, sending only the missing grants
+/// and revocations. With `dry_run`, the changes are only printed.
+pub async fn sync<A, P>(
+    world: &WorldContract<A>,
+    path: P,
+    dry_run: bool,
+    txn_config: TxnConfig,
+) -> Result<()>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+    P: AsRef<Path>,
+{
+    let manifest = PermissionsManifest::load(path)?;
+    let diff = permissions_diff(world, &manifest).await?;
+
+    if diff.is_empty() {
+        println!("Permissions are already up to date.");
+        return Ok(());
+    }
+
+    let mut calls = Vec::new();
+
+    for WriterGrant { model, contract } in &diff.grant_writers {
+        println!("+ writer {} {contract:#x}", parse_cairo_short_string(model)?);
+        calls.push(world.grant_writer_getcall(model, &(*contract).into()));
+    }
+    for WriterGrant { model, contract } in &diff.revoke_writers {
+        println!("- writer {} {contract:#x}", parse_cairo_short_string(model)?);
+        calls.push(world.revoke_writer_getcall(model, &(*contract).into()));
+    }
+    for OwnerGrant { resource, owner } in &diff.grant_owners {
+        println!("+ owner {resource:#x} {owner:#x}");
+        calls.push(world.grant_owner_getcall(&(*owner).into(), resource));
+    }
+    for OwnerGrant { resource, owner } in &diff.revoke_owners {
+        println!("- owner {resource:#x} {owner:#x}");
+        calls.push(world.revoke_owner_getcall(&(*owner).into(), resource));
+    }
+
+    if !dry_run {
+        execute_calls(world, calls, txn_config).await?;
+    }
+
+    Ok(())
+}
+
+/// Folds the `WriterUpdated` and `OwnerUpdated` events of the World into its current writers and
+/// owners.
+async fn permissions_from_events<P>(
+    provider: &P,
+    world_address: FieldElement,
+) -> Result<(Vec<WriterGrant>, Vec<OwnerGrant>)>
+where
+    P: Provider + Sync,
+{
+    let writer_updated = starknet_keccak("WriterUpdated".as_bytes());
+    let owner_updated = starknet_keccak("OwnerUpdated".as_bytes());
+
+    let filter = EventFilter {
+        from_block: None,
+        to_block: Some(BlockId::Tag(BlockTag::Pending)),
+        address: Some(world_address),
+        keys: Some(vec![vec![writer_updated, owner_updated]]),
+    };
+
+    let mut writers = vec![];
+    let mut owners = vec![];
+    let mut continuation_token = None;
+
+    loop {
+        let page = provider
+            .get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE)
+            .await
+            .with_context(|| "Failed to fetch the permission events of the World")?;
+
+        for event in page.events {
+            match DecodedEvent::try_from(&event) {
+                Ok(DecodedEvent::WriterUpdated(WriterUpdated { model, system, value })) => {
+                    let grant = WriterGrant { model, contract: system };
+                    writers.retain(|g| *g != grant);
+                    if value {
+                        writers.push(grant);
+                    }
+                }
+                Ok(DecodedEvent::OwnerUpdated(OwnerUpdated { address, resource, value })) => {
+                    let grant = OwnerGrant { owner: address, resource };
+                    owners.retain(|g| *g != grant);
+                    if value {
+                        owners.push(grant);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        continuation_token = page.continuation_token;
+        if continuation_token.is_none() {
+            break;
+        }
+    }
+
+    Ok((writers, owners))
 }
 
 pub async fn grant_writer<A>(
@@ -118,22 +404,7 @@ where
         }
     }
 
-    if !calls.is_empty() {
-        let res = world
-            .account
-            .execute(calls)
-            .send_with_cfg(&txn_config)
-            .await
-            .with_context(|| "Failed to send transaction")?;
-
-        utils::handle_transaction_result(
-            &world.account.provider(),
-            res,
-            txn_config.wait,
-            txn_config.receipt,
-        )
-        .await?;
-    }
+    execute_calls(world, calls, txn_config).await?;
 
     Ok(())
 }
@@ -159,20 +430,7 @@ where
         calls.push(world.grant_owner_getcall(&or.owner.into(), &resource));
     }
 
-    let res = world
-        .account
-        .execute(calls)
-        .send_with_cfg(&txn_config)
-        .await
-        .with_context(|| "Failed to send transaction")?;
-
-    utils::handle_transaction_result(
-        &world.account.provider(),
-        res,
-        txn_config.wait,
-        txn_config.receipt,
-    )
-    .await?;
+    execute_calls(world, calls, txn_config).await?;
 
     Ok(())
 }
@@ -208,22 +466,7 @@ where
         }
     }
 
-    if !calls.is_empty() {
-        let res = world
-            .account
-            .execute(calls)
-            .send_with_cfg(&txn_config)
-            .await
-            .with_context(|| "Failed to send transaction")?;
-
-        utils::handle_transaction_result(
-            &world.account.provider(),
-            res,
-            txn_config.wait,
-            txn_config.receipt,
-        )
-        .await?;
-    }
+    execute_calls(world, calls, txn_config).await?;
 
     Ok(())
 }
@@ -249,20 +492,40 @@ where
         calls.push(world.revoke_owner_getcall(&or.owner.into(), &resource));
     }
 
-    let res = world
-        .account
-        .execute(calls)
-        .send_with_cfg(&txn_config)
-        .await
-        .with_context(|| "Failed to send transaction")?;
-
-    utils::handle_transaction_result(
-        &world.account.provider(),
-        res,
-        txn_config.wait,
-        txn_config.receipt,
-    )
-    .await?;
+    execute_calls(world, calls, txn_config).await?;
+
+    Ok(())
+}
+
+/// Sends the `calls` in as few transactions as allowed by [`utils::batch_calls`]. All the
+/// transactions but the last one are waited for, so that the next one is sent with the right nonce.
+async fn execute_calls<A>(
+    world: &WorldContract<A>,
+    calls: Vec<Call>,
+    txn_config: TxnConfig,
+) -> Result<()>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+{
+    let batches = utils::batch_calls(calls, txn_config.max_calls);
+    let last = batches.len().saturating_sub(1);
+
+    for (i, batch) in batches.into_iter().enumerate() {
+        let res = world
+            .account
+            .execute(batch)
+            .send_with_cfg(&txn_config)
+            .await
+            .with_context(|| "Failed to send transaction")?;
+
+        utils::handle_transaction_result(
+            &world.account.provider(),
+            res,
+            txn_config.wait || i < last,
+            txn_config.receipt,
+        )
+        .await?;
+    }
 
     Ok(())
 }
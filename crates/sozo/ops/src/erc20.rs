@@ -0,0 +1,169 @@
+use anyhow::{anyhow, Context, Result};
+use starknet::core::types::{BlockId, BlockTag, FieldElement, FunctionCall};
+use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
+use starknet::providers::Provider;
+
+use crate::utils::parse_block_id;
+
+/// Prints the balance of `account` in the ERC20 token at `token`.
+pub async fn balance<P: Provider + Sync + Send>(
+    provider: P,
+    token: FieldElement,
+    account: FieldElement,
+    block_id: Option<String>,
+) -> Result<()> {
+    let block_id = block_id_or_pending(block_id)?;
+    let balance = call_u256(&provider, token, "balanceOf", vec![account], block_id).await?;
+    print_amount(&provider, token, balance, block_id).await;
+    Ok(())
+}
+
+/// Prints the amount of the ERC20 token at `token` that `spender` is allowed to spend on behalf of
+/// `owner`.
+pub async fn allowance<P: Provider + Sync + Send>(
+    provider: P,
+    token: FieldElement,
+    owner: FieldElement,
+    spender: FieldElement,
+    block_id: Option<String>,
+) -> Result<()> {
+    let block_id = block_id_or_pending(block_id)?;
+    let allowance =
+        call_u256(&provider, token, "allowance", vec![owner, spender], block_id).await?;
+    print_amount(&provider, token, allowance, block_id).await;
+    Ok(())
+}
+
+fn block_id_or_pending(block_id: Option<String>) -> Result<BlockId> {
+    match block_id {
+        Some(block_id) => parse_block_id(block_id),
+        None => Ok(BlockId::Tag(BlockTag::Pending)),
+    }
+}
+
+async fn call<P: Provider + Sync + Send>(
+    provider: &P,
+    token: FieldElement,
+    entrypoint: &str,
+    calldata: Vec<FieldElement>,
+    block_id: BlockId,
+) -> Result<Vec<FieldElement>> {
+    provider
+        .call(
+            FunctionCall {
+                contract_address: token,
+                entry_point_selector: get_selector_from_name(entrypoint)?,
+                calldata,
+            },
+            block_id,
+        )
+        .await
+        .with_context(|| format!("Failed to call {entrypoint} on token {token:#x}"))
+}
+
+/// Calls an entrypoint returning a `u256`, returned as its `(low, high)` parts.
+async fn call_u256<P: Provider + Sync + Send>(
+    provider: &P,
+    token: FieldElement,
+    entrypoint: &str,
+    calldata: Vec<FieldElement>,
+    block_id: BlockId,
+) -> Result<(u128, u128)> {
+    let output = call(provider, token, entrypoint, calldata, block_id).await?;
+    let [low, high] = output[..] else {
+        return Err(anyhow!("Unexpected output of {entrypoint}, expected a u256: {output:?}"));
+    };
+
+    let low = low.try_into().map_err(|_| anyhow!("Invalid u256 low part {low:#x}"))?;
+    let high = high.try_into().map_err(|_| anyhow!("Invalid u256 high part {high:#x}"))?;
+    Ok((low, high))
+}
+
+/// Prints a raw token amount, along with its value in token units when the token exposes its
+/// decimals and symbol.
+async fn print_amount<P: Provider + Sync + Send>(
+    provider: &P,
+    token: FieldElement,
+    (low, high): (u128, u128),
+    block_id: BlockId,
+) {
+    let amount = u256_to_decimal(low, high);
+
+    let decimals = call(provider, token, "decimals", vec![], block_id)
+        .await
+        .ok()
+        .and_then(|output| output.first().and_then(|decimals| u8::try_from(*decimals).ok()));
+    let symbol = call(provider, token, "symbol", vec![], block_id)
+        .await
+        .ok()
+        .and_then(|output| output.first().and_then(|symbol| parse_cairo_short_string(symbol).ok()));
+
+    match (decimals, symbol) {
+        (Some(decimals), Some(symbol)) => {
+            println!("{amount} ({} {symbol})", format_units(&amount, decimals as usize))
+        }
+        _ => println!("{amount}"),
+    }
+}
+
+/// Formats the `u256` made of `low` and `high` as a decimal integer.
+fn u256_to_decimal(low: u128, high: u128) -> String {
+    if high == 0 {
+        return low.to_string();
+    }
+
+    // big endian 64 bits limbs, divided by 10 until they are all zero
+    let mut limbs = [(high >> 64) as u64, high as u64, (low >> 64) as u64, low as u64];
+    let mut digits = Vec::new();
+
+    while limbs.iter().any(|limb| *limb != 0) {
+        let mut remainder = 0u128;
+        for limb in limbs.iter_mut() {
+            let current = (remainder << 64) | *limb as u128;
+            *limb = (current / 10) as u64;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+
+    digits.reverse();
+    String::from_utf8(digits).expect("digits are ascii")
+}
+
+/// Formats a decimal integer amount in units of `10^decimals`, without trailing zeros.
+pub(crate) fn format_units(amount: &str, decimals: usize) -> String {
+    let padded = format!("{amount:0>width$}", width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn u256_to_decimal_works() {
+        assert_eq!(u256_to_decimal(0, 0), "0");
+        assert_eq!(u256_to_decimal(12345, 0), "12345");
+        assert_eq!(u256_to_decimal(0, 1), "340282366920938463463374607431768211456");
+        assert_eq!(
+            u256_to_decimal(u128::MAX, u128::MAX),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+    }
+
+    #[test]
+    fn format_units_works() {
+        assert_eq!(format_units("1000000000000000000000", 18), "1000");
+        assert_eq!(format_units("1500000000000000000", 18), "1.5");
+        assert_eq!(format_units("1", 18), "0.000000000000000001");
+        assert_eq!(format_units("0", 18), "0");
+        assert_eq!(format_units("42", 0), "42");
+    }
+}
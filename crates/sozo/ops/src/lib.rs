@@ -1,11 +1,13 @@
 pub mod auth;
 pub mod call;
+pub mod erc20;
 pub mod events;
 pub mod execute;
 pub mod migration;
 pub mod model;
 pub mod register;
 pub mod utils;
+pub mod verify;
 
 #[cfg(test)]
 pub mod tests;
@@ -0,0 +1,250 @@
+//! Estimation of the resources used by a whole migration, to budget its deployment before
+//! signing anything.
+//!
+//! Unlike the operations of a [MigrationPlan](super::MigrationPlan), which are estimated one by
+//! one, the transactions of the migration are simulated in a single batch, each on top of the
+//! previous ones, so that the operations depending on a previous operation, like the registration
+//! of a model declared by the same migration, are estimated too. Nothing is sent: simulating
+//! against a local Katana, or a Katana forking the target network, gives the cost of the
+//! migration on that network.
+
+use anyhow::{anyhow, Result};
+use dojo_world::migration::strategy::MigrationStrategy;
+use scarb_ui::Ui;
+use serde::Serialize;
+use starknet::accounts::{Account, ConnectedAccount, SingleOwnerAccount};
+use starknet::core::types::{
+    BlockId, BlockTag, BroadcastedDeclareTransaction, BroadcastedTransaction, ExecuteInvocation,
+    ExecutionResources, FieldElement, MaybePendingBlockWithTxHashes, SimulationFlag,
+    TransactionTrace,
+};
+use starknet::providers::Provider;
+use starknet::signers::Signer;
+
+use super::plan::{format_fee, operation_txs, OperationTx, PlanFormat, PlannedOperation};
+use super::ui::{bold_message, MigrationUi};
+use crate::erc20::format_units;
+
+/// An operation of the migration, with the fee and resources of its simulation.
+#[derive(Debug, Clone, Serialize)]
+pub struct EstimatedOperation {
+    /// The operation, whose `fee_error` is the revert reason of its simulation.
+    #[serde(flatten)]
+    pub operation: PlannedOperation,
+    /// The resources used by the execution of the operation, `None` for declarations.
+    pub resources: Option<ExecutionResources>,
+}
+
+/// The resources used by a migration, simulated against the pending state of the chain.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationEstimate {
+    pub world_address: FieldElement,
+    pub operations: Vec<EstimatedOperation>,
+    pub total_gas: FieldElement,
+    /// The total fee of the migration paid in ETH, in WEI.
+    pub total_fee: FieldElement,
+    /// The total fee of the migration paid in STRK, in FRI, converted from the simulated fee at
+    /// the ratio of the gas prices of the pending block.
+    pub total_fee_strk: FieldElement,
+}
+
+impl MigrationEstimate {
+    /// Simulates the transactions of `strategy`, sent from `account`, in a single batch.
+    pub async fn compute<P, S>(
+        strategy: &MigrationStrategy,
+        account: &SingleOwnerAccount<P, S>,
+    ) -> Result<Self>
+    where
+        P: Provider + Sync + Send + 'static,
+        S: Signer + Sync + Send + 'static,
+    {
+        let world_address = strategy.world_address()?;
+        let provider = account.provider();
+        let block_id = BlockId::Tag(BlockTag::Pending);
+
+        let mut operations = vec![];
+        let mut transactions = vec![];
+        let mut nonce = account.get_nonce().await?;
+
+        // the fee is not charged, so that the simulation doesn't depend on the balance of the
+        // account. the transactions are still validated, for the fee to include the validation.
+        for (operation, tx) in operation_txs(strategy, account).await? {
+            let Some(tx) = tx else {
+                let error = operation.fee_error.unwrap_or_default();
+                return Err(anyhow!("Failed to prepare the {} operation: {error}", operation.name));
+            };
+
+            let transaction = match tx {
+                OperationTx::Declare { class, compiled_class_hash } => {
+                    let request = account
+                        .declare(class, compiled_class_hash)
+                        .nonce(nonce)
+                        .max_fee(FieldElement::ZERO)
+                        .prepared()?
+                        .get_declare_request(true)
+                        .await
+                        .map_err(|e| anyhow!("Failed to sign declaration: {e}"))?;
+                    BroadcastedTransaction::Declare(BroadcastedDeclareTransaction::V2(request))
+                }
+                OperationTx::Invoke(calls) => {
+                    let request = account
+                        .execute(calls)
+                        .nonce(nonce)
+                        .max_fee(FieldElement::ZERO)
+                        .prepared()?
+                        .get_invoke_request(true)
+                        .await
+                        .map_err(|e| anyhow!("Failed to sign invocation: {e}"))?;
+                    BroadcastedTransaction::Invoke(request)
+                }
+            };
+
+            nonce += FieldElement::ONE;
+            operations.push(operation);
+            transactions.push(transaction);
+        }
+
+        let simulated = provider
+            .simulate_transactions(block_id, &transactions, [SimulationFlag::SkipFeeCharge])
+            .await?;
+
+        let mut total_gas = FieldElement::ZERO;
+        let mut total_fee = FieldElement::ZERO;
+
+        let operations = operations
+            .into_iter()
+            .zip(simulated)
+            .map(|(mut operation, simulated)| {
+                let (resources, revert_reason) = match simulated.transaction_trace {
+                    TransactionTrace::Invoke(trace) => match trace.execute_invocation {
+                        ExecuteInvocation::Success(invocation) => {
+                            (Some(invocation.execution_resources), None)
+                        }
+                        ExecuteInvocation::Reverted(reverted) => {
+                            (None, Some(reverted.revert_reason))
+                        }
+                    },
+                    _ => (None, None),
+                };
+
+                total_gas += simulated.fee_estimation.gas_consumed;
+                total_fee += simulated.fee_estimation.overall_fee;
+
+                operation.fee = Some(simulated.fee_estimation);
+                operation.fee_error = revert_reason;
+                EstimatedOperation { operation, resources }
+            })
+            .collect();
+
+        let gas_price = match provider.get_block_with_tx_hashes(block_id).await? {
+            MaybePendingBlockWithTxHashes::Block(block) => block.l1_gas_price,
+            MaybePendingBlockWithTxHashes::PendingBlock(block) => block.l1_gas_price,
+        };
+        let total_fee_strk = fee_in_fri(total_fee, gas_price.price_in_wei, gas_price.price_in_fri)?;
+
+        Ok(Self { world_address, operations, total_gas, total_fee, total_fee_strk })
+    }
+
+    pub fn print(&self, ui: &Ui, format: PlanFormat) -> Result<()> {
+        match format {
+            PlanFormat::Json => println!("{}", serde_json::to_string_pretty(self)?),
+            PlanFormat::Text => self.print_text(ui),
+        }
+
+        Ok(())
+    }
+
+    fn print_text(&self, ui: &Ui) {
+        ui.print(format!(
+            "\n💰 Migration Estimate for World {}\n",
+            bold_message(format!("{:#x}", self.world_address))
+        ));
+
+        for EstimatedOperation { operation, resources } in &self.operations {
+            operation.print_header(ui);
+
+            if let Some(fee) = &operation.fee {
+                ui.print_sub(format!("Fee: {}", format_fee(fee)));
+            }
+            if let Some(resources) = resources {
+                ui.print_sub(format!("Cairo steps: {}", resources.steps));
+                let builtins = format_builtins(resources);
+                if !builtins.is_empty() {
+                    ui.print_sub(format!("Builtins: {builtins}"));
+                }
+            }
+            if let Some(reason) = &operation.fee_error {
+                ui.print_sub("Reverted");
+                ui.print_hidden_sub(reason);
+            }
+        }
+
+        ui.print(format!("\nTotal operations: {}, gas: {}", self.operations.len(), self.total_gas));
+        ui.print(format!(
+            "Estimated fee: {} ETH ({} WEI), or {} STRK when paid in STRK",
+            format_units(&self.total_fee.to_string(), 18),
+            self.total_fee,
+            format_units(&self.total_fee_strk.to_string(), 18),
+        ));
+
+        let reverted = self.operations.iter().filter(|op| op.operation.fee_error.is_some()).count();
+        if reverted > 0 {
+            ui.print(format!(
+                "{reverted} operation(s) reverted in the simulation, the migration would fail."
+            ));
+        }
+    }
+}
+
+/// Converts a fee paid in WEI into the fee paid in FRI for the same resources, at the ratio of the
+/// gas prices in FRI and WEI. The simulated fee is converted, rather than the gas consumed, for the
+/// fee in FRI to include everything the simulated fee does, like the data availability.
+fn fee_in_fri(
+    fee: FieldElement,
+    price_in_wei: FieldElement,
+    price_in_fri: FieldElement,
+) -> Result<FieldElement> {
+    let overflow = || anyhow!("Fee in FRI overflows");
+    let to_u128 = |value: FieldElement| u128::try_from(value).map_err(|_| overflow());
+
+    let (fee, price_in_wei, price_in_fri) =
+        (to_u128(fee)?, to_u128(price_in_wei)?, to_u128(price_in_fri)?);
+    if price_in_wei == 0 {
+        return Ok(FieldElement::ZERO);
+    }
+
+    let fee = fee.checked_mul(price_in_fri).ok_or_else(overflow)?;
+    Ok((fee / price_in_wei).into())
+}
+
+/// Formats the number of applications of the builtins used by an execution.
+fn format_builtins(resources: &ExecutionResources) -> String {
+    [
+        ("range_check", resources.range_check_builtin_applications),
+        ("pedersen", resources.pedersen_builtin_applications),
+        ("poseidon", resources.poseidon_builtin_applications),
+        ("ec_op", resources.ec_op_builtin_applications),
+        ("ecdsa", resources.ecdsa_builtin_applications),
+        ("bitwise", resources.bitwise_builtin_applications),
+        ("keccak", resources.keccak_builtin_applications),
+        ("segment_arena", resources.segment_arena_builtin),
+    ]
+    .into_iter()
+    .filter_map(|(name, count)| count.filter(|count| *count > 0).map(|c| format!("{name}: {c}")))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_fee_to_fri() {
+        let fee = |fee: u64, wei: u64, fri: u64| fee_in_fri(fee.into(), wei.into(), fri.into());
+
+        assert_eq!(fee(1000, 10, 25).unwrap(), FieldElement::from(2500u64));
+        assert_eq!(fee(1000, 0, 25).unwrap(), FieldElement::ZERO);
+        assert!(fee_in_fri(FieldElement::MAX, 1u64.into(), 1u64.into()).is_err());
+    }
+}
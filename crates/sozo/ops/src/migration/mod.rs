@@ -6,6 +6,7 @@ use dojo_lang::compiler::{ABIS_DIR, BASE_DIR, DEPLOYMENTS_DIR, MANIFESTS_DIR, OV
 use dojo_world::contracts::abi::world::ResourceMetadata;
 use dojo_world::contracts::cairo_utils;
 use dojo_world::contracts::world::WorldContract;
+use dojo_world::lockfile::{LockedWorld, Lockfile, LOCKFILE_NAME};
 use dojo_world::manifest::{
     AbiFormat, AbstractManifestError, BaseManifest, DeploymentManifest, DojoContract, DojoModel,
     Manifest, ManifestMethods, OverlayManifest, WorldContract as ManifestWorldContract,
@@ -16,29 +17,31 @@ use dojo_world::migration::contract::ContractMigration;
 use dojo_world::migration::strategy::{generate_salt, prepare_for_migration, MigrationStrategy};
 use dojo_world::migration::world::WorldDiff;
 use dojo_world::migration::{
-    Declarable, DeployOutput, Deployable, MigrationError, RegisterOutput, StateDiff, TxnConfig,
-    Upgradable, UpgradeOutput,
+    Declarable, DeployOutput, Deployable, DojoContractCall, MigrationError, RegisterOutput,
+    StateDiff, TxnConfig, Upgradable, UpgradeOutput,
 };
-use dojo_world::utils::{TransactionExt, TransactionWaiter};
 use futures::future;
 use scarb::core::Workspace;
 use scarb_ui::Ui;
-use starknet::accounts::{Account, ConnectedAccount, SingleOwnerAccount};
-use starknet::core::types::{
-    BlockId, BlockTag, FieldElement, FunctionCall, InvokeTransactionResult, StarknetError,
-};
+use starknet::accounts::{ConnectedAccount, SingleOwnerAccount};
+use starknet::core::types::FieldElement;
 use starknet::core::utils::{
     cairo_short_string_to_felt, get_contract_address, get_selector_from_name,
 };
-use starknet::providers::{Provider, ProviderError};
+use starknet::providers::Provider;
 use tokio::fs;
 
+mod estimate;
+mod plan;
 mod ui;
 
 use starknet::signers::Signer;
 use ui::MigrationUi;
 
+pub use self::estimate::{EstimatedOperation, MigrationEstimate};
+pub use self::plan::{FeeTotal, MigrationPlan, OperationKind, PlanFormat, PlannedOperation};
 use self::ui::{bold_message, italic_message};
+use crate::utils::execute_batched;
 
 #[derive(Debug, Default, Clone)]
 pub struct MigrationOutput {
@@ -60,6 +63,19 @@ pub struct ContractMigrationOutput {
     base_class_hash: FieldElement,
 }
 
+/// How a migration is run without being applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRun {
+    /// Prints the [MigrationPlan], and updates the manifests.
+    Plan(PlanFormat),
+    /// Prints the [MigrationEstimate].
+    Estimate(PlanFormat),
+}
+
+/// Migrates the World of the workspace, or only runs the migration dry if `dry_run` is set.
+///
+/// Returns the address of the World, `None` if it is already up to date and its address was not
+/// given.
 #[allow(clippy::too_many_arguments)]
 pub async fn migrate<P, S>(
     ws: &Workspace<'_>,
@@ -68,9 +84,9 @@ pub async fn migrate<P, S>(
     rpc_url: String,
     account: &SingleOwnerAccount<P, S>,
     name: Option<String>,
-    dry_run: bool,
+    dry_run: Option<DryRun>,
     txn_config: TxnConfig,
-) -> Result<()>
+) -> Result<Option<FieldElement>>
 where
     P: Provider + Sync + Send + 'static,
     S: Signer + Sync + Send + 'static,
@@ -108,64 +124,70 @@ where
 
     if total_diffs == 0 {
         ui.print("\n✨ No changes to be made. Remote World is already up to date!");
-        return Ok(());
+        return Ok(world_address);
     }
 
     let mut strategy = prepare_migration(&target_dir, diff, name.clone(), world_address, &ui)?;
     let world_address = strategy.world_address().expect("world address must exist");
 
-    if dry_run {
-        print_strategy(&ui, account.provider(), &strategy).await;
-
-        update_manifests_and_abis(
-            ws,
-            local_manifest,
-            &profile_dir,
-            &profile_name,
-            &rpc_url,
-            world_address,
-            None,
-            name.as_ref(),
-        )
-        .await?;
-    } else {
-        // Migrate according to the diff.
-        match apply_diff(ws, account, txn_config, &mut strategy).await {
-            Ok(migration_output) => {
-                update_manifests_and_abis(
-                    ws,
-                    local_manifest.clone(),
-                    &profile_dir,
-                    &profile_name,
-                    &rpc_url,
-                    world_address,
-                    Some(migration_output.clone()),
-                    name.as_ref(),
-                )
-                .await?;
-
-                if !ws.config().offline() {
-                    upload_metadata(ws, account, migration_output, txn_config).await?;
+    match dry_run {
+        Some(DryRun::Estimate(format)) => {
+            MigrationEstimate::compute(&strategy, account).await?.print(&ui, format)?;
+        }
+        Some(DryRun::Plan(format)) => {
+            MigrationPlan::compute(&strategy, account).await?.print(&ui, format)?;
+
+            update_manifests_and_abis(
+                ws,
+                local_manifest,
+                &profile_dir,
+                &profile_name,
+                &rpc_url,
+                world_address,
+                None,
+                name.as_ref(),
+            )
+            .await?;
+        }
+        None => {
+            // Migrate according to the diff.
+            match apply_diff(ws, account, txn_config, &mut strategy).await {
+                Ok(migration_output) => {
+                    update_manifests_and_abis(
+                        ws,
+                        local_manifest.clone(),
+                        &profile_dir,
+                        &profile_name,
+                        &rpc_url,
+                        world_address,
+                        Some(migration_output.clone()),
+                        name.as_ref(),
+                    )
+                    .await?;
+
+                    if !ws.config().offline() {
+                        upload_metadata(ws, account, migration_output, txn_config).await?;
+                    }
+                }
+                Err(e) => {
+                    update_manifests_and_abis(
+                        ws,
+                        local_manifest,
+                        &profile_dir,
+                        &profile_name,
+                        &rpc_url,
+                        world_address,
+                        None,
+                        name.as_ref(),
+                    )
+                    .await?;
+                    return Err(e)?;
                 }
-            }
-            Err(e) => {
-                update_manifests_and_abis(
-                    ws,
-                    local_manifest,
-                    &profile_dir,
-                    &profile_name,
-                    &rpc_url,
-                    world_address,
-                    None,
-                    name.as_ref(),
-                )
-                .await?;
-                return Err(e)?;
             }
         }
-    };
+    }
 
-    Ok(())
+    Ok(Some(world_address))
 }
 
 async fn update_manifests_and_abis(
@@ -203,6 +225,7 @@ async fn update_manifests_and_abis(
 
     // when the migration has not been applied because in `plan` mode or because of an error,
     // the `migration_output` is empty.
+    let migrated = migration_output.is_some();
     if let Some(migration_output) = migration_output {
         if migration_output.world_tx_hash.is_some() {
             local_manifest.world.inner.transaction_hash = migration_output.world_tx_hash;
@@ -238,11 +261,50 @@ async fn update_manifests_and_abis(
 
     local_manifest.write_to_path_toml(&deployed_path)?;
     local_manifest.write_to_path_json(&deployed_path_json, profile_dir)?;
+
+    // the lockfile only records the Worlds which are actually deployed
+    if migrated {
+        let lockfile_path = ws.manifest_path().parent().unwrap().join(LOCKFILE_NAME);
+        update_lockfile(lockfile_path.as_std_path(), &local_manifest, profile_name, rpc_url)?;
+    }
+
     ui.print("\n✨ Done.");
 
     Ok(())
 }
 
+/// Records the World of the profile in the lockfile at `lockfile_path`, along with the classes of
+/// the manifest which are now declared.
+pub(crate) fn update_lockfile(
+    lockfile_path: &Path,
+    manifest: &DeploymentManifest,
+    profile_name: &str,
+    rpc_url: &str,
+) -> Result<()> {
+    let mut lockfile = Lockfile::load_from_path(lockfile_path)?;
+
+    let classes = [
+        (manifest.world.name.to_string(), manifest.world.inner.class_hash),
+        (manifest.base.name.to_string(), manifest.base.inner.class_hash),
+    ]
+    .into_iter()
+    .chain(manifest.contracts.iter().map(|c| (c.name.to_string(), c.inner.class_hash)))
+    .chain(manifest.models.iter().map(|m| (m.name.to_string(), m.inner.class_hash)))
+    .collect();
+
+    lockfile.set_world(
+        profile_name,
+        LockedWorld {
+            world_address: manifest.world.inner.address.expect("world address must exist"),
+            deployment_block: manifest.world.inner.block_number,
+            rpc_url: Some(rpc_url.to_string()),
+            classes,
+        },
+    );
+
+    lockfile.write_to_path(lockfile_path)
+}
+
 async fn update_manifest_abis(
     local_manifest: &mut DeploymentManifest,
     profile_dir: &Utf8PathBuf,
@@ -722,16 +784,17 @@ where
         })
         .collect::<Vec<_>>();
 
-    let InvokeTransactionResult { transaction_hash } =
-        world.account.execute(calls).send_with_cfg(&txn_config).await.map_err(|e| {
-            ui.verbose(format!("{e:?}"));
-            anyhow!("Failed to register models to World: {e}")
-        })?;
-
-    TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
+    let transaction_hashes = execute_batched(migrator, calls, txn_config).await.map_err(|e| {
+        ui.verbose(format!("{e:?}"));
+        anyhow!("Failed to register models to World: {e}")
+    })?;
 
-    ui.print(format!("All models are registered at: {transaction_hash:#x}"));
+    ui.print(format!(
+        "All models are registered at: {}",
+        format_transaction_hashes(&transaction_hashes)
+    ));
 
+    let transaction_hash = transaction_hashes.last().copied().unwrap_or_default();
     Ok(RegisterOutput { transaction_hash, declare_output, registered_model_names })
 }
 
@@ -754,6 +817,7 @@ where
     ui.print_header(format!("# Contracts ({})", contracts.len()));
 
     let mut deploy_output = vec![];
+    let mut calls = vec![];
 
     let world_address = strategy.world_address()?;
 
@@ -761,55 +825,51 @@ where
     for contract in contracts {
         let name = &contract.diff.name;
         ui.print(italic_message(name).to_string());
+
+        match contract.declare(migrator, txn_config).await {
+            Ok(output) => {
+                ui.print_hidden_sub(format!("Declare transaction: {:#x}", output.transaction_hash));
+            }
+            Err(MigrationError::ClassAlreadyDeclared) => {}
+            Err(MigrationError::ArtifactError(e)) => {
+                return Err(handle_artifact_error(ui, contract.artifact_path(), e));
+            }
+            Err(e) => {
+                ui.verbose(format!("{e:?}"));
+                return Err(anyhow!("Failed to migrate {name}: {e}"));
+            }
+        }
+
+        // the deployments and upgrades are sent together once all the classes are declared
         match contract
-            .deploy_dojo_contract(
+            .deploy_dojo_contract_call(
                 world_address,
                 contract.diff.local_class_hash,
                 contract.diff.base_class_hash,
                 migrator,
-                txn_config,
             )
             .await
         {
-            Ok(output) => {
-                if let Some(ref declare) = output.declare {
-                    ui.print_hidden_sub(format!(
-                        "Declare transaction: {:#x}",
-                        declare.transaction_hash
-                    ));
-                }
+            Ok(DojoContractCall { call, contract_address, was_upgraded }) => {
+                contract.contract_address = contract_address;
 
-                contract.contract_address = output.contract_address;
-
-                if output.was_upgraded {
-                    ui.print_hidden_sub(format!(
-                        "Invoke transaction to upgrade: {:#x}",
-                        output.transaction_hash
-                    ));
-                    ui.print_sub(format!(
-                        "Contract address [upgraded]: {:#x}",
-                        output.contract_address
-                    ));
+                if was_upgraded {
+                    ui.print_sub(format!("Contract address [upgraded]: {:#x}", contract_address));
                 } else {
-                    ui.print_hidden_sub(format!(
-                        "Deploy transaction: {:#x}",
-                        output.transaction_hash
-                    ));
-                    ui.print_sub(format!("Contract address: {:#x}", output.contract_address));
+                    ui.print_sub(format!("Contract address: {:#x}", contract_address));
                 }
+
+                calls.push(call);
                 deploy_output.push(Some(ContractMigrationOutput {
                     name: name.to_string(),
-                    contract_address: output.contract_address,
-                    base_class_hash: output.base_class_hash,
+                    contract_address,
+                    base_class_hash: contract.diff.base_class_hash,
                 }));
             }
             Err(MigrationError::ContractAlreadyDeployed(contract_address)) => {
                 ui.print_sub(format!("Already deployed: {:#x}", contract_address));
                 deploy_output.push(None);
             }
-            Err(MigrationError::ArtifactError(e)) => {
-                return Err(handle_artifact_error(ui, contract.artifact_path(), e));
-            }
             Err(e) => {
                 ui.verbose(format!("{e:?}"));
                 return Err(anyhow!("Failed to migrate {name}: {e}"));
@@ -817,9 +877,26 @@ where
         }
     }
 
+    if !calls.is_empty() {
+        let transaction_hashes =
+            execute_batched(migrator, calls, txn_config).await.map_err(|e| {
+                ui.verbose(format!("{e:?}"));
+                anyhow!("Failed to deploy contracts: {e}")
+            })?;
+
+        ui.print(format!(
+            "All contracts are deployed at: {}",
+            format_transaction_hashes(&transaction_hashes)
+        ));
+    }
+
     Ok(deploy_output)
 }
 
+fn format_transaction_hashes(transaction_hashes: &[FieldElement]) -> String {
+    transaction_hashes.iter().map(|hash| format!("{hash:#x}")).collect::<Vec<_>>().join(", ")
+}
+
 pub fn handle_artifact_error(ui: &Ui, artifact_path: &Path, error: anyhow::Error) -> anyhow::Error {
     let path = artifact_path.to_string_lossy();
     let name = artifact_path.file_name().unwrap().to_string_lossy();
@@ -831,81 +908,6 @@ pub fn handle_artifact_error(ui: &Ui, artifact_path: &Path, error: anyhow::Error
     )
 }
 
-pub async fn get_contract_operation_name<P>(
-    provider: &P,
-    contract: &ContractMigration,
-    world_address: Option<FieldElement>,
-) -> String
-where
-    P: Provider + Sync + Send + 'static,
-{
-    if let Some(world_address) = world_address {
-        if let Ok(base_class_hash) = provider
-            .call(
-                FunctionCall {
-                    contract_address: world_address,
-                    calldata: vec![],
-                    entry_point_selector: get_selector_from_name("base").unwrap(),
-                },
-                BlockId::Tag(BlockTag::Pending),
-            )
-            .await
-        {
-            let contract_address =
-                get_contract_address(contract.salt, base_class_hash[0], &[], world_address);
-
-            match provider
-                .get_class_hash_at(BlockId::Tag(BlockTag::Pending), contract_address)
-                .await
-            {
-                Ok(current_class_hash) if current_class_hash != contract.diff.local_class_hash => {
-                    return format!("upgrade {}", contract.diff.name);
-                }
-                Err(ProviderError::StarknetError(StarknetError::ContractNotFound)) => {
-                    return format!("deploy {}", contract.diff.name);
-                }
-                Ok(_) => return "already deployed".to_string(),
-                Err(_) => return format!("deploy {}", contract.diff.name),
-            }
-        }
-    }
-    format!("deploy {}", contract.diff.name)
-}
-
-pub async fn print_strategy<P>(ui: &Ui, provider: &P, strategy: &MigrationStrategy)
-where
-    P: Provider + Sync + Send + 'static,
-{
-    ui.print("\n📋 Migration Strategy\n");
-
-    if let Some(base) = &strategy.base {
-        ui.print_header("# Base Contract");
-        ui.print_sub(format!("declare (class hash: {:#x})\n", base.diff.local));
-    }
-
-    if let Some(world) = &strategy.world {
-        ui.print_header("# World");
-        ui.print_sub(format!("declare (class hash: {:#x})\n", world.diff.local_class_hash));
-    }
-
-    if !&strategy.models.is_empty() {
-        ui.print_header(format!("# Models ({})", &strategy.models.len()));
-        for m in &strategy.models {
-            ui.print_sub(format!("register {} (class hash: {:#x})", m.diff.name, m.diff.local));
-        }
-        ui.print(" ");
-    }
-
-    if !&strategy.contracts.is_empty() {
-        ui.print_header(format!("# Contracts ({})", &strategy.contracts.len()));
-        for c in &strategy.contracts {
-            let op_name = get_contract_operation_name(provider, c, strategy.world_address).await;
-            ui.print_sub(format!("{op_name} (class hash: {:#x})", c.diff.local_class_hash));
-        }
-        ui.print(" ");
-    }
-}
-
 /// Upload a metadata as a IPFS artifact and then create a resource to register
 /// into the Dojo resource registry.
 ///
@@ -1038,17 +1040,14 @@ where
 
     let calls = resources.iter().map(|r| world.set_metadata_getcall(r)).collect::<Vec<_>>();
 
-    let InvokeTransactionResult { transaction_hash } =
-        migrator.execute(calls).send_with_cfg(&txn_config).await.map_err(|e| {
-            ui.verbose(format!("{e:?}"));
-            anyhow!("Failed to register metadata into the resource registry: {e}")
-        })?;
-
-    TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
+    let transaction_hashes = execute_batched(migrator, calls, &txn_config).await.map_err(|e| {
+        ui.verbose(format!("{e:?}"));
+        anyhow!("Failed to register metadata into the resource registry: {e}")
+    })?;
 
     ui.print(format!(
-        "> All metadata have been registered in the resource registry (tx hash: \
-         {transaction_hash:#x})"
+        "> All metadata have been registered in the resource registry (tx hash: {})",
+        format_transaction_hashes(&transaction_hashes)
     ));
 
     ui.print("");
@@ -0,0 +1,387 @@
+//! Dry-run of a migration: the operations of a [MigrationStrategy] along with their estimated
+//! fees, computed without sending any transaction.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use dojo_world::contracts::world::WorldContract;
+use dojo_world::migration::strategy::MigrationStrategy;
+use dojo_world::migration::{
+    Declarable, Deployable, DojoContractCall, MigrationError, UDC_ADDRESS,
+};
+use scarb_ui::Ui;
+use serde::Serialize;
+use starknet::accounts::{Account, Call, SingleOwnerAccount};
+use starknet::core::types::{FeeEstimate, FieldElement, FlattenedSierraClass, PriceUnit};
+use starknet::macros::selector;
+use starknet::providers::Provider;
+use starknet::signers::Signer;
+
+use super::ui::{bold_message, italic_message, MigrationUi};
+
+/// The format a [MigrationPlan] is printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Declare,
+    DeployWorld,
+    UpgradeWorld,
+    RegisterModel,
+    DeployContract,
+    UpgradeContract,
+}
+
+/// An operation that would be sent to migrate the World.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedOperation {
+    pub kind: OperationKind,
+    /// The name of the class, model or contract the operation concerns.
+    pub name: String,
+    pub class_hash: FieldElement,
+    /// The address of the deployed or upgraded contract.
+    pub contract_address: Option<FieldElement>,
+    pub fee: Option<FeeEstimate>,
+    /// Why the fee couldn't be estimated, usually because the operation depends on a previous
+    /// operation of the plan.
+    pub fee_error: Option<String>,
+}
+
+impl PlannedOperation {
+    /// Prints what the operation does, and the class and contract it concerns.
+    pub(super) fn print_header(&self, ui: &Ui) {
+        let action = match self.kind {
+            OperationKind::Declare => "declare",
+            OperationKind::DeployWorld | OperationKind::DeployContract => "deploy",
+            OperationKind::UpgradeWorld | OperationKind::UpgradeContract => "upgrade",
+            OperationKind::RegisterModel => "register",
+        };
+
+        ui.print(format!("{action} {}", italic_message(&self.name)));
+        ui.print_sub(format!("Class hash: {:#x}", self.class_hash));
+        if let Some(address) = self.contract_address {
+            ui.print_sub(format!("Contract address: {address:#x}"));
+        }
+    }
+}
+
+/// The operations needed to migrate the World. The fee of each operation is estimated on its own
+/// against the pending state of the chain.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationPlan {
+    pub world_address: FieldElement,
+    pub operations: Vec<PlannedOperation>,
+    /// The sums of the estimated fees in each price unit the fees are paid in, which don't
+    /// include the operations whose fee couldn't be estimated.
+    pub estimated_fees: Vec<FeeTotal>,
+}
+
+/// The sum of the fees paid in a price unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FeeTotal {
+    pub unit: PriceUnit,
+    pub amount: FieldElement,
+}
+
+impl MigrationPlan {
+    /// Computes the operations of `strategy` and estimates their fees using `account`.
+    pub async fn compute<P, S>(
+        strategy: &MigrationStrategy,
+        account: &SingleOwnerAccount<P, S>,
+    ) -> Result<Self>
+    where
+        P: Provider + Sync + Send + 'static,
+        S: Signer + Sync + Send + 'static,
+    {
+        let world_address = strategy.world_address()?;
+        let mut operations = vec![];
+
+        for (mut operation, tx) in operation_txs(strategy, account).await? {
+            // the error preparing the transaction is already recorded
+            let Some(tx) = tx else {
+                operations.push(operation);
+                continue;
+            };
+
+            let estimate = match tx {
+                OperationTx::Declare { class, compiled_class_hash } => {
+                    account.declare(class, compiled_class_hash).estimate_fee().await
+                }
+                OperationTx::Invoke(calls) => account.execute(calls).estimate_fee().await,
+            };
+
+            match estimate {
+                Ok(fee) => operation.fee = Some(fee),
+                Err(e) => operation.fee_error = Some(e.to_string()),
+            }
+            operations.push(operation);
+        }
+
+        let estimated_fees = fee_totals(operations.iter().filter_map(|op| op.fee.as_ref()));
+        Ok(Self { world_address, operations, estimated_fees })
+    }
+
+    pub fn print(&self, ui: &Ui, format: PlanFormat) -> Result<()> {
+        match format {
+            PlanFormat::Json => println!("{}", serde_json::to_string_pretty(self)?),
+            PlanFormat::Text => self.print_text(ui),
+        }
+
+        Ok(())
+    }
+
+    fn print_text(&self, ui: &Ui) {
+        ui.print(format!(
+            "\n📋 Migration Plan for World {}\n",
+            bold_message(format!("{:#x}", self.world_address))
+        ));
+
+        for op in &self.operations {
+            op.print_header(ui);
+
+            match (&op.fee, &op.fee_error) {
+                (Some(fee), _) => ui.print_sub(format!("Estimated fee: {}", format_fee(fee))),
+                (None, Some(error)) => {
+                    ui.print_sub("Estimated fee: unavailable");
+                    ui.print_hidden_sub(error);
+                }
+                (None, None) => {}
+            }
+        }
+
+        let unestimated = self.operations.iter().filter(|op| op.fee.is_none()).count();
+        let estimated_fee = if self.estimated_fees.is_empty() {
+            "0".to_string()
+        } else {
+            let totals = self.estimated_fees.iter();
+            let totals = totals.map(|total| format!("{} {}", total.amount, unit_name(total.unit)));
+            totals.collect::<Vec<_>>().join(" + ")
+        };
+        ui.print(format!(
+            "\nTotal operations: {}, estimated fee: {estimated_fee}",
+            self.operations.len()
+        ));
+        if unestimated > 0 {
+            ui.print(format!(
+                "The fee of {unestimated} operation(s) depending on previous operations couldn't \
+                 be estimated."
+            ));
+        }
+    }
+}
+
+/// The transaction an operation is sent as.
+pub(crate) enum OperationTx {
+    Declare { class: Arc<FlattenedSierraClass>, compiled_class_hash: FieldElement },
+    Invoke(Vec<Call>),
+}
+
+/// Returns the operations of `strategy`, in the order they are applied, along with their
+/// transactions. The fees of the operations are not estimated, and the transaction of an
+/// operation is `None` if it couldn't be prepared, the error being its `fee_error`.
+pub(crate) async fn operation_txs<P, S>(
+    strategy: &MigrationStrategy,
+    account: &SingleOwnerAccount<P, S>,
+) -> Result<Vec<(PlannedOperation, Option<OperationTx>)>>
+where
+    P: Provider + Sync + Send + 'static,
+    S: Signer + Sync + Send + 'static,
+{
+    let world_address = strategy.world_address()?;
+    let mut operations = vec![];
+
+    if let Some(base) = &strategy.base {
+        if let Some(op) = plan_declare(account, base, "base", base.diff.local).await? {
+            operations.push(op);
+        }
+    }
+
+    if let Some(world) = &strategy.world {
+        let class_hash = world.diff.local_class_hash;
+        if let Some(op) = plan_declare(account, world, "world", class_hash).await? {
+            operations.push(op);
+        }
+
+        let (kind, call) = if world.diff.remote_class_hash.is_some() {
+            let call = Call {
+                to: world.contract_address,
+                selector: selector!("upgrade"),
+                calldata: vec![class_hash],
+            };
+            (OperationKind::UpgradeWorld, call)
+        } else {
+            let base_class_hash = strategy.base.as_ref().map(|b| b.diff.local).unwrap_or_default();
+            let call = Call {
+                to: UDC_ADDRESS,
+                selector: selector!("deployContract"),
+                calldata: vec![
+                    class_hash,
+                    world.salt,
+                    FieldElement::ZERO,
+                    FieldElement::ONE,
+                    base_class_hash,
+                ],
+            };
+            (OperationKind::DeployWorld, call)
+        };
+
+        let op = operation(kind, "world", class_hash, Some(world.contract_address));
+        operations.push((op, Some(OperationTx::Invoke(vec![call]))));
+    }
+
+    let world = WorldContract::new(world_address, account);
+
+    for model in &strategy.models {
+        if let Some(op) = plan_declare(account, model, &model.diff.name, model.diff.local).await? {
+            operations.push(op);
+        }
+
+        let call = world.register_model_getcall(&model.diff.local.into());
+        let op = operation(OperationKind::RegisterModel, &model.diff.name, model.diff.local, None);
+        operations.push((op, Some(OperationTx::Invoke(vec![call]))));
+    }
+
+    for contract in &strategy.contracts {
+        let name = &contract.diff.name;
+        let class_hash = contract.diff.local_class_hash;
+
+        let DojoContractCall { call, contract_address, was_upgraded } = match contract
+            .deploy_dojo_contract_call(
+                world_address,
+                class_hash,
+                contract.diff.base_class_hash,
+                account,
+            )
+            .await
+        {
+            Ok(call) => call,
+            Err(MigrationError::ContractAlreadyDeployed(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let kind = if was_upgraded {
+            OperationKind::UpgradeContract
+        } else {
+            OperationKind::DeployContract
+        };
+
+        if let Some(op) = plan_declare(account, contract, name, class_hash).await? {
+            operations.push(op);
+        }
+
+        let op = operation(kind, name, class_hash, Some(contract_address));
+        operations.push((op, Some(OperationTx::Invoke(vec![call]))));
+    }
+
+    Ok(operations)
+}
+
+/// Returns the declaration of the class at `class_hash`, or `None` if it is already declared.
+async fn plan_declare<P, S, D>(
+    account: &SingleOwnerAccount<P, S>,
+    class: &D,
+    name: &str,
+    class_hash: FieldElement,
+) -> Result<Option<(PlannedOperation, Option<OperationTx>)>>
+where
+    P: Provider + Sync + Send + 'static,
+    S: Signer + Sync + Send + 'static,
+    D: Declarable + Sync,
+{
+    let mut op = operation(OperationKind::Declare, name, class_hash, None);
+    let tx = match class.declaration(account).await {
+        Ok((class, compiled_class_hash)) => {
+            Some(OperationTx::Declare { class: Arc::new(class), compiled_class_hash })
+        }
+        Err(MigrationError::ClassAlreadyDeclared) => return Ok(None),
+        Err(MigrationError::ArtifactError(e)) => return Err(e),
+        Err(e) => {
+            op.fee_error = Some(e.to_string());
+            None
+        }
+    };
+
+    Ok(Some((op, tx)))
+}
+
+fn operation(
+    kind: OperationKind,
+    name: &str,
+    class_hash: FieldElement,
+    contract_address: Option<FieldElement>,
+) -> PlannedOperation {
+    PlannedOperation {
+        kind,
+        name: name.to_string(),
+        class_hash,
+        contract_address,
+        fee: None,
+        fee_error: None,
+    }
+}
+
+/// Returns the sums of `fees` in each price unit they are paid in, the units without any fee left
+/// out.
+fn fee_totals<'a>(fees: impl Iterator<Item = &'a FeeEstimate>) -> Vec<FeeTotal> {
+    let mut totals: Vec<FeeTotal> = vec![];
+    for fee in fees {
+        match totals.iter_mut().find(|total| total.unit == fee.unit) {
+            Some(total) => total.amount += fee.overall_fee,
+            None => totals.push(FeeTotal { unit: fee.unit, amount: fee.overall_fee }),
+        }
+    }
+    totals
+}
+
+fn unit_name(unit: PriceUnit) -> &'static str {
+    match unit {
+        PriceUnit::Wei => "WEI",
+        PriceUnit::Fri => "FRI",
+    }
+}
+
+pub(crate) fn format_fee(fee: &FeeEstimate) -> String {
+    let unit = unit_name(fee.unit);
+    format!("{} {unit} ({} gas at {} {unit})", fee.overall_fee, fee.gas_consumed, fee.gas_price)
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    fn fee(overall_fee: u64, unit: PriceUnit) -> FeeEstimate {
+        FeeEstimate {
+            gas_consumed: FieldElement::ONE,
+            gas_price: overall_fee.into(),
+            overall_fee: overall_fee.into(),
+            unit,
+        }
+    }
+
+    #[test]
+    fn fees_are_summed_per_unit() {
+        let fees = [fee(10, PriceUnit::Wei), fee(100, PriceUnit::Fri), fee(5, PriceUnit::Wei)];
+        assert_eq!(
+            fee_totals(fees.iter()),
+            vec![
+                FeeTotal { unit: PriceUnit::Wei, amount: felt!("15") },
+                FeeTotal { unit: PriceUnit::Fri, amount: felt!("100") },
+            ]
+        );
+
+        assert_eq!(fee_totals(std::iter::empty()), vec![]);
+    }
+
+    #[test]
+    fn fees_are_formatted_with_their_unit() {
+        assert_eq!(format_fee(&fee(10, PriceUnit::Wei)), "10 WEI (1 gas at 10 WEI)");
+        assert_eq!(format_fee(&fee(7, PriceUnit::Fri)), "7 FRI (1 gas at 7 FRI)");
+    }
+}
@@ -1,3 +1,5 @@
+use assert_fs::prelude::*;
+use assert_fs::NamedTempFile;
 use dojo_test_utils::sequencer::{
     get_default_test_starknet_config, SequencerConfig, TestSequencer,
 };
@@ -7,7 +9,7 @@ use starknet::accounts::{Account, ConnectedAccount};
 use starknet::core::utils::cairo_short_string_to_felt;
 
 use super::setup;
-use crate::auth::{self, ModelContract, OwnerResource, ResourceType};
+use crate::auth::{self, ModelContract, OwnerResource, PermissionsManifest, ResourceType};
 use crate::execute;
 
 const ACTION_CONTRACT_NAME: &str = "dojo_examples::actions::actions";
@@ -190,6 +192,71 @@ async fn auth_revoke_owner_ok() {
 
     assert!(!execute_spawn(&world_2).await);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn auth_sync_ok() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let world = setup::setup(&sequencer).await.unwrap();
+
+    let account_2 = sequencer.account_at_index(2);
+    let world_2 = WorldContract::new(world.address, account_2);
+
+    assert!(!execute_spawn(&world_2).await);
+
+    let permissions = NamedTempFile::new("permissions.toml").unwrap();
+    permissions
+        .write_str(&format!(
+            r#"
+            [[writers]]
+            model = "Moves"
+            contracts = ["{ACTION_CONTRACT_NAME}"]
+
+            [[writers]]
+            model = "Position"
+            contracts = ["{ACTION_CONTRACT_NAME}"]
+            "#
+        ))
+        .unwrap();
+
+    auth::sync(&world, permissions.path(), false, TxnConfig { wait: true, ..Default::default() })
+        .await
+        .unwrap();
+
+    assert!(execute_spawn(&world_2).await);
+
+    // Already in sync, nothing to send.
+    let manifest = PermissionsManifest::load(permissions.path()).unwrap();
+    assert!(auth::permissions_diff(&world, &manifest).await.unwrap().is_empty());
+
+    // Removing the writers from the file revokes them.
+    permissions
+        .write_str(
+            r#"
+            [[writers]]
+            model = "Moves"
+            contracts = []
+
+            [[writers]]
+            model = "Position"
+            contracts = []
+            "#,
+        )
+        .unwrap();
+
+    let manifest = PermissionsManifest::load(permissions.path()).unwrap();
+    let diff = auth::permissions_diff(&world, &manifest).await.unwrap();
+    assert!(diff.grant_writers.is_empty());
+    assert_eq!(diff.revoke_writers.len(), 2);
+
+    auth::sync(&world, permissions.path(), false, TxnConfig { wait: true, ..Default::default() })
+        .await
+        .unwrap();
+
+    assert!(!execute_spawn(&world_2).await);
+}
+
 /// Executes the `spawn` system on `actions` contract.
 ///
 /// # Returns
@@ -1,5 +1,6 @@
 use std::str;
 
+use assert_fs::TempDir;
 use camino::Utf8Path;
 use dojo_lang::compiler::{BASE_DIR, MANIFESTS_DIR};
 use dojo_test_utils::compiler::build_full_test_config;
@@ -7,6 +8,7 @@ use dojo_test_utils::sequencer::{
     get_default_test_starknet_config, SequencerConfig, StarknetConfig, TestSequencer,
 };
 use dojo_world::contracts::WorldContractReader;
+use dojo_world::lockfile::{LockedWorld, Lockfile, LOCKFILE_NAME};
 use dojo_world::manifest::{BaseManifest, DeploymentManifest, WORLD_CONTRACT_NAME};
 use dojo_world::metadata::{
     dojo_metadata_from_workspace, ArtifactMetadata, DojoMetadata, Uri, WorldMetadata,
@@ -19,7 +21,7 @@ use futures::TryStreamExt;
 use ipfs_api_backend_hyper::{HyperBackend, IpfsApi, IpfsClient, TryFromUri};
 use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
 use starknet::core::chain_id;
-use starknet::core::types::{BlockId, BlockTag};
+use starknet::core::types::{BlockId, BlockTag, PriceUnit};
 use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
 use starknet::macros::felt;
 use starknet::providers::jsonrpc::HttpTransport;
@@ -28,7 +30,10 @@ use starknet::signers::{LocalWallet, SigningKey};
 use starknet_crypto::FieldElement;
 
 use super::setup::{load_config, setup_migration, setup_ws};
-use crate::migration::{execute_strategy, upload_metadata};
+use crate::migration::{
+    execute_strategy, update_lockfile, upload_metadata, FeeTotal, MigrationEstimate, MigrationPlan,
+    OperationKind,
+};
 use crate::utils::get_contract_address_from_reader;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -49,6 +54,56 @@ async fn migrate_with_auto_mine() {
     sequencer.stop().unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn estimate_whole_migration() {
+    let migration = setup_migration().unwrap();
+
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let mut account = sequencer.account();
+    account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+    let estimate = MigrationEstimate::compute(&migration, &account).await.unwrap();
+
+    // the operations depending on the declarations of the migration are simulated too
+    assert!(estimate.operations.iter().any(|op| op.operation.kind == OperationKind::RegisterModel));
+    for op in &estimate.operations {
+        assert!(op.operation.fee.is_some());
+        assert_eq!(op.operation.fee_error, None, "{} reverted", op.operation.name);
+        assert_eq!(op.resources.is_none(), op.operation.kind == OperationKind::Declare);
+    }
+    assert!(estimate.total_fee > FieldElement::ZERO);
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn plan_whole_migration() {
+    let migration = setup_migration().unwrap();
+
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let mut account = sequencer.account();
+    account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+    let plan = MigrationPlan::compute(&migration, &account).await.unwrap();
+
+    assert_eq!(plan.operations[0].kind, OperationKind::Declare);
+    assert!(plan.operations.iter().any(|op| op.kind == OperationKind::RegisterModel));
+    for op in &plan.operations {
+        assert_ne!(op.fee.is_some(), op.fee_error.is_some(), "{}", op.name);
+    }
+
+    // the fees are summed in the unit they are paid in
+    let fees = plan.operations.iter().filter_map(|op| op.fee.as_ref());
+    let wei = fees.fold(FieldElement::ZERO, |total, fee| total + fee.overall_fee);
+    assert_eq!(plan.estimated_fees, vec![FeeTotal { unit: PriceUnit::Wei, amount: wei }]);
+
+    sequencer.stop().unwrap();
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn migrate_with_block_time() {
     let config = load_config();
@@ -97,7 +152,7 @@ async fn migrate_with_small_fee_multiplier_will_fail() {
             &ws,
             &mut migration,
             &account,
-            TxnConfig { fee_estimate_multiplier: Some(0.2f64), wait: false, receipt: false },
+            TxnConfig { fee_estimate_multiplier: Some(0.2f64), ..Default::default() },
         )
         .await
         .is_err()
@@ -119,6 +174,49 @@ fn migrate_world_without_seed_will_fail() {
     assert!(res.is_err_and(|e| e.to_string().contains("Missing seed for World deployment.")))
 }
 
+#[test]
+fn update_lockfile_of_profile() {
+    let base = "../../../examples/spawn-and-move";
+    let manifest = BaseManifest::load_from_path(
+        &Utf8Path::new(base).to_path_buf().join(MANIFESTS_DIR).join("dev").join(BASE_DIR),
+    )
+    .unwrap();
+    let mut manifest = DeploymentManifest::from(manifest);
+    manifest.world.inner.address = Some(felt!("0x1234"));
+    manifest.world.inner.block_number = Some(5);
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(LOCKFILE_NAME);
+    let other = LockedWorld {
+        world_address: felt!("0x42"),
+        deployment_block: None,
+        rpc_url: None,
+        classes: Default::default(),
+    };
+    let mut lockfile = Lockfile::default();
+    lockfile.set_world("other", other.clone());
+    lockfile.write_to_path(&path).unwrap();
+
+    update_lockfile(&path, &manifest, "dev", "http://localhost:5050").unwrap();
+
+    let lockfile = Lockfile::load_from_path(&path).unwrap();
+    let world = lockfile.world("dev").unwrap();
+    assert_eq!(world.world_address, felt!("0x1234"));
+    assert_eq!(world.deployment_block, Some(5));
+    assert_eq!(world.rpc_url.as_deref(), Some("http://localhost:5050"));
+    assert_eq!(world.classes[manifest.world.name.as_str()], manifest.world.inner.class_hash);
+    assert_eq!(world.classes.len(), 2 + manifest.contracts.len() + manifest.models.len());
+    // the Worlds of the other profiles are kept
+    assert_eq!(lockfile.world("other"), Some(&other));
+
+    // a new migration of the profile replaces its World
+    manifest.world.inner.address = Some(felt!("0x5678"));
+    update_lockfile(&path, &manifest, "dev", "http://localhost:5050").unwrap();
+    let lockfile = Lockfile::load_from_path(&path).unwrap();
+    assert_eq!(lockfile.world("dev").unwrap().world_address, felt!("0x5678"));
+    assert_eq!(lockfile.profiles.len(), 2);
+}
+
 #[tokio::test]
 async fn migration_from_remote() {
     let config = load_config();
@@ -3,3 +3,4 @@ pub mod call;
 pub mod migration;
 pub mod setup;
 pub mod utils;
+pub mod verify;
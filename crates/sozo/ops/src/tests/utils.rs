@@ -1,12 +1,15 @@
+use assert_fs::prelude::*;
+use assert_fs::NamedTempFile;
 use dojo_test_utils::sequencer::{
     get_default_test_starknet_config, SequencerConfig, TestSequencer,
 };
 use dojo_world::contracts::world::WorldContract;
 use dojo_world::contracts::WorldContractReader;
-use starknet::accounts::ConnectedAccount;
+use starknet::accounts::{Call, ConnectedAccount};
 use starknet::core::types::{BlockId, BlockTag, FieldElement};
 
 use super::setup;
+use crate::auth::PermissionsManifest;
 use crate::utils;
 
 const ACTION_CONTRACT_NAME: &str = "dojo_examples::actions::actions";
@@ -103,3 +106,80 @@ fn parse_block_id_latest() {
 fn parse_block_id_number() {
     assert!(utils::parse_block_id("42".to_string()).unwrap() == BlockId::Number(42));
 }
+
+fn call(calldata_len: usize) -> Call {
+    Call {
+        to: FieldElement::ONE,
+        selector: FieldElement::TWO,
+        calldata: vec![FieldElement::ZERO; calldata_len],
+    }
+}
+
+#[test]
+fn batch_calls_by_count() {
+    let calls = vec![call(1); 5];
+
+    let batches = utils::batch_calls(calls.clone(), Some(2));
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 1]);
+
+    let batches = utils::batch_calls(calls, None);
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![5]);
+
+    assert!(utils::batch_calls(vec![], Some(2)).is_empty());
+}
+
+#[test]
+fn batch_calls_by_calldata_len() {
+    let half = utils::MAX_CALLDATA_LEN / 2;
+    let calls = vec![call(half), call(half), call(utils::MAX_CALLDATA_LEN), call(1)];
+
+    let batches = utils::batch_calls(calls, None);
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![1, 1, 1, 1]);
+
+    let batches = utils::batch_calls(vec![call(10); 400], None);
+    assert!(batches.len() > 1);
+    assert!(batches
+        .iter()
+        .all(|batch| batch.iter().map(|c| 4 + c.calldata.len()).sum::<usize>()
+            < utils::MAX_CALLDATA_LEN));
+}
+
+#[test]
+fn load_toml_or_json_file() {
+    let toml = NamedTempFile::new("permissions.toml").unwrap();
+    toml.write_str(
+        r#"
+        [[writers]]
+        model = "Position"
+        contracts = ["dojo_examples::actions::actions"]
+
+        [[owners]]
+        resource = "model:Position"
+        owners = ["0x1234"]
+        "#,
+    )
+    .unwrap();
+
+    let json = NamedTempFile::new("permissions.json").unwrap();
+    json.write_str(
+        r#"{
+            "writers": [{ "model": "Position", "contracts": ["dojo_examples::actions::actions"] }],
+            "owners": [{ "resource": "model:Position", "owners": ["0x1234"] }]
+        }"#,
+    )
+    .unwrap();
+
+    let manifest: PermissionsManifest = utils::load_toml_or_json(toml.path(), "test").unwrap();
+    assert_eq!(manifest, utils::load_toml_or_json(json.path(), "test").unwrap());
+    assert_eq!(manifest.writers[0].model, "Position");
+    assert_eq!(manifest.owners[0].owners, vec![FieldElement::from(0x1234_u32)]);
+
+    let unknown = NamedTempFile::new("permissions.toml").unwrap();
+    unknown.write_str("[[readers]]\nmodel = \"Position\"").unwrap();
+    let error = utils::load_toml_or_json::<PermissionsManifest>(unknown.path(), "test");
+    assert!(error.unwrap_err().to_string().starts_with("Failed to parse test file"));
+
+    let missing =
+        utils::load_toml_or_json::<PermissionsManifest>("/nonexistent.json".as_ref(), "test");
+    assert!(missing.unwrap_err().to_string().starts_with("Failed to read test file"));
+}
@@ -0,0 +1,70 @@
+use assert_fs::prelude::*;
+use assert_fs::NamedTempFile;
+use dojo_test_utils::sequencer::{
+    get_default_test_starknet_config, SequencerConfig, TestSequencer,
+};
+use starknet::accounts::Account;
+use starknet::core::types::FieldElement;
+
+use super::setup;
+use crate::verify::{self, VerifyManifest};
+
+const ACTION_CONTRACT_NAME: &str = "dojo_examples::actions::actions";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_migrated_world() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let world = setup::setup(&sequencer).await.unwrap();
+    let migrator = world.account.address();
+
+    let checks = NamedTempFile::new("verify.toml").unwrap();
+    checks
+        .write_str(&format!(
+            r#"
+            models = ["Moves", "Position"]
+
+            [[owners]]
+            resource = "model:Position"
+            owner = "{migrator:#x}"
+
+            [[calls]]
+            contract = "{ACTION_CONTRACT_NAME}"
+            entrypoint = "dojo_resource"
+            expected = ["0x616374696f6e73"]
+
+            [[simulations]]
+            contract = "{ACTION_CONTRACT_NAME}"
+            entrypoint = "spawn"
+            "#
+        ))
+        .unwrap();
+
+    verify::verify(&world, checks.path()).await.unwrap();
+
+    // Failed checks don't stop the others from running.
+    let mut manifest = VerifyManifest::load(checks.path()).unwrap();
+    manifest.models = vec!["Unknown".to_string(), "Moves".to_string()];
+    manifest.owners[0].owner = FieldElement::from(0x1234_u32);
+    manifest.calls[0].expected = Some(vec![FieldElement::ZERO]);
+    manifest.simulations[0].entrypoint = "unknown".to_string();
+
+    let results = verify::check_world(&world, &manifest).await.unwrap();
+    let failed = results.iter().filter(|r| r.error.is_some()).map(|r| r.name.clone());
+    assert_eq!(results.len(), 6);
+    assert_eq!(
+        failed.collect::<Vec<_>>(),
+        vec![
+            "Model Unknown registered".to_string(),
+            "0x1234 owns model:Position".to_string(),
+            format!("Call {ACTION_CONTRACT_NAME}.dojo_resource"),
+            format!("Simulation of {ACTION_CONTRACT_NAME}.unknown"),
+        ]
+    );
+
+    // The simulation is not sent.
+    let nonce = world.account.get_nonce().await.unwrap();
+    verify::check_world(&world, &VerifyManifest::load(checks.path()).unwrap()).await.unwrap();
+    assert_eq!(world.account.get_nonce().await.unwrap(), nonce);
+}
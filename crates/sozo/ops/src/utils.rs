@@ -1,13 +1,102 @@
-use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
 use dojo_world::contracts::world::{WorldContract, WorldContractReader};
 use dojo_world::migration::strategy::generate_salt;
-use dojo_world::utils::{execution_status_from_maybe_pending_receipt, TransactionWaiter};
-use starknet::accounts::ConnectedAccount;
+use dojo_world::migration::TxnConfig;
+use dojo_world::utils::{
+    execution_status_from_maybe_pending_receipt, TransactionExt, TransactionWaiter,
+};
+use serde::de::DeserializeOwned;
+use starknet::accounts::{Call, ConnectedAccount};
 use starknet::core::types::{
     BlockId, BlockTag, ExecutionResult, FieldElement, InvokeTransactionResult,
 };
 use starknet::providers::Provider;
 
+/// The maximum length of the calldata of a transaction accepted by Starknet.
+pub const MAX_CALLDATA_LEN: usize = 4000;
+
+/// Splits `calls` in batches of at most `max_calls` calls, in order, so that each batch fits in a
+/// single multicall transaction.
+///
+/// A batch is also closed before its encoded calldata exceeds [`MAX_CALLDATA_LEN`]. A single call
+/// exceeding the limit is left in its own batch.
+///
+/// # Arguments
+///
+/// * `calls` - The calls to batch.
+/// * `max_calls` - The maximum number of calls in a batch, unlimited if `None`.
+///
+/// # Returns
+///
+/// The batches of calls, each with at least one call.
+pub fn batch_calls(calls: Vec<Call>, max_calls: Option<usize>) -> Vec<Vec<Call>> {
+    let max_calls = max_calls.unwrap_or(usize::MAX).max(1);
+
+    let mut batches = vec![];
+    let mut batch: Vec<Call> = vec![];
+    // the number of calls, then the headers and calldata of each call
+    let mut batch_len = 1;
+
+    for call in calls {
+        // an upper bound of the header of a call for both the Cairo 0 and Cairo 1 multicall
+        // encodings: address, selector, calldata offset and length
+        let call_len = 4 + call.calldata.len();
+
+        if !batch.is_empty()
+            && (batch.len() == max_calls || batch_len + call_len > MAX_CALLDATA_LEN)
+        {
+            batches.push(std::mem::take(&mut batch));
+            batch_len = 1;
+        }
+
+        batch_len += call_len;
+        batch.push(call);
+    }
+
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    batches
+}
+
+/// Sends `calls` in as few multicall transactions as allowed by [`batch_calls`], waiting for each
+/// transaction to be accepted before sending the next one.
+///
+/// # Arguments
+///
+/// * `account` - The account sending the transactions.
+/// * `calls` - The calls to send.
+/// * `txn_config` - The configuration of the transactions, including the batch size.
+///
+/// # Returns
+///
+/// The hashes of the sent transactions, in order.
+pub async fn execute_batched<A>(
+    account: &A,
+    calls: Vec<Call>,
+    txn_config: &TxnConfig,
+) -> Result<Vec<FieldElement>>
+where
+    A: ConnectedAccount + Sync,
+    A::SignError: 'static,
+{
+    let mut transaction_hashes = vec![];
+
+    for batch in batch_calls(calls, txn_config.max_calls) {
+        let InvokeTransactionResult { transaction_hash } =
+            account.execute(batch).send_with_cfg(txn_config).await?;
+
+        TransactionWaiter::new(transaction_hash, account.provider()).await?;
+        transaction_hashes.push(transaction_hash);
+    }
+
+    Ok(transaction_hashes)
+}
+
 /// Retrieves a contract address from it's name
 /// using the world's data, or parses a hex string into
 /// a [`FieldElement`].
@@ -134,3 +223,22 @@ pub fn parse_block_id(block_str: String) -> Result<BlockId> {
         }
     }
 }
+
+/// Loads the file at `path`, parsed as JSON if it has the `json` extension and as TOML otherwise.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+/// * `kind` - What the file holds, for the error messages, eg. `permissions`.
+pub fn load_toml_or_json<T: DeserializeOwned>(path: &Path, kind: &str) -> Result<T> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {kind} file {}", path.display()))?;
+
+    if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {kind} file {}", path.display()))
+    } else {
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {kind} file {}", path.display()))
+    }
+}
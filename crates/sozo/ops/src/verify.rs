@@ -0,0 +1,255 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use dojo_world::contracts::model::ModelError;
+use dojo_world::contracts::world::WorldContract;
+use dojo_world::contracts::WorldContractReader;
+use serde::Deserialize;
+use starknet::accounts::{Call, ConnectedAccount};
+use starknet::core::types::{
+    BlockId, BlockTag, ExecuteInvocation, FieldElement, FunctionCall, TransactionTrace,
+};
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::Provider;
+
+use crate::auth::ResourceType;
+use crate::utils;
+
+/// The checks run against a World after its migration, described in a verification file, eg:
+///
+/// ```toml
+/// models = ["Position", "Moves"]
+///
+/// [[owners]]
+/// resource = "model:Position"
+/// owner = "0x1234"
+///
+/// [[calls]]
+/// contract = "dojo_examples::actions::actions"
+/// entrypoint = "dojo_resource"
+/// expected = ["0x616374696f6e73"]
+///
+/// [[simulations]]
+/// contract = "dojo_examples::actions::actions"
+/// entrypoint = "spawn"
+/// ```
+///
+/// Calls are read-only calls which must succeed, and return `expected` if set. Simulations are
+/// invocations simulated from the migration account, which must not revert, and are never sent.
+/// The file is read as JSON if its extension is `json`, and as TOML otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VerifyManifest {
+    /// The names of the models which must be registered in the World.
+    #[serde(default)]
+    pub models: Vec<String>,
+    #[serde(default)]
+    pub owners: Vec<OwnerCheck>,
+    #[serde(default)]
+    pub calls: Vec<CallCheck>,
+    #[serde(default)]
+    pub simulations: Vec<SimulationCheck>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OwnerCheck {
+    /// The resource, as `model:model_name` or `contract:name_or_address`.
+    pub resource: String,
+    /// The address expected to own the resource.
+    pub owner: FieldElement,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CallCheck {
+    /// The name or address of the called contract.
+    pub contract: String,
+    pub entrypoint: String,
+    #[serde(default)]
+    pub calldata: Vec<FieldElement>,
+    /// The expected output of the call, any output is accepted if not set.
+    pub expected: Option<Vec<FieldElement>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimulationCheck {
+    /// The name or address of the invoked contract.
+    pub contract: String,
+    pub entrypoint: String,
+    #[serde(default)]
+    pub calldata: Vec<FieldElement>,
+}
+
+impl VerifyManifest {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        utils::load_toml_or_json(path.as_ref(), "verification")
+    }
+}
+
+/// The outcome of a check, `error` being the reason of its failure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckResult {
+    pub name: String,
+    pub error: Option<String>,
+}
+
+/// Runs the checks of `manifest` against the World, along with the check that the World is
+/// deployed. All the checks are run, even after a failure.
+pub async fn check_world<A>(
+    world: &WorldContract<A>,
+    manifest: &VerifyManifest,
+) -> Result<Vec<CheckResult>>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+{
+    let provider = world.account.provider();
+    let block_id = BlockId::Tag(BlockTag::Pending);
+    let world_reader = WorldContractReader::new(world.address, provider).with_block(block_id);
+
+    let mut results = vec![];
+
+    let deployed = provider.get_class_hash_at(block_id, world.address).await;
+    results.push(CheckResult {
+        name: format!("World deployed at {:#x}", world.address),
+        error: deployed.err().map(|e| e.to_string()),
+    });
+
+    for model in &manifest.models {
+        let error = match world_reader.model_reader(model).await {
+            Ok(_) => None,
+            Err(ModelError::ModelNotFound) => Some("not registered".to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        results.push(CheckResult { name: format!("Model {model} registered"), error });
+    }
+
+    for check in &manifest.owners {
+        let error = match is_owner(world, &world_reader, check).await {
+            Ok(true) => None,
+            Ok(false) => Some("not an owner".to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        results.push(CheckResult {
+            name: format!("{:#x} owns {}", check.owner, check.resource),
+            error,
+        });
+    }
+
+    for check in &manifest.calls {
+        let error = match call(world, check).await {
+            Ok(output) => match &check.expected {
+                Some(expected) if *expected != output => {
+                    Some(format!("returned [{}], expected [{}]", hex(&output), hex(expected)))
+                }
+                _ => None,
+            },
+            Err(e) => Some(e.to_string()),
+        };
+        results.push(CheckResult {
+            name: format!("Call {}.{}", check.contract, check.entrypoint),
+            error,
+        });
+    }
+
+    for check in &manifest.simulations {
+        let error = simulate(world, check).await.err().map(|e| e.to_string());
+        results.push(CheckResult {
+            name: format!("Simulation of {}.{}", check.contract, check.entrypoint),
+            error,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Runs the checks of the verification file at `path` against the World, printing their
+/// outcome. Fails if any check failed, so that a broken deployment stops the pipeline running the
+/// migration.
+pub async fn verify<A, P>(world: &WorldContract<A>, path: P) -> Result<()>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+    P: AsRef<Path>,
+{
+    let manifest = VerifyManifest::load(path)?;
+    let results = check_world(world, &manifest).await?;
+
+    let mut failures = 0;
+    for CheckResult { name, error } in &results {
+        match error {
+            None => println!("✓ {name}"),
+            Some(error) => {
+                failures += 1;
+                println!("✗ {name}: {error}");
+            }
+        }
+    }
+
+    if failures > 0 {
+        bail!("{failures} of {} verification checks failed", results.len());
+    }
+
+    println!("All {} verification checks passed.", results.len());
+    Ok(())
+}
+
+async fn is_owner<A, P>(
+    world: &WorldContract<A>,
+    world_reader: &WorldContractReader<P>,
+    check: &OwnerCheck,
+) -> Result<bool>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+    P: Provider + Sync + Send,
+{
+    let resource = match check.resource.parse()? {
+        ResourceType::Model(name) => name,
+        ResourceType::Contract(name_or_address) => {
+            utils::get_contract_address(world, name_or_address).await?
+        }
+    };
+
+    Ok(world_reader.is_owner(&check.owner.into(), &resource).call().await?)
+}
+
+async fn call<A>(world: &WorldContract<A>, check: &CallCheck) -> Result<Vec<FieldElement>>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+{
+    let contract_address = utils::get_contract_address(world, check.contract.clone()).await?;
+    let call = FunctionCall {
+        contract_address,
+        entry_point_selector: get_selector_from_name(&check.entrypoint)?,
+        calldata: check.calldata.clone(),
+    };
+
+    Ok(world.account.provider().call(call, BlockId::Tag(BlockTag::Pending)).await?)
+}
+
+async fn simulate<A>(world: &WorldContract<A>, check: &SimulationCheck) -> Result<()>
+where
+    A: ConnectedAccount + Sync + Send + 'static,
+{
+    let to = utils::get_contract_address(world, check.contract.clone()).await?;
+    let call = Call {
+        to,
+        selector: get_selector_from_name(&check.entrypoint)?,
+        calldata: check.calldata.clone(),
+    };
+
+    // the fee is not charged, so that the check doesn't depend on the balance of the account
+    let simulated = world.account.execute(vec![call]).simulate(false, true).await?;
+
+    if let TransactionTrace::Invoke(trace) = simulated.transaction_trace {
+        if let ExecuteInvocation::Reverted(reverted) = trace.execute_invocation {
+            bail!("reverted: {}", reverted.revert_reason);
+        }
+    }
+
+    Ok(())
+}
+
+fn hex(values: &[FieldElement]) -> String {
+    values.iter().map(|v| format!("{v:#x}")).collect::<Vec<_>>().join(", ")
+}
@@ -0,0 +1,342 @@
+//! Storage backends the indexed data is written to.
+//!
+//! The statements queued by [Sql](crate::sql::Sql) are written in the SQLite dialect. The SQLite
+//! backend executes them as is, while the Postgres backend translates them with
+//! [postgres_statement] first.
+//!
+//! The engine and the GraphQL and gRPC servers read from SQLite, so the Postgres backend is a
+//! mirror of the SQLite database of Torii, see [Sql::with_mirror](crate::sql::Sql::with_mirror).
+//! The search index is kept up to date by SQLite triggers, which aren't mirrored.
+
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::postgres::{PgPoolOptions, Postgres};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Database, Executor, IntoArguments, Pool, Sqlite};
+use tracing::warn;
+
+use crate::query_queue::Argument;
+
+pub(crate) const LOG_TARGET: &str = "torii_core::backend";
+
+/// The number of times a batch is retried when it conflicts with a concurrent writer.
+const MAX_BATCH_RETRIES: usize = 5;
+
+/// The kind of database a connection string points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    Sqlite,
+    Postgres,
+}
+
+impl DatabaseBackend {
+    /// Returns the backend of the connection string `url`. Anything that isn't a Postgres url is
+    /// considered to be a SQLite database path or url.
+    pub fn from_url(url: &str) -> Self {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            DatabaseBackend::Postgres
+        } else {
+            DatabaseBackend::Sqlite
+        }
+    }
+}
+
+/// A database the queued statements are executed on.
+#[async_trait]
+pub trait StorageBackend: Debug + Send + Sync {
+    fn kind(&self) -> DatabaseBackend;
+
+    /// Executes the statements in a single transaction, returning the number of affected rows.
+    async fn execute_batch(&self, statements: &[(String, Vec<Argument>)]) -> sqlx::Result<u64>;
+}
+
+#[async_trait]
+impl StorageBackend for Pool<Sqlite> {
+    fn kind(&self) -> DatabaseBackend {
+        DatabaseBackend::Sqlite
+    }
+
+    async fn execute_batch(&self, statements: &[(String, Vec<Argument>)]) -> sqlx::Result<u64> {
+        // SQLite has a single writer, concurrent writers wait for the lock up to the busy timeout
+        execute_in_transaction(self, statements.iter().map(|(s, a)| (s.as_str(), a))).await
+    }
+}
+
+#[async_trait]
+impl StorageBackend for Pool<Postgres> {
+    fn kind(&self) -> DatabaseBackend {
+        DatabaseBackend::Postgres
+    }
+
+    async fn execute_batch(&self, statements: &[(String, Vec<Argument>)]) -> sqlx::Result<u64> {
+        let statements = statements
+            .iter()
+            .filter(|(statement, _)| !is_sqlite_trigger(statement))
+            .map(|(statement, arguments)| (postgres_statement(statement), arguments))
+            .collect::<Vec<_>>();
+
+        let mut retries = 0;
+        loop {
+            let batch = statements.iter().map(|(s, a)| (s.as_str(), *a));
+            match execute_in_transaction(self, batch).await {
+                Err(e) if retries < MAX_BATCH_RETRIES && is_write_conflict(&e) => {
+                    retries += 1;
+                    warn!(target: LOG_TARGET, error = %e, retries, "Retrying conflicting batch.");
+                    tokio::time::sleep(Duration::from_millis(50 * retries as u64)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Connects to the database of the connection string `url` and runs its migrations.
+pub async fn connect(url: &str) -> Result<Arc<dyn StorageBackend>> {
+    match DatabaseBackend::from_url(url) {
+        DatabaseBackend::Sqlite => {
+            let url =
+                if url.starts_with("sqlite:") { url.to_string() } else { format!("sqlite:{url}") };
+            let options =
+                SqliteConnectOptions::from_str(&url)?.create_if_missing(true).with_regexp();
+            let pool = SqlitePoolOptions::new()
+                .min_connections(1)
+                .max_connections(5)
+                .connect_with(options)
+                .await?;
+
+            sqlx::migrate!("../migrations").run(&pool).await?;
+            Ok(Arc::new(pool))
+        }
+
+        DatabaseBackend::Postgres => {
+            let pool = PgPoolOptions::new().max_connections(5).connect(url).await?;
+
+            sqlx::migrate!("../migrations-postgres").run(&pool).await?;
+            Ok(Arc::new(pool))
+        }
+    }
+}
+
+async fn execute_in_transaction<'a, DB, I>(pool: &Pool<DB>, statements: I) -> sqlx::Result<u64>
+where
+    DB: Database,
+    I: Iterator<Item = (&'a str, &'a Vec<Argument>)>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as sqlx::database::HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> Option<String>: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    for<'q> i64: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    for<'q> bool: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    for<'q> String: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+    for<'q> Vec<u8>: sqlx::Encode<'q, DB> + sqlx::Type<DB>,
+{
+    let mut total_affected = 0_u64;
+    let mut tx = pool.begin().await?;
+
+    for (statement, arguments) in statements {
+        let mut query = sqlx::query::<DB>(statement);
+
+        for arg in arguments {
+            query = match arg {
+                Argument::Null => query.bind(None::<String>),
+                Argument::Int(integer) => query.bind(*integer),
+                Argument::Bool(bool) => query.bind(*bool),
+                Argument::String(string) => query.bind(string.clone()),
+                Argument::FieldElement(felt) => query.bind(format!("{:#x}", felt)),
+                Argument::Blob(blob) => query.bind(blob.clone()),
+            }
+        }
+
+        total_affected += (&mut *tx).execute(query).await?.rows_affected();
+    }
+
+    tx.commit().await?;
+
+    Ok(total_affected)
+}
+
+/// The triggers keeping the search index in sync are written in the SQLite dialect, and only
+/// exist in SQLite.
+fn is_sqlite_trigger(statement: &str) -> bool {
+    statement.trim_start().starts_with("CREATE TRIGGER")
+}
+
+/// Serialization failures and deadlocks happen when several Torii instances write to the same
+/// database, and succeed when retried.
+fn is_write_conflict(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Database(e) => matches!(e.code().as_deref(), Some("40001") | Some("40P01")),
+        _ => false,
+    }
+}
+
+/// Translates a statement written in the SQLite dialect used by Torii to Postgres:
+/// - `?` placeholders become numbered `$n` placeholders.
+/// - `[table]` identifiers become `"table"`.
+/// - the `BLOB`, `DATETIME` and `INTEGER` column types become `BYTEA`, `TEXT` and `BIGINT`.
+///   Datetimes are stored as the same text as in SQLite.
+/// - `INSERT OR IGNORE` becomes `INSERT ... ON CONFLICT DO NOTHING`, and `INSERT OR REPLACE`
+///   becomes an upsert of every column on the `id` primary key of the model tables.
+pub fn postgres_statement(statement: &str) -> String {
+    let mut translated = String::with_capacity(statement.len());
+    let mut placeholders = 0;
+    let mut in_literal = false;
+    let mut word = String::new();
+
+    let flush_word = |word: &mut String, translated: &mut String| {
+        translated.push_str(match word.as_str() {
+            "BLOB" => "BYTEA",
+            "DATETIME" => "TEXT",
+            "INTEGER" => "BIGINT",
+            word => word,
+        });
+        word.clear();
+    };
+
+    for c in statement.chars() {
+        if in_literal {
+            translated.push(c);
+            in_literal = c != '\'';
+            continue;
+        }
+
+        if c.is_ascii_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+
+        flush_word(&mut word, &mut translated);
+
+        match c {
+            '\'' => {
+                in_literal = true;
+                translated.push(c);
+            }
+            '?' => {
+                placeholders += 1;
+                translated.push_str(&format!("${placeholders}"));
+            }
+            '[' | ']' => translated.push('"'),
+            c => translated.push(c),
+        }
+    }
+
+    flush_word(&mut word, &mut translated);
+
+    if let Some(rest) = translated.strip_prefix("INSERT OR IGNORE INTO ") {
+        return format!("INSERT INTO {} ON CONFLICT DO NOTHING", rest.trim_end_matches(';'));
+    }
+
+    if let Some(rest) = translated.strip_prefix("INSERT OR REPLACE INTO ") {
+        let columns = rest
+            .split_once('(')
+            .and_then(|(_, rest)| rest.split_once(')'))
+            .map(|(columns, _)| columns.split(',').map(str::trim).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let updates = columns
+            .iter()
+            .filter(|column| **column != "id")
+            .map(|column| format!("{column}=EXCLUDED.{column}"))
+            .collect::<Vec<_>>();
+
+        return if updates.is_empty() {
+            format!("INSERT INTO {} ON CONFLICT (id) DO NOTHING", rest.trim_end_matches(';'))
+        } else {
+            format!(
+                "INSERT INTO {} ON CONFLICT (id) DO UPDATE SET {}",
+                rest.trim_end_matches(';'),
+                updates.join(", ")
+            )
+        };
+    }
+
+    translated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_from_url() {
+        assert_eq!(DatabaseBackend::from_url(":memory:"), DatabaseBackend::Sqlite);
+        assert_eq!(DatabaseBackend::from_url("indexer.db"), DatabaseBackend::Sqlite);
+        assert_eq!(DatabaseBackend::from_url("sqlite:indexer.db"), DatabaseBackend::Sqlite);
+        assert_eq!(
+            DatabaseBackend::from_url("postgres://torii@localhost/torii"),
+            DatabaseBackend::Postgres
+        );
+        assert_eq!(
+            DatabaseBackend::from_url("postgresql://localhost:5432/torii"),
+            DatabaseBackend::Postgres
+        );
+    }
+
+    #[test]
+    fn translate_placeholders_and_identifiers() {
+        assert_eq!(
+            postgres_statement("DELETE FROM [Position$vec] WHERE entity_id = ? AND id = ?"),
+            r#"DELETE FROM "Position$vec" WHERE entity_id = $1 AND id = $2"#
+        );
+
+        // literals are left untouched
+        assert_eq!(
+            postgres_statement("UPDATE metadata SET uri = '[?]' WHERE id = ?"),
+            "UPDATE metadata SET uri = '[?]' WHERE id = $1"
+        );
+    }
+
+    #[test]
+    fn translate_column_types() {
+        assert_eq!(
+            postgres_statement(
+                "CREATE TABLE IF NOT EXISTS [Moves] (id TEXT NOT NULL PRIMARY KEY, \
+                 external_remaining INTEGER, external_dir TEXT CHECK(external_dir IN ('INTEGER', \
+                 'BLOB')) NOT NULL, external_path BLOB, executed_at DATETIME NOT NULL);"
+            ),
+            "CREATE TABLE IF NOT EXISTS \"Moves\" (id TEXT NOT NULL PRIMARY KEY, \
+             external_remaining BIGINT, external_dir TEXT CHECK(external_dir IN ('INTEGER', \
+             'BLOB')) NOT NULL, external_path BYTEA, executed_at TEXT NOT NULL);"
+        );
+    }
+
+    #[test]
+    fn skip_triggers() {
+        assert!(is_sqlite_trigger(
+            "CREATE TRIGGER IF NOT EXISTS [search_Player_name_insert] AFTER INSERT ON [Player] \
+             BEGIN SELECT 1; END;"
+        ));
+        assert!(!is_sqlite_trigger("CREATE TABLE IF NOT EXISTS [Player] (id TEXT NOT NULL)"));
+    }
+
+    #[test]
+    fn translate_inserts() {
+        assert_eq!(
+            postgres_statement("INSERT OR IGNORE INTO events (id, keys) VALUES (?, ?)"),
+            "INSERT INTO events (id, keys) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+        );
+
+        assert_eq!(
+            postgres_statement(
+                "INSERT OR REPLACE INTO [Position] (id,event_id,external_x) VALUES (?,?,?)"
+            ),
+            "INSERT INTO \"Position\" (id,event_id,external_x) VALUES ($1,$2,$3) ON CONFLICT (id) \
+             DO UPDATE SET event_id=EXCLUDED.event_id, external_x=EXCLUDED.external_x"
+        );
+
+        // upserts are valid in both dialects
+        assert_eq!(
+            postgres_statement(
+                "INSERT INTO entity_model (entity_id, model_id) VALUES (?, ?) ON \
+                 CONFLICT(entity_id, model_id) DO NOTHING"
+            ),
+            "INSERT INTO entity_model (entity_id, model_id) VALUES ($1, $2) ON \
+             CONFLICT(entity_id, model_id) DO NOTHING"
+        );
+    }
+}
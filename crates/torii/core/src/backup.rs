@@ -0,0 +1,323 @@
+//! Incremental backups of the indexer database, and their point-in-time restore.
+//!
+//! A backup directory holds one generation per run of Torii, numbered from the oldest to the
+//! newest:
+//!
+//! ```text
+//! backups/
+//! ├── 000001/
+//! │   ├── base.db      # snapshot of the database when the run started
+//! │   ├── base.json    # the head of the indexer at the time of the snapshot
+//! │   └── log.jsonl    # the batches of statements applied to the database since
+//! └── 000002/
+//! ```
+//!
+//! Every batch executed by the [QueryQueue](crate::query_queue::QueryQueue) is appended to the
+//! log once committed, along with the head it moved the indexer to, if any. Restoring to a block
+//! replays the log of the newest generation whose snapshot is older than the block, up to the
+//! last batch that leaves the head at or before the block, so that the restored database and its
+//! sync cursor are consistent.
+//!
+//! The snapshots are SQLite files, so backups are restored to a SQLite database, whichever the
+//! databases the batches are mirrored to.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Pool, Sqlite};
+use tracing::{info, warn};
+
+use crate::backend::StorageBackend;
+use crate::query_queue::Argument;
+
+pub(crate) const LOG_TARGET: &str = "torii_core::backup";
+
+const BASE_FILE: &str = "base.db";
+const BASE_METADATA_FILE: &str = "base.json";
+const LOG_FILE: &str = "log.jsonl";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaseMetadata {
+    head: u64,
+}
+
+/// A batch of statements committed to the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct LogRecord {
+    /// The head the batch moved the indexer to, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    head: Option<u64>,
+    #[serde(default)]
+    statements: Vec<(String, Vec<Argument>)>,
+}
+
+/// The generation of a backup a run of Torii writes to.
+#[derive(Debug)]
+pub struct IncrementalBackup {
+    dir: PathBuf,
+    log: Mutex<File>,
+    /// Set once an append failed, after which the log has a gap and nothing is appended anymore.
+    broken: AtomicBool,
+}
+
+impl IncrementalBackup {
+    /// Starts a new generation in `backup_dir`, with a snapshot of the database at `head`.
+    pub async fn start(pool: &Pool<Sqlite>, backup_dir: &Path, head: u64) -> Result<Self> {
+        let generation = generations(backup_dir)?.last().map_or(1, |(number, _)| number + 1);
+        let dir = backup_dir.join(format!("{generation:06}"));
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create backup directory {}", dir.display()))?;
+
+        let base = dir.join(BASE_FILE);
+        sqlx::query("VACUUM INTO ?")
+            .bind(base.to_string_lossy().into_owned())
+            .execute(pool)
+            .await
+            .context("Failed to snapshot the database")?;
+
+        // written last, the generations without metadata are incomplete and ignored
+        fs::write(dir.join(BASE_METADATA_FILE), serde_json::to_vec(&BaseMetadata { head })?)?;
+
+        let log = OpenOptions::new().create(true).append(true).open(dir.join(LOG_FILE))?;
+        info!(target: LOG_TARGET, dir = %dir.display(), %head, "Started incremental backup.");
+
+        Ok(Self { dir, log: Mutex::new(log), broken: AtomicBool::new(false) })
+    }
+
+    /// The directory of the generation.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Appends a committed batch to the log. The log is synced to the disk before returning, and
+    /// fails for good after a first failure, so that indexing stops rather than leave a gap in
+    /// the backup.
+    pub fn append(
+        &self,
+        head: Option<u64>,
+        statements: &[(String, Vec<Argument>)],
+    ) -> io::Result<()> {
+        if statements.is_empty() {
+            return Ok(());
+        }
+
+        if self.broken.load(Ordering::Acquire) {
+            return Err(io::Error::other("incremental backup log is broken by a previous failure"));
+        }
+
+        let record = LogRecord { head, statements: statements.to_vec() };
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+
+        let mut log = self.log.lock().expect("poisoned lock");
+        let result = log.write_all(&line).and_then(|_| log.sync_data());
+        if result.is_err() {
+            self.broken.store(true, Ordering::Release);
+        }
+
+        result
+    }
+}
+
+/// Restores the backup of `backup_dir` to the SQLite database file `database`, as it was once the
+/// block `to_block` was indexed. Returns the head of the restored database, which is before
+/// `to_block` if the backup has no later state.
+pub async fn restore(backup_dir: &Path, database: &Path, to_block: u64) -> Result<u64> {
+    if database.exists() {
+        bail!("Database {} already exists, restore to a new file", database.display());
+    }
+
+    let Some((_, dir, base_head)) = generations(backup_dir)?
+        .into_iter()
+        .filter_map(|(number, dir)| base_head(&dir).map(|head| (number, dir, head)))
+        .filter(|(_, _, head)| *head <= to_block)
+        .last()
+    else {
+        bail!("No backup in {} is older than block {to_block}", backup_dir.display());
+    };
+
+    let log = dir.join(LOG_FILE);
+    let (last_record, head) = restore_point(&log, base_head, to_block)?;
+
+    fs::copy(dir.join(BASE_FILE), database)
+        .with_context(|| format!("Failed to copy the snapshot to {}", database.display()))?;
+
+    let options =
+        SqliteConnectOptions::from_str(&format!("sqlite:{}", database.display()))?.with_regexp();
+    let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await?;
+
+    for record in read_log(&log)?.take(last_record.map_or(0, |index| index + 1)) {
+        pool.execute_batch(&record?.statements).await?;
+    }
+
+    pool.close().await;
+    info!(target: LOG_TARGET, generation = %dir.display(), %head, "Restored backup.");
+
+    Ok(head)
+}
+
+/// Returns the index of the last record of the log to replay to restore the database to
+/// `to_block`, `None` if the snapshot already is, and the head of the restored database.
+fn restore_point(log: &Path, base_head: u64, to_block: u64) -> Result<(Option<usize>, u64)> {
+    let mut head = base_head;
+    let mut restore_point = (None, base_head);
+
+    for (index, record) in read_log(log)?.enumerate() {
+        head = record?.head.unwrap_or(head);
+        // the head moves back when blocks are rolled back, so the whole log is scanned
+        if head <= to_block {
+            restore_point = (Some(index), head);
+        }
+    }
+
+    Ok(restore_point)
+}
+
+/// Reads the records of a log. A record that can't be parsed ends the log, as only the last one
+/// can be partially written.
+fn read_log(log: &Path) -> Result<impl Iterator<Item = Result<LogRecord>>> {
+    let path = log.display().to_string();
+    let lines =
+        BufReader::new(File::open(log).with_context(|| format!("Failed to open {path}"))?).lines();
+
+    Ok(lines
+        .map(move |line| -> Result<Option<LogRecord>> {
+            let line = line.with_context(|| format!("Failed to read {path}"))?;
+            match serde_json::from_str(&line) {
+                Ok(record) => Ok(Some(record)),
+                Err(e) => {
+                    warn!(target: LOG_TARGET, log = %path, error = %e, "Truncated backup log.");
+                    Ok(None)
+                }
+            }
+        })
+        .map_while(Result::transpose))
+}
+
+/// Returns the generations of a backup directory, from the oldest to the newest.
+fn generations(backup_dir: &Path) -> Result<Vec<(u64, PathBuf)>> {
+    if !backup_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut generations = fs::read_dir(backup_dir)
+        .with_context(|| format!("Failed to read backup directory {}", backup_dir.display()))?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let number = path.file_name()?.to_str()?.parse().ok()?;
+            path.is_dir().then_some((number, path))
+        })
+        .collect::<Vec<_>>();
+    generations.sort();
+
+    Ok(generations)
+}
+
+fn base_head(dir: &Path) -> Option<u64> {
+    let metadata = fs::read(dir.join(BASE_METADATA_FILE)).ok()?;
+    serde_json::from_slice::<BaseMetadata>(&metadata).ok().map(|metadata| metadata.head)
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet_crypto::FieldElement;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::sql::Sql;
+
+    async fn connect(path: &Path) -> Pool<Sqlite> {
+        let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", path.display()))
+            .unwrap()
+            .create_if_missing(true);
+        SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap()
+    }
+
+    async fn index_block(db: &mut Sql, number: u64, hash: u64) {
+        db.store_block(number, FieldElement::from(hash));
+        db.set_head(number);
+        db.execute().await.unwrap();
+    }
+
+    async fn restored_blocks(backup_dir: &Path, database: &Path, to_block: u64) -> (u64, Vec<u64>) {
+        let head = restore(backup_dir, database, to_block).await.unwrap();
+
+        let db = Sql::new(connect(database).await, FieldElement::ONE).await.unwrap();
+        assert_eq!(db.head().await.unwrap(), head);
+
+        let hashes = db.blocks().await.unwrap().into_iter().map(|(_, hash)| hash);
+        (head, hashes.map(|hash| hash.try_into().unwrap()).collect())
+    }
+
+    #[tokio::test]
+    async fn restore_to_block() {
+        let dir = tempdir().unwrap();
+        let backup_dir = dir.path().join("backups");
+
+        let pool = connect(&dir.path().join("indexer.db")).await;
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        let mut db = Sql::new(pool.clone(), FieldElement::ONE).await.unwrap();
+        index_block(&mut db, 1, 10).await;
+
+        // the blocks indexed before the backup is started are in its snapshot
+        let mut db = db.with_backup(&backup_dir).await.unwrap();
+        index_block(&mut db, 2, 20).await;
+        index_block(&mut db, 3, 30).await;
+
+        // the blocks 2 and 3 are orphaned, and the new block 2 is indexed
+        db.rollback(Some(1)).await.unwrap();
+        index_block(&mut db, 2, 21).await;
+
+        let restored = |name: &str| dir.path().join(name);
+        assert_eq!(restored_blocks(&backup_dir, &restored("1.db"), 1).await, (1, vec![10]));
+        assert_eq!(restored_blocks(&backup_dir, &restored("2.db"), 2).await, (2, vec![21, 10]));
+        // the block 3 of the canonical chain was never indexed
+        assert_eq!(restored_blocks(&backup_dir, &restored("3.db"), 3).await, (2, vec![21, 10]));
+
+        assert!(restore(&backup_dir, &restored("1.db"), 1).await.is_err());
+        assert!(restore(&backup_dir, &restored("0.db"), 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn restore_from_newest_generation() {
+        let dir = tempdir().unwrap();
+        let backup_dir = dir.path().join("backups");
+
+        let pool = connect(&dir.path().join("indexer.db")).await;
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        let mut db = Sql::new(pool.clone(), FieldElement::ONE)
+            .await
+            .unwrap()
+            .with_backup(&backup_dir)
+            .await
+            .unwrap();
+        index_block(&mut db, 1, 10).await;
+
+        // a restart of Torii starts a new generation
+        let mut db = Sql::new(pool, FieldElement::ONE)
+            .await
+            .unwrap()
+            .with_backup(&backup_dir)
+            .await
+            .unwrap();
+        index_block(&mut db, 2, 20).await;
+        assert_eq!(generations(&backup_dir).unwrap().len(), 2);
+
+        let restored = dir.path().join("restored.db");
+        assert_eq!(restored_blocks(&backup_dir, &restored, 2).await, (2, vec![20, 10]));
+
+        // a truncated record ends the log
+        let log = generations(&backup_dir).unwrap()[1].1.join(LOG_FILE);
+        fs::write(&log, fs::read_to_string(&log).unwrap() + "{\"head\":3,\"stat").unwrap();
+        assert_eq!(restore_point(&log, 1, 3).unwrap(), (Some(0), 2));
+    }
+}
@@ -6,29 +6,51 @@ use tokio::sync::RwLock;
 
 use crate::error::{Error, QueryError};
 use crate::model::{parse_sql_model_members, SqlModelMember};
+use crate::privacy::Privacy;
 
 type ModelName = String;
 
+/// A cache of the public schemas of the models, keyed by model id. The private members of the
+/// models are removed from their schema, and private models are treated as missing.
 pub struct ModelCache {
     pool: SqlitePool,
-    cache: RwLock<HashMap<ModelName, Ty>>,
+    privacy: Privacy,
+    cache: RwLock<HashMap<ModelName, Option<Ty>>>,
 }
 
 impl ModelCache {
     pub fn new(pool: SqlitePool) -> Self {
-        Self { pool, cache: RwLock::new(HashMap::new()) }
+        Self::with_privacy(pool, Privacy::default())
     }
 
+    pub fn with_privacy(pool: SqlitePool, privacy: Privacy) -> Self {
+        Self { pool, privacy, cache: RwLock::new(HashMap::new()) }
+    }
+
+    pub fn privacy(&self) -> &Privacy {
+        &self.privacy
+    }
+
+    /// Returns the schemas of the public models among `models`.
     pub async fn schemas(&self, models: Vec<&str>) -> Result<Vec<Ty>, Error> {
         let mut schemas = Vec::with_capacity(models.len());
         for model in models {
-            schemas.push(self.schema(model).await?);
+            if let Some(schema) = self.public_schema(model).await? {
+                schemas.push(schema);
+            }
         }
 
         Ok(schemas)
     }
 
     pub async fn schema(&self, model: &str) -> Result<Ty, Error> {
+        self.public_schema(model)
+            .await?
+            .ok_or_else(|| QueryError::ModelNotFound(model.into()).into())
+    }
+
+    /// Returns the schema of `model`, or `None` if the model is private.
+    pub async fn public_schema(&self, model: &str) -> Result<Option<Ty>, Error> {
         {
             let cache = self.cache.read().await;
             if let Some(schema) = cache.get(model) {
@@ -39,7 +61,7 @@ impl ModelCache {
         self.update_schema(model).await
     }
 
-    async fn update_schema(&self, model: &str) -> Result<Ty, Error> {
+    async fn update_schema(&self, model: &str) -> Result<Option<Ty>, Error> {
         let model_name: String = sqlx::query_scalar("SELECT name FROM models WHERE id = ?")
             .bind(model)
             .fetch_one(&self.pool)
@@ -56,7 +78,7 @@ impl ModelCache {
             return Err(QueryError::ModelNotFound(model.into()).into());
         }
 
-        let ty = parse_sql_model_members(&model_name, &model_members);
+        let ty = self.privacy.public_schema(parse_sql_model_members(&model_name, &model_members));
         let mut cache = self.cache.write().await;
         cache.insert(model.into(), ty.clone());
 
@@ -0,0 +1,134 @@
+//! Coalescing of the rapid successive updates of high-frequency models.
+//!
+//! Games writing the positions of their entities every block update the same rows over and over.
+//! The updates of the models declared safe to coalesce, the ones for which only the latest value
+//! matters, are buffered for a window, so that the successive updates of an entity are merged into
+//! a single write of its latest value and a single subscription push.
+//!
+//! The history of the entities still records every update, as it is indexed. The head of the
+//! indexer isn't moved while updates are buffered, so after a restart the blocks they come from
+//! are indexed again: their history, events and transactions are already stored and are skipped,
+//! only the buffered updates are written.
+
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+
+use dojo_types::schema::Ty;
+
+#[derive(Debug, Clone, Default)]
+pub struct CoalescingConfig {
+    /// How long the updates are buffered for, from the first buffered update.
+    pub window: Duration,
+    /// The names of the models whose updates are coalesced.
+    pub models: HashSet<String>,
+}
+
+/// The latest value of a model of an entity, waiting to be written.
+#[derive(Debug, Clone)]
+pub(crate) struct PendingUpdate {
+    pub entity: Ty,
+    pub event_id: String,
+    pub block_timestamp: u64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Coalescer {
+    config: CoalescingConfig,
+    /// The pending updates, by entity id and model name.
+    pending: BTreeMap<(String, String), PendingUpdate>,
+    window_start: Option<Instant>,
+    /// The head reached while updates were pending, written along with them.
+    deferred_head: Option<u64>,
+}
+
+impl Coalescer {
+    pub fn new(config: CoalescingConfig) -> Self {
+        Self { config, pending: BTreeMap::new(), window_start: None, deferred_head: None }
+    }
+
+    pub fn is_coalesced(&self, model: &str) -> bool {
+        self.config.models.contains(model)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Whether the window of the pending updates has elapsed.
+    pub fn is_due(&self) -> bool {
+        self.window_start.is_some_and(|start| start.elapsed() >= self.config.window)
+    }
+
+    /// Buffers an update, replacing the pending update of the same model of the entity.
+    pub fn push(&mut self, entity_id: String, update: PendingUpdate) {
+        self.window_start.get_or_insert_with(Instant::now);
+        self.pending.insert((entity_id, update.entity.name()), update);
+    }
+
+    /// Drops the pending update of a model of an entity, eg. once the entity is deleted.
+    pub fn remove(&mut self, entity_id: &str, model: &str) {
+        self.pending.remove(&(entity_id.to_string(), model.to_string()));
+    }
+
+    pub fn defer_head(&mut self, head: u64) {
+        self.deferred_head = Some(head);
+    }
+
+    /// Takes the pending updates, and the head deferred while they were pending.
+    pub fn take(&mut self) -> (Vec<PendingUpdate>, Option<u64>) {
+        self.window_start = None;
+        let pending = std::mem::take(&mut self.pending);
+        (pending.into_values().collect(), self.deferred_head.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::schema::Struct;
+
+    use super::*;
+
+    fn update(model: &str, event_id: &str) -> PendingUpdate {
+        let entity = Ty::Struct(Struct { name: model.to_string(), children: vec![] });
+        PendingUpdate { entity, event_id: event_id.to_string(), block_timestamp: 0 }
+    }
+
+    #[test]
+    fn latest_update_wins() {
+        let mut coalescer = Coalescer::new(CoalescingConfig {
+            window: Duration::from_secs(60),
+            models: HashSet::from(["Position".to_string()]),
+        });
+        assert!(coalescer.is_coalesced("Position"));
+        assert!(!coalescer.is_coalesced("Moves"));
+        assert!(!coalescer.is_due());
+
+        coalescer.push("0x1".to_string(), update("Position", "0x1:0x0:0x0"));
+        coalescer.push("0x1".to_string(), update("Position", "0x2:0x0:0x0"));
+        coalescer.push("0x2".to_string(), update("Position", "0x2:0x0:0x1"));
+        coalescer.defer_head(2);
+        assert!(!coalescer.is_due());
+
+        let (updates, head) = coalescer.take();
+        let event_ids = updates.iter().map(|u| u.event_id.as_str()).collect::<Vec<_>>();
+        assert_eq!(event_ids, ["0x2:0x0:0x0", "0x2:0x0:0x1"]);
+        assert_eq!(head, Some(2));
+        assert!(!coalescer.is_pending());
+    }
+
+    #[test]
+    fn window_elapses() {
+        let mut coalescer = Coalescer::new(CoalescingConfig {
+            window: Duration::ZERO,
+            models: HashSet::from(["Position".to_string()]),
+        });
+
+        coalescer.push("0x1".to_string(), update("Position", "0x1:0x0:0x0"));
+        assert!(coalescer.is_due());
+
+        coalescer.remove("0x1", "Position");
+        assert!(!coalescer.is_pending());
+        assert!(coalescer.take().0.is_empty());
+        assert!(!coalescer.is_due());
+    }
+}
@@ -1,20 +1,23 @@
-use std::time::Duration;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::{
     BlockId, EmittedEvent, Event, EventFilter, MaybePendingBlockWithTxHashes,
-    MaybePendingTransactionReceipt, Transaction, TransactionReceipt,
+    MaybePendingTransactionReceipt, StarknetError, Transaction, TransactionReceipt,
 };
 use starknet::core::utils::get_selector_from_name;
-use starknet::providers::Provider;
+use starknet::providers::{Provider, ProviderError};
 use starknet_crypto::FieldElement;
 use tokio::sync::broadcast::Sender;
 use tokio::sync::mpsc::Sender as BoundedSender;
 use tokio::time::sleep;
 use tracing::{error, info, trace, warn};
 
-use crate::processors::{BlockProcessor, EventProcessor, TransactionProcessor};
+use crate::filter::IndexingFilterFile;
+use crate::lag::{IndexingLag, LagMonitor, LagSlo};
+use crate::processors::{BlockProcessor, EventProcessor, Processor, TransactionProcessor};
 use crate::sql::Sql;
 
 pub struct Processors<P: Provider + Sync> {
@@ -29,18 +32,58 @@ impl<P: Provider + Sync> Default for Processors<P> {
     }
 }
 
+impl<P: Provider + Sync> Processors<P> {
+    /// Registers all the processors of `processor`.
+    pub fn register<T: Processor<P> + ?Sized>(&mut self, processor: &T) -> &mut Self {
+        processor.register(self);
+        self
+    }
+
+    pub fn add_block(&mut self, processor: impl BlockProcessor<P> + 'static) -> &mut Self {
+        self.block.push(Box::new(processor));
+        self
+    }
+
+    pub fn add_transaction(
+        &mut self,
+        processor: impl TransactionProcessor<P> + 'static,
+    ) -> &mut Self {
+        self.transaction.push(Box::new(processor));
+        self
+    }
+
+    pub fn add_event(&mut self, processor: impl EventProcessor<P> + 'static) -> &mut Self {
+        self.event.push(Box::new(processor));
+        self
+    }
+}
+
 pub(crate) const LOG_TARGET: &str = "tori_core::engine";
 
+/// The number of latest indexed blocks compared with the chain at once to find the canonical
+/// ancestor of a reorg.
+const REORG_DEPTH: u64 = 64;
+
 #[derive(Debug)]
 pub struct EngineConfig {
     pub block_time: Duration,
     pub start_block: u64,
     pub events_chunk_size: u64,
+    /// Restricts the processed events. The filter is reloaded when its file is modified.
+    pub filter: Option<IndexingFilterFile>,
+    /// The maximum lag of the indexer, whose breaches are alerted.
+    pub lag_slo: Option<LagSlo>,
 }
 
 impl Default for EngineConfig {
     fn default() -> Self {
-        Self { block_time: Duration::from_secs(1), start_block: 0, events_chunk_size: 1000 }
+        Self {
+            block_time: Duration::from_secs(1),
+            start_block: 0,
+            events_chunk_size: 1000,
+            filter: None,
+            lag_slo: None,
+        }
     }
 }
 
@@ -52,6 +95,9 @@ pub struct Engine<P: Provider + Sync> {
     config: EngineConfig,
     shutdown_tx: Sender<()>,
     block_tx: Option<BoundedSender<u64>>,
+    lag: LagMonitor,
+    /// The latest block of the chain when the lag was last computed.
+    lag_latest_block: Option<u64>,
 }
 
 struct UnprocessedEvent {
@@ -69,7 +115,18 @@ impl<P: Provider + Sync> Engine<P> {
         shutdown_tx: Sender<()>,
         block_tx: Option<BoundedSender<u64>>,
     ) -> Self {
-        Self { world, db, provider: Box::new(provider), processors, config, shutdown_tx, block_tx }
+        let lag = LagMonitor::new(world.address, config.lag_slo.clone());
+        Self {
+            world,
+            db,
+            provider: Box::new(provider),
+            processors,
+            config,
+            shutdown_tx,
+            block_tx,
+            lag,
+            lag_latest_block: None,
+        }
     }
 
     pub async fn start(&mut self) -> Result<()> {
@@ -88,9 +145,10 @@ impl<P: Provider + Sync> Engine<P> {
         loop {
             tokio::select! {
                 _ = shutdown_rx.recv() => {
-                    break Ok(());
+                    break self.db.flush_coalesced().await;
                 }
                 _ = async {
+                    self.reload_filter();
                     match self.sync_to_head(head).await {
                         Ok(latest_block_number) => {
                             head = latest_block_number;
@@ -98,6 +156,7 @@ impl<P: Provider + Sync> Engine<P> {
                         }
                         Err(e) => {
                             error!(target: LOG_TARGET, error = %e, "Getting block.");
+                            self.lag.record_error();
                             sleep(backoff_delay).await;
                             if backoff_delay < max_backoff_delay {
                                 backoff_delay *= 2;
@@ -111,52 +170,220 @@ impl<P: Provider + Sync> Engine<P> {
     }
 
     pub async fn sync_to_head(&mut self, from: u64) -> Result<u64> {
+        let from = self.handle_reorg().await?.unwrap_or(from);
         let latest_block_number = self.provider.block_hash_and_number().await?.block_number;
 
+        // computing the lag of an indexer behind the chain takes a request, so it is only computed
+        // again once the chain moved. it is only a metric, so failing to compute it is not fatal.
+        if from >= latest_block_number || self.lag_latest_block != Some(latest_block_number) {
+            match self.indexing_lag(from, latest_block_number).await {
+                Ok(lag) => {
+                    self.lag.record_lag(lag);
+                    self.lag_latest_block = Some(latest_block_number);
+                }
+                Err(error) => warn!(target: LOG_TARGET, %error, "Computing indexing lag."),
+            }
+        }
+
         if from < latest_block_number {
             // if `from` == 0, then the block may or may not be processed yet.
             let from = if from == 0 { from } else { from + 1 };
             self.sync_range(from, latest_block_number).await?;
-        };
+        } else {
+            // the coalesced updates are written once their window elapsed, even without new blocks
+            self.db.flush_coalesced_if_due().await?;
+        }
 
         Ok(latest_block_number)
     }
 
     pub async fn sync_range(&mut self, from: u64, to: u64) -> Result<()> {
         // Process all blocks from current to latest.
-        let get_events = |token: Option<String>| {
-            self.provider.get_events(
-                EventFilter {
-                    from_block: Some(BlockId::Number(from)),
-                    to_block: Some(BlockId::Number(to)),
-                    address: Some(self.world.address),
-                    keys: None,
-                },
-                token,
-                self.config.events_chunk_size,
-            )
+        let contracts = self.indexed_contracts();
+        let mut events = Vec::new();
+        for &address in &contracts {
+            events.extend(self.get_events(from, to, address).await?);
+        }
+
+        if contracts.len() > 1 {
+            self.sort_events(&mut events).await?;
+        }
+
+        let mut last_block: u64 = 0;
+        let mut processed_transactions = HashSet::new();
+        for event in events {
+            self.process(event, &mut last_block, &mut processed_transactions).await?;
+        }
+
+        self.db.execute().await?;
+
+        Ok(())
+    }
+
+    /// Returns the events emitted by the contract `address` from the block `from` to `to`.
+    async fn get_events(
+        &self,
+        from: u64,
+        to: u64,
+        address: FieldElement,
+    ) -> Result<Vec<EmittedEvent>> {
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from)),
+            to_block: Some(BlockId::Number(to)),
+            address: Some(address),
+            keys: None,
         };
 
         // handle next events pages
-        let mut events_pages = vec![get_events(None).await?];
+        let mut events = Vec::new();
+        let mut token = None;
+        loop {
+            let page = self
+                .provider
+                .get_events(filter.clone(), token, self.config.events_chunk_size)
+                .await?;
+            events.extend(page.events);
 
-        while let Some(token) = &events_pages.last().unwrap().continuation_token {
-            events_pages.push(get_events(Some(token.clone())).await?);
+            match page.continuation_token {
+                Some(next) => token = Some(next),
+                None => return Ok(events),
+            }
         }
+    }
 
-        let mut last_block: u64 = 0;
-        let mut last_transaction_hash: FieldElement = FieldElement::ZERO;
-        for events_page in events_pages {
-            for event in events_page.events {
-                self.process(event, &mut last_block, &mut last_transaction_hash).await?;
-            }
+    /// Sorts the events of several contracts by block and by the position of their transaction
+    /// in the block, the events of a same contract staying in their order.
+    async fn sort_events(&self, events: &mut [EmittedEvent]) -> Result<()> {
+        let blocks = events.iter().filter_map(|event| event.block_number).collect::<BTreeSet<_>>();
+
+        let mut positions = HashMap::new();
+        for block_number in blocks {
+            let transactions = match self
+                .provider
+                .get_block_with_tx_hashes(BlockId::Number(block_number))
+                .await?
+            {
+                MaybePendingBlockWithTxHashes::Block(block) => block.transactions,
+                MaybePendingBlockWithTxHashes::PendingBlock(block) => block.transactions,
+            };
+            positions.extend(transactions.into_iter().enumerate().map(|(i, hash)| (hash, i)));
         }
 
-        self.db.execute().await?;
+        events.sort_by_key(|event| {
+            let position = positions.get(&event.transaction_hash).copied();
+            (event.block_number.unwrap_or(u64::MAX), position.unwrap_or(usize::MAX))
+        });
 
         Ok(())
     }
 
+    /// Returns the addresses of the contracts whose events are processed: the world and the
+    /// contracts declared by the event processors.
+    fn indexed_contracts(&self) -> Vec<FieldElement> {
+        let mut contracts = vec![self.world.address];
+        for address in self.processors.event.iter().flat_map(|p| p.contract_addresses()) {
+            if !contracts.contains(&address) {
+                contracts.push(address);
+            }
+        }
+        contracts
+    }
+
+    /// Returns the lag of the indexer at `head` behind the block `latest` of the chain.
+    async fn indexing_lag(&self, head: u64, latest: u64) -> Result<IndexingLag> {
+        if head >= latest {
+            return Ok(IndexingLag::default());
+        }
+
+        let oldest_timestamp = self.get_block_timestamp(head + 1).await?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Ok(IndexingLag { blocks: latest - head, seconds: now.saturating_sub(oldest_timestamp) })
+    }
+
+    /// Checks that the latest indexed block is still part of the canonical chain. If it isn't, eg.
+    /// after a reorg or a restart of the sequencer, the data indexed from the orphaned blocks is
+    /// rolled back and the block to sync from is returned.
+    ///
+    /// The latest [REORG_DEPTH] indexed blocks are compared with the chain at once. If none of them
+    /// is canonical, they are rolled back and the older ones are compared.
+    async fn handle_reorg(&mut self) -> Result<Option<u64>> {
+        let mut orphaned = false;
+
+        loop {
+            let blocks = self.db.latest_blocks(REORG_DEPTH).await?;
+
+            // the latest indexed block that is still canonical
+            let mut ancestor = None;
+            for &(number, hash) in &blocks {
+                if self.is_canonical(number, hash).await? {
+                    ancestor = Some(number);
+                    break;
+                }
+            }
+
+            if ancestor == blocks.first().map(|&(number, _)| number) {
+                return Ok(orphaned.then(|| ancestor.unwrap_or(self.config.start_block)));
+            }
+
+            if !orphaned {
+                let block_number = blocks[0].0;
+                warn!(target: LOG_TARGET, %block_number, "Indexed block orphaned by a reorg.");
+                orphaned = true;
+            }
+
+            let rollback_to = match ancestor {
+                Some(number) => Some(number),
+                // every indexed block has been compared
+                None if blocks.len() < REORG_DEPTH as usize => None,
+                None => blocks[blocks.len() - 1].0.checked_sub(1),
+            };
+
+            self.db.rollback(rollback_to).await?;
+            info!(target: LOG_TARGET, block_number = ?rollback_to, "Rolled back orphaned blocks.");
+
+            if ancestor.is_some() || rollback_to.is_none() {
+                return Ok(Some(rollback_to.unwrap_or(self.config.start_block)));
+            }
+        }
+    }
+
+    /// Returns `true` if the block `number` of the chain has the hash `hash`.
+    async fn is_canonical(&self, number: u64, hash: FieldElement) -> Result<bool> {
+        match self.provider.get_block_with_tx_hashes(BlockId::Number(number)).await {
+            Ok(MaybePendingBlockWithTxHashes::Block(block)) => Ok(block.block_hash == hash),
+            Ok(MaybePendingBlockWithTxHashes::PendingBlock(_)) => Ok(false),
+            Err(ProviderError::StarknetError(StarknetError::BlockNotFound)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn reload_filter(&mut self) {
+        let Some(filter) = self.config.filter.as_mut() else { return };
+        match filter.reload_if_modified() {
+            Ok(true) => info!(target: LOG_TARGET, "Reloaded indexing filter."),
+            Ok(false) => {}
+            Err(e) => error!(target: LOG_TARGET, error = %e, "Reloading indexing filter."),
+        }
+    }
+
+    /// Returns `true` if the event passes the indexing filter, if any. Only the events of the
+    /// world are filtered.
+    fn is_indexed(
+        &self,
+        from_address: FieldElement,
+        keys: &[FieldElement],
+        data: &[FieldElement],
+    ) -> bool {
+        if from_address != self.world.address {
+            return true;
+        }
+
+        self.config
+            .filter
+            .as_ref()
+            .map_or(true, |filter| filter.filter().allows(from_address, keys, data))
+    }
+
     async fn get_block_timestamp(&self, block_number: u64) -> Result<u64> {
         match self.provider.get_block_with_tx_hashes(BlockId::Number(block_number)).await? {
             MaybePendingBlockWithTxHashes::Block(block) => Ok(block.timestamp),
@@ -168,7 +395,7 @@ impl<P: Provider + Sync> Engine<P> {
         &mut self,
         event: EmittedEvent,
         last_block: &mut u64,
-        last_transaction_hash: &mut FieldElement,
+        processed_transactions: &mut HashSet<FieldElement>,
     ) -> Result<()> {
         let block_number = match event.block_number {
             Some(block_number) => block_number,
@@ -180,23 +407,33 @@ impl<P: Provider + Sync> Engine<P> {
         let block_timestamp = self.get_block_timestamp(block_number).await?;
 
         if block_number > *last_block {
+            // the statements of the previous block are committed on their own, so that the
+            // batches of the backup log never span several blocks
+            self.db.execute().await?;
             *last_block = block_number;
+            processed_transactions.clear();
 
             if let Some(ref block_tx) = self.block_tx {
                 block_tx.send(block_number).await?;
             }
 
-            Self::process_block(self, block_number, block_timestamp, event.block_hash.unwrap())
-                .await?;
+            let block_hash = event.block_hash.unwrap();
+            Self::process_block(self, block_number, block_timestamp, block_hash).await?;
             info!(target: LOG_TARGET, block_number = %block_number, "Processed block.");
 
+            self.db.store_block(block_number, block_hash);
             self.db.set_head(block_number);
         }
 
+        // skip the transaction early if the event is filtered out, another event of the same
+        // transaction may still pass the filter
+        if !self.is_indexed(event.from_address, &event.keys, &event.data) {
+            return Ok(());
+        }
+
         // We index transaction only once for all events in the same transaction
         // Events are indexed with the transaction processing
-        if event.transaction_hash != *last_transaction_hash {
-            *last_transaction_hash = event.transaction_hash;
+        if processed_transactions.insert(event.transaction_hash) {
             let transaction = self.provider.get_transaction_by_hash(event.transaction_hash).await?;
             self.process_transaction_and_receipt(
                 event.transaction_hash,
@@ -240,13 +477,16 @@ impl<P: Provider + Sync> Engine<P> {
                 _ => return Ok(()),
             };
 
-            let mut world_event = false;
+            let contracts = self.indexed_contracts();
+            let mut indexed_event = false;
             for (event_idx, event) in events.iter().enumerate() {
-                if event.from_address != self.world.address {
+                if !contracts.contains(&event.from_address)
+                    || !self.is_indexed(event.from_address, &event.keys, &event.data)
+                {
                     continue;
                 }
 
-                world_event = true;
+                indexed_event = true;
                 let event_id =
                     format!("{:#064x}:{:#x}:{:#04x}", block_number, transaction_hash, event_idx);
 
@@ -261,7 +501,7 @@ impl<P: Provider + Sync> Engine<P> {
                 .await?;
             }
 
-            if world_event {
+            if indexed_event {
                 Self::process_transaction(
                     self,
                     block_number,
@@ -341,8 +581,9 @@ impl<P: Provider + Sync> Engine<P> {
         for processor in &self.processors.event {
             // If the processor has no event_key, means it's a catch-all processor.
             // We also validate the event
-            if (processor.event_key().is_empty()
-                || get_selector_from_name(&processor.event_key())? == event.keys[0])
+            if processes_contract(processor.as_ref(), self.world.address, event.from_address)
+                && (processor.event_key().is_empty()
+                    || get_selector_from_name(&processor.event_key())? == event.keys[0])
                 && processor.validate(event)
             {
                 processor
@@ -373,3 +614,17 @@ impl<P: Provider + Sync> Engine<P> {
         Ok(())
     }
 }
+
+/// Returns `true` if `processor` processes the events of the contract `address`.
+fn processes_contract<P: Provider + Sync>(
+    processor: &dyn EventProcessor<P>,
+    world_address: FieldElement,
+    address: FieldElement,
+) -> bool {
+    let contracts = processor.contract_addresses();
+    if contracts.is_empty() {
+        address == world_address
+    } else {
+        contracts.contains(&address)
+    }
+}
@@ -31,6 +31,8 @@ pub enum ParseError {
     FromByteSliceError(#[from] FromByteSliceError),
     #[error(transparent)]
     ParseIntError(#[from] ParseIntError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -41,6 +43,8 @@ pub enum QueryError {
     MissingParam(String),
     #[error("model not found: {0}")]
     ModelNotFound(String),
+    #[error("member not found: {0}")]
+    MemberNotFound(String),
     #[error("exceeds sqlite `JOIN` limit (64)")]
     SqliteJoinLimit,
 }
@@ -0,0 +1,266 @@
+//! Filters restricting the events processed by the engine.
+//!
+//! An [IndexingFilter] is a whitelist of world addresses, model names and event selectors, applied
+//! to the events of the indexed world. It is described in a TOML file, eg:
+//!
+//! ```toml
+//! worlds = ["0x64613f376f05242cfcab6ac55b9a1e4c1f5e4a3c7a3a2e1c7e7f0d5e2c9f9a1"]
+//! models = ["Position", "Moves"]
+//! # event names or selectors
+//! events = ["StoreSetRecord", "StoreDelRecord"]
+//! ```
+//!
+//! An empty or missing list allows everything. The events of the other contracts indexed by the
+//! processors are not filtered.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
+use starknet_crypto::FieldElement;
+
+/// The selectors of the world events whose first data element is the name of the model they
+/// concern.
+static MODEL_EVENTS: Lazy<HashSet<FieldElement>> = Lazy::new(|| {
+    ["ModelRegistered", "StoreSetRecord", "StoreDelRecord"]
+        .iter()
+        .map(|name| get_selector_from_name(name).expect("valid event name"))
+        .collect()
+});
+
+/// The selectors of the other events of the world. The world also emits event messages, whose
+/// selector is the one of their model.
+static WORLD_EVENTS: Lazy<HashSet<FieldElement>> = Lazy::new(|| {
+    [
+        "WorldSpawned",
+        "ContractDeployed",
+        "ContractUpgraded",
+        "WorldUpgraded",
+        "MetadataUpdate",
+        "WriterUpdated",
+        "OwnerUpdated",
+    ]
+    .iter()
+    .map(|name| get_selector_from_name(name).expect("valid event name"))
+    .collect()
+});
+
+/// The file representation of an [IndexingFilter].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexingFilterConfig {
+    /// The addresses of the worlds whose events are processed.
+    #[serde(default)]
+    pub worlds: Vec<FieldElement>,
+    /// The names of the models whose events are processed.
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// The names, or the selectors as hex strings, of the events that are processed.
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+/// A whitelist of the events to process. Events that don't pass the filter are skipped before
+/// their transaction is fetched.
+#[derive(Debug, Clone, Default)]
+pub struct IndexingFilter {
+    worlds: HashSet<FieldElement>,
+    models: HashSet<String>,
+    model_selectors: HashSet<FieldElement>,
+    events: HashSet<FieldElement>,
+}
+
+impl IndexingFilter {
+    pub fn new(config: IndexingFilterConfig) -> Result<Self> {
+        let model_selectors = config
+            .models
+            .iter()
+            .map(|name| get_selector_from_name(name))
+            .collect::<Result<_, _>>()
+            .context("Invalid model name in indexing filter")?;
+
+        let events = config
+            .events
+            .iter()
+            .map(|event| {
+                if event.starts_with("0x") {
+                    FieldElement::from_hex_be(event).map_err(anyhow::Error::from)
+                } else {
+                    get_selector_from_name(event).map_err(anyhow::Error::from)
+                }
+            })
+            .collect::<Result<_>>()
+            .context("Invalid event in indexing filter")?;
+
+        Ok(Self {
+            worlds: config.worlds.into_iter().collect(),
+            models: config.models.into_iter().collect(),
+            model_selectors,
+            events,
+        })
+    }
+
+    /// Loads a filter from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Reading indexing filter at {}", path.display()))?;
+        let config = toml::from_str(&content)
+            .with_context(|| format!("Parsing indexing filter at {}", path.display()))?;
+        Self::new(config)
+    }
+
+    /// Returns `true` if the event emitted by the world at `world_address` with the given keys and
+    /// data should be processed.
+    pub fn allows(
+        &self,
+        world_address: FieldElement,
+        keys: &[FieldElement],
+        data: &[FieldElement],
+    ) -> bool {
+        if !self.worlds.is_empty() && !self.worlds.contains(&world_address) {
+            return false;
+        }
+
+        let Some(selector) = keys.first() else { return self.events.is_empty() };
+
+        if !self.events.is_empty() && !self.events.contains(selector) {
+            return false;
+        }
+
+        if self.models.is_empty() {
+            return true;
+        }
+
+        if MODEL_EVENTS.contains(selector) {
+            return data
+                .first()
+                .and_then(|name| parse_cairo_short_string(name).ok())
+                .is_some_and(|name| self.models.contains(&name));
+        }
+
+        if WORLD_EVENTS.contains(selector) {
+            return true;
+        }
+
+        // any other event is an event message, whose selector is the one of its model
+        self.model_selectors.contains(selector)
+    }
+}
+
+/// An [IndexingFilter] loaded from a file, which is reloaded when the file is modified.
+#[derive(Debug)]
+pub struct IndexingFilterFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    filter: IndexingFilter,
+}
+
+impl IndexingFilterFile {
+    pub fn load<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let modified = modified_time(&path);
+        let filter = IndexingFilter::load(&path)?;
+        Ok(Self { path, modified, filter })
+    }
+
+    pub fn filter(&self) -> &IndexingFilter {
+        &self.filter
+    }
+
+    /// Reloads the filter if the file has been modified since it was last loaded. Returns whether
+    /// the filter has been reloaded. The current filter is kept if the file can't be loaded.
+    pub fn reload_if_modified(&mut self) -> Result<bool> {
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return Ok(false);
+        }
+
+        self.modified = modified;
+        self.filter = IndexingFilter::load(&self.path)?;
+        Ok(true)
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::utils::cairo_short_string_to_felt;
+    use starknet::macros::felt;
+
+    use super::*;
+
+    fn filter(content: &str) -> IndexingFilter {
+        IndexingFilter::new(toml::from_str(content).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn empty_filter_allows_everything() {
+        let filter = filter("");
+        let selector = get_selector_from_name("StoreSetRecord").unwrap();
+        assert!(filter.allows(felt!("0x1"), &[selector], &[]));
+        assert!(filter.allows(felt!("0x1"), &[felt!("0x1")], &[]));
+        assert!(filter.allows(felt!("0x2"), &[], &[]));
+    }
+
+    #[test]
+    fn filter_by_world_and_event() {
+        let filter = filter(
+            r#"
+            worlds = ["0x1"]
+            events = ["StoreSetRecord", "0x1234"]
+            "#,
+        );
+
+        let set_record = get_selector_from_name("StoreSetRecord").unwrap();
+        let del_record = get_selector_from_name("StoreDelRecord").unwrap();
+
+        assert!(filter.allows(felt!("0x1"), &[set_record], &[]));
+        assert!(filter.allows(felt!("0x1"), &[felt!("0x1234")], &[]));
+        assert!(!filter.allows(felt!("0x1"), &[del_record], &[]));
+        assert!(!filter.allows(felt!("0x1"), &[], &[]));
+        assert!(!filter.allows(felt!("0x2"), &[set_record], &[]));
+    }
+
+    #[test]
+    fn filter_by_model() {
+        let filter = filter(r#"models = ["Position"]"#);
+
+        let set_record = get_selector_from_name("StoreSetRecord").unwrap();
+        let position = cairo_short_string_to_felt("Position").unwrap();
+        let moves = cairo_short_string_to_felt("Moves").unwrap();
+
+        assert!(filter.allows(felt!("0x1"), &[set_record], &[position]));
+        assert!(!filter.allows(felt!("0x1"), &[set_record], &[moves]));
+
+        // event messages, whatever their number of keys
+        let position_selector = get_selector_from_name("Position").unwrap();
+        let moves_selector = get_selector_from_name("Moves").unwrap();
+        assert!(filter.allows(felt!("0x1"), &[position_selector, felt!("0x1"), felt!("0x2")], &[]));
+        assert!(filter.allows(felt!("0x1"), &[position_selector], &[]));
+        assert!(!filter.allows(felt!("0x1"), &[moves_selector, felt!("0x1"), felt!("0x2")], &[]));
+        assert!(!filter.allows(felt!("0x1"), &[moves_selector], &[]));
+
+        // world events unrelated to models, even with several keys
+        let metadata_update = get_selector_from_name("MetadataUpdate").unwrap();
+        let writer_updated = get_selector_from_name("WriterUpdated").unwrap();
+        assert!(filter.allows(felt!("0x1"), &[metadata_update], &[]));
+        assert!(filter.allows(felt!("0x1"), &[writer_updated, felt!("0x1"), felt!("0x2")], &[]));
+    }
+
+    #[test]
+    fn invalid_filter() {
+        assert!(toml::from_str::<IndexingFilterConfig>(r#"unknown = []"#).is_err());
+        assert!(toml::from_str::<IndexingFilterConfig>(r#"worlds = ["0xzz"]"#).is_err());
+        let config = IndexingFilterConfig { events: vec!["0xzz".into()], ..Default::default() };
+        assert!(IndexingFilter::new(config).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Monitoring of the lag of the indexer behind the head of the chain.
+//!
+//! The lag of the indexed World, in blocks and in seconds, and the errors of the indexer are
+//! exported as metrics labeled with the address of the World. When a [LagSlo] is configured, the
+//! indexer is expected to stay within it: the breaches of the SLO are logged and, if the SLO has a
+//! webhook, posted to it as alerts in the format of the Alertmanager webhooks, once when the lag
+//! exceeds the SLO and once when it's back within it.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dojo_metrics::Metrics;
+use metrics::{Counter, Gauge};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Url;
+use serde::Serialize;
+use starknet_crypto::FieldElement;
+use tracing::{info, warn};
+
+pub(crate) const LOG_TARGET: &str = "torii_core::lag";
+
+/// The maximum lag of the indexer behind the head of the chain.
+#[derive(Debug, Clone, Default)]
+pub struct LagSlo {
+    /// The maximum number of blocks the indexer may be behind the head.
+    pub max_blocks: Option<u64>,
+    /// The maximum number of seconds the oldest block not indexed yet may have been waiting for.
+    pub max_seconds: Option<u64>,
+    /// The URL the alerts are posted to.
+    pub webhook: Option<Url>,
+}
+
+impl LagSlo {
+    pub fn is_breached(&self, lag: &IndexingLag) -> bool {
+        self.max_blocks.is_some_and(|max| lag.blocks > max)
+            || self.max_seconds.is_some_and(|max| lag.seconds > max)
+    }
+}
+
+/// How far the indexer is behind the head of the chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexingLag {
+    /// The number of blocks not indexed yet.
+    pub blocks: u64,
+    /// The age of the oldest block not indexed yet, zero if the indexer is at the head.
+    pub seconds: u64,
+}
+
+#[derive(Metrics)]
+#[metrics(scope = "indexer")]
+struct IndexerMetrics {
+    /// The number of blocks the indexer is behind the head of the chain.
+    lag_blocks: Gauge,
+    /// The age of the oldest block not indexed yet, in seconds.
+    lag_seconds: Gauge,
+    /// Whether the lag exceeds the SLO, 1 if it does.
+    slo_breached: Gauge,
+    /// The number of errors the indexer ran into.
+    errors_total: Counter,
+    /// The unix timestamp of the last error of the indexer.
+    last_error_timestamp_seconds: Gauge,
+}
+
+pub(crate) struct LagMonitor {
+    world: FieldElement,
+    slo: Option<LagSlo>,
+    breached: bool,
+    metrics: IndexerMetrics,
+    client: reqwest::Client,
+}
+
+impl LagMonitor {
+    pub fn new(world: FieldElement, slo: Option<LagSlo>) -> Self {
+        let metrics = IndexerMetrics::new_with_labels(&[("world", format!("{world:#x}"))]);
+        Self { world, slo, breached: false, metrics, client: reqwest::Client::new() }
+    }
+
+    pub fn record_lag(&mut self, lag: IndexingLag) {
+        self.metrics.lag_blocks.set(lag.blocks as f64);
+        self.metrics.lag_seconds.set(lag.seconds as f64);
+
+        let Some(slo) = &self.slo else { return };
+        let breached = slo.is_breached(&lag);
+        self.metrics.slo_breached.set(if breached { 1.0 } else { 0.0 });
+
+        if breached == self.breached {
+            return;
+        }
+        self.breached = breached;
+
+        if breached {
+            warn!(target: LOG_TARGET, blocks = %lag.blocks, seconds = %lag.seconds, "Indexing lag exceeds the SLO.");
+        } else {
+            info!(target: LOG_TARGET, "Indexing lag back within the SLO.");
+        }
+
+        if let Some(webhook) = slo.webhook.clone() {
+            let payload = AlertPayload::new(self.world, slo, lag, breached);
+            let request = self.client.post(webhook).timeout(Duration::from_secs(10));
+
+            // the indexer doesn't wait for the alert to be delivered
+            tokio::spawn(async move {
+                let body = serde_json::to_vec(&payload).expect("alert is serializable");
+                let response = request.header(CONTENT_TYPE, "application/json").body(body).send();
+                if let Err(e) = response.await.and_then(|r| r.error_for_status()) {
+                    warn!(target: LOG_TARGET, error = %e, "Posting indexing lag alert.");
+                }
+            });
+        }
+    }
+
+    pub fn record_error(&self) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.metrics.errors_total.increment(1);
+        self.metrics.last_error_timestamp_seconds.set(now.as_secs() as f64);
+    }
+}
+
+/// The body of the alerts posted to the webhook of the SLO, as sent by Alertmanager.
+#[derive(Debug, Serialize)]
+struct AlertPayload {
+    status: &'static str,
+    alerts: Vec<Alert>,
+}
+
+#[derive(Debug, Serialize)]
+struct Alert {
+    status: &'static str,
+    labels: BTreeMap<&'static str, String>,
+    annotations: BTreeMap<&'static str, String>,
+}
+
+impl AlertPayload {
+    fn new(world: FieldElement, slo: &LagSlo, lag: IndexingLag, firing: bool) -> Self {
+        let status = if firing { "firing" } else { "resolved" };
+
+        let labels = BTreeMap::from([
+            ("alertname", "ToriiIndexingLag".to_string()),
+            ("world", format!("{world:#x}")),
+        ]);
+
+        let limits = [
+            slo.max_blocks.map(|max| format!("{max} blocks")),
+            slo.max_seconds.map(|max| format!("{max} seconds")),
+        ];
+        let limits = limits.into_iter().flatten().collect::<Vec<_>>().join(" or ");
+
+        let summary = if firing {
+            format!("Torii is {} blocks and {} seconds behind the chain", lag.blocks, lag.seconds)
+        } else {
+            "Torii is back within its indexing lag SLO".to_string()
+        };
+        let annotations = BTreeMap::from([
+            ("summary", summary),
+            (
+                "description",
+                format!("The indexer of World {world:#x} is expected to lag by at most {limits}."),
+            ),
+        ]);
+
+        Self { status, alerts: vec![Alert { status, labels, annotations }] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn slo_breaches() {
+        let slo = LagSlo { max_blocks: Some(10), max_seconds: Some(60), webhook: None };
+        assert!(!slo.is_breached(&IndexingLag { blocks: 10, seconds: 60 }));
+        assert!(slo.is_breached(&IndexingLag { blocks: 11, seconds: 0 }));
+        assert!(slo.is_breached(&IndexingLag { blocks: 1, seconds: 61 }));
+        assert!(!LagSlo::default().is_breached(&IndexingLag { blocks: 100, seconds: 100 }));
+
+        let mut monitor = LagMonitor::new(FieldElement::ONE, Some(slo));
+        monitor.record_lag(IndexingLag { blocks: 11, seconds: 0 });
+        assert!(monitor.breached);
+        monitor.record_lag(IndexingLag::default());
+        assert!(!monitor.breached);
+    }
+
+    #[test]
+    fn alert_payload() {
+        let slo = LagSlo { max_blocks: Some(10), max_seconds: None, webhook: None };
+        let lag = IndexingLag { blocks: 12, seconds: 30 };
+
+        let payload = serde_json::to_value(AlertPayload::new(FieldElement::ONE, &slo, lag, true));
+        assert_eq!(
+            payload.unwrap(),
+            json!({
+                "status": "firing",
+                "alerts": [{
+                    "status": "firing",
+                    "labels": { "alertname": "ToriiIndexingLag", "world": "0x1" },
+                    "annotations": {
+                        "summary": "Torii is 12 blocks and 30 seconds behind the chain",
+                        "description": "The indexer of World 0x1 is expected to lag by at most 10 \
+                                        blocks."
+                    }
+                }]
+            })
+        );
+    }
+}
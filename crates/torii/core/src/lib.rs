@@ -3,12 +3,19 @@ use sqlx::FromRow;
 
 use crate::types::SQLFieldElement;
 
+pub mod backend;
+pub mod backup;
 pub mod cache;
+pub mod coalesce;
 pub mod engine;
 pub mod error;
+pub mod filter;
+pub mod lag;
 pub mod model;
+pub mod privacy;
 pub mod processors;
 pub mod query_queue;
+pub mod resolver;
 pub mod simple_broker;
 pub mod sql;
 pub mod types;
@@ -12,6 +12,7 @@ use starknet::core::utils::get_selector_from_name;
 
 use super::error::{self, Error};
 use crate::error::{ParseError, QueryError};
+use crate::utils::utc_dt_string_from_timestamp;
 
 pub struct ModelSQLReader {
     /// The name of the model
@@ -159,6 +160,16 @@ pub fn parse_sql_model_members(model: &str, model_members_all: &[SqlModelMember]
                     }),
                 },
 
+                "Array" => {
+                    Member { key: child.key, name: child.name.to_owned(), ty: Ty::Array(vec![]) }
+                }
+
+                "ByteArray" => Member {
+                    key: child.key,
+                    name: child.name.to_owned(),
+                    ty: Ty::ByteArray(String::new()),
+                },
+
                 ty => {
                     unimplemented!("unimplemented type_enum: {ty}");
                 }
@@ -304,6 +315,17 @@ pub fn map_row_to_ty(path: &str, struct_ty: &mut Struct, row: &SqliteRow) -> Res
                 let path = [path, &member.name].join("$");
                 map_row_to_ty(&path, struct_ty, row)?;
             }
+            Ty::Array(items) => {
+                let value = row.try_get::<Vec<u8>, &str>(&column_name)?;
+                *items = value
+                    .chunks(32)
+                    .map(FieldElement::from_byte_slice_be)
+                    .collect::<Result<_, _>>()
+                    .map_err(ParseError::FromByteSliceError)?;
+            }
+            Ty::ByteArray(string) => {
+                *string = row.try_get::<String, &str>(&column_name)?;
+            }
             ty => {
                 unimplemented!("unimplemented type_enum: {ty}");
             }
@@ -313,6 +335,81 @@ pub fn map_row_to_ty(path: &str, struct_ty: &mut Struct, row: &SqliteRow) -> Res
     Ok(())
 }
 
+/// The latest UNIX timestamp whose RFC 3339 representation, as the execution times of the history
+/// are stored, still sorts like the timestamp itself.
+const MAX_HISTORY_TIMESTAMP: u64 = 253402300799;
+
+/// A point of the history of the indexed world. The state at that point is the one at the end of
+/// the last block indexed up to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryPoint {
+    /// The end of the block of this number.
+    Block(u64),
+    /// The end of the last block with a timestamp lower than or equal to this UNIX timestamp.
+    Timestamp(u64),
+}
+
+impl HistoryPoint {
+    /// Returns the SQL condition on the rows of `entity_model_history` selecting the changes made
+    /// up to this point.
+    fn condition(&self) -> String {
+        match self {
+            Self::Block(number) => {
+                format!("block_number <= {}", i64::try_from(*number).unwrap_or(i64::MAX))
+            }
+            Self::Timestamp(timestamp) => {
+                let timestamp = (*timestamp).min(MAX_HISTORY_TIMESTAMP);
+                format!("executed_at <= '{}'", utc_dt_string_from_timestamp(timestamp))
+            }
+        }
+    }
+}
+
+/// Returns the ids and values of the models of the entity `entity_id` at the history point `at`,
+/// from the history of its changes. Models that didn't exist yet or had been deleted at that point
+/// are omitted.
+pub async fn entity_models_at(
+    pool: &Pool<Sqlite>,
+    entity_id: &str,
+    at: HistoryPoint,
+) -> Result<Vec<(String, Ty)>, Error> {
+    // the latest change of each model of the entity up to the point
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(&format!(
+        "SELECT model_id, data FROM entity_model_history AS h WHERE entity_id = ? AND id = \
+         (SELECT MAX(id) FROM entity_model_history WHERE entity_id = h.entity_id AND model_id = \
+         h.model_id AND {}) ORDER BY model_id",
+        at.condition()
+    ))
+    .bind(entity_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .filter_map(|(model_id, data)| data.map(|data| (model_id, data)))
+        .map(|(model_id, data)| {
+            let model = serde_json::from_str(&data).map_err(ParseError::Json)?;
+            Ok((model_id, model))
+        })
+        .collect()
+}
+
+/// Returns a SQL table expression with the columns of the `entities` table, whose rows are the
+/// entities as they were at the history point `at`: the entities with at least one model at that
+/// point, the event and the execution time of each being the ones of its latest change up to it.
+pub fn entities_at_query(at: HistoryPoint) -> String {
+    let condition = at.condition();
+    format!(
+        "(SELECT entity_id AS id, keys, event_id, executed_at, created_at, executed_at AS \
+         updated_at FROM (SELECT entity_id, keys, event_id, executed_at, MIN(executed_at) OVER \
+         (PARTITION BY entity_id) AS created_at, ROW_NUMBER() OVER (PARTITION BY entity_id ORDER \
+         BY id DESC) AS entity_rank FROM entity_model_history WHERE {condition}) WHERE \
+         entity_rank = 1 AND entity_id IN (SELECT entity_id FROM (SELECT entity_id, data, \
+         ROW_NUMBER() OVER (PARTITION BY entity_id, model_id ORDER BY id DESC) AS model_rank FROM \
+         entity_model_history WHERE {condition}) WHERE model_rank = 1 AND data IS NOT NULL)) AS \
+         entities_at"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
@@ -0,0 +1,338 @@
+//! Models and members excluded from the public responses of the GraphQL and gRPC servers.
+//!
+//! Private data is indexed like any other data, it is only hidden from the servers' responses and
+//! can't be used to filter entities. Since the raw events of a model with private members carry
+//! the private data too, they are left out of the events served, and the transactions emitting them
+//! are served without their calldata. The private models and members are described in a TOML
+//! file, eg:
+//!
+//! ```toml
+//! # models whose data is hidden entirely
+//! models = ["Hand"]
+//! # members hidden from their model, as `Model.member`
+//! members = ["Player.secret"]
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use dojo_types::schema::Ty;
+use serde::Deserialize;
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::macros::selector;
+use starknet_crypto::FieldElement;
+
+use crate::sql::FELT_DELIMITER;
+
+/// The file representation of a [Privacy].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrivacyConfig {
+    /// The names of the models whose data is private.
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// The private members, as `Model.member`.
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+/// The private models and members. Everything is public by default.
+#[derive(Debug, Clone, Default)]
+pub struct Privacy {
+    models: HashSet<String>,
+    members: HashMap<String, HashSet<String>>,
+    /// The names of the models with private members as short strings, which the records set and
+    /// deleted by the World start their data with.
+    record_tables: HashSet<FieldElement>,
+    /// The selectors of the names of the models with private members, which the event messages
+    /// start their keys with.
+    message_selectors: HashSet<FieldElement>,
+}
+
+impl Privacy {
+    pub fn new(config: PrivacyConfig) -> Result<Self> {
+        let mut members: HashMap<String, HashSet<String>> = HashMap::new();
+        for member in config.members {
+            let (model, member) = member
+                .split_once('.')
+                .filter(|(model, member)| !model.is_empty() && !member.is_empty())
+                .ok_or_else(|| {
+                    anyhow!("Invalid private member `{member}`, expected `Model.member`")
+                })?;
+            members.entry(model.to_string()).or_default().insert(member.to_string());
+        }
+
+        let models: HashSet<String> = config.models.into_iter().collect();
+        let mut record_tables = HashSet::new();
+        let mut message_selectors = HashSet::new();
+        for model in models.iter().chain(members.keys()) {
+            // a name which isn't a short string isn't the name of any model
+            if let Ok(table) = cairo_short_string_to_felt(model) {
+                record_tables.insert(table);
+                message_selectors.insert(get_selector_from_name(model)?);
+            }
+        }
+
+        Ok(Self { models, members, record_tables, message_selectors })
+    }
+
+    /// Loads the private models and members from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Reading privacy config at {}", path.display()))?;
+        let config = toml::from_str(&content)
+            .with_context(|| format!("Parsing privacy config at {}", path.display()))?;
+        Self::new(config)
+    }
+
+    pub fn is_private_model(&self, model: &str) -> bool {
+        self.models.contains(model)
+    }
+
+    pub fn is_private_member(&self, model: &str, member: &str) -> bool {
+        self.is_private_model(model)
+            || self.members.get(model).is_some_and(|members| members.contains(member))
+    }
+
+    /// Returns `true` if some members of `model` are private, or if the whole model is.
+    pub fn has_private_members(&self, model: &str) -> bool {
+        self.is_private_model(model) || self.members.contains_key(model)
+    }
+
+    /// Returns `true` if the event, whose keys and data are stored as by [Sql](crate::sql::Sql),
+    /// carries the data of a model with private members.
+    pub fn is_private_event(&self, keys: &str, data: &str) -> bool {
+        let first = |felts: &str| {
+            felts.split(FELT_DELIMITER).next().and_then(|felt| FieldElement::from_str(felt).ok())
+        };
+
+        match first(keys) {
+            Some(key)
+                if key == selector!("StoreSetRecord") || key == selector!("StoreDelRecord") =>
+            {
+                first(data).is_some_and(|table| self.record_tables.contains(&table))
+            }
+            Some(key) => self.message_selectors.contains(&key),
+            None => false,
+        }
+    }
+
+    /// Returns the SQL condition matching the rows of the `events` table for which
+    /// [Self::is_private_event] is `true`, or `None` if there are none.
+    pub fn private_events_condition(&self) -> Option<String> {
+        let records = format!(
+            "keys IN ('{:#x}{FELT_DELIMITER}', '{:#x}{FELT_DELIMITER}')",
+            selector!("StoreSetRecord"),
+            selector!("StoreDelRecord")
+        );
+
+        let conditions = self
+            .record_tables
+            .iter()
+            .map(|table| format!("({records} AND data LIKE '{table:#x}{FELT_DELIMITER}%')"))
+            .chain(
+                self.message_selectors
+                    .iter()
+                    .map(|selector| format!("keys LIKE '{selector:#x}{FELT_DELIMITER}%'")),
+            )
+            .collect::<Vec<_>>();
+
+        (!conditions.is_empty()).then(|| conditions.join(" OR "))
+    }
+
+    /// Returns the `events` table without the private events, to be selected from.
+    pub fn public_events_table(&self) -> String {
+        match self.private_events_condition() {
+            Some(condition) => format!("(SELECT * FROM events WHERE NOT ({condition}))"),
+            None => "events".to_string(),
+        }
+    }
+
+    /// Returns the `transactions` table, without the calldata of the transactions emitting private
+    /// events since it usually holds the private data they carry, to be selected from.
+    pub fn public_transactions_table(&self) -> String {
+        let Some(condition) = self.private_events_condition() else {
+            return "transactions".to_string();
+        };
+
+        format!(
+            "(SELECT id, transaction_hash, sender_address, CASE WHEN transaction_hash IN (SELECT \
+             transaction_hash FROM events WHERE {condition}) THEN '' ELSE calldata END AS \
+             calldata, max_fee, signature, nonce, transaction_type, executed_at, created_at FROM \
+             transactions)"
+        )
+    }
+
+    /// Returns the `models` table without the private models, to be selected from as `models`.
+    pub fn public_models_table(&self) -> String {
+        if self.models.is_empty() {
+            return "models".to_string();
+        }
+
+        let names = self.models.iter().map(|name| format!("'{}'", name.replace('\'', "''")));
+        let names = names.collect::<Vec<_>>().join(", ");
+        format!("(SELECT * FROM models WHERE name NOT IN ({names})) AS models")
+    }
+
+    /// Returns the `search_documents` table without the documents of the private models and
+    /// members, nested members included, to be selected from as `search_documents`.
+    pub fn public_search_documents_table(&self) -> String {
+        let quote = |name: &str| format!("'{}'", name.replace('\'', "''"));
+
+        let conditions = self
+            .models
+            .iter()
+            .map(|model| format!("model = {}", quote(model)))
+            .chain(self.members.iter().flat_map(|(model, members)| {
+                members.iter().map(move |member| {
+                    format!(
+                        "(model = {} AND (member = {} OR substr(member, 1, {}) = {}))",
+                        quote(model),
+                        quote(member),
+                        member.len() + 1,
+                        quote(&format!("{member}.")),
+                    )
+                })
+            }))
+            .collect::<Vec<_>>();
+
+        if conditions.is_empty() {
+            return "search_documents".to_string();
+        }
+
+        format!(
+            "(SELECT * FROM search_documents WHERE NOT ({})) AS search_documents",
+            conditions.join(" OR ")
+        )
+    }
+
+    /// Returns the public part of the model `schema`, without its private members, or `None` if
+    /// the whole model is private.
+    pub fn public_schema(&self, mut schema: Ty) -> Option<Ty> {
+        let name = schema.name();
+        if self.is_private_model(&name) {
+            return None;
+        }
+
+        if let (Ty::Struct(s), Some(members)) = (&mut schema, self.members.get(&name)) {
+            s.children.retain(|member| !members.contains(&member.name));
+        }
+
+        Some(schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct};
+
+    use super::*;
+
+    fn privacy(content: &str) -> Privacy {
+        Privacy::new(toml::from_str(content).unwrap()).unwrap()
+    }
+
+    fn model(name: &str, members: &[&str]) -> Ty {
+        Ty::Struct(Struct {
+            name: name.to_string(),
+            children: members
+                .iter()
+                .map(|member| Member {
+                    name: member.to_string(),
+                    ty: Ty::Primitive(Primitive::U32(None)),
+                    key: false,
+                })
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn empty_config_is_public() {
+        let privacy = privacy("");
+        assert!(!privacy.is_private_model("Position"));
+        assert!(!privacy.is_private_member("Position", "x"));
+        assert_eq!(
+            privacy.public_schema(model("Position", &["x", "y"])),
+            Some(model("Position", &["x", "y"]))
+        );
+    }
+
+    #[test]
+    fn private_models_and_members() {
+        let privacy = privacy(
+            r#"
+            models = ["Hand"]
+            members = ["Player.secret"]
+            "#,
+        );
+
+        assert!(privacy.is_private_model("Hand"));
+        assert!(privacy.is_private_member("Hand", "cards"));
+        assert!(privacy.is_private_member("Player", "secret"));
+        assert!(!privacy.is_private_member("Player", "score"));
+        assert!(privacy.has_private_members("Player"));
+        assert!(!privacy.has_private_members("Position"));
+
+        assert_eq!(privacy.public_schema(model("Hand", &["cards"])), None);
+        assert_eq!(
+            privacy.public_schema(model("Player", &["score", "secret"])),
+            Some(model("Player", &["score"]))
+        );
+    }
+
+    #[test]
+    fn private_events() {
+        let privacy = privacy(
+            r#"
+            models = ["Hand"]
+            members = ["Player.secret"]
+            "#,
+        );
+
+        let set_record = format!("{:#x}/", selector!("StoreSetRecord"));
+        let del_record = format!("{:#x}/", selector!("StoreDelRecord"));
+        let table =
+            |name: &str| format!("{:#x}/0x1/0x2/", cairo_short_string_to_felt(name).unwrap());
+        let message = |name: &str| format!("{:#x}/0x1/", get_selector_from_name(name).unwrap());
+
+        assert!(privacy.is_private_event(&set_record, &table("Hand")));
+        assert!(privacy.is_private_event(&del_record, &table("Player")));
+        assert!(!privacy.is_private_event(&set_record, &table("Position")));
+        assert!(privacy.is_private_event(&message("Player"), "0x1/"));
+        assert!(!privacy.is_private_event(&message("Position"), "0x1/"));
+        assert!(!privacy.is_private_event("", ""));
+
+        assert_eq!(Privacy::default().private_events_condition(), None);
+        assert_eq!(Privacy::default().public_events_table(), "events");
+        assert_eq!(Privacy::default().public_models_table(), "models");
+    }
+
+    #[test]
+    fn private_search_documents() {
+        let privacy = privacy(
+            r#"
+            models = ["Hand"]
+            members = ["Player.o'secret"]
+            "#,
+        );
+
+        assert_eq!(
+            privacy.public_search_documents_table(),
+            "(SELECT * FROM search_documents WHERE NOT (model = 'Hand' OR (model = 'Player' AND \
+             (member = 'o''secret' OR substr(member, 1, 9) = 'o''secret.')))) AS search_documents"
+        );
+        assert_eq!(Privacy::default().public_search_documents_table(), "search_documents");
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(toml::from_str::<PrivacyConfig>(r#"unknown = []"#).is_err());
+        let config = PrivacyConfig { members: vec!["Player".into()], ..Default::default() };
+        assert!(Privacy::new(config).is_err());
+    }
+}
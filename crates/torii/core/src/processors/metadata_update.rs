@@ -1,29 +1,33 @@
-use std::time::Duration;
+use std::sync::Arc;
 
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use base64::engine::general_purpose;
-use base64::Engine as _;
+use dojo_world::contracts::events::MetadataUpdate;
 use dojo_world::contracts::world::WorldContractReader;
 use dojo_world::metadata::{Uri, WorldMetadata};
-use reqwest::Client;
 use starknet::core::types::{Event, TransactionReceipt};
-use starknet::core::utils::parse_cairo_short_string;
 use starknet::providers::Provider;
 use starknet_crypto::FieldElement;
-use tokio_util::bytes::Bytes;
 use tracing::{error, info};
 
 use super::EventProcessor;
+use crate::resolver::ContentResolver;
 use crate::sql::Sql;
 
-const IPFS_URL: &str = "https://cartridge.infura-ipfs.io/ipfs/";
-const MAX_RETRY: u8 = 3;
-
 pub(crate) const LOG_TARGET: &str = "torii_core::processors::metadata_update";
 
-#[derive(Default)]
-pub struct MetadataUpdateProcessor;
+/// Stores the metadata URIs set for the resources of the World, and resolves their content along
+/// with the images it references.
+#[derive(Debug, Default)]
+pub struct MetadataUpdateProcessor {
+    resolver: Arc<ContentResolver>,
+}
+
+impl MetadataUpdateProcessor {
+    pub fn new(resolver: Arc<ContentResolver>) -> Self {
+        Self { resolver }
+    }
+}
 
 #[async_trait]
 impl<P> EventProcessor<P> for MetadataUpdateProcessor
@@ -57,18 +61,9 @@ where
         _event_id: &str,
         event: &Event,
     ) -> Result<(), Error> {
-        let resource = &event.data[0];
-        let uri_len: u8 = event.data[1].try_into().unwrap();
-
-        let uri_str = if uri_len > 0 {
-            event.data[2..=uri_len as usize + 1]
-                .iter()
-                .map(parse_cairo_short_string)
-                .collect::<Result<Vec<_>, _>>()?
-                .concat()
-        } else {
-            "".to_string()
-        };
+        let metadata = MetadataUpdate::decode(&event.data)?;
+        let resource = &metadata.resource;
+        let uri_str = metadata.uri()?;
 
         info!(
             target: LOG_TARGET,
@@ -78,18 +73,27 @@ where
         );
         db.set_metadata(resource, &uri_str, block_timestamp);
 
+        // the indexing waits for a resolution to end rather than spawning them without bound
+        let permit = self.resolver.reserve().await;
         let db = db.clone();
+        let resolver = Arc::clone(&self.resolver);
         let resource = *resource;
         tokio::spawn(async move {
-            try_retrieve(db, resource, uri_str).await;
+            try_retrieve(db, resolver, resource, uri_str).await;
+            drop(permit);
         });
 
         Ok(())
     }
 }
 
-async fn try_retrieve(mut db: Sql, resource: FieldElement, uri_str: String) {
-    match metadata(uri_str.clone()).await {
+async fn try_retrieve(
+    mut db: Sql,
+    resolver: Arc<ContentResolver>,
+    resource: FieldElement,
+    uri_str: String,
+) {
+    match metadata(&mut db, &resolver, &uri_str).await {
         Ok((metadata, icon_img, cover_img)) => {
             db.update_metadata(&resource, &uri_str, &metadata, &icon_img, &cover_img)
                 .await
@@ -97,7 +101,7 @@ async fn try_retrieve(mut db: Sql, resource: FieldElement, uri_str: String) {
             info!(
                 target: LOG_TARGET,
                 resource = %format!("{:#x}", resource),
-                "Updated resource metadata from ipfs."
+                "Updated resource metadata."
             );
         }
         Err(e) => {
@@ -112,51 +116,36 @@ async fn try_retrieve(mut db: Sql, resource: FieldElement, uri_str: String) {
     }
 }
 
-async fn metadata(uri_str: String) -> Result<(WorldMetadata, Option<String>, Option<String>)> {
-    let uri = Uri::Ipfs(uri_str);
-    let cid = uri.cid().ok_or("Uri is malformed").map_err(Error::msg)?;
-
-    let bytes = fetch_content(cid, MAX_RETRY).await?;
-    let metadata: WorldMetadata = serde_json::from_str(std::str::from_utf8(&bytes)?)?;
+async fn metadata(
+    db: &mut Sql,
+    resolver: &ContentResolver,
+    uri_str: &str,
+) -> Result<(WorldMetadata, Option<String>, Option<String>)> {
+    let metadata: WorldMetadata = resolver.resolve(db, uri_str).await?.json()?;
 
-    let icon_img = fetch_image(&metadata.icon_uri).await;
-    let cover_img = fetch_image(&metadata.cover_uri).await;
+    let icon_img = fetch_image(db, resolver, &metadata.icon_uri).await;
+    let cover_img = fetch_image(db, resolver, &metadata.cover_uri).await;
 
     Ok((metadata, icon_img, cover_img))
 }
 
-async fn fetch_image(image_uri: &Option<Uri>) -> Option<String> {
-    if let Some(uri) = image_uri {
-        let data = fetch_content(uri.cid()?, MAX_RETRY).await.ok()?;
-        let encoded = general_purpose::STANDARD.encode(data);
-        return Some(encoded);
-    }
-
-    None
-}
-
-async fn fetch_content(cid: &str, mut retries: u8) -> Result<Bytes> {
-    while retries > 0 {
-        let response = Client::new().get(format!("{IPFS_URL}{}", cid)).send().await;
-
-        match response {
-            Ok(response) => return response.bytes().await.map_err(|e| e.into()),
-            Err(e) => {
-                retries -= 1;
-                if retries > 0 {
-                    info!(
-                        target: LOG_TARGET,
-                        error = %e,
-                        "Fetch uri."
-                    );
-                    tokio::time::sleep(Duration::from_secs(3)).await;
-                }
-            }
+async fn fetch_image(
+    db: &mut Sql,
+    resolver: &ContentResolver,
+    image_uri: &Option<Uri>,
+) -> Option<String> {
+    let uri = match image_uri.as_ref()? {
+        Uri::Ipfs(uri) => uri.clone(),
+        Uri::Http(url) => url.to_string(),
+        // the files of the machine the World was migrated from can't be resolved
+        Uri::File(_) => return None,
+    };
+
+    match resolver.resolve(db, &uri).await {
+        Ok(content) => Some(content.base64()),
+        Err(e) => {
+            error!(target: LOG_TARGET, %uri, error = %e, "Retrieving image.");
+            None
         }
     }
-
-    Err(Error::msg(format!(
-        "Failed to pull data from IPFS after {} attempts, cid: {}",
-        MAX_RETRY, cid
-    )))
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use dojo_world::contracts::world::WorldContractReader;
@@ -5,6 +7,8 @@ use starknet::core::types::{Event, Transaction, TransactionReceipt};
 use starknet::providers::Provider;
 use starknet_crypto::FieldElement;
 
+use crate::engine::Processors;
+use crate::resolver::ContentResolver;
 use crate::sql::Sql;
 
 pub mod event_message;
@@ -14,8 +18,14 @@ pub mod store_del_record;
 pub mod store_set_record;
 pub mod store_transaction;
 
+use self::event_message::EventMessageProcessor;
+use self::metadata_update::MetadataUpdateProcessor;
+use self::register_model::RegisterModelProcessor;
+use self::store_del_record::StoreDelRecordProcessor;
+use self::store_set_record::StoreSetRecordProcessor;
+use self::store_transaction::StoreTransactionProcessor;
+
 const MODEL_INDEX: usize = 0;
-const NUM_KEYS_INDEX: usize = 1;
 
 #[async_trait]
 pub trait EventProcessor<P>
@@ -24,6 +34,11 @@ where
 {
     fn event_key(&self) -> String;
 
+    /// The addresses of the contracts whose events are processed, the world's if empty.
+    fn contract_addresses(&self) -> Vec<FieldElement> {
+        Vec::new()
+    }
+
     fn event_keys_as_string(&self, event: &Event) -> String {
         event.keys.iter().map(|i| format!("{:#064x}", i)).collect::<Vec<_>>().join(",")
     }
@@ -70,3 +85,42 @@ pub trait TransactionProcessor<P: Provider + Sync> {
         transaction: &Transaction,
     ) -> Result<(), Error>;
 }
+
+/// A set of block, transaction and event processors that is registered to the engine as a whole.
+///
+/// Downstream crates implement this trait to index their own contracts (eg. a game specific
+/// marketplace) from a custom Torii binary, while reusing the engine, storage and API layers. The
+/// event processors of other contracts than the world declare their addresses with
+/// [EventProcessor::contract_addresses], so that the engine fetches their events too.
+pub trait Processor<P: Provider + Sync> {
+    /// Adds the processors to `processors`.
+    fn register(&self, processors: &mut Processors<P>);
+}
+
+/// The processors indexing the Dojo world itself.
+#[derive(Debug, Default)]
+pub struct WorldProcessor {
+    /// Resolves the metadata of the resources of the world.
+    resolver: Arc<ContentResolver>,
+}
+
+impl WorldProcessor {
+    pub fn new(resolver: Arc<ContentResolver>) -> Self {
+        Self { resolver }
+    }
+}
+
+impl<P> Processor<P> for WorldProcessor
+where
+    P: Provider + Send + Sync,
+{
+    fn register(&self, processors: &mut Processors<P>) {
+        processors
+            .add_event(RegisterModelProcessor)
+            .add_event(StoreSetRecordProcessor)
+            .add_event(MetadataUpdateProcessor::new(Arc::clone(&self.resolver)))
+            .add_event(StoreDelRecordProcessor)
+            .add_event(EventMessageProcessor)
+            .add_transaction(StoreTransactionProcessor);
+    }
+}
@@ -1,9 +1,9 @@
 use anyhow::{Error, Ok, Result};
 use async_trait::async_trait;
+use dojo_world::contracts::events::ModelRegistered;
 use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::{Event, TransactionReceipt};
-use starknet::core::utils::parse_cairo_short_string;
 use starknet::providers::Provider;
 use tracing::{debug, info};
 
@@ -47,7 +47,8 @@ where
         _event_id: &str,
         event: &Event,
     ) -> Result<(), Error> {
-        let name = parse_cairo_short_string(&event.data[0])?;
+        let ModelRegistered { name, class_hash, address: contract_address, .. } =
+            ModelRegistered::decode(&event.data)?;
 
         let model = world.model_reader(&name).await?;
         let schema = model.schema().await?;
@@ -56,9 +57,6 @@ where
         let unpacked_size: u32 = model.unpacked_size().await?.try_into()?;
         let packed_size: u32 = model.packed_size().await?.try_into()?;
 
-        let class_hash = event.data[1];
-        let contract_address = event.data[3];
-
         info!(
             target: LOG_TARGET,
             name = %name,
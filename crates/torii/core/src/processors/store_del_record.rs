@@ -1,14 +1,14 @@
 use anyhow::{Error, Ok, Result};
 use async_trait::async_trait;
+use dojo_world::contracts::events::StoreDelRecord;
 use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::{Event, TransactionReceipt};
-use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
+use starknet::core::utils::get_selector_from_name;
 use starknet::providers::Provider;
 use tracing::info;
 
 use super::EventProcessor;
-use crate::processors::{MODEL_INDEX, NUM_KEYS_INDEX};
 use crate::sql::Sql;
 
 pub(crate) const LOG_TARGET: &str = "torii_core::processors::store_del_record";
@@ -42,13 +42,13 @@ where
         &self,
         _world: &WorldContractReader<P>,
         db: &mut Sql,
-        _block_number: u64,
-        _block_timestamp: u64,
+        block_number: u64,
+        block_timestamp: u64,
         _transaction_receipt: &TransactionReceipt,
-        _event_id: &str,
+        event_id: &str,
         event: &Event,
     ) -> Result<(), Error> {
-        let name = parse_cairo_short_string(&event.data[MODEL_INDEX])?;
+        let StoreDelRecord { table: name, keys } = StoreDelRecord::decode(&event.data)?;
         info!(
             target: LOG_TARGET,
             name = %name,
@@ -58,9 +58,9 @@ where
         // this is temporary until the model name hash is precomputed
         let model = db.model(&format!("{:#x}", get_selector_from_name(&name)?)).await?;
 
-        let keys_start = NUM_KEYS_INDEX + 1;
-        let keys = event.data[keys_start..].to_vec();
         let entity = model.schema().await?;
+
+        db.store_entity_history(&keys, &name, None, event_id, block_number, block_timestamp)?;
         db.delete_entity(keys, entity).await?;
         Ok(())
     }
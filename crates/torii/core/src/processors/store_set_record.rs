@@ -1,14 +1,14 @@
 use anyhow::{Error, Ok, Result};
 use async_trait::async_trait;
+use dojo_world::contracts::events::StoreSetRecord;
 use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::world::WorldContractReader;
 use starknet::core::types::{Event, TransactionReceipt};
-use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
+use starknet::core::utils::get_selector_from_name;
 use starknet::providers::Provider;
 use tracing::info;
 
 use super::EventProcessor;
-use crate::processors::{MODEL_INDEX, NUM_KEYS_INDEX};
 use crate::sql::Sql;
 
 pub(crate) const LOG_TARGET: &str = "torii_core::processors::store_set_record";
@@ -42,13 +42,13 @@ where
         &self,
         _world: &WorldContractReader<P>,
         db: &mut Sql,
-        _block_number: u64,
+        block_number: u64,
         block_timestamp: u64,
         _transaction_receipt: &TransactionReceipt,
         event_id: &str,
         event: &Event,
     ) -> Result<(), Error> {
-        let name = parse_cairo_short_string(&event.data[MODEL_INDEX])?;
+        let StoreSetRecord { table: name, keys, values } = StoreSetRecord::decode(&event.data)?;
         info!(
             target: LOG_TARGET,
             name = %name,
@@ -58,21 +58,19 @@ where
         // this is temporary until the model name hash is precomputed
         let model = db.model(&format!("{:#x}", get_selector_from_name(&name)?)).await?;
 
-        let keys_start = NUM_KEYS_INDEX + 1;
-        let keys_end: usize = keys_start + usize::from(u8::try_from(event.data[NUM_KEYS_INDEX])?);
-        let keys = event.data[keys_start..keys_end].to_vec();
-
-        // keys_end is already the length of the values array.
-
-        let values_start = keys_end + 1;
-        let values_end: usize = values_start + usize::from(u8::try_from(event.data[keys_end])?);
-
-        let values = event.data[values_start..values_end].to_vec();
-        let mut keys_and_unpacked = [keys, values].concat();
+        let mut keys_and_unpacked = [keys.as_slice(), values.as_slice()].concat();
 
         let mut entity = model.schema().await?;
         entity.deserialize(&mut keys_and_unpacked)?;
 
+        db.store_entity_history(
+            &keys,
+            &name,
+            Some(&entity),
+            event_id,
+            block_number,
+            block_timestamp,
+        )?;
         db.set_entity(entity, event_id, block_timestamp).await?;
         Ok(())
     }
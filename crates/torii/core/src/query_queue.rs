@@ -1,26 +1,55 @@
 use std::collections::VecDeque;
+use std::sync::Arc;
 
-use sqlx::{Executor, Pool, Sqlite};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
 use starknet_crypto::FieldElement;
 
-#[derive(Debug, Clone)]
+use crate::backend::StorageBackend;
+use crate::backup::IncrementalBackup;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Argument {
     Null,
     Int(i64),
     Bool(bool),
     String(String),
     FieldElement(FieldElement),
+    Blob(Vec<u8>),
 }
 
 #[derive(Debug, Clone)]
 pub struct QueryQueue {
     pool: Pool<Sqlite>,
     queue: VecDeque<(String, Vec<Argument>)>,
+    /// The head the queued statements move the indexer to, if they do.
+    head: Option<u64>,
+    backup: Option<Arc<IncrementalBackup>>,
+    /// Another database every batch is written to once it's written to SQLite.
+    mirror: Option<Arc<dyn StorageBackend>>,
 }
 
 impl QueryQueue {
     pub fn new(pool: Pool<Sqlite>) -> Self {
-        QueryQueue { pool, queue: VecDeque::new() }
+        QueryQueue { pool, queue: VecDeque::new(), head: None, backup: None, mirror: None }
+    }
+
+    /// Appends every executed batch to the log of `backup`.
+    pub fn with_backup(mut self, backup: Arc<IncrementalBackup>) -> Self {
+        self.backup = Some(backup);
+        self
+    }
+
+    /// Writes every executed batch to `mirror` too.
+    pub fn with_mirror(mut self, mirror: Arc<dyn StorageBackend>) -> Self {
+        self.mirror = Some(mirror);
+        self
+    }
+
+    /// Records that the queued statements move the head of the indexer to `head`, which is
+    /// logged along with them in the backup.
+    pub fn mark_head(&mut self, head: u64) {
+        self.head = Some(head);
     }
 
     pub fn enqueue<S: Into<String>>(&mut self, statement: S, arguments: Vec<Argument>) {
@@ -32,27 +61,18 @@ impl QueryQueue {
     }
 
     pub async fn execute_all(&mut self) -> sqlx::Result<u64> {
-        let mut total_affected = 0_u64;
-        let mut tx = self.pool.begin().await?;
-
-        while let Some((statement, arguments)) = self.queue.pop_front() {
-            let mut query = sqlx::query(&statement);
-
-            for arg in &arguments {
-                query = match arg {
-                    Argument::Null => query.bind(None::<String>),
-                    Argument::Int(integer) => query.bind(integer),
-                    Argument::Bool(bool) => query.bind(bool),
-                    Argument::String(string) => query.bind(string),
-                    Argument::FieldElement(felt) => query.bind(format!("{:#x}", felt)),
-                }
-            }
-
-            total_affected += tx.execute(query).await?.rows_affected();
+        let statements = self.queue.drain(..).collect::<Vec<_>>();
+        let head = self.head.take();
+        let affected = self.pool.execute_batch(&statements).await?;
+
+        if let Some(mirror) = &self.mirror {
+            mirror.execute_batch(&statements).await?;
         }
 
-        tx.commit().await?;
+        if let Some(backup) = &self.backup {
+            backup.append(head, &statements)?;
+        }
 
-        Ok(total_affected)
+        Ok(affected)
     }
 }
@@ -0,0 +1,462 @@
+//! Resolution of the external content referenced by the indexed resources, eg. the metadata of a
+//! World and its images, or the metadata of the tokens indexed by a custom processor.
+//!
+//! The URIs are resolved through gateways:
+//!
+//! - `ipfs://<cid>[/<path>]` through the IPFS gateways, as `<gateway>/<cid>[/<path>]`
+//! - `ar://<id>[/<path>]` through the Arweave gateways, as `<gateway>/<id>[/<path>]`
+//! - `http://` and `https://` URIs as they are
+//!
+//! The gateways of a scheme are tried in order, each of them with retries after an exponentially
+//! growing backoff, until one of them returns the content. The contents larger than the size
+//! limit are rejected without being downloaded completely.
+//!
+//! The URIs come from the indexed events, so unless explicitly allowed, the downloads from the
+//! loopback, private and link-local addresses, and from `localhost`, are refused, including the
+//! ones redirected to from a public address. The number of contents resolved at once is bounded,
+//! the resolutions run in the background waiting for their turn with [`ContentResolver::reserve`].
+//!
+//! Resolved contents are stored in the `resolved_contents` table, where they can be queried by
+//! their URI. The contents of the IPFS and Arweave URIs can't change, so they are read back from
+//! the table instead of being downloaded again.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose;
+use base64::Engine as _;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+use serde::de::DeserializeOwned;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::bytes::{Bytes, BytesMut};
+use tracing::debug;
+
+use crate::sql::Sql;
+
+pub(crate) const LOG_TARGET: &str = "torii_core::resolver";
+
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://cartridge.infura-ipfs.io/ipfs/";
+pub const DEFAULT_ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+
+/// The maximum number of redirects followed by a download.
+const MAX_REDIRECTS: usize = 10;
+
+/// The encoding of a stored content: as is if it's valid UTF-8, base64 otherwise.
+pub const UTF8_ENCODING: &str = "utf-8";
+pub const BASE64_ENCODING: &str = "base64";
+
+#[derive(Debug, Clone)]
+pub struct ResolverConfig {
+    pub ipfs_gateways: Vec<Url>,
+    pub arweave_gateways: Vec<Url>,
+    /// The number of attempts made with every gateway.
+    pub max_attempts: u32,
+    /// The wait before the second attempt with a gateway, doubled after every attempt.
+    pub backoff: Duration,
+    /// The timeout of a download.
+    pub timeout: Duration,
+    /// The maximum size of a content, in bytes.
+    pub max_size: usize,
+    /// The maximum number of contents resolved at once.
+    pub max_concurrent: usize,
+    /// Whether the contents can be downloaded from private addresses, eg. from a local gateway.
+    pub allow_private_addresses: bool,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            ipfs_gateways: vec![Url::parse(DEFAULT_IPFS_GATEWAY).expect("valid url")],
+            arweave_gateways: vec![Url::parse(DEFAULT_ARWEAVE_GATEWAY).expect("valid url")],
+            max_attempts: 3,
+            backoff: Duration::from_secs(1),
+            timeout: Duration::from_secs(30),
+            max_size: 10 * 1024 * 1024,
+            max_concurrent: 16,
+            allow_private_addresses: false,
+        }
+    }
+}
+
+/// The content a URI resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedContent {
+    pub uri: String,
+    /// The media type of the content, as reported by the gateway.
+    pub content_type: Option<String>,
+    pub data: Bytes,
+}
+
+impl ResolvedContent {
+    /// Parses the content as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.data)?)
+    }
+
+    /// Returns the content encoded in base64, eg. to store an image.
+    pub fn base64(&self) -> String {
+        general_purpose::STANDARD.encode(&self.data)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("content of {uri} exceeds the size limit of {max_size} bytes")]
+pub struct ContentTooLarge {
+    pub uri: String,
+    pub max_size: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("refusing to download from {host}, which is a private address")]
+pub struct PrivateAddress {
+    pub host: String,
+}
+
+#[derive(Debug)]
+pub struct ContentResolver {
+    config: ResolverConfig,
+    client: Client,
+    permits: Arc<Semaphore>,
+}
+
+impl Default for ContentResolver {
+    fn default() -> Self {
+        Self::new(ResolverConfig::default())
+    }
+}
+
+impl ContentResolver {
+    pub fn new(config: ResolverConfig) -> Self {
+        let builder = Client::builder();
+        let builder = if config.allow_private_addresses {
+            builder.redirect(Policy::limited(MAX_REDIRECTS))
+        } else {
+            // the host names are checked once resolved, and the addresses as they are
+            builder.dns_resolver(Arc::new(PublicResolver)).redirect(Policy::custom(|attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if let Err(e) = check_public_host(attempt.url()) {
+                    attempt.error(e)
+                } else {
+                    attempt.follow()
+                }
+            }))
+        };
+        let client = builder.build().expect("valid http client");
+        let permits = Arc::new(Semaphore::new(config.max_concurrent.max(1)));
+
+        Self { config, client, permits }
+    }
+
+    pub fn config(&self) -> &ResolverConfig {
+        &self.config
+    }
+
+    /// Waits for a resolution to be allowed to run, until the returned permit is dropped. Meant to
+    /// be held by the tasks resolving contents in the background, so that they are not spawned
+    /// faster than they complete.
+    pub async fn reserve(&self) -> OwnedSemaphorePermit {
+        Arc::clone(&self.permits).acquire_owned().await.expect("semaphore never closed")
+    }
+
+    /// Resolves the content of `uri` and stores it, unless it was already stored and can't have
+    /// changed since.
+    pub async fn resolve(&self, db: &mut Sql, uri: &str) -> Result<ResolvedContent> {
+        if is_immutable(uri) {
+            if let Some(content) = db.resolved_content(uri).await? {
+                debug!(target: LOG_TARGET, %uri, "Resolved content from storage.");
+                return Ok(content);
+            }
+        }
+
+        let content = self.fetch(uri).await?;
+        db.store_resolved_content(&content).await?;
+
+        Ok(content)
+    }
+
+    /// Downloads the content of `uri` from the first gateway returning it, without storing it.
+    pub async fn fetch(&self, uri: &str) -> Result<ResolvedContent> {
+        let urls = gateway_urls(&self.config, uri)?;
+        if urls.is_empty() {
+            bail!("No gateway configured to resolve {uri}");
+        }
+
+        let mut last_error = None;
+        for url in urls {
+            let mut backoff = self.config.backoff;
+            for attempt in 1..=self.config.max_attempts {
+                match self.download(uri, url.clone()).await {
+                    Ok((content_type, data)) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            %uri,
+                            %url,
+                            size = %data.len(),
+                            "Resolved content."
+                        );
+                        return Ok(ResolvedContent { uri: uri.to_string(), content_type, data });
+                    }
+                    // a larger content is not going to be smaller on another gateway
+                    Err(e) if e.is::<ContentTooLarge>() => return Err(e),
+                    Err(e) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            %uri,
+                            %url,
+                            %attempt,
+                            error = %e,
+                            "Resolving content."
+                        );
+                        last_error = Some(e);
+                    }
+                }
+
+                if attempt < self.config.max_attempts {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+        }
+
+        Err(anyhow!(
+            "Failed to resolve {uri}: {}",
+            last_error.map_or("no attempt made".to_string(), |e| e.to_string())
+        ))
+    }
+
+    async fn download(&self, uri: &str, url: Url) -> Result<(Option<String>, Bytes)> {
+        let max_size = self.config.max_size;
+        let too_large = || ContentTooLarge { uri: uri.to_string(), max_size };
+
+        if !self.config.allow_private_addresses {
+            check_public_host(&url)?;
+        }
+
+        let mut response =
+            self.client.get(url).timeout(self.config.timeout).send().await?.error_for_status()?;
+        if response.content_length().is_some_and(|length| length > max_size as u64) {
+            return Err(too_large().into());
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        // the length isn't always reported, so the limit is also enforced while downloading
+        let mut data = BytesMut::new();
+        while let Some(chunk) = response.chunk().await? {
+            if data.len() + chunk.len() > max_size {
+                return Err(too_large().into());
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        Ok((content_type, data.freeze()))
+    }
+}
+
+/// Resolves the host names of the downloads, failing if any of their addresses is private.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await?.collect::<Vec<_>>();
+            if addrs.iter().any(|addr| is_private(addr.ip())) {
+                return Err(PrivateAddress { host: name.as_str().to_string() }.into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Fails if the host of `url` is `localhost` or a private address. The other host names are
+/// checked once resolved, by [`PublicResolver`].
+fn check_public_host(url: &Url) -> Result<(), PrivateAddress> {
+    let host = url.host_str().unwrap_or_default();
+    // the IPv6 addresses are enclosed in brackets
+    let is_private_host = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => is_private(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            domain.is_empty() || domain == "localhost" || domain.ends_with(".localhost")
+        }
+    };
+
+    if is_private_host {
+        return Err(PrivateAddress { host: host.to_string() });
+    }
+    Ok(())
+}
+
+/// Whether `ip` is a loopback, private, link-local or otherwise non public address.
+fn is_private(ip: IpAddr) -> bool {
+    fn is_private_v4(ip: Ipv4Addr) -> bool {
+        let [a, b, ..] = ip.octets();
+        ip.is_loopback()
+            || ip.is_private()
+            || ip.is_link_local()
+            || ip.is_unspecified()
+            || ip.is_broadcast()
+            // the shared address space of the carrier-grade NATs, 100.64.0.0/10
+            || (a == 100 && (b & 0xc0) == 64)
+    }
+
+    fn is_private_v6(ip: Ipv6Addr) -> bool {
+        let segment = ip.segments()[0];
+        ip.is_loopback()
+            || ip.is_unspecified()
+            // the unique local addresses, fc00::/7, and the link-local ones, fe80::/10
+            || (segment & 0xfe00) == 0xfc00
+            || (segment & 0xffc0) == 0xfe80
+            || ip.to_ipv4_mapped().is_some_and(is_private_v4)
+    }
+
+    match ip {
+        IpAddr::V4(ip) => is_private_v4(ip),
+        IpAddr::V6(ip) => is_private_v6(ip),
+    }
+}
+
+/// Whether the content of `uri` is addressed by its hash, and thus can't change.
+fn is_immutable(uri: &str) -> bool {
+    uri.starts_with("ipfs://") || uri.starts_with("ar://")
+}
+
+/// Returns the URLs `uri` is downloaded from, in the order they are tried.
+fn gateway_urls(config: &ResolverConfig, uri: &str) -> Result<Vec<Url>> {
+    let (gateways, path) = if let Some(path) = uri.strip_prefix("ipfs://") {
+        (&config.ipfs_gateways, path)
+    } else if let Some(path) = uri.strip_prefix("ar://") {
+        (&config.arweave_gateways, path)
+    } else if uri.starts_with("http://") || uri.starts_with("https://") {
+        return Ok(vec![Url::parse(uri)?]);
+    } else {
+        bail!("Unsupported URI {uri}");
+    };
+
+    if path.is_empty() {
+        bail!("Malformed URI {uri}");
+    }
+
+    gateways
+        .iter()
+        .map(|gateway| {
+            // without a trailing slash, the last segment of the gateway would be replaced
+            let mut gateway = gateway.clone();
+            if !gateway.path().ends_with('/') {
+                gateway.set_path(&format!("{}/", gateway.path()));
+            }
+            Ok(gateway.join(path)?)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use starknet_crypto::FieldElement;
+
+    use super::*;
+
+    fn config(ipfs_gateways: &[&str]) -> ResolverConfig {
+        ResolverConfig {
+            ipfs_gateways: ipfs_gateways.iter().map(|url| Url::parse(url).unwrap()).collect(),
+            arweave_gateways: vec![Url::parse("https://arweave.net").unwrap()],
+            max_attempts: 2,
+            backoff: Duration::ZERO,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn urls_of_gateways() {
+        let config = config(&["https://ipfs.io/ipfs/", "https://dweb.link/ipfs"]);
+
+        let urls = gateway_urls(&config, "ipfs://QmCid/metadata.json").unwrap();
+        assert_eq!(
+            urls.iter().map(Url::as_str).collect::<Vec<_>>(),
+            [
+                "https://ipfs.io/ipfs/QmCid/metadata.json",
+                "https://dweb.link/ipfs/QmCid/metadata.json"
+            ]
+        );
+
+        let urls = gateway_urls(&config, "ar://TxId").unwrap();
+        assert_eq!(urls[0].as_str(), "https://arweave.net/TxId");
+
+        let urls = gateway_urls(&config, "https://example.com/token/1.json").unwrap();
+        assert_eq!(urls[0].as_str(), "https://example.com/token/1.json");
+
+        assert!(gateway_urls(&config, "ipfs://").is_err());
+        assert!(gateway_urls(&config, "file:///etc/passwd").is_err());
+    }
+
+    #[tokio::test]
+    async fn refuse_private_addresses() {
+        for url in [
+            "http://127.0.0.1:8080/metadata.json",
+            "http://10.0.0.1/metadata.json",
+            "http://192.168.1.1/metadata.json",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]/metadata.json",
+            "http://[::ffff:172.16.0.1]/metadata.json",
+            "http://localhost:5050/metadata.json",
+            "http://api.localhost/metadata.json",
+        ] {
+            let error = check_public_host(&Url::parse(url).unwrap());
+            assert!(error.is_err(), "{url} is private");
+        }
+        assert!(check_public_host(&Url::parse("https://8.8.8.8/metadata.json").unwrap()).is_ok());
+        assert!(check_public_host(&Url::parse("https://ipfs.io/ipfs/QmCid").unwrap()).is_ok());
+
+        let resolver = ContentResolver::new(config(&["http://127.0.0.1:1/ipfs/"]));
+        let error = resolver.download("ipfs://QmCid", Url::parse("http://127.0.0.1:1/").unwrap());
+        assert!(error.await.unwrap_err().is::<PrivateAddress>());
+        // the host names resolving to private addresses are refused too
+        let name = Name::from_str("localhost").unwrap();
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn resolve_stored_content() {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:").unwrap();
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        let mut db = Sql::new(pool, FieldElement::ZERO).await.unwrap();
+        // nothing listens on the gateway, so that only the stored contents can be resolved
+        let resolver = ContentResolver::new(config(&["http://127.0.0.1:1/ipfs/"]));
+
+        let json = ResolvedContent {
+            uri: "ipfs://QmJson".to_string(),
+            content_type: Some("application/json".to_string()),
+            data: Bytes::from_static(br#"{"name":"Sword"}"#),
+        };
+        let image = ResolvedContent {
+            uri: "ipfs://QmImage".to_string(),
+            content_type: None,
+            data: Bytes::from_static(&[0x89, 0x50, 0x4e, 0x47, 0xff]),
+        };
+        db.store_resolved_content(&json).await.unwrap();
+        db.store_resolved_content(&image).await.unwrap();
+
+        let resolved = resolver.resolve(&mut db, "ipfs://QmJson").await.unwrap();
+        assert_eq!(resolved, json);
+        assert_eq!(resolved.json::<serde_json::Value>().unwrap()["name"], "Sword");
+        assert_eq!(resolver.resolve(&mut db, "ipfs://QmImage").await.unwrap(), image);
+
+        let error = resolver.resolve(&mut db, "ipfs://QmMissing").await.unwrap_err();
+        assert!(error.to_string().starts_with("Failed to resolve ipfs://QmMissing"));
+    }
+}
@@ -1,10 +1,17 @@
+use std::collections::HashSet;
 use std::convert::TryInto;
+use std::path::Path;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use chrono::Utc;
-use dojo_types::primitive::Primitive;
+use base64::engine::general_purpose;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use dojo_types::primitive::{Primitive, SqlType};
 use dojo_types::schema::Ty;
+use dojo_world::contracts::events;
+use dojo_world::contracts::model::ModelReader;
 use dojo_world::metadata::WorldMetadata;
 use sqlx::pool::PoolConnection;
 use sqlx::{Pool, Sqlite};
@@ -13,14 +20,27 @@ use starknet::core::utils::get_selector_from_name;
 use starknet_crypto::poseidon_hash_many;
 
 use super::World;
+use crate::backend::StorageBackend;
+use crate::backup::IncrementalBackup;
+use crate::coalesce::{Coalescer, CoalescingConfig, PendingUpdate};
 use crate::model::ModelSQLReader;
 use crate::query_queue::{Argument, QueryQueue};
+use crate::resolver::{ResolvedContent, BASE64_ENCODING, UTF8_ENCODING};
 use crate::simple_broker::SimpleBroker;
 use crate::types::{Entity as EntityUpdated, Event as EventEmitted, Model as ModelRegistered};
 use crate::utils::{must_utc_datetime_from_timestamp, utc_dt_string_from_timestamp};
 
 pub const FELT_DELIMITER: &str = "/";
 
+/// The kind of the documents of the `search_documents` table holding the keys of the entities.
+pub const SEARCH_ENTITY_KEY: &str = "ENTITY_KEY";
+/// The kind of the documents of the `search_documents` table holding the string members of the
+/// models of the entities.
+pub const SEARCH_MODEL_MEMBER: &str = "MODEL_MEMBER";
+/// The kind of the documents of the `search_documents` table holding the name and description of
+/// the metadata.
+pub const SEARCH_METADATA: &str = "METADATA";
+
 #[cfg(test)]
 #[path = "sql_test.rs"]
 mod test;
@@ -30,24 +50,43 @@ pub struct Sql {
     world_address: FieldElement,
     pub pool: Pool<Sqlite>,
     query_queue: QueryQueue,
+    coalescer: Option<Coalescer>,
 }
 
 impl Sql {
     pub async fn new(pool: Pool<Sqlite>, world_address: FieldElement) -> Result<Self> {
         let mut query_queue = QueryQueue::new(pool.clone());
 
-        query_queue.enqueue(
-            "INSERT OR IGNORE INTO indexers (id, head) VALUES (?, ?)",
-            vec![Argument::FieldElement(world_address), Argument::Int(0)],
-        );
-        query_queue.enqueue(
-            "INSERT OR IGNORE INTO worlds (id, world_address) VALUES (?, ?)",
-            vec![Argument::FieldElement(world_address), Argument::FieldElement(world_address)],
-        );
+        for (statement, arguments) in world_statements(world_address) {
+            query_queue.enqueue(statement, arguments);
+        }
 
         query_queue.execute_all().await?;
 
-        Ok(Self { pool, world_address, query_queue })
+        Ok(Self { pool, world_address, query_queue, coalescer: None })
+    }
+
+    /// Starts an incremental backup of the database in `backup_dir`. The clones of the returned
+    /// `Sql` write to the same backup.
+    pub async fn with_backup(mut self, backup_dir: &Path) -> Result<Self> {
+        let backup = IncrementalBackup::start(&self.pool, backup_dir, self.head().await?).await?;
+        self.query_queue = self.query_queue.with_backup(Arc::new(backup));
+        Ok(self)
+    }
+
+    /// Writes the indexed data to `mirror` too, eg. a Postgres database, see
+    /// [backend](crate::backend). The reads still go through the SQLite pool.
+    pub async fn with_mirror(mut self, mirror: Arc<dyn StorageBackend>) -> Result<Self> {
+        mirror.execute_batch(&world_statements(self.world_address)).await?;
+        self.query_queue = self.query_queue.with_mirror(mirror);
+        Ok(self)
+    }
+
+    /// Coalesces the successive updates of the entities of the models of `config`, see
+    /// [coalesce](crate::coalesce).
+    pub fn with_coalescing(mut self, config: CoalescingConfig) -> Self {
+        self.coalescer = Some(Coalescer::new(config));
+        self
     }
 
     pub async fn head(&self) -> Result<u64> {
@@ -60,12 +99,292 @@ impl Sql {
     }
 
     pub fn set_head(&mut self, head: u64) {
+        // the head is written once the pending updates are, so that their blocks are indexed
+        // again after a restart
+        if let Some(coalescer) = self.coalescer.as_mut().filter(|c| c.is_pending()) {
+            coalescer.defer_head(head);
+            return;
+        }
+
+        self.query_queue.mark_head(head);
         let head = Argument::Int(head.try_into().expect("doesn't fit in u64"));
         let id = Argument::String(format!("{:#x}", self.world_address));
 
         self.query_queue.enqueue("UPDATE indexers SET head = ? WHERE id = ?", vec![head, id]);
     }
 
+    /// Records the hash of an indexed block.
+    pub fn store_block(&mut self, block_number: u64, block_hash: FieldElement) {
+        self.query_queue.enqueue(
+            "INSERT OR REPLACE INTO blocks (id, hash) VALUES (?, ?)",
+            vec![
+                Argument::Int(block_number.try_into().expect("doesn't fit in i64")),
+                Argument::FieldElement(block_hash),
+            ],
+        );
+    }
+
+    /// Returns the numbers and hashes of the indexed blocks, from the latest to the oldest.
+    pub async fn blocks(&self) -> Result<Vec<(u64, FieldElement)>> {
+        self.blocks_with_limit(-1).await
+    }
+
+    /// Returns the numbers and hashes of the `count` latest indexed blocks, from the latest to the
+    /// oldest.
+    pub async fn latest_blocks(&self, count: u64) -> Result<Vec<(u64, FieldElement)>> {
+        self.blocks_with_limit(count.try_into()?).await
+    }
+
+    async fn blocks_with_limit(&self, limit: i64) -> Result<Vec<(u64, FieldElement)>> {
+        let rows: Vec<(i64, String)> =
+            sqlx::query_as("SELECT id, hash FROM blocks ORDER BY id DESC LIMIT ?")
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.into_iter()
+            .map(|(number, hash)| Ok((number.try_into()?, FieldElement::from_str(&hash)?)))
+            .collect()
+    }
+
+    /// Rolls back the data indexed from the blocks after `block_number`, or from all the blocks if
+    /// `None`. The entities are reverted to their value at `block_number` using their history, the
+    /// event messages last set and the models first registered in the rolled back blocks are
+    /// deleted, the metadata is reverted to its latest update in the kept blocks, the events and
+    /// transactions of the rolled back blocks are deleted, and the head is reset.
+    pub async fn rollback(&mut self, block_number: Option<u64>) -> Result<()> {
+        self.flush_coalesced().await?;
+
+        let first_orphan: i64 = block_number.map_or(0, |number| number + 1).try_into()?;
+
+        let changed: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT DISTINCT h.entity_id, h.model_id, m.name FROM entity_model_history h JOIN \
+             models m ON m.id = h.model_id WHERE h.block_number >= ?",
+        )
+        .bind(first_orphan)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (entity_id, model_id, model_name) in changed {
+            let previous: Option<(Option<String>, String, DateTime<Utc>)> = sqlx::query_as(
+                "SELECT data, event_id, executed_at FROM entity_model_history WHERE entity_id = ? \
+                 AND model_id = ? AND block_number < ? ORDER BY id DESC LIMIT 1",
+            )
+            .bind(&entity_id)
+            .bind(&model_id)
+            .bind(first_orphan)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            match previous {
+                Some((Some(data), event_id, executed_at)) => {
+                    let entity: Ty = serde_json::from_str(&data)?;
+                    let block_timestamp = executed_at.timestamp().try_into()?;
+                    self.write_entity(entity, &event_id, block_timestamp).await?;
+                }
+                // the model didn't exist or was deleted at the block
+                _ => {
+                    let schema = self.model(&model_name).await?.schema().await?;
+                    self.build_delete_entity_queries_recursive(
+                        vec![schema.name()],
+                        &entity_id,
+                        &schema,
+                        false,
+                    );
+                    self.query_queue.enqueue(
+                        "DELETE FROM entity_model WHERE entity_id = ? AND model_id = ?",
+                        vec![Argument::String(entity_id.clone()), Argument::String(model_id)],
+                    );
+                    self.query_queue.enqueue(
+                        "DELETE FROM entities WHERE id = ? AND NOT EXISTS (SELECT 1 FROM \
+                         entity_model WHERE entity_id = ?)",
+                        vec![Argument::String(entity_id.clone()), Argument::String(entity_id)],
+                    );
+                    self.query_queue.execute_all().await?;
+                }
+            }
+        }
+
+        // the ids of the events and transactions start with the number of their block
+        let id_boundary = format!("{:#064x}", first_orphan);
+        self.rollback_event_messages(&id_boundary).await?;
+        self.rollback_metadata(&id_boundary).await?;
+        self.rollback_models(&id_boundary).await?;
+
+        let id_boundary = Argument::String(id_boundary);
+        let first_orphan = Argument::Int(first_orphan);
+
+        self.query_queue.enqueue(
+            "DELETE FROM entity_model_history WHERE block_number >= ?",
+            vec![first_orphan.clone()],
+        );
+        self.query_queue.enqueue("DELETE FROM events WHERE id >= ?", vec![id_boundary.clone()]);
+        self.query_queue.enqueue("DELETE FROM transactions WHERE id >= ?", vec![id_boundary]);
+        self.query_queue.enqueue("DELETE FROM blocks WHERE id >= ?", vec![first_orphan]);
+        self.set_head(block_number.unwrap_or_default());
+        self.query_queue.execute_all().await?;
+
+        Ok(())
+    }
+
+    /// Deletes the models of the event messages last set by the events from `id_boundary` on, and
+    /// the event messages left without any model.
+    async fn rollback_event_messages(&mut self, id_boundary: &str) -> Result<()> {
+        let models: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT m.id, m.name FROM event_model e JOIN models m ON m.id = e.model_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (model_id, model_name) in models {
+            let orphaned: Vec<(String,)> = sqlx::query_as(&format!(
+                "SELECT event_message_id FROM [{model_name}] WHERE event_message_id IS NOT NULL \
+                 AND event_id >= ?"
+            ))
+            .bind(id_boundary)
+            .fetch_all(&self.pool)
+            .await?;
+
+            if orphaned.is_empty() {
+                continue;
+            }
+
+            let schema = self.model(&model_name).await?.schema().await?;
+            for (message_id,) in orphaned {
+                self.build_delete_entity_queries_recursive(
+                    vec![schema.name()],
+                    &message_id,
+                    &schema,
+                    true,
+                );
+                self.query_queue.enqueue(
+                    "DELETE FROM event_model WHERE entity_id = ? AND model_id = ?",
+                    vec![Argument::String(message_id), Argument::String(model_id.clone())],
+                );
+            }
+        }
+
+        self.query_queue.enqueue(
+            "DELETE FROM event_messages WHERE id NOT IN (SELECT entity_id FROM event_model)",
+            vec![],
+        );
+        self.query_queue.execute_all().await?;
+
+        Ok(())
+    }
+
+    /// Reverts the metadata updated by the events from `id_boundary` on to their latest update
+    /// before, using the stored `MetadataUpdate` events. The content resolved from an uri which
+    /// isn't the latest one anymore is cleared.
+    async fn rollback_metadata(&mut self, id_boundary: &str) -> Result<()> {
+        let keys = felts_sql_string(&[get_selector_from_name("MetadataUpdate")?]);
+        let orphaned: Vec<(String,)> =
+            sqlx::query_as("SELECT data FROM events WHERE id >= ? AND keys = ?")
+                .bind(id_boundary)
+                .bind(&keys)
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut resources = HashSet::new();
+        for (data,) in orphaned {
+            resources
+                .insert(events::MetadataUpdate::decode(&felts_from_sql_string(&data)?)?.resource);
+        }
+
+        for resource in resources {
+            let previous: Option<(String, String)> = sqlx::query_as(
+                "SELECT data, executed_at FROM events WHERE id < ? AND keys = ? AND data LIKE ? \
+                 ORDER BY id DESC LIMIT 1",
+            )
+            .bind(id_boundary)
+            .bind(&keys)
+            .bind(format!("{resource:#x}{FELT_DELIMITER}%"))
+            .fetch_optional(&self.pool)
+            .await?;
+
+            let id = Argument::FieldElement(resource);
+            match previous {
+                Some((data, executed_at)) => {
+                    let uri =
+                        events::MetadataUpdate::decode(&felts_from_sql_string(&data)?)?.uri()?;
+                    let uri = Argument::String(uri);
+                    self.query_queue.enqueue(
+                        "UPDATE metadata SET json = NULL, icon_img = NULL, cover_img = NULL WHERE \
+                         id = ? AND uri IS NOT ?",
+                        vec![id.clone(), uri.clone()],
+                    );
+                    self.query_queue.enqueue(
+                        "UPDATE metadata SET uri = ?, executed_at = ?, \
+                         updated_at=CURRENT_TIMESTAMP WHERE id = ?",
+                        vec![uri, Argument::String(executed_at), id],
+                    );
+                }
+                None => self.query_queue.enqueue("DELETE FROM metadata WHERE id = ?", vec![id]),
+            }
+        }
+
+        self.query_queue.execute_all().await?;
+
+        Ok(())
+    }
+
+    /// Deletes the models first registered by the events from `id_boundary` on, along with their
+    /// tables, using the stored `ModelRegistered` events. The models upgraded by these events are
+    /// pointed back to their previous class and contract, but keep their upgraded schema.
+    async fn rollback_models(&mut self, id_boundary: &str) -> Result<()> {
+        let keys = felts_sql_string(&[get_selector_from_name("ModelRegistered")?]);
+        let registered: Vec<(String,)> =
+            sqlx::query_as("SELECT data FROM events WHERE id >= ? AND keys = ? ORDER BY id ASC")
+                .bind(id_boundary)
+                .bind(&keys)
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut rolled_back = HashSet::new();
+        for (data,) in registered {
+            let event = events::ModelRegistered::decode(&felts_from_sql_string(&data)?)?;
+            // the oldest registration of a model is the one reverted
+            if !rolled_back.insert(event.name.clone()) {
+                continue;
+            }
+
+            let model_id = Argument::String(format!("{:#x}", get_selector_from_name(&event.name)?));
+            if event.is_upgrade() {
+                self.query_queue.enqueue(
+                    "UPDATE models SET class_hash = ?, contract_address = ? WHERE id = ?",
+                    vec![
+                        Argument::FieldElement(event.prev_class_hash),
+                        Argument::FieldElement(event.prev_address),
+                        model_id,
+                    ],
+                );
+                continue;
+            }
+
+            // the tables of the nested types are prefixed with the model name
+            let tables: Vec<(String,)> = sqlx::query_as(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND (name = ? OR \
+                 substr(name, 1, length(?) + 1) = ? || '$')",
+            )
+            .bind(&event.name)
+            .bind(&event.name)
+            .bind(&event.name)
+            .fetch_all(&self.pool)
+            .await?;
+
+            for (table,) in tables {
+                self.query_queue.enqueue(format!("DROP TABLE IF EXISTS [{table}]"), vec![]);
+            }
+            self.query_queue
+                .enqueue("DELETE FROM model_members WHERE model_id = ?", vec![model_id.clone()]);
+            self.query_queue.enqueue("DELETE FROM models WHERE id = ?", vec![model_id]);
+        }
+
+        self.query_queue.execute_all().await?;
+
+        Ok(())
+    }
+
     pub async fn world(&self) -> Result<World> {
         let mut conn: PoolConnection<Sqlite> = self.pool.acquire().await?;
         let meta: World = sqlx::query_as("SELECT * FROM worlds WHERE id = ?")
@@ -87,6 +406,9 @@ impl Sql {
         unpacked_size: u32,
         block_timestamp: u64,
     ) -> Result<()> {
+        // the pending updates are written with the schema they were emitted with
+        self.flush_coalesced().await?;
+
         let layout_blob = layout
             .iter()
             .map(|x| <FieldElement as TryInto<u8>>::try_into(*x).unwrap())
@@ -132,16 +454,55 @@ impl Sql {
         event_id: &str,
         block_timestamp: u64,
     ) -> Result<()> {
-        let keys = if let Ty::Struct(s) = &entity {
-            let mut keys = Vec::new();
-            for m in s.keys() {
-                keys.extend(m.serialize()?);
-            }
-            keys
-        } else {
-            return Err(anyhow!("Entity is not a struct"));
+        let Some(coalescer) = self.coalescer.as_mut().filter(|c| c.is_coalesced(&entity.name()))
+        else {
+            return self.write_entity(entity, event_id, block_timestamp).await;
+        };
+
+        let entity_id = format!("{:#x}", poseidon_hash_many(&entity_keys(&entity)?));
+        let event_id = event_id.to_string();
+        coalescer.push(entity_id, PendingUpdate { entity, event_id, block_timestamp });
+
+        self.flush_coalesced_if_due().await
+    }
+
+    /// Writes the buffered updates of the coalesced models, and the head deferred while they were
+    /// buffered.
+    pub async fn flush_coalesced(&mut self) -> Result<()> {
+        let Some(coalescer) = self.coalescer.as_mut() else {
+            return Ok(());
         };
 
+        let (updates, head) = coalescer.take();
+        for PendingUpdate { entity, event_id, block_timestamp } in updates {
+            self.write_entity(entity, &event_id, block_timestamp).await?;
+        }
+
+        if let Some(head) = head {
+            self.set_head(head);
+            self.query_queue.execute_all().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the buffered updates of the coalesced models once their window has elapsed.
+    pub async fn flush_coalesced_if_due(&mut self) -> Result<()> {
+        if self.coalescer.as_ref().is_some_and(Coalescer::is_due) {
+            self.flush_coalesced().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn write_entity(
+        &mut self,
+        entity: Ty,
+        event_id: &str,
+        block_timestamp: u64,
+    ) -> Result<()> {
+        let keys = entity_keys(&entity)?;
+
         let entity_id = format!("{:#x}", poseidon_hash_many(&keys));
         self.query_queue.enqueue(
             "INSERT INTO entity_model (entity_id, model_id) VALUES (?, ?) ON CONFLICT(entity_id, \
@@ -238,12 +599,51 @@ impl Sql {
 
     pub async fn delete_entity(&mut self, keys: Vec<FieldElement>, entity: Ty) -> Result<()> {
         let entity_id = format!("{:#x}", poseidon_hash_many(&keys));
+        if let Some(coalescer) = self.coalescer.as_mut() {
+            coalescer.remove(&entity_id, &entity.name());
+        }
+
         let path = vec![entity.name()];
-        self.build_delete_entity_queries_recursive(path, &entity_id, &entity);
+        self.build_delete_entity_queries_recursive(path, &entity_id, &entity, false);
         self.query_queue.execute_all().await?;
         Ok(())
     }
 
+    /// Records the value of the model `model` of the entity of keys `keys` after a change at
+    /// `block_number`, or its deletion if `value` is `None`. A change already recorded, from a
+    /// block indexed again, is skipped.
+    pub fn store_entity_history(
+        &mut self,
+        keys: &[FieldElement],
+        model: &str,
+        value: Option<&Ty>,
+        event_id: &str,
+        block_number: u64,
+        block_timestamp: u64,
+    ) -> Result<()> {
+        let data = match value {
+            Some(value) => Argument::String(serde_json::to_string(value)?),
+            None => Argument::Null,
+        };
+
+        self.query_queue.enqueue(
+            "INSERT INTO entity_model_history (entity_id, keys, model_id, event_id, block_number, \
+             data, executed_at) VALUES (?, ?, ?, ?, ?, ?, ?) ON CONFLICT(entity_id, model_id, \
+             event_id) DO NOTHING",
+            vec![
+                Argument::String(format!("{:#x}", poseidon_hash_many(keys))),
+                Argument::String(felts_sql_string(keys)),
+                Argument::String(format!("{:#x}", get_selector_from_name(model)?)),
+                Argument::String(event_id.to_string()),
+                Argument::Int(block_number.try_into().expect("doesn't fit in i64")),
+                data,
+                Argument::String(utc_dt_string_from_timestamp(block_timestamp)),
+            ],
+        );
+
+        Ok(())
+    }
+
     pub fn set_metadata(&mut self, resource: &FieldElement, uri: &str, block_timestamp: u64) {
         let resource = Argument::FieldElement(*resource);
         let uri = Argument::String(uri.to_string());
@@ -289,6 +689,58 @@ impl Sql {
         Ok(())
     }
 
+    /// Returns the stored content of `uri`, if it was resolved before.
+    pub async fn resolved_content(&self, uri: &str) -> Result<Option<ResolvedContent>> {
+        let row: Option<(String, String, String)> = sqlx::query_as(
+            "SELECT content_type, encoding, data FROM resolved_contents WHERE id = ?",
+        )
+        .bind(uri)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some((content_type, encoding, data)) = row else {
+            return Ok(None);
+        };
+
+        let data = match encoding.as_str() {
+            UTF8_ENCODING => data.into_bytes(),
+            BASE64_ENCODING => general_purpose::STANDARD.decode(data)?,
+            _ => return Err(anyhow!("Unknown encoding {encoding} of the content of {uri}")),
+        };
+
+        Ok(Some(ResolvedContent {
+            uri: uri.to_string(),
+            content_type: (!content_type.is_empty()).then_some(content_type),
+            data: data.into(),
+        }))
+    }
+
+    /// Stores a resolved content, replacing the previous content of its URI.
+    pub async fn store_resolved_content(&mut self, content: &ResolvedContent) -> Result<()> {
+        // the text contents, eg. JSON metadata, are stored as is so that they can be queried
+        let (encoding, data) = match std::str::from_utf8(&content.data) {
+            Ok(text) => (UTF8_ENCODING, text.to_string()),
+            Err(_) => (BASE64_ENCODING, content.base64()),
+        };
+
+        self.query_queue.enqueue(
+            "INSERT INTO resolved_contents (id, content_type, encoding, size, data) VALUES (?, ?, \
+             ?, ?, ?) ON CONFLICT(id) DO UPDATE SET content_type=excluded.content_type, \
+             encoding=excluded.encoding, size=excluded.size, data=excluded.data, \
+             updated_at=CURRENT_TIMESTAMP",
+            vec![
+                Argument::String(content.uri.clone()),
+                Argument::String(content.content_type.clone().unwrap_or_default()),
+                Argument::String(encoding.to_string()),
+                Argument::Int(content.data.len().try_into().expect("doesn't fit in i64")),
+                Argument::String(data),
+            ],
+        );
+        self.query_queue.execute_all().await?;
+
+        Ok(())
+    }
+
     pub async fn model(&self, model: &str) -> Result<ModelSQLReader> {
         let reader = ModelSQLReader::new(model, self.pool.clone()).await?;
         Ok(reader)
@@ -409,7 +861,8 @@ impl Sql {
 
         if let Ty::Struct(s) = model {
             for member in s.children.iter() {
-                if let Ty::Primitive(_) = member.ty {
+                // arrays and byte arrays are stored in a column of the parent table
+                if let Ty::Primitive(_) | Ty::Array(_) | Ty::ByteArray(_) = member.ty {
                     continue;
                 }
 
@@ -463,12 +916,27 @@ impl Sql {
                     match &member.ty {
                         Ty::Primitive(ty) => {
                             columns.push(format!("external_{}", &member.name));
-                            arguments.push(Argument::String(ty.to_sql_value().unwrap()));
+                            let value = ty.to_sql_value().unwrap();
+                            // bound as integers for the backends with strictly typed columns
+                            arguments.push(match (ty.to_sql_type(), value.parse()) {
+                                (SqlType::Integer, Ok(integer)) => Argument::Int(integer),
+                                _ => Argument::String(value),
+                            });
                         }
                         Ty::Enum(e) => {
                             columns.push(format!("external_{}", &member.name));
                             arguments.push(Argument::String(e.to_sql_value().unwrap()));
                         }
+                        Ty::Array(items) => {
+                            columns.push(format!("external_{}", &member.name));
+                            arguments.push(Argument::Blob(
+                                items.iter().flat_map(|item| item.to_bytes_be()).collect(),
+                            ));
+                        }
+                        Ty::ByteArray(string) => {
+                            columns.push(format!("external_{}", &member.name));
+                            arguments.push(Argument::String(string.clone()));
+                        }
                         _ => {}
                     }
                 }
@@ -520,11 +988,13 @@ impl Sql {
         path: Vec<String>,
         entity_id: &str,
         entity: &Ty,
+        is_event_message: bool,
     ) {
         match entity {
             Ty::Struct(s) => {
                 let table_id = path.join("$");
-                let statement = format!("DELETE FROM [{table_id}] WHERE entity_id = ?");
+                let column = if is_event_message { "event_message_id" } else { "entity_id" };
+                let statement = format!("DELETE FROM [{table_id}] WHERE {column} = ?");
                 self.query_queue
                     .push_front(statement, vec![Argument::String(entity_id.to_string())]);
                 for member in s.children.iter() {
@@ -532,7 +1002,10 @@ impl Sql {
                         let mut path_clone = path.clone();
                         path_clone.push(member.name.clone());
                         self.build_delete_entity_queries_recursive(
-                            path_clone, entity_id, &member.ty,
+                            path_clone,
+                            entity_id,
+                            &member.ty,
+                            is_event_message,
                         );
                     }
                 }
@@ -541,7 +1014,12 @@ impl Sql {
                 for child in e.options.iter() {
                     let mut path_clone = path.clone();
                     path_clone.push(child.name.clone());
-                    self.build_delete_entity_queries_recursive(path_clone, entity_id, &child.ty);
+                    self.build_delete_entity_queries_recursive(
+                        path_clone,
+                        entity_id,
+                        &child.ty,
+                        is_event_message,
+                    );
                 }
             }
             _ => {}
@@ -575,6 +1053,10 @@ impl Sql {
                         "CREATE INDEX IF NOT EXISTS idx_{table_id}_{name} ON [{table_id}] \
                          (external_{name});"
                     ));
+
+                    if member.key {
+                        indices.extend(search_document_triggers(SEARCH_ENTITY_KEY, &path, &name));
+                    }
                 } else if let Ty::Enum(e) = &member.ty {
                     let all_options = e
                         .options
@@ -600,6 +1082,12 @@ impl Sql {
                             .join(",")
                             .to_string(),
                     ));
+                } else if let Ty::ByteArray(_) = &member.ty {
+                    create_table_query.push_str(&format!("external_{name} TEXT, "));
+                    indices.extend(search_document_triggers(SEARCH_MODEL_MEMBER, &path, &name));
+                } else if let Ty::Array(_) = &member.ty {
+                    // felts are stored as 32 bytes big endian each
+                    create_table_query.push_str(&format!("external_{name} BLOB, "));
                 }
 
                 let statement = "INSERT OR IGNORE INTO model_members (id, model_id, model_idx, \
@@ -633,7 +1121,7 @@ impl Sql {
         if path.len() > 1 {
             let parent_table_id = path[..path.len() - 1].join("$");
             create_table_query
-                .push_str(&format!("FOREIGN KEY (id) REFERENCES {parent_table_id} (id), "));
+                .push_str(&format!("FOREIGN KEY (id) REFERENCES [{parent_table_id}] (id), "));
         };
 
         create_table_query.push_str("FOREIGN KEY (entity_id) REFERENCES entities(id), ");
@@ -649,13 +1137,83 @@ impl Sql {
     }
 
     pub async fn execute(&mut self) -> Result<()> {
+        self.flush_coalesced_if_due().await?;
         self.query_queue.execute_all().await?;
 
         Ok(())
     }
 }
 
+/// Returns the statements registering the indexer and the world of `world_address`.
+fn world_statements(world_address: FieldElement) -> Vec<(String, Vec<Argument>)> {
+    vec![
+        (
+            "INSERT OR IGNORE INTO indexers (id, head) VALUES (?, ?)".to_string(),
+            vec![Argument::FieldElement(world_address), Argument::Int(0)],
+        ),
+        (
+            "INSERT OR IGNORE INTO worlds (id, world_address) VALUES (?, ?)".to_string(),
+            vec![Argument::FieldElement(world_address), Argument::FieldElement(world_address)],
+        ),
+    ]
+}
+
+/// Returns the statements creating the triggers which keep the document of the member `name` of
+/// the entities, stored in the table of `path`, in sync in the `search_documents` table.
+fn search_document_triggers(kind: &str, path: &[String], name: &str) -> Vec<String> {
+    let table_id = path.join("$");
+    let model = &path[0];
+    let member = path[1..].iter().map(String::as_str).chain([name]).collect::<Vec<_>>().join(".");
+
+    let upsert = format!(
+        "INSERT INTO search_documents (kind, target_id, model, member, value) VALUES ('{kind}', \
+         new.entity_id, '{model}', '{member}', new.external_{name}) ON CONFLICT (kind, target_id, \
+         model, member) DO UPDATE SET value = excluded.value;"
+    );
+    let delete = format!(
+        "DELETE FROM search_documents WHERE kind = '{kind}' AND target_id = old.entity_id AND \
+         model = '{model}' AND member = '{member}';"
+    );
+
+    // the rows of the event messages have no entity
+    vec![
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS [search_{table_id}_{name}_insert] AFTER INSERT ON \
+             [{table_id}] WHEN new.entity_id IS NOT NULL AND new.external_{name} IS NOT NULL \
+             BEGIN {upsert} END;"
+        ),
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS [search_{table_id}_{name}_update] AFTER UPDATE OF \
+             external_{name} ON [{table_id}] WHEN new.entity_id IS NOT NULL AND \
+             new.external_{name} IS NOT NULL BEGIN {upsert} END;"
+        ),
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS [search_{table_id}_{name}_delete] AFTER DELETE ON \
+             [{table_id}] WHEN old.entity_id IS NOT NULL BEGIN {delete} END;"
+        ),
+    ]
+}
+
+fn entity_keys(entity: &Ty) -> Result<Vec<FieldElement>> {
+    let Ty::Struct(s) = entity else {
+        return Err(anyhow!("Entity is not a struct"));
+    };
+
+    let mut keys = Vec::new();
+    for m in s.keys() {
+        keys.extend(m.serialize()?);
+    }
+    Ok(keys)
+}
+
 fn felts_sql_string(felts: &[FieldElement]) -> String {
     felts.iter().map(|k| format!("{:#x}", k)).collect::<Vec<String>>().join(FELT_DELIMITER)
         + FELT_DELIMITER
 }
+
+fn felts_from_sql_string(felts: &str) -> Result<Vec<FieldElement>> {
+    felts
+        .split_terminator(FELT_DELIMITER)
+        .map(|felt| FieldElement::from_str(felt).map_err(Into::into))
+        .collect()
+}
@@ -1,27 +1,39 @@
+use std::collections::HashSet;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use async_trait::async_trait;
 use dojo_test_utils::compiler::build_test_config;
 use dojo_test_utils::migration::prepare_migration;
 use dojo_test_utils::sequencer::{
     get_default_test_starknet_config, SequencerConfig, TestSequencer,
 };
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Member, Struct, Ty};
 use dojo_world::contracts::world::WorldContractReader;
 use dojo_world::migration::TxnConfig;
 use dojo_world::utils::TransactionWaiter;
+use katana_primitives::genesis::constant::DEFAULT_FEE_TOKEN_ADDRESS;
 use scarb::ops;
 use sozo_ops::migration::execute_strategy;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
 use starknet::accounts::{Account, Call};
-use starknet::core::types::{BlockId, BlockTag};
-use starknet::core::utils::get_selector_from_name;
+use starknet::core::types::{BlockId, BlockTag, Event, TransactionReceipt};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider};
-use starknet_crypto::poseidon_hash_many;
+use starknet_crypto::{poseidon_hash_many, FieldElement};
 use tokio::sync::broadcast;
 
+use crate::coalesce::CoalescingConfig;
 use crate::engine::{Engine, EngineConfig, Processors};
+use crate::filter::IndexingFilterFile;
+use crate::model::{entities_at_query, entity_models_at, HistoryPoint};
 use crate::processors::register_model::RegisterModelProcessor;
 use crate::processors::store_set_record::StoreSetRecordProcessor;
+use crate::processors::EventProcessor;
 use crate::sql::Sql;
 
 pub async fn bootstrap_engine<P>(
@@ -137,3 +149,450 @@ async fn test_load_from_remote() {
 
     db.execute().await.unwrap();
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_entity_history() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), FieldElement::ONE).await.unwrap();
+
+    let position = |x: Option<u32>| {
+        Ty::Struct(Struct {
+            name: "Position".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    ty: Ty::Primitive(Primitive::ContractAddress(x.map(|_| FieldElement::TWO))),
+                    key: true,
+                },
+                Member { name: "x".into(), ty: Ty::Primitive(Primitive::U32(x)), key: false },
+            ],
+        })
+    };
+
+    db.register_model(position(None), vec![], FieldElement::ONE, FieldElement::ONE, 0, 0, 0)
+        .await
+        .unwrap();
+
+    // the block timestamps are ten times the block numbers
+    let keys = [FieldElement::TWO];
+    let entity_id = format!("{:#x}", poseidon_hash_many(&keys));
+    db.store_entity_history(&keys, "Position", Some(&position(Some(1))), "0x1", 1, 10).unwrap();
+    db.store_entity_history(&keys, "Position", Some(&position(Some(2))), "0x2", 3, 30).unwrap();
+    db.store_entity_history(&keys, "Position", None, "0x3", 5, 50).unwrap();
+    db.execute().await.unwrap();
+
+    let model_id = format!("{:#x}", get_selector_from_name("Position").unwrap());
+    for (block, expected) in [(0, None), (1, Some(1)), (2, Some(1)), (3, Some(2)), (5, None)] {
+        let expected =
+            expected.map(|x| (model_id.clone(), position(Some(x)))).into_iter().collect::<Vec<_>>();
+
+        let models = entity_models_at(&pool, &entity_id, HistoryPoint::Block(block)).await;
+        assert_eq!(models.unwrap(), expected, "block {block}");
+        let models = entity_models_at(&pool, &entity_id, HistoryPoint::Timestamp(block * 10)).await;
+        assert_eq!(models.unwrap(), expected, "timestamp {}", block * 10);
+
+        // the entity is listed as long as it has a model
+        let entities: Vec<(String, String, String)> = sqlx::query_as(&format!(
+            "SELECT id, keys, event_id FROM {}",
+            entities_at_query(HistoryPoint::Block(block))
+        ))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let expected = match block {
+            1 | 2 => vec![(entity_id.clone(), "0x2/".to_string(), "0x1".to_string())],
+            3 => vec![(entity_id.clone(), "0x2/".to_string(), "0x2".to_string())],
+            _ => vec![],
+        };
+        assert_eq!(entities, expected, "block {block}");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_coalesced_block_indexed_again_after_restart() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let coalescing = CoalescingConfig {
+        window: Duration::from_secs(3600),
+        models: HashSet::from(["Position".to_string()]),
+    };
+    let mut db = Sql::new(pool.clone(), FieldElement::ONE)
+        .await
+        .unwrap()
+        .with_coalescing(coalescing.clone());
+
+    let position = |x: Option<u32>| {
+        Ty::Struct(Struct {
+            name: "Position".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    ty: Ty::Primitive(Primitive::ContractAddress(x.map(|_| FieldElement::TWO))),
+                    key: true,
+                },
+                Member { name: "x".into(), ty: Ty::Primitive(Primitive::U32(x)), key: false },
+            ],
+        })
+    };
+
+    db.register_model(position(None), vec![], FieldElement::ONE, FieldElement::ONE, 0, 0, 0)
+        .await
+        .unwrap();
+
+    let keys = [FieldElement::TWO];
+    let index_block = |db: &mut Sql| {
+        let event = Event { from_address: FieldElement::ONE, keys: vec![], data: vec![] };
+        db.store_event("0x1:0x1:0x0", &event, FieldElement::ONE, 10);
+        db.store_entity_history(&keys, "Position", Some(&position(Some(1))), "0x1:0x1:0x0", 1, 10)
+    };
+
+    async fn count(pool: &SqlitePool, query: &str) -> i64 {
+        sqlx::query_as::<_, (i64,)>(query).fetch_one(pool).await.unwrap().0
+    }
+
+    // the update is buffered, and the head is not moved until it is written
+    index_block(&mut db).unwrap();
+    db.set_entity(position(Some(1)), "0x1:0x1:0x0", 10).await.unwrap();
+    db.set_head(1);
+    db.execute().await.unwrap();
+    assert_eq!(db.head().await.unwrap(), 0);
+    assert_eq!(count(&pool, "SELECT COUNT(*) FROM [Position]").await, 0);
+
+    // Torii stops before the update is written, and indexes the block again once restarted
+    drop(db);
+    let mut db =
+        Sql::new(pool.clone(), FieldElement::ONE).await.unwrap().with_coalescing(coalescing);
+    index_block(&mut db).unwrap();
+    db.set_entity(position(Some(1)), "0x1:0x1:0x0", 10).await.unwrap();
+    db.set_head(1);
+    db.flush_coalesced().await.unwrap();
+
+    assert_eq!(db.head().await.unwrap(), 1);
+    assert_eq!(count(&pool, "SELECT COUNT(*) FROM [Position]").await, 1);
+    assert_eq!(count(&pool, "SELECT COUNT(*) FROM entity_model_history").await, 1);
+    assert_eq!(count(&pool, "SELECT COUNT(*) FROM events").await, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rollback() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let mut db = Sql::new(pool.clone(), FieldElement::ONE).await.unwrap();
+
+    let position = |x: Option<u32>| {
+        Ty::Struct(Struct {
+            name: "Position".into(),
+            children: vec![
+                Member {
+                    name: "player".into(),
+                    ty: Ty::Primitive(Primitive::ContractAddress(x.map(|_| FieldElement::TWO))),
+                    key: true,
+                },
+                Member { name: "x".into(), ty: Ty::Primitive(Primitive::U32(x)), key: false },
+            ],
+        })
+    };
+
+    db.register_model(position(None), vec![], FieldElement::ONE, FieldElement::ONE, 0, 0, 0)
+        .await
+        .unwrap();
+
+    let world_event = |name: &str, data: Vec<FieldElement>| Event {
+        from_address: FieldElement::ONE,
+        keys: vec![get_selector_from_name(name).unwrap()],
+        data,
+    };
+    let metadata_update = |uri: &str| {
+        let uri = cairo_short_string_to_felt(uri).unwrap();
+        world_event("MetadataUpdate", vec![FieldElement::ZERO, FieldElement::ONE, uri])
+    };
+
+    let entity_id = format!("{:#x}", poseidon_hash_many(&[FieldElement::TWO]));
+    for (block, x, uri) in [(1_u64, 1, "ipfs://a"), (3, 2, "ipfs://b")] {
+        let event_id = format!("{:#064x}:0x1:0x00", block);
+        db.store_entity_history(
+            &[FieldElement::TWO],
+            "Position",
+            Some(&position(Some(x))),
+            &event_id,
+            block,
+            0,
+        )
+        .unwrap();
+        db.set_entity(position(Some(x)), &event_id, 0).await.unwrap();
+
+        let event_id = format!("{:#064x}:0x1:0x01", block);
+        db.store_event(&event_id, &metadata_update(uri), FieldElement::ONE, 0);
+        db.set_metadata(&FieldElement::ZERO, uri, 0);
+
+        db.store_block(block, FieldElement::from(block));
+        db.set_head(block);
+    }
+
+    // a model registered and an event message emitted in the third block
+    let moves = Ty::Struct(Struct {
+        name: "Moves".into(),
+        children: vec![
+            Member {
+                name: "player".into(),
+                ty: Ty::Primitive(Primitive::ContractAddress(None)),
+                key: true,
+            },
+            Member { name: "remaining".into(), ty: Ty::Primitive(Primitive::U8(None)), key: false },
+        ],
+    });
+    db.register_model(moves, vec![], FieldElement::TWO, FieldElement::TWO, 0, 0, 0).await.unwrap();
+    let name = cairo_short_string_to_felt("Moves").unwrap();
+    let model_registered = world_event(
+        "ModelRegistered",
+        vec![name, FieldElement::TWO, FieldElement::ZERO, FieldElement::TWO, FieldElement::ZERO],
+    );
+    db.store_event(&format!("{:#064x}:0x1:0x02", 3), &model_registered, FieldElement::ONE, 0);
+    let event_id = format!("{:#064x}:0x1:0x03", 3);
+    db.set_event_message(position(Some(5)), &event_id, 0).await.unwrap();
+    db.execute().await.unwrap();
+
+    async fn count(pool: &SqlitePool, query: &str) -> i64 {
+        sqlx::query_as::<_, (i64,)>(query).fetch_one(pool).await.unwrap().0
+    }
+    async fn metadata_uri(pool: &SqlitePool) -> Option<String> {
+        sqlx::query_as::<_, (String,)>("SELECT uri FROM metadata WHERE id = '0x0'")
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+            .map(|(uri,)| uri)
+    }
+
+    assert_eq!(count(&pool, "SELECT COUNT(*) FROM event_messages").await, 1);
+    assert_eq!(metadata_uri(&pool).await.as_deref(), Some("ipfs://b"));
+
+    async fn position_x(pool: &SqlitePool, entity_id: &str) -> Option<i64> {
+        sqlx::query_as::<_, (i64,)>("SELECT external_x FROM [Position] WHERE entity_id = ?")
+            .bind(entity_id)
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+            .map(|(x,)| x)
+    }
+
+    assert_eq!(position_x(&pool, &entity_id).await, Some(2));
+
+    db.rollback(Some(2)).await.unwrap();
+    assert_eq!(position_x(&pool, &entity_id).await, Some(1));
+    assert_eq!(db.head().await.unwrap(), 2);
+    assert_eq!(db.blocks().await.unwrap(), vec![(1, FieldElement::ONE)]);
+
+    // the model registered in the rolled back block is dropped, along with its table
+    assert_eq!(count(&pool, "SELECT COUNT(*) FROM models WHERE name = 'Moves'").await, 0);
+    let moves_tables = "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'Moves'";
+    assert_eq!(count(&pool, moves_tables).await, 0);
+    assert_eq!(count(&pool, "SELECT COUNT(*) FROM models WHERE name = 'Position'").await, 1);
+
+    // the event message is deleted, and the metadata reverted to its previous update
+    assert_eq!(count(&pool, "SELECT COUNT(*) FROM event_messages").await, 0);
+    let message_rows = "SELECT COUNT(*) FROM [Position] WHERE event_message_id IS NOT NULL";
+    assert_eq!(count(&pool, message_rows).await, 0);
+    assert_eq!(metadata_uri(&pool).await.as_deref(), Some("ipfs://a"));
+
+    db.rollback(None).await.unwrap();
+    assert_eq!(position_x(&pool, &entity_id).await, None);
+    assert_eq!(db.head().await.unwrap(), 0);
+    assert!(db.blocks().await.unwrap().is_empty());
+    assert_eq!(metadata_uri(&pool).await, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mirror() {
+    async fn memory_pool() -> SqlitePool {
+        let options =
+            SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    let pool = memory_pool().await;
+    let mirror = memory_pool().await;
+
+    let mut db = Sql::new(pool.clone(), FieldElement::ONE)
+        .await
+        .unwrap()
+        .with_mirror(Arc::new(mirror.clone()))
+        .await
+        .unwrap();
+
+    let event = Event { from_address: FieldElement::ONE, keys: vec![], data: vec![] };
+    db.store_event(&format!("{:#064x}:0x1:0x00", 1), &event, FieldElement::ONE, 0);
+    db.store_block(1, FieldElement::ONE);
+    db.set_head(1);
+    db.execute().await.unwrap();
+
+    // the mirror has the world and the indexed data too
+    for pool in [&pool, &mirror] {
+        let head = sqlx::query_as::<_, (i64,)>("SELECT head FROM indexers WHERE id = '0x1'")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(head.0, 1);
+
+        let events = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM events")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(events.0, 1);
+    }
+}
+
+/// Records the transfers of the fee token, which isn't part of the world.
+struct TransferProcessor {
+    token: FieldElement,
+    transfers: Arc<Mutex<Vec<Vec<FieldElement>>>>,
+}
+
+#[async_trait]
+impl<P> EventProcessor<P> for TransferProcessor
+where
+    P: Provider + Send + Sync,
+{
+    fn event_key(&self) -> String {
+        "Transfer".to_string()
+    }
+
+    fn contract_addresses(&self) -> Vec<FieldElement> {
+        vec![self.token]
+    }
+
+    fn validate(&self, _event: &Event) -> bool {
+        true
+    }
+
+    async fn process(
+        &self,
+        _world: &WorldContractReader<P>,
+        _db: &mut Sql,
+        _block_number: u64,
+        _block_timestamp: u64,
+        _transaction_receipt: &TransactionReceipt,
+        _event_id: &str,
+        event: &Event,
+    ) -> anyhow::Result<()> {
+        self.transfers.lock().unwrap().push(event.data.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_processor_of_other_contract() {
+    let options =
+        SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let provider = JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+    let account = sequencer.account();
+
+    let token = DEFAULT_FEE_TOKEN_ADDRESS.into();
+    let recipient = FieldElement::from(0x1234_u32);
+    let tx = account
+        .execute(vec![Call {
+            to: token,
+            selector: get_selector_from_name("transfer").unwrap(),
+            calldata: vec![recipient, FieldElement::from(100_u32), FieldElement::ZERO],
+        }])
+        .send()
+        .await
+        .unwrap();
+    TransactionWaiter::new(tx.transaction_hash, &provider).await.unwrap();
+
+    // no world is deployed, the only indexed events are the ones of the token
+    let world = WorldContractReader::new(FieldElement::from(0xdead_u32), &provider);
+    let db = Sql::new(pool.clone(), world.address).await.unwrap();
+    let transfers = Arc::new(Mutex::new(Vec::new()));
+    let processor = TransferProcessor { token, transfers: Arc::clone(&transfers) };
+
+    let (shutdown_tx, _) = broadcast::channel(1);
+    let mut engine = Engine::new(
+        world,
+        db,
+        &provider,
+        Processors { event: vec![Box::new(processor)], ..Processors::default() },
+        EngineConfig::default(),
+        shutdown_tx,
+        None,
+    );
+    engine.sync_to_head(0).await.unwrap();
+
+    let transfers = transfers.lock().unwrap();
+    assert!(transfers
+        .iter()
+        .any(|data| data[1] == recipient && data[2] == FieldElement::from(100_u32)));
+
+    let events = sqlx::query_as::<_, (String,)>("SELECT transaction_hash FROM events")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert!(events.contains(&(format!("{:#x}", tx.transaction_hash),)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_indexing_filter_worlds() {
+    let base_path = "../../../examples/spawn-and-move";
+    let target_path = format!("{}/target/dev", base_path);
+    let mut migration = prepare_migration(base_path.into(), target_path.into()).unwrap();
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let provider = JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+    let world_address = migration.world_address().unwrap();
+
+    let mut account = sequencer.account();
+    account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+    let config = build_test_config("../../../examples/spawn-and-move/Scarb.toml").unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)
+        .unwrap_or_else(|op| panic!("Error building workspace: {op:?}"));
+    execute_strategy(&ws, &mut migration, &account, TxnConfig::default()).await.unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    for (worlds, expected_models) in [(format!("{world_address:#x}"), 4), ("0x1".to_string(), 0)] {
+        let options =
+            SqliteConnectOptions::from_str("sqlite::memory:").unwrap().create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(5).connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+
+        let path = dir.path().join(format!("filter-{worlds}.toml"));
+        std::fs::write(&path, format!("worlds = [\"{worlds}\"]")).unwrap();
+        let filter = IndexingFilterFile::load(&path).unwrap();
+
+        let world = WorldContractReader::new(world_address, &provider);
+        let db = Sql::new(pool.clone(), world_address).await.unwrap();
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let mut engine = Engine::new(
+            world,
+            db,
+            &provider,
+            Processors {
+                event: vec![Box::new(RegisterModelProcessor), Box::new(StoreSetRecordProcessor)],
+                ..Processors::default()
+            },
+            EngineConfig { filter: Some(filter), ..EngineConfig::default() },
+            shutdown_tx,
+            None,
+        );
+        engine.sync_to_head(0).await.unwrap();
+
+        let models = sqlx::query("SELECT * FROM models").fetch_all(&pool).await.unwrap();
+        assert_eq!(models.len(), expected_models, "worlds = [{worlds}]");
+    }
+}
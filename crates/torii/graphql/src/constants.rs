@@ -9,6 +9,7 @@ pub const EVENT_MESSAGE_TABLE: &str = "event_messages";
 pub const MODEL_TABLE: &str = "models";
 pub const TRANSACTION_TABLE: &str = "transactions";
 pub const METADATA_TABLE: &str = "metadata";
+pub const RESOLVED_CONTENT_TABLE: &str = "resolved_contents";
 
 pub const ID_COLUMN: &str = "id";
 pub const EVENT_ID_COLUMN: &str = "event_id";
@@ -17,6 +18,8 @@ pub const JSON_COLUMN: &str = "json";
 pub const TRANSACTION_HASH_COLUMN: &str = "transaction_hash";
 
 pub const INTERNAL_ENTITY_ID_KEY: &str = "$entity_id$";
+pub const INTERNAL_BLOCK_KEY: &str = "$block$";
+pub const INTERNAL_TIMESTAMP_KEY: &str = "$timestamp$";
 
 // objects namespaced to avoid conflicts with user models
 pub const ENTITY_TYPE_NAME: &str = "World__Entity";
@@ -28,10 +31,13 @@ pub const CONTENT_TYPE_NAME: &str = "World__Content";
 pub const METADATA_TYPE_NAME: &str = "World__Metadata";
 pub const PAGE_INFO_TYPE_NAME: &str = "World__PageInfo";
 pub const TRANSACTION_TYPE_NAME: &str = "World__Transaction";
+pub const RESOLVED_CONTENT_TYPE_NAME: &str = "World__ResolvedContent";
 pub const QUERY_TYPE_NAME: &str = "World__Query";
 pub const SUBSCRIPTION_TYPE_NAME: &str = "World__Subscription";
 pub const MODEL_ORDER_TYPE_NAME: &str = "World__ModelOrder";
 pub const MODEL_ORDER_FIELD_TYPE_NAME: &str = "World__ModelOrderField";
+pub const SEARCH_RESULT_TYPE_NAME: &str = "World__SearchResult";
+pub const SEARCH_KIND_TYPE_NAME: &str = "World__SearchKind";
 
 // objects' single and plural names
 pub const ENTITY_NAMES: (&str, &str) = ("entity", "entities");
@@ -42,6 +48,8 @@ pub const SOCIAL_NAMES: (&str, &str) = ("social", "socials");
 pub const CONTENT_NAMES: (&str, &str) = ("content", "contents");
 pub const METADATA_NAMES: (&str, &str) = ("metadata", "metadatas");
 pub const TRANSACTION_NAMES: (&str, &str) = ("transaction", "transactions");
+pub const RESOLVED_CONTENT_NAMES: (&str, &str) = ("resolvedContent", "resolvedContents");
+pub const SEARCH_RESULT_NAMES: (&str, &str) = ("searchResult", "search");
 pub const PAGE_INFO_NAMES: (&str, &str) = ("pageInfo", "");
 
 // misc
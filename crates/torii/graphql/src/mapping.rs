@@ -4,7 +4,7 @@ use async_graphql::Name;
 use dojo_types::primitive::Primitive;
 use lazy_static::lazy_static;
 
-use crate::constants::{CONTENT_TYPE_NAME, SOCIAL_TYPE_NAME};
+use crate::constants::{CONTENT_TYPE_NAME, SEARCH_KIND_TYPE_NAME, SOCIAL_TYPE_NAME};
 use crate::types::{GraphqlType, TypeData, TypeMapping};
 
 lazy_static! {
@@ -98,6 +98,24 @@ lazy_static! {
             TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
         ),
     ]);
+    pub static ref RESOLVED_CONTENT_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("id"), TypeData::Simple(TypeRef::named(TypeRef::ID))),
+        (Name::new("contentType"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (Name::new("encoding"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (
+            Name::new("size"),
+            TypeData::Simple(TypeRef::named(Primitive::U32(None).to_string()))
+        ),
+        (Name::new("data"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (
+            Name::new("createdAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+        (
+            Name::new("updatedAt"),
+            TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string())),
+        ),
+    ]);
     pub static ref PAGE_INFO_TYPE_MAPPING: TypeMapping = TypeMapping::from([
         (Name::new("hasPreviousPage"), TypeData::Simple(TypeRef::named(TypeRef::BOOLEAN))),
         (Name::new("hasNextPage"), TypeData::Simple(TypeRef::named(TypeRef::BOOLEAN))),
@@ -143,4 +161,13 @@ lazy_static! {
             TypeData::Simple(TypeRef::named(GraphqlType::DateTime.to_string()))
         ),
     ]);
+    pub static ref SEARCH_RESULT_TYPE_MAPPING: TypeMapping = IndexMap::from([
+        (Name::new("kind"), TypeData::Simple(TypeRef::named_nn(SEARCH_KIND_TYPE_NAME))),
+        (Name::new("id"), TypeData::Simple(TypeRef::named_nn(TypeRef::ID))),
+        (Name::new("worldAddress"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("model"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (Name::new("member"), TypeData::Simple(TypeRef::named(TypeRef::STRING))),
+        (Name::new("value"), TypeData::Simple(TypeRef::named_nn(TypeRef::STRING))),
+        (Name::new("rank"), TypeData::Simple(TypeRef::named_nn(TypeRef::INT))),
+    ]);
 }
@@ -1,12 +1,15 @@
 use async_graphql::dynamic::indexmap::IndexMap;
 use async_graphql::dynamic::{
-    Field, FieldFuture, FieldValue, InputValue, SubscriptionField, SubscriptionFieldFuture, TypeRef,
+    Field, FieldFuture, FieldValue, InputValue, ResolverContext, SubscriptionField,
+    SubscriptionFieldFuture, TypeRef,
 };
-use async_graphql::{Name, Value};
+use async_graphql::{Error, Name, Value};
 use async_recursion::async_recursion;
 use sqlx::pool::PoolConnection;
 use sqlx::{Pool, Sqlite};
 use tokio_stream::StreamExt;
+use torii_core::model::{entities_at_query, entity_models_at, HistoryPoint};
+use torii_core::privacy::Privacy;
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::types::Entity;
 
@@ -14,10 +17,18 @@ use super::inputs::keys_input::keys_argument;
 use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
 use crate::constants::{
     DATETIME_FORMAT, ENTITY_NAMES, ENTITY_TABLE, ENTITY_TYPE_NAME, EVENT_ID_COLUMN, ID_COLUMN,
+    INTERNAL_BLOCK_KEY, INTERNAL_TIMESTAMP_KEY,
 };
 use crate::mapping::ENTITY_TYPE_MAPPING;
-use crate::object::{resolve_many, resolve_one};
-use crate::query::{type_mapping_query, value_mapping_from_row};
+use crate::object::connection::{
+    connection_arguments, connection_output, parse_connection_arguments,
+};
+use crate::object::inputs::keys_input::parse_keys_argument;
+use crate::object::inputs::order_input::parse_order_argument;
+use crate::query::data::{count_rows, fetch_multiple_rows, fetch_single_row};
+use crate::query::{
+    public_type_mapping, type_mapping_query, value_mapping_from_row, value_mapping_from_ty,
+};
 use crate::types::TypeData;
 use crate::utils::extract;
 pub struct EntityObject;
@@ -42,29 +53,20 @@ impl BasicObject for EntityObject {
 
 impl ResolvableObject for EntityObject {
     fn resolvers(&self) -> Vec<Field> {
-        let resolve_one = resolve_one(
-            ENTITY_TABLE,
-            ID_COLUMN,
-            self.name().0,
-            self.type_name(),
-            self.type_mapping(),
-        );
-
-        let mut resolve_many = resolve_many(
-            ENTITY_TABLE,
-            EVENT_ID_COLUMN,
-            self.name().1,
-            self.type_name(),
-            self.type_mapping(),
-        );
+        let resolve_one = resolve_one_at(self.name().0, self.type_name(), self.type_mapping());
+
+        let mut resolve_many =
+            resolve_many_at(self.name().1, self.type_name(), self.type_mapping());
         resolve_many = keys_argument(resolve_many);
 
         vec![resolve_one, resolve_many]
     }
 
     fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
-        Some(vec![
-            SubscriptionField::new("entityUpdated", TypeRef::named_nn(self.type_name()), |ctx| {
+        Some(vec![SubscriptionField::new(
+            "entityUpdated",
+            TypeRef::named_nn(self.type_name()),
+            |ctx| {
                 SubscriptionFieldFuture::new(async move {
                     let id = match ctx.args.get("id") {
                         Some(id) => Some(id.string()?.to_string()),
@@ -81,9 +83,9 @@ impl ResolvableObject for EntityObject {
                         }
                     }))
                 })
-            })
-            .argument(InputValue::new("id", TypeRef::named(TypeRef::ID))),
-        ])
+            },
+        )
+        .argument(InputValue::new("id", TypeRef::named(TypeRef::ID)))])
     }
 }
 
@@ -110,14 +112,166 @@ impl EntityObject {
     }
 }
 
+// Resolves a single entity. With a `block` or a `timestamp` argument, the entity is resolved as it
+// was at that point of the history, with the values its models had then, even if it has been
+// deleted since.
+fn resolve_one_at(field_name: &str, type_name: &str, type_mapping: &TypeMapping) -> Field {
+    let type_mapping = type_mapping.clone();
+
+    let field = Field::new(field_name, TypeRef::named_nn(type_name), move |ctx| {
+        let type_mapping = type_mapping.clone();
+
+        FieldFuture::new(async move {
+            let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+            let id = extract::<String>(ctx.args.as_index_map(), ID_COLUMN)?;
+            let at = parse_history_point(&ctx)?;
+
+            let table = at.map_or(ENTITY_TABLE.to_string(), entities_at_query);
+            let data = fetch_single_row(&mut conn, &table, ID_COLUMN, &id).await?;
+            let mut entity = value_mapping_from_row(&data, &type_mapping, false)?;
+            if let Some(at) = at {
+                insert_history_point(&mut entity, at);
+            }
+
+            Ok(Some(Value::Object(entity)))
+        })
+    })
+    .argument(InputValue::new(ID_COLUMN, TypeRef::named_nn(TypeRef::ID)));
+
+    history_arguments(field)
+}
+
+// Resolves the entities. With a `block` or a `timestamp` argument, the entities are the ones which
+// had at least one model at that point of the history, resolved as they were then.
+fn resolve_many_at(field_name: &str, type_name: &str, type_mapping: &TypeMapping) -> Field {
+    let type_mapping = type_mapping.clone();
+
+    let field =
+        Field::new(field_name, TypeRef::named(format!("{}Connection", type_name)), move |ctx| {
+            let type_mapping = type_mapping.clone();
+
+            FieldFuture::new(async move {
+                let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                let connection = parse_connection_arguments(&ctx)?;
+                let keys = parse_keys_argument(&ctx)?;
+                let order = parse_order_argument(&ctx);
+                let at = parse_history_point(&ctx)?;
+
+                let table = at.map_or(ENTITY_TABLE.to_string(), entities_at_query);
+                let total_count = count_rows(&mut conn, &table, &keys, &None).await?;
+
+                let (data, page_info) = fetch_multiple_rows(
+                    &mut conn,
+                    &table,
+                    EVENT_ID_COLUMN,
+                    &keys,
+                    &order,
+                    &None,
+                    &connection,
+                    total_count,
+                )
+                .await?;
+                let mut results = connection_output(
+                    &data,
+                    &type_mapping,
+                    &order,
+                    EVENT_ID_COLUMN,
+                    total_count,
+                    false,
+                    page_info,
+                )?;
+
+                if let (Some(at), Some(Value::List(edges))) = (at, results.get_mut("edges")) {
+                    for edge in edges {
+                        if let Value::Object(edge) = edge {
+                            if let Some(Value::Object(node)) = edge.get_mut("node") {
+                                insert_history_point(node, at);
+                            }
+                        }
+                    }
+                }
+
+                Ok(Some(Value::Object(results)))
+            })
+        });
+
+    history_arguments(connection_arguments(field))
+}
+
+fn history_arguments(field: Field) -> Field {
+    field
+        .argument(InputValue::new("block", TypeRef::named(TypeRef::INT)))
+        .argument(InputValue::new("timestamp", TypeRef::named(TypeRef::INT)))
+}
+
+fn parse_history_point(ctx: &ResolverContext<'_>) -> Result<Option<HistoryPoint>, Error> {
+    let block = ctx.args.get("block").map(|block| block.u64()).transpose()?;
+    let timestamp = ctx.args.get("timestamp").map(|timestamp| timestamp.u64()).transpose()?;
+
+    match (block, timestamp) {
+        (Some(_), Some(_)) => Err("Only one of `block` and `timestamp` can be provided".into()),
+        (Some(block), None) => Ok(Some(HistoryPoint::Block(block))),
+        (None, Some(timestamp)) => Ok(Some(HistoryPoint::Timestamp(timestamp))),
+        (None, None) => Ok(None),
+    }
+}
+
+// The history point of an entity is passed to the resolver of its models through its value mapping.
+fn insert_history_point(entity: &mut ValueMapping, at: HistoryPoint) {
+    match at {
+        HistoryPoint::Block(block) => entity.insert(Name::new(INTERNAL_BLOCK_KEY), block.into()),
+        HistoryPoint::Timestamp(timestamp) => {
+            entity.insert(Name::new(INTERNAL_TIMESTAMP_KEY), timestamp.into())
+        }
+    };
+}
+
+fn history_point(entity: &ValueMapping) -> Result<Option<HistoryPoint>, Error> {
+    if entity.contains_key(INTERNAL_BLOCK_KEY) {
+        return Ok(Some(HistoryPoint::Block(extract::<u64>(entity, INTERNAL_BLOCK_KEY)?)));
+    }
+    if entity.contains_key(INTERNAL_TIMESTAMP_KEY) {
+        return Ok(Some(HistoryPoint::Timestamp(extract::<u64>(entity, INTERNAL_TIMESTAMP_KEY)?)));
+    }
+    Ok(None)
+}
+
 fn model_union_field() -> Field {
     Field::new("models", TypeRef::named_list("ModelUnion"), move |ctx| {
         FieldFuture::new(async move {
             match ctx.parent_value.try_to_value()? {
                 Value::Object(indexmap) => {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let privacy = ctx.data::<Privacy>()?;
 
                     let entity_id = extract::<String>(indexmap, "id")?;
+
+                    if let Some(at) = history_point(indexmap)? {
+                        let pool = ctx.data::<Pool<Sqlite>>()?;
+
+                        let mut results: Vec<FieldValue<'_>> = Vec::new();
+                        for (id, model) in entity_models_at(pool, &entity_id, at).await? {
+                            let Some(model) = model.as_struct() else { continue };
+                            if privacy.is_private_model(&model.name) {
+                                continue;
+                            }
+
+                            let type_mapping = public_type_mapping(
+                                type_mapping_query(&mut conn, &id).await?,
+                                &model.name,
+                                privacy,
+                            );
+                            let data = value_mapping_from_ty(model, &type_mapping, &entity_id)?;
+
+                            results.push(FieldValue::with_type(
+                                FieldValue::owned_any(data),
+                                model.name.clone(),
+                            ));
+                        }
+
+                        return Ok(Some(FieldValue::list(results)));
+                    }
+
                     // fetch name from the models table
                     // using the model id (hashed model name)
                     let model_ids: Vec<(String, String)> = sqlx::query_as(
@@ -135,8 +289,16 @@ fn model_union_field() -> Field {
 
                     let mut results: Vec<FieldValue<'_>> = Vec::new();
                     for (id, name) in model_ids {
+                        if privacy.is_private_model(&name) {
+                            continue;
+                        }
+
                         // the model id in the model mmeebrs table is the hashed model name (id)
-                        let type_mapping = type_mapping_query(&mut conn, &id).await?;
+                        let type_mapping = public_type_mapping(
+                            type_mapping_query(&mut conn, &id).await?,
+                            &name,
+                            privacy,
+                        );
 
                         // but the table name for the model data is the unhashed model name
                         let data = model_data_recursive_query(
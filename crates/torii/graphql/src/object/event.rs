@@ -3,17 +3,27 @@ use async_graphql::dynamic::{
 };
 use async_graphql::{Name, Result, Value};
 use tokio_stream::{Stream, StreamExt};
+use torii_core::privacy::Privacy;
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::sql::FELT_DELIMITER;
 use torii_core::types::Event;
 
 use super::inputs::keys_input::{keys_argument, parse_keys_argument};
 use super::{resolve_many, BasicObject, ResolvableObject, TypeMapping};
-use crate::constants::{DATETIME_FORMAT, EVENT_NAMES, EVENT_TABLE, EVENT_TYPE_NAME, ID_COLUMN};
+use crate::constants::{DATETIME_FORMAT, EVENT_NAMES, EVENT_TYPE_NAME, ID_COLUMN};
 use crate::mapping::EVENT_TYPE_MAPPING;
 use crate::types::ValueMapping;
 
-pub struct EventObject;
+pub struct EventObject {
+    table_name: String,
+}
+
+impl EventObject {
+    /// The private events are neither resolved nor streamed.
+    pub fn new(privacy: &Privacy) -> Self {
+        Self { table_name: privacy.public_events_table() }
+    }
+}
 
 impl BasicObject for EventObject {
     fn name(&self) -> (&str, &str) {
@@ -32,7 +42,7 @@ impl BasicObject for EventObject {
 impl ResolvableObject for EventObject {
     fn resolvers(&self) -> Vec<Field> {
         let mut resolve_many = resolve_many(
-            EVENT_TABLE,
+            &self.table_name,
             ID_COLUMN,
             self.name().1,
             self.type_name(),
@@ -48,7 +58,8 @@ impl ResolvableObject for EventObject {
             SubscriptionField::new("eventEmitted", TypeRef::named_nn(self.type_name()), |ctx| {
                 SubscriptionFieldFuture::new(async move {
                     let input_keys = parse_keys_argument(&ctx)?;
-                    Ok(EventObject::subscription_stream(input_keys))
+                    let privacy = ctx.data::<Privacy>()?.clone();
+                    Ok(EventObject::subscription_stream(input_keys, privacy))
                 })
             })
             .argument(InputValue::new("keys", TypeRef::named_list(TypeRef::STRING))),
@@ -76,8 +87,15 @@ impl EventObject {
         ])
     }
 
-    fn subscription_stream(input_keys: Option<Vec<String>>) -> impl Stream<Item = Result<Value>> {
+    fn subscription_stream(
+        input_keys: Option<Vec<String>>,
+        privacy: Privacy,
+    ) -> impl Stream<Item = Result<Value>> {
         SimpleBroker::<Event>::subscribe().filter_map(move |event| {
+            if privacy.is_private_event(&event.keys, &event.data) {
+                return None;
+            }
+
             EventObject::match_and_map_event(&input_keys, event)
                 .map(|value_mapping| Ok(Value::Object(value_mapping)))
         })
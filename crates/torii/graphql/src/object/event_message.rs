@@ -7,6 +7,7 @@ use async_recursion::async_recursion;
 use sqlx::pool::PoolConnection;
 use sqlx::{Pool, Sqlite};
 use tokio_stream::StreamExt;
+use torii_core::privacy::Privacy;
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::types::Entity;
 
@@ -17,7 +18,7 @@ use crate::constants::{
 };
 use crate::mapping::ENTITY_TYPE_MAPPING;
 use crate::object::{resolve_many, resolve_one};
-use crate::query::{type_mapping_query, value_mapping_from_row};
+use crate::query::{public_type_mapping, type_mapping_query, value_mapping_from_row};
 use crate::types::TypeData;
 use crate::utils::extract;
 pub struct EventMessageObject;
@@ -116,6 +117,7 @@ fn model_union_field() -> Field {
             match ctx.parent_value.try_to_value()? {
                 Value::Object(indexmap) => {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let privacy = ctx.data::<Privacy>()?;
 
                     let entity_id = extract::<String>(indexmap, "id")?;
                     // fetch name from the models table
@@ -135,8 +137,16 @@ fn model_union_field() -> Field {
 
                     let mut results: Vec<FieldValue<'_>> = Vec::new();
                     for (id, name) in model_ids {
+                        if privacy.is_private_model(&name) {
+                            continue;
+                        }
+
                         // the model id is used as the id for the model members
-                        let type_mapping = type_mapping_query(&mut conn, &id).await?;
+                        let type_mapping = public_type_mapping(
+                            type_mapping_query(&mut conn, &id).await?,
+                            &name,
+                            privacy,
+                        );
 
                         // but the model data tables use the unhashed model name as the table name
                         let data = model_data_recursive_query(
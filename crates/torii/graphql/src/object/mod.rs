@@ -6,6 +6,8 @@ pub mod inputs;
 pub mod metadata;
 pub mod model;
 pub mod model_data;
+pub mod resolved_content;
+pub mod search;
 pub mod transaction;
 
 use async_graphql::dynamic::{
@@ -4,13 +4,14 @@ use async_graphql::dynamic::{
 };
 use async_graphql::{Name, Value};
 use tokio_stream::StreamExt;
+use torii_core::privacy::Privacy;
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::types::Model;
 
 use super::{resolve_many, BasicObject, ResolvableObject, TypeMapping, ValueMapping};
 use crate::constants::{
     DATETIME_FORMAT, ID_COLUMN, MODEL_NAMES, MODEL_ORDER_FIELD_TYPE_NAME, MODEL_ORDER_TYPE_NAME,
-    MODEL_TABLE, MODEL_TYPE_NAME, ORDER_ASC, ORDER_DESC, ORDER_DIR_TYPE_NAME,
+    MODEL_TYPE_NAME, ORDER_ASC, ORDER_DESC, ORDER_DIR_TYPE_NAME,
 };
 use crate::mapping::MODEL_TYPE_MAPPING;
 use crate::object::resolve_one;
@@ -18,7 +19,16 @@ use crate::object::resolve_one;
 const ORDER_BY_NAME: &str = "NAME";
 const ORDER_BY_HASH: &str = "CLASS_HASH";
 
-pub struct ModelObject;
+pub struct ModelObject {
+    table_name: String,
+}
+
+impl ModelObject {
+    /// The private models are neither resolved nor streamed.
+    pub fn new(privacy: &Privacy) -> Self {
+        Self { table_name: privacy.public_models_table() }
+    }
+}
 
 impl BasicObject for ModelObject {
     fn name(&self) -> (&str, &str) {
@@ -53,7 +63,7 @@ impl ResolvableObject for ModelObject {
 
     fn resolvers(&self) -> Vec<Field> {
         let resolve_one = resolve_one(
-            MODEL_TABLE,
+            &self.table_name,
             ID_COLUMN,
             self.name().0,
             self.type_name(),
@@ -61,7 +71,7 @@ impl ResolvableObject for ModelObject {
         );
 
         let mut resolve_many = resolve_many(
-            MODEL_TABLE,
+            &self.table_name,
             ID_COLUMN,
             self.name().1,
             self.type_name(),
@@ -82,9 +92,14 @@ impl ResolvableObject for ModelObject {
                             Some(id) => Some(id.string()?.to_string()),
                             None => None,
                         };
+                        let privacy = ctx.data::<Privacy>()?.clone();
                         // if id is None, then subscribe to all models
                         // if id is Some, then subscribe to only the model with that id
                         Ok(SimpleBroker::<Model>::subscribe().filter_map(move |model: Model| {
+                            if privacy.is_private_model(&model.name) {
+                                return None;
+                            }
+
                             if id.is_none() || id == Some(model.id.clone()) {
                                 Some(Ok(Value::Object(ModelObject::value_mapping(model))))
                             } else {
@@ -187,6 +187,10 @@ pub fn object(type_name: &str, type_mapping: &TypeMapping, path_array: Vec<Strin
                     // Nested types resolution
                     if let TypeData::Nested((_, nested_mapping)) = type_data {
                         return match ctx.parent_value.try_to_value()? {
+                            // already resolved, eg. values of models at a past block
+                            Value::Object(indexmap) if indexmap.contains_key(&field_name) => {
+                                Ok(Some(indexmap[&field_name].clone()))
+                            }
                             Value::Object(indexmap) => {
                                 let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                                 let entity_id =
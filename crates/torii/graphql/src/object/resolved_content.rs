@@ -0,0 +1,47 @@
+use async_graphql::dynamic::Field;
+
+use super::{BasicObject, ResolvableObject, TypeMapping};
+use crate::constants::{
+    ID_COLUMN, RESOLVED_CONTENT_NAMES, RESOLVED_CONTENT_TABLE, RESOLVED_CONTENT_TYPE_NAME,
+};
+use crate::mapping::RESOLVED_CONTENT_MAPPING;
+use crate::object::{resolve_many, resolve_one};
+
+/// The external contents referenced by the indexed resources, eg. metadata and images, by URI.
+pub struct ResolvedContentObject;
+
+impl BasicObject for ResolvedContentObject {
+    fn name(&self) -> (&str, &str) {
+        RESOLVED_CONTENT_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        RESOLVED_CONTENT_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &RESOLVED_CONTENT_MAPPING
+    }
+}
+
+impl ResolvableObject for ResolvedContentObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let resolve_one = resolve_one(
+            RESOLVED_CONTENT_TABLE,
+            ID_COLUMN,
+            self.name().0,
+            self.type_name(),
+            self.type_mapping(),
+        );
+
+        let resolve_many = resolve_many(
+            RESOLVED_CONTENT_TABLE,
+            ID_COLUMN,
+            self.name().1,
+            self.type_name(),
+            self.type_mapping(),
+        );
+
+        vec![resolve_one, resolve_many]
+    }
+}
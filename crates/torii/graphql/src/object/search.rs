@@ -0,0 +1,294 @@
+use std::str::FromStr;
+
+use async_graphql::dynamic::{Enum, Field, FieldFuture, InputValue, Object, TypeRef};
+use async_graphql::{Name, Value};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+use torii_core::privacy::Privacy;
+use torii_core::sql::{SEARCH_ENTITY_KEY, SEARCH_METADATA, SEARCH_MODEL_MEMBER};
+use tracing::warn;
+
+use super::{BasicObject, ResolvableObject, TypeMapping, ValueMapping};
+use crate::constants::{
+    DEFAULT_LIMIT, SEARCH_KIND_TYPE_NAME, SEARCH_RESULT_NAMES, SEARCH_RESULT_TYPE_NAME,
+};
+use crate::mapping::SEARCH_RESULT_TYPE_MAPPING;
+use crate::query::data::fetch_world_address;
+use crate::utils::extract;
+
+// The trigram tokenizer of the search index only matches terms of at least 3 characters.
+const MIN_INDEXED_TERM_LEN: usize = 3;
+
+/// The databases of the other worlds searched along with the one of the schema, each indexing a
+/// single world. The privacy of the schema applies to all of them.
+#[derive(Debug, Clone, Default)]
+pub struct SearchPools(pub Vec<Pool<Sqlite>>);
+
+/// How closely a result matches the search term, the higher the better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Rank {
+    Substring = 1,
+    Prefix = 2,
+    Exact = 3,
+}
+
+impl Rank {
+    fn of(value: &str, term: &str) -> Self {
+        let (value, term) = (value.to_lowercase(), term.to_lowercase());
+        if value == term {
+            Rank::Exact
+        } else if value.starts_with(&term) {
+            Rank::Prefix
+        } else {
+            Rank::Substring
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SearchDocument {
+    kind: String,
+    target_id: String,
+    model: String,
+    member: String,
+    value: String,
+}
+
+#[derive(Debug)]
+struct SearchResult {
+    kind: String,
+    id: String,
+    world_address: String,
+    model: Option<String>,
+    member: Option<String>,
+    value: String,
+    rank: Rank,
+}
+
+impl SearchResult {
+    fn new(document: SearchDocument, world_address: &str, value: String, rank: Rank) -> Self {
+        // the metadata are not stored in the tables of a model
+        let model = Some(document.model).filter(|model| !model.is_empty());
+        Self {
+            kind: document.kind,
+            id: document.target_id,
+            world_address: world_address.to_string(),
+            model,
+            member: Some(document.member),
+            value,
+            rank,
+        }
+    }
+
+    fn value_mapping(self) -> ValueMapping {
+        ValueMapping::from([
+            (Name::new("kind"), Value::Enum(Name::new(self.kind))),
+            (Name::new("id"), Value::from(self.id)),
+            (Name::new("worldAddress"), Value::from(self.world_address)),
+            (Name::new("model"), Value::from(self.model)),
+            (Name::new("member"), Value::from(self.member)),
+            (Name::new("value"), Value::from(self.value)),
+            (Name::new("rank"), Value::from(self.rank as i64)),
+        ])
+    }
+}
+
+// Searches a term across the keys of the entities, the string members of the models and the
+// metadata of the world resources, of the world of the schema and of the worlds of the
+// [SearchPools]. Results are ordered by rank, exact matches first.
+pub struct SearchObject;
+
+impl BasicObject for SearchObject {
+    fn name(&self) -> (&str, &str) {
+        SEARCH_RESULT_NAMES
+    }
+
+    fn type_name(&self) -> &str {
+        SEARCH_RESULT_TYPE_NAME
+    }
+
+    fn type_mapping(&self) -> &TypeMapping {
+        &SEARCH_RESULT_TYPE_MAPPING
+    }
+}
+
+impl ResolvableObject for SearchObject {
+    fn resolvers(&self) -> Vec<Field> {
+        let field =
+            Field::new(self.name().1, TypeRef::named_nn_list_nn(self.type_name()), move |ctx| {
+                FieldFuture::new(async move {
+                    let pool = ctx.data::<Pool<Sqlite>>()?;
+                    let others = ctx.data_opt::<SearchPools>().map(|pools| pools.0.as_slice());
+                    let privacy = ctx.data::<Privacy>()?;
+                    let term = extract::<String>(ctx.args.as_index_map(), "term")?;
+                    let limit = ctx
+                        .args
+                        .get("limit")
+                        .map(|limit| limit.u64())
+                        .transpose()?
+                        .unwrap_or(DEFAULT_LIMIT);
+                    let term = term.trim();
+
+                    let mut results = search(pool, privacy, term, limit).await?;
+                    for other in others.unwrap_or_default() {
+                        // a world which can't be searched doesn't fail the search of the others
+                        match search(other, privacy, term, limit).await {
+                            Ok(other_results) => results.extend(other_results),
+                            Err(error) => warn!(%error, "Searching the database of a world."),
+                        }
+                    }
+
+                    // stable sort, so that results of the same rank keep the order of their world
+                    results.sort_by(|a, b| b.rank.cmp(&a.rank));
+                    results.truncate(limit as usize);
+
+                    Ok(Some(Value::List(
+                        results
+                            .into_iter()
+                            .map(|result| Value::Object(result.value_mapping()))
+                            .collect(),
+                    )))
+                })
+            })
+            .argument(InputValue::new("term", TypeRef::named_nn(TypeRef::STRING)))
+            .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)));
+
+        vec![field]
+    }
+
+    fn enum_objects(&self) -> Option<Vec<Enum>> {
+        let kind = Enum::new(SEARCH_KIND_TYPE_NAME)
+            .item(SEARCH_ENTITY_KEY)
+            .item(SEARCH_MODEL_MEMBER)
+            .item(SEARCH_METADATA);
+
+        Some(vec![kind])
+    }
+
+    // search results are not paginated
+    fn connection_objects(&self) -> Option<Vec<Object>> {
+        None
+    }
+}
+
+async fn search(
+    pool: &Pool<Sqlite>,
+    privacy: &Privacy,
+    term: &str,
+    limit: u64,
+) -> sqlx::Result<Vec<SearchResult>> {
+    if term.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut conn = pool.acquire().await?;
+    let world_address = fetch_world_address(&mut conn).await?;
+    let documents = privacy.public_search_documents_table();
+
+    let mut results = search_entity_keys(&mut conn, &documents, term, limit).await?;
+    results.extend(search_values(&mut conn, &documents, term, limit).await?);
+
+    results.sort_by(|a, b| b.1.cmp(&a.1));
+    results.truncate(limit as usize);
+
+    Ok(results
+        .into_iter()
+        .map(|(document, rank, value)| SearchResult::new(document, &world_address, value, rank))
+        .collect())
+}
+
+// Keys are felts, so they are matched as a whole against the term parsed as a felt or as a cairo
+// short string, as stored in the tables of the models.
+async fn search_entity_keys(
+    conn: &mut SqliteConnection,
+    documents: &str,
+    term: &str,
+    limit: u64,
+) -> sqlx::Result<Vec<(SearchDocument, Rank, String)>> {
+    let mut keys = Vec::new();
+    for felt in [FieldElement::from_str(term).ok(), cairo_short_string_to_felt(term).ok()] {
+        // the small integer keys are stored as decimals
+        keys.extend(felt.into_iter().flat_map(|felt| [format!("0x{felt:064x}"), felt.to_string()]));
+    }
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = vec!["?"; keys.len()].join(", ");
+    let query = format!(
+        "SELECT kind, target_id, model, member, value FROM {documents} WHERE kind = ? AND value \
+         IN ({placeholders}) LIMIT ?"
+    );
+    let mut query = sqlx::query_as::<_, SearchDocument>(&query).bind(SEARCH_ENTITY_KEY);
+    for key in &keys {
+        query = query.bind(key);
+    }
+    let rows = query.bind(limit as i64).fetch_all(&mut *conn).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|document| {
+            let value = FieldElement::from_str(&document.value)
+                .map_or_else(|_| document.value.clone(), |felt| format!("{felt:#x}"));
+            (document, Rank::Exact, value)
+        })
+        .collect())
+}
+
+// The string members and the metadata are matched anywhere in their value through the trigram
+// index, or only from their start through the index of the values for the shorter terms, in which
+// case the case matters.
+async fn search_values(
+    conn: &mut SqliteConnection,
+    documents: &str,
+    term: &str,
+    limit: u64,
+) -> sqlx::Result<Vec<(SearchDocument, Rank, String)>> {
+    let columns = "search_documents.kind, search_documents.target_id, search_documents.model, \
+                   search_documents.member, search_documents.value";
+    let kinds = format!("search_documents.kind IN ('{SEARCH_MODEL_MEMBER}', '{SEARCH_METADATA}')");
+
+    let rows: Vec<SearchDocument> = if term.chars().count() >= MIN_INDEXED_TERM_LEN {
+        // the best matches are kept if there are more than `limit` of them
+        let query = format!(
+            "SELECT {columns} FROM search_index JOIN {documents} ON search_documents.id = \
+             search_index.rowid WHERE search_index MATCH ? AND {kinds} ORDER BY CASE WHEN \
+             search_documents.value LIKE ? ESCAPE '\\' THEN 0 WHEN search_documents.value LIKE ? \
+             ESCAPE '\\' THEN 1 ELSE 2 END LIMIT ?"
+        );
+        let escaped = escape_like(term);
+        sqlx::query_as(&query)
+            .bind(format!("\"{}\"", term.replace('"', "\"\"")))
+            .bind(&escaped)
+            .bind(format!("{escaped}%"))
+            .bind(limit as i64)
+            .fetch_all(&mut *conn)
+            .await?
+    } else {
+        let query = format!(
+            "SELECT {columns} FROM {documents} WHERE {kinds} AND search_documents.value >= ? AND \
+             search_documents.value < ? ORDER BY length(search_documents.value) LIMIT ?"
+        );
+        sqlx::query_as(&query)
+            .bind(term)
+            .bind(format!("{term}{}", char::MAX))
+            .bind(limit as i64)
+            .fetch_all(&mut *conn)
+            .await?
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|document| {
+            let rank = Rank::of(&document.value, term);
+            let value = document.value.clone();
+            (document, rank, value)
+        })
+        .collect())
+}
+
+// Escapes the wildcards of `term`, to be matched literally by `LIKE ? ESCAPE '\'`.
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
@@ -1,13 +1,23 @@
 use async_graphql::dynamic::Field;
+use torii_core::privacy::Privacy;
 
 use super::{BasicObject, ResolvableObject, TypeMapping};
 use crate::constants::{
-    ID_COLUMN, TRANSACTION_HASH_COLUMN, TRANSACTION_NAMES, TRANSACTION_TABLE, TRANSACTION_TYPE_NAME,
+    ID_COLUMN, TRANSACTION_HASH_COLUMN, TRANSACTION_NAMES, TRANSACTION_TYPE_NAME,
 };
 use crate::mapping::TRANSACTION_MAPPING;
 use crate::object::{resolve_many, resolve_one};
 
-pub struct TransactionObject;
+pub struct TransactionObject {
+    table_name: String,
+}
+
+impl TransactionObject {
+    /// The transactions are resolved without the calldata of the ones emitting private events.
+    pub fn new(privacy: &Privacy) -> Self {
+        Self { table_name: privacy.public_transactions_table() }
+    }
+}
 
 impl BasicObject for TransactionObject {
     fn name(&self) -> (&str, &str) {
@@ -26,7 +36,7 @@ impl BasicObject for TransactionObject {
 impl ResolvableObject for TransactionObject {
     fn resolvers(&self) -> Vec<Field> {
         let resolve_one = resolve_one(
-            TRANSACTION_TABLE,
+            &self.table_name,
             TRANSACTION_HASH_COLUMN,
             self.name().0,
             self.type_name(),
@@ -34,7 +44,7 @@ impl ResolvableObject for TransactionObject {
         );
 
         let resolve_many = resolve_many(
-            TRANSACTION_TABLE,
+            &self.table_name,
             ID_COLUMN,
             self.name().1,
             self.type_name(),
@@ -86,7 +86,8 @@ pub async fn fetch_multiple_rows(
     match order {
         Some(order) => {
             let mut column_name = order.field.clone();
-            if table_name != MODEL_TABLE {
+            // the models may be selected from a subquery aliased as the models table
+            if table_name != MODEL_TABLE && !table_name.ends_with(&format!(" AS {MODEL_TABLE}")) {
                 column_name = format!("external_{}", column_name);
             }
             query.push_str(&format!(
@@ -5,8 +5,10 @@ use async_graphql::{Name, Value};
 use chrono::{DateTime, Utc};
 use convert_case::{Case, Casing};
 use dojo_types::primitive::{Primitive, SqlType};
+use dojo_types::schema::{Struct, Ty};
 use sqlx::sqlite::SqliteRow;
 use sqlx::{Row, SqliteConnection};
+use torii_core::privacy::Privacy;
 use torii_core::sql::FELT_DELIMITER;
 
 use crate::constants::{BOOLEAN_TRUE, ENTITY_ID_COLUMN, INTERNAL_ENTITY_ID_KEY};
@@ -28,6 +30,16 @@ pub async fn type_mapping_query(
     build_type_mapping(&root_members, &nested_members)
 }
 
+/// Removes the private members of `model` from its type mapping.
+pub fn public_type_mapping(
+    mut type_mapping: TypeMapping,
+    model: &str,
+    privacy: &Privacy,
+) -> TypeMapping {
+    type_mapping.retain(|name, _| !privacy.is_private_member(model, name.as_str()));
+    type_mapping
+}
+
 async fn fetch_model_members(
     conn: &mut SqliteConnection,
     model_id: &str,
@@ -72,6 +84,8 @@ fn member_to_type_data(member: &ModelMember, nested_members: &[&ModelMember]) ->
     match member.type_enum.as_str() {
         "Primitive" => TypeData::Simple(TypeRef::named(&member.ty)),
         "Enum" => TypeData::Simple(TypeRef::named("Enum")),
+        "ByteArray" => TypeData::Simple(TypeRef::named(TypeRef::STRING)),
+        "Array" => TypeData::Simple(TypeRef::named_list(Primitive::Felt252(None).to_string())),
         _ => parse_nested_type(
             &member.model_id,
             &member.id,
@@ -150,6 +164,54 @@ pub fn value_mapping_from_row(
     Ok(value_mapping)
 }
 
+/// Builds the value mapping of a model from its value, like [value_mapping_from_row] does from its
+/// row. Used for models whose values are read from their history instead of their tables.
+pub fn value_mapping_from_ty(
+    model: &Struct,
+    types: &TypeMapping,
+    entity_id: &str,
+) -> anyhow::Result<ValueMapping> {
+    let mut value_mapping = ValueMapping::new();
+
+    for member in &model.children {
+        let Some(type_data) = types.get(member.name.as_str()) else { continue };
+
+        let value = match (&member.ty, type_data) {
+            (Ty::Struct(nested), TypeData::Nested((_, nested_mapping))) => {
+                Value::Object(value_mapping_from_ty(nested, nested_mapping, entity_id)?)
+            }
+            (Ty::Primitive(primitive), _) => primitive_value(primitive)?,
+            (Ty::Enum(enum_ty), _) => Value::from(enum_ty.to_sql_value()?),
+            (Ty::ByteArray(string), _) => Value::from(string.clone()),
+            (Ty::Array(items), _) => Value::List(
+                items
+                    .iter()
+                    .map(|item| remove_hex_leading_zeros(Value::from(format!("{item:#x}"))))
+                    .collect(),
+            ),
+            _ => continue,
+        };
+
+        value_mapping.insert(Name::new(&member.name), value);
+    }
+
+    value_mapping.insert(Name::new(INTERNAL_ENTITY_ID_KEY), Value::from(entity_id));
+
+    Ok(value_mapping)
+}
+
+fn primitive_value(primitive: &Primitive) -> anyhow::Result<Value> {
+    let value = primitive.to_sql_value()?;
+
+    Ok(match primitive {
+        Primitive::Bool(_) => Value::from(value.parse::<i64>()? == BOOLEAN_TRUE),
+        _ => match primitive.to_sql_type() {
+            SqlType::Integer => Value::from(value.parse::<i64>()?),
+            SqlType::Text => remove_hex_leading_zeros(Value::from(value)),
+        },
+    })
+}
+
 fn fetch_value(
     row: &SqliteRow,
     field_name: &str,
@@ -184,6 +246,18 @@ fn fetch_value(
                         .to_rfc3339();
                     Value::from(dt)
                 }
+                // felt arrays of models are stored as blobs of 32 bytes big endian felts
+                "[felt252]" if is_external => {
+                    let blob = row.try_get::<Vec<u8>, &str>(&column_name)?;
+                    let felts = blob
+                        .chunks(32)
+                        .map(|felt| {
+                            let hex = felt.iter().map(|b| format!("{b:02x}")).collect::<String>();
+                            remove_hex_leading_zeros(Value::from(format!("0x{hex}")))
+                        })
+                        .collect();
+                    Value::List(felts)
+                }
                 _ => {
                     let s = row.try_get::<String, &str>(&column_name)?;
                     Value::from(s)
@@ -2,6 +2,7 @@ use anyhow::Result;
 use async_graphql::dynamic::{Object, Scalar, Schema, Subscription, Union};
 use convert_case::{Case, Casing};
 use sqlx::SqlitePool;
+use torii_core::privacy::Privacy;
 use torii_core::types::Model;
 
 use super::object::connection::page_info::PageInfoObject;
@@ -15,17 +16,34 @@ use crate::object::metadata::content::ContentObject;
 use crate::object::metadata::social::SocialObject;
 use crate::object::metadata::MetadataObject;
 use crate::object::model::ModelObject;
+use crate::object::resolved_content::ResolvedContentObject;
+use crate::object::search::{SearchObject, SearchPools};
 use crate::object::transaction::TransactionObject;
 use crate::object::ObjectVariant;
-use crate::query::type_mapping_query;
+use crate::query::{public_type_mapping, type_mapping_query};
 
 // The graphql schema is built dynamically at runtime, this is because we won't know the schema of
 // the models until runtime. There are however, predefined objects such as entities and
 // events, their schema is known but we generate them dynamically as well because async-graphql
 // does not allow mixing of static and dynamic schemas.
 pub async fn build_schema(pool: &SqlitePool) -> Result<Schema> {
+    build_schema_with_privacy(pool, Privacy::default()).await
+}
+
+/// Builds the schema without the private models and members of `privacy`.
+pub async fn build_schema_with_privacy(pool: &SqlitePool, privacy: Privacy) -> Result<Schema> {
+    build_schema_with_search(pool, privacy, Vec::new()).await
+}
+
+/// Builds the schema without the private models and members of `privacy`, whose search also
+/// searches the databases of the worlds of `search_pools`.
+pub async fn build_schema_with_search(
+    pool: &SqlitePool,
+    privacy: Privacy,
+    search_pools: Vec<SqlitePool>,
+) -> Result<Schema> {
     // build world gql objects
-    let (objects, union) = build_objects(pool).await?;
+    let (objects, union) = build_objects(pool, &privacy).await?;
 
     let mut schema_builder = Schema::build(QUERY_TYPE_NAME, None, Some(SUBSCRIPTION_TYPE_NAME));
     let mut query_root = Object::new(QUERY_TYPE_NAME);
@@ -94,11 +112,16 @@ pub async fn build_schema(pool: &SqlitePool) -> Result<Schema> {
         .register(query_root)
         .register(subscription_root)
         .data(pool.clone())
+        .data(privacy)
+        .data(SearchPools(search_pools))
         .finish()
         .map_err(|e| e.into())
 }
 
-async fn build_objects(pool: &SqlitePool) -> Result<(Vec<ObjectVariant>, Union)> {
+async fn build_objects(
+    pool: &SqlitePool,
+    privacy: &Privacy,
+) -> Result<(Vec<ObjectVariant>, Union)> {
     let mut conn = pool.acquire().await?;
     let models: Vec<Model> = sqlx::query_as("SELECT * FROM models").fetch_all(&mut *conn).await?;
 
@@ -106,10 +129,12 @@ async fn build_objects(pool: &SqlitePool) -> Result<(Vec<ObjectVariant>, Union)>
     let mut objects: Vec<ObjectVariant> = vec![
         ObjectVariant::Resolvable(Box::new(EntityObject)),
         ObjectVariant::Resolvable(Box::new(EventMessageObject)),
-        ObjectVariant::Resolvable(Box::new(EventObject)),
+        ObjectVariant::Resolvable(Box::new(EventObject::new(privacy))),
         ObjectVariant::Resolvable(Box::new(MetadataObject)),
-        ObjectVariant::Resolvable(Box::new(ModelObject)),
-        ObjectVariant::Resolvable(Box::new(TransactionObject)),
+        ObjectVariant::Resolvable(Box::new(ModelObject::new(privacy))),
+        ObjectVariant::Resolvable(Box::new(ResolvedContentObject)),
+        ObjectVariant::Resolvable(Box::new(SearchObject)),
+        ObjectVariant::Resolvable(Box::new(TransactionObject::new(privacy))),
         ObjectVariant::Basic(Box::new(SocialObject)),
         ObjectVariant::Basic(Box::new(ContentObject)),
         ObjectVariant::Basic(Box::new(PageInfoObject)),
@@ -120,7 +145,15 @@ async fn build_objects(pool: &SqlitePool) -> Result<(Vec<ObjectVariant>, Union)>
 
     // model data objects
     for model in models {
-        let type_mapping = type_mapping_query(&mut conn, &model.id).await?;
+        if privacy.is_private_model(&model.name) {
+            continue;
+        }
+
+        let type_mapping = public_type_mapping(
+            type_mapping_query(&mut conn, &model.id).await?,
+            &model.name,
+            privacy,
+        );
 
         if !type_mapping.is_empty() {
             let field_name = model.name.to_case(Case::Camel);
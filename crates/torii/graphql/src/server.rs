@@ -8,10 +8,11 @@ use async_graphql_warp::graphql_subscription;
 use serde_json::json;
 use sqlx::{Pool, Sqlite};
 use tokio::sync::broadcast::Receiver;
+use torii_core::privacy::Privacy;
 use url::Url;
 use warp::{Filter, Rejection, Reply};
 
-use super::schema::build_schema;
+use super::schema::build_schema_with_search;
 use crate::constants::MODEL_TABLE;
 use crate::query::data::count_rows;
 
@@ -19,8 +20,10 @@ pub async fn new(
     mut shutdown_rx: Receiver<()>,
     pool: &Pool<Sqlite>,
     external_url: Option<Url>,
+    privacy: Privacy,
+    search_pools: Vec<Pool<Sqlite>>,
 ) -> (SocketAddr, impl Future<Output = ()> + 'static) {
-    let schema = build_schema(pool).await.unwrap();
+    let schema = build_schema_with_search(pool, privacy, search_pools).await.unwrap();
     let mut conn = pool.acquire().await.unwrap();
     let num_models = count_rows(&mut conn, MODEL_TABLE, &None, &None).await.unwrap();
 
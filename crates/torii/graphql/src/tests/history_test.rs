@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use serde_json::{json, Value};
+    use sqlx::SqlitePool;
+    use starknet_crypto::{poseidon_hash_many, FieldElement};
+    use torii_core::sql::Sql;
+
+    use crate::schema::build_schema;
+    use crate::tests::run_graphql_query;
+
+    fn position(x: Option<u32>) -> Ty {
+        Ty::Struct(Struct {
+            name: "Position".to_string(),
+            children: vec![
+                Member {
+                    name: "player".to_string(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::ContractAddress(x.map(|_| FieldElement::TWO))),
+                },
+                Member { name: "x".to_string(), key: false, ty: Ty::Primitive(Primitive::U32(x)) },
+            ],
+        })
+    }
+
+    async fn entities_at(pool: &SqlitePool, at: &str) -> Value {
+        let schema = build_schema(pool).await.unwrap();
+        let query = format!(
+            "{{ entities({at}) {{ totalCount edges {{ node {{ id keys models {{ ... on Position \
+             {{ x }} }} }} }} }} }}"
+        );
+        let result = run_graphql_query(&schema, &query).await;
+        result.get("entities").unwrap().clone()
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_entities_at(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+        db.register_model(position(None), vec![], FieldElement::ONE, FieldElement::TWO, 0, 0, 0)
+            .await
+            .unwrap();
+
+        // the block timestamps are ten times the block numbers, and the entity is deleted at the
+        // fifth block
+        let keys = [FieldElement::TWO];
+        db.store_entity_history(&keys, "Position", Some(&position(Some(1))), "0x1", 1, 10).unwrap();
+        db.store_entity_history(&keys, "Position", Some(&position(Some(2))), "0x2", 3, 30).unwrap();
+        db.store_entity_history(&keys, "Position", None, "0x3", 5, 50).unwrap();
+        db.execute().await.unwrap();
+
+        let id = format!("{:#x}", poseidon_hash_many(&keys));
+        for (at, x) in
+            [("block: 2", 1), ("block: 3", 2), ("timestamp: 29", 1), ("timestamp: 30", 2)]
+        {
+            let entities = entities_at(&pool, at).await;
+            assert_eq!(entities["totalCount"], 1, "{at}");
+            let node = &entities["edges"][0]["node"];
+            assert_eq!(node["id"], id.as_str(), "{at}");
+            assert_eq!(node["keys"], json!(["0x2"]), "{at}");
+            assert_eq!(node["models"], json!([{ "x": x }]), "{at}");
+        }
+
+        // the entity isn't listed before its first model nor after its deletion
+        for at in ["block: 0", "block: 5", "timestamp: 9"] {
+            let entities = entities_at(&pool, at).await;
+            assert_eq!(entities["totalCount"], 0, "{at}");
+            assert_eq!(entities["edges"], json!([]), "{at}");
+        }
+
+        // an entity deleted since is still resolved as it was
+        let schema = build_schema(&pool).await.unwrap();
+        let query = format!(
+            r#"{{ entity(id: "{id}", block: 4) {{ id models {{ ... on Position {{ x }} }} }} }}"#
+        );
+        let result = run_graphql_query(&schema, &query).await;
+        assert_eq!(result["entity"]["models"], json!([{ "x": 2 }]));
+
+        // a block and a timestamp can't be both given
+        let query = format!(r#"{{ entity(id: "{id}", block: 4, timestamp: 40) {{ id }} }}"#);
+        assert!(!schema.execute(&query).await.errors.is_empty());
+    }
+}
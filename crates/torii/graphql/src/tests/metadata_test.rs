@@ -3,6 +3,7 @@ mod tests {
     use dojo_world::metadata::{project_to_world_metadata, ProjectMetadata};
     use sqlx::SqlitePool;
     use starknet_crypto::FieldElement;
+    use torii_core::resolver::ResolvedContent;
     use torii_core::sql::Sql;
 
     use crate::schema::build_schema;
@@ -115,4 +116,28 @@ mod tests {
             }
         );
     }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_resolved_contents(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+        let schema = build_schema(&pool).await.unwrap();
+
+        db.store_resolved_content(&ResolvedContent {
+            uri: URI.to_string(),
+            content_type: Some("application/json".to_string()),
+            data: r#"{"name":"example"}"#.into(),
+        })
+        .await
+        .unwrap();
+
+        let query = format!(
+            r#"{{ resolvedContent(id: "{URI}") {{ id contentType encoding size data }} }}"#
+        );
+        let result = run_graphql_query(&schema, &query).await;
+        let content = result.get("resolvedContent").ok_or("content not found").unwrap();
+        assert_eq!(content["contentType"], "application/json");
+        assert_eq!(content["encoding"], "utf-8");
+        assert_eq!(content["size"], 18);
+        assert_eq!(content["data"], r#"{"name":"example"}"#);
+    }
 }
@@ -34,9 +34,12 @@ use torii_core::sql::Sql;
 
 mod entities_test;
 mod events_test;
+mod history_test;
 mod metadata_test;
 mod models_ordering_test;
 mod models_test;
+mod privacy_test;
+mod search_test;
 mod subscription_test;
 
 use crate::schema::build_schema;
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use serde_json::Value;
+    use sqlx::SqlitePool;
+    use starknet::core::types::Event;
+    use starknet::core::utils::cairo_short_string_to_felt;
+    use starknet::macros::selector;
+    use starknet_crypto::FieldElement;
+    use torii_core::privacy::{Privacy, PrivacyConfig};
+    use torii_core::sql::Sql;
+
+    use crate::schema::build_schema_with_privacy;
+    use crate::tests::run_graphql_query;
+
+    const BLOCK_TIMESTAMP: u64 = 1710754478;
+
+    fn model(name: &str) -> Ty {
+        Ty::Struct(Struct {
+            name: name.to_string(),
+            children: vec![Member {
+                name: "value".to_string(),
+                key: false,
+                ty: Ty::Primitive(Primitive::U32(None)),
+            }],
+        })
+    }
+
+    fn set_record(model: &str) -> Event {
+        Event {
+            from_address: FieldElement::ZERO,
+            keys: vec![selector!("StoreSetRecord")],
+            data: vec![cairo_short_string_to_felt(model).unwrap(), FieldElement::ONE],
+        }
+    }
+
+    fn nodes(result: &Value, field: &str) -> Vec<Value> {
+        let edges = result.get(field).unwrap().get("edges").unwrap().as_array().unwrap();
+        edges.iter().map(|edge| edge.get("node").unwrap().clone()).collect()
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_private_events_transactions_and_models(pool: SqlitePool) {
+        let mut db = Sql::new(pool.clone(), FieldElement::ZERO).await.unwrap();
+        for (i, name) in ["Hand", "Position"].into_iter().enumerate() {
+            let hash = FieldElement::from(i as u8);
+            db.register_model(model(name), vec![], hash, hash, 0, 0, BLOCK_TIMESTAMP)
+                .await
+                .unwrap();
+        }
+
+        // the first transaction sets a private record, the second a public one
+        db.store_event("0x1", &set_record("Hand"), FieldElement::ONE, BLOCK_TIMESTAMP);
+        db.store_event("0x2", &set_record("Position"), FieldElement::TWO, BLOCK_TIMESTAMP);
+        db.execute().await.unwrap();
+
+        for hash in ["0x1", "0x2"] {
+            sqlx::query(
+                "INSERT INTO transactions (id, transaction_hash, sender_address, calldata, \
+                 max_fee, signature, nonce, transaction_type, executed_at) VALUES (?, ?, '0x0', \
+                 '0x5/', '0x0', '', '0x0', 'INVOKE', '2024-03-18T09:34:38+00:00')",
+            )
+            .bind(hash)
+            .bind(hash)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let config = PrivacyConfig { models: vec!["Hand".to_string()], ..Default::default() };
+        let schema = build_schema_with_privacy(&pool, Privacy::new(config).unwrap()).await.unwrap();
+        let result = run_graphql_query(
+            &schema,
+            r#"{
+                events { totalCount edges { node { id } } }
+                transactions { edges { node { transactionHash calldata } } }
+                models { totalCount edges { node { name } } }
+            }"#,
+        )
+        .await;
+
+        let events = nodes(&result, "events");
+        assert_eq!(result["events"]["totalCount"], 1);
+        assert_eq!(events[0]["id"], "0x2");
+
+        let transactions = nodes(&result, "transactions");
+        assert_eq!(transactions.len(), 2);
+        for transaction in transactions {
+            let calldata: &[Value] = match transaction["transactionHash"].as_str().unwrap() {
+                "0x1" => &[],
+                _ => &[Value::from("0x5")],
+            };
+            assert_eq!(transaction["calldata"].as_array().unwrap(), calldata);
+        }
+
+        let models = nodes(&result, "models");
+        assert_eq!(result["models"]["totalCount"], 1);
+        assert_eq!(models[0]["name"], "Position");
+    }
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use dojo_types::primitive::Primitive;
+    use dojo_types::schema::{Member, Struct, Ty};
+    use serde_json::Value;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::SqlitePool;
+    use starknet_crypto::{poseidon_hash_many, FieldElement};
+    use torii_core::privacy::{Privacy, PrivacyConfig};
+    use torii_core::sql::Sql;
+
+    use crate::schema::build_schema_with_search;
+    use crate::tests::run_graphql_query;
+
+    const BLOCK_TIMESTAMP: u64 = 1710754478;
+
+    fn player(address: Option<FieldElement>, name: &str) -> Ty {
+        Ty::Struct(Struct {
+            name: "Player".to_string(),
+            children: vec![
+                Member {
+                    name: "address".to_string(),
+                    key: true,
+                    ty: Ty::Primitive(Primitive::ContractAddress(address)),
+                },
+                Member {
+                    name: "name".to_string(),
+                    key: false,
+                    ty: Ty::ByteArray(name.to_string()),
+                },
+            ],
+        })
+    }
+
+    async fn search(pool: &SqlitePool, term: &str) -> Vec<Value> {
+        search_with(pool, Privacy::default(), vec![], term).await
+    }
+
+    async fn search_with(
+        pool: &SqlitePool,
+        privacy: Privacy,
+        search_pools: Vec<SqlitePool>,
+        term: &str,
+    ) -> Vec<Value> {
+        let schema = build_schema_with_search(pool, privacy, search_pools).await.unwrap();
+        let query = format!(
+            r#"{{ search(term: "{term}") {{ kind id worldAddress model member value rank }} }}"#
+        );
+        let result = run_graphql_query(&schema, &query).await;
+        result.get("search").unwrap().as_array().unwrap().clone()
+    }
+
+    // Indexes the players of `players`, as the entities of the world `world_address`.
+    async fn index_players(
+        pool: &SqlitePool,
+        world_address: FieldElement,
+        players: &[(FieldElement, &str)],
+    ) -> Sql {
+        let mut db = Sql::new(pool.clone(), world_address).await.unwrap();
+        db.register_model(
+            player(None, ""),
+            vec![],
+            FieldElement::ONE,
+            FieldElement::TWO,
+            0,
+            0,
+            BLOCK_TIMESTAMP,
+        )
+        .await
+        .unwrap();
+
+        for (index, (address, name)) in players.iter().enumerate() {
+            db.set_entity(player(Some(*address), name), &format!("0x{index}"), BLOCK_TIMESTAMP)
+                .await
+                .unwrap();
+        }
+        db
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_search(pool: SqlitePool) {
+        let players = [
+            (FieldElement::from(0x10u8), "alice2"),
+            (FieldElement::from(0x20u8), "alice"),
+            (FieldElement::from(0x30u8), "malice"),
+        ];
+        let mut db = index_players(&pool, FieldElement::ZERO, &players).await;
+
+        // exact matches are ranked first, then prefix matches, regardless of the case
+        let results = search(&pool, "Alice").await;
+        let values = results.iter().map(|result| result["value"].clone()).collect::<Vec<_>>();
+        assert_eq!(values, vec!["alice", "alice2", "malice"]);
+        let ranks = results.iter().map(|result| result["rank"].clone()).collect::<Vec<_>>();
+        assert_eq!(ranks, vec![3, 2, 1]);
+
+        let result = &results[2];
+        assert_eq!(result["kind"], "MODEL_MEMBER");
+        assert_eq!(result["model"], "Player");
+        assert_eq!(result["member"], "name");
+        assert_eq!(result["worldAddress"], "0x0");
+        assert_eq!(result["id"], format!("{:#x}", poseidon_hash_many(&[players[2].0])));
+
+        // keys are matched as felts
+        let results = search(&pool, "0x0020").await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["kind"], "ENTITY_KEY");
+        assert_eq!(results[0]["id"], format!("{:#x}", poseidon_hash_many(&[players[1].0])));
+        assert_eq!(results[0]["value"], "0x20");
+        assert_eq!(results[0]["rank"], 3);
+
+        // wildcards are matched literally
+        assert!(search(&pool, "%").await.is_empty());
+        assert!(search(&pool, "al_ce").await.is_empty());
+
+        // the terms too short for the index are only matched at the start of the values
+        let results = search(&pool, "al").await;
+        let values = results.iter().map(|result| result["value"].clone()).collect::<Vec<_>>();
+        assert_eq!(values, vec!["alice", "alice2"]);
+
+        // the documents of the deleted entities are removed
+        db.delete_entity(vec![players[2].0], player(Some(players[2].0), "")).await.unwrap();
+        let results = search(&pool, "malice").await;
+        assert!(results.iter().all(|result| result["value"] != "malice"));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_search_private_members(pool: SqlitePool) {
+        let players = [(FieldElement::from(0x20u8), "alice")];
+        index_players(&pool, FieldElement::ZERO, &players).await;
+
+        let privacy = |members: &[&str], models: &[&str]| {
+            let members = members.iter().map(|member| member.to_string()).collect();
+            let models = models.iter().map(|model| model.to_string()).collect();
+            Privacy::new(PrivacyConfig { members, models }).unwrap()
+        };
+
+        // the private members are not searched, the keys still are
+        let privacy_name = privacy(&["Player.name"], &[]);
+        assert!(search_with(&pool, privacy_name.clone(), vec![], "alice").await.is_empty());
+        assert_eq!(search_with(&pool, privacy_name, vec![], "0x20").await.len(), 1);
+
+        // neither are the keys of the private models
+        let privacy_player = privacy(&[], &["Player"]);
+        assert!(search_with(&pool, privacy_player.clone(), vec![], "alice").await.is_empty());
+        assert!(search_with(&pool, privacy_player, vec![], "0x20").await.is_empty());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_search_across_worlds(pool: SqlitePool) {
+        index_players(&pool, FieldElement::ZERO, &[(FieldElement::from(0x10u8), "alice2")]).await;
+
+        let options = SqliteConnectOptions::from_str("sqlite::memory:").unwrap();
+        let other = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+        sqlx::migrate!("../migrations").run(&other).await.unwrap();
+        index_players(&other, FieldElement::ONE, &[(FieldElement::from(0x20u8), "alice")]).await;
+
+        // the results of all the worlds are ranked together
+        let results = search_with(&pool, Privacy::default(), vec![other], "alice").await;
+        let values = results.iter().map(|result| result["value"].clone()).collect::<Vec<_>>();
+        assert_eq!(values, vec!["alice", "alice2"]);
+        let worlds =
+            results.iter().map(|result| result["worldAddress"].clone()).collect::<Vec<_>>();
+        assert_eq!(worlds, vec!["0x1", "0x0"]);
+    }
+}
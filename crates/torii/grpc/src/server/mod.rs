@@ -30,7 +30,8 @@ use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 use torii_core::cache::ModelCache;
 use torii_core::error::{Error, ParseError, QueryError};
-use torii_core::model::{build_sql_query, map_row_to_ty};
+use torii_core::model::{build_sql_query, entity_models_at, map_row_to_ty, HistoryPoint};
+use torii_core::privacy::Privacy;
 
 use self::subscriptions::entity::EntityManager;
 use self::subscriptions::event_message::EventMessageManager;
@@ -57,8 +58,9 @@ impl DojoWorld {
         block_rx: Receiver<u64>,
         world_address: FieldElement,
         provider: Arc<JsonRpcClient<HttpTransport>>,
+        privacy: Privacy,
     ) -> Self {
-        let model_cache = Arc::new(ModelCache::new(pool.clone()));
+        let model_cache = Arc::new(ModelCache::with_privacy(pool.clone(), privacy));
         let entity_manager = Arc::new(EntityManager::default());
         let event_message_manager = Arc::new(EventMessageManager::default());
         let state_diff_manager = Arc::new(StateDiffManager::default());
@@ -111,7 +113,7 @@ impl DojoWorld {
 
         let mut models_metadata = Vec::with_capacity(models.len());
         for model in models {
-            let schema = self.model_cache.schema(&model.0).await?;
+            let Some(schema) = self.model_cache.public_schema(&model.0).await? else { continue };
             models_metadata.push(proto::types::ModelMetadata {
                 name: model.1,
                 class_hash: model.2,
@@ -141,13 +143,15 @@ impl DojoWorld {
     }
 
     async fn events_all(&self, limit: u32, offset: u32) -> Result<Vec<proto::types::Event>, Error> {
-        let query = r#"
+        let query = format!(
+            r#"
             SELECT keys, data, transaction_hash
-            FROM events 
+            FROM {}
             ORDER BY id DESC
             LIMIT ? OFFSET ?
-         "#
-        .to_string();
+         "#,
+            self.model_cache.privacy().public_events_table()
+        );
 
         let row_events: Vec<(String, String, String)> =
             sqlx::query_as(&query).bind(limit).bind(offset).fetch_all(&self.pool).await?;
@@ -211,6 +215,9 @@ impl DojoWorld {
         for (entity_id, models_str) in db_entities {
             let model_ids: Vec<&str> = models_str.split(',').collect();
             let schemas = self.model_cache.schemas(model_ids).await?;
+            if schemas.is_empty() {
+                continue;
+            }
 
             let entity_query = format!("{} WHERE {table}.id = ?", build_sql_query(&schemas)?);
             let row = sqlx::query(&entity_query).bind(&entity_id).fetch_one(&self.pool).await?;
@@ -257,6 +264,13 @@ impl DojoWorld {
             .collect::<Result<Vec<_>, Error>>()?;
         let keys_pattern = keys.join("/") + "/%";
 
+        // private models can't be queried
+        let model_id = format!(
+            "{:#x}",
+            get_selector_from_name(&keys_clause.model).map_err(ParseError::NonAsciiName)?
+        );
+        self.model_cache.schema(&model_id).await?;
+
         let count_query = format!(
             r#"
             SELECT count(*)
@@ -264,10 +278,7 @@ impl DojoWorld {
             JOIN {model_relation_table} ON {table}.id = {model_relation_table}.entity_id
             WHERE {model_relation_table}.model_id = '{}' and {table}.keys LIKE ?
         "#,
-            format!(
-                "{:#x}",
-                get_selector_from_name(&keys_clause.model).map_err(ParseError::NonAsciiName)?
-            ),
+            model_id,
         );
 
         // total count of rows that matches keys_pattern without limit and offset
@@ -284,10 +295,7 @@ impl DojoWorld {
             HAVING INSTR(model_ids, '{}') > 0
             LIMIT 1
         "#,
-            format!(
-                "{:#x}",
-                get_selector_from_name(&keys_clause.model).map_err(ParseError::NonAsciiName)?
-            ),
+            model_id,
         );
         let (models_str,): (String,) =
             sqlx::query_as(&models_query).bind(&keys_pattern).fetch_one(&self.pool).await?;
@@ -338,14 +346,16 @@ impl DojoWorld {
             .collect::<Result<Vec<_>, Error>>()?;
         let keys_pattern = keys.join("/") + "/%";
 
-        let events_query = r#"
+        let events_query = format!(
+            r#"
             SELECT keys, data, transaction_hash
-            FROM events
+            FROM {}
             WHERE keys LIKE ?
             ORDER BY id DESC
             LIMIT ? OFFSET ?
-        "#
-        .to_string();
+        "#,
+            self.model_cache.privacy().public_events_table()
+        );
 
         let row_events: Vec<(String, String, String)> = sqlx::query_as(&events_query)
             .bind(&keys_pattern)
@@ -388,6 +398,16 @@ impl DojoWorld {
             _ => return Err(QueryError::UnsupportedQuery.into()),
         };
 
+        // private members can't be used to filter entities
+        if self.model_cache.privacy().is_private_member(&member_clause.model, &member_clause.member)
+        {
+            return Err(QueryError::MemberNotFound(format!(
+                "{}.{}",
+                member_clause.model, member_clause.member
+            ))
+            .into());
+        }
+
         let models_query = format!(
             r#"
             SELECT group_concat({model_relation_table}.model_id) as model_ids
@@ -477,6 +497,12 @@ impl DojoWorld {
     ) -> Result<Receiver<Result<proto::world::SubscribeModelsResponse, tonic::Status>>, Error> {
         let mut subs = Vec::with_capacity(models_keys.len());
         for keys in models_keys {
+            // the diffs are the raw storage of the models, which would include their private
+            // members
+            if self.model_cache.privacy().has_private_members(&keys.model) {
+                return Err(QueryError::ModelNotFound(keys.model).into());
+            }
+
             let model = cairo_short_string_to_felt(&keys.model)
                 .map_err(ParseError::CairoShortStringToFelt)?;
 
@@ -506,6 +532,16 @@ impl DojoWorld {
         &self,
         query: proto::types::Query,
     ) -> Result<proto::world::RetrieveEntitiesResponse, Error> {
+        let at = match (query.block_number, query.timestamp) {
+            (Some(_), Some(_)) => return Err(QueryError::UnsupportedQuery.into()),
+            (Some(block_number), None) => Some(HistoryPoint::Block(block_number)),
+            (None, Some(timestamp)) => Some(HistoryPoint::Timestamp(timestamp)),
+            (None, None) => None,
+        };
+        if let Some(at) = at {
+            return self.retrieve_entities_at(query, at).await;
+        }
+
         let (entities, total_count) = match query.clause {
             None => self.entities_all(query.limit, query.offset).await?,
             Some(clause) => {
@@ -572,6 +608,48 @@ impl DojoWorld {
         Ok(RetrieveEntitiesResponse { entities, total_count })
     }
 
+    /// Retrieves the entities of a hashed keys clause with the values their models had at the
+    /// history point `at`. Entities without any model at that point are omitted, and aren't
+    /// counted in the total.
+    async fn retrieve_entities_at(
+        &self,
+        query: proto::types::Query,
+        at: HistoryPoint,
+    ) -> Result<proto::world::RetrieveEntitiesResponse, Error> {
+        let Some(ClauseType::HashedKeys(hashed_keys)) =
+            query.clause.and_then(|clause| clause.clause_type)
+        else {
+            return Err(QueryError::UnsupportedQuery.into());
+        };
+
+        let mut entities = Vec::with_capacity(hashed_keys.hashed_keys.len());
+        for id in &hashed_keys.hashed_keys {
+            let hashed_keys =
+                FieldElement::from_byte_slice_be(id).map_err(ParseError::FromByteSliceError)?;
+            let models = entity_models_at(&self.pool, &format!("{hashed_keys:#x}"), at)
+                .await?
+                .into_iter()
+                .filter_map(|(_, model)| self.model_cache.privacy().public_schema(model))
+                .map(|model| {
+                    let struct_ty = model.as_struct().expect("model should be struct").to_owned();
+                    struct_ty.try_into().unwrap()
+                })
+                .collect::<Vec<_>>();
+
+            if !models.is_empty() {
+                entities.push(proto::types::Entity {
+                    hashed_keys: hashed_keys.to_bytes_be().to_vec(),
+                    models,
+                });
+            }
+        }
+
+        let total_count = entities.len() as u32;
+        let entities =
+            entities.into_iter().skip(query.offset as usize).take(query.limit as usize).collect();
+        Ok(RetrieveEntitiesResponse { entities, total_count })
+    }
+
     async fn subscribe_event_messages(
         &self,
         hashed_keys: Vec<FieldElement>,
@@ -583,6 +661,11 @@ impl DojoWorld {
         &self,
         query: proto::types::Query,
     ) -> Result<proto::world::RetrieveEntitiesResponse, Error> {
+        // the history of event messages isn't stored
+        if query.block_number.is_some() || query.timestamp.is_some() {
+            return Err(QueryError::UnsupportedQuery.into());
+        }
+
         let (entities, total_count) = match query.clause {
             None => self.entities_all(query.limit, query.offset).await?,
             Some(clause) => {
@@ -819,6 +902,7 @@ pub async fn new(
     block_rx: Receiver<u64>,
     world_address: FieldElement,
     provider: Arc<JsonRpcClient<HttpTransport>>,
+    privacy: Privacy,
 ) -> Result<
     (SocketAddr, impl Future<Output = Result<(), tonic::transport::Error>> + 'static),
     std::io::Error,
@@ -831,7 +915,7 @@ pub async fn new(
         .build()
         .unwrap();
 
-    let world = DojoWorld::new(pool.clone(), block_rx, world_address, provider);
+    let world = DojoWorld::new(pool.clone(), block_rx, world_address, provider, privacy);
     let server = WorldServer::new(world);
 
     let server_future = Server::builder()
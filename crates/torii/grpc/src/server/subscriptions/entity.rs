@@ -101,6 +101,9 @@ impl Service {
                     sqlx::query_as(models_query).bind(hashed_keys).fetch_one(&pool).await?;
                 let model_ids: Vec<&str> = model_ids.split(',').collect();
                 let schemas = cache.schemas(model_ids).await?;
+                if schemas.is_empty() {
+                    continue;
+                }
 
                 let entity_query = format!("{} WHERE entities.id = ?", build_sql_query(&schemas)?);
                 let row = sqlx::query(&entity_query).bind(hashed_keys).fetch_one(&pool).await?;
@@ -20,6 +20,7 @@ use starknet::providers::JsonRpcClient;
 use starknet_crypto::poseidon_hash_many;
 use tokio::sync::broadcast;
 use torii_core::engine::{Engine, EngineConfig, Processors};
+use torii_core::privacy::Privacy;
 use torii_core::processors::register_model::RegisterModelProcessor;
 use torii_core::processors::store_set_record::StoreSetRecordProcessor;
 use torii_core::sql::Sql;
@@ -82,8 +83,13 @@ async fn test_entities_queries() {
     let _ = engine.sync_to_head(0).await.unwrap();
 
     let (_, receiver) = tokio::sync::mpsc::channel(1);
-    let grpc =
-        DojoWorld::new(db.pool, receiver, migration.world_address().unwrap(), provider.clone());
+    let grpc = DojoWorld::new(
+        db.pool,
+        receiver,
+        migration.world_address().unwrap(),
+        provider.clone(),
+        Privacy::default(),
+    );
 
     let entities = grpc
         .query_by_keys(
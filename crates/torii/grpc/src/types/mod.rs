@@ -20,6 +20,11 @@ pub struct Query {
     pub clause: Option<Clause>,
     pub limit: u32,
     pub offset: u32,
+    /// The block at the end of which the entities are retrieved, the latest one if `None`.
+    pub block_number: Option<u64>,
+    /// The UNIX timestamp, in seconds, at which the entities are retrieved, exclusive with
+    /// `block_number`.
+    pub timestamp: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Hash, Eq, Clone)]
@@ -149,7 +154,13 @@ impl TryFrom<proto::types::WorldMetadata> for dojo_types::WorldMetadata {
 
 impl From<Query> for proto::types::Query {
     fn from(value: Query) -> Self {
-        Self { clause: value.clause.map(|c| c.into()), limit: value.limit, offset: value.offset }
+        Self {
+            clause: value.clause.map(|c| c.into()),
+            limit: value.limit,
+            offset: value.offset,
+            block_number: value.block_number,
+            timestamp: value.timestamp,
+        }
     }
 }
 
@@ -58,6 +58,10 @@ impl TryFrom<Ty> for proto::types::Ty {
             Ty::Enum(r#enum) => Some(proto::types::ty::TyType::Enum(r#enum.into())),
             Ty::Struct(r#struct) => Some(proto::types::ty::TyType::Struct(r#struct.try_into()?)),
             Ty::Tuple(_) => unimplemented!("unimplemented typle type"),
+            Ty::Array(items) => Some(proto::types::ty::TyType::Array(proto::types::Array {
+                items: items.iter().map(|item| item.to_bytes_be().to_vec()).collect(),
+            })),
+            Ty::ByteArray(string) => Some(proto::types::ty::TyType::ByteArray(string)),
         };
 
         Ok(proto::types::Ty { ty_type })
@@ -260,6 +264,15 @@ impl TryFrom<proto::types::Ty> for Ty {
             }
             proto::types::ty::TyType::Struct(r#struct) => Ok(Ty::Struct(r#struct.try_into()?)),
             proto::types::ty::TyType::Enum(r#enum) => Ok(Ty::Enum(r#enum.into())),
+            proto::types::ty::TyType::Array(array) => Ok(Ty::Array(
+                array
+                    .items
+                    .iter()
+                    .map(|item| FieldElement::from_byte_slice_be(item))
+                    .collect::<Result<_, _>>()
+                    .map_err(ClientError::SliceError)?,
+            )),
+            proto::types::ty::TyType::ByteArray(string) => Ok(Ty::ByteArray(string)),
         }
     }
 }
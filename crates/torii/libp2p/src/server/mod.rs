@@ -481,6 +481,8 @@ pub fn ty_to_string_type(ty: &Ty) -> String {
         Ty::Struct(_) => "struct".to_string(),
         Ty::Tuple(_) => "array".to_string(),
         Ty::Enum(_) => "enum".to_string(),
+        Ty::Array(_) => "array".to_string(),
+        Ty::ByteArray(_) => "string".to_string(),
     }
 }
 
@@ -519,6 +521,7 @@ pub fn parse_ty_to_primitive(ty: &Ty) -> Result<PrimitiveType, Error> {
             )),
             Primitive::Bool(value) => Ok(PrimitiveType::Bool(value.unwrap_or(false))),
         },
+        Ty::ByteArray(string) => Ok(PrimitiveType::String(string.clone())),
         _ => Err(Error::InvalidMessageError("Expected Primitive type".to_string())),
     }
 }
@@ -605,6 +608,9 @@ pub fn parse_object_to_ty(
                         *v = Some(bool::from_str(string).unwrap());
                     }
                 },
+                Ty::ByteArray(v) => {
+                    *v = string.clone();
+                }
                 _ => {
                     return Err(Error::InvalidMessageError("Invalid string type".to_string()));
                 }
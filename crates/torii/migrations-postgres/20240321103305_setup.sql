@@ -0,0 +1,163 @@
+-- Postgres schema equivalent to the SQLite migrations in `../migrations`.
+--
+-- Datetimes are stored as text, like in SQLite, since Torii binds them as strings. Model layouts
+-- are stored as their hex encoding.
+
+CREATE TABLE indexers (
+    id TEXT PRIMARY KEY NOT NULL,
+    head BIGINT NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE worlds (
+    id TEXT PRIMARY KEY NOT NULL,
+    world_address TEXT NOT NULL,
+    world_class_hash TEXT,
+    executor_address TEXT,
+    executor_class_hash TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE metadata (
+    id TEXT PRIMARY KEY NOT NULL,
+    uri TEXT,
+    json TEXT,
+    icon_img TEXT,
+    cover_img TEXT,
+    executed_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE models (
+    id TEXT NOT NULL PRIMARY KEY,
+    name TEXT NOT NULL,
+    layout TEXT NOT NULL,
+    transaction_hash TEXT,
+    class_hash TEXT NOT NULL,
+    contract_address TEXT NOT NULL DEFAULT '0',
+    packed_size BIGINT NOT NULL,
+    unpacked_size BIGINT NOT NULL,
+    executed_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_models_created_at ON models (created_at);
+
+CREATE TABLE model_members (
+    id TEXT NOT NULL,
+    model_idx BIGINT NOT NULL,
+    member_idx BIGINT NOT NULL,
+    model_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    type TEXT NOT NULL,
+    type_enum TEXT NOT NULL DEFAULT 'Primitive' CHECK (
+        type_enum IN ('Primitive', 'Struct', 'Enum', 'Tuple')
+    ),
+    enum_options TEXT NULL,
+    key BOOLEAN NOT NULL,
+    executed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id, member_idx),
+    FOREIGN KEY (model_id) REFERENCES models (id)
+);
+
+CREATE INDEX idx_model_members_model_id ON model_members (model_id);
+
+CREATE TABLE entities (
+    id TEXT NOT NULL PRIMARY KEY,
+    keys TEXT,
+    event_id TEXT NOT NULL,
+    executed_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_entities_keys ON entities (keys);
+CREATE INDEX idx_entities_event_id ON entities (event_id);
+
+CREATE TABLE entity_model (
+    entity_id TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    UNIQUE (entity_id, model_id),
+    FOREIGN KEY (entity_id) REFERENCES entities (id),
+    FOREIGN KEY (model_id) REFERENCES models (id)
+);
+
+CREATE INDEX idx_entity_model_entity_id ON entity_model (entity_id);
+CREATE INDEX idx_entity_model_model_id ON entity_model (model_id);
+
+CREATE TABLE event_messages (
+    id TEXT NOT NULL PRIMARY KEY,
+    keys TEXT,
+    event_id TEXT NOT NULL,
+    model_names TEXT,
+    executed_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_event_messages_keys ON event_messages (keys);
+CREATE INDEX idx_event_messages_event_id ON event_messages (event_id);
+
+CREATE TABLE event_model (
+    entity_id TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    UNIQUE (entity_id, model_id),
+    FOREIGN KEY (entity_id) REFERENCES event_messages (id),
+    FOREIGN KEY (model_id) REFERENCES models (id)
+);
+
+CREATE INDEX idx_event_model_event_id ON event_model (entity_id);
+CREATE INDEX idx_event_model_model_id ON event_model (model_id);
+
+CREATE TABLE entity_model_history (
+    id BIGSERIAL PRIMARY KEY,
+    entity_id TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    block_number BIGINT NOT NULL,
+    data TEXT,
+    executed_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (model_id) REFERENCES models (id)
+);
+
+CREATE INDEX idx_entity_model_history_entity_id ON entity_model_history (entity_id, model_id, block_number);
+
+CREATE TABLE events (
+    id TEXT NOT NULL PRIMARY KEY,
+    keys TEXT NOT NULL,
+    data TEXT NOT NULL,
+    transaction_hash TEXT,
+    executed_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_events_keys ON events (keys);
+
+CREATE TABLE transactions (
+    id TEXT NOT NULL PRIMARY KEY,
+    transaction_hash TEXT NOT NULL,
+    sender_address TEXT NOT NULL,
+    calldata TEXT NOT NULL,
+    max_fee TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    transaction_type TEXT NOT NULL DEFAULT 'INVOKE',
+    executed_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (transaction_hash)
+);
+
+CREATE TABLE transaction_receipts (
+    id TEXT NOT NULL PRIMARY KEY,
+    transaction_hash TEXT NOT NULL,
+    actual_fee TEXT NOT NULL,
+    finality_status TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    block_number BIGINT NOT NULL,
+    execution_result TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (transaction_hash)
+);
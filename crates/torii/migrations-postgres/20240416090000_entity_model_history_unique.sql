@@ -0,0 +1,7 @@
+-- Every change of a model of an entity is recorded once, so that indexing its block again, eg.
+-- after a restart before the head was written, doesn't duplicate it.
+DELETE FROM entity_model_history WHERE id NOT IN (
+    SELECT MIN(id) FROM entity_model_history GROUP BY entity_id, model_id, event_id
+);
+
+CREATE UNIQUE INDEX idx_entity_model_history_event ON entity_model_history (entity_id, model_id, event_id);
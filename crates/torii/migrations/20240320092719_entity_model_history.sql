@@ -0,0 +1,15 @@
+-- Values of the models of each entity after every change, to query the state of an entity at a
+-- past block. A NULL data is a deleted model.
+CREATE TABLE entity_model_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_id TEXT NOT NULL,
+    model_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    data TEXT,
+    executed_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (model_id) REFERENCES models (id)
+);
+
+CREATE INDEX idx_entity_model_history_entity_id ON entity_model_history (entity_id, model_id, block_number);
@@ -0,0 +1,6 @@
+-- Hashes of the indexed blocks, to detect the blocks that are orphaned by a reorg.
+CREATE TABLE blocks (
+    id INTEGER PRIMARY KEY NOT NULL,
+    hash TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
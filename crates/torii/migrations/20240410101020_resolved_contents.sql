@@ -0,0 +1,11 @@
+-- The external contents referenced by the indexed resources, by URI.
+CREATE TABLE resolved_contents (
+    id TEXT PRIMARY KEY NOT NULL,
+    content_type TEXT NOT NULL DEFAULT '',
+    -- `utf-8` if the data is the content as is, `base64` if it's the content encoded in base64
+    encoding TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,8 @@
+-- The keys of the entities in the history of their models, to query the entities at a past block
+-- even once their models are deleted.
+ALTER TABLE entity_model_history ADD COLUMN keys TEXT NOT NULL DEFAULT '';
+
+UPDATE entity_model_history SET keys = COALESCE(
+    (SELECT keys FROM entities WHERE entities.id = entity_model_history.entity_id),
+    ''
+);
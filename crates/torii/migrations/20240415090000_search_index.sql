@@ -0,0 +1,59 @@
+-- The documents searched by the `search` query: the keys of the entities, the string members of
+-- their models and the name and description of the metadata. They are kept in sync by triggers,
+-- created along with the tables of the models for the members of the models.
+CREATE TABLE search_documents (
+    id INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    -- empty for the metadata
+    model TEXT NOT NULL,
+    -- the path of the member in its model, as `member.nested`
+    member TEXT NOT NULL,
+    value TEXT NOT NULL,
+    UNIQUE (kind, target_id, model, member)
+);
+
+CREATE INDEX idx_search_documents_value ON search_documents (kind, value);
+
+-- The trigram tokenizer matches any substring of at least 3 characters, regardless of the case.
+CREATE VIRTUAL TABLE search_index USING fts5(
+    value,
+    content = 'search_documents',
+    content_rowid = 'id',
+    tokenize = 'trigram'
+);
+
+CREATE TRIGGER search_documents_insert AFTER INSERT ON search_documents BEGIN
+    INSERT INTO search_index (rowid, value) VALUES (new.id, new.value);
+END;
+
+CREATE TRIGGER search_documents_delete AFTER DELETE ON search_documents BEGIN
+    INSERT INTO search_index (search_index, rowid, value) VALUES ('delete', old.id, old.value);
+END;
+
+CREATE TRIGGER search_documents_update AFTER UPDATE ON search_documents BEGIN
+    INSERT INTO search_index (search_index, rowid, value) VALUES ('delete', old.id, old.value);
+    INSERT INTO search_index (rowid, value) VALUES (new.id, new.value);
+END;
+
+CREATE TRIGGER search_metadata_insert AFTER INSERT ON metadata BEGIN
+    INSERT INTO search_documents (kind, target_id, model, member, value)
+    SELECT 'METADATA', new.id, '', key, value FROM json_each(new.json)
+    WHERE key IN ('name', 'description') AND type = 'text';
+END;
+
+CREATE TRIGGER search_metadata_update AFTER UPDATE OF json ON metadata BEGIN
+    DELETE FROM search_documents WHERE kind = 'METADATA' AND target_id = old.id;
+    INSERT INTO search_documents (kind, target_id, model, member, value)
+    SELECT 'METADATA', new.id, '', key, value FROM json_each(new.json)
+    WHERE key IN ('name', 'description') AND type = 'text';
+END;
+
+CREATE TRIGGER search_metadata_delete AFTER DELETE ON metadata BEGIN
+    DELETE FROM search_documents WHERE kind = 'METADATA' AND target_id = old.id;
+END;
+
+INSERT INTO search_documents (kind, target_id, model, member, value)
+SELECT 'METADATA', metadata.id, '', json_each.key, json_each.value
+FROM metadata, json_each(metadata.json)
+WHERE json_each.key IN ('name', 'description') AND json_each.type = 'text';